@@ -0,0 +1,203 @@
+//! Cache storage backends
+
+use async_trait::async_trait;
+use industrydb_core::error::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Key/value storage for cached results and version counters
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Value stored under `key`, unless missing or expired
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store `value` under `key` for `ttl`
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()>;
+
+    /// Current value of each version counter, 0 for counters never bumped
+    async fn versions(&self, keys: &[String]) -> Result<Vec<u64>>;
+
+    /// Increment a version counter and return its new value
+    async fn bump(&self, key: &str) -> Result<u64>;
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    entries: HashMap<String, (Instant, Vec<u8>)>,
+    versions: HashMap<String, u64>,
+    /// Total size of the entries' values
+    bytes: usize,
+}
+
+impl MemoryState {
+    fn remove(&mut self, key: &str) {
+        if let Some((_, value)) = self.entries.remove(key) {
+            self.bytes -= value.len();
+        }
+    }
+}
+
+/// Cache held in this process
+///
+/// Expired entries are dropped when read and whenever the cache reaches its
+/// entry or byte limit; while it is still full after that, the entries
+/// closest to expiry are evicted. A value larger than the byte limit on its
+/// own is not stored.
+#[derive(Debug)]
+pub struct MemoryBackend {
+    state: Mutex<MemoryState>,
+    max_entries: usize,
+    max_bytes: Option<usize>,
+}
+
+impl MemoryBackend {
+    /// Create a cache holding at most `max_entries` results
+    pub fn new(max_entries: usize) -> Self {
+        Self::with_max_bytes(max_entries, None)
+    }
+
+    /// Create a cache holding at most `max_entries` results and, when set,
+    /// at most `max_bytes` bytes of them
+    pub fn with_max_bytes(max_entries: usize, max_bytes: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(MemoryState::default()),
+            max_entries: max_entries.max(1),
+            max_bytes,
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut state = self.state();
+        match state.entries.get(key) {
+            Some((expires, value)) if *expires > Instant::now() => Ok(Some(value.clone())),
+            Some(_) => {
+                state.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+        if self.max_bytes.is_some_and(|max| value.len() > max) {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut state = self.state();
+        state.remove(key);
+
+        let full = |state: &MemoryState| {
+            state.entries.len() >= self.max_entries
+                || self
+                    .max_bytes
+                    .is_some_and(|max| state.bytes + value.len() > max)
+        };
+        if full(&state) {
+            let expired: Vec<String> = state
+                .entries
+                .iter()
+                .filter(|(_, (expires, _))| *expires <= now)
+                .map(|(k, _)| k.clone())
+                .collect();
+            for key in expired {
+                state.remove(&key);
+            }
+        }
+        while full(&state) {
+            let soonest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (expires, _))| *expires)
+                .map(|(k, _)| k.clone());
+            match soonest {
+                Some(soonest) => state.remove(&soonest),
+                None => break,
+            }
+        }
+        state.bytes += value.len();
+        state.entries.insert(key.to_string(), (now + ttl, value));
+        Ok(())
+    }
+
+    async fn versions(&self, keys: &[String]) -> Result<Vec<u64>> {
+        let state = self.state();
+        Ok(keys
+            .iter()
+            .map(|k| state.versions.get(k).copied().unwrap_or(0))
+            .collect())
+    }
+
+    async fn bump(&self, key: &str) -> Result<u64> {
+        let mut state = self.state();
+        let version = state.versions.entry(key.to_string()).or_insert(0);
+        *version += 1;
+        Ok(*version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_ttl_and_eviction() {
+        let backend = MemoryBackend::new(2);
+        backend
+            .set("a", vec![1], Duration::from_secs(60))
+            .await
+            .unwrap();
+        backend.set("b", vec![2], Duration::ZERO).await.unwrap();
+        assert_eq!(backend.get("a").await.unwrap(), Some(vec![1]));
+        assert_eq!(backend.get("b").await.unwrap(), None);
+
+        backend
+            .set("c", vec![3], Duration::from_secs(30))
+            .await
+            .unwrap();
+        backend
+            .set("d", vec![4], Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(backend.get("c").await.unwrap(), None);
+        assert_eq!(backend.get("d").await.unwrap(), Some(vec![4]));
+    }
+
+    #[tokio::test]
+    async fn test_memory_byte_limit() {
+        let backend = MemoryBackend::with_max_bytes(100, Some(10));
+        let ttl = Duration::from_secs(60);
+        backend.set("a", vec![0; 4], ttl).await.unwrap();
+        backend.set("b", vec![0; 4], ttl * 2).await.unwrap();
+        backend.set("c", vec![0; 4], ttl * 3).await.unwrap();
+        assert_eq!(backend.get("a").await.unwrap(), None);
+        assert!(backend.get("b").await.unwrap().is_some());
+        assert!(backend.get("c").await.unwrap().is_some());
+
+        backend.set("big", vec![0; 11], ttl).await.unwrap();
+        assert_eq!(backend.get("big").await.unwrap(), None);
+        assert_eq!(backend.state().bytes, 8);
+    }
+
+    #[tokio::test]
+    async fn test_memory_versions() {
+        let backend = MemoryBackend::default();
+        let keys = vec!["v:readings".to_string(), "v:tags".to_string()];
+        assert_eq!(backend.versions(&keys).await.unwrap(), vec![0, 0]);
+        assert_eq!(backend.bump("v:tags").await.unwrap(), 1);
+        assert_eq!(backend.versions(&keys).await.unwrap(), vec![0, 1]);
+    }
+}
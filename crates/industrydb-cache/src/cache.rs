@@ -0,0 +1,227 @@
+//! Cached query execution
+
+use industrydb_core::{
+    codec::{BatchCodec, CodecConfig},
+    config::DatabaseType,
+    error::Result,
+    params::Value,
+    sql::{extract_tables, normalize_sql},
+    traits::DatabaseConnector,
+};
+use polars::prelude::DataFrame;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::backend::{CacheBackend, MemoryBackend};
+
+/// Cache settings
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// How long a result stays cached
+    pub ttl: Duration,
+    /// Prefix of every key; use one per database when several share a
+    /// Redis server
+    pub prefix: String,
+    /// Compression of the stored Arrow IPC
+    pub codec: CodecConfig,
+    /// Most results kept by an in-process cache
+    pub max_entries: usize,
+    /// Most bytes of compressed results kept by an in-process cache;
+    /// unlimited when `None`
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            prefix: "industrydb".to_string(),
+            codec: CodecConfig::default(),
+            max_entries: 1024,
+            max_bytes: None,
+        }
+    }
+}
+
+/// Caches query results in a [`CacheBackend`]
+///
+/// The cache is an optimization only: if the backend fails (Redis
+/// unreachable, say) or holds an entry that cannot be decoded, queries go
+/// to the database as if nothing were cached.
+#[derive(Clone)]
+pub struct QueryCache {
+    backend: Arc<dyn CacheBackend>,
+    config: CacheConfig,
+    codec: BatchCodec,
+}
+
+impl QueryCache {
+    /// Cache in `backend`
+    pub fn new(backend: Arc<dyn CacheBackend>, config: CacheConfig) -> Self {
+        let codec = BatchCodec::new(config.codec.clone());
+        Self {
+            backend,
+            config,
+            codec,
+        }
+    }
+
+    /// Cache in this process only, within `max_entries` and `max_bytes`
+    pub fn memory(config: CacheConfig) -> Self {
+        let backend = MemoryBackend::with_max_bytes(config.max_entries, config.max_bytes);
+        Self::new(Arc::new(backend), config)
+    }
+
+    /// Cache in Redis, shared by every process using the same URL and prefix
+    #[cfg(feature = "redis")]
+    pub async fn redis(url: &str, config: CacheConfig) -> Result<Self> {
+        let backend = crate::redis::RedisBackend::connect(url).await?;
+        Ok(Self::new(Arc::new(backend), config))
+    }
+
+    /// Cache settings
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Result of a query, from the cache when a fresh copy is there
+    ///
+    /// Entries are keyed by the database type, the query as normalized by
+    /// [`normalize_sql`] and the parameters, so reformatting a query does
+    /// not miss the cache while a different parameter does.
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<DataFrame> {
+        let db_type = conn.db_type().parse::<DatabaseType>().ok();
+        // Queries the parser does not understand are keyed by the global
+        // version only, so `invalidate_all` still reaches them
+        let tables = extract_tables(sql, db_type).unwrap_or_default();
+        let version_keys = self.version_keys(&tables);
+
+        let Ok(versions) = self.backend.versions(&version_keys).await else {
+            return conn.execute_with_params(sql, params).await;
+        };
+        let key = self.entry_key(conn.db_type(), sql, params, &versions);
+
+        if let Ok(Some(bytes)) = self.backend.get(&key).await {
+            if let Ok(df) = self.codec.decode(&bytes) {
+                return Ok(df);
+            }
+        }
+
+        let mut df = conn.execute_with_params(sql, params).await?;
+        if let Ok(bytes) = self.codec.encode(&mut df) {
+            let _ = self.backend.set(&key, bytes, self.config.ttl).await;
+        }
+        Ok(df)
+    }
+
+    /// Make every cached query reading `table` stale
+    ///
+    /// Call after writing to the table. The name is matched case-insensitively
+    /// and without quotes, as it appears in the query (`dbo.readings` and
+    /// `readings` are different tables).
+    pub async fn invalidate(&self, table: &str) -> Result<()> {
+        self.backend
+            .bump(&self.table_version_key(table))
+            .await
+            .map(|_| ())
+    }
+
+    /// Make every cached query stale
+    pub async fn invalidate_all(&self) -> Result<()> {
+        self.backend
+            .bump(&self.global_version_key())
+            .await
+            .map(|_| ())
+    }
+
+    fn global_version_key(&self) -> String {
+        format!("{}:version", self.config.prefix)
+    }
+
+    fn table_version_key(&self, table: &str) -> String {
+        let table: String = table
+            .chars()
+            .filter(|c| !matches!(c, '"' | '[' | ']' | '`'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        format!("{}:version:{}", self.config.prefix, table)
+    }
+
+    fn version_keys(&self, tables: &[String]) -> Vec<String> {
+        std::iter::once(self.global_version_key())
+            .chain(tables.iter().map(|t| self.table_version_key(t)))
+            .collect()
+    }
+
+    fn entry_key(&self, db_type: &str, sql: &str, params: &[Value], versions: &[u64]) -> String {
+        let mut hasher = Sha256::new();
+        for part in [db_type, &normalize_sql(sql)] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update(serde_json::to_vec(params).unwrap_or_default());
+        for version in versions {
+            hasher.update(version.to_le_bytes());
+        }
+        let digest: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("{}:result:{}", self.config.prefix, digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_keys_normalize_table_names() {
+        let cache = QueryCache::memory(CacheConfig {
+            prefix: "plant1".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(
+            cache.version_keys(&["\"Readings\"".to_string(), "[dbo].[tags]".to_string()]),
+            vec![
+                "plant1:version".to_string(),
+                "plant1:version:readings".to_string(),
+                "plant1:version:dbo.tags".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_key_changes_with_params_and_versions() {
+        let cache = QueryCache::memory(CacheConfig::default());
+        let sql = "SELECT * FROM readings WHERE tag = ?";
+        let key =
+            |params: &[Value], versions: &[u64]| cache.entry_key("sqlite", sql, params, versions);
+
+        let base = key(&["TI-101".into()], &[0, 0]);
+        assert!(base.starts_with("industrydb:result:"));
+        assert_eq!(base, key(&["TI-101".into()], &[0, 0]));
+        assert_ne!(base, key(&["TI-102".into()], &[0, 0]));
+        assert_ne!(base, key(&["TI-101".into()], &[0, 1]));
+        assert_eq!(
+            base,
+            cache.entry_key(
+                "sqlite",
+                "SELECT *\n  FROM readings\n  WHERE tag = ?;",
+                &["TI-101".into()],
+                &[0, 0]
+            )
+        );
+
+        let keys = cache.version_keys(&["readings".to_string()]);
+        cache.invalidate("READINGS").await.unwrap();
+        assert_eq!(cache.backend.versions(&keys).await.unwrap(), vec![0, 1]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Cached queries behind HMI trend screens
+//!
+//! [`Dashboard`] answers the three questions every trend screen asks of a
+//! long-format readings table (`ts`, `tag`, `value`): the latest value of
+//! each tag, a time range downsampled to one row per tag and interval, and
+//! alarm counts. Results are cached for a TTL, so many screens refreshing
+//! every few seconds cost one query per TTL. When the database fails, the
+//! last good result is served and marked stale instead of blanking the
+//! screen.
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use industrydb_core::{
+    error::{IndustryDbError, Result},
+    params::Value,
+    time::{format_timestamp, parse_timestamp},
+    traits::DatabaseConnector,
+};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::cache::{CacheConfig, QueryCache};
+
+/// Tables and columns a [`Dashboard`] reads, and how long results are kept
+#[derive(Debug, Clone)]
+pub struct DashboardConfig {
+    /// Long-format readings table
+    pub table: String,
+    /// Timestamp column of the readings
+    pub time_column: String,
+    /// Tag (signal name) column of the readings
+    pub tag_column: String,
+    /// Value column of the readings
+    pub value_column: String,
+    /// Alarm events table, one row per alarm
+    pub alarm_table: String,
+    /// Timestamp column of the alarms
+    pub alarm_time_column: String,
+    /// How long a result is served from the cache
+    pub ttl: Duration,
+    /// How old a result may get while the database is failing; no limit
+    /// when `None`
+    pub max_stale: Option<Duration>,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            table: "readings".to_string(),
+            time_column: "ts".to_string(),
+            tag_column: "tag".to_string(),
+            value_column: "value".to_string(),
+            alarm_table: "alarms".to_string(),
+            alarm_time_column: "ts".to_string(),
+            ttl: Duration::from_secs(5),
+            max_stale: None,
+        }
+    }
+}
+
+/// Result of a dashboard query
+#[derive(Debug, Clone)]
+pub struct Panel {
+    /// Rows to display
+    pub frame: DataFrame,
+    /// Whether the rows are a previous result served because the query failed
+    pub stale: bool,
+    /// Why the query failed, for stale results
+    pub error: Option<String>,
+}
+
+/// Cached latest-value, trend and alarm queries over one database
+pub struct Dashboard {
+    config: DashboardConfig,
+    cache: QueryCache,
+    last_good: Mutex<HashMap<String, (Instant, DataFrame)>>,
+}
+
+impl Dashboard {
+    /// Dashboard caching in this process
+    pub fn new(config: DashboardConfig) -> Self {
+        let cache = QueryCache::memory(CacheConfig {
+            ttl: config.ttl,
+            prefix: "industrydb:dashboard".to_string(),
+            ..Default::default()
+        });
+        Self::with_cache(config, cache)
+    }
+
+    /// Dashboard sharing `cache`, e.g. a Redis cache used by every worker
+    ///
+    /// The cache's own TTL applies.
+    pub fn with_cache(config: DashboardConfig, cache: QueryCache) -> Self {
+        Self {
+            config,
+            cache,
+            last_good: Mutex::default(),
+        }
+    }
+
+    /// Settings of this dashboard
+    pub fn config(&self) -> &DashboardConfig {
+        &self.config
+    }
+
+    /// Latest reading of each tag (every tag when `tags` is empty)
+    ///
+    /// Columns are the tag, time and value columns, one row per tag.
+    pub async fn latest<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        tags: &[String],
+    ) -> Result<Panel> {
+        let d = conn.dialect();
+        let c = &self.config;
+        let (table, ts, tag, value) = (
+            d.identifier(&c.table)?,
+            d.identifier(&c.time_column)?,
+            d.identifier(&c.tag_column)?,
+            d.identifier(&c.value_column)?,
+        );
+        let (filter, params) = tag_filter(conn, &tag, tags, "WHERE");
+        let sql = format!(
+            "SELECT r.{tag}, r.{ts}, r.{value} FROM {table} r \
+             JOIN (SELECT {tag}, MAX({ts}) AS latest_ts FROM {table}{filter} GROUP BY {tag}) l \
+             ON r.{tag} = l.{tag} AND r.{ts} = l.latest_ts ORDER BY r.{tag}",
+        );
+        self.fetch(conn, sql, params).await
+    }
+
+    /// Readings of `[start, end)` averaged per tag and `interval`
+    ///
+    /// Columns are the tag, the bucket start under the time column's name,
+    /// and `avg`, `min`, `max` and `count` of the values. Bucketing happens
+    /// after the fetch so that it works the same on every database; only
+    /// the raw rows of the range are cached.
+    pub async fn trend<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        tags: &[String],
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        interval: TimeDelta,
+    ) -> Result<Panel> {
+        if start >= end {
+            return Err(IndustryDbError::invalid_parameter(
+                "Trend start must be before end",
+            ));
+        }
+        if interval <= TimeDelta::zero() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Trend interval must be positive",
+            ));
+        }
+        let d = conn.dialect();
+        let c = &self.config;
+        let (table, ts, tag, value) = (
+            d.identifier(&c.table)?,
+            d.identifier(&c.time_column)?,
+            d.identifier(&c.tag_column)?,
+            d.identifier(&c.value_column)?,
+        );
+        let (filter, params) = tag_filter(conn, &tag, tags, "AND");
+        let sql = format!(
+            "SELECT {tag}, {ts}, {value} FROM {table} \
+             WHERE {ts} >= '{from}' AND {ts} < '{to}'{filter} ORDER BY {ts}",
+            from = format_timestamp(&start),
+            to = format_timestamp(&end),
+        );
+        let mut panel = self.fetch(conn, sql, params).await?;
+        panel.frame = downsample(&panel.frame, c, start, interval)?;
+        Ok(panel)
+    }
+
+    /// Number of alarms in `[start, end)` per value of the `by` column,
+    /// most frequent first
+    ///
+    /// Columns are `by` and `alarms`.
+    pub async fn alarm_counts<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        by: &str,
+    ) -> Result<Panel> {
+        let d = conn.dialect();
+        let c = &self.config;
+        let (table, ts, by) = (
+            d.identifier(&c.alarm_table)?,
+            d.identifier(&c.alarm_time_column)?,
+            d.identifier(by)?,
+        );
+        let sql = format!(
+            "SELECT {by}, COUNT(*) AS alarms FROM {table} \
+             WHERE {ts} >= '{from}' AND {ts} < '{to}' GROUP BY {by} ORDER BY alarms DESC, {by}",
+            from = format_timestamp(&start),
+            to = format_timestamp(&end),
+        );
+        self.fetch(conn, sql, Vec::new()).await
+    }
+
+    /// Range of the last `window` ending at the next `interval` boundary
+    /// after now (UTC)
+    ///
+    /// Aligning the end keeps the range, and so the cache key, the same
+    /// for every refresh within one interval.
+    pub fn recent(window: TimeDelta, interval: TimeDelta) -> (NaiveDateTime, NaiveDateTime) {
+        let now = Utc::now().naive_utc();
+        let step = interval.num_milliseconds().max(1);
+        let millis = now.and_utc().timestamp_millis();
+        let aligned = (millis + step - 1).div_euclid(step) * step;
+        let end =
+            chrono::DateTime::from_timestamp_millis(aligned).map_or(now, |end| end.naive_utc());
+        (end - window, end)
+    }
+
+    /// Run a query through the cache, falling back to its last good result
+    async fn fetch<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        sql: String,
+        params: Vec<Value>,
+    ) -> Result<Panel> {
+        let key = format!(
+            "{}\0{}",
+            sql,
+            serde_json::to_string(&params).unwrap_or_default()
+        );
+        match self.cache.fetch(conn, &sql, &params).await {
+            Ok(frame) => {
+                self.last_good()
+                    .insert(key, (Instant::now(), frame.clone()));
+                Ok(Panel {
+                    frame,
+                    stale: false,
+                    error: None,
+                })
+            }
+            Err(e) => {
+                let last_good = self.last_good();
+                match last_good.get(&key) {
+                    Some((at, frame))
+                        if self.config.max_stale.is_none_or(|max| at.elapsed() <= max) =>
+                    {
+                        Ok(Panel {
+                            frame: frame.clone(),
+                            stale: true,
+                            error: Some(e.to_string()),
+                        })
+                    }
+                    _ => Err(e),
+                }
+            }
+        }
+    }
+
+    fn last_good(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, DataFrame)>> {
+        self.last_good.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// ` {keyword} tag IN (...)` with its parameters, or nothing for all tags
+fn tag_filter<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    tag: &str,
+    tags: &[String],
+    keyword: &str,
+) -> (String, Vec<Value>) {
+    if tags.is_empty() {
+        return (String::new(), Vec::new());
+    }
+    let placeholders: Vec<String> = (1..=tags.len())
+        .map(|i| conn.dialect().placeholder(i))
+        .collect();
+    (
+        format!(" {} {} IN ({})", keyword, tag, placeholders.join(", ")),
+        tags.iter().map(|t| Value::Text(t.clone())).collect(),
+    )
+}
+
+/// Aggregate raw readings into `interval` buckets counted from `start`
+fn downsample(
+    df: &DataFrame,
+    config: &DashboardConfig,
+    start: NaiveDateTime,
+    interval: TimeDelta,
+) -> Result<DataFrame> {
+    let polars_err = |e: PolarsError| IndustryDbError::PolarsError(e.to_string());
+    let ts_name = config.time_column.as_str();
+    if df.height() == 0 {
+        // Databases may not report the columns of an empty result
+        let columns = [
+            (config.tag_column.as_str(), DataType::String),
+            (ts_name, DataType::Datetime(TimeUnit::Milliseconds, None)),
+            ("avg", DataType::Float64),
+            ("min", DataType::Float64),
+            ("max", DataType::Float64),
+            ("count", IDX_DTYPE),
+        ];
+        let columns = columns
+            .into_iter()
+            .map(|(name, dtype)| Series::new_empty(name.into(), &dtype).into_column())
+            .collect();
+        return DataFrame::new(columns).map_err(polars_err);
+    }
+    let origin = start.and_utc().timestamp_millis();
+    let step = interval.num_milliseconds().max(1);
+
+    let buckets: Vec<Option<i64>> = timestamps_ms(df.column(ts_name).map_err(polars_err)?)?
+        .into_iter()
+        .map(|ms| ms.map(|ms| origin + (ms - origin).div_euclid(step) * step))
+        .collect();
+    let bucket = Series::new(ts_name.into(), buckets)
+        .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+        .map_err(polars_err)?;
+
+    let mut frame = df.clone();
+    frame.with_column(bucket).map_err(polars_err)?;
+    let value = col(config.value_column.as_str()).cast(DataType::Float64);
+    frame
+        .lazy()
+        .group_by([col(config.tag_column.as_str()), col(ts_name)])
+        .agg([
+            value.clone().mean().alias("avg"),
+            value.clone().min().alias("min"),
+            value.clone().max().alias("max"),
+            value.count().alias("count"),
+        ])
+        .sort(
+            [config.tag_column.as_str(), ts_name],
+            SortMultipleOptions::default(),
+        )
+        .collect()
+        .map_err(polars_err)
+}
+
+/// Milliseconds since the epoch of a timestamp column, which arrives as a
+/// datetime, a date or text depending on the database
+fn timestamps_ms(column: &Column) -> Result<Vec<Option<i64>>> {
+    let polars_err = |e: PolarsError| IndustryDbError::PolarsError(e.to_string());
+    let series = column.as_materialized_series();
+    match series.dtype() {
+        DataType::Datetime(unit, _) => {
+            let divisor = match unit {
+                TimeUnit::Nanoseconds => 1_000_000,
+                TimeUnit::Microseconds => 1_000,
+                TimeUnit::Milliseconds => 1,
+            };
+            let physical = series.to_physical_repr();
+            Ok(physical
+                .i64()
+                .map_err(polars_err)?
+                .into_iter()
+                .map(|v| v.map(|v| v.div_euclid(divisor)))
+                .collect())
+        }
+        DataType::Date => {
+            let physical = series.to_physical_repr();
+            Ok(physical
+                .i32()
+                .map_err(polars_err)?
+                .into_iter()
+                .map(|v| v.map(|days| days as i64 * 86_400_000))
+                .collect())
+        }
+        DataType::String => series
+            .str()
+            .map_err(polars_err)?
+            .into_iter()
+            .map(|v| {
+                v.map(|s| parse_timestamp(s).map(|ts| ts.and_utc().timestamp_millis()))
+                    .transpose()
+            })
+            .collect(),
+        other => Err(IndustryDbError::invalid_parameter(format!(
+            "Column {} has type {} and cannot be used as a timestamp",
+            series.name(),
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(h, m, s)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_downsample_buckets_text_timestamps() {
+        let df = df!(
+            "tag" => ["a", "a", "a", "b"],
+            "ts" => [
+                "2024-03-01 08:00:10",
+                "2024-03-01 08:00:50",
+                "2024-03-01 08:01:05",
+                "2024-03-01 08:00:30",
+            ],
+            "value" => [1.0, 3.0, 10.0, 5.0],
+        )
+        .unwrap();
+        let out = downsample(
+            &df,
+            &DashboardConfig::default(),
+            at(8, 0, 0),
+            TimeDelta::minutes(1),
+        )
+        .unwrap();
+
+        assert_eq!(out.height(), 3);
+        let avg: Vec<_> = out
+            .column("avg")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(avg, vec![Some(2.0), Some(10.0), Some(5.0)]);
+        let counts = out.column("count").unwrap().cast(&DataType::Int64).unwrap();
+        assert_eq!(counts.i64().unwrap().get(0), Some(2));
+        assert_eq!(
+            timestamps_ms(out.column("ts").unwrap()).unwrap()[1],
+            Some(at(8, 1, 0).and_utc().timestamp_millis())
+        );
+    }
+
+    #[test]
+    fn test_recent_aligns_to_interval() {
+        let (start, end) = Dashboard::recent(TimeDelta::hours(1), TimeDelta::minutes(5));
+        assert_eq!(end - start, TimeDelta::hours(1));
+        assert_eq!(end.and_utc().timestamp() % 300, 0);
+        assert!(end >= Utc::now().naive_utc() - TimeDelta::seconds(1));
+    }
+}
@@ -0,0 +1,26 @@
+//! Query result caching for IndustryDB
+//!
+//! Dashboards re-run the same queries every few seconds. [`QueryCache`]
+//! keeps their results as compressed Arrow IPC, either in process
+//! ([`MemoryBackend`]) or in Redis ([`RedisBackend`], `redis` feature) so
+//! that every worker process of a deployment shares one copy instead of each
+//! querying the database.
+//!
+//! Entries expire after a TTL. Writers invalidate by table: each table has a
+//! version counter that is part of the cache key, so bumping it makes every
+//! cached query on that table unreachable without scanning for keys.
+//!
+//! [`Dashboard`] builds the latest-value, trend and alarm-count queries of
+//! HMI screens on top of the cache.
+
+mod backend;
+mod cache;
+mod dashboard;
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisBackend;
+pub use backend::{CacheBackend, MemoryBackend};
+pub use cache::{CacheConfig, QueryCache};
+pub use dashboard::{Dashboard, DashboardConfig, Panel};
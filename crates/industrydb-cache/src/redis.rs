@@ -0,0 +1,72 @@
+//! Redis cache backend
+
+use async_trait::async_trait;
+use industrydb_core::error::{IndustryDbError, Result};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::time::Duration;
+
+use crate::backend::CacheBackend;
+
+fn redis_err(e: redis::RedisError) -> IndustryDbError {
+    IndustryDbError::connection_error(format!("Redis: {}", e))
+}
+
+/// Cache shared through a Redis server
+///
+/// Results are stored with `SET ... PX`, so Redis expires them itself;
+/// version counters are plain `INCR` keys without expiry. The connection
+/// reconnects automatically after Redis restarts.
+#[derive(Clone)]
+pub struct RedisBackend {
+    conn: ConnectionManager,
+}
+
+impl RedisBackend {
+    /// Connect to a URL such as `redis://cache:6379/0` or `rediss://...`
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(redis_err)?;
+        let conn = ConnectionManager::new(client).await.map_err(redis_err)?;
+        Ok(Self { conn })
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedisBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.conn.clone().get(key).await.map_err(redis_err)
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+        // PX 0 is rejected; a zero TTL means the entry is not worth storing
+        let millis = ttl.as_millis() as u64;
+        if millis == 0 {
+            return Ok(());
+        }
+        redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("PX")
+            .arg(millis)
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(redis_err)
+    }
+
+    async fn versions(&self, keys: &[String]) -> Result<Vec<u64>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        // MGET always replies with an array, even for a single key
+        let values: Vec<Option<u64>> = redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(redis_err)?;
+        Ok(values.into_iter().map(|v| v.unwrap_or(0)).collect())
+    }
+
+    async fn bump(&self, key: &str) -> Result<u64> {
+        self.conn.clone().incr(key, 1u64).await.map_err(redis_err)
+    }
+}
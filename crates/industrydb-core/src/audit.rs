@@ -0,0 +1,222 @@
+//! Audit columns
+//!
+//! Tables often carry `created_at`, `updated_at` and `created_by` columns
+//! that every ingestion script fills in its own way. [`AuditColumns`]
+//! fills those the target table has: both timestamps on insert, the same
+//! UTC time for every row of a write, `updated_at` alone on update, and
+//! `created_by` with the writing user. Values the caller supplies are
+//! left alone, and upserts keep the creation columns of existing rows.
+//!
+//! ```ignore
+//! let options = InsertOptions::default().audit(AuditColumns::default().user("etl"));
+//! options.upsert(&conn, "readings", df, &["ts".into(), "tag".into()]).await?;
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Timelike, Utc};
+use polars::prelude::{DataFrame, DataType, NamedFrom, Series, TimeUnit};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DatabaseType;
+use crate::conform::{find_column, table_columns};
+use crate::error::Result;
+use crate::params::Value;
+use crate::schema::{quote_literal, ColumnInfo};
+use crate::time::format_timestamp;
+use crate::traits::CrudOperations;
+
+/// Names of the audit columns, and the user recorded in `created_by`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditColumns {
+    /// Time a row was inserted
+    pub created_at: String,
+    /// Time a row was last written
+    pub updated_at: String,
+    /// User that inserted a row
+    pub created_by: String,
+    /// User written to `created_by`, which is left alone without one
+    pub user: Option<String>,
+}
+
+impl Default for AuditColumns {
+    fn default() -> Self {
+        Self {
+            created_at: "created_at".to_string(),
+            updated_at: "updated_at".to_string(),
+            created_by: "created_by".to_string(),
+            user: None,
+        }
+    }
+}
+
+/// Current UTC time, to the millisecond every supported timestamp type
+/// stores
+pub fn audit_time() -> NaiveDateTime {
+    let now = Utc::now().naive_utc();
+    now.with_nanosecond(now.nanosecond() / 1_000_000 * 1_000_000)
+        .unwrap_or(now)
+}
+
+impl AuditColumns {
+    /// Record `user` in `created_by`
+    pub fn user(mut self, user: &str) -> Self {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    /// Add the audit columns the table has and `df` lacks, stamped `now`
+    pub fn stamp_insert(
+        &self,
+        mut df: DataFrame,
+        columns: &[ColumnInfo],
+        db_type: DatabaseType,
+        now: NaiveDateTime,
+    ) -> Result<DataFrame> {
+        let rows = df.height();
+        let ts = now.and_utc().timestamp_micros();
+        for name in [&self.created_at, &self.updated_at, &self.created_by] {
+            let Some(column) = find_column(columns, name, db_type) else {
+                continue;
+            };
+            let supplied = df
+                .get_column_names_str()
+                .into_iter()
+                .any(|c| find_column(std::slice::from_ref(column), c, db_type).is_some());
+            if supplied {
+                continue;
+            }
+            let values = if name == &self.created_by {
+                match &self.user {
+                    Some(user) => {
+                        Series::new(column.name.as_str().into(), vec![user.as_str(); rows])
+                    }
+                    None => continue,
+                }
+            } else {
+                Series::new(column.name.as_str().into(), vec![ts; rows])
+                    .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?
+            };
+            df.with_column(values)?;
+        }
+        Ok(df)
+    }
+
+    /// Table columns an upsert must not overwrite in existing rows
+    pub fn creation_columns(&self, columns: &[ColumnInfo], db_type: DatabaseType) -> Vec<String> {
+        [&self.created_at, &self.created_by]
+            .into_iter()
+            .filter_map(|name| find_column(columns, name, db_type))
+            .map(|column| column.name.clone())
+            .collect()
+    }
+
+    /// Set `updated_at` to `now` in the SQL values of an update, when the
+    /// table has it and `values` do not set it
+    pub fn stamp_update(
+        &self,
+        values: &mut HashMap<String, String>,
+        columns: &[ColumnInfo],
+        db_type: DatabaseType,
+        now: NaiveDateTime,
+    ) {
+        let Some(column) = find_column(columns, &self.updated_at, db_type) else {
+            return;
+        };
+        let set = values
+            .keys()
+            .any(|c| find_column(std::slice::from_ref(column), c, db_type).is_some());
+        if !set {
+            values.insert(column.name.clone(), quote_literal(&format_timestamp(&now)));
+        }
+    }
+
+    /// Update rows as [`CrudOperations::update`], stamping `updated_at`
+    pub async fn update<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        table: &str,
+        values: &HashMap<String, String>,
+        where_clause: Option<&str>,
+        params: &[Value],
+    ) -> Result<usize> {
+        let columns = table_columns(conn, table).await?;
+        let mut values = values.clone();
+        self.stamp_update(
+            &mut values,
+            &columns,
+            conn.dialect().db_type(),
+            audit_time(),
+        );
+        conn.update(table, &values, where_clause, params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::parse_timestamp;
+    use polars::prelude::df;
+
+    fn column(name: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: "TEXT".to_string(),
+            nullable: true,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn test_audit_columns() {
+        let columns = [
+            column("tag"),
+            column("Created_At"),
+            column("updated_at"),
+            column("created_by"),
+        ];
+        let now = parse_timestamp("2024-03-01 08:00:00.5").unwrap();
+        let audit = AuditColumns::default().user("etl");
+
+        let df = df!("tag" => ["A", "B"], "UPDATED_AT" => ["x", "y"]).unwrap();
+        let df = audit
+            .stamp_insert(df, &columns, DatabaseType::Sqlite, now)
+            .unwrap();
+        assert_eq!(
+            df.get_column_names_str(),
+            ["tag", "UPDATED_AT", "Created_At", "created_by"]
+        );
+        let created = df
+            .column("Created_At")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap();
+        assert_eq!(
+            created.i64().unwrap().get(1),
+            Some(now.and_utc().timestamp_micros())
+        );
+        assert_eq!(
+            df.column("created_by").unwrap().str().unwrap().get(0),
+            Some("etl")
+        );
+
+        let postgres = AuditColumns::default()
+            .stamp_insert(
+                df!("tag" => ["A"]).unwrap(),
+                &columns,
+                DatabaseType::Postgres,
+                now,
+            )
+            .unwrap();
+        assert_eq!(postgres.get_column_names_str(), ["tag", "updated_at"]);
+        assert_eq!(
+            audit.creation_columns(&columns, DatabaseType::Mssql),
+            ["Created_At", "created_by"]
+        );
+
+        let mut values = HashMap::from([("tag".to_string(), "'C'".to_string())]);
+        audit.stamp_update(&mut values, &columns, DatabaseType::Sqlite, now);
+        assert_eq!(values["updated_at"], "'2024-03-01 08:00:00.500'");
+    }
+}
@@ -0,0 +1,350 @@
+//! Backfill of historical ranges between databases
+//!
+//! Copies a time range from a source table to a target table in fixed-size
+//! time chunks. Progress is checkpointed after every chunk so an interrupted
+//! backfill resumes where it stopped, and throughput can be capped so a
+//! refill after an outage does not starve live writers.
+
+use chrono::{NaiveDateTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::batching::{insert_adaptive, AdaptiveBatchConfig, AdaptiveBatcher};
+use crate::error::{IndustryDbError, Result};
+use crate::time::{format_timestamp, parse_timestamp};
+use crate::traits::{CrudOperations, DatabaseConnector};
+
+/// What to backfill and how fast
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Table to read from
+    pub source_table: String,
+    /// Table to write to
+    pub target_table: String,
+    /// Column the range and chunks are defined on
+    pub time_column: String,
+    /// Inclusive start of the range
+    pub start: NaiveDateTime,
+    /// Exclusive end of the range
+    pub end: NaiveDateTime,
+    /// Width of each chunk
+    pub chunk_interval: TimeDelta,
+    /// Throughput cap; unlimited when `None`
+    pub max_rows_per_minute: Option<u64>,
+    /// File the checkpoint is persisted to; no resume across runs when `None`
+    pub checkpoint_path: Option<PathBuf>,
+    /// Insert each chunk in adaptively sized batches; one insert per chunk when `None`
+    pub batching: Option<AdaptiveBatchConfig>,
+}
+
+impl BackfillConfig {
+    /// Create a config copying `table` into a table of the same name
+    pub fn new(
+        table: &str,
+        time_column: &str,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        chunk_interval: TimeDelta,
+    ) -> Self {
+        Self {
+            source_table: table.to_string(),
+            target_table: table.to_string(),
+            time_column: time_column.to_string(),
+            start,
+            end,
+            chunk_interval,
+            max_rows_per_minute: None,
+            checkpoint_path: None,
+            batching: None,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.start >= self.end {
+            return Err(IndustryDbError::invalid_parameter(
+                "Backfill start must be before end",
+            ));
+        }
+        if self.chunk_interval <= TimeDelta::zero() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Backfill chunk interval must be positive",
+            ));
+        }
+        if self.max_rows_per_minute == Some(0) {
+            return Err(IndustryDbError::invalid_parameter(
+                "max_rows_per_minute must be positive",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Progress of a backfill, reported after every chunk
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackfillProgress {
+    /// Chunks completed, including those from a resumed checkpoint
+    pub chunks_done: usize,
+    /// Total number of chunks in the range
+    pub chunks_total: usize,
+    /// Rows copied, including those from a resumed checkpoint
+    pub rows_copied: usize,
+    /// Start of the next chunk to copy (`end` once complete)
+    pub next_start: String,
+    /// Whether the whole range has been copied
+    pub complete: bool,
+    /// Current insert batch size when batching adaptively
+    #[serde(default)]
+    pub batch_rows: usize,
+}
+
+/// Checkpoint file contents; the range is stored to detect a changed config
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    source_table: String,
+    target_table: String,
+    start: String,
+    end: String,
+    progress: BackfillProgress,
+}
+
+/// Pause/resume/cancel handle for a running backfill
+///
+/// Clones share state, so one clone can be handed to the backfill and
+/// another kept by the caller.
+#[derive(Debug, Clone, Default)]
+pub struct BackfillControl {
+    paused: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl BackfillControl {
+    /// Create a handle in the running state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause after the current chunk
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resume a paused backfill
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Stop after the current chunk; the checkpoint is kept for a later run
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the backfill is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Whether the backfill has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Copy `config.start..config.end` from `source` to `target` chunk by chunk
+///
+/// Resumes from the checkpoint file when one exists for the same tables and
+/// range. Returns the final progress; `complete` is false when cancelled.
+pub async fn backfill<S, T>(
+    source: &S,
+    target: &T,
+    config: &BackfillConfig,
+    control: &BackfillControl,
+    on_progress: Option<&(dyn Fn(&BackfillProgress) + Send + Sync)>,
+) -> Result<BackfillProgress>
+where
+    S: DatabaseConnector + ?Sized,
+    T: CrudOperations + ?Sized,
+{
+    config.validate()?;
+
+    let chunks_total = chunk_count(config);
+    let mut progress = match load_checkpoint(config)? {
+        Some(progress) => progress,
+        None => BackfillProgress {
+            chunks_total,
+            next_start: format_timestamp(&config.start),
+            ..Default::default()
+        },
+    };
+    let mut chunk_start = parse_timestamp(&progress.next_start)?;
+    let mut batcher = config
+        .batching
+        .clone()
+        .map(AdaptiveBatcher::new)
+        .transpose()?;
+
+    while chunk_start < config.end {
+        while control.is_paused() && !control.is_cancelled() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        if control.is_cancelled() {
+            return Ok(progress);
+        }
+
+        let chunk_end = (chunk_start + config.chunk_interval).min(config.end);
+        let started = Instant::now();
+
+        let sql = format!(
+            "SELECT * FROM {table} WHERE {col} >= '{from}' AND {col} < '{to}' ORDER BY {col}",
+            table = source.dialect().identifier(&config.source_table)?,
+            col = source.dialect().identifier(&config.time_column)?,
+            from = format_timestamp(&chunk_start),
+            to = format_timestamp(&chunk_end),
+        );
+        let df = source.execute(&sql).await?;
+        let rows = match (&mut batcher, df.height()) {
+            (_, 0) => 0,
+            (Some(batcher), _) => {
+                insert_adaptive(target, &config.target_table, &df, batcher).await?
+            }
+            (None, _) => target.insert(&config.target_table, df).await?,
+        };
+
+        chunk_start = chunk_end;
+        progress.chunks_done += 1;
+        progress.rows_copied += rows;
+        progress.next_start = format_timestamp(&chunk_start);
+        progress.complete = chunk_start >= config.end;
+        if let Some(batcher) = &batcher {
+            progress.batch_rows = batcher.batch_rows();
+        }
+        save_checkpoint(config, &progress)?;
+
+        if let Some(callback) = on_progress {
+            callback(&progress);
+        }
+
+        if let Some(limit) = config.max_rows_per_minute {
+            let budget = Duration::from_secs_f64(rows as f64 * 60.0 / limit as f64);
+            if let Some(wait) = budget.checked_sub(started.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+
+    progress.complete = true;
+    Ok(progress)
+}
+
+fn chunk_count(config: &BackfillConfig) -> usize {
+    let range = (config.end - config.start).num_milliseconds();
+    let chunk = config.chunk_interval.num_milliseconds().max(1);
+    (range.max(0) as u64).div_ceil(chunk as u64) as usize
+}
+
+fn load_checkpoint(config: &BackfillConfig) -> Result<Option<BackfillProgress>> {
+    let Some(path) = &config.checkpoint_path else {
+        return Ok(None);
+    };
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let checkpoint: Checkpoint = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let same_range = checkpoint.source_table == config.source_table
+        && checkpoint.target_table == config.target_table
+        && checkpoint.start == format_timestamp(&config.start)
+        && checkpoint.end == format_timestamp(&config.end);
+    if !same_range {
+        return Err(IndustryDbError::config_error(format!(
+            "Checkpoint {} belongs to a different backfill; remove it to start over",
+            path.display()
+        )));
+    }
+
+    Ok(Some(checkpoint.progress))
+}
+
+fn save_checkpoint(config: &BackfillConfig, progress: &BackfillProgress) -> Result<()> {
+    let Some(path) = &config.checkpoint_path else {
+        return Ok(());
+    };
+
+    let checkpoint = Checkpoint {
+        source_table: config.source_table.clone(),
+        target_table: config.target_table.clone(),
+        start: format_timestamp(&config.start),
+        end: format_timestamp(&config.end),
+        progress: progress.clone(),
+    };
+    // Write then rename so a crash never leaves a truncated checkpoint
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(&checkpoint)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::parse_interval;
+
+    fn config() -> BackfillConfig {
+        BackfillConfig::new(
+            "readings",
+            "ts",
+            parse_timestamp("2024-01-01").unwrap(),
+            parse_timestamp("2024-01-02 01:00").unwrap(),
+            parse_interval("6h").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_chunk_count_rounds_up() {
+        assert_eq!(chunk_count(&config()), 5);
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_range() {
+        let mut config = config();
+        config.end = config.start;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("industrydb-backfill-{}.json", std::process::id()));
+        let mut config = config();
+        config.checkpoint_path = Some(path.clone());
+
+        let progress = BackfillProgress {
+            chunks_done: 2,
+            chunks_total: 5,
+            rows_copied: 42,
+            next_start: "2024-01-01 12:00:00".to_string(),
+            complete: false,
+            batch_rows: 0,
+        };
+        save_checkpoint(&config, &progress).unwrap();
+        assert_eq!(load_checkpoint(&config).unwrap(), Some(progress));
+
+        config.target_table = "other".to_string();
+        assert!(load_checkpoint(&config).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_control_shares_state() {
+        let control = BackfillControl::new();
+        let handle = control.clone();
+        handle.pause();
+        assert!(control.is_paused());
+        handle.resume();
+        handle.cancel();
+        assert!(!control.is_paused());
+        assert!(control.is_cancelled());
+    }
+}
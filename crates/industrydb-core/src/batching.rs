@@ -0,0 +1,254 @@
+//! Adaptive batch sizing
+//!
+//! Writers over links whose quality changes during the day cannot pick one
+//! batch size that suits both good and bad hours. [`AdaptiveBatcher`] sizes
+//! batches with AIMD: it grows the batch additively while commits finish
+//! within the target latency and shrinks it multiplicatively when they are
+//! slow or fail.
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::error::{IndustryDbError, Result};
+use crate::traits::CrudOperations;
+
+fn default_initial_rows() -> usize {
+    1_000
+}
+
+fn default_min_rows() -> usize {
+    50
+}
+
+fn default_max_rows() -> usize {
+    50_000
+}
+
+fn default_target_latency_ms() -> u64 {
+    1_000
+}
+
+fn default_increase_rows() -> usize {
+    500
+}
+
+fn default_decrease_factor() -> f64 {
+    0.5
+}
+
+/// AIMD settings
+///
+/// ```toml
+/// [batching]
+/// initial_rows = 1000
+/// max_rows = 20000
+/// target_latency_ms = 500
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveBatchConfig {
+    /// Batch size before any commit has been observed
+    #[serde(default = "default_initial_rows")]
+    pub initial_rows: usize,
+    /// Smallest batch size
+    #[serde(default = "default_min_rows")]
+    pub min_rows: usize,
+    /// Largest batch size
+    #[serde(default = "default_max_rows")]
+    pub max_rows: usize,
+    /// Commits slower than this shrink the batch
+    #[serde(default = "default_target_latency_ms")]
+    pub target_latency_ms: u64,
+    /// Rows added after each commit within the target latency
+    #[serde(default = "default_increase_rows")]
+    pub increase_rows: usize,
+    /// Factor applied after a slow or failed commit
+    #[serde(default = "default_decrease_factor")]
+    pub decrease_factor: f64,
+}
+
+impl Default for AdaptiveBatchConfig {
+    fn default() -> Self {
+        Self {
+            initial_rows: default_initial_rows(),
+            min_rows: default_min_rows(),
+            max_rows: default_max_rows(),
+            target_latency_ms: default_target_latency_ms(),
+            increase_rows: default_increase_rows(),
+            decrease_factor: default_decrease_factor(),
+        }
+    }
+}
+
+impl AdaptiveBatchConfig {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.min_rows == 0 || self.min_rows > self.max_rows {
+            return Err(IndustryDbError::config_error(
+                "Batch sizes must satisfy 0 < min_rows <= max_rows",
+            ));
+        }
+        if !(self.decrease_factor > 0.0 && self.decrease_factor < 1.0) {
+            return Err(IndustryDbError::config_error(
+                "decrease_factor must be between 0 and 1",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Counters of an [`AdaptiveBatcher`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchStats {
+    /// Current batch size
+    pub batch_rows: usize,
+    /// Commits within the target latency
+    pub fast_commits: u64,
+    /// Commits over the target latency
+    pub slow_commits: u64,
+    /// Failed commits
+    pub failures: u64,
+}
+
+/// AIMD batch size controller
+#[derive(Debug, Clone)]
+pub struct AdaptiveBatcher {
+    config: AdaptiveBatchConfig,
+    stats: BatchStats,
+}
+
+impl AdaptiveBatcher {
+    /// Create a controller starting at `initial_rows` (clamped to the bounds)
+    pub fn new(config: AdaptiveBatchConfig) -> Result<Self> {
+        config.validate()?;
+        let stats = BatchStats {
+            batch_rows: config.initial_rows.clamp(config.min_rows, config.max_rows),
+            ..Default::default()
+        };
+        Ok(Self { config, stats })
+    }
+
+    /// Rows to put in the next batch
+    pub fn batch_rows(&self) -> usize {
+        self.stats.batch_rows
+    }
+
+    /// Counters so far
+    pub fn stats(&self) -> &BatchStats {
+        &self.stats
+    }
+
+    /// Report a successful commit and its latency
+    pub fn record_success(&mut self, latency: Duration) {
+        if latency <= Duration::from_millis(self.config.target_latency_ms) {
+            self.stats.fast_commits += 1;
+            self.stats.batch_rows =
+                (self.stats.batch_rows + self.config.increase_rows).min(self.config.max_rows);
+        } else {
+            self.stats.slow_commits += 1;
+            self.decrease();
+        }
+    }
+
+    /// Report a failed commit
+    pub fn record_failure(&mut self) {
+        self.stats.failures += 1;
+        self.decrease();
+    }
+
+    fn decrease(&mut self) {
+        let shrunk = (self.stats.batch_rows as f64 * self.config.decrease_factor) as usize;
+        self.stats.batch_rows = shrunk.max(self.config.min_rows);
+    }
+}
+
+/// Insert a DataFrame in batches sized by `batcher`
+///
+/// Each batch's commit latency feeds back into the next batch size. A failed
+/// batch shrinks the size and aborts the write; rows of earlier batches stay
+/// committed. Returns the number of rows inserted.
+pub async fn insert_adaptive<C: CrudOperations + ?Sized>(
+    conn: &C,
+    table: &str,
+    data: &DataFrame,
+    batcher: &mut AdaptiveBatcher,
+) -> Result<usize> {
+    let mut offset = 0;
+    let mut inserted = 0;
+
+    while offset < data.height() {
+        let len = batcher.batch_rows().min(data.height() - offset);
+        let batch = data.slice(offset as i64, len);
+
+        let started = Instant::now();
+        match conn.insert(table, batch).await {
+            Ok(rows) => {
+                batcher.record_success(started.elapsed());
+                inserted += rows;
+                offset += len;
+            }
+            Err(e) => {
+                batcher.record_failure();
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batcher() -> AdaptiveBatcher {
+        AdaptiveBatcher::new(AdaptiveBatchConfig {
+            initial_rows: 1_000,
+            min_rows: 100,
+            max_rows: 2_000,
+            target_latency_ms: 100,
+            increase_rows: 500,
+            decrease_factor: 0.5,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_additive_increase_up_to_max() {
+        let mut batcher = batcher();
+        batcher.record_success(Duration::from_millis(10));
+        assert_eq!(batcher.batch_rows(), 1_500);
+        batcher.record_success(Duration::from_millis(10));
+        batcher.record_success(Duration::from_millis(10));
+        assert_eq!(batcher.batch_rows(), 2_000);
+        assert_eq!(batcher.stats().fast_commits, 3);
+    }
+
+    #[test]
+    fn test_multiplicative_decrease_down_to_min() {
+        let mut batcher = batcher();
+        batcher.record_success(Duration::from_millis(500));
+        assert_eq!(batcher.batch_rows(), 500);
+        batcher.record_failure();
+        batcher.record_failure();
+        batcher.record_failure();
+        assert_eq!(batcher.batch_rows(), 100);
+        assert_eq!(batcher.stats().slow_commits, 1);
+        assert_eq!(batcher.stats().failures, 3);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = AdaptiveBatchConfig {
+            min_rows: 10,
+            max_rows: 5,
+            ..Default::default()
+        };
+        assert!(AdaptiveBatcher::new(config).is_err());
+
+        let config = AdaptiveBatchConfig {
+            decrease_factor: 1.5,
+            ..Default::default()
+        };
+        assert!(AdaptiveBatcher::new(config).is_err());
+    }
+}
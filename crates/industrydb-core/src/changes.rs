@@ -0,0 +1,227 @@
+//! Change capture by polling a watermark column
+//!
+//! For databases without a change log to read, such as SQLite or SQL Server
+//! without CDC enabled, changes are found by querying each table for rows
+//! whose watermark column, an `updated_at` timestamp or a SQL Server
+//! `rowversion`, is past the last one seen. The watermarks are persisted so
+//! a restarted poller picks up where it stopped. Unlike a replication slot
+//! this sees no deletes, and only the latest version of a row changed twice
+//! between polls.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use polars::prelude::*;
+
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::sync::next_page;
+use crate::traits::CrudOperations;
+
+/// Rows read per table and poll when none is configured
+pub const DEFAULT_POLL_ROWS: usize = 10_000;
+
+/// A table to poll and the column that grows on every insert or update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolledTable {
+    /// Table to read from
+    pub table: String,
+    /// Column that grows on every insert or update of a row
+    pub watermark_column: String,
+}
+
+impl PolledTable {
+    /// Poll `table` on `watermark_column`
+    pub fn new(table: &str, watermark_column: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            watermark_column: watermark_column.to_string(),
+        }
+    }
+}
+
+/// New or changed rows of one table
+#[derive(Debug, Clone)]
+pub struct ChangedRows {
+    /// Table the rows were read from
+    pub table: String,
+    /// The rows, in watermark order
+    pub frame: DataFrame,
+}
+
+/// Watermark of one table in the state file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TableState {
+    watermark_column: String,
+    watermark: Option<Value>,
+}
+
+/// Finds the rows of a set of tables changed since the last poll
+///
+/// Like [`sync_table`](crate::sync::sync_table), this relies on the
+/// watermark column growing on every write: a row written with a
+/// watermark no greater than one already seen is missed.
+#[derive(Debug)]
+pub struct ChangePoller {
+    tables: Vec<PolledTable>,
+    chunk_rows: usize,
+    state_path: Option<PathBuf>,
+    watermarks: BTreeMap<String, TableState>,
+    /// Watermarks of the rows returned by the last poll
+    pending: Vec<(String, Value)>,
+}
+
+impl ChangePoller {
+    /// Create a poller for `tables`, loading their watermarks from
+    /// `state_path` when it exists
+    ///
+    /// A table without a persisted watermark starts from its first row.
+    /// Fails when the state file tracks a table on a different column.
+    pub fn new(tables: Vec<PolledTable>, state_path: Option<PathBuf>) -> Result<Self> {
+        if tables.is_empty() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Change poller needs at least one table",
+            ));
+        }
+
+        let mut watermarks = match &state_path {
+            Some(path) if path.exists() => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            _ => BTreeMap::new(),
+        };
+        for polled in &tables {
+            let state = watermarks
+                .entry(polled.table.clone())
+                .or_insert_with(|| TableState {
+                    watermark_column: polled.watermark_column.clone(),
+                    watermark: None,
+                });
+            if state.watermark_column != polled.watermark_column {
+                return Err(IndustryDbError::config_error(format!(
+                    "Poller state tracks {} on {}, not {}; remove it to start over",
+                    polled.table, state.watermark_column, polled.watermark_column
+                )));
+            }
+        }
+
+        Ok(Self {
+            tables,
+            chunk_rows: DEFAULT_POLL_ROWS,
+            state_path,
+            watermarks,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Read at most about `chunk_rows` rows per table and poll
+    pub fn with_chunk_rows(mut self, chunk_rows: usize) -> Result<Self> {
+        if chunk_rows == 0 {
+            return Err(IndustryDbError::invalid_parameter(
+                "chunk_rows must be positive",
+            ));
+        }
+        self.chunk_rows = chunk_rows;
+        Ok(self)
+    }
+
+    /// Watermark of the last acknowledged row of `table`
+    pub fn watermark(&self, table: &str) -> Option<&Value> {
+        self.watermarks.get(table)?.watermark.as_ref()
+    }
+
+    /// Acknowledge the rows returned so far and read the next ones
+    ///
+    /// Returns the changed rows of each table that has any. Rows are only
+    /// acknowledged, and the state file written, by the next poll or
+    /// [`acknowledge`](Self::acknowledge), so rows being handled when the
+    /// process dies are returned again rather than lost. A table with more
+    /// changes than `chunk_rows` returns the rest on the following polls.
+    pub async fn poll<C: CrudOperations + ?Sized>(&mut self, conn: &C) -> Result<Vec<ChangedRows>> {
+        self.acknowledge()?;
+
+        let mut changes = Vec::new();
+        for polled in &self.tables {
+            let (frame, _) = next_page(
+                conn,
+                &polled.table,
+                &polled.watermark_column,
+                self.chunk_rows,
+                self.watermarks[&polled.table].watermark.as_ref(),
+            )
+            .await?;
+            if frame.height() == 0 {
+                continue;
+            }
+
+            // Pages come in watermark order, so the last row has the highest
+            let last = frame
+                .column(&polled.watermark_column)?
+                .as_materialized_series()
+                .get(frame.height() - 1)?;
+            self.pending.push((polled.table.clone(), Value::from(last)));
+            changes.push(ChangedRows {
+                table: polled.table.clone(),
+                frame,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Advance the watermarks past the rows returned by the last poll and
+    /// persist them
+    pub fn acknowledge(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        for (table, watermark) in self.pending.drain(..) {
+            if let Some(state) = self.watermarks.get_mut(&table) {
+                state.watermark = Some(watermark);
+            }
+        }
+        self.save_state()
+    }
+
+    fn save_state(&self) -> Result<()> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        // Write then rename so a crash never leaves a truncated state file
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.watermarks)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acknowledge_persists_watermarks() {
+        let path =
+            std::env::temp_dir().join(format!("industrydb-poller-{}.json", std::process::id()));
+        let tables = vec![
+            PolledTable::new("batches", "updated_at"),
+            PolledTable::new("alarms", "id"),
+        ];
+        let mut poller = ChangePoller::new(tables.clone(), Some(path.clone())).unwrap();
+        poller.pending.push((
+            "batches".to_string(),
+            Value::Text("2024-03-01 08:00:00".to_string()),
+        ));
+        assert_eq!(poller.watermark("batches"), None);
+        poller.acknowledge().unwrap();
+
+        let poller = ChangePoller::new(tables, Some(path.clone())).unwrap();
+        assert_eq!(
+            poller.watermark("batches"),
+            Some(&Value::Text("2024-03-01 08:00:00".to_string()))
+        );
+        assert_eq!(poller.watermark("alarms"), None);
+
+        let moved = vec![PolledTable::new("batches", "created_at")];
+        assert!(ChangePoller::new(moved, Some(path.clone())).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+//! Compression codecs for locally buffered batches
+//!
+//! Batches are serialized as Arrow IPC and compressed as a whole. Telemetry
+//! batches repeat the same tag names over and over, so ZSTD with a dictionary
+//! trained on those names compresses them several times better than plain
+//! ZSTD on small batches.
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read, Write};
+
+use crate::error::{IndustryDbError, Result};
+
+/// Compression codec
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    /// Store IPC bytes uncompressed
+    None,
+    /// Zstandard
+    #[default]
+    Zstd,
+}
+
+impl Codec {
+    fn id(self) -> u8 {
+        match self {
+            Codec::None => 0,
+            Codec::Zstd => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Codec::None),
+            1 => Ok(Codec::Zstd),
+            other => Err(IndustryDbError::invalid_parameter(format!(
+                "Unknown codec id {} in buffered batch",
+                other
+            ))),
+        }
+    }
+}
+
+impl std::str::FromStr for Codec {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Codec::None),
+            "zstd" => Ok(Codec::Zstd),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported codec: {}",
+                s
+            ))),
+        }
+    }
+}
+
+fn default_level() -> i32 {
+    3
+}
+
+fn default_dictionary_size() -> usize {
+    16 * 1024
+}
+
+/// Codec settings for buffer storage
+///
+/// ```toml
+/// [buffer]
+/// codec = "zstd"
+/// level = 9
+/// dictionary = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodecConfig {
+    /// Compression codec
+    #[serde(default)]
+    pub codec: Codec,
+    /// Compression level (ZSTD: 1-22)
+    #[serde(default = "default_level")]
+    pub level: i32,
+    /// Train a dictionary on the string columns (tag names) of sample batches
+    #[serde(default)]
+    pub dictionary: bool,
+    /// Maximum dictionary size in bytes
+    #[serde(default = "default_dictionary_size")]
+    pub dictionary_size: usize,
+}
+
+impl Default for CodecConfig {
+    fn default() -> Self {
+        Self {
+            codec: Codec::default(),
+            level: default_level(),
+            dictionary: false,
+            dictionary_size: default_dictionary_size(),
+        }
+    }
+}
+
+impl CodecConfig {
+    /// Reject a level the codec does not support
+    pub fn validate(&self) -> Result<()> {
+        let levels = zstd::compression_level_range();
+        if self.codec == Codec::Zstd && !levels.contains(&self.level) {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "ZSTD level must be between {} and {}",
+                levels.start(),
+                levels.end()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Encodes DataFrame batches to compressed bytes and back
+///
+/// Each encoded batch starts with a two-byte header (codec id, dictionary
+/// flag), so batches written with different settings can still be decoded.
+/// A batch compressed with a dictionary needs the same dictionary to decode;
+/// callers persist [`BatchCodec::dictionary`] alongside the buffer.
+#[derive(Debug, Clone, Default)]
+pub struct BatchCodec {
+    config: CodecConfig,
+    dictionary: Option<Vec<u8>>,
+}
+
+impl BatchCodec {
+    /// Create a codec without a dictionary
+    pub fn new(config: CodecConfig) -> Self {
+        Self {
+            config,
+            dictionary: None,
+        }
+    }
+
+    /// Create a codec with a previously trained dictionary
+    pub fn with_dictionary(config: CodecConfig, dictionary: Vec<u8>) -> Self {
+        Self {
+            config,
+            dictionary: Some(dictionary),
+        }
+    }
+
+    /// Codec settings
+    pub fn config(&self) -> &CodecConfig {
+        &self.config
+    }
+
+    /// Trained dictionary, if any
+    pub fn dictionary(&self) -> Option<&[u8]> {
+        self.dictionary.as_deref()
+    }
+
+    /// Train a dictionary from the string values of sample batches
+    ///
+    /// Does nothing unless dictionaries are enabled and the codec is ZSTD.
+    /// Returns whether a dictionary was trained; too few distinct samples
+    /// leave the codec without one rather than failing.
+    pub fn train(&mut self, samples: &[DataFrame]) -> Result<bool> {
+        if !self.config.dictionary || self.config.codec != Codec::Zstd {
+            return Ok(false);
+        }
+
+        let mut values: Vec<Vec<u8>> = Vec::new();
+        for df in samples {
+            for column in df.get_columns() {
+                if column.dtype() != &DataType::String {
+                    continue;
+                }
+                values.extend(
+                    column
+                        .str()?
+                        .into_iter()
+                        .flatten()
+                        .map(|s| s.as_bytes().to_vec()),
+                );
+            }
+        }
+
+        match zstd::dict::from_samples(&values, self.config.dictionary_size) {
+            Ok(dictionary) => {
+                self.dictionary = Some(dictionary);
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Serialize and compress a batch
+    pub fn encode(&self, df: &mut DataFrame) -> Result<Vec<u8>> {
+        let mut ipc = Vec::new();
+        IpcWriter::new(&mut ipc).finish(df)?;
+
+        let use_dictionary = self.config.codec == Codec::Zstd && self.dictionary.is_some();
+        let mut out = vec![self.config.codec.id(), use_dictionary as u8];
+
+        match self.config.codec {
+            Codec::None => out.extend_from_slice(&ipc),
+            Codec::Zstd => {
+                let mut encoder = match &self.dictionary {
+                    Some(dictionary) => {
+                        zstd::stream::Encoder::with_dictionary(out, self.config.level, dictionary)?
+                    }
+                    None => zstd::stream::Encoder::new(out, self.config.level)?,
+                };
+                encoder.write_all(&ipc)?;
+                out = encoder.finish()?;
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Decompress and deserialize a batch produced by [`BatchCodec::encode`]
+    pub fn decode(&self, bytes: &[u8]) -> Result<DataFrame> {
+        if bytes.len() < 2 {
+            return Err(IndustryDbError::invalid_parameter(
+                "Buffered batch is missing its header",
+            ));
+        }
+        let (header, payload) = bytes.split_at(2);
+        let codec = Codec::from_id(header[0])?;
+        let uses_dictionary = header[1] != 0;
+
+        let ipc = match codec {
+            Codec::None => payload.to_vec(),
+            Codec::Zstd => {
+                let mut ipc = Vec::new();
+                if uses_dictionary {
+                    let dictionary = self.dictionary.as_deref().ok_or_else(|| {
+                        IndustryDbError::invalid_parameter(
+                            "Buffered batch was compressed with a dictionary that is not loaded",
+                        )
+                    })?;
+                    zstd::stream::Decoder::with_dictionary(payload, dictionary)?
+                        .read_to_end(&mut ipc)?;
+                } else {
+                    zstd::stream::Decoder::new(payload)?.read_to_end(&mut ipc)?;
+                }
+                ipc
+            }
+        };
+
+        Ok(IpcReader::new(Cursor::new(ipc)).finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(offset: i64) -> DataFrame {
+        let tags: Vec<String> = (0..200)
+            .map(|i| format!("plant1.line{}.motor{}.temperature", i % 4, i % 10))
+            .collect();
+        let ts: Vec<i64> = (0..200).map(|i| offset + i).collect();
+        df!("tag" => tags, "ts" => ts).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip_each_codec() {
+        for codec in [Codec::None, Codec::Zstd] {
+            let codec = BatchCodec::new(CodecConfig {
+                codec,
+                ..Default::default()
+            });
+            let mut df = batch(0);
+            let bytes = codec.encode(&mut df).unwrap();
+            assert!(codec.decode(&bytes).unwrap().equals(&df));
+        }
+    }
+
+    #[test]
+    fn test_dictionary_roundtrip() {
+        let mut codec = BatchCodec::new(CodecConfig {
+            dictionary: true,
+            ..Default::default()
+        });
+        let samples: Vec<DataFrame> = (0..20).map(|i| batch(i * 200)).collect();
+        codec.train(&samples).unwrap();
+
+        let mut df = batch(10_000);
+        let bytes = codec.encode(&mut df).unwrap();
+        assert!(codec.decode(&bytes).unwrap().equals(&df));
+
+        if codec.dictionary().is_some() {
+            let without = BatchCodec::new(CodecConfig::default());
+            assert!(without.decode(&bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: CodecConfig = toml::from_str("codec = \"zstd\"\nlevel = 9").unwrap();
+        assert_eq!(config.codec, Codec::Zstd);
+        assert_eq!(config.level, 9);
+        assert!(!config.dictionary);
+        assert!(config.validate().is_ok());
+
+        let config = CodecConfig {
+            level: 99,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -1,10 +1,11 @@
 //! Configuration types and parsing
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 use crate::error::{IndustryDbError, Result};
+use crate::policy::AccessPolicy;
 
 /// Database type enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -86,11 +87,174 @@ pub struct ConnectionConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u32>,
 
+    /// Read access policy enforced on every query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<AccessPolicy>,
+
+    /// Registered plugin connector to use instead of the built-in one
+    ///
+    /// `db_type` still selects the SQL dialect the connector speaks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connector: Option<String>,
+
+    /// PRAGMAs of SQLite connections; [`SqliteOptions::default`] when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sqlite: Option<SqliteOptions>,
+
+    /// How SQL Server results are read; [`MssqlOptions::default`] when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mssql: Option<MssqlOptions>,
+
+    /// Zone timestamps are returned in; [`TimestampMode::Utc`] when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<TimestampMode>,
+
+    /// What happens to values too wide for their column's dtype;
+    /// [`OverflowMode::Error`] when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overflow: Option<OverflowMode>,
+
     /// Additional connection options
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+/// PRAGMAs applied to every pooled SQLite connection
+///
+/// The defaults suit several writers sharing a file: WAL lets readers run
+/// next to a writer, and a busy timeout makes a second writer wait for the
+/// lock instead of failing with "database is locked".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SqliteOptions {
+    /// `journal_mode`: `wal`, `delete`, `truncate`, `persist`, `memory` or `off`
+    pub journal_mode: String,
+    /// `synchronous`: `off`, `normal`, `full` or `extra`
+    pub synchronous: String,
+    /// How long to wait for a lock held by another connection, in milliseconds
+    pub busy_timeout_ms: u64,
+    /// Enforce foreign key constraints
+    pub foreign_keys: bool,
+    /// `cache_size`: pages when positive, KiB when negative; SQLite's
+    /// default when `None`
+    pub cache_size: Option<i64>,
+    /// Database files attached to every connection, by alias, so queries
+    /// can join across files (`SELECT ... FROM day_0301.readings`)
+    pub attach: BTreeMap<String, String>,
+}
+
+impl Default for SqliteOptions {
+    fn default() -> Self {
+        Self {
+            journal_mode: "wal".to_string(),
+            synchronous: "normal".to_string(),
+            busy_timeout_ms: 5000,
+            foreign_keys: true,
+            cache_size: None,
+            attach: BTreeMap::new(),
+        }
+    }
+}
+
+/// How SQL Server results are read
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MssqlOptions {
+    /// How `decimal`, `numeric` and `money` columns are read
+    pub decimals: DecimalMode,
+}
+
+/// Polars dtype exact numeric columns are read as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecimalMode {
+    /// `Decimal`, keeping every digit
+    #[default]
+    Decimal,
+    /// `Float64`, which arithmetic and plotting take directly
+    Float,
+}
+
+/// What happens to a value read from the database that its column's dtype
+/// cannot hold, such as a PostgreSQL `numeric` with more than 38 digits or
+/// `NaN` in a `Decimal` column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowMode {
+    /// Fail the query, naming the column and value
+    #[default]
+    Error,
+    /// Clamp to the largest value of the dtype; values that are not numbers
+    /// (`NaN`) become null
+    Saturate,
+    /// Read the whole column as text, keeping every value as written
+    String,
+}
+
+/// Zone of returned timestamp columns
+///
+/// Applies to columns the database stores with an offset (`timestamptz`,
+/// `datetimeoffset`), which are read as UTC instants. Columns stored
+/// without one (`timestamp`, `datetime2`) carry no zone to convert from
+/// and stay naive under every mode. Written as `"utc"`, `"naive"` or a
+/// zone name such as `"Europe/Berlin"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TimestampMode {
+    /// Zone-aware columns in UTC
+    #[default]
+    Utc,
+    /// UTC wall-clock time without a zone, so every column is naive
+    Naive,
+    /// Zone-aware columns in the given IANA zone
+    Zone(String),
+}
+
+impl std::fmt::Display for TimestampMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimestampMode::Utc => write!(f, "utc"),
+            TimestampMode::Naive => write!(f, "naive"),
+            TimestampMode::Zone(zone) => write!(f, "{}", zone),
+        }
+    }
+}
+
+impl std::str::FromStr for TimestampMode {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "utc" => Ok(TimestampMode::Utc),
+            "naive" => Ok(TimestampMode::Naive),
+            _ if !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "/_+-".contains(c)) =>
+            {
+                Ok(TimestampMode::Zone(s.to_string()))
+            }
+            _ => Err(IndustryDbError::config_error(format!(
+                "Invalid timestamp mode '{}': expected utc, naive or a zone name",
+                s
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for TimestampMode {
+    type Error = IndustryDbError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<TimestampMode> for String {
+    fn from(mode: TimestampMode) -> Self {
+        mode.to_string()
+    }
+}
+
 impl ConnectionConfig {
     /// Create a new PostgreSQL configuration
     pub fn postgres(
@@ -111,6 +275,12 @@ impl ConnectionConfig {
             path: None,
             trusted_connection: None,
             timeout: None,
+            policy: None,
+            connector: None,
+            sqlite: None,
+            mssql: None,
+            timestamps: None,
+            overflow: None,
             extra: HashMap::new(),
         }
     }
@@ -128,6 +298,12 @@ impl ConnectionConfig {
             path: Some(path.as_ref().to_string_lossy().to_string()),
             trusted_connection: None,
             timeout: None,
+            policy: None,
+            connector: None,
+            sqlite: None,
+            mssql: None,
+            timestamps: None,
+            overflow: None,
             extra: HashMap::new(),
         }
     }
@@ -145,6 +321,12 @@ impl ConnectionConfig {
             path: None,
             trusted_connection: None,
             timeout: None,
+            policy: None,
+            connector: None,
+            sqlite: None,
+            mssql: None,
+            timestamps: None,
+            overflow: None,
             extra: HashMap::new(),
         }
     }
@@ -268,6 +450,10 @@ impl ConnectionConfig {
 
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
+        // Plugin connectors check their own settings when they connect
+        if self.connector.is_some() {
+            return Ok(());
+        }
         match self.db_type {
             DatabaseType::Postgres => {
                 if self.host.is_none() {
@@ -355,6 +541,81 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_sqlite_options() {
+        let config: ConnectionConfig = toml::from_str(
+            r#"
+            type = "sqlite"
+            path = "edge.db"
+
+            [sqlite]
+            journal_mode = "delete"
+            cache_size = -8000
+            "#,
+        )
+        .unwrap();
+        let options = config.sqlite.unwrap();
+        assert_eq!(options.journal_mode, "delete");
+        assert_eq!(options.cache_size, Some(-8000));
+        // Options left out keep their defaults
+        assert_eq!(options.busy_timeout_ms, 5000);
+        assert!(options.foreign_keys);
+        assert!(ConnectionConfig::sqlite("edge.db").sqlite.is_none());
+    }
+
+    #[test]
+    fn test_mssql_options() {
+        let config: ConnectionConfig = toml::from_str(
+            r#"
+            type = "mssql"
+            server = "historian"
+
+            [mssql]
+            decimals = "float"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.mssql.unwrap().decimals, DecimalMode::Float);
+        assert_eq!(MssqlOptions::default().decimals, DecimalMode::Decimal);
+    }
+
+    #[test]
+    fn test_overflow_mode() {
+        let config: ConnectionConfig = toml::from_str(
+            r#"
+            type = "postgres"
+            overflow = "saturate"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.overflow, Some(OverflowMode::Saturate));
+        assert!(
+            toml::from_str::<ConnectionConfig>("type = \"postgres\"\noverflow = \"wrap\"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_timestamp_mode() {
+        let config: ConnectionConfig = toml::from_str(
+            r#"
+            type = "postgres"
+            timestamps = "Europe/Berlin"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.timestamps,
+            Some(TimestampMode::Zone("Europe/Berlin".to_string()))
+        );
+        assert_eq!("UTC".parse::<TimestampMode>().unwrap(), TimestampMode::Utc);
+        assert_eq!(
+            "naive".parse::<TimestampMode>().unwrap(),
+            TimestampMode::Naive
+        );
+        assert!("Europe/ Berlin".parse::<TimestampMode>().is_err());
+        assert!("".parse::<TimestampMode>().is_err());
+    }
+
     #[test]
     fn test_uri_generation() {
         let config = ConnectionConfig::postgres(
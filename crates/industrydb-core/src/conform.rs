@@ -0,0 +1,777 @@
+//! Conforming frames to the tables they are written to
+//!
+//! A frame that does not fit its target table fails part-way through an
+//! insert, with a driver error about one value of one row. Checking the
+//! frame against the table's introspected columns first reports every
+//! mismatch at once, before anything is written. Frames read from CSV
+//! files mostly hold text; a [`CoercionPolicy`] converts them to the
+//! column types up front, deciding per column whether a value that does
+//! not convert cleanly is an error, a null or, for over-long text, cut to
+//! fit. [`InsertOptions`] also fills the table's audit columns, see
+//! [`crate::audit`].
+//!
+//! ```ignore
+//! let options = InsertOptions::default()
+//!     .coerce(CoercionPolicy::new(Coercion::Lossy).column("tag", Coercion::Strict))
+//!     .validate(true);
+//! let df = options.prepare(&conn, "readings", df).await?;
+//! conn.insert("readings", df).await?;
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDateTime;
+use polars::prelude::{
+    ChunkCompareEq, DataFrame, DataType, NamedFrom, PlSmallStr, PolarsError, Series, TimeUnit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{audit_time, AuditColumns};
+use crate::config::DatabaseType;
+use crate::ddl::native_dtype;
+use crate::error::{IndustryDbError, Result};
+use crate::schema::{column_infos, quote_literal, split_qualified, ColumnInfo};
+use crate::time::parse_timestamp;
+use crate::traits::{CrudOperations, DatabaseConnector};
+
+/// How values are converted to the type of their table column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coercion {
+    /// Every value must convert without change; anything else is an error
+    #[default]
+    Strict,
+    /// Values that do not convert become null, and floats written to
+    /// integer columns are truncated toward zero
+    Lossy,
+    /// As [`Lossy`](Self::Lossy), and text longer than its column is cut
+    /// to the column's length instead of failing
+    Truncate,
+}
+
+impl std::str::FromStr for Coercion {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(Coercion::Strict),
+            "lossy" => Ok(Coercion::Lossy),
+            "truncate" => Ok(Coercion::Truncate),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported coercion: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Coercion of a frame's columns, with per-column overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoercionPolicy {
+    /// Coercion of columns without an override
+    #[serde(default)]
+    pub default: Coercion,
+    /// Overrides by column name
+    #[serde(default)]
+    pub columns: BTreeMap<String, Coercion>,
+}
+
+impl CoercionPolicy {
+    /// Coerce every column as `default`
+    pub fn new(default: Coercion) -> Self {
+        Self {
+            default,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    /// Coerce `column` as `coercion` instead
+    pub fn column(mut self, column: &str, coercion: Coercion) -> Self {
+        self.columns.insert(column.to_string(), coercion);
+        self
+    }
+
+    fn coercion(&self, column: &str) -> Coercion {
+        self.columns.get(column).copied().unwrap_or(self.default)
+    }
+}
+
+/// What to do to a frame before it is written to a table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InsertOptions {
+    coerce: Option<CoercionPolicy>,
+    validate: bool,
+    audit: Option<AuditColumns>,
+}
+
+impl InsertOptions {
+    /// Convert the frame's values to the column types first, see
+    /// [`coerce_frame`]
+    pub fn coerce(mut self, policy: CoercionPolicy) -> Self {
+        self.coerce = Some(policy);
+        self
+    }
+
+    /// Check the frame against the table's columns, after any coercion,
+    /// see [`validate_insert`]
+    pub fn validate(mut self, enabled: bool) -> Self {
+        self.validate = enabled;
+        self
+    }
+
+    /// Fill the audit columns the table has, see [`AuditColumns`]
+    pub fn audit(mut self, columns: AuditColumns) -> Self {
+        self.audit = Some(columns);
+        self
+    }
+
+    /// Whether the frame is written as is
+    pub fn is_noop(&self) -> bool {
+        self.coerce.is_none() && !self.validate && self.audit.is_none()
+    }
+
+    /// Apply the options to a frame about to be written to `table`
+    ///
+    /// The table is introspected once, and not at all when there is
+    /// nothing to do.
+    pub async fn prepare<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        table: &str,
+        df: DataFrame,
+    ) -> Result<DataFrame> {
+        Ok(self.conform(conn, table, df).await?.0)
+    }
+
+    /// Insert `df` into `table` after [`prepare`](Self::prepare)
+    pub async fn insert<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        table: &str,
+        df: DataFrame,
+    ) -> Result<usize> {
+        let (df, _) = self.conform(conn, table, df).await?;
+        conn.insert(table, df).await
+    }
+
+    /// Upsert `df` into `table` after [`prepare`](Self::prepare), keeping
+    /// the creation audit columns of existing rows
+    pub async fn upsert<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        table: &str,
+        df: DataFrame,
+        key_columns: &[String],
+    ) -> Result<usize> {
+        let (df, keep) = self.conform(conn, table, df).await?;
+        conn.upsert_keeping(table, df, key_columns, &keep).await
+    }
+
+    /// The prepared frame, and the columns an upsert must not overwrite
+    async fn conform<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        table: &str,
+        df: DataFrame,
+    ) -> Result<(DataFrame, Vec<String>)> {
+        if self.is_noop() {
+            return Ok((df, Vec::new()));
+        }
+        let columns = table_columns(conn, table).await?;
+        let db_type = conn.dialect().db_type();
+        let mut df = match &self.coerce {
+            Some(policy) => {
+                let lengths = text_lengths(conn, table, &columns).await?;
+                coerce_frame(df, &columns, &lengths, db_type, policy).map_err(|problems| {
+                    mismatch_error("Cannot coerce data to table", table, &problems)
+                })?
+            }
+            None => df,
+        };
+        let mut keep = Vec::new();
+        if let Some(audit) = &self.audit {
+            df = audit.stamp_insert(df, &columns, db_type, audit_time())?;
+            keep = audit.creation_columns(&columns, db_type);
+        }
+        if self.validate {
+            let mismatches = schema_mismatches(&df, &columns, db_type);
+            if !mismatches.is_empty() {
+                return Err(mismatch_error(
+                    "Data does not match table",
+                    table,
+                    &mismatches,
+                ));
+            }
+        }
+        Ok((df, keep))
+    }
+}
+
+/// One way a frame column does not fit its table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaMismatch {
+    /// Frame column
+    pub column: String,
+    /// What does not fit
+    pub problem: String,
+}
+
+impl std::fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "column '{}' {}", self.column, self.problem)
+    }
+}
+
+/// Kinds of values a column type holds, for comparing frame and table
+/// types loosely
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Boolean,
+    Integer,
+    Float,
+    Decimal,
+    Text,
+    Binary,
+    Date,
+    Datetime,
+    Time,
+    Duration,
+    Other,
+}
+
+fn kind(dtype: &DataType) -> Kind {
+    match dtype {
+        DataType::Boolean => Kind::Boolean,
+        dtype if dtype.is_integer() => Kind::Integer,
+        dtype if dtype.is_float() => Kind::Float,
+        DataType::Decimal(..) => Kind::Decimal,
+        DataType::String => Kind::Text,
+        DataType::Binary => Kind::Binary,
+        DataType::Date => Kind::Date,
+        DataType::Datetime(..) => Kind::Datetime,
+        DataType::Time => Kind::Time,
+        DataType::Duration(..) => Kind::Duration,
+        _ => Kind::Other,
+    }
+}
+
+/// Whether values of frame kind `value` can be written to a column of
+/// kind `column`
+///
+/// Integers widen to floats and decimals, and text is accepted wherever
+/// the database parses it (dates and times). Text columns take anything
+/// but binary, as every database casts values to text on assignment.
+fn accepts(column: Kind, value: Kind) -> bool {
+    use Kind::*;
+    match (column, value) {
+        (_, Other) => true,
+        (Text, value) => value != Binary,
+        (Integer, Boolean) => true,
+        (Float | Decimal, Integer | Float | Decimal) => true,
+        (Date | Datetime, Date | Datetime | Text) => true,
+        (Time | Duration, Text) => true,
+        (column, value) => column == value,
+    }
+}
+
+/// Table column a frame column is written to; names are case-insensitive
+/// except on PostgreSQL
+pub(crate) fn find_column<'a>(
+    columns: &'a [ColumnInfo],
+    name: &str,
+    db_type: DatabaseType,
+) -> Option<&'a ColumnInfo> {
+    columns.iter().find(|c| match db_type {
+        DatabaseType::Postgres => c.name == name,
+        _ => c.name.eq_ignore_ascii_case(name),
+    })
+}
+
+/// Mismatches between a frame and the columns of its target table
+///
+/// Reports frame columns the table does not have, values of a type the
+/// column cannot take, and nulls in `NOT NULL` columns. Table columns the
+/// frame leaves out are not checked, since identity, computed and
+/// defaulted columns fill themselves. Column names are compared
+/// case-insensitively except on PostgreSQL, and columns of SQLite tables
+/// declared without a type take any value.
+pub fn schema_mismatches(
+    df: &DataFrame,
+    columns: &[ColumnInfo],
+    db_type: DatabaseType,
+) -> Vec<SchemaMismatch> {
+    let mut mismatches = Vec::new();
+    for values in df.get_columns() {
+        let name = values.name().as_str();
+        let mismatch = |problem: String| SchemaMismatch {
+            column: name.to_string(),
+            problem,
+        };
+        let Some(column) = find_column(columns, name, db_type) else {
+            mismatches.push(mismatch("is not a column of the table".to_string()));
+            continue;
+        };
+        let untyped = db_type == DatabaseType::Sqlite && column.data_type.trim().is_empty();
+        if !untyped
+            && values.dtype() != &DataType::Null
+            && !accepts(
+                kind(&native_dtype(&column.data_type, db_type)),
+                kind(values.dtype()),
+            )
+        {
+            mismatches.push(mismatch(format!(
+                "holds {} values but the table column is {}",
+                values.dtype(),
+                column.data_type
+            )));
+        }
+        let nulls = values.null_count();
+        if !column.nullable && nulls > 0 {
+            mismatches.push(mismatch(format!(
+                "has {} null values but the table column is NOT NULL",
+                nulls
+            )));
+        }
+    }
+    mismatches
+}
+
+/// Check that `df` fits the introspected columns of `table` before it is
+/// written, see [`schema_mismatches`]
+///
+/// Fails with a constraint violation listing every mismatch.
+pub async fn validate_insert<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    table: &str,
+    df: &DataFrame,
+) -> Result<()> {
+    let columns = table_columns(conn, table).await?;
+    let mismatches = schema_mismatches(df, &columns, conn.dialect().db_type());
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    Err(mismatch_error(
+        "Data does not match table",
+        table,
+        &mismatches,
+    ))
+}
+
+/// Introspected columns of `table`, which must exist
+pub(crate) async fn table_columns<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    table: &str,
+) -> Result<Vec<ColumnInfo>> {
+    let columns = column_infos(&conn.describe_table(table).await?)?;
+    if columns.is_empty() {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Table {} does not exist",
+            table
+        )));
+    }
+    Ok(columns)
+}
+
+fn mismatch_error(what: &str, table: &str, mismatches: &[SchemaMismatch]) -> IndustryDbError {
+    let lines: Vec<String> = mismatches.iter().map(|m| format!("  {}", m)).collect();
+    IndustryDbError::constraint_violation(format!("{} {}:\n{}", what, table, lines.join("\n")))
+}
+
+/// Declared length of a text type such as `VARCHAR(20)`
+fn declared_length(data_type: &str) -> Option<usize> {
+    let lower = data_type.to_lowercase();
+    let (base, args) = lower.split_once('(')?;
+    if !base.contains("char") && !base.contains("text") {
+        return None;
+    }
+    args.trim_end_matches(')').trim().parse().ok()
+}
+
+/// Longest text each bounded text column of `table` takes, in characters
+///
+/// PostgreSQL and SQL Server report lengths apart from the type name, so
+/// they are looked up in the catalog; SQLite does not enforce lengths and
+/// the declared ones are used.
+async fn text_lengths<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    table: &str,
+    columns: &[ColumnInfo],
+) -> Result<HashMap<String, usize>> {
+    let (schema, name) = split_qualified(table);
+    let sql = match conn.dialect().db_type() {
+        DatabaseType::Sqlite => {
+            return Ok(columns
+                .iter()
+                .filter_map(|c| Some((c.name.clone(), declared_length(&c.data_type)?)))
+                .collect())
+        }
+        DatabaseType::Postgres => format!(
+            "SELECT column_name, character_maximum_length AS max_length \
+             FROM information_schema.columns \
+             WHERE table_schema = {} AND table_name = {} \
+             AND character_maximum_length IS NOT NULL",
+            schema.map_or("current_schema()".to_string(), quote_literal),
+            quote_literal(name)
+        ),
+        DatabaseType::Mssql => format!(
+            "SELECT c.name AS column_name, \
+             CASE WHEN t.name IN ('nchar', 'nvarchar') THEN c.max_length / 2 \
+             ELSE c.max_length END AS max_length \
+             FROM sys.columns c JOIN sys.types t ON c.user_type_id = t.user_type_id \
+             WHERE c.object_id = OBJECT_ID({}) \
+             AND t.name IN ('char', 'varchar', 'nchar', 'nvarchar') AND c.max_length > 0",
+            quote_literal(&conn.dialect().identifier(table)?)
+        ),
+    };
+    let df = conn.execute(&sql).await?;
+    if df.height() == 0 {
+        return Ok(HashMap::new());
+    }
+    let names = df.column("column_name")?.cast(&DataType::String)?;
+    let lengths = df.column("max_length")?.cast(&DataType::Int64)?;
+    Ok(names
+        .str()?
+        .into_iter()
+        .zip(lengths.i64()?)
+        .filter_map(|(name, length)| Some((name?.to_string(), usize::try_from(length?).ok()?)))
+        .collect())
+}
+
+/// Convert the columns of a frame to the types of the table columns they
+/// are written to
+///
+/// Text is parsed into the column's type: surrounding whitespace is
+/// ignored and empty text is null, timestamps are parsed as
+/// [`parse_timestamp`] does and booleans from `true`/`false`, `t`/`f`,
+/// `yes`/`no` and `1`/`0`. Other values the column does not take are
+/// cast, text columns take anything as text, and text is checked against
+/// the column length in `lengths`. What happens to values that do not
+/// convert cleanly is up to `policy`; with [`Coercion::Strict`] they are
+/// reported, all columns at once. Frame columns the table does not have
+/// are left alone.
+pub fn coerce_frame(
+    mut df: DataFrame,
+    columns: &[ColumnInfo],
+    lengths: &HashMap<String, usize>,
+    db_type: DatabaseType,
+    policy: &CoercionPolicy,
+) -> std::result::Result<DataFrame, Vec<SchemaMismatch>> {
+    let mut problems = Vec::new();
+    let names: Vec<String> = df
+        .get_column_names_str()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    for name in names {
+        let Some(column) = find_column(columns, &name, db_type) else {
+            continue;
+        };
+        if db_type == DatabaseType::Sqlite && column.data_type.trim().is_empty() {
+            continue;
+        }
+        let target = native_dtype(&column.data_type, db_type);
+        let length = lengths.get(&column.name).copied();
+        let Ok(values) = df.column(&name).map(|c| c.as_materialized_series().clone()) else {
+            continue;
+        };
+        match coerce_values(&values, &target, length, policy.coercion(&name)) {
+            Ok(Some(coerced)) => {
+                if let Err(e) = df.with_column(coerced) {
+                    problems.push(SchemaMismatch {
+                        column: name,
+                        problem: e.to_string(),
+                    });
+                }
+            }
+            Ok(None) => {}
+            Err(problem) => problems.push(SchemaMismatch {
+                column: name,
+                problem,
+            }),
+        }
+    }
+    match problems.is_empty() {
+        true => Ok(df),
+        false => Err(problems),
+    }
+}
+
+/// `values` converted to `target`, `None` when they are written as they
+/// are, or the reason they cannot be
+fn coerce_values(
+    values: &Series,
+    target: &DataType,
+    length: Option<usize>,
+    coercion: Coercion,
+) -> std::result::Result<Option<Series>, String> {
+    let (column, value) = (kind(target), kind(values.dtype()));
+    let text = |e: PolarsError| e.to_string();
+
+    if column == Kind::Text {
+        if value == Kind::Binary {
+            return Err(format!(
+                "holds binary values but the table column is {}",
+                target
+            ));
+        }
+        let values = match value {
+            Kind::Text => values.clone(),
+            _ => values.cast(&DataType::String).map_err(text)?,
+        };
+        let Some(length) = length else {
+            return Ok((value != Kind::Text).then_some(values));
+        };
+        let strings = values.str().map_err(text)?;
+        let long = strings
+            .into_iter()
+            .flatten()
+            .filter(|s| s.chars().count() > length)
+            .count();
+        if long == 0 {
+            return Ok((value != Kind::Text).then_some(values));
+        }
+        if coercion < Coercion::Truncate {
+            return Err(format!(
+                "has {} values longer than the column's {} characters",
+                long, length
+            ));
+        }
+        let cut: Vec<Option<String>> = strings
+            .into_iter()
+            .map(|s| s.map(|s| s.chars().take(length).collect()))
+            .collect();
+        return Ok(Some(Series::new(values.name().clone(), cut)));
+    }
+
+    if value == Kind::Text {
+        return parse_text(values, target, column, coercion);
+    }
+    if accepts(column, value) || matches!(column, Kind::Other | Kind::Time | Kind::Duration) {
+        return Ok(None);
+    }
+    let cast = values.cast(target).map_err(text)?;
+    let back = cast.cast(values.dtype()).map_err(text)?;
+    let changed = values
+        .not_equal_missing(&back)
+        .map_err(text)?
+        .sum()
+        .unwrap_or(0);
+    if changed > 0 && coercion == Coercion::Strict {
+        return Err(format!(
+            "has {} {} values that do not fit the table column type {}",
+            changed,
+            values.dtype(),
+            target
+        ));
+    }
+    Ok(Some(cast))
+}
+
+/// Text parsed into the type of its table column
+fn parse_text(
+    values: &Series,
+    target: &DataType,
+    column: Kind,
+    coercion: Coercion,
+) -> std::result::Result<Option<Series>, String> {
+    let text = |e: PolarsError| e.to_string();
+    let name: PlSmallStr = values.name().clone();
+    let trimmed: Vec<Option<&str>> = values
+        .str()
+        .map_err(text)?
+        .into_iter()
+        .map(|v| v.map(str::trim).filter(|v| !v.is_empty()))
+        .collect();
+    let parsed = match column {
+        Kind::Date | Kind::Datetime => {
+            let micros: Vec<Option<i64>> = trimmed
+                .iter()
+                .map(|v| {
+                    v.and_then(|v| parse_timestamp(v).ok())
+                        .map(|ts: NaiveDateTime| ts.and_utc().timestamp_micros())
+                })
+                .collect();
+            Series::new(name, micros)
+                .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
+                .and_then(|ts| ts.cast(target))
+                .map_err(text)?
+        }
+        Kind::Boolean => {
+            let flags: Vec<Option<bool>> = trimmed
+                .iter()
+                .map(|v| {
+                    v.and_then(|v| match v.to_lowercase().as_str() {
+                        "true" | "t" | "yes" | "y" | "1" => Some(true),
+                        "false" | "f" | "no" | "n" | "0" => Some(false),
+                        _ => None,
+                    })
+                })
+                .collect();
+            Series::new(name, flags)
+        }
+        Kind::Text | Kind::Time | Kind::Duration | Kind::Other => return Ok(None),
+        _ => Series::new(name, &trimmed).cast(target).map_err(text)?,
+    };
+
+    // Values that were there before parsing and are null after it
+    let nulls = parsed.is_null();
+    let failed: Vec<&str> = trimmed
+        .iter()
+        .zip(&nulls)
+        .filter_map(|(v, null)| v.filter(|_| null == Some(true)))
+        .collect();
+    if let (Some(first), Coercion::Strict) = (failed.first(), coercion) {
+        return Err(format!(
+            "has {} values that are not {}, such as '{}'",
+            failed.len(),
+            target,
+            first
+        ));
+    }
+    Ok(Some(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::df;
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn test_schema_mismatches() {
+        let columns = [
+            column("ts", "timestamp without time zone", false),
+            column("tag", "text", false),
+            column("value", "double precision", true),
+            column("count", "integer", true),
+        ];
+        let fits = df!(
+            "ts" => ["2024-03-01 08:00:00"],
+            "tag" => ["TI-101"],
+            "value" => [1i64],
+        )
+        .unwrap();
+        assert!(schema_mismatches(&fits, &columns, DatabaseType::Postgres).is_empty());
+
+        let messy = df!(
+            "tag" => [Some("TI-101"), None],
+            "count" => [1.5, 2.0],
+            "Value" => [1.0, 2.0],
+        )
+        .unwrap();
+        let found: Vec<String> = schema_mismatches(&messy, &columns, DatabaseType::Postgres)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "column 'tag' has 1 null values but the table column is NOT NULL",
+                "column 'count' holds f64 values but the table column is integer",
+                "column 'Value' is not a column of the table",
+            ]
+        );
+        assert_eq!(
+            schema_mismatches(&messy, &columns, DatabaseType::Sqlite).len(),
+            2
+        );
+
+        let untyped = [column("count", "", true)];
+        assert!(schema_mismatches(
+            &messy.select(["count"]).unwrap(),
+            &untyped,
+            DatabaseType::Sqlite
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_coerce_frame() {
+        let columns = [
+            column("ts", "TIMESTAMP", false),
+            column("tag", "VARCHAR(6)", false),
+            column("value", "REAL", true),
+            column("count", "INTEGER", true),
+            column("ok", "BOOLEAN", true),
+        ];
+        let lengths = HashMap::from([("tag".to_string(), 6)]);
+        let messy = df!(
+            "ts" => ["2024-03-01 08:00:00", " 2024-03-01T09:00:00 "],
+            "tag" => ["TI-101", "TI-1002"],
+            "value" => ["1.5", "n/a"],
+            "count" => [1.0, 2.5],
+            "ok" => ["yes", ""],
+        )
+        .unwrap();
+
+        let strict = coerce_frame(
+            messy.clone(),
+            &columns,
+            &lengths,
+            DatabaseType::Sqlite,
+            &CoercionPolicy::default(),
+        )
+        .unwrap_err();
+        let found: Vec<String> = strict.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            [
+                "column 'tag' has 1 values longer than the column's 6 characters",
+                "column 'value' has 1 values that are not f64, such as 'n/a'",
+                "column 'count' has 1 f64 values that do not fit the table column type i64",
+            ]
+        );
+
+        let policy = CoercionPolicy::new(Coercion::Lossy).column("tag", Coercion::Truncate);
+        let df = coerce_frame(messy, &columns, &lengths, DatabaseType::Sqlite, &policy).unwrap();
+        assert!(schema_mismatches(&df, &columns, DatabaseType::Sqlite).is_empty());
+        assert_eq!(
+            df.column("ts").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+        assert_eq!(df.column("ts").unwrap().null_count(), 0);
+        let tags: Vec<Option<&str>> = df
+            .column("tag")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(tags, [Some("TI-101"), Some("TI-100")]);
+        let values: Vec<Option<f64>> = df
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(1.5), None]);
+        let counts: Vec<Option<i64>> = df
+            .column("count")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(counts, [Some(1), Some(2)]);
+        let ok: Vec<Option<bool>> = df
+            .column("ok")
+            .unwrap()
+            .bool()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(ok, [Some(true), None]);
+
+        assert_eq!("Lossy".parse::<Coercion>().unwrap(), Coercion::Lossy);
+        assert!("loose".parse::<Coercion>().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Bulk transfer with PostgreSQL's COPY
+//!
+//! [`DatabaseConnector::copy_out`](crate::traits::DatabaseConnector::copy_out)
+//! streams a query's rows in the server's own text or binary encoding,
+//! skipping the per-row protocol and decoding that
+//! [`execute`](crate::traits::DatabaseConnector::execute) goes through;
+//! [`copy_in`](crate::traits::DatabaseConnector::copy_in) loads CSV into a
+//! table the same way, without building INSERT statements.
+
+use crate::error::{IndustryDbError, Result};
+
+/// Encoding of the rows of a COPY
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyFormat {
+    /// CSV with a header row
+    #[default]
+    Csv,
+    /// PostgreSQL's binary COPY format, readable by `COPY ... FROM` with
+    /// `FORMAT binary`
+    Binary,
+}
+
+impl std::str::FromStr for CopyFormat {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(CopyFormat::Csv),
+            "binary" => Ok(CopyFormat::Binary),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported COPY format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl CopyFormat {
+    /// Options of a `COPY ... TO STDOUT` in this format
+    pub fn options(&self) -> &'static str {
+        match self {
+            CopyFormat::Csv => "FORMAT csv, HEADER true",
+            CopyFormat::Binary => "FORMAT binary",
+        }
+    }
+}
+
+/// Bytes read from the input and sent to the server at a time by
+/// [`DatabaseConnector::copy_in`](crate::traits::DatabaseConnector::copy_in)
+pub const COPY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// How the CSV of a `COPY ... FROM STDIN` is laid out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvCopyOptions {
+    /// Character between fields
+    pub delimiter: char,
+    /// Unquoted text read as NULL; an empty unquoted field by default
+    pub null: String,
+    /// Whether the first line is a header to skip
+    pub header: bool,
+}
+
+impl Default for CsvCopyOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            null: String::new(),
+            header: true,
+        }
+    }
+}
+
+impl CsvCopyOptions {
+    /// Options of a `COPY ... FROM STDIN` reading this CSV
+    ///
+    /// Fails for a delimiter PostgreSQL cannot use: a line break, a quote,
+    /// or a character wider than one byte.
+    pub fn options(&self) -> Result<String> {
+        if !self.delimiter.is_ascii() || matches!(self.delimiter, '\r' | '\n' | '"') {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported CSV delimiter: {:?}",
+                self.delimiter
+            )));
+        }
+        Ok(format!(
+            "FORMAT csv, DELIMITER {}, NULL {}, HEADER {}",
+            literal(&self.delimiter.to_string()),
+            literal(&self.null),
+            self.header
+        ))
+    }
+}
+
+/// `text` as a SQL string literal
+fn literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_copy_options() {
+        assert_eq!(
+            CsvCopyOptions::default().options().unwrap(),
+            "FORMAT csv, DELIMITER ',', NULL '', HEADER true"
+        );
+        let options = CsvCopyOptions {
+            delimiter: ';',
+            null: "N'A".to_string(),
+            header: false,
+        };
+        assert_eq!(
+            options.options().unwrap(),
+            "FORMAT csv, DELIMITER ';', NULL 'N''A', HEADER false"
+        );
+        for delimiter in ['\n', '"', '§'] {
+            let options = CsvCopyOptions {
+                delimiter,
+                ..Default::default()
+            };
+            assert!(options.options().is_err());
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! DDL generation
+//!
+//! Maps between native column types and Polars dtypes so a schema read from
+//! one database can be recreated on another, e.g. a plant-site SQLite schema
+//! on a central PostgreSQL server.
+
+use polars::prelude::*;
+
+use crate::config::DatabaseType;
+use crate::dialect::dialect_for;
+use crate::diff::{DatabaseSchema, TableSchema};
+use crate::error::Result;
+use crate::schema::{ColumnInfo, IndexInfo};
+use crate::traits::DatabaseConnector;
+
+/// Quote a (possibly schema-qualified) identifier for a database
+pub fn quote_identifier(name: &str, db_type: DatabaseType) -> String {
+    dialect_for(db_type).quote_identifier(name)
+}
+
+/// Map a Polars dtype to the closest native column type of a database
+pub fn column_type(dtype: &DataType, db_type: DatabaseType) -> Result<String> {
+    dialect_for(db_type).column_type(dtype)
+}
+
+/// Map a native column type of a database to a Polars dtype
+///
+/// Length and precision arguments are ignored except for DECIMAL/NUMERIC.
+/// Unknown types map to `String`, the lossless fallback.
+pub fn native_dtype(native: &str, db_type: DatabaseType) -> DataType {
+    let lower = native.trim().to_lowercase();
+    let (base, args) = match lower.split_once('(') {
+        Some((base, args)) => (base.trim(), Some(args.trim_end_matches(')'))),
+        None => (lower.as_str(), None),
+    };
+
+    match base {
+        "bool" | "boolean" | "bit" => DataType::Boolean,
+        "tinyint" => DataType::UInt8,
+        "smallint" | "int2" => DataType::Int16,
+        // SQLite INTEGER is a 64-bit integer
+        "int" | "integer" | "int4" | "mediumint" if db_type == DatabaseType::Sqlite => {
+            DataType::Int64
+        }
+        "int" | "integer" | "int4" | "mediumint" | "serial" => DataType::Int32,
+        "bigint" | "int8" | "bigserial" => DataType::Int64,
+        // SQLite REAL is an 8-byte float
+        "real" | "float4" if db_type != DatabaseType::Sqlite => DataType::Float32,
+        "real" | "float4" | "float" | "float8" | "double" | "double precision" => DataType::Float64,
+        "numeric" | "decimal" => {
+            let mut parts = args
+                .unwrap_or_default()
+                .split(',')
+                .map(|p| p.trim().parse::<usize>().ok());
+            let precision = parts.next().flatten();
+            let scale = parts.next().flatten().or(precision.map(|_| 0));
+            DataType::Decimal(precision, scale)
+        }
+        "money" => DataType::Decimal(Some(19), Some(4)),
+        "smallmoney" => DataType::Decimal(Some(10), Some(4)),
+        "bytea" | "blob" | "binary" | "varbinary" | "image" => DataType::Binary,
+        "date" => DataType::Date,
+        "time" | "time without time zone" => DataType::Time,
+        "timestamp"
+        | "timestamp without time zone"
+        | "datetime"
+        | "datetime2"
+        | "smalldatetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+        "timestamptz" | "timestamp with time zone" | "datetimeoffset" => {
+            DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into()))
+        }
+        "interval" => DataType::Duration(TimeUnit::Microseconds),
+        _ => DataType::String,
+    }
+}
+
+/// Column definition for CREATE TABLE / ALTER TABLE ADD, using the native type as-is
+pub(crate) fn column_definition(column: &ColumnInfo, db_type: DatabaseType) -> String {
+    let mut def = format!(
+        "{} {}",
+        quote_identifier(&column.name, db_type),
+        column.data_type
+    );
+    if !column.nullable {
+        def.push_str(" NOT NULL");
+    }
+    if let Some(default) = &column.default {
+        def.push_str(&format!(" DEFAULT {}", default));
+    }
+    def
+}
+
+/// CREATE TABLE statement for a table definition, with its primary key
+pub fn create_table_sql(table: &TableSchema, db_type: DatabaseType) -> String {
+    let mut definitions: Vec<String> = table
+        .columns
+        .iter()
+        .map(|c| column_definition(c, db_type))
+        .collect();
+    if !table.primary_key.is_empty() {
+        let key = table
+            .primary_key
+            .iter()
+            .map(|c| quote_identifier(c, db_type))
+            .collect::<Vec<_>>();
+        definitions.push(format!("PRIMARY KEY ({})", key.join(", ")));
+    }
+    format!(
+        "CREATE TABLE {} ({})",
+        quote_identifier(&table.name, db_type),
+        definitions.join(", ")
+    )
+}
+
+/// CREATE INDEX statement for an index definition
+pub fn create_index_sql(table: &str, index: &IndexInfo, db_type: DatabaseType) -> String {
+    let columns = index
+        .columns
+        .iter()
+        .map(|c| quote_identifier(c, db_type))
+        .collect::<Vec<_>>();
+    format!(
+        "CREATE {}INDEX {} ON {} ({})",
+        if index.unique { "UNIQUE " } else { "" },
+        quote_identifier(&index.name, db_type),
+        quote_identifier(table, db_type),
+        columns.join(", ")
+    )
+}
+
+/// Translate a table definition from one database's types to another's
+///
+/// Column defaults are dialect-specific expressions and are only kept when
+/// source and target are the same database.
+pub fn translate_table(
+    table: &TableSchema,
+    source: DatabaseType,
+    target: DatabaseType,
+) -> Result<TableSchema> {
+    if source == target {
+        return Ok(table.clone());
+    }
+
+    let columns = table
+        .columns
+        .iter()
+        .map(|c| {
+            Ok(ColumnInfo {
+                name: c.name.clone(),
+                data_type: column_type(&native_dtype(&c.data_type, source), target)?,
+                nullable: c.nullable,
+                default: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(TableSchema {
+        name: table.name.clone(),
+        columns,
+        indexes: table.indexes.clone(),
+        primary_key: table.primary_key.clone(),
+    })
+}
+
+/// Generate CREATE TABLE / CREATE INDEX statements for a schema
+///
+/// Indexes that only back the primary key are skipped, as the key is part
+/// of the CREATE TABLE statement.
+pub fn schema_ddl(
+    schema: &DatabaseSchema,
+    source: DatabaseType,
+    target: DatabaseType,
+) -> Result<Vec<String>> {
+    let mut ddl = Vec::new();
+    for table in &schema.tables {
+        let table = translate_table(table, source, target)?;
+        ddl.push(create_table_sql(&table, target));
+        for index in &table.indexes {
+            let backs_primary_key = index.unique
+                && index.columns.len() == table.primary_key.len()
+                && index
+                    .columns
+                    .iter()
+                    .zip(&table.primary_key)
+                    .all(|(a, b)| a.eq_ignore_ascii_case(b));
+            if !backs_primary_key {
+                ddl.push(create_index_sql(&table.name, index, target));
+            }
+        }
+    }
+    Ok(ddl)
+}
+
+/// Introspect a connection and generate DDL to recreate its tables
+///
+/// `target` selects the dialect of the generated statements (the
+/// connection's own dialect when `None`).
+pub async fn dump_schema<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    target: Option<DatabaseType>,
+    schema_name: Option<&str>,
+) -> Result<Vec<String>> {
+    let source: DatabaseType = conn.db_type().parse()?;
+    let schema = DatabaseSchema::introspect(conn, schema_name).await?;
+    schema_ddl(&schema, source, target.unwrap_or(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_dtype() {
+        assert_eq!(
+            native_dtype("INTEGER", DatabaseType::Sqlite),
+            DataType::Int64
+        );
+        assert_eq!(
+            native_dtype("integer", DatabaseType::Postgres),
+            DataType::Int32
+        );
+        assert_eq!(
+            native_dtype("numeric(18, 4)", DatabaseType::Postgres),
+            DataType::Decimal(Some(18), Some(4))
+        );
+        assert_eq!(
+            native_dtype("datetime2", DatabaseType::Mssql),
+            DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+        assert_eq!(
+            native_dtype("geometry", DatabaseType::Postgres),
+            DataType::String
+        );
+    }
+
+    #[test]
+    fn test_sqlite_to_postgres() {
+        let schema = DatabaseSchema {
+            tables: vec![TableSchema {
+                name: "readings".to_string(),
+                columns: vec![
+                    ColumnInfo {
+                        name: "id".to_string(),
+                        data_type: "INTEGER".to_string(),
+                        nullable: false,
+                        default: None,
+                    },
+                    ColumnInfo {
+                        name: "value".to_string(),
+                        data_type: "REAL".to_string(),
+                        nullable: true,
+                        default: Some("0".to_string()),
+                    },
+                ],
+                indexes: vec![
+                    IndexInfo {
+                        name: "sqlite_autoindex_readings_1".to_string(),
+                        columns: vec!["id".to_string()],
+                        unique: true,
+                    },
+                    IndexInfo {
+                        name: "idx_value".to_string(),
+                        columns: vec!["value".to_string()],
+                        unique: false,
+                    },
+                ],
+                primary_key: vec!["id".to_string()],
+            }],
+        };
+
+        let ddl = schema_ddl(&schema, DatabaseType::Sqlite, DatabaseType::Postgres).unwrap();
+        assert_eq!(
+            ddl,
+            vec![
+                "CREATE TABLE \"readings\" (\"id\" BIGINT NOT NULL, \"value\" DOUBLE PRECISION, PRIMARY KEY (\"id\"))",
+                "CREATE INDEX \"idx_value\" ON \"readings\" (\"value\")",
+            ]
+        );
+    }
+}
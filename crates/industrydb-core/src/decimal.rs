@@ -0,0 +1,223 @@
+//! Exact decimal values
+//!
+//! Decimals are passed around as text in [`Value::Decimal`] and stored as an
+//! integer with a scale (`12.50` is 1250 with scale 2), which is how Polars
+//! `Decimal` columns and the database drivers hold them. Up to 38 digits fit
+//! in the 128-bit integer.
+//!
+//! [`Value::Decimal`]: crate::params::Value::Decimal
+
+use polars::prelude::*;
+
+use crate::config::OverflowMode;
+use crate::error::{IndustryDbError, Result};
+
+/// Most digits a decimal can have
+pub const MAX_PRECISION: usize = 38;
+
+/// Largest integer value of a decimal, all 38 digits nines
+const MAX_VALUE: i128 = 10i128.pow(MAX_PRECISION as u32) - 1;
+
+/// Integer value and scale of decimal text such as `-12.50` or `1.5E-7`
+pub fn parse(s: &str) -> Result<(i128, u32)> {
+    let invalid = || IndustryDbError::invalid_parameter(format!("Invalid decimal '{}'", s));
+    let text = s.trim();
+    let (digits, exponent) = match text.find(['e', 'E']) {
+        Some(at) => (
+            &text[..at],
+            text[at + 1..].parse::<i32>().map_err(|_| invalid())?,
+        ),
+        None => (text, 0),
+    };
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, digits.strip_prefix('+').unwrap_or(digits)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !all_digits(whole) || !all_digits(fraction) {
+        return Err(invalid());
+    }
+
+    let mut value: i128 = 0;
+    for b in whole.bytes().chain(fraction.bytes()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((b - b'0') as i128))
+            .ok_or_else(|| too_many_digits(s))?;
+    }
+    let mut scale = fraction.len() as i64 - exponent as i64;
+    if scale < 0 {
+        value = 10i128
+            .checked_pow(scale.unsigned_abs() as u32)
+            .and_then(|factor| value.checked_mul(factor))
+            .ok_or_else(|| too_many_digits(s))?;
+        scale = 0;
+    }
+    if scale > MAX_PRECISION as i64 {
+        return Err(too_many_digits(s));
+    }
+    Ok((if negative { -value } else { value }, scale as u32))
+}
+
+/// Decimal text for an integer value and scale, keeping trailing zeros
+pub fn format(value: i128, scale: u32) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let scale = scale as usize;
+    let padded = format!("{:0>width$}", digits, width = scale + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - scale);
+    let sign = if value < 0 { "-" } else { "" };
+    if fraction.is_empty() {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
+/// Decimal column from integer values and scales, all brought to the
+/// largest scale among them
+pub fn to_series(name: &str, values: &[Option<(i128, u32)>]) -> Result<Series> {
+    let scale = values.iter().flatten().map(|(_, s)| *s).max().unwrap_or(0);
+    let rescaled = values
+        .iter()
+        .map(|v| match v {
+            Some((value, s)) => rescale(*value, *s, scale).map(Some).ok_or_else(|| {
+                IndustryDbError::PolarsError(format!(
+                    "Decimal column '{}' needs more than {} digits",
+                    name, MAX_PRECISION
+                ))
+            }),
+            None => Ok(None),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    decimal_series(name, rescaled, scale)
+}
+
+/// Decimal column from decimal text, with values a decimal cannot hold
+/// (more than 38 digits, `NaN`, `Infinity`) handled as `overflow` says
+pub fn fit_series(name: &str, values: &[Option<String>], overflow: OverflowMode) -> Result<Series> {
+    let parsed: Vec<Option<(i128, u32)>> = values
+        .iter()
+        .map(|v| v.as_deref().and_then(|text| parse(text).ok()))
+        .collect();
+    let scale = parsed.iter().flatten().map(|(_, s)| *s).max().unwrap_or(0);
+
+    let mut fitted = Vec::with_capacity(values.len());
+    for (text, parsed) in values.iter().zip(parsed) {
+        let Some(text) = text else {
+            fitted.push(None);
+            continue;
+        };
+        if let Some(value) = parsed.and_then(|(value, s)| rescale(value, s, scale)) {
+            fitted.push(Some(value));
+            continue;
+        }
+        match overflow {
+            OverflowMode::Error => {
+                return Err(IndustryDbError::QueryError(format!(
+                    "Value {} of column '{}' does not fit a {}-digit decimal; \
+                     set overflow to \"saturate\" or \"string\" to read it",
+                    text, name, MAX_PRECISION
+                )))
+            }
+            OverflowMode::Saturate if text.trim().eq_ignore_ascii_case("nan") => fitted.push(None),
+            OverflowMode::Saturate if text.trim_start().starts_with('-') => {
+                fitted.push(Some(-MAX_VALUE))
+            }
+            OverflowMode::Saturate => fitted.push(Some(MAX_VALUE)),
+            OverflowMode::String => return Ok(Series::new(name.into(), values)),
+        }
+    }
+    decimal_series(name, fitted, scale)
+}
+
+/// `value` at scale `from` brought to scale `to`, if it stays within 38 digits
+fn rescale(value: i128, from: u32, to: u32) -> Option<i128> {
+    10i128
+        .checked_pow(to.checked_sub(from)?)
+        .and_then(|factor| value.checked_mul(factor))
+        .filter(|v| v.unsigned_abs() <= MAX_VALUE as u128)
+}
+
+fn decimal_series(name: &str, values: Vec<Option<i128>>, scale: u32) -> Result<Series> {
+    Int128Chunked::from_iter_options(name.into(), values.into_iter())
+        .into_decimal(Some(MAX_PRECISION), scale as usize)
+        .map(|ca| ca.into_series())
+        .map_err(|e| IndustryDbError::PolarsError(e.to_string()))
+}
+
+fn too_many_digits(s: &str) -> IndustryDbError {
+    IndustryDbError::invalid_parameter(format!(
+        "Decimal '{}' has more than {} digits",
+        s, MAX_PRECISION
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("12.50").unwrap(), (1250, 2));
+        assert_eq!(parse("-0.05").unwrap(), (-5, 2));
+        assert_eq!(parse("+7").unwrap(), (7, 0));
+        assert_eq!(parse(".5").unwrap(), (5, 1));
+        assert_eq!(parse("1.5E-7").unwrap(), (15, 8));
+        assert_eq!(parse("1E+2").unwrap(), (100, 0));
+        assert!(parse("NaN").is_err());
+        assert!(parse("1.2.3").is_err());
+        assert!(parse("-").is_err());
+        assert!(parse(&"9".repeat(40)).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(1250, 2), "12.50");
+        assert_eq!(format(-5, 2), "-0.05");
+        assert_eq!(format(42, 0), "42");
+        for text in ["0.001", "-123.4500", "99999999999999999999.123456789"] {
+            let (value, scale) = parse(text).unwrap();
+            assert_eq!(format(value, scale), text);
+        }
+    }
+
+    #[test]
+    fn test_to_series_uses_largest_scale() {
+        let series = to_series("price", &[Some((125, 1)), None, Some((5, 3))]).unwrap();
+        assert_eq!(
+            series.dtype(),
+            &DataType::Decimal(Some(MAX_PRECISION), Some(3))
+        );
+        let physical = series.to_physical_repr();
+        let values: Vec<_> = physical.i128().unwrap().into_iter().collect();
+        assert_eq!(values, [Some(12_500), None, Some(5)]);
+    }
+
+    #[test]
+    fn test_fit_series_overflow() {
+        let wide = format!("-{}", "9".repeat(40));
+        let values = [
+            Some("1.5".to_string()),
+            Some("NaN".to_string()),
+            Some(wide.clone()),
+            None,
+        ];
+        assert!(fit_series("v", &values, OverflowMode::Error).is_err());
+
+        let saturated = fit_series("v", &values, OverflowMode::Saturate).unwrap();
+        let physical = saturated.to_physical_repr();
+        let saturated: Vec<_> = physical.i128().unwrap().into_iter().collect();
+        assert_eq!(saturated, [Some(15), None, Some(-MAX_VALUE), None]);
+
+        let text = fit_series("v", &values, OverflowMode::String).unwrap();
+        assert_eq!(text.dtype(), &DataType::String);
+        assert_eq!(text.str().unwrap().get(2), Some(wide.as_str()));
+
+        let fits = fit_series("v", &values[..1], OverflowMode::Error).unwrap();
+        assert_eq!(
+            fits.dtype(),
+            &DataType::Decimal(Some(MAX_PRECISION), Some(1))
+        );
+    }
+}
@@ -0,0 +1,959 @@
+//! SQL dialects
+//!
+//! Everything that differs in how SQL is written for each database lives
+//! here — identifier quoting, placeholders, row limits, literals, column
+//! types and upserts — so the operations modules build SQL through one
+//! interface instead of formatting it by hand.
+
+use polars::prelude::*;
+
+use crate::config::DatabaseType;
+use crate::decimal;
+use crate::error::{IndustryDbError, Result};
+use crate::sql::{split_batches, split_statement_batches, ScriptBatch};
+
+/// SQL syntax of one database
+pub trait Dialect: Send + Sync + std::fmt::Debug {
+    /// Database this dialect belongs to
+    fn db_type(&self) -> DatabaseType;
+
+    /// Opening and closing identifier quote characters
+    fn identifier_quotes(&self) -> (char, char) {
+        ('"', '"')
+    }
+
+    /// Quote a (possibly schema-qualified) identifier, escaping embedded quotes
+    fn quote_identifier(&self, name: &str) -> String {
+        let (open, close) = self.identifier_quotes();
+        name.split('.')
+            .map(|part| {
+                let escaped = part.replace(close, &format!("{}{}", close, close));
+                format!("{}{}{}", open, escaped, close)
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Longest identifier (per name part) the database accepts, if limited
+    fn max_identifier_length(&self) -> Option<usize> {
+        Some(128)
+    }
+
+    /// Check that a (possibly schema-qualified) identifier is usable
+    ///
+    /// Quoting makes any other character safe, so only empty parts, control
+    /// characters and over-long names are rejected.
+    fn validate_identifier(&self, name: &str) -> Result<()> {
+        let invalid = |reason: &str| {
+            IndustryDbError::invalid_parameter(format!("Invalid identifier '{}': {}", name, reason))
+        };
+        for part in name.split('.') {
+            if part.is_empty() {
+                return Err(invalid("empty name"));
+            }
+            if part.chars().any(char::is_control) {
+                return Err(invalid("contains control characters"));
+            }
+            if let Some(max) = self.max_identifier_length() {
+                if part.chars().count() > max {
+                    return Err(invalid(&format!("longer than {} characters", max)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate and quote an identifier
+    fn identifier(&self, name: &str) -> Result<String> {
+        self.validate_identifier(name)?;
+        Ok(self.quote_identifier(name))
+    }
+
+    /// Validate and quote a list of identifiers
+    fn identifiers(&self, names: &[String]) -> Result<Vec<String>> {
+        names.iter().map(|n| self.identifier(n)).collect()
+    }
+
+    /// Bind parameter placeholder for the 1-based parameter `index`
+    fn placeholder(&self, index: usize) -> String;
+
+    /// Boolean literal
+    fn bool_literal(&self, value: bool) -> &'static str {
+        if value {
+            "TRUE"
+        } else {
+            "FALSE"
+        }
+    }
+
+    /// Binary literal
+    fn binary_literal(&self, bytes: &[u8]) -> String;
+
+    /// Array literal from the literals of its elements
+    fn array_literal(&self, elements: &[String]) -> Result<String> {
+        let _ = elements;
+        Err(IndustryDbError::invalid_parameter(format!(
+            "{} has no array type for list values",
+            self.db_type()
+        )))
+    }
+
+    /// Most rows a single multi-row VALUES list may carry
+    fn max_rows_per_statement(&self) -> usize {
+        1000
+    }
+
+    /// Whether DDL runs inside a transaction and rolls back with it
+    fn transactional_ddl(&self) -> bool {
+        false
+    }
+
+    /// Closest native column type for a Polars dtype
+    fn column_type(&self, dtype: &DataType) -> Result<String>;
+
+    /// Keyword opening a recursive common table expression
+    fn with_recursive(&self) -> &'static str {
+        "WITH RECURSIVE"
+    }
+
+    /// Concatenate string expressions
+    fn concat(&self, parts: &[String]) -> String {
+        parts.join(" || ")
+    }
+
+    /// Query hint lifting the database's own recursion limit, with a leading
+    /// space, for recursive queries bounded by their own depth predicate
+    fn unbounded_recursion(&self) -> &'static str {
+        ""
+    }
+
+    /// Split a script into the units sent to the database one at a time
+    ///
+    /// Statements separated by `;` by default.
+    fn split_script(&self, sql: &str) -> Vec<ScriptBatch> {
+        split_statement_batches(sql, false)
+    }
+
+    /// One ORDER BY term for an already rendered expression
+    ///
+    /// `collation` is a collation name such as `"C"` (PostgreSQL), `NOCASE`
+    /// (SQLite) or `Latin1_General_CI_AS` (SQL Server).
+    fn order_term(
+        &self,
+        expr: &str,
+        descending: bool,
+        nulls: Option<NullsOrder>,
+        collation: Option<&str>,
+    ) -> Result<String> {
+        let mut term = expr.to_string();
+        if let Some(name) = collation {
+            term.push_str(&format!(" COLLATE {}", self.identifier(name)?));
+        }
+        term.push_str(if descending { " DESC" } else { " ASC" });
+        match nulls {
+            Some(NullsOrder::First) => term.push_str(" NULLS FIRST"),
+            Some(NullsOrder::Last) => term.push_str(" NULLS LAST"),
+            None => {}
+        }
+        Ok(term)
+    }
+
+    /// SELECT over a single table with optional grouping, ordering and paging
+    fn select_sql(
+        &self,
+        columns: &str,
+        table: &str,
+        where_clause: Option<&str>,
+        options: &SelectOptions,
+    ) -> String {
+        let mut sql = format!(
+            "SELECT {}{}",
+            distinct(options),
+            select_body(columns, table, where_clause, options)
+        );
+        if let Some(order) = &options.order_by {
+            sql.push_str(&format!(" ORDER BY {}", order));
+        }
+        sql.push_str(&self.limit_clause(options.limit, options.offset));
+        sql
+    }
+
+    /// Trailing row limit and offset, with a leading space when not empty
+    fn limit_clause(&self, limit: Option<usize>, offset: Option<usize>) -> String {
+        let mut clause = String::new();
+        if let Some(n) = limit {
+            clause.push_str(&format!(" LIMIT {}", n));
+        }
+        if let Some(n) = offset {
+            clause.push_str(&format!(" OFFSET {}", n));
+        }
+        clause
+    }
+
+    /// Insert-or-update of literal rows keyed on `keys`
+    ///
+    /// `rows` hold already-rendered values (literals or placeholders) in
+    /// `columns` order. `keep` columns are written for new rows only and
+    /// left as they are in existing ones.
+    fn upsert_sql(
+        &self,
+        table: &str,
+        columns: &[String],
+        keys: &[String],
+        keep: &[String],
+        rows: &[Vec<String>],
+    ) -> Result<String> {
+        validate_upsert(columns, keys, rows)?;
+
+        let quoted = self.identifiers(columns)?;
+        let conflict = self.identifiers(keys)?;
+        let updates: Vec<String> = columns
+            .iter()
+            .filter(|c| !keys.iter().chain(keep).any(|k| k.eq_ignore_ascii_case(c)))
+            .map(|c| {
+                let q = self.quote_identifier(c);
+                format!("{} = excluded.{}", q, q)
+            })
+            .collect();
+
+        let action = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!("DO UPDATE SET {}", updates.join(", "))
+        };
+
+        Ok(format!(
+            "INSERT INTO {} ({}) VALUES {} ON CONFLICT ({}) {}",
+            self.identifier(table)?,
+            quoted.join(", "),
+            render_rows(rows),
+            conflict.join(", "),
+            action
+        ))
+    }
+
+    /// SQL literal for one value of a Series; NaN and infinite floats have
+    /// none and are rejected rather than written as NULL
+    fn format_value(&self, series: &Series, idx: usize) -> Result<String> {
+        let value = series.get(idx)?;
+        Ok(match value {
+            AnyValue::Null => "NULL".to_string(),
+            AnyValue::Boolean(b) => self.bool_literal(b).to_string(),
+            AnyValue::Int8(_)
+            | AnyValue::Int16(_)
+            | AnyValue::Int32(_)
+            | AnyValue::Int64(_)
+            | AnyValue::UInt8(_)
+            | AnyValue::UInt16(_)
+            | AnyValue::UInt32(_)
+            | AnyValue::UInt64(_) => value.to_string(),
+            AnyValue::Float32(f) if f.is_finite() => value.to_string(),
+            AnyValue::Float64(f) if f.is_finite() => value.to_string(),
+            AnyValue::Float32(_) | AnyValue::Float64(_) => {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Column '{}' holds {} at row {}, which has no SQL literal; \
+                     replace it with null (e.g. fill_nan(None)) before writing",
+                    series.name(),
+                    value,
+                    idx
+                )))
+            }
+            AnyValue::Binary(bytes) => self.binary_literal(bytes),
+            AnyValue::BinaryOwned(ref bytes) => self.binary_literal(bytes),
+            AnyValue::Decimal(v, scale) => decimal::format(v, scale as u32),
+            AnyValue::List(items) => {
+                let elements = (0..items.len())
+                    .map(|i| self.format_value(&items, i))
+                    .collect::<Result<Vec<_>>>()?;
+                self.array_literal(&elements)?
+            }
+            other => match other.get_str() {
+                Some(s) => quote_string(s),
+                None => quote_string(&other.to_string()),
+            },
+        })
+    }
+
+    /// SQL literals for every row of a DataFrame, in column order
+    fn format_rows(&self, data: &DataFrame) -> Result<Vec<Vec<String>>> {
+        let columns = data.get_columns();
+        (0..data.height())
+            .map(|idx| {
+                columns
+                    .iter()
+                    .map(|c| self.format_value(c.as_materialized_series(), idx))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Where NULLs sort relative to other values
+///
+/// Without it each database uses its own default: PostgreSQL sorts NULLs as
+/// the largest values, SQLite and SQL Server as the smallest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    /// NULLs before all other values
+    First,
+    /// NULLs after all other values
+    Last,
+}
+
+impl std::str::FromStr for NullsOrder {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "first" => Ok(NullsOrder::First),
+            "last" => Ok(NullsOrder::Last),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported NULL order: {} (expected 'first' or 'last')",
+                s
+            ))),
+        }
+    }
+}
+
+/// Clauses of a single-table SELECT after WHERE
+///
+/// `group_by`, `having` and `order_by` are clause bodies, e.g. `"ts DESC"`;
+/// `having` may contain placeholders, bound after those of the WHERE clause.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectOptions {
+    /// Drop duplicate rows
+    pub distinct: bool,
+    /// Body of a GROUP BY clause
+    pub group_by: Option<String>,
+    /// Body of a HAVING clause
+    pub having: Option<String>,
+    /// Body of an ORDER BY clause
+    pub order_by: Option<String>,
+    /// Most rows to return
+    pub limit: Option<usize>,
+    /// Rows to skip before the first one returned
+    pub offset: Option<usize>,
+}
+
+fn distinct(options: &SelectOptions) -> &'static str {
+    if options.distinct {
+        "DISTINCT "
+    } else {
+        ""
+    }
+}
+
+/// Everything of a SELECT after the keyword (and DISTINCT / TOP) up to ORDER BY
+fn select_body(
+    columns: &str,
+    table: &str,
+    where_clause: Option<&str>,
+    options: &SelectOptions,
+) -> String {
+    let mut sql = format!("{} FROM {}", columns, table);
+    if let Some(where_cond) = where_clause {
+        sql.push_str(&format!(" WHERE {}", where_cond));
+    }
+    if let Some(group) = &options.group_by {
+        sql.push_str(&format!(" GROUP BY {}", group));
+    }
+    if let Some(having) = &options.having {
+        sql.push_str(&format!(" HAVING {}", having));
+    }
+    sql
+}
+
+/// Quote a string literal, doubling embedded single quotes
+fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn render_rows(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| format!("({})", row.join(", ")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn validate_upsert(columns: &[String], keys: &[String], rows: &[Vec<String>]) -> Result<()> {
+    if keys.is_empty() {
+        return Err(IndustryDbError::invalid_parameter(
+            "Upsert requires at least one key column",
+        ));
+    }
+    if let Some(key) = keys
+        .iter()
+        .find(|k| !columns.iter().any(|c| c.eq_ignore_ascii_case(k)))
+    {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Upsert key column '{}' is not among the inserted columns",
+            key
+        )));
+    }
+    if rows.is_empty() || rows.iter().any(|r| r.len() != columns.len()) {
+        return Err(IndustryDbError::invalid_parameter(
+            "Upsert rows must be non-empty and match the column count",
+        ));
+    }
+    Ok(())
+}
+
+/// PostgreSQL dialect
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostgresDialect;
+
+impl Dialect for PostgresDialect {
+    fn db_type(&self) -> DatabaseType {
+        DatabaseType::Postgres
+    }
+
+    fn transactional_ddl(&self) -> bool {
+        true
+    }
+
+    /// Statements separated by `;`, keeping `$tag$` function bodies whole
+    fn split_script(&self, sql: &str) -> Vec<ScriptBatch> {
+        split_statement_batches(sql, true)
+    }
+
+    /// NAMEDATALEN - 1; longer names are silently truncated by the server
+    fn max_identifier_length(&self) -> Option<usize> {
+        Some(63)
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        format!("${}", index)
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("'\\x{}'::bytea", hex(bytes))
+    }
+
+    /// An empty list is written `'{}'` so it takes the column's array type
+    fn array_literal(&self, elements: &[String]) -> Result<String> {
+        if elements.is_empty() {
+            return Ok("'{}'".to_string());
+        }
+        Ok(format!("ARRAY[{}]", elements.join(", ")))
+    }
+
+    fn column_type(&self, dtype: &DataType) -> Result<String> {
+        let ty = match dtype {
+            DataType::Boolean => "BOOLEAN".to_string(),
+            DataType::Int8 | DataType::Int16 | DataType::UInt8 => "SMALLINT".to_string(),
+            DataType::Int32 | DataType::UInt16 => "INTEGER".to_string(),
+            DataType::Int64 | DataType::UInt32 => "BIGINT".to_string(),
+            DataType::UInt64 => "NUMERIC(20, 0)".to_string(),
+            DataType::Float32 => "REAL".to_string(),
+            DataType::Float64 => "DOUBLE PRECISION".to_string(),
+            DataType::Decimal(Some(p), Some(s)) => format!("NUMERIC({}, {})", p, s),
+            DataType::Decimal(_, _) => "NUMERIC".to_string(),
+            DataType::String | DataType::Null => "TEXT".to_string(),
+            DataType::Binary => "BYTEA".to_string(),
+            DataType::Date => "DATE".to_string(),
+            DataType::Datetime(_, None) => "TIMESTAMP".to_string(),
+            DataType::Datetime(_, Some(_)) => "TIMESTAMPTZ".to_string(),
+            DataType::Time => "TIME".to_string(),
+            DataType::Duration(_) => "INTERVAL".to_string(),
+            DataType::List(inner) => format!("{}[]", self.column_type(inner)?),
+            other => return Err(no_column_type(self.db_type(), other)),
+        };
+        Ok(ty)
+    }
+}
+
+/// SQLite dialect
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteDialect;
+
+impl Dialect for SqliteDialect {
+    fn db_type(&self) -> DatabaseType {
+        DatabaseType::Sqlite
+    }
+
+    fn transactional_ddl(&self) -> bool {
+        true
+    }
+
+    fn max_identifier_length(&self) -> Option<usize> {
+        None
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        format!("?{}", index)
+    }
+
+    fn bool_literal(&self, value: bool) -> &'static str {
+        if value {
+            "1"
+        } else {
+            "0"
+        }
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("X'{}'", hex(bytes))
+    }
+
+    /// SQLite only accepts OFFSET after a LIMIT; -1 means no limit
+    fn limit_clause(&self, limit: Option<usize>, offset: Option<usize>) -> String {
+        match (limit, offset) {
+            (None, Some(n)) => format!(" LIMIT -1 OFFSET {}", n),
+            (Some(l), Some(n)) => format!(" LIMIT {} OFFSET {}", l, n),
+            (Some(l), None) => format!(" LIMIT {}", l),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// SQLite caps compound SELECTs, which VALUES lists count as, at 500
+    fn max_rows_per_statement(&self) -> usize {
+        500
+    }
+
+    /// SQLite uses type affinity; dates are stored as ISO-8601 text
+    fn column_type(&self, dtype: &DataType) -> Result<String> {
+        let ty = match dtype {
+            DataType::Boolean
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64 => "INTEGER",
+            DataType::Float32 | DataType::Float64 => "REAL",
+            DataType::Decimal(_, _) => "NUMERIC",
+            DataType::String | DataType::Null => "TEXT",
+            DataType::Binary => "BLOB",
+            DataType::Date | DataType::Datetime(_, _) | DataType::Time => "TEXT",
+            other => return Err(no_column_type(self.db_type(), other)),
+        };
+        Ok(ty.to_string())
+    }
+}
+
+/// Microsoft SQL Server dialect
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MssqlDialect;
+
+impl Dialect for MssqlDialect {
+    fn db_type(&self) -> DatabaseType {
+        DatabaseType::Mssql
+    }
+
+    fn transactional_ddl(&self) -> bool {
+        true
+    }
+
+    fn identifier_quotes(&self) -> (char, char) {
+        ('[', ']')
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        format!("@P{}", index)
+    }
+
+    fn bool_literal(&self, value: bool) -> &'static str {
+        if value {
+            "1"
+        } else {
+            "0"
+        }
+    }
+
+    fn binary_literal(&self, bytes: &[u8]) -> String {
+        format!("0x{}", hex(bytes))
+    }
+
+    fn column_type(&self, dtype: &DataType) -> Result<String> {
+        let ty = match dtype {
+            DataType::Boolean => "BIT".to_string(),
+            DataType::UInt8 => "TINYINT".to_string(),
+            DataType::Int8 | DataType::Int16 => "SMALLINT".to_string(),
+            DataType::Int32 | DataType::UInt16 => "INT".to_string(),
+            DataType::Int64 | DataType::UInt32 => "BIGINT".to_string(),
+            DataType::UInt64 => "DECIMAL(20, 0)".to_string(),
+            DataType::Float32 => "REAL".to_string(),
+            DataType::Float64 => "FLOAT".to_string(),
+            DataType::Decimal(Some(p), Some(s)) => format!("DECIMAL({}, {})", p, s),
+            DataType::Decimal(_, _) => "DECIMAL(38, 10)".to_string(),
+            DataType::String | DataType::Null => "NVARCHAR(MAX)".to_string(),
+            DataType::Binary => "VARBINARY(MAX)".to_string(),
+            DataType::Date => "DATE".to_string(),
+            DataType::Datetime(_, None) => "DATETIME2".to_string(),
+            DataType::Datetime(_, Some(_)) => "DATETIMEOFFSET".to_string(),
+            DataType::Time => "TIME".to_string(),
+            other => return Err(no_column_type(self.db_type(), other)),
+        };
+        Ok(ty)
+    }
+
+    fn with_recursive(&self) -> &'static str {
+        "WITH"
+    }
+
+    fn concat(&self, parts: &[String]) -> String {
+        parts.join(" + ")
+    }
+
+    /// Recursive CTEs stop after 100 levels unless told otherwise
+    fn unbounded_recursion(&self) -> &'static str {
+        " OPTION (MAXRECURSION 0)"
+    }
+
+    /// Batches separated by `GO` lines, as written for sqlcmd and SSMS
+    fn split_script(&self, sql: &str) -> Vec<ScriptBatch> {
+        split_batches(sql)
+    }
+
+    /// T-SQL has no NULLS FIRST/LAST, so a CASE on NULL sorts first, and
+    /// collation names are not quotable identifiers
+    fn order_term(
+        &self,
+        expr: &str,
+        descending: bool,
+        nulls: Option<NullsOrder>,
+        collation: Option<&str>,
+    ) -> Result<String> {
+        let mut term = String::new();
+        match nulls {
+            Some(NullsOrder::First) => {
+                term.push_str(&format!("CASE WHEN {} IS NULL THEN 0 ELSE 1 END, ", expr))
+            }
+            Some(NullsOrder::Last) => {
+                term.push_str(&format!("CASE WHEN {} IS NULL THEN 1 ELSE 0 END, ", expr))
+            }
+            None => {}
+        }
+        term.push_str(expr);
+        if let Some(name) = collation {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Invalid collation name: {}",
+                    name
+                )));
+            }
+            term.push_str(&format!(" COLLATE {}", name));
+        }
+        term.push_str(if descending { " DESC" } else { " ASC" });
+        Ok(term)
+    }
+
+    /// T-SQL has no LIMIT: the row limit is `TOP (n)` for unordered selects
+    /// without an offset, and OFFSET/FETCH otherwise. OFFSET needs an ORDER
+    /// BY, so `ORDER BY (SELECT NULL)` is added when none is given.
+    fn select_sql(
+        &self,
+        columns: &str,
+        table: &str,
+        where_clause: Option<&str>,
+        options: &SelectOptions,
+    ) -> String {
+        let paged = options.order_by.is_some() || options.offset.is_some();
+        let top = match (paged, options.limit) {
+            (false, Some(n)) => format!("TOP ({}) ", n),
+            _ => String::new(),
+        };
+        let mut sql = format!(
+            "SELECT {}{}{}",
+            distinct(options),
+            top,
+            select_body(columns, table, where_clause, options)
+        );
+        if let Some(order) = &options.order_by {
+            sql.push_str(&format!(" ORDER BY {}", order));
+        } else if options.offset.is_some() {
+            sql.push_str(" ORDER BY (SELECT NULL)");
+        }
+        if paged && (options.limit.is_some() || options.offset.is_some()) {
+            sql.push_str(&format!(" OFFSET {} ROWS", options.offset.unwrap_or(0)));
+            if let Some(n) = options.limit {
+                sql.push_str(&format!(" FETCH NEXT {} ROWS ONLY", n));
+            }
+        }
+        sql
+    }
+
+    /// T-SQL upserts with MERGE over a VALUES source
+    fn upsert_sql(
+        &self,
+        table: &str,
+        columns: &[String],
+        keys: &[String],
+        keep: &[String],
+        rows: &[Vec<String>],
+    ) -> Result<String> {
+        validate_upsert(columns, keys, rows)?;
+
+        let quoted = self.identifiers(columns)?;
+        let on: Vec<String> = keys
+            .iter()
+            .map(|k| {
+                let q = self.quote_identifier(k);
+                format!("target.{} = source.{}", q, q)
+            })
+            .collect();
+        let updates: Vec<String> = columns
+            .iter()
+            .filter(|c| !keys.iter().chain(keep).any(|k| k.eq_ignore_ascii_case(c)))
+            .map(|c| {
+                let q = self.quote_identifier(c);
+                format!("{} = source.{}", q, q)
+            })
+            .collect();
+        let source_columns: Vec<String> = quoted.iter().map(|q| format!("source.{}", q)).collect();
+
+        let mut sql = format!(
+            "MERGE INTO {} AS target USING (VALUES {}) AS source ({}) ON {}",
+            self.identifier(table)?,
+            render_rows(rows),
+            quoted.join(", "),
+            on.join(" AND ")
+        );
+        if !updates.is_empty() {
+            sql.push_str(&format!(
+                " WHEN MATCHED THEN UPDATE SET {}",
+                updates.join(", ")
+            ));
+        }
+        sql.push_str(&format!(
+            " WHEN NOT MATCHED THEN INSERT ({}) VALUES ({});",
+            quoted.join(", "),
+            source_columns.join(", ")
+        ));
+        Ok(sql)
+    }
+}
+
+fn no_column_type(db_type: DatabaseType, dtype: &DataType) -> IndustryDbError {
+    IndustryDbError::invalid_parameter(format!("No {} column type for dtype {}", db_type, dtype))
+}
+
+/// Dialect for a database type
+pub fn dialect_for(db_type: DatabaseType) -> &'static dyn Dialect {
+    match db_type {
+        DatabaseType::Postgres => &PostgresDialect,
+        DatabaseType::Sqlite => &SqliteDialect,
+        DatabaseType::Mssql => &MssqlDialect,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(
+            PostgresDialect.quote_identifier("public.my\"table"),
+            "\"public\".\"my\"\"table\""
+        );
+        assert_eq!(MssqlDialect.quote_identifier("dbo.a]b"), "[dbo].[a]]b]");
+    }
+
+    #[test]
+    fn test_validate_identifier() {
+        assert_eq!(
+            PostgresDialect.identifier("my table").unwrap(),
+            "\"my table\""
+        );
+        assert_eq!(
+            PostgresDialect
+                .identifier("x\"; DROP TABLE users; --")
+                .unwrap(),
+            "\"x\"\"; DROP TABLE users; --\""
+        );
+        assert!(PostgresDialect.identifier("").is_err());
+        assert!(PostgresDialect.identifier("public.").is_err());
+        assert!(PostgresDialect.identifier("bad\nname").is_err());
+        assert!(PostgresDialect.identifier(&"a".repeat(64)).is_err());
+        assert!(SqliteDialect.identifier(&"a".repeat(64)).is_ok());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(PostgresDialect.placeholder(2), "$2");
+        assert_eq!(SqliteDialect.placeholder(2), "?2");
+        assert_eq!(MssqlDialect.placeholder(2), "@P2");
+    }
+
+    fn paging(
+        order_by: Option<&str>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> SelectOptions {
+        SelectOptions {
+            order_by: order_by.map(str::to_string),
+            limit,
+            offset,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_limit_vs_top() {
+        assert_eq!(
+            SqliteDialect.select_sql(
+                "*",
+                "t",
+                Some("a = 1"),
+                &paging(Some("ts DESC"), Some(5), None)
+            ),
+            "SELECT * FROM t WHERE a = 1 ORDER BY ts DESC LIMIT 5"
+        );
+        assert_eq!(
+            MssqlDialect.select_sql("*", "t", Some("a = 1"), &paging(None, Some(5), None)),
+            "SELECT TOP (5) * FROM t WHERE a = 1"
+        );
+        assert_eq!(
+            MssqlDialect.select_sql("*", "t", None, &paging(Some("ts DESC"), Some(5), None)),
+            "SELECT * FROM t ORDER BY ts DESC OFFSET 0 ROWS FETCH NEXT 5 ROWS ONLY"
+        );
+        assert_eq!(
+            MssqlDialect.select_sql("*", "t", None, &paging(Some("ts"), None, None)),
+            "SELECT * FROM t ORDER BY ts"
+        );
+    }
+
+    #[test]
+    fn test_order_terms() {
+        assert_eq!(
+            PostgresDialect
+                .order_term("\"ts\"", true, Some(NullsOrder::Last), Some("C"))
+                .unwrap(),
+            "\"ts\" COLLATE \"C\" DESC NULLS LAST"
+        );
+        assert_eq!(
+            SqliteDialect
+                .order_term("\"name\"", false, Some(NullsOrder::First), Some("NOCASE"))
+                .unwrap(),
+            "\"name\" COLLATE \"NOCASE\" ASC NULLS FIRST"
+        );
+        assert_eq!(
+            MssqlDialect
+                .order_term(
+                    "[ts]",
+                    true,
+                    Some(NullsOrder::Last),
+                    Some("Latin1_General_CI_AS")
+                )
+                .unwrap(),
+            "CASE WHEN [ts] IS NULL THEN 1 ELSE 0 END, [ts] COLLATE Latin1_General_CI_AS DESC"
+        );
+        assert!(MssqlDialect
+            .order_term("[ts]", false, None, Some("x; DROP TABLE t"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_offset_and_grouping() {
+        let options = SelectOptions {
+            group_by: Some("tag".to_string()),
+            having: Some("COUNT(*) > 1".to_string()),
+            ..paging(Some("tag"), Some(10), Some(20))
+        };
+        assert_eq!(
+            PostgresDialect.select_sql("tag, COUNT(*)", "t", None, &options),
+            "SELECT tag, COUNT(*) FROM t GROUP BY tag HAVING COUNT(*) > 1 \
+             ORDER BY tag LIMIT 10 OFFSET 20"
+        );
+        assert_eq!(
+            MssqlDialect.select_sql("tag, COUNT(*)", "t", None, &options),
+            "SELECT tag, COUNT(*) FROM t GROUP BY tag HAVING COUNT(*) > 1 \
+             ORDER BY tag OFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+        );
+
+        let unique = SelectOptions {
+            distinct: true,
+            ..paging(None, Some(3), None)
+        };
+        assert_eq!(
+            MssqlDialect.select_sql("tag", "t", None, &unique),
+            "SELECT DISTINCT TOP (3) tag FROM t"
+        );
+        assert_eq!(
+            PostgresDialect.select_sql("tag", "t", None, &unique),
+            "SELECT DISTINCT tag FROM t LIMIT 3"
+        );
+
+        let skip = paging(None, None, Some(5));
+        assert_eq!(
+            SqliteDialect.select_sql("*", "t", None, &skip),
+            "SELECT * FROM t LIMIT -1 OFFSET 5"
+        );
+        assert_eq!(
+            MssqlDialect.select_sql("*", "t", None, &skip),
+            "SELECT * FROM t ORDER BY (SELECT NULL) OFFSET 5 ROWS"
+        );
+    }
+
+    #[test]
+    fn test_format_value() {
+        let s = Series::new("b".into(), [Some(true), None]);
+        assert_eq!(PostgresDialect.format_value(&s, 0).unwrap(), "TRUE");
+        assert_eq!(MssqlDialect.format_value(&s, 0).unwrap(), "1");
+        assert_eq!(SqliteDialect.format_value(&s, 1).unwrap(), "NULL");
+
+        let s = Series::new("s".into(), ["it's"]);
+        assert_eq!(PostgresDialect.format_value(&s, 0).unwrap(), "'it''s'");
+
+        let s = crate::decimal::to_series("d", &[Some((1250, 2))]).unwrap();
+        assert_eq!(MssqlDialect.format_value(&s, 0).unwrap(), "12.50");
+
+        let s = Series::new(
+            "l".into(),
+            [
+                Some(Series::new("".into(), ["a", "b'c"])),
+                Some(Series::new_empty("".into(), &DataType::String)),
+            ],
+        );
+        assert_eq!(
+            PostgresDialect.format_value(&s, 0).unwrap(),
+            "ARRAY['a', 'b''c']"
+        );
+        assert_eq!(PostgresDialect.format_value(&s, 1).unwrap(), "'{}'");
+        assert!(SqliteDialect.format_value(&s, 0).is_err());
+
+        let s = Series::new("f".into(), [1.5, f64::NAN, f64::NEG_INFINITY]);
+        assert_eq!(SqliteDialect.format_value(&s, 0).unwrap(), "1.5");
+        assert!(SqliteDialect.format_value(&s, 1).is_err());
+        assert!(PostgresDialect.format_value(&s, 2).is_err());
+    }
+
+    #[test]
+    fn test_upsert() {
+        let columns = vec!["id".to_string(), "value".to_string()];
+        let keys = vec!["id".to_string()];
+        let rows = vec![vec!["1".to_string(), "2.5".to_string()]];
+
+        assert_eq!(
+            PostgresDialect
+                .upsert_sql("t", &columns, &keys, &[], &rows)
+                .unwrap(),
+            "INSERT INTO \"t\" (\"id\", \"value\") VALUES (1, 2.5) ON CONFLICT (\"id\") \
+             DO UPDATE SET \"value\" = excluded.\"value\""
+        );
+        assert_eq!(
+            MssqlDialect
+                .upsert_sql("t", &columns, &keys, &[], &rows)
+                .unwrap(),
+            "MERGE INTO [t] AS target USING (VALUES (1, 2.5)) AS source ([id], [value]) \
+             ON target.[id] = source.[id] WHEN MATCHED THEN UPDATE SET [value] = source.[value] \
+             WHEN NOT MATCHED THEN INSERT ([id], [value]) VALUES (source.[id], source.[value]);"
+        );
+        assert!(PostgresDialect
+            .upsert_sql("t", &columns, &[], &[], &rows)
+            .is_err());
+        assert_eq!(
+            SqliteDialect
+                .upsert_sql("t", &columns, &keys, &["value".to_string()], &rows)
+                .unwrap(),
+            "INSERT INTO \"t\" (\"id\", \"value\") VALUES (1, 2.5) ON CONFLICT (\"id\") DO NOTHING"
+        );
+    }
+}
@@ -0,0 +1,399 @@
+//! Schema comparison between databases
+//!
+//! Compares an expected schema (introspected from a reference connection or
+//! declared in TOML/JSON) with the actual schema of a connection and reports
+//! the differences. Fix-up DDL is additive: missing tables, columns and
+//! indexes are created and column types altered, but nothing is dropped.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::DatabaseType;
+use crate::ddl::{column_definition, create_index_sql, create_table_sql, quote_identifier};
+use crate::error::Result;
+use crate::schema::{self, ColumnInfo, IndexInfo};
+use crate::traits::DatabaseConnector;
+
+/// Declared or introspected definition of one table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    /// Table name
+    pub name: String,
+    /// Columns in table order
+    pub columns: Vec<ColumnInfo>,
+    /// Indexes on the table
+    #[serde(default)]
+    pub indexes: Vec<IndexInfo>,
+    /// Primary key columns in key order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub primary_key: Vec<String>,
+}
+
+/// A set of table definitions
+///
+/// ```toml
+/// [[tables]]
+/// name = "readings"
+/// columns = [
+///     { name = "ts", data_type = "timestamp", nullable = false },
+///     { name = "value", data_type = "double precision", nullable = true },
+/// ]
+/// indexes = [{ name = "idx_readings_ts", columns = ["ts"] }]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    /// Tables in the schema
+    #[serde(default)]
+    pub tables: Vec<TableSchema>,
+}
+
+impl DatabaseSchema {
+    /// Introspect the tables of a connection (its default schema when `None`)
+    pub async fn introspect<C: DatabaseConnector + ?Sized>(
+        conn: &C,
+        schema_name: Option<&str>,
+    ) -> Result<Self> {
+        let mut tables = Vec::new();
+        for name in conn.list_tables(schema_name).await? {
+            let qualified = match schema_name {
+                Some(s) => format!("{}.{}", s, name),
+                None => name.clone(),
+            };
+            let columns = schema::column_infos(&conn.describe_table(&qualified).await?)?;
+            let indexes = schema::index_infos(&conn.indexes(&qualified).await?)?;
+            let primary_key = schema::first_column_strings(&conn.primary_keys(&qualified).await?)?;
+            tables.push(TableSchema {
+                name,
+                columns,
+                indexes,
+                primary_key,
+            });
+        }
+        Ok(Self { tables })
+    }
+
+    /// Find a table by name, case-insensitively
+    pub fn table(&self, name: &str) -> Option<&TableSchema> {
+        self.tables
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Compare this (expected) schema against an actual one
+    pub fn diff(&self, actual: &DatabaseSchema) -> SchemaDiff {
+        let mut changes = Vec::new();
+
+        for expected in &self.tables {
+            let Some(actual_table) = actual.table(&expected.name) else {
+                changes.push(SchemaChange::MissingTable {
+                    table: expected.clone(),
+                });
+                continue;
+            };
+            diff_table(expected, actual_table, &mut changes);
+        }
+
+        for table in &actual.tables {
+            if self.table(&table.name).is_none() {
+                changes.push(SchemaChange::ExtraTable {
+                    table: table.name.clone(),
+                });
+            }
+        }
+
+        SchemaDiff { changes }
+    }
+}
+
+/// One difference between the expected and actual schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchemaChange {
+    /// Table is expected but does not exist
+    MissingTable { table: TableSchema },
+    /// Table exists but is not expected
+    ExtraTable { table: String },
+    /// Column is expected but does not exist
+    MissingColumn { table: String, column: ColumnInfo },
+    /// Column exists but is not expected
+    ExtraColumn { table: String, column: String },
+    /// Column exists with a different type
+    TypeMismatch {
+        table: String,
+        column: String,
+        expected: String,
+        actual: String,
+    },
+    /// Column exists with different nullability
+    NullabilityMismatch {
+        table: String,
+        column: String,
+        expected: bool,
+        actual: bool,
+    },
+    /// Index is expected but does not exist
+    MissingIndex { table: String, index: IndexInfo },
+    /// Index exists but is not expected
+    ExtraIndex { table: String, index: String },
+    /// Index exists with different columns or uniqueness
+    IndexMismatch {
+        table: String,
+        expected: IndexInfo,
+        actual: IndexInfo,
+    },
+}
+
+/// Result of comparing two schemas
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    /// Differences, grouped by table in expected-schema order
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Whether the schemas match
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// DDL that brings the actual schema in line with the expected one
+    ///
+    /// Extra tables, columns and indexes are left alone. Type changes are
+    /// skipped for SQLite, which cannot alter a column type in place.
+    pub fn fixup_ddl(&self, db_type: DatabaseType) -> Vec<String> {
+        let q = |name: &str| quote_identifier(name, db_type);
+        let mut ddl = Vec::new();
+
+        for change in &self.changes {
+            match change {
+                SchemaChange::MissingTable { table } => {
+                    ddl.push(create_table_sql(table, db_type));
+                    ddl.extend(
+                        table
+                            .indexes
+                            .iter()
+                            .map(|idx| create_index_sql(&table.name, idx, db_type)),
+                    );
+                }
+                SchemaChange::MissingColumn { table, column } => {
+                    let add = match db_type {
+                        DatabaseType::Mssql => "ADD",
+                        _ => "ADD COLUMN",
+                    };
+                    ddl.push(format!(
+                        "ALTER TABLE {} {} {}",
+                        q(table),
+                        add,
+                        column_definition(column, db_type)
+                    ));
+                }
+                SchemaChange::TypeMismatch {
+                    table,
+                    column,
+                    expected,
+                    ..
+                } => match db_type {
+                    DatabaseType::Postgres => ddl.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
+                        q(table),
+                        q(column),
+                        expected
+                    )),
+                    DatabaseType::Mssql => ddl.push(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} {}",
+                        q(table),
+                        q(column),
+                        expected
+                    )),
+                    DatabaseType::Sqlite => {}
+                },
+                SchemaChange::MissingIndex { table, index } => {
+                    ddl.push(create_index_sql(table, index, db_type));
+                }
+                _ => {}
+            }
+        }
+
+        ddl
+    }
+}
+
+fn diff_table(expected: &TableSchema, actual: &TableSchema, changes: &mut Vec<SchemaChange>) {
+    let table = &expected.name;
+
+    for column in &expected.columns {
+        let Some(found) = actual
+            .columns
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&column.name))
+        else {
+            changes.push(SchemaChange::MissingColumn {
+                table: table.clone(),
+                column: column.clone(),
+            });
+            continue;
+        };
+
+        if !same_type(&column.data_type, &found.data_type) {
+            changes.push(SchemaChange::TypeMismatch {
+                table: table.clone(),
+                column: column.name.clone(),
+                expected: column.data_type.clone(),
+                actual: found.data_type.clone(),
+            });
+        }
+        if column.nullable != found.nullable {
+            changes.push(SchemaChange::NullabilityMismatch {
+                table: table.clone(),
+                column: column.name.clone(),
+                expected: column.nullable,
+                actual: found.nullable,
+            });
+        }
+    }
+
+    for column in &actual.columns {
+        if !expected
+            .columns
+            .iter()
+            .any(|c| c.name.eq_ignore_ascii_case(&column.name))
+        {
+            changes.push(SchemaChange::ExtraColumn {
+                table: table.clone(),
+                column: column.name.clone(),
+            });
+        }
+    }
+
+    for index in &expected.indexes {
+        match actual
+            .indexes
+            .iter()
+            .find(|i| i.name.eq_ignore_ascii_case(&index.name))
+        {
+            None => changes.push(SchemaChange::MissingIndex {
+                table: table.clone(),
+                index: index.clone(),
+            }),
+            Some(found) if !same_index(index, found) => changes.push(SchemaChange::IndexMismatch {
+                table: table.clone(),
+                expected: index.clone(),
+                actual: found.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for index in &actual.indexes {
+        if !expected
+            .indexes
+            .iter()
+            .any(|i| i.name.eq_ignore_ascii_case(&index.name))
+        {
+            changes.push(SchemaChange::ExtraIndex {
+                table: table.clone(),
+                index: index.name.clone(),
+            });
+        }
+    }
+}
+
+/// Compare native type names ignoring case and whitespace
+fn same_type(a: &str, b: &str) -> bool {
+    let norm = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    norm(a) == norm(b)
+}
+
+fn same_index(a: &IndexInfo, b: &IndexInfo) -> bool {
+    a.unique == b.unique
+        && a.columns.len() == b.columns.len()
+        && a.columns
+            .iter()
+            .zip(&b.columns)
+            .all(|(x, y)| x.eq_ignore_ascii_case(y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default: None,
+        }
+    }
+
+    fn readings(columns: Vec<ColumnInfo>, indexes: Vec<IndexInfo>) -> DatabaseSchema {
+        DatabaseSchema {
+            tables: vec![TableSchema {
+                name: "readings".to_string(),
+                columns,
+                indexes,
+                primary_key: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let schema = readings(vec![column("ts", "INTEGER", false)], vec![]);
+        assert!(schema.diff(&schema.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_column_and_index_changes() {
+        let idx = IndexInfo {
+            name: "idx_ts".to_string(),
+            columns: vec!["ts".to_string()],
+            unique: false,
+        };
+        let expected = readings(
+            vec![
+                column("ts", "bigint", false),
+                column("value", "double precision", true),
+            ],
+            vec![idx],
+        );
+        let actual = readings(
+            vec![column("ts", "integer", false), column("note", "text", true)],
+            vec![],
+        );
+
+        let diff = expected.diff(&actual);
+        assert_eq!(diff.changes.len(), 4);
+        assert!(matches!(diff.changes[0], SchemaChange::TypeMismatch { .. }));
+        assert!(matches!(
+            diff.changes[1],
+            SchemaChange::MissingColumn { .. }
+        ));
+        assert!(matches!(diff.changes[2], SchemaChange::ExtraColumn { .. }));
+        assert!(matches!(diff.changes[3], SchemaChange::MissingIndex { .. }));
+
+        let ddl = diff.fixup_ddl(DatabaseType::Postgres);
+        assert_eq!(
+            ddl,
+            vec![
+                "ALTER TABLE \"readings\" ALTER COLUMN \"ts\" TYPE bigint",
+                "ALTER TABLE \"readings\" ADD COLUMN \"value\" double precision",
+                "CREATE INDEX \"idx_ts\" ON \"readings\" (\"ts\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_table_ddl() {
+        let expected = readings(vec![column("ts", "DATETIME2", false)], vec![]);
+        let diff = expected.diff(&DatabaseSchema::default());
+        assert_eq!(
+            diff.fixup_ddl(DatabaseType::Mssql),
+            vec!["CREATE TABLE [readings] ([ts] DATETIME2 NOT NULL)"]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Memory-saving dtype downcasting
+//!
+//! Databases report most numeric columns as 64-bit and text as plain
+//! strings, which makes wide historian reads much larger in memory than the
+//! data needs. [`downcast`] looks at the values actually read and narrows
+//! each column to the smallest type that holds them:
+//!
+//! - integers to `Int32`, `Int16` or `Int8` when their range fits
+//! - `Float64` to `Float32` when every value keeps its shortest decimal
+//!   form, as readings stored with a few decimals do
+//! - strings with few distinct values (tag names, units, states) to
+//!   `Categorical`
+//!
+//! The original type of every narrowed column is recorded so the frame can
+//! be cast back before it is written elsewhere.
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// What [`downcast`] may narrow
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DowncastOptions {
+    /// Narrow `Int64` and `Int32` columns to the smallest type holding their
+    /// range
+    pub integers: bool,
+    /// Narrow `Float64` columns to `Float32` when no value changes its
+    /// shortest decimal representation
+    pub floats: bool,
+    /// Convert string columns whose distinct values are at most this
+    /// fraction of their rows to `Categorical`; `None` keeps strings
+    pub categorical_ratio: Option<f64>,
+}
+
+impl Default for DowncastOptions {
+    fn default() -> Self {
+        Self {
+            integers: true,
+            floats: true,
+            categorical_ratio: Some(0.5),
+        }
+    }
+}
+
+/// A downcast DataFrame and the types its narrowed columns had before
+#[derive(Debug, Clone)]
+pub struct Downcast {
+    /// The narrowed frame
+    pub frame: DataFrame,
+    /// `(column, original type)` for every column that was narrowed, in
+    /// column order
+    pub original_types: Vec<(String, DataType)>,
+}
+
+impl Downcast {
+    /// Cast the narrowed columns back to their original types
+    ///
+    /// Floats come back as the `f64` nearest their `f32` value, which equals
+    /// the original only for values exact in binary such as `20.5`.
+    pub fn restore(self) -> Result<DataFrame> {
+        let mut frame = self.frame;
+        for (name, dtype) in &self.original_types {
+            let column = frame.column(name)?.cast(dtype)?;
+            frame.with_column(column)?;
+        }
+        Ok(frame)
+    }
+}
+
+/// Narrow the columns of `df` as far as their values allow
+pub fn downcast(df: DataFrame, options: &DowncastOptions) -> Result<Downcast> {
+    let mut original_types = Vec::new();
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|column| {
+            let target = narrower_type(column, options)?;
+            Ok(match target {
+                Some(dtype) => {
+                    original_types.push((column.name().to_string(), column.dtype().clone()));
+                    column.cast(&dtype)?
+                }
+                None => column.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Downcast {
+        frame: DataFrame::new(columns)?,
+        original_types,
+    })
+}
+
+/// The narrowest type holding every value of `column`, if narrower than its
+/// own
+fn narrower_type(column: &Column, options: &DowncastOptions) -> Result<Option<DataType>> {
+    if column.len() == column.null_count() {
+        return Ok(None);
+    }
+
+    Ok(match column.dtype() {
+        DataType::Int64 | DataType::Int32 if options.integers => {
+            let values = column.cast(&DataType::Int64)?;
+            let values = values.i64()?;
+            let (min, max) = match (values.min(), values.max()) {
+                (Some(min), Some(max)) => (min, max),
+                _ => return Ok(None),
+            };
+            let fits = |lo: i64, hi: i64| min >= lo && max <= hi;
+            let target = if fits(i8::MIN as i64, i8::MAX as i64) {
+                DataType::Int8
+            } else if fits(i16::MIN as i64, i16::MAX as i64) {
+                DataType::Int16
+            } else if fits(i32::MIN as i64, i32::MAX as i64) {
+                DataType::Int32
+            } else {
+                return Ok(None);
+            };
+            (&target != column.dtype()).then_some(target)
+        }
+        DataType::Float64 if options.floats => {
+            let survives = column
+                .f64()?
+                .into_iter()
+                .flatten()
+                .all(|v| v.is_nan() || (v as f32).to_string().parse::<f64>() == Ok(v));
+            survives.then_some(DataType::Float32)
+        }
+        DataType::String => {
+            let Some(ratio) = options.categorical_ratio else {
+                return Ok(None);
+            };
+            let distinct = column.as_materialized_series().n_unique()?;
+            let rows = column.len() - column.null_count();
+            (distinct as f64 <= ratio * rows as f64)
+                .then_some(DataType::Categorical(None, CategoricalOrdering::Physical))
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readings() -> DataFrame {
+        df!(
+            "id" => [1i64, 2, 3, 40_000],
+            "quality" => [0i64, 192, 0, 192],
+            "value" => [20.5f64, 21.25, 19.75, 20.0],
+            "precise" => [0.1f64, 0.2, 0.3, 0.123_456_789_012],
+            "tag" => ["TI-101", "TI-101", "TI-102", "TI-101"],
+            "note" => ["a", "b", "c", "d"]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_downcast_by_observed_values() {
+        let result = downcast(readings(), &DowncastOptions::default()).unwrap();
+        let dtypes: Vec<DataType> = result.frame.dtypes();
+        assert_eq!(dtypes[0], DataType::Int32);
+        assert_eq!(dtypes[1], DataType::Int16);
+        assert_eq!(dtypes[2], DataType::Float32);
+        assert_eq!(dtypes[3], DataType::Float64);
+        assert!(matches!(dtypes[4], DataType::Categorical(_, _)));
+        assert_eq!(dtypes[5], DataType::String);
+        assert_eq!(
+            result.original_types,
+            vec![
+                ("id".to_string(), DataType::Int64),
+                ("quality".to_string(), DataType::Int64),
+                ("value".to_string(), DataType::Float64),
+                ("tag".to_string(), DataType::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_and_disabled_options() {
+        let restored = downcast(readings(), &DowncastOptions::default())
+            .unwrap()
+            .restore()
+            .unwrap();
+        assert!(restored.equals(&readings()));
+
+        let options = DowncastOptions {
+            integers: false,
+            floats: false,
+            categorical_ratio: None,
+        };
+        let result = downcast(readings(), &options).unwrap();
+        assert!(result.original_types.is_empty());
+        assert!(result.frame.equals(&readings()));
+    }
+}
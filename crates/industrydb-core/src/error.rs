@@ -63,6 +63,18 @@ pub enum IndustryDbError {
     /// Not implemented error
     #[error("Not implemented: {0}")]
     NotImplemented(String),
+
+    /// SQL parse error
+    #[error("SQL parse error: {0}")]
+    SqlParseError(String),
+
+    /// Access denied by the connection policy
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+
+    /// Object storage error
+    #[error("Storage error: {0}")]
+    StorageError(String),
 }
 
 // Convert from polars errors
@@ -72,9 +84,8 @@ impl From<polars::prelude::PolarsError> for IndustryDbError {
     }
 }
 
-// Convert from connectorx errors
-// Conversion from ConnectorXError is implemented in database-specific crates to avoid
-// coupling core to optional dependencies.
+// ConnectorX errors are converted in the PostgreSQL and MSSQL crates, which
+// depend on it behind their `connectorx` feature, to keep it out of core.
 
 // Implement custom Display if needed for special formatting
 impl IndustryDbError {
@@ -102,6 +113,21 @@ impl IndustryDbError {
     pub fn invalid_parameter<S: Into<String>>(msg: S) -> Self {
         IndustryDbError::InvalidParameter(msg.into())
     }
+
+    /// Create a SQL parse error
+    pub fn sql_parse_error<S: Into<String>>(msg: S) -> Self {
+        IndustryDbError::SqlParseError(msg.into())
+    }
+
+    /// Create an access denied error
+    pub fn access_denied<S: Into<String>>(msg: S) -> Self {
+        IndustryDbError::AccessDenied(msg.into())
+    }
+
+    /// Create a storage error
+    pub fn storage_error<S: Into<String>>(msg: S) -> Self {
+        IndustryDbError::StorageError(msg.into())
+    }
 }
 
 #[cfg(test)]
@@ -1,67 +1,134 @@
 //! Connection factory for creating database connectors
 
-use crate::config::{ConnectionConfig, DatabaseType};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::config::ConnectionConfig;
 use crate::error::{IndustryDbError, Result};
-use crate::traits::DatabaseConnector;
+use crate::traits::CrudOperations;
 
-/// Factory for creating database connections
+/// Factory for connectors registered at runtime
+///
+/// The built-in connectors live in separate crates (`industrydb-postgres`,
+/// `industrydb-sqlite`, `industrydb-mssql`) and are created directly by the
+/// bindings. Any other connector, such as an in-house historian driver, is
+/// registered here under a name, either from Rust code linked into the
+/// application or from a shared library loaded with
+/// [`load_plugin`](crate::plugin::load_plugin). A [`ConnectionConfig`] whose
+/// `connector` field names it then connects through that builder.
 pub struct ConnectionFactory;
 
 impl ConnectionFactory {
-    /// Create a new database connector based on configuration
-    ///
-    /// This is a factory method that will instantiate the appropriate
-    /// connector implementation based on the database type.
+    /// Create a connector through the builder registered for `config`
     ///
-    /// Note: The actual implementations are in separate crates:
-    /// - `industrydb-postgres` for PostgreSQL
-    /// - `industrydb-sqlite` for SQLite
-    /// - `industrydb-mssql` for MSSQL
-    ///
-    /// This method returns a trait object, allowing for dynamic dispatch.
-    pub fn create(_config: &ConnectionConfig) -> Result<Box<dyn DatabaseConnector>> {
-        // This will be implemented in the integration layer
-        // For now, return an error indicating the connector must be registered
-        Err(IndustryDbError::NotImplemented(
-            "ConnectionFactory::create must be called with registered connectors".to_string(),
-        ))
+    /// The builder is looked up by `config.connector`, falling back to the
+    /// name of `config.db_type` so that a registered builder can also stand
+    /// in for a built-in database type.
+    pub async fn create(config: &ConnectionConfig) -> Result<Box<dyn CrudOperations>> {
+        let name = config
+            .connector
+            .clone()
+            .unwrap_or_else(|| config.db_type.to_string());
+        let builder = Self::builder(&name).ok_or_else(|| {
+            IndustryDbError::config_error(format!(
+                "No connector named '{}' is registered; load its plugin first",
+                name
+            ))
+        })?;
+        builder.build(config).await
     }
 
-    /// Register a connector builder for a specific database type
+    /// Register a connector builder under a name
     ///
-    /// This allows the factory to create connectors without having
-    /// a direct dependency on the implementation crates.
+    /// Fails if the name is already taken, so two plugins cannot silently
+    /// replace each other's connector.
     ///
-    /// Example usage in the integration layer:
     /// ```ignore
-    /// ConnectionFactory::register(
-    ///     DatabaseType::Postgres,
-    ///     Box::new(|config| Box::new(PostgresConnector::new(config)?))
-    /// );
+    /// ConnectionFactory::register("historian", Arc::new(HistorianBuilder))?;
     /// ```
-    pub fn register(_db_type: DatabaseType, _builder: Box<dyn ConnectorBuilder>) -> Result<()> {
-        // Implementation will use a static registry (e.g., once_cell)
-        // For MVP, we'll handle this in the Python bindings layer
+    pub fn register(name: impl Into<String>, builder: Arc<dyn ConnectorBuilder>) -> Result<()> {
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Connector name must not be empty",
+            ));
+        }
+        let mut builders = registry().write().unwrap_or_else(|e| e.into_inner());
+        if builders.contains_key(&name) {
+            return Err(IndustryDbError::config_error(format!(
+                "A connector named '{}' is already registered",
+                name
+            )));
+        }
+        builders.insert(name, builder);
         Ok(())
     }
+
+    /// Whether a builder is registered under `name`
+    pub fn is_registered(name: &str) -> bool {
+        Self::builder(name).is_some()
+    }
+
+    /// Names of the registered connectors, sorted
+    pub fn registered() -> Vec<String> {
+        let builders = registry().read().unwrap_or_else(|e| e.into_inner());
+        builders.keys().cloned().collect()
+    }
+
+    fn builder(name: &str) -> Option<Arc<dyn ConnectorBuilder>> {
+        let builders = registry().read().unwrap_or_else(|e| e.into_inner());
+        builders.get(name).cloned()
+    }
+}
+
+fn registry() -> &'static RwLock<BTreeMap<String, Arc<dyn ConnectorBuilder>>> {
+    static REGISTRY: OnceLock<RwLock<BTreeMap<String, Arc<dyn ConnectorBuilder>>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
 }
 
 /// Trait for connector builders
 ///
-/// Each database implementation crate should provide a builder
-/// that implements this trait.
+/// Each connector registered with [`ConnectionFactory`] provides a builder
+/// that opens a connection from configuration.
+#[async_trait]
 pub trait ConnectorBuilder: Send + Sync {
     /// Build a connector from configuration
-    fn build(&self, config: &ConnectionConfig) -> Result<Box<dyn DatabaseConnector>>;
+    async fn build(&self, config: &ConnectionConfig) -> Result<Box<dyn CrudOperations>>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_factory_exists() {
-        // Just verify the factory type exists
-        let _factory = ConnectionFactory;
+    struct FailingBuilder;
+
+    #[async_trait]
+    impl ConnectorBuilder for FailingBuilder {
+        async fn build(&self, config: &ConnectionConfig) -> Result<Box<dyn CrudOperations>> {
+            Err(IndustryDbError::connection_error(format!(
+                "cannot reach {}",
+                config.host.as_deref().unwrap_or("?")
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_builder_is_used() {
+        ConnectionFactory::register("factory-test", Arc::new(FailingBuilder)).unwrap();
+        assert!(ConnectionFactory::is_registered("factory-test"));
+        assert!(ConnectionFactory::registered().contains(&"factory-test".to_string()));
+        assert!(ConnectionFactory::register("factory-test", Arc::new(FailingBuilder)).is_err());
+
+        let mut config = ConnectionConfig::sqlite("unused.db");
+        config.host = Some("historian01".to_string());
+        config.connector = Some("factory-test".to_string());
+        let err = ConnectionFactory::create(&config).await.err().unwrap();
+        assert!(err.to_string().contains("cannot reach historian01"));
+
+        config.connector = Some("missing".to_string());
+        let err = ConnectionFactory::create(&config).await.err().unwrap();
+        assert!(err.to_string().contains("No connector named 'missing'"));
     }
 }
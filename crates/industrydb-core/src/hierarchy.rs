@@ -0,0 +1,247 @@
+//! Hierarchy queries
+//!
+//! Flattens parent/child tables (equipment hierarchies, bills of materials)
+//! with a recursive CTE rendered for each dialect. Every row of the result
+//! carries its `depth` below the root and its `path` from the root, e.g.
+//! `Plant/Line 1/Pump 3`.
+//!
+//! ```ignore
+//! let tree = Hierarchy::new("equipment", "id", "parent_id")
+//!     .label("name")
+//!     .columns(["kind"])
+//!     .fetch(&conn)
+//!     .await?;
+//! ```
+
+use polars::prelude::{DataFrame, DataType};
+
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::schema::quote_literal;
+use crate::traits::DatabaseConnector;
+
+/// Name of the CTE, and of the depth and path columns it adds
+const CTE: &str = "hierarchy";
+const DEPTH: &str = "depth";
+const PATH: &str = "path";
+
+/// A parent/child table to flatten
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hierarchy {
+    table: String,
+    id_column: String,
+    parent_column: String,
+    label_column: Option<String>,
+    columns: Vec<String>,
+    roots: Option<Vec<Value>>,
+    max_depth: usize,
+    separator: String,
+}
+
+impl Hierarchy {
+    /// Rows of `table` linked by `parent_column` referencing `id_column`
+    ///
+    /// Roots are the rows whose parent is NULL; the depth is limited to 100
+    /// levels so cycles cannot recurse forever.
+    pub fn new(table: &str, id_column: &str, parent_column: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            id_column: id_column.to_string(),
+            parent_column: parent_column.to_string(),
+            label_column: None,
+            columns: Vec::new(),
+            roots: None,
+            max_depth: 100,
+            separator: "/".to_string(),
+        }
+    }
+
+    /// Build paths from this column instead of the id
+    pub fn label(mut self, column: &str) -> Self {
+        self.label_column = Some(column.to_string());
+        self
+    }
+
+    /// Also return these columns
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.columns = columns
+            .into_iter()
+            .map(|c| c.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Start from the rows with these ids instead of the parentless ones
+    pub fn roots<I, V>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        self.roots = Some(ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Stop `n` levels below the roots
+    pub fn max_depth(mut self, n: usize) -> Self {
+        self.max_depth = n;
+        self
+    }
+
+    /// Separator between path segments
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Render the query and its parameters in `dialect`
+    ///
+    /// Returns the id, parent, label and extra columns followed by `depth`
+    /// and `path`, ordered by path.
+    pub fn render(&self, dialect: &dyn Dialect) -> Result<(String, Vec<Value>)> {
+        let text = dialect.column_type(&DataType::String)?;
+        let id = dialect.identifier(&self.id_column)?;
+        let parent = dialect.identifier(&self.parent_column)?;
+        let label = dialect.identifier(self.label_column.as_ref().unwrap_or(&self.id_column))?;
+
+        let mut columns = vec![id.clone(), parent.clone()];
+        for column in self.label_column.iter().chain(&self.columns) {
+            let column = dialect.identifier(column)?;
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+
+        let cte = dialect.identifier(CTE)?;
+        let depth = dialect.identifier(DEPTH)?;
+        let path = dialect.identifier(PATH)?;
+        let child = |column: &str| format!("c.{}", column);
+
+        let mut params = Vec::new();
+        let anchor_filter = match &self.roots {
+            None => format!("{} IS NULL", parent),
+            Some(ids) if ids.is_empty() => {
+                return Err(IndustryDbError::invalid_parameter(
+                    "Hierarchy roots must not be empty",
+                ))
+            }
+            Some(ids) => {
+                let placeholders: Vec<String> = ids
+                    .iter()
+                    .map(|v| {
+                        params.push(v.clone());
+                        dialect.placeholder(params.len())
+                    })
+                    .collect();
+                format!("{} IN ({})", id, placeholders.join(", "))
+            }
+        };
+
+        let anchor = format!(
+            "SELECT {}, 0 AS {}, CAST({} AS {}) AS {} FROM {} WHERE {}",
+            columns.join(", "),
+            depth,
+            label,
+            text,
+            path,
+            dialect.identifier(&self.table)?,
+            anchor_filter
+        );
+        let child_path = dialect.concat(&[
+            format!("{}.{}", cte, path),
+            quote_literal(&self.separator),
+            format!("CAST({} AS {})", child(&label), text),
+        ]);
+        let recursive = format!(
+            "SELECT {}, {}.{} + 1, CAST({} AS {}) \
+             FROM {} c JOIN {} ON {} = {}.{} WHERE {}.{} < {}",
+            columns
+                .iter()
+                .map(|c| child(c))
+                .collect::<Vec<_>>()
+                .join(", "),
+            cte,
+            depth,
+            child_path,
+            text,
+            dialect.identifier(&self.table)?,
+            cte,
+            child(&parent),
+            cte,
+            id,
+            cte,
+            depth,
+            self.max_depth
+        );
+
+        let sql = format!(
+            "{} {} AS ({} UNION ALL {}) SELECT * FROM {} ORDER BY {}{}",
+            dialect.with_recursive(),
+            cte,
+            anchor,
+            recursive,
+            cte,
+            path,
+            dialect.unbounded_recursion()
+        );
+        Ok((sql, params))
+    }
+
+    /// Run the query on a connection
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<DataFrame> {
+        let (sql, params) = self.render(conn.dialect())?;
+        conn.execute_with_params(&sql, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect};
+
+    #[test]
+    fn test_render_postgres() {
+        let (sql, params) = Hierarchy::new("equipment", "id", "parent_id")
+            .label("name")
+            .render(&PostgresDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "WITH RECURSIVE \"hierarchy\" AS (\
+             SELECT \"id\", \"parent_id\", \"name\", 0 AS \"depth\", \
+             CAST(\"name\" AS TEXT) AS \"path\" FROM \"equipment\" WHERE \"parent_id\" IS NULL \
+             UNION ALL \
+             SELECT c.\"id\", c.\"parent_id\", c.\"name\", \"hierarchy\".\"depth\" + 1, \
+             CAST(\"hierarchy\".\"path\" || '/' || CAST(c.\"name\" AS TEXT) AS TEXT) \
+             FROM \"equipment\" c JOIN \"hierarchy\" ON c.\"parent_id\" = \"hierarchy\".\"id\" \
+             WHERE \"hierarchy\".\"depth\" < 100) \
+             SELECT * FROM \"hierarchy\" ORDER BY \"path\""
+        );
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_render_mssql_with_roots() {
+        let (sql, params) = Hierarchy::new("dbo.bom", "part", "assembly")
+            .roots([10, 20])
+            .max_depth(5)
+            .separator(" > ")
+            .render(&MssqlDialect)
+            .unwrap();
+        assert!(sql.starts_with("WITH [hierarchy] AS (SELECT [part], [assembly], 0 AS [depth]"));
+        assert!(sql.contains("FROM [dbo].[bom] WHERE [part] IN (@P1, @P2)"));
+        assert!(sql.contains("[hierarchy].[path] + ' > ' + CAST(c.[part] AS NVARCHAR(MAX))"));
+        assert!(sql.contains("[hierarchy].[depth] < 5"));
+        assert!(sql.ends_with("ORDER BY [path] OPTION (MAXRECURSION 0)"));
+        assert_eq!(params, vec![Value::Int(10), Value::Int(20)]);
+
+        assert!(Hierarchy::new("t", "id", "parent")
+            .roots(Vec::<i64>::new())
+            .render(&MssqlDialect)
+            .is_err());
+    }
+}
@@ -3,15 +3,103 @@
 //! Core abstractions and traits for database connectivity.
 //! This crate defines the interface that all database connectors must implement.
 
+pub mod audit;
+pub mod backfill;
+pub mod batching;
+pub mod changes;
+pub mod codec;
 pub mod config;
+pub mod conform;
+pub mod copy;
+pub mod ddl;
+pub mod decimal;
+pub mod dialect;
+pub mod diff;
+pub mod downcast;
 pub mod error;
 pub mod factory;
+pub mod hierarchy;
+pub mod locks;
+pub mod metrics;
+pub mod params;
+pub mod partition;
+pub mod pivot;
+pub mod plugin;
+pub mod policy;
+pub mod procedure;
+pub mod quality;
+pub mod query;
+pub mod replay;
+pub mod retention;
+pub mod schema;
+pub mod sink;
+pub mod spill;
+pub mod sql;
+pub mod sync;
+pub mod synth;
+pub mod tags;
+pub mod time;
+pub mod timescale;
+pub mod timeseries;
 pub mod traits;
+pub mod transfer;
+pub mod value_at;
+pub mod writer;
 
-pub use config::{ConnectionConfig, DatabaseConfig, DatabaseType};
+pub use audit::AuditColumns;
+pub use backfill::{backfill, BackfillConfig, BackfillControl, BackfillProgress};
+pub use batching::{AdaptiveBatchConfig, AdaptiveBatcher, BatchStats};
+pub use changes::{ChangePoller, ChangedRows, PolledTable};
+pub use codec::{BatchCodec, Codec, CodecConfig};
+pub use config::{
+    ConnectionConfig, DatabaseConfig, DatabaseType, DecimalMode, MssqlOptions, OverflowMode,
+    SqliteOptions, TimestampMode,
+};
+pub use conform::{
+    coerce_frame, schema_mismatches, validate_insert, Coercion, CoercionPolicy, InsertOptions,
+    SchemaMismatch,
+};
+pub use copy::{CopyFormat, CsvCopyOptions};
+pub use dialect::{
+    dialect_for, Dialect, MssqlDialect, NullsOrder, PostgresDialect, SelectOptions, SqliteDialect,
+};
+pub use diff::{DatabaseSchema, SchemaChange, SchemaDiff, TableSchema};
+pub use downcast::{downcast, Downcast, DowncastOptions};
 pub use error::{IndustryDbError, Result};
-pub use factory::ConnectionFactory;
-pub use traits::{CrudOperations, DatabaseConnector};
+pub use factory::{ConnectionFactory, ConnectorBuilder};
+pub use hierarchy::Hierarchy;
+pub use locks::{TableLocks, TableWriteGuard, WriteLockStats};
+pub use metrics::{MetricsSnapshot, QueryMetrics, QueryStats};
+pub use params::{bind_named, Value};
+pub use partition::read_partitioned;
+pub use pivot::{pivot, unpivot, Pivoted};
+#[cfg(feature = "plugins")]
+pub use plugin::load_plugin;
+pub use plugin::{register_plugin, PluginDeclaration, PluginRegistrar};
+pub use policy::AccessPolicy;
+pub use procedure::{ParamMode, ProcedureArg, ProcedureResult};
+pub use quality::{check_source, run_checks, Check, CheckResult, QualityReport};
+pub use query::{
+    col, order_by_sql, param, select_list_sql, Aggregate, Expr, JoinKind, OrderBy, Query,
+};
+pub use replay::{replay, ReplayConfig, ReplayControl, ReplayProgress};
+pub use retention::{RetentionPolicy, RetentionSummary, DEFAULT_RETENTION_BATCH};
+pub use schema::{ColumnInfo, IndexInfo};
+pub use sink::FrameSink;
+pub use spill::{SpillStore, DEFAULT_SPILL_TABLE};
+pub use sql::{
+    ensure_returns_rows, normalize_sql, parse_sql, read_script, split_batches,
+    split_statement_batches, split_statements, ParsedStatement, ScriptBatch, StatementKind,
+};
+pub use sync::{sync_table, OnConflict, SyncConfig, SyncControl, SyncProgress};
+pub use synth::{ColumnGenerator, SyntheticColumn, SyntheticTable};
+pub use tags::{join_metadata, TagInfo, TagRegistry, DEFAULT_TAG_TABLE};
+pub use timescale::{timescale_version, ContinuousAggregate};
+pub use timeseries::{FillMethod, TimeBuckets, TimeRange, BUCKET_COLUMN};
+pub use traits::{CrudOperations, DatabaseConnector, QueryResult};
+pub use transfer::{copy_table, CopyMode, TableCopyConfig, TableCopyProgress};
+pub use value_at::{AtMode, ValueAt};
+pub use writer::{BufferedWriter, BufferedWriterConfig, QueueOverflow, WriterStats, WriterTask};
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,196 @@
+//! In-process write locks per table
+//!
+//! Concurrent writers to the same table (threads sharing a process, a
+//! pipeline next to ad-hoc loads) can deadlock on the database's own locks
+//! or interleave partial batches. [`TableLocks`] serializes them inside the
+//! process: writers of one table in the same scope wait for each other,
+//! writers of different tables do not. Scopes are process-wide, so every
+//! connection to the same database shares its locks.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::OwnedMutexGuard;
+
+use crate::config::ConnectionConfig;
+
+/// Wait statistics of one table's write lock
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteLockStats {
+    /// Times the lock was taken
+    pub acquisitions: u64,
+    /// Times a writer had to wait for another
+    pub contended: u64,
+    /// Total time spent waiting, in microseconds
+    pub total_wait_us: u64,
+    /// Longest single wait, in microseconds
+    pub max_wait_us: u64,
+}
+
+impl WriteLockStats {
+    fn record(&mut self, waited: Duration, contended: bool) {
+        let waited = waited.as_micros() as u64;
+        self.acquisitions += 1;
+        if contended {
+            self.contended += 1;
+        }
+        self.total_wait_us += waited;
+        self.max_wait_us = self.max_wait_us.max(waited);
+    }
+}
+
+#[derive(Default)]
+struct TableLock {
+    lock: Arc<tokio::sync::Mutex<()>>,
+    stats: Mutex<WriteLockStats>,
+}
+
+type Registry = Mutex<HashMap<(String, String), Arc<TableLock>>>;
+
+fn registry() -> MutexGuard<'static, HashMap<(String, String), Arc<TableLock>>> {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Write locks of the tables in one scope
+///
+/// Table names are compared case-insensitively, erring on the side of
+/// serializing writers.
+#[derive(Debug, Clone)]
+pub struct TableLocks {
+    scope: String,
+}
+
+impl TableLocks {
+    /// Locks of a named scope
+    pub fn new(scope: impl Into<String>) -> Self {
+        Self {
+            scope: scope.into(),
+        }
+    }
+
+    /// Locks shared by every connection to the database of `config`
+    ///
+    /// The scope is the database type, server, port and database name or
+    /// file; credentials are not part of it.
+    pub fn for_database(config: &ConnectionConfig) -> Self {
+        let server = config
+            .server
+            .as_deref()
+            .or(config.host.as_deref())
+            .unwrap_or_default();
+        let port = config.port.map(|p| p.to_string()).unwrap_or_default();
+        let database = config
+            .database
+            .as_deref()
+            .or(config.path.as_deref())
+            .unwrap_or_default();
+        Self::new(format!(
+            "{}://{}:{}/{}",
+            config.db_type, server, port, database
+        ))
+    }
+
+    /// Scope the locks belong to
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    /// Wait for the write lock of `table`
+    ///
+    /// The lock is held until the returned guard is dropped.
+    pub async fn lock(&self, table: &str) -> TableWriteGuard {
+        let entry = registry()
+            .entry((self.scope.clone(), table.trim().to_lowercase()))
+            .or_default()
+            .clone();
+
+        let started = Instant::now();
+        let (guard, contended) = match entry.lock.clone().try_lock_owned() {
+            Ok(guard) => (guard, false),
+            Err(_) => (entry.lock.clone().lock_owned().await, true),
+        };
+        let waited = started.elapsed();
+        entry
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(waited, contended);
+
+        TableWriteGuard {
+            _guard: guard,
+            waited,
+        }
+    }
+
+    /// Wait statistics of the tables locked in this scope, by table
+    pub fn stats(&self) -> BTreeMap<String, WriteLockStats> {
+        registry()
+            .iter()
+            .filter(|((scope, _), _)| *scope == self.scope)
+            .map(|((_, table), entry)| {
+                let stats = entry.stats.lock().unwrap_or_else(|e| e.into_inner());
+                (table.clone(), stats.clone())
+            })
+            .collect()
+    }
+}
+
+/// Held write lock of a table, released on drop
+pub struct TableWriteGuard {
+    _guard: OwnedMutexGuard<()>,
+    waited: Duration,
+}
+
+impl TableWriteGuard {
+    /// Time spent waiting for the lock
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writers_of_a_table_are_serialized() {
+        let locks = TableLocks::new("locks-test");
+        let guard = locks.lock("Readings").await;
+
+        let other = locks.clone();
+        let waiter = tokio::spawn(async move {
+            let guard = other.lock("readings").await;
+            guard.waited()
+        });
+        // Another table is not blocked
+        drop(locks.lock("alarms").await);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(guard);
+        assert!(waiter.await.unwrap() >= Duration::from_millis(10));
+
+        let stats = locks.stats();
+        assert_eq!(stats["readings"].acquisitions, 2);
+        assert_eq!(stats["readings"].contended, 1);
+        assert_eq!(stats["alarms"].contended, 0);
+        assert!(TableLocks::new("other-scope").stats().is_empty());
+    }
+
+    #[test]
+    fn test_scope_ignores_credentials() {
+        let config = ConnectionConfig::postgres(
+            "db01".to_string(),
+            5432,
+            "plant".to_string(),
+            "user".to_string(),
+            "secret".to_string(),
+        );
+        let scope = TableLocks::for_database(&config);
+        assert_eq!(scope.scope(), "postgres://db01:5432/plant");
+    }
+}
@@ -0,0 +1,522 @@
+//! Per-query resource accounting
+//!
+//! Connectors time each query's fetch and measure the CPU time and peak
+//! memory of decoding the rows into a DataFrame, so load on a shared
+//! gateway can be attributed to the jobs causing it.
+//!
+//! Peak memory is measured by [`TrackingAllocator`], which counts
+//! allocations per thread. It only takes effect in binaries that install it
+//! as the global allocator, as the `track-memory` feature does; otherwise
+//! the size of the decoded DataFrame is reported instead.
+
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use polars::prelude::{DataFrame, DataType, TimeUnit};
+
+use crate::error::{IndustryDbError, Result};
+use crate::sink::FrameSink;
+
+/// Longest SQL prefix kept in [`QueryStats`]
+const SQL_PREFIX_LEN: usize = 200;
+
+/// Number of recent queries kept by [`QueryMetrics`]
+const RECENT_QUERIES: usize = 100;
+
+/// Rows a connector buffers from the driver before decoding them, so a
+/// result never sits in memory as both rows and columns
+pub const DECODE_CHUNK_ROWS: usize = 8192;
+
+thread_local! {
+    static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+    static PEAK_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// Global allocator that tracks live and peak bytes per thread
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: industrydb_core::metrics::TrackingAllocator =
+///     industrydb_core::metrics::TrackingAllocator;
+/// ```
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn track(delta: isize) {
+        if !TRACKING.load(Ordering::Relaxed) {
+            TRACKING.store(true, Ordering::Relaxed);
+        }
+        // try_with: allocations can happen while thread-locals are torn down
+        let _ = LIVE_BYTES.try_with(|live| {
+            let now = live.get() + delta;
+            live.set(now);
+            let _ = PEAK_BYTES.try_with(|peak| {
+                if now > peak.get() {
+                    peak.set(now);
+                }
+            });
+        });
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::track(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            Self::track(new_size as isize - layout.size() as isize);
+        }
+        new
+    }
+}
+
+/// Installed by the `track-memory` feature
+#[cfg(feature = "track-memory")]
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+/// CPU time and peak memory of a section of code on the current thread
+///
+/// Sections must not nest: starting one resets the thread's peak.
+struct Section {
+    cpu: cpu_time::ThreadTime,
+    wall: Instant,
+    base_bytes: isize,
+}
+
+impl Section {
+    fn start() -> Self {
+        let base_bytes = LIVE_BYTES.with(|live| live.get());
+        PEAK_BYTES.with(|peak| peak.set(base_bytes));
+        Self {
+            cpu: cpu_time::ThreadTime::now(),
+            wall: Instant::now(),
+            base_bytes,
+        }
+    }
+
+    /// CPU time, wall time and peak bytes above the starting point
+    fn finish(self) -> (Duration, Duration, Option<usize>) {
+        let peak = TRACKING
+            .load(Ordering::Relaxed)
+            .then(|| PEAK_BYTES.with(|peak| (peak.get() - self.base_bytes).max(0) as usize));
+        (self.cpu.elapsed(), self.wall.elapsed(), peak)
+    }
+}
+
+/// Resource usage of one query
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryStats {
+    /// Leading part of the SQL text
+    pub sql: String,
+    /// Rows returned
+    pub rows: usize,
+    /// Columns returned
+    pub columns: usize,
+    /// Wall time until all rows were fetched, in microseconds
+    pub fetch_us: u64,
+    /// Wall time decoding rows into a DataFrame, in microseconds
+    pub decode_us: u64,
+    /// CPU time decoding rows into a DataFrame, in microseconds
+    pub decode_cpu_us: u64,
+    /// Peak bytes allocated while decoding (the DataFrame size when untracked)
+    pub peak_memory_bytes: usize,
+    /// Estimated size of the resulting DataFrame
+    pub output_bytes: usize,
+    /// Whether `peak_memory_bytes` was measured by the tracking allocator
+    pub memory_tracked: bool,
+}
+
+/// Totals across all recorded queries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Queries recorded
+    pub queries: u64,
+    /// Rows returned
+    pub rows: u64,
+    /// Total fetch wall time, in microseconds
+    pub fetch_us: u64,
+    /// Total decode wall time, in microseconds
+    pub decode_us: u64,
+    /// Total decode CPU time, in microseconds
+    pub decode_cpu_us: u64,
+    /// Largest per-query peak memory
+    pub max_peak_memory_bytes: usize,
+    /// Most recent queries, oldest first
+    pub recent: Vec<QueryStats>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    totals: MetricsSnapshot,
+    recent: VecDeque<QueryStats>,
+}
+
+/// Query resource accounting shared by clones of a connector
+#[derive(Debug, Clone, Default)]
+pub struct QueryMetrics {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl QueryMetrics {
+    /// Create empty metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MetricsState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Decode fetched rows under measurement and record the query
+    ///
+    /// `fetch` is the time taken to fetch the rows; `decode` converts them.
+    pub fn record_decode<F>(&self, sql: &str, fetch: Duration, decode: F) -> Result<DataFrame>
+    where
+        F: FnOnce() -> Result<DataFrame>,
+    {
+        let section = Section::start();
+        let df = decode()?;
+        let (cpu, wall, peak) = section.finish();
+
+        let output_bytes = df.estimated_size();
+        self.record(QueryStats {
+            sql: sql.chars().take(SQL_PREFIX_LEN).collect(),
+            rows: df.height(),
+            columns: df.width(),
+            fetch_us: fetch.as_micros() as u64,
+            decode_us: wall.as_micros() as u64,
+            decode_cpu_us: cpu.as_micros() as u64,
+            peak_memory_bytes: peak.unwrap_or(output_bytes),
+            output_bytes,
+            memory_tracked: peak.is_some(),
+        });
+        Ok(df)
+    }
+
+    /// Start decoding a query's rows chunk by chunk as they are fetched
+    pub fn chunked<'a>(&'a self, sql: &'a str) -> ChunkedDecode<'a> {
+        ChunkedDecode {
+            metrics: self,
+            sql,
+            started: Instant::now(),
+            frame: None,
+            sink: None,
+            written: (0, 0),
+            decode: Duration::ZERO,
+            cpu: Duration::ZERO,
+            peak: None,
+        }
+    }
+
+    /// Record the stats of one query
+    pub fn record(&self, stats: QueryStats) {
+        let mut state = self.state();
+        let totals = &mut state.totals;
+        totals.queries += 1;
+        totals.rows += stats.rows as u64;
+        totals.fetch_us += stats.fetch_us;
+        totals.decode_us += stats.decode_us;
+        totals.decode_cpu_us += stats.decode_cpu_us;
+        totals.max_peak_memory_bytes = totals.max_peak_memory_bytes.max(stats.peak_memory_bytes);
+
+        if state.recent.len() == RECENT_QUERIES {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(stats);
+    }
+
+    /// Stats of the most recent query
+    pub fn last(&self) -> Option<QueryStats> {
+        self.state().recent.back().cloned()
+    }
+
+    /// Totals plus the most recent queries
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let state = self.state();
+        MetricsSnapshot {
+            recent: state.recent.iter().cloned().collect(),
+            ..state.totals.clone()
+        }
+    }
+
+    /// Clear all recorded stats
+    pub fn reset(&self) {
+        *self.state() = MetricsState::default();
+    }
+}
+
+/// A query result built from chunks of rows decoded as they arrive
+///
+/// Each chunk is decoded under measurement and appended to the result, so
+/// the driver's rows are released a chunk at a time. Columns whose decoded
+/// dtype differs between chunks (a SQLite column holding whole numbers,
+/// then fractions) are widened to a common dtype. With
+/// [`into_sink`](Self::into_sink) the chunks are written to a sink instead
+/// of kept. The query is recorded by [`finish`](Self::finish), with the time
+/// not spent decoding as its fetch time.
+pub struct ChunkedDecode<'a> {
+    metrics: &'a QueryMetrics,
+    sql: &'a str,
+    started: Instant,
+    frame: Option<DataFrame>,
+    sink: Option<&'a mut dyn FrameSink>,
+    /// Rows and columns handed to the sink
+    written: (usize, usize),
+    decode: Duration,
+    cpu: Duration,
+    peak: Option<usize>,
+}
+
+impl<'a> ChunkedDecode<'a> {
+    /// Hand each decoded chunk to `sink` instead of keeping it, so
+    /// [`finish`](Self::finish) returns an empty DataFrame
+    pub fn into_sink(mut self, sink: &'a mut dyn FrameSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Decode one chunk of rows and append it to the result, or write it
+    /// to the sink
+    pub fn decode<F>(&mut self, decode: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<DataFrame>,
+    {
+        let section = Section::start();
+        let mut chunk = decode()?;
+        let kept = match self.sink.as_mut() {
+            Some(sink) => {
+                self.written.0 += chunk.height();
+                self.written.1 = self.written.1.max(chunk.width());
+                sink.write(&mut chunk)?;
+                0
+            }
+            None => {
+                let frame = match self.frame.take() {
+                    Some(frame) => append_chunk(frame, chunk)?,
+                    None => chunk,
+                };
+                let size = frame.estimated_size();
+                self.frame = Some(frame);
+                size
+            }
+        };
+        let (cpu, wall, peak) = section.finish();
+
+        self.decode += wall;
+        self.cpu += cpu;
+        // What the chunk allocated on top of the result so far
+        self.peak = peak.map(|peak| peak + kept).max(self.peak);
+        Ok(())
+    }
+
+    /// Record the query and return its result, empty when no chunk was
+    /// decoded or the chunks went to a sink
+    pub fn finish(self) -> Result<DataFrame> {
+        let df = self.frame.unwrap_or_default();
+        let output_bytes = df.estimated_size();
+        let fetch = self.started.elapsed().saturating_sub(self.decode);
+        self.metrics.record(QueryStats {
+            sql: self.sql.chars().take(SQL_PREFIX_LEN).collect(),
+            rows: df.height() + self.written.0,
+            columns: df.width().max(self.written.1),
+            fetch_us: fetch.as_micros() as u64,
+            decode_us: self.decode.as_micros() as u64,
+            decode_cpu_us: self.cpu.as_micros() as u64,
+            peak_memory_bytes: self.peak.unwrap_or(output_bytes),
+            output_bytes,
+            memory_tracked: self.peak.is_some(),
+        });
+        Ok(df)
+    }
+}
+
+/// Dtype holding the values of both chunks of a column: the wider number,
+/// the larger decimal scale, the coarser time unit, else text
+fn common_dtype(a: &DataType, b: &DataType) -> DataType {
+    match (a, b) {
+        (DataType::Null, other) | (other, DataType::Null) => other.clone(),
+        (DataType::Decimal(_, s1), DataType::Decimal(_, s2)) => {
+            DataType::Decimal(Some(crate::decimal::MAX_PRECISION), (*s1).max(*s2))
+        }
+        (DataType::Datetime(_, tz), DataType::Datetime(_, _)) => {
+            DataType::Datetime(TimeUnit::Microseconds, tz.clone())
+        }
+        (a, b) if a.is_integer() && b.is_integer() => DataType::Int64,
+        (a, b) if a.is_numeric() && b.is_numeric() => DataType::Float64,
+        _ => DataType::String,
+    }
+}
+
+/// `chunk` appended to `frame`, with columns of differing dtypes cast to
+/// a common one; frames without columns (empty results) are skipped
+pub(crate) fn append_chunk(mut frame: DataFrame, mut chunk: DataFrame) -> Result<DataFrame> {
+    let polars_err = |e: polars::error::PolarsError| IndustryDbError::PolarsError(e.to_string());
+    if frame.width() == 0 {
+        return Ok(chunk);
+    }
+    if chunk.width() == 0 {
+        return Ok(frame);
+    }
+    for index in 0..frame.width() {
+        let ours = frame.get_columns()[index].dtype().clone();
+        let theirs = chunk
+            .get_columns()
+            .get(index)
+            .map(|c| c.dtype().clone())
+            .unwrap_or(ours.clone());
+        if ours == theirs {
+            continue;
+        }
+        let common = common_dtype(&ours, &theirs);
+        for df in [&mut frame, &mut chunk] {
+            let column = df.get_columns()[index].cast(&common).map_err(polars_err)?;
+            df.replace_column(index, column).map_err(polars_err)?;
+        }
+    }
+    frame.vstack_mut(&chunk).map_err(polars_err)?;
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_record_decode() {
+        let metrics = QueryMetrics::new();
+        let df = metrics
+            .record_decode("SELECT * FROM readings", Duration::from_millis(3), || {
+                Ok(df!("value" => (0..1000).collect::<Vec<i64>>())?)
+            })
+            .unwrap();
+        assert_eq!(df.height(), 1000);
+
+        let stats = metrics.last().unwrap();
+        assert_eq!(stats.rows, 1000);
+        assert_eq!(stats.columns, 1);
+        assert_eq!(stats.fetch_us, 3000);
+        assert!(stats.output_bytes >= 8000);
+    }
+
+    #[cfg(feature = "track-memory")]
+    #[test]
+    fn test_memory_tracked() {
+        let metrics = QueryMetrics::new();
+        metrics
+            .record_decode("SELECT * FROM readings", Duration::ZERO, || {
+                Ok(df!("value" => (0..1000).collect::<Vec<i64>>())?)
+            })
+            .unwrap();
+
+        let stats = metrics.last().unwrap();
+        assert!(stats.memory_tracked);
+        assert!(stats.peak_memory_bytes >= 8000);
+    }
+
+    #[cfg(not(feature = "track-memory"))]
+    #[test]
+    fn test_memory_untracked() {
+        let metrics = QueryMetrics::new();
+        metrics
+            .record_decode("SELECT * FROM readings", Duration::ZERO, || {
+                Ok(df!("value" => (0..1000).collect::<Vec<i64>>())?)
+            })
+            .unwrap();
+
+        // No tracking allocator: the DataFrame size stands in
+        let stats = metrics.last().unwrap();
+        assert!(!stats.memory_tracked);
+        assert_eq!(stats.peak_memory_bytes, stats.output_bytes);
+    }
+
+    #[test]
+    fn test_chunked_decode_widens_dtypes() {
+        let metrics = QueryMetrics::new();
+        let mut decode = metrics.chunked("SELECT value FROM readings");
+        decode.decode(|| Ok(df!("value" => [1i64, 2])?)).unwrap();
+        decode.decode(|| Ok(df!("value" => [2.5f64])?)).unwrap();
+        let df = decode.finish().unwrap();
+
+        let value = df.column("value").unwrap();
+        assert_eq!(value.dtype(), &DataType::Float64);
+        let values: Vec<_> = value.f64().unwrap().into_iter().collect();
+        assert_eq!(values, [Some(1.0), Some(2.0), Some(2.5)]);
+        assert_eq!(metrics.last().unwrap().rows, 3);
+
+        let empty = metrics.chunked("SELECT 1 WHERE false").finish().unwrap();
+        assert_eq!(empty.height(), 0);
+        assert_eq!(metrics.snapshot().queries, 2);
+    }
+
+    #[test]
+    fn test_chunked_decode_into_sink() {
+        let metrics = QueryMetrics::new();
+        let mut chunks: Vec<DataFrame> = Vec::new();
+        let mut decode = metrics
+            .chunked("SELECT value FROM readings")
+            .into_sink(&mut chunks);
+        decode.decode(|| Ok(df!("value" => [1i64, 2])?)).unwrap();
+        decode.decode(|| Ok(df!("value" => [2.5f64])?)).unwrap();
+        assert_eq!(decode.finish().unwrap().height(), 0);
+
+        // Chunks reach the sink as decoded, without widening
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[1].column("value").unwrap().dtype(),
+            &DataType::Float64
+        );
+        assert_eq!(metrics.last().unwrap().rows, 3);
+        assert_eq!(metrics.last().unwrap().columns, 1);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_recent_queries() {
+        let metrics = QueryMetrics::new();
+        for i in 0..(RECENT_QUERIES + 5) {
+            metrics.record(QueryStats {
+                sql: format!("q{}", i),
+                rows: 2,
+                ..Default::default()
+            });
+        }
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.queries, RECENT_QUERIES as u64 + 5);
+        assert_eq!(snapshot.rows, 2 * (RECENT_QUERIES as u64 + 5));
+        assert_eq!(snapshot.recent.len(), RECENT_QUERIES);
+        assert_eq!(snapshot.recent[0].sql, "q5");
+
+        metrics.reset();
+        assert!(metrics.last().is_none());
+        assert_eq!(metrics.snapshot().queries, 0);
+    }
+}
@@ -0,0 +1,286 @@
+//! Bound query parameters
+//!
+//! Values passed alongside a statement instead of being interpolated into
+//! its text. Placeholders use the database's own syntax (`$1` on
+//! PostgreSQL, `?` or `?1` on SQLite, `@P1` on SQL Server); see
+//! [`Dialect::placeholder`](crate::dialect::Dialect::placeholder).
+//! [`bind_named`] rewrites the more readable `:name` / `@name` style into
+//! those placeholders.
+
+use polars::prelude::AnyValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::DatabaseType;
+use crate::decimal;
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::sql::skip_quoted;
+
+/// A value bound to a statement placeholder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    /// SQL NULL
+    Null,
+    /// Boolean
+    Bool(bool),
+    /// 64-bit integer
+    Int(i64),
+    /// 64-bit float
+    Float(f64),
+    /// Text, also used for dates and timestamps
+    Text(String),
+    /// Binary data
+    Bytes(Vec<u8>),
+    /// Exact decimal as text such as `-12.50`, see [`decimal`](crate::decimal)
+    Decimal(String),
+    /// UUID as hyphenated text, bound as the database's UUID type
+    Uuid(String),
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::Int(v as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::Float(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Text(v.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Text(v)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(v: Vec<u8>) -> Self {
+        Value::Bytes(v)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(Value::Null)
+    }
+}
+
+/// A value read back from a DataFrame
+///
+/// Integers and floats of every width widen to 64 bits and decimals stay
+/// exact; dates, times and any other type become their text representation.
+impl From<AnyValue<'_>> for Value {
+    fn from(v: AnyValue<'_>) -> Self {
+        match v {
+            AnyValue::Null => Value::Null,
+            AnyValue::Boolean(b) => Value::Bool(b),
+            AnyValue::Int8(n) => Value::Int(n as i64),
+            AnyValue::Int16(n) => Value::Int(n as i64),
+            AnyValue::Int32(n) => Value::Int(n as i64),
+            AnyValue::Int64(n) => Value::Int(n),
+            AnyValue::UInt8(n) => Value::Int(n as i64),
+            AnyValue::UInt16(n) => Value::Int(n as i64),
+            AnyValue::UInt32(n) => Value::Int(n as i64),
+            // Kept exact as a decimal beyond the range of i64
+            AnyValue::UInt64(n) => match i64::try_from(n) {
+                Ok(n) => Value::Int(n),
+                Err(_) => Value::Decimal(n.to_string()),
+            },
+            AnyValue::Float32(f) => Value::Float(f as f64),
+            AnyValue::Float64(f) => Value::Float(f),
+            AnyValue::Binary(bytes) => Value::Bytes(bytes.to_vec()),
+            AnyValue::BinaryOwned(bytes) => Value::Bytes(bytes),
+            AnyValue::Decimal(v, scale) => Value::Decimal(decimal::format(v, scale as u32)),
+            other => match other.get_str() {
+                Some(s) => Value::Text(s.to_string()),
+                None => Value::Text(other.to_string()),
+            },
+        }
+    }
+}
+
+/// Error for the parameter set at `index` of an `execute_many` call
+pub fn parameter_set_error(index: usize, error: IndustryDbError) -> IndustryDbError {
+    IndustryDbError::query_error(format!("Parameter set {} failed: {}", index + 1, error))
+}
+
+/// Rewrite `:name` and `@name` parameters to the dialect's placeholders
+///
+/// Returns the rewritten SQL and the values in placeholder order. A name used
+/// several times is bound once. Only names present in `params` are
+/// rewritten, so `::` casts, `@@` globals and SQL Server variables declared in
+/// the statement are left alone, as is anything inside quotes or comments.
+/// Every entry of `params` must be used.
+pub fn bind_named(
+    sql: &str,
+    params: &HashMap<String, Value>,
+    dialect: &dyn Dialect,
+) -> Result<(String, Vec<Value>)> {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    let mut values = Vec::new();
+    let mut indexes: HashMap<&str, usize> = HashMap::new();
+    let mut copied = 0;
+    let mut i = 0;
+    let dollar_quotes = dialect.db_type() == DatabaseType::Postgres;
+
+    while i < bytes.len() {
+        if let Some(end) = skip_quoted(sql, i, dollar_quotes) {
+            i = end;
+            continue;
+        }
+
+        let sigil = bytes[i];
+        if sigil != b':' && sigil != b'@' {
+            i += 1;
+            continue;
+        }
+        // `::type` casts and `@@GLOBAL` variables
+        if bytes.get(i + 1) == Some(&sigil) {
+            i += 2;
+            continue;
+        }
+
+        let name_len = sql[i + 1..]
+            .bytes()
+            .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+            .count();
+        let name = &sql[i + 1..i + 1 + name_len];
+        let starts_like_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+
+        match params.get(name) {
+            Some(value) if starts_like_name => {
+                let index = *indexes.entry(name).or_insert_with(|| {
+                    values.push(value.clone());
+                    values.len()
+                });
+                out.push_str(&sql[copied..i]);
+                out.push_str(&dialect.placeholder(index));
+                i += 1 + name_len;
+                copied = i;
+            }
+            _ => i += 1 + name_len,
+        }
+    }
+    out.push_str(&sql[copied..]);
+
+    if let Some(unused) = params.keys().find(|k| !indexes.contains_key(k.as_str())) {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Parameter '{}' is not used in the statement",
+            unused
+        )));
+    }
+
+    Ok((out, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect, SqliteDialect};
+
+    fn named(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_bind_named_per_dialect() {
+        let params = named(&[("tag", "TI-101".into()), ("since", "2024-01-01".into())]);
+        let sql = "SELECT * FROM t WHERE tag = :tag AND ts >= @since OR alt = :tag";
+
+        let (pg, values) = bind_named(sql, &params, &PostgresDialect).unwrap();
+        assert_eq!(
+            pg,
+            "SELECT * FROM t WHERE tag = $1 AND ts >= $2 OR alt = $1"
+        );
+        assert_eq!(
+            values,
+            vec![Value::from("TI-101"), Value::from("2024-01-01")]
+        );
+
+        let (lite, _) = bind_named(sql, &params, &SqliteDialect).unwrap();
+        assert!(lite.contains("tag = ?1 AND ts >= ?2"));
+
+        let (ms, _) = bind_named(sql, &params, &MssqlDialect).unwrap();
+        assert!(ms.contains("tag = @P1 AND ts >= @P2"));
+    }
+
+    #[test]
+    fn test_bind_named_skips_casts_literals_and_unknown_names() {
+        let params = named(&[("v", 1.into())]);
+        let sql = "SELECT x::int, ':v', @@VERSION, @local FROM t -- :v\nWHERE y = :v";
+        let (pg, values) = bind_named(sql, &params, &PostgresDialect).unwrap();
+        assert_eq!(
+            pg,
+            "SELECT x::int, ':v', @@VERSION, @local FROM t -- :v\nWHERE y = $1"
+        );
+        assert_eq!(values, vec![Value::Int(1)]);
+
+        let unused = named(&[("v", 1.into()), ("w", 2.into())]);
+        assert!(bind_named(sql, &unused, &PostgresDialect).is_err());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Value::from(3), Value::Int(3));
+        assert_eq!(Value::from("TI-101"), Value::Text("TI-101".to_string()));
+        assert_eq!(Value::from(None::<f64>), Value::Null);
+        assert_eq!(Value::from(Some(true)), Value::Bool(true));
+    }
+
+    #[test]
+    fn test_from_any_value() {
+        assert_eq!(Value::from(AnyValue::Int32(7)), Value::Int(7));
+        assert_eq!(Value::from(AnyValue::Float32(0.5)), Value::Float(0.5));
+        assert_eq!(Value::from(AnyValue::String("a")), Value::from("a"));
+        assert_eq!(
+            Value::from(AnyValue::UInt64(u64::MAX)),
+            Value::Decimal(u64::MAX.to_string())
+        );
+        assert_eq!(Value::from(AnyValue::Null), Value::Null);
+        assert_eq!(
+            Value::from(AnyValue::Decimal(-1250, 3)),
+            Value::Decimal("-1.250".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_untagged() {
+        let values: Vec<Value> = serde_json::from_str(r#"[null, true, 1, 2.5, "a"]"#).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Value::Null,
+                Value::Bool(true),
+                Value::Int(1),
+                Value::Float(2.5),
+                Value::Text("a".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Partitioned parallel reads
+//!
+//! Splits a table or query into ranges of one column and fetches the ranges
+//! concurrently, each over its own pooled connection, then stacks them into
+//! one DataFrame. The ranges are even splits of the column's span between
+//! its minimum and maximum, so skewed data gives uneven partitions.
+
+use chrono::{NaiveDateTime, TimeDelta};
+use futures_util::future::try_join_all;
+use polars::prelude::*;
+
+use crate::error::{IndustryDbError, Result};
+use crate::metrics::append_chunk;
+use crate::params::Value;
+use crate::time::{format_timestamp, parse_timestamp};
+use crate::traits::DatabaseConnector;
+
+/// Read `source` in `num_partitions` concurrent ranges of `partition_column`
+///
+/// `source` is a table name or a `SELECT` statement, which is read as a
+/// derived table (so SQL Server does not allow an `ORDER BY` in it). The
+/// column must hold numbers or timestamps; rows where it is null are read
+/// with the first partition. Rows come back grouped by partition, in no
+/// particular order within one.
+pub async fn read_partitioned<C>(
+    conn: &C,
+    source: &str,
+    partition_column: &str,
+    num_partitions: usize,
+) -> Result<DataFrame>
+where
+    C: DatabaseConnector + ?Sized,
+{
+    if num_partitions == 0 {
+        return Err(IndustryDbError::invalid_parameter(
+            "num_partitions must be positive",
+        ));
+    }
+    let dialect = conn.dialect();
+    let source = if source.split_whitespace().nth(1).is_some() {
+        format!("({}) {}", source, dialect.identifier("partition_source")?)
+    } else {
+        dialect.identifier(source)?
+    };
+    let column = dialect.identifier(partition_column)?;
+
+    let bounds = conn
+        .execute(&format!(
+            "SELECT MIN({col}) AS lo, MAX({col}) AS hi FROM {source}",
+            col = column,
+            source = source,
+        ))
+        .await?;
+    let cuts = match (key(&bounds, "lo")?, key(&bounds, "hi")?) {
+        (Some(lo), Some(hi)) => cut_points(&lo, &hi, num_partitions),
+        _ => Vec::new(),
+    };
+
+    let select = format!("SELECT * FROM {}", source);
+    let queries: Vec<(String, Vec<Value>)> = if cuts.is_empty() {
+        vec![(select, Vec::new())]
+    } else {
+        let p = |index| dialect.placeholder(index);
+        let mut queries = vec![(
+            format!(
+                "{} WHERE {col} < {} OR {col} IS NULL",
+                select,
+                p(1),
+                col = column
+            ),
+            vec![cuts[0].clone()],
+        )];
+        for pair in cuts.windows(2) {
+            queries.push((
+                format!(
+                    "{} WHERE {col} >= {} AND {col} < {}",
+                    select,
+                    p(1),
+                    p(2),
+                    col = column
+                ),
+                pair.to_vec(),
+            ));
+        }
+        queries.push((
+            format!("{} WHERE {} >= {}", select, column, p(1)),
+            vec![cuts[cuts.len() - 1].clone()],
+        ));
+        queries
+    };
+
+    let parts = try_join_all(
+        queries
+            .iter()
+            .map(|(sql, params)| conn.execute_with_params(sql, params)),
+    )
+    .await?;
+    parts
+        .into_iter()
+        .try_fold(DataFrame::empty(), |frame, part| append_chunk(frame, part))
+}
+
+/// Minimum or maximum of the partition column
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Int(i64),
+    Float(f64),
+    Time(NaiveDateTime),
+}
+
+/// The value of `name` in the bounds query's single row
+fn key(bounds: &DataFrame, name: &str) -> Result<Option<Key>> {
+    let unsupported = |dtype: &DataType| {
+        IndustryDbError::invalid_parameter(format!(
+            "Cannot partition on a column of type {}; use a numeric or timestamp column",
+            dtype
+        ))
+    };
+    let column = bounds.column(name)?;
+    if column.null_count() == column.len() {
+        return Ok(None);
+    }
+    let dtype = column.dtype().clone();
+    Ok(Some(match &dtype {
+        dtype if dtype.is_integer() => {
+            Key::Int(column.cast(&DataType::Int64)?.i64()?.get(0).unwrap_or(0))
+        }
+        DataType::Float32 | DataType::Float64 | DataType::Decimal(_, _) => Key::Float(
+            column
+                .cast(&DataType::Float64)?
+                .f64()?
+                .get(0)
+                .unwrap_or(0.0),
+        ),
+        DataType::Date | DataType::Datetime(_, _) => {
+            let micros = column
+                .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?
+                .to_physical_repr()
+                .i64()?
+                .get(0)
+                .unwrap_or(0);
+            Key::Time(
+                chrono::DateTime::from_timestamp_micros(micros)
+                    .ok_or_else(|| unsupported(&dtype))?
+                    .naive_utc(),
+            )
+        }
+        // SQLite keeps timestamps as text
+        DataType::String => Key::Time(
+            parse_timestamp(column.str()?.get(0).unwrap_or_default())
+                .map_err(|_| unsupported(&dtype))?,
+        ),
+        _ => return Err(unsupported(&dtype)),
+    }))
+}
+
+/// The values splitting `lo..=hi` into `n` even ranges, without repeats
+fn cut_points(lo: &Key, hi: &Key, n: usize) -> Vec<Value> {
+    let mut cuts: Vec<Value> = Vec::new();
+    for i in 1..n {
+        let cut = match (lo, hi) {
+            (Key::Int(lo), Key::Int(hi)) => {
+                let span = *hi as i128 - *lo as i128;
+                Value::Int((*lo as i128 + span * i as i128 / n as i128) as i64)
+            }
+            (Key::Float(lo), Key::Float(hi)) => Value::Float(lo + (hi - lo) * i as f64 / n as f64),
+            (Key::Time(lo), Key::Time(hi)) => {
+                let span = (*hi - *lo).num_microseconds().unwrap_or(i64::MAX);
+                let offset = (span as i128 * i as i128 / n as i128) as i64;
+                Value::Text(format_timestamp(&(*lo + TimeDelta::microseconds(offset))))
+            }
+            _ => return Vec::new(),
+        };
+        // A cut at the minimum would leave the first partition empty
+        let at_min = match (&cut, lo) {
+            (Value::Int(c), Key::Int(lo)) => c == lo,
+            (Value::Float(c), Key::Float(lo)) => c == lo,
+            (Value::Text(c), Key::Time(lo)) => *c == format_timestamp(lo),
+            _ => false,
+        };
+        if !at_min && cuts.last() != Some(&cut) {
+            cuts.push(cut);
+        }
+    }
+    cuts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cut_points() {
+        assert_eq!(
+            cut_points(&Key::Int(0), &Key::Int(100), 4),
+            [Value::Int(25), Value::Int(50), Value::Int(75)]
+        );
+        // A narrow span gives fewer partitions than asked for
+        assert_eq!(cut_points(&Key::Int(1), &Key::Int(2), 4), [Value::Int(2)]);
+        assert!(cut_points(&Key::Int(5), &Key::Int(5), 4).is_empty());
+        assert!(cut_points(&Key::Int(0), &Key::Int(100), 1).is_empty());
+
+        let lo = parse_timestamp("2024-03-01").unwrap();
+        let hi = parse_timestamp("2024-03-02").unwrap();
+        assert_eq!(
+            cut_points(&Key::Time(lo), &Key::Time(hi), 2),
+            [Value::Text("2024-03-01 12:00:00".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_key_types() {
+        let bounds = df!(
+            "lo" => [Some(3i32)],
+            "hi" => [None::<i32>],
+            "ts" => ["2024-03-01 08:00:00"],
+            "tag" => [true],
+        )
+        .unwrap();
+        assert_eq!(key(&bounds, "lo").unwrap(), Some(Key::Int(3)));
+        assert_eq!(key(&bounds, "hi").unwrap(), None);
+        assert_eq!(
+            key(&bounds, "ts").unwrap(),
+            Some(Key::Time(parse_timestamp("2024-03-01 08:00:00").unwrap()))
+        );
+        assert!(key(&bounds, "tag").is_err());
+    }
+}
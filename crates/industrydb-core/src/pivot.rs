@@ -0,0 +1,359 @@
+//! Tag-value historian layouts
+//!
+//! Historians usually store one row per timestamp and tag (`ts`, `tag`,
+//! `value`), while analysis wants one column per tag. [`Pivoted`] reads the
+//! narrow layout into the wide one and [`unpivot`] turns a wide frame back
+//! into narrow rows for writing.
+//!
+//! ```ignore
+//! let wide = Pivoted::new("readings", "ts", "tag", "value")
+//!     .tags(["TI-101", "TI-102"])
+//!     .between(parse_timestamp("2024-03-01")?, end)
+//!     .fetch(&conn)
+//!     .await?;
+//!
+//! conn.insert("readings", unpivot(wide, "ts", "tag", "value")?).await?;
+//! ```
+
+use chrono::NaiveDateTime;
+use polars::prelude::{
+    col, lit, DataFrame, DataType, Expr, IntoLazy, Series, SortMultipleOptions, TimeUnit,
+    UniqueKeepStrategy,
+};
+
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::schema::coerce_columns;
+use crate::time::format_timestamp;
+use crate::traits::DatabaseConnector;
+
+/// Narrow tag-value rows to read as one column per tag
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pivoted {
+    table: String,
+    ts_column: String,
+    tag_column: String,
+    value_column: String,
+    tags: Vec<String>,
+    range: Option<(NaiveDateTime, NaiveDateTime)>,
+}
+
+impl Pivoted {
+    /// Rows of `table` holding `value_column` of the tag in `tag_column`
+    /// at `ts_column`
+    pub fn new(table: &str, ts_column: &str, tag_column: &str, value_column: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            ts_column: ts_column.to_string(),
+            tag_column: tag_column.to_string(),
+            value_column: value_column.to_string(),
+            tags: Vec::new(),
+            range: None,
+        }
+    }
+
+    /// Only these tags, as columns in this order; every tag in the rows
+    /// otherwise
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags = tags.into_iter().map(|t| t.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Only the rows from `start` up to, but not including, `end`
+    pub fn between(mut self, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        self.range = Some((start, end));
+        self
+    }
+
+    /// Render the query for the narrow rows and its parameters in `dialect`
+    pub fn render(&self, dialect: &dyn Dialect) -> Result<(String, Vec<Value>)> {
+        let ts = dialect.identifier(&self.ts_column)?;
+        let tag = dialect.identifier(&self.tag_column)?;
+        let mut params = Vec::new();
+        let mut filters = Vec::new();
+        if let Some((start, end)) = &self.range {
+            if start >= end {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Time range start {} is not before its end {}",
+                    start, end
+                )));
+            }
+            params.push(Value::Text(format_timestamp(start)));
+            params.push(Value::Text(format_timestamp(end)));
+            filters.push(format!(
+                "{} >= {} AND {} < {}",
+                ts,
+                dialect.placeholder(1),
+                ts,
+                dialect.placeholder(2)
+            ));
+        }
+        if !self.tags.is_empty() {
+            let placeholders: Vec<String> = self
+                .tags
+                .iter()
+                .map(|t| {
+                    params.push(Value::Text(t.clone()));
+                    dialect.placeholder(params.len())
+                })
+                .collect();
+            filters.push(format!("{} IN ({})", tag, placeholders.join(", ")));
+        }
+
+        let mut sql = format!(
+            "SELECT {}, {}, {} FROM {}",
+            ts,
+            tag,
+            dialect.identifier(&self.value_column)?,
+            dialect.identifier(&self.table)?
+        );
+        if !filters.is_empty() {
+            sql.push_str(&format!(" WHERE {}", filters.join(" AND ")));
+        }
+        sql.push_str(&format!(" ORDER BY {}", ts));
+        Ok((sql, params))
+    }
+
+    /// Run the query on a connection and pivot the rows, see [`pivot`]
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<DataFrame> {
+        let (sql, params) = self.render(conn.dialect())?;
+        let df = conn.execute_with_params(&sql, &params).await?;
+        pivot(
+            df,
+            &self.ts_column,
+            &self.tag_column,
+            &self.value_column,
+            &self.tags,
+        )
+    }
+}
+
+/// One row per timestamp and one column per tag from narrow rows
+///
+/// The columns are `tags` in that order, or every tag in the rows sorted
+/// by name when empty; a tag without a value at a timestamp is null, and
+/// when a tag has several values at one timestamp the first is kept. Text
+/// timestamps, as SQLite returns them, are parsed. Rows are ordered by
+/// timestamp.
+pub fn pivot(
+    df: DataFrame,
+    ts_column: &str,
+    tag_column: &str,
+    value_column: &str,
+    tags: &[String],
+) -> Result<DataFrame> {
+    let df = match df.column(ts_column)?.dtype() {
+        DataType::String => coerce_columns(
+            df,
+            &[(
+                ts_column.to_string(),
+                DataType::Datetime(TimeUnit::Microseconds, None),
+            )],
+        )?,
+        _ => df,
+    };
+    let df = df
+        .lazy()
+        .select([
+            col(ts_column),
+            col(tag_column).cast(DataType::String),
+            col(value_column),
+        ])
+        .collect()?;
+
+    let tags = match tags {
+        [] => {
+            let names = df
+                .select([tag_column])?
+                .lazy()
+                .unique(None, UniqueKeepStrategy::Any)
+                .sort([tag_column], SortMultipleOptions::default())
+                .collect()?;
+            names
+                .column(tag_column)?
+                .str()?
+                .into_iter()
+                .flatten()
+                .map(str::to_string)
+                .collect()
+        }
+        tags => tags.to_vec(),
+    };
+    if tags.iter().any(|t| t == ts_column) {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Tag '{}' has the name of the timestamp column",
+            ts_column
+        )));
+    }
+
+    let values: Vec<Expr> = tags
+        .iter()
+        .map(|t| {
+            col(value_column)
+                .filter(col(tag_column).eq(lit(t.as_str())))
+                .first()
+                .alias(t.as_str())
+        })
+        .collect();
+    Ok(df
+        .lazy()
+        .group_by_stable([col(ts_column)])
+        .agg(values)
+        .sort([ts_column], SortMultipleOptions::default())
+        .collect()?)
+}
+
+/// Narrow rows from a frame with one column per tag, the reverse of
+/// [`pivot`]
+///
+/// Every column besides `ts_column` becomes rows of `tag_column`, holding
+/// the column name, and `value_column`; null values are left out. Value
+/// columns of different numeric types are widened to float, and mixed
+/// columns to text. Rows are ordered by timestamp, then by column.
+pub fn unpivot(
+    df: DataFrame,
+    ts_column: &str,
+    tag_column: &str,
+    value_column: &str,
+) -> Result<DataFrame> {
+    let ts = df.column(ts_column)?.as_materialized_series().clone();
+    let columns: Vec<Series> = df
+        .get_columns()
+        .iter()
+        .filter(|c| c.name().as_str() != ts_column)
+        .map(|c| c.as_materialized_series().clone())
+        .collect();
+    let Some(first) = columns.first() else {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "No tag columns besides '{}' to unpivot",
+            ts_column
+        )));
+    };
+    let dtype = match first.dtype() {
+        first if columns.iter().all(|c| c.dtype() == first) => first.clone(),
+        _ if columns.iter().all(|c| c.dtype().is_numeric()) => DataType::Float64,
+        _ => DataType::String,
+    };
+
+    let rows = |column: &Series| -> Result<DataFrame> {
+        let tags = Series::new(tag_column.into(), vec![column.name().as_str(); df.height()]);
+        let values = column.cast(&dtype)?.with_name(value_column.into());
+        Ok(DataFrame::new(vec![
+            ts.clone().into(),
+            tags.into(),
+            values.into(),
+        ])?)
+    };
+    let mut narrow = rows(first)?;
+    for column in &columns[1..] {
+        narrow.vstack_mut(&rows(column)?)?;
+    }
+    Ok(narrow
+        .lazy()
+        .filter(col(value_column).is_not_null())
+        .sort(
+            [ts_column],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .collect()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{PostgresDialect, SqliteDialect};
+    use crate::time::parse_timestamp;
+    use polars::prelude::df;
+
+    #[test]
+    fn test_render_pivoted() {
+        let (sql, params) = Pivoted::new("readings", "ts", "tag", "value")
+            .tags(["TI-101", "TI-102"])
+            .between(
+                parse_timestamp("2024-03-01").unwrap(),
+                parse_timestamp("2024-03-02").unwrap(),
+            )
+            .render(&PostgresDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT \"ts\", \"tag\", \"value\" FROM \"readings\" \
+             WHERE \"ts\" >= $1 AND \"ts\" < $2 AND \"tag\" IN ($3, $4) ORDER BY \"ts\""
+        );
+        assert_eq!(params.len(), 4);
+
+        let (sql, params) = Pivoted::new("readings", "ts", "tag", "value")
+            .render(&SqliteDialect)
+            .unwrap();
+        assert!(!sql.contains("WHERE"));
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_pivot_and_unpivot() {
+        let narrow = df!(
+            "ts" => ["2024-03-01 08:00", "2024-03-01 08:00", "2024-03-01 08:01", "2024-03-01 08:01"],
+            "tag" => ["TI-102", "TI-101", "TI-101", "TI-101"],
+            "value" => [2.0, 1.0, 3.0, 4.0],
+        )
+        .unwrap();
+
+        let wide = pivot(narrow.clone(), "ts", "tag", "value", &[]).unwrap();
+        assert_eq!(wide.get_column_names_str(), ["ts", "TI-101", "TI-102"]);
+        assert_eq!(
+            wide.column("ts").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+        let values: Vec<_> = wide
+            .column("TI-101")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(1.0), Some(3.0)]);
+        let values: Vec<_> = wide
+            .column("TI-102")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(2.0), None]);
+
+        let tags = ["TI-102".to_string(), "TI-103".to_string()];
+        let picked = pivot(narrow, "ts", "tag", "value", &tags).unwrap();
+        assert_eq!(picked.get_column_names_str(), ["ts", "TI-102", "TI-103"]);
+        assert_eq!(picked.column("TI-103").unwrap().null_count(), 2);
+
+        let back = unpivot(wide, "ts", "tag", "value").unwrap();
+        assert_eq!(back.get_column_names_str(), ["ts", "tag", "value"]);
+        let tags: Vec<_> = back
+            .column("tag")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(tags, [Some("TI-101"), Some("TI-102"), Some("TI-101")]);
+        let values: Vec<_> = back
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(1.0), Some(2.0), Some(3.0)]);
+
+        let mixed = df!("ts" => [1i64], "a" => [1i32], "b" => [2.5]).unwrap();
+        let back = unpivot(mixed, "ts", "tag", "value").unwrap();
+        assert_eq!(back.column("value").unwrap().dtype(), &DataType::Float64);
+        let only_ts = df!("ts" => [1i64]).unwrap();
+        assert!(unpivot(only_ts, "ts", "tag", "value").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Connector plugins
+//!
+//! A plugin is a crate that registers one or more connectors with
+//! [`ConnectionFactory`]. It declares itself with [`export_plugin!`]:
+//!
+//! ```ignore
+//! fn register(registrar: &mut PluginRegistrar) {
+//!     registrar.register_connector("historian", Arc::new(HistorianBuilder));
+//! }
+//!
+//! industrydb_core::export_plugin!(register);
+//! ```
+//!
+//! Linked statically, the plugin is installed with
+//! `register_plugin(&historian::INDUSTRYDB_PLUGIN)`. Built as a `cdylib`, it
+//! is loaded at runtime with [`load_plugin`] (feature `plugins`). The
+//! declaration carries [`PLUGIN_ABI_VERSION`] and the `industrydb-core`
+//! version the plugin was built against, and loading refuses any mismatch.
+//! Connectors cross the boundary as Rust trait objects, so a dynamic plugin
+//! must also be built with the same Rust toolchain as the application.
+
+use std::sync::Arc;
+
+use crate::error::{IndustryDbError, Result};
+use crate::factory::{ConnectionFactory, ConnectorBuilder};
+
+/// Version of the plugin declaration layout; bumped whenever
+/// [`PluginDeclaration`] or [`PluginRegistrar`] change incompatibly
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Name of the symbol [`export_plugin!`] defines
+pub const PLUGIN_SYMBOL: &str = "INDUSTRYDB_PLUGIN";
+
+/// What a plugin exports, see [`export_plugin!`]
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// [`PLUGIN_ABI_VERSION`] the plugin was built with
+    pub abi_version: u32,
+    /// `industrydb-core` version the plugin was built against
+    pub core_version: &'static str,
+    /// Registers the plugin's connectors
+    pub register: fn(&mut PluginRegistrar),
+}
+
+/// Collects the connectors a plugin registers
+#[derive(Default)]
+pub struct PluginRegistrar {
+    connectors: Vec<(String, Arc<dyn ConnectorBuilder>)>,
+}
+
+impl PluginRegistrar {
+    /// Register a connector under a name, used as `connector` in configs
+    pub fn register_connector(
+        &mut self,
+        name: impl Into<String>,
+        builder: Arc<dyn ConnectorBuilder>,
+    ) {
+        self.connectors.push((name.into(), builder));
+    }
+}
+
+/// Declare a plugin whose connectors are registered by `register`, a
+/// `fn(&mut PluginRegistrar)`
+///
+/// Defines the `INDUSTRYDB_PLUGIN` static that [`register_plugin`] and
+/// [`load_plugin`] read.
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:expr) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static INDUSTRYDB_PLUGIN: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration {
+                abi_version: $crate::plugin::PLUGIN_ABI_VERSION,
+                core_version: $crate::VERSION,
+                register: $register,
+            };
+    };
+}
+
+/// Register the connectors of a plugin and return their names
+///
+/// Fails without registering anything if the plugin was built for another
+/// ABI or core version, or if one of its connector names is taken.
+pub fn register_plugin(declaration: &PluginDeclaration) -> Result<Vec<String>> {
+    if declaration.abi_version != PLUGIN_ABI_VERSION {
+        return Err(IndustryDbError::config_error(format!(
+            "Plugin uses plugin ABI version {}, but this build supports version {}",
+            declaration.abi_version, PLUGIN_ABI_VERSION
+        )));
+    }
+    if declaration.core_version != crate::VERSION {
+        return Err(IndustryDbError::config_error(format!(
+            "Plugin was built against industrydb-core {}, but this build uses {}",
+            declaration.core_version,
+            crate::VERSION
+        )));
+    }
+
+    let mut registrar = PluginRegistrar::default();
+    (declaration.register)(&mut registrar);
+    if let Some((name, _)) = registrar
+        .connectors
+        .iter()
+        .find(|(name, _)| ConnectionFactory::is_registered(name))
+    {
+        return Err(IndustryDbError::config_error(format!(
+            "A connector named '{}' is already registered",
+            name
+        )));
+    }
+
+    let mut names = Vec::with_capacity(registrar.connectors.len());
+    for (name, builder) in registrar.connectors {
+        ConnectionFactory::register(name.clone(), builder)?;
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Load a plugin from a shared library and register its connectors
+///
+/// Returns the names of the registered connectors. The library stays loaded
+/// for the rest of the process, since its connectors run its code.
+///
+/// # Safety
+///
+/// Loading a library runs its initialisers, and the library must have been
+/// built with [`export_plugin!`] and the same Rust toolchain. Only load
+/// trusted plugins.
+#[cfg(feature = "plugins")]
+pub unsafe fn load_plugin(path: impl AsRef<std::path::Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let library = libloading::Library::new(path).map_err(|e| {
+        IndustryDbError::config_error(format!("Failed to load plugin {}: {}", path.display(), e))
+    })?;
+    let declaration = library
+        .get::<*const PluginDeclaration>(PLUGIN_SYMBOL.as_bytes())
+        .map_err(|e| {
+            IndustryDbError::config_error(format!(
+                "{} is not an industrydb plugin: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    let names = register_plugin(&**declaration)?;
+    std::mem::forget(library);
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConnectionConfig;
+    use crate::traits::CrudOperations;
+    use async_trait::async_trait;
+
+    struct NullBuilder;
+
+    #[async_trait]
+    impl ConnectorBuilder for NullBuilder {
+        async fn build(&self, _config: &ConnectionConfig) -> Result<Box<dyn CrudOperations>> {
+            Err(IndustryDbError::NotImplemented("null".to_string()))
+        }
+    }
+
+    fn register(registrar: &mut PluginRegistrar) {
+        registrar.register_connector("plugin-test-a", Arc::new(NullBuilder));
+        registrar.register_connector("plugin-test-b", Arc::new(NullBuilder));
+    }
+
+    export_plugin!(register);
+
+    #[test]
+    fn test_register_plugin() {
+        assert_eq!(
+            register_plugin(&INDUSTRYDB_PLUGIN).unwrap(),
+            vec!["plugin-test-a", "plugin-test-b"]
+        );
+        assert!(ConnectionFactory::is_registered("plugin-test-b"));
+        // Registering twice is refused as a whole
+        assert!(register_plugin(&INDUSTRYDB_PLUGIN).is_err());
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let declaration = PluginDeclaration {
+            abi_version: PLUGIN_ABI_VERSION + 1,
+            core_version: crate::VERSION,
+            register,
+        };
+        let err = register_plugin(&declaration).unwrap_err();
+        assert!(err.to_string().contains("plugin ABI version"));
+
+        let declaration = PluginDeclaration {
+            abi_version: PLUGIN_ABI_VERSION,
+            core_version: "0.0.0-other",
+            register,
+        };
+        assert!(register_plugin(&declaration).is_err());
+    }
+}
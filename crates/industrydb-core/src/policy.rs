@@ -0,0 +1,679 @@
+//! Access policies enforced on the read path
+//!
+//! A policy restricts which columns of which tables a connection may read.
+//! Queries are parsed with the SQL layer; `SELECT *` over a restricted table
+//! is rewritten to the permitted column list and explicit references to other
+//! columns are rejected, so the restriction also applies to raw `execute`.
+//!
+//! Every expression of a statement is checked, not only the select list: a
+//! hidden column in a WHERE, GROUP BY, HAVING, ORDER BY or join condition
+//! leaks through which rows come back. Nested queries are checked against
+//! their own tables plus those of the queries around them, which they can
+//! reference. An unqualified column is rejected when it could resolve to a
+//! hidden column of a restricted table, i.e. when some restricted table in
+//! scope does not permit it and an unrestricted table, whose columns the
+//! policy does not know, is in scope too. A `t.*` function argument or a
+//! `RETURNING *` over a restricted table is rejected rather than expanded.
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, FunctionArguments, Ident, JoinConstraint, JoinOperator,
+    ObjectName, Query, Select, SelectItem, SetExpr, Statement, TableFactor, Visit, VisitMut,
+    Visitor, VisitorMut,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::config::DatabaseType;
+use crate::error::{IndustryDbError, Result};
+use crate::sql::parse_statements;
+
+/// Column-level read policy for a connection
+///
+/// ```toml
+/// [connections.plant.policy.columns]
+/// readings = ["ts", "tag_id", "value"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessPolicy {
+    /// Readable columns per table; tables not listed are unrestricted
+    #[serde(default)]
+    pub columns: HashMap<String, Vec<String>>,
+}
+
+/// A table a query reads, in its FROM clause or an enclosing query's
+#[derive(Clone)]
+struct ScopeTable<'p> {
+    /// Name used to qualify columns (alias or table name)
+    qualifier: String,
+    /// Permitted columns when the table is restricted
+    allowed: Option<&'p [String]>,
+}
+
+impl AccessPolicy {
+    /// Create a policy from a table → columns map
+    pub fn new(columns: HashMap<String, Vec<String>>) -> Self {
+        Self { columns }
+    }
+
+    /// Whether the policy restricts anything
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Permitted columns for a table, matched case-insensitively on either
+    /// the qualified name or the bare table name
+    pub fn allowed_columns(&self, table: &str) -> Option<&[String]> {
+        let bare = table.rsplit('.').next().unwrap_or(table);
+        self.columns
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(table) || name.eq_ignore_ascii_case(bare))
+            .map(|(_, cols)| cols.as_slice())
+    }
+
+    /// Enforce the policy on a SQL string
+    ///
+    /// Returns the SQL unchanged when nothing needs rewriting, the rewritten
+    /// SQL when a wildcard was expanded, or `AccessDenied` when a restricted
+    /// column is referenced.
+    pub fn enforce<'a>(&self, sql: &'a str, db_type: DatabaseType) -> Result<Cow<'a, str>> {
+        if self.is_empty() {
+            return Ok(Cow::Borrowed(sql));
+        }
+
+        let mut statements = match parse_statements(sql, Some(db_type)) {
+            Ok(statements) => statements,
+            // Unparseable SQL is only let through when it cannot touch a restricted table
+            Err(e) => {
+                let lower = sql.to_lowercase();
+                let touches_restricted = self.columns.keys().any(|table| {
+                    let bare = table.rsplit('.').next().unwrap_or(table);
+                    lower.contains(&bare.to_lowercase())
+                });
+                return if touches_restricted {
+                    Err(IndustryDbError::access_denied(format!(
+                        "cannot verify column access for unparseable SQL: {}",
+                        e
+                    )))
+                } else {
+                    Ok(Cow::Borrowed(sql))
+                };
+            }
+        };
+
+        let mut changed = false;
+        for stmt in statements.iter_mut() {
+            changed |= match stmt {
+                Statement::Query(query) => self.enforce_query(query, &[])?,
+                // UPDATE and DELETE filter on the tables they write, and
+                // INSERT ... SELECT or CREATE TABLE ... AS read through a query
+                stmt => {
+                    let scope = self.tables(&*stmt, &[]);
+                    self.check_returning(stmt, &scope)?;
+                    ScopeCheck::new(self, &scope).run(stmt)?
+                }
+            };
+        }
+
+        if !changed {
+            return Ok(Cow::Borrowed(sql));
+        }
+
+        Ok(Cow::Owned(
+            statements
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+        ))
+    }
+
+    /// Tables `node` reads outside its nested queries, followed by `outer`
+    fn tables<'p, V: Visit>(&'p self, node: &V, outer: &[ScopeTable<'p>]) -> Vec<ScopeTable<'p>> {
+        let mut collector = TableCollector {
+            policy: self,
+            depth: 0,
+            tables: Vec::new(),
+        };
+        let _ = node.visit(&mut collector);
+        collector.tables.extend(outer.iter().cloned());
+        collector.tables
+    }
+
+    /// Reject `RETURNING *` over a restricted table, which is not expanded,
+    /// and check the RETURNING list of an INSERT against its target table
+    fn check_returning<'p>(&'p self, stmt: &mut Statement, scope: &[ScopeTable<'p>]) -> Result<()> {
+        let (returning, target) = match stmt {
+            Statement::Insert(insert) => {
+                let target = ScopeTable {
+                    qualifier: insert
+                        .table_alias
+                        .as_ref()
+                        .or(insert.table_name.0.last())
+                        .map(|i| i.value.clone())
+                        .unwrap_or_default(),
+                    allowed: self.allowed_columns(&insert.table_name.to_string()),
+                };
+                (insert.returning.as_mut(), Some(target))
+            }
+            Statement::Update { returning, .. } => (returning.as_mut(), None),
+            Statement::Delete(delete) => (delete.returning.as_mut(), None),
+            _ => return Ok(()),
+        };
+        let Some(returning) = returning else {
+            return Ok(());
+        };
+        let scope: Vec<ScopeTable<'p>> = target.into_iter().chain(scope.iter().cloned()).collect();
+        if scope.iter().any(|t| t.allowed.is_some())
+            && returning.iter().any(|item| {
+                matches!(
+                    item,
+                    SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..)
+                )
+            })
+        {
+            return Err(IndustryDbError::access_denied(
+                "RETURNING * over a restricted table cannot be checked against the connection \
+                 policy; list the returned columns",
+            ));
+        }
+        ScopeCheck::new(self, &scope).run(returning)?;
+        Ok(())
+    }
+
+    fn enforce_query<'p>(&'p self, query: &mut Query, outer: &[ScopeTable<'p>]) -> Result<bool> {
+        let mut changed = false;
+        if let Some(with) = query.with.as_mut() {
+            for cte in with.cte_tables.iter_mut() {
+                changed |= self.enforce_query(&mut cte.query, outer)?;
+            }
+        }
+        changed |= self.enforce_set_expr(&mut query.body, outer)?;
+
+        // ORDER BY, LIMIT and OFFSET see the tables of a plain SELECT body;
+        // ORDER BY may also name its output columns, and those of a set
+        // operation are all it can name
+        let (scope, outputs) = match query.body.as_ref() {
+            SetExpr::Select(select) => (self.tables(&select.from, outer), Some(aliases(select))),
+            _ => (outer.to_vec(), None),
+        };
+        let mut check = ScopeCheck::new(self, &scope);
+        if let Some(order_by) = query.order_by.as_mut() {
+            for item in order_by.exprs.iter_mut() {
+                let output = match (&item.expr, &outputs) {
+                    (Expr::Identifier(ident), Some(names)) => {
+                        names.iter().any(|n| n.eq_ignore_ascii_case(&ident.value))
+                    }
+                    (Expr::Identifier(_), None) => true,
+                    _ => false,
+                };
+                if !output {
+                    changed |= check.run(&mut item.expr)?;
+                }
+            }
+        }
+        changed |= check.run(&mut query.limit)?;
+        changed |= check.run(&mut query.offset)?;
+        Ok(changed)
+    }
+
+    fn enforce_set_expr<'p>(
+        &'p self,
+        body: &mut SetExpr,
+        outer: &[ScopeTable<'p>],
+    ) -> Result<bool> {
+        match body {
+            SetExpr::Select(select) => self.enforce_select(select, outer),
+            SetExpr::Query(query) => self.enforce_query(query, outer),
+            SetExpr::SetOperation { left, right, .. } => {
+                let left = self.enforce_set_expr(left, outer)?;
+                let right = self.enforce_set_expr(right, outer)?;
+                Ok(left || right)
+            }
+            body => ScopeCheck::new(self, outer).run(body),
+        }
+    }
+
+    fn enforce_select<'p>(&'p self, select: &mut Select, outer: &[ScopeTable<'p>]) -> Result<bool> {
+        let mut changed = false;
+        let own = self.tables(&select.from, &[]);
+
+        if own.iter().any(|t| t.allowed.is_some()) {
+            let qualify = own.len() > 1;
+            let mut projection = Vec::with_capacity(select.projection.len());
+            for item in select.projection.drain(..) {
+                match item {
+                    SelectItem::Wildcard(_) => {
+                        for table in &own {
+                            projection.extend(expand_table(table, qualify));
+                        }
+                        changed = true;
+                    }
+                    SelectItem::QualifiedWildcard(ref name, _) => {
+                        let qualifier = name.0.last().map(|i| i.value.as_str()).unwrap_or("");
+                        match own
+                            .iter()
+                            .find(|t| t.qualifier.eq_ignore_ascii_case(qualifier))
+                        {
+                            Some(table) if table.allowed.is_some() => {
+                                projection.extend(expand_table(table, true));
+                                changed = true;
+                            }
+                            _ => projection.push(item),
+                        }
+                    }
+                    item => projection.push(item),
+                }
+            }
+            select.projection = projection;
+        }
+
+        let scope = self.tables(&select.from, outer);
+        let mut check = ScopeCheck::new(self, &scope);
+        for join in select.from.iter().flat_map(|t| &t.joins) {
+            match join_constraint(&join.join_operator) {
+                Some(JoinConstraint::Using(columns)) => {
+                    for column in columns {
+                        if !check.permits(None, &column.value) {
+                            return Err(denied(&column.value));
+                        }
+                    }
+                }
+                Some(JoinConstraint::Natural) if scope.iter().any(|t| t.allowed.is_some()) => {
+                    return Err(IndustryDbError::access_denied(
+                        "NATURAL joins over a restricted table cannot be checked against the \
+                         connection policy",
+                    ));
+                }
+                _ => {}
+            }
+        }
+        changed |= check.run(select)?;
+        Ok(changed)
+    }
+}
+
+/// Collects the tables a node reads, skipping those of nested queries
+struct TableCollector<'p> {
+    policy: &'p AccessPolicy,
+    depth: usize,
+    tables: Vec<ScopeTable<'p>>,
+}
+
+impl Visitor for TableCollector<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, factor: &TableFactor) -> ControlFlow<()> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        let alias = |alias: &Option<sqlparser::ast::TableAlias>| {
+            alias.as_ref().map(|a| a.name.value.clone())
+        };
+        let table = match factor {
+            TableFactor::Table { name, alias: a, .. } => {
+                let table = name.to_string();
+                ScopeTable {
+                    qualifier: alias(a)
+                        .or_else(|| name.0.last().map(|i| i.value.clone()))
+                        .unwrap_or_else(|| table.clone()),
+                    allowed: self.policy.allowed_columns(&table),
+                }
+            }
+            // Its tables are collected on their own
+            TableFactor::NestedJoin { .. } => return ControlFlow::Continue(()),
+            TableFactor::Derived { alias: a, .. }
+            | TableFactor::TableFunction { alias: a, .. }
+            | TableFactor::Function { alias: a, .. }
+            | TableFactor::UNNEST { alias: a, .. } => ScopeTable {
+                qualifier: alias(a).unwrap_or_default(),
+                allowed: None,
+            },
+            _ => ScopeTable {
+                qualifier: String::new(),
+                allowed: None,
+            },
+        };
+        self.tables.push(table);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Checks the column references of a node against a scope, and enforces
+/// the policy on the queries nested in it with that scope as their outer one
+struct ScopeCheck<'s, 'p> {
+    policy: &'p AccessPolicy,
+    scope: &'s [ScopeTable<'p>],
+    depth: usize,
+    changed: bool,
+}
+
+impl<'s, 'p> ScopeCheck<'s, 'p> {
+    fn new(policy: &'p AccessPolicy, scope: &'s [ScopeTable<'p>]) -> Self {
+        Self {
+            policy,
+            scope,
+            depth: 0,
+            changed: false,
+        }
+    }
+
+    /// Check `node`; whether any nested query was rewritten so far
+    fn run<V: VisitMut>(&mut self, node: &mut V) -> Result<bool> {
+        match node.visit(self) {
+            ControlFlow::Break(e) => Err(e),
+            ControlFlow::Continue(()) => Ok(self.changed),
+        }
+    }
+
+    /// Error for a `name.*` argument over a restricted table
+    fn hidden_wildcard(&self, name: &ObjectName) -> Option<IndustryDbError> {
+        let qualifier = name.0.last().map(|i| i.value.as_str()).unwrap_or("");
+        self.scope
+            .iter()
+            .any(|t| t.allowed.is_some() && t.qualifier.eq_ignore_ascii_case(qualifier))
+            .then(|| {
+                IndustryDbError::access_denied(format!(
+                    "'{}.*' reads columns hidden by the connection policy",
+                    name
+                ))
+            })
+    }
+
+    /// Whether a column reference, qualified or not, is readable
+    fn permits(&self, qualifier: Option<&str>, column: &str) -> bool {
+        let allows = |cols: &[String]| cols.iter().any(|c| c.eq_ignore_ascii_case(column));
+        match qualifier {
+            Some(qualifier) => self
+                .scope
+                .iter()
+                .find(|t| t.qualifier.eq_ignore_ascii_case(qualifier))
+                .and_then(|t| t.allowed)
+                .map_or(true, allows),
+            None => {
+                let mut restricted = self.scope.iter().filter_map(|t| t.allowed).peekable();
+                if restricted.peek().is_none() {
+                    true
+                } else if self.scope.iter().any(|t| t.allowed.is_none()) {
+                    restricted.all(allows)
+                } else {
+                    restricted.any(allows)
+                }
+            }
+        }
+    }
+}
+
+impl VisitorMut for ScopeCheck<'_, '_> {
+    type Break = IndustryDbError;
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<IndustryDbError> {
+        if self.depth == 0 {
+            match self.policy.enforce_query(query, self.scope) {
+                Ok(changed) => self.changed |= changed,
+                Err(e) => return ControlFlow::Break(e),
+            }
+        }
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<IndustryDbError> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<IndustryDbError> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        let rejected = match expr {
+            Expr::Identifier(ident) => {
+                (!self.permits(None, &ident.value)).then(|| denied(&ident.value))
+            }
+            Expr::CompoundIdentifier(parts) if parts.len() >= 2 => {
+                let qualifier = &parts[parts.len() - 2].value;
+                let column = &parts[parts.len() - 1].value;
+                (!self.permits(Some(qualifier), column))
+                    .then(|| denied(&format!("{}.{}", qualifier, column)))
+            }
+            // `r.*` passed to a function, e.g. row_to_json(r.*), reads
+            // every column of the table
+            Expr::QualifiedWildcard(name) => self.hidden_wildcard(name),
+            Expr::Function(function) => match &function.args {
+                FunctionArguments::List(list) => list.args.iter().find_map(|arg| match arg {
+                    FunctionArg::Named { arg, .. } | FunctionArg::Unnamed(arg) => match arg {
+                        FunctionArgExpr::QualifiedWildcard(name) => self.hidden_wildcard(name),
+                        _ => None,
+                    },
+                }),
+                _ => None,
+            },
+            _ => None,
+        };
+        match rejected {
+            Some(e) => ControlFlow::Break(e),
+            None => ControlFlow::Continue(()),
+        }
+    }
+}
+
+fn denied(column: &str) -> IndustryDbError {
+    IndustryDbError::access_denied(format!(
+        "column '{}' is not readable under the connection policy",
+        column
+    ))
+}
+
+/// Constraint of a join, if it has one
+fn join_constraint(operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match operator {
+        JoinOperator::Inner(c)
+        | JoinOperator::LeftOuter(c)
+        | JoinOperator::RightOuter(c)
+        | JoinOperator::FullOuter(c)
+        | JoinOperator::LeftSemi(c)
+        | JoinOperator::RightSemi(c)
+        | JoinOperator::LeftAnti(c)
+        | JoinOperator::RightAnti(c)
+        | JoinOperator::AsOf { constraint: c, .. } => Some(c),
+        JoinOperator::CrossJoin | JoinOperator::CrossApply | JoinOperator::OuterApply => None,
+    }
+}
+
+/// Output column aliases of a SELECT, which ORDER BY may name
+fn aliases(select: &Select) -> Vec<String> {
+    select
+        .projection
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::ExprWithAlias { alias, .. } => Some(alias.value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Expand a wildcard over one table into its permitted columns
+fn expand_table(table: &ScopeTable<'_>, qualify: bool) -> Vec<SelectItem> {
+    match table.allowed {
+        Some(columns) => columns
+            .iter()
+            .map(|col| {
+                let expr = if qualify {
+                    Expr::CompoundIdentifier(vec![
+                        Ident::new(table.qualifier.clone()),
+                        Ident::new(col.clone()),
+                    ])
+                } else {
+                    Expr::Identifier(Ident::new(col.clone()))
+                };
+                SelectItem::UnnamedExpr(expr)
+            })
+            .collect(),
+        None => vec![SelectItem::QualifiedWildcard(
+            ObjectName(vec![Ident::new(table.qualifier.clone())]),
+            Default::default(),
+        )],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AccessPolicy {
+        AccessPolicy::new(HashMap::from([(
+            "readings".to_string(),
+            vec!["ts".to_string(), "value".to_string()],
+        )]))
+    }
+
+    #[test]
+    fn test_wildcard_is_rewritten() {
+        let sql = policy()
+            .enforce(
+                "SELECT * FROM readings WHERE ts > 0",
+                DatabaseType::Postgres,
+            )
+            .unwrap();
+        assert_eq!(sql, "SELECT ts, value FROM readings WHERE ts > 0");
+    }
+
+    #[test]
+    fn test_restricted_column_is_denied() {
+        let err = policy()
+            .enforce("SELECT ts, operator FROM readings", DatabaseType::Sqlite)
+            .unwrap_err();
+        assert!(matches!(err, IndustryDbError::AccessDenied(_)));
+    }
+
+    #[test]
+    fn test_unrestricted_tables_pass_through() {
+        let sql = "SELECT * FROM tags";
+        let out = policy().enforce(sql, DatabaseType::Postgres).unwrap();
+        assert!(matches!(out, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_join_qualifies_columns() {
+        let sql = policy()
+            .enforce(
+                "SELECT * FROM readings r JOIN tags t ON r.ts = t.ts",
+                DatabaseType::Postgres,
+            )
+            .unwrap();
+        assert!(sql.starts_with("SELECT r.ts, r.value, t.* FROM"));
+    }
+
+    fn denies(sql: &str) -> bool {
+        let policy = AccessPolicy::new(HashMap::from([
+            (
+                "readings".to_string(),
+                vec!["ts".to_string(), "value".to_string()],
+            ),
+            ("secrets".to_string(), vec!["id".to_string()]),
+        ]));
+        matches!(
+            policy.enforce(sql, DatabaseType::Postgres),
+            Err(IndustryDbError::AccessDenied(_))
+        )
+    }
+
+    #[test]
+    fn test_hidden_columns_outside_projection_are_denied() {
+        assert!(denies("SELECT ts FROM readings WHERE operator = 'x'"));
+        assert!(denies("SELECT ts FROM readings GROUP BY operator"));
+        assert!(denies(
+            "SELECT ts FROM readings GROUP BY ts HAVING MAX(operator) > 'a'"
+        ));
+        assert!(denies("SELECT ts FROM readings ORDER BY operator"));
+        assert!(denies(
+            "SELECT t.name FROM tags t JOIN readings r ON r.operator = t.name"
+        ));
+        assert!(denies(
+            "SELECT t.name FROM tags t JOIN readings r USING (operator)"
+        ));
+        assert!(denies("DELETE FROM readings WHERE operator = 'x'"));
+        assert!(!denies(
+            "SELECT ts, value AS v FROM readings WHERE value > 1 ORDER BY v, ts"
+        ));
+        assert!(denies(
+            "SELECT value AS v FROM readings ORDER BY operator + 1"
+        ));
+    }
+
+    #[test]
+    fn test_subqueries_use_their_own_scope() {
+        assert!(denies("SELECT (SELECT secret FROM secrets) FROM tags"));
+        assert!(denies(
+            "SELECT name FROM tags WHERE name IN (SELECT s.secret FROM secrets s)"
+        ));
+        assert!(denies(
+            "SELECT name FROM tags WHERE EXISTS (SELECT 1 FROM secrets WHERE secret > 100)"
+        ));
+        assert!(denies("SELECT x FROM (SELECT secret AS x FROM secrets) d"));
+        assert!(denies(
+            "WITH s AS (SELECT secret FROM secrets) SELECT * FROM s"
+        ));
+        assert!(denies("INSERT INTO copy SELECT secret FROM secrets"));
+        // Correlated references reach the outer restricted table
+        assert!(denies(
+            "SELECT ts FROM readings WHERE EXISTS (SELECT 1 FROM tags t WHERE t.name = operator)"
+        ));
+        assert!(!denies("SELECT (SELECT MAX(id) FROM secrets) FROM tags"));
+        assert!(!denies(
+            "SELECT name FROM tags WHERE name IN (SELECT CAST(id AS TEXT) FROM secrets)"
+        ));
+    }
+
+    #[test]
+    fn test_ambiguous_unqualified_columns_are_denied() {
+        // `secret` may resolve to the restricted table's hidden column
+        assert!(denies("SELECT secret FROM tags, secrets"));
+        assert!(denies(
+            "SELECT name FROM tags JOIN secrets ON secret = name"
+        ));
+        // Permitted by every restricted table in scope
+        assert!(!denies(
+            "SELECT id, t.name FROM tags t JOIN secrets ON id = t.tag_id"
+        ));
+        // Only restricted tables: it must be a permitted column of one
+        assert!(!denies("SELECT ts, id FROM readings, secrets"));
+        assert!(denies("SELECT ts, name FROM readings, secrets"));
+    }
+
+    #[test]
+    fn test_wildcard_arguments_are_denied() {
+        assert!(denies("SELECT row_to_json(r.*) FROM readings r"));
+        assert!(denies("SELECT to_jsonb(readings.*) FROM readings"));
+        assert!(!denies("SELECT row_to_json(t.*) FROM tags t"));
+        assert!(!denies("SELECT COUNT(*) FROM readings"));
+    }
+
+    #[test]
+    fn test_returning_is_checked() {
+        assert!(denies("UPDATE readings SET value = value RETURNING *"));
+        assert!(denies(
+            "DELETE FROM readings WHERE ts < 0 RETURNING readings.*"
+        ));
+        assert!(denies(
+            "INSERT INTO readings (ts, value) VALUES (1, 2) RETURNING *"
+        ));
+        assert!(denies(
+            "INSERT INTO readings (ts, value) VALUES (1, 2) RETURNING operator"
+        ));
+        assert!(!denies("UPDATE readings SET value = 0 RETURNING ts, value"));
+        assert!(!denies("INSERT INTO tags (name) VALUES ('a') RETURNING *"));
+    }
+}
@@ -0,0 +1,337 @@
+//! Stored procedure calls
+//!
+//! Arguments and results of [`DatabaseConnector::call_procedure`]. Each
+//! argument is an input, an output or both; outputs come back by name next
+//! to the result sets the procedure returned and its return value.
+//!
+//! [`DatabaseConnector::call_procedure`]: crate::traits::DatabaseConnector::call_procedure
+
+use polars::prelude::*;
+
+use crate::config::DatabaseType;
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+
+/// Direction of a procedure argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamMode {
+    /// Passed to the procedure
+    In,
+    /// Set by the procedure
+    Out,
+    /// Passed to the procedure and set by it
+    InOut,
+}
+
+/// One argument of a procedure call
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureArg {
+    /// Parameter name, without a leading `@`; positional when `None`
+    pub name: Option<String>,
+    /// Value passed in; ignored for [`ParamMode::Out`]
+    pub value: Value,
+    /// Direction of the argument
+    pub mode: ParamMode,
+    /// SQL type of the parameter, e.g. `INT` or `numeric(10, 2)`
+    ///
+    /// SQL Server declares output variables with it (`NVARCHAR(4000)` when
+    /// `None`); PostgreSQL and function calls cast the argument to it, which
+    /// picks the right overload where the bound type (`bigint`, `text`, ...)
+    /// would not.
+    pub sql_type: Option<String>,
+}
+
+impl ProcedureArg {
+    /// Positional input
+    pub fn input(value: impl Into<Value>) -> Self {
+        Self {
+            name: None,
+            value: value.into(),
+            mode: ParamMode::In,
+            sql_type: None,
+        }
+    }
+
+    /// Named input
+    pub fn named(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::input(value)
+        }
+    }
+
+    /// Named output
+    pub fn output(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            value: Value::Null,
+            mode: ParamMode::Out,
+            sql_type: None,
+        }
+    }
+
+    /// Named argument passed in and set by the procedure
+    pub fn in_out(name: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self {
+            name: Some(name.into()),
+            value: value.into(),
+            mode: ParamMode::InOut,
+            sql_type: None,
+        }
+    }
+
+    /// Set the SQL type of the parameter
+    pub fn with_type(mut self, sql_type: impl Into<String>) -> Self {
+        self.sql_type = Some(sql_type.into());
+        self
+    }
+
+    /// Whether the procedure sets this argument
+    pub fn is_output(&self) -> bool {
+        self.mode != ParamMode::In
+    }
+}
+
+/// What a procedure call returned
+#[derive(Debug, Clone, Default)]
+pub struct ProcedureResult {
+    /// Result sets, in the order the procedure produced them
+    pub result_sets: Vec<DataFrame>,
+    /// Output parameters by name, in argument order
+    pub outputs: Vec<(String, Value)>,
+    /// Value of the procedure's RETURN statement (SQL Server) or of a
+    /// scalar function (PostgreSQL)
+    pub return_value: Option<Value>,
+}
+
+impl ProcedureResult {
+    /// Output parameter by name, compared case-insensitively
+    pub fn output(&self, name: &str) -> Option<&Value> {
+        self.outputs
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v)
+    }
+}
+
+/// Words that may follow the first word of a type name, as in `double
+/// precision`, `character varying` or `timestamp with time zone`
+const TYPE_WORDS: &[&str] = &[
+    "precision",
+    "varying",
+    "character",
+    "char",
+    "with",
+    "without",
+    "time",
+    "zone",
+    "unsigned",
+];
+
+/// Whether `ty` is a plain SQL type name
+///
+/// That is one identifier, optionally followed by words of [`TYPE_WORDS`],
+/// with an optional `(p)` or `(p, s)` of digits or `max`, as in
+/// `numeric(10, 2)`, `nvarchar(max)` or `timestamp(3) with time zone`.
+/// Anything else could smuggle a statement into `DECLARE` or `CAST`.
+pub fn is_type_name(ty: &str) -> bool {
+    let ty = ty.trim();
+    let (name, args, suffix) = match ty.split_once('(') {
+        Some((name, rest)) => match rest.split_once(')') {
+            Some((args, suffix)) => (name, Some(args), suffix),
+            None => return false,
+        },
+        None => (ty, None, ""),
+    };
+    let mut words = name.split_whitespace();
+    let first = words.next().is_some_and(|word| {
+        word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    let keyword = |word: &str| TYPE_WORDS.iter().any(|k| k.eq_ignore_ascii_case(word));
+    let args = args.map_or(true, |args| {
+        let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        parts.len() <= 2
+            && parts.iter().all(|part| {
+                part.eq_ignore_ascii_case("max")
+                    || (!part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+            })
+    });
+    first && words.all(keyword) && suffix.split_whitespace().all(keyword) && args
+}
+
+/// Check the names and types of procedure arguments
+///
+/// Names and types end up in the SQL text, so names are limited to
+/// identifier characters and types to a type name, see [`is_type_name`].
+/// Outputs must be named so they can be returned by name.
+pub fn validate_args(args: &[ProcedureArg]) -> Result<()> {
+    for (index, arg) in args.iter().enumerate() {
+        match &arg.name {
+            Some(name) => {
+                let bare = name.strip_prefix('@').unwrap_or(name);
+                if bare.is_empty() || !bare.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(IndustryDbError::invalid_parameter(format!(
+                        "Invalid procedure parameter name '{}'",
+                        name
+                    )));
+                }
+            }
+            None if arg.is_output() => {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Output argument {} needs a parameter name",
+                    index + 1
+                )));
+            }
+            None => {}
+        }
+        if let Some(ty) = &arg.sql_type {
+            if !is_type_name(ty) {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Invalid SQL type '{}' for a procedure argument",
+                    ty
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `CAST(expr AS type)` when the argument has a SQL type, else `expr`
+pub fn typed(expr: String, arg: &ProcedureArg) -> String {
+    match &arg.sql_type {
+        Some(ty) => format!("CAST({} AS {})", expr, ty),
+        None => expr,
+    }
+}
+
+/// `SELECT * FROM name(...)` calling a function with its input arguments,
+/// and the values bound to its placeholders
+///
+/// Output arguments are left out, as functions return them as columns.
+/// Named arguments (`name => value`) are PostgreSQL syntax.
+pub fn function_call_sql(
+    dialect: &dyn Dialect,
+    name: &str,
+    args: &[ProcedureArg],
+) -> Result<(String, Vec<Value>)> {
+    validate_args(args)?;
+    let mut params = Vec::new();
+    let mut passed = Vec::new();
+    for arg in args.iter().filter(|arg| arg.mode != ParamMode::Out) {
+        params.push(arg.value.clone());
+        let value = typed(dialect.placeholder(params.len()), arg);
+        passed.push(match &arg.name {
+            Some(name) if dialect.db_type() == DatabaseType::Postgres => {
+                format!("{} => {}", dialect.quote_identifier(name), value)
+            }
+            Some(name) => {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Named function argument '{}' is only supported by PostgreSQL",
+                    name
+                )))
+            }
+            None => value,
+        });
+    }
+    let sql = format!(
+        "SELECT * FROM {}({})",
+        dialect.identifier(name)?,
+        passed.join(", ")
+    );
+    Ok((sql, params))
+}
+
+/// `(column, value)` pairs of the first row of `df`, empty without rows
+pub fn first_row(df: &DataFrame) -> Result<Vec<(String, Value)>> {
+    if df.height() == 0 {
+        return Ok(Vec::new());
+    }
+    df.get_columns()
+        .iter()
+        .map(|c| Ok((c.name().to_string(), Value::from(c.get(0)?))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_args() {
+        let args = vec![
+            ProcedureArg::input(3),
+            ProcedureArg::named("@line", "L1"),
+            ProcedureArg::output("total").with_type("numeric(10, 2)"),
+        ];
+        assert!(validate_args(&args).is_ok());
+
+        let unnamed = ProcedureArg {
+            name: None,
+            ..ProcedureArg::output("x")
+        };
+        assert!(validate_args(&[unnamed]).is_err());
+        assert!(validate_args(&[ProcedureArg::named("a; DROP", 1)]).is_err());
+        assert!(validate_args(&[ProcedureArg::output("x").with_type("INT; DROP")]).is_err());
+
+        for ty in [
+            "int",
+            "nvarchar(max)",
+            "double precision",
+            "character varying(20)",
+            "timestamp(3) with time zone",
+        ] {
+            assert!(is_type_name(ty), "{}", ty);
+        }
+        for ty in [
+            "",
+            "INT EXEC dbo.drop_all",
+            "INT EXEC drop_all",
+            "int), pg_sleep(10",
+            "int) AS x, pg_sleep(10) AS (int",
+            "numeric(10, 2, 3)",
+            "varchar(n)",
+            "varchar(10",
+            "1int",
+        ] {
+            assert!(!is_type_name(ty), "{}", ty);
+            let arg = ProcedureArg::output("x").with_type(ty);
+            assert!(validate_args(&[arg]).is_err(), "{}", ty);
+        }
+    }
+
+    #[test]
+    fn test_function_call_sql() {
+        use crate::dialect::{PostgresDialect, SqliteDialect};
+
+        let args = vec![
+            ProcedureArg::input("2024-03-01").with_type("date"),
+            ProcedureArg::output("total"),
+            ProcedureArg::named("line", "L1"),
+        ];
+        let (sql, params) = function_call_sql(&PostgresDialect, "plant.shifts", &args).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM \"plant\".\"shifts\"(CAST($1 AS date), \"line\" => $2)"
+        );
+        assert_eq!(params, vec![Value::from("2024-03-01"), Value::from("L1")]);
+        assert!(function_call_sql(&SqliteDialect, "f", &args).is_err());
+
+        let (sql, _) =
+            function_call_sql(&SqliteDialect, "json_each", &[ProcedureArg::input("[1]")]).unwrap();
+        assert_eq!(sql, "SELECT * FROM \"json_each\"(?1)");
+    }
+
+    #[test]
+    fn test_output_lookup() {
+        let df = df!("Total" => [7i64], "note" => ["ok"]).unwrap();
+        let result = ProcedureResult {
+            outputs: first_row(&df).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(result.output("total"), Some(&Value::Int(7)));
+        assert_eq!(result.output("missing"), None);
+        assert!(first_row(&DataFrame::empty()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+//! Data quality checks
+//!
+//! A list of [`Check`]s describes what a table, query result or ingested
+//! batch must look like (at most so many nulls, values within bounds,
+//! timestamps in order, unique keys) and [`run_checks`] reports how each
+//! fared. Checks are plain data, so they can be kept in a pipeline or job
+//! file:
+//!
+//! ```toml
+//! [[validation.checks]]
+//! check = "null_rate"
+//! column = "value"
+//! max_rate = 0.01
+//!
+//! [[validation.checks]]
+//! check = "duplicate_keys"
+//! columns = ["ts", "tag"]
+//! ```
+
+use polars::prelude::{col, lit, BooleanChunked, DataFrame, DataType, IntoLazy};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IndustryDbError, Result};
+use crate::traits::DatabaseConnector;
+
+/// One declarative check on a frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum Check {
+    /// At most `max_rate` of the values of `column` are null
+    NullRate {
+        column: String,
+        #[serde(default)]
+        max_rate: f64,
+    },
+    /// Values of `column` lie within inclusive bounds; nulls pass and NaN
+    /// fails
+    Range {
+        column: String,
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    /// Timestamps in `column` never go back from one row to the next,
+    /// within each group of the `by` columns; with `strict` they never
+    /// repeat either
+    MonotonicTimestamps {
+        column: String,
+        #[serde(default)]
+        strict: bool,
+        #[serde(default)]
+        by: Vec<String>,
+    },
+    /// No two rows share the values of `columns`
+    DuplicateKeys { columns: Vec<String> },
+}
+
+impl Check {
+    /// Name of the kind of check, as written in configs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Check::NullRate { .. } => "null_rate",
+            Check::Range { .. } => "range",
+            Check::MonotonicTimestamps { .. } => "monotonic_timestamps",
+            Check::DuplicateKeys { .. } => "duplicate_keys",
+        }
+    }
+
+    /// Columns the check looks at
+    pub fn columns(&self) -> Vec<String> {
+        match self {
+            Check::NullRate { column, .. } | Check::Range { column, .. } => vec![column.clone()],
+            Check::MonotonicTimestamps { column, by, .. } => {
+                let mut columns = vec![column.clone()];
+                columns.extend(by.iter().cloned());
+                columns
+            }
+            Check::DuplicateKeys { columns } => columns.clone(),
+        }
+    }
+
+    /// Reject settings no frame could pass
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Check::NullRate { column, max_rate } if !(0.0..=1.0).contains(max_rate) => {
+                Err(IndustryDbError::config_error(format!(
+                    "Null rate limit of {} must be between 0 and 1",
+                    column
+                )))
+            }
+            Check::Range {
+                column,
+                min: Some(min),
+                max: Some(max),
+            } if min > max => Err(IndustryDbError::config_error(format!(
+                "Range of {} has min above max",
+                column
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Why the check cannot run on `df`, if it cannot
+    fn unusable(&self, df: &DataFrame) -> Option<String> {
+        let columns = self.columns();
+        if columns.is_empty() {
+            return Some("no key columns".to_string());
+        }
+        columns
+            .into_iter()
+            .find(|c| df.get_column_index(c).is_none())
+            .map(|c| format!("column '{}' is missing", c))
+    }
+
+    /// Rows failing the check
+    fn failing_rows(&self, df: &DataFrame) -> Result<u64> {
+        let failing = match self {
+            Check::NullRate { column, .. } => df.column(column)?.null_count(),
+            Check::Range { column, min, max } => {
+                let values = df.column(column)?.cast(&DataType::Float64)?;
+                let outside: BooleanChunked = values
+                    .f64()?
+                    .into_iter()
+                    .map(|v| {
+                        v.is_some_and(|v| {
+                            v.is_nan()
+                                || min.is_some_and(|min| v < min)
+                                || max.is_some_and(|max| v > max)
+                        })
+                    })
+                    .collect();
+                outside.sum().unwrap_or(0) as usize
+            }
+            Check::MonotonicTimestamps { column, strict, by } => {
+                let previous = col(column.as_str()).shift(lit(1));
+                let previous = match by.as_slice() {
+                    [] => previous,
+                    by => previous.over(by.iter().map(|c| col(c.as_str())).collect::<Vec<_>>()),
+                };
+                let back = if *strict {
+                    col(column.as_str()).lt_eq(previous)
+                } else {
+                    col(column.as_str()).lt(previous)
+                };
+                let back = df.clone().lazy().select([back.alias("back")]).collect()?;
+                back.column("back")?.bool()?.sum().unwrap_or(0) as usize
+            }
+            Check::DuplicateKeys { columns } => {
+                let keys = df.select(columns.iter().map(String::as_str))?;
+                keys.is_duplicated()?.sum().unwrap_or(0) as usize
+            }
+        };
+        Ok(failing as u64)
+    }
+
+    fn passes(&self, failing: u64, rows: u64) -> bool {
+        match self {
+            Check::NullRate { max_rate, .. } => rate(failing, rows) <= *max_rate,
+            _ => failing == 0,
+        }
+    }
+}
+
+/// Share of `rows` that failed, 0 without rows
+fn rate(failing: u64, rows: u64) -> f64 {
+    match rows {
+        0 => 0.0,
+        rows => failing as f64 / rows as f64,
+    }
+}
+
+/// How one [`Check`] fared
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// Kind of check, see [`Check::name`]
+    pub check: String,
+    /// Columns the check looked at
+    pub columns: Vec<String>,
+    /// Whether the check passed
+    pub passed: bool,
+    /// Rows failing the check: null values, values out of range,
+    /// timestamps going back, or rows sharing a key
+    pub failing_rows: u64,
+    /// `failing_rows` as a share of all rows
+    pub failing_rate: f64,
+    /// Why the check could not run, e.g. a missing column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Results of a list of checks on one frame
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualityReport {
+    /// Rows checked
+    pub rows: u64,
+    /// One result per check, in the order given
+    pub results: Vec<CheckResult>,
+}
+
+impl QualityReport {
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    /// Results of the checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results.iter().filter(|r| !r.passed)
+    }
+}
+
+/// Run `checks` on a frame
+///
+/// A check on a column the frame does not have fails, with the reason in
+/// its `error`.
+pub fn run_checks(df: &DataFrame, checks: &[Check]) -> Result<QualityReport> {
+    for check in checks {
+        check.validate()?;
+    }
+    let rows = df.height() as u64;
+    let results = checks
+        .iter()
+        .map(|check| {
+            let (failing_rows, error) = match check.unusable(df) {
+                Some(error) => (0, Some(error)),
+                None => (check.failing_rows(df)?, None),
+            };
+            Ok(CheckResult {
+                check: check.name().to_string(),
+                columns: check.columns(),
+                passed: error.is_none() && check.passes(failing_rows, rows),
+                failing_rows,
+                failing_rate: rate(failing_rows, rows),
+                error,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(QualityReport { rows, results })
+}
+
+/// Run `checks` on a table or the result of a query
+///
+/// `source` is a table name or a `SELECT` statement; its rows are fetched
+/// and checked in memory, in the order the database returns them, so
+/// timestamp order checks on a query need its `ORDER BY`.
+pub async fn check_source<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    source: &str,
+    checks: &[Check],
+) -> Result<QualityReport> {
+    let sql = if source.split_whitespace().nth(1).is_some() {
+        source.to_string()
+    } else {
+        format!("SELECT * FROM {}", conn.dialect().identifier(source)?)
+    };
+    run_checks(&conn.execute(&sql).await?, checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::df;
+
+    #[test]
+    fn test_run_checks() {
+        let df = df!(
+            "ts" => [1i64, 2, 2, 1, 3],
+            "tag" => ["A", "A", "B", "B", "A"],
+            "value" => [Some(1.0), None, Some(250.0), Some(f64::NAN), Some(3.0)],
+        )
+        .unwrap();
+        let checks: Vec<Check> = serde_json::from_str(
+            r#"[
+                {"check": "null_rate", "column": "value", "max_rate": 0.25},
+                {"check": "null_rate", "column": "value"},
+                {"check": "range", "column": "value", "min": 0, "max": 100},
+                {"check": "monotonic_timestamps", "column": "ts"},
+                {"check": "monotonic_timestamps", "column": "ts", "by": ["tag"]},
+                {"check": "monotonic_timestamps", "column": "ts", "strict": true},
+                {"check": "duplicate_keys", "columns": ["ts", "tag"]},
+                {"check": "duplicate_keys", "columns": ["ts"]},
+                {"check": "null_rate", "column": "missing"}
+            ]"#,
+        )
+        .unwrap();
+
+        let report = run_checks(&df, &checks).unwrap();
+        assert_eq!(report.rows, 5);
+        let outcome: Vec<(bool, u64)> = report
+            .results
+            .iter()
+            .map(|r| (r.passed, r.failing_rows))
+            .collect();
+        assert_eq!(
+            outcome,
+            [
+                (true, 1),
+                (false, 1),
+                (false, 2),
+                (false, 1),
+                (false, 1),
+                (false, 2),
+                (true, 0),
+                (false, 4),
+                (false, 0),
+            ]
+        );
+        assert!((report.results[0].failing_rate - 0.2).abs() < 1e-9);
+        assert!(report.results[8].error.is_some());
+        assert!(!report.passed());
+        assert_eq!(report.failures().count(), 7);
+
+        let inverted = Check::Range {
+            column: "value".to_string(),
+            min: Some(1.0),
+            max: Some(0.0),
+        };
+        assert!(run_checks(&df, &[inverted]).is_err());
+    }
+}
@@ -0,0 +1,836 @@
+//! Fluent query builder
+//!
+//! Builds common SELECTs, including simple joins, without writing SQL.
+//! Identifiers are validated and quoted, values are bound as parameters, and
+//! the statement is rendered in the connection's dialect:
+//!
+//! ```ignore
+//! let df = Query::select("readings")
+//!     .columns(["ts", "value"])
+//!     .filter(col("tag").eq("TI-101").and(col("ts").gt("2024-01-01")))
+//!     .order_by_desc("ts")
+//!     .limit(100)
+//!     .fetch(&conn)
+//!     .await?;
+//! ```
+
+use polars::prelude::DataFrame;
+
+use crate::dialect::{Dialect, NullsOrder, SelectOptions};
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::traits::DatabaseConnector;
+
+/// Comparison operator of a [`Expr::Compare`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// `=`
+    Eq,
+    /// `<>`
+    NotEq,
+    /// `>`
+    Gt,
+    /// `>=`
+    GtEq,
+    /// `<`
+    Lt,
+    /// `<=`
+    LtEq,
+    /// `LIKE`
+    Like,
+}
+
+impl CompareOp {
+    fn as_sql(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::NotEq => "<>",
+            CompareOp::Gt => ">",
+            CompareOp::GtEq => ">=",
+            CompareOp::Lt => "<",
+            CompareOp::LtEq => "<=",
+            CompareOp::Like => "LIKE",
+        }
+    }
+}
+
+/// A filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Column reference
+    Column(String),
+    /// Bound parameter
+    Param(Value),
+    /// Binary comparison
+    Compare {
+        left: Box<Expr>,
+        op: CompareOp,
+        right: Box<Expr>,
+    },
+    /// Both conditions hold
+    And(Box<Expr>, Box<Expr>),
+    /// Either condition holds
+    Or(Box<Expr>, Box<Expr>),
+    /// Negated condition
+    Not(Box<Expr>),
+    /// `IS NULL`
+    IsNull(Box<Expr>),
+    /// `IS NOT NULL`
+    IsNotNull(Box<Expr>),
+    /// `IN (...)`; an empty list matches nothing
+    InList(Box<Expr>, Vec<Expr>),
+    /// `BETWEEN low AND high` (inclusive)
+    Between(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// Reference a column
+pub fn col(name: &str) -> Expr {
+    Expr::Column(name.to_string())
+}
+
+/// Bind a value as a parameter
+pub fn param(value: impl Into<Value>) -> Expr {
+    Expr::Param(value.into())
+}
+
+impl From<Value> for Expr {
+    fn from(value: Value) -> Self {
+        Expr::Param(value)
+    }
+}
+
+macro_rules! param_from {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Expr {
+                fn from(value: $ty) -> Self {
+                    Expr::Param(value.into())
+                }
+            }
+        )*
+    };
+}
+
+param_from!(bool, i32, i64, f64, &str, String, Vec<u8>);
+
+impl Expr {
+    fn compare(self, op: CompareOp, other: impl Into<Expr>) -> Expr {
+        Expr::Compare {
+            left: Box::new(self),
+            op,
+            right: Box::new(other.into()),
+        }
+    }
+
+    /// `self = other`
+    #[allow(clippy::should_implement_trait)]
+    pub fn eq(self, other: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::Eq, other)
+    }
+
+    /// `self <> other`
+    pub fn not_eq(self, other: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::NotEq, other)
+    }
+
+    /// `self > other`
+    pub fn gt(self, other: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::Gt, other)
+    }
+
+    /// `self >= other`
+    pub fn gt_eq(self, other: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::GtEq, other)
+    }
+
+    /// `self < other`
+    pub fn lt(self, other: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::Lt, other)
+    }
+
+    /// `self <= other`
+    pub fn lt_eq(self, other: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::LtEq, other)
+    }
+
+    /// `self LIKE pattern`
+    pub fn like(self, pattern: impl Into<Expr>) -> Expr {
+        self.compare(CompareOp::Like, pattern)
+    }
+
+    /// `self AND other`
+    pub fn and(self, other: Expr) -> Expr {
+        Expr::And(Box::new(self), Box::new(other))
+    }
+
+    /// `self OR other`
+    pub fn or(self, other: Expr) -> Expr {
+        Expr::Or(Box::new(self), Box::new(other))
+    }
+
+    /// `NOT self`
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+
+    /// `self IS NULL`
+    pub fn is_null(self) -> Expr {
+        Expr::IsNull(Box::new(self))
+    }
+
+    /// `self IS NOT NULL`
+    pub fn is_not_null(self) -> Expr {
+        Expr::IsNotNull(Box::new(self))
+    }
+
+    /// `self IN (values...)`
+    pub fn is_in<I, V>(self, values: I) -> Expr
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Expr>,
+    {
+        Expr::InList(Box::new(self), values.into_iter().map(Into::into).collect())
+    }
+
+    /// `self BETWEEN low AND high`
+    pub fn between(self, low: impl Into<Expr>, high: impl Into<Expr>) -> Expr {
+        Expr::Between(Box::new(self), Box::new(low.into()), Box::new(high.into()))
+    }
+}
+
+/// Renders expressions, collecting bound values in placeholder order
+struct Renderer<'a> {
+    dialect: &'a dyn Dialect,
+    params: Vec<Value>,
+}
+
+impl Renderer<'_> {
+    fn expr(&mut self, expr: &Expr) -> Result<String> {
+        Ok(match expr {
+            Expr::Column(name) => self.dialect.identifier(name)?,
+            Expr::Param(value) => {
+                self.params.push(value.clone());
+                self.dialect.placeholder(self.params.len())
+            }
+            Expr::Compare { left, op, right } => {
+                format!("{} {} {}", self.expr(left)?, op.as_sql(), self.expr(right)?)
+            }
+            Expr::And(a, b) => format!("({} AND {})", self.expr(a)?, self.expr(b)?),
+            Expr::Or(a, b) => format!("({} OR {})", self.expr(a)?, self.expr(b)?),
+            Expr::Not(e) => format!("NOT ({})", self.expr(e)?),
+            Expr::IsNull(e) => format!("{} IS NULL", self.expr(e)?),
+            Expr::IsNotNull(e) => format!("{} IS NOT NULL", self.expr(e)?),
+            Expr::InList(_, values) if values.is_empty() => "1 = 0".to_string(),
+            Expr::InList(e, values) => {
+                let left = self.expr(e)?;
+                let values = values
+                    .iter()
+                    .map(|v| self.expr(v))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("{} IN ({})", left, values.join(", "))
+            }
+            Expr::Between(e, low, high) => format!(
+                "{} BETWEEN {} AND {}",
+                self.expr(e)?,
+                self.expr(low)?,
+                self.expr(high)?
+            ),
+        })
+    }
+}
+
+/// A column to sort by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBy {
+    /// Column name
+    pub column: String,
+    /// Sort descending instead of ascending
+    pub descending: bool,
+    /// Where NULLs sort; the database default when `None`
+    pub nulls: Option<NullsOrder>,
+    /// Collation to compare with; the column's own when `None`
+    pub collation: Option<String>,
+}
+
+impl OrderBy {
+    /// Sort ascending by `column`
+    pub fn asc(column: &str) -> Self {
+        Self {
+            column: column.to_string(),
+            descending: false,
+            nulls: None,
+            collation: None,
+        }
+    }
+
+    /// Sort descending by `column`
+    pub fn desc(column: &str) -> Self {
+        Self {
+            descending: true,
+            ..Self::asc(column)
+        }
+    }
+
+    /// Sort NULLs before all other values
+    pub fn nulls_first(mut self) -> Self {
+        self.nulls = Some(NullsOrder::First);
+        self
+    }
+
+    /// Sort NULLs after all other values
+    pub fn nulls_last(mut self) -> Self {
+        self.nulls = Some(NullsOrder::Last);
+        self
+    }
+
+    /// Compare with the collation `name`
+    pub fn collate(mut self, name: &str) -> Self {
+        self.collation = Some(name.to_string());
+        self
+    }
+}
+
+/// Render an ORDER BY clause body in `dialect`
+pub fn order_by_sql(orders: &[OrderBy], dialect: &dyn Dialect) -> Result<String> {
+    let terms = orders
+        .iter()
+        .map(|o| {
+            dialect.order_term(
+                &dialect.identifier(&o.column)?,
+                o.descending,
+                o.nulls,
+                o.collation.as_deref(),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(terms.join(", "))
+}
+
+/// Aggregate function allowed in a select list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// `COUNT`
+    Count,
+    /// `SUM`
+    Sum,
+    /// `AVG`
+    Avg,
+    /// `MIN`
+    Min,
+    /// `MAX`
+    Max,
+}
+
+impl Aggregate {
+    pub(crate) fn as_sql(self) -> &'static str {
+        match self {
+            Aggregate::Count => "COUNT",
+            Aggregate::Sum => "SUM",
+            Aggregate::Avg => "AVG",
+            Aggregate::Min => "MIN",
+            Aggregate::Max => "MAX",
+        }
+    }
+}
+
+impl std::str::FromStr for Aggregate {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "count" => Ok(Aggregate::Count),
+            "sum" => Ok(Aggregate::Sum),
+            "avg" | "mean" => Ok(Aggregate::Avg),
+            "min" => Ok(Aggregate::Min),
+            "max" => Ok(Aggregate::Max),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported aggregate: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Render one select-list entry
+///
+/// Accepts a column name, `func(column)`, `func(DISTINCT column)` or
+/// `count(*)` for the functions of [`Aggregate`], each optionally followed by
+/// `AS alias`. Names are validated and quoted; anything else is rejected.
+fn select_item_sql(spec: &str, dialect: &dyn Dialect) -> Result<String> {
+    let invalid = || {
+        IndustryDbError::invalid_parameter(format!(
+            "Unsupported select column '{}': expected a column name or \
+             count/sum/avg/min/max(column), optionally with AS alias",
+            spec
+        ))
+    };
+
+    let lower = spec.to_ascii_lowercase();
+    let (expr, alias) = match lower.rfind(" as ") {
+        Some(at) => (spec[..at].trim(), Some(spec[at + 4..].trim())),
+        None => (spec.trim(), None),
+    };
+
+    let rendered = match expr.split_once('(') {
+        Some((func, rest)) => {
+            let func: Aggregate = func.trim().parse().map_err(|_| invalid())?;
+            let arg = rest.strip_suffix(')').ok_or_else(invalid)?.trim();
+            let (distinct, arg) = match arg.get(..9) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("distinct ") => (true, arg[9..].trim()),
+                _ => (false, arg),
+            };
+            let arg = match arg {
+                "*" if func == Aggregate::Count && !distinct => "*".to_string(),
+                "*" => return Err(invalid()),
+                _ if arg.contains(['(', ')']) => return Err(invalid()),
+                _ => dialect.identifier(arg)?,
+            };
+            format!(
+                "{}({}{})",
+                func.as_sql(),
+                if distinct { "DISTINCT " } else { "" },
+                arg
+            )
+        }
+        None if expr.contains(')') => return Err(invalid()),
+        None => dialect.identifier(expr)?,
+    };
+
+    Ok(match alias {
+        Some(alias) => format!("{} AS {}", rendered, dialect.identifier(alias)?),
+        None => rendered,
+    })
+}
+
+/// Render a select list of column names and aggregates (see
+/// [`CrudOperations::select`](crate::traits::CrudOperations::select))
+pub fn select_list_sql(columns: &[String], dialect: &dyn Dialect) -> Result<String> {
+    let items = columns
+        .iter()
+        .map(|c| select_item_sql(c, dialect))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(items.join(", "))
+}
+
+/// How a joined table is combined with the rows so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoinKind {
+    /// Only rows with a match on both sides
+    #[default]
+    Inner,
+    /// Every row of the left side, NULLs where the right has no match
+    Left,
+    /// Every row of the right side, NULLs where the left has no match
+    Right,
+    /// Every row of both sides
+    Full,
+}
+
+impl JoinKind {
+    fn as_sql(self) -> &'static str {
+        match self {
+            JoinKind::Inner => "INNER JOIN",
+            JoinKind::Left => "LEFT JOIN",
+            JoinKind::Right => "RIGHT JOIN",
+            JoinKind::Full => "FULL OUTER JOIN",
+        }
+    }
+}
+
+impl std::str::FromStr for JoinKind {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "inner" => Ok(JoinKind::Inner),
+            "left" => Ok(JoinKind::Left),
+            "right" => Ok(JoinKind::Right),
+            "full" | "outer" => Ok(JoinKind::Full),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported join type: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// A table joined to the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    /// Join type
+    pub kind: JoinKind,
+    /// Joined table
+    pub table: String,
+    /// Join condition
+    pub on: Expr,
+}
+
+/// A SELECT on one table and the tables joined to it
+///
+/// With joins, qualify column names with their table, e.g.
+/// `col("readings.tag_id").eq(col("tags.id"))`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    table: String,
+    joins: Vec<Join>,
+    columns: Vec<String>,
+    distinct: bool,
+    filter: Option<Expr>,
+    group_by: Vec<String>,
+    order_by: Vec<OrderBy>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+impl Query {
+    /// Select all columns of `table`
+    pub fn select(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            joins: Vec::new(),
+            columns: Vec::new(),
+            distinct: false,
+            filter: None,
+            group_by: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Select only these columns
+    ///
+    /// Entries may also be aggregates such as `"avg(value) as avg_v"`; see
+    /// [`Query::aggregate`].
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.columns = columns
+            .into_iter()
+            .map(|c| c.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Add `func(column) AS alias` to the selected columns
+    ///
+    /// A `column` of `"*"` counts rows.
+    pub fn aggregate(mut self, func: Aggregate, column: &str, alias: &str) -> Self {
+        self.columns
+            .push(format!("{}({}) as {}", func.as_sql(), column, alias));
+        self
+    }
+
+    /// Add `COUNT(*) AS alias` to the selected columns
+    pub fn count_all(self, alias: &str) -> Self {
+        self.aggregate(Aggregate::Count, "*", alias)
+    }
+
+    /// Drop duplicate rows
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    /// Group by these columns
+    pub fn group_by<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.group_by = columns
+            .into_iter()
+            .map(|c| c.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Join `table` on a condition
+    pub fn join(mut self, kind: JoinKind, table: &str, on: Expr) -> Self {
+        self.joins.push(Join {
+            kind,
+            table: table.to_string(),
+            on,
+        });
+        self
+    }
+
+    /// Inner join `table` on a condition
+    pub fn inner_join(self, table: &str, on: Expr) -> Self {
+        self.join(JoinKind::Inner, table, on)
+    }
+
+    /// Left join `table` on a condition
+    pub fn left_join(self, table: &str, on: Expr) -> Self {
+        self.join(JoinKind::Left, table, on)
+    }
+
+    /// Add a filter; repeated filters are combined with AND
+    pub fn filter(mut self, expr: Expr) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(existing) => existing.and(expr),
+            None => expr,
+        });
+        self
+    }
+
+    /// Sort ascending by a column, after any earlier sort columns
+    pub fn order_by(self, column: &str) -> Self {
+        self.order_by_with(OrderBy::asc(column))
+    }
+
+    /// Sort descending by a column, after any earlier sort columns
+    pub fn order_by_desc(self, column: &str) -> Self {
+        self.order_by_with(OrderBy::desc(column))
+    }
+
+    /// Sort as described by `order`, after any earlier sort columns
+    ///
+    /// ```ignore
+    /// query.order_by_with(OrderBy::desc("ts").nulls_last().collate("C"))
+    /// ```
+    pub fn order_by_with(mut self, order: OrderBy) -> Self {
+        self.order_by.push(order);
+        self
+    }
+
+    /// Return at most `n` rows
+    pub fn limit(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Skip the first `n` rows
+    pub fn offset(mut self, n: usize) -> Self {
+        self.offset = Some(n);
+        self
+    }
+
+    /// Render the statement and its parameters in `dialect`
+    pub fn render(&self, dialect: &dyn Dialect) -> Result<(String, Vec<Value>)> {
+        let mut renderer = Renderer {
+            dialect,
+            params: Vec::new(),
+        };
+
+        let columns = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            select_list_sql(&self.columns, dialect)?
+        };
+        let mut from = dialect.identifier(&self.table)?;
+        for join in &self.joins {
+            from.push_str(&format!(
+                " {} {} ON {}",
+                join.kind.as_sql(),
+                dialect.identifier(&join.table)?,
+                renderer.expr(&join.on)?
+            ));
+        }
+        let filter = self.filter.as_ref().map(|f| renderer.expr(f)).transpose()?;
+        let options = SelectOptions {
+            distinct: self.distinct,
+            group_by: (!self.group_by.is_empty())
+                .then(|| dialect.identifiers(&self.group_by).map(|g| g.join(", ")))
+                .transpose()?,
+            order_by: (!self.order_by.is_empty())
+                .then(|| order_by_sql(&self.order_by, dialect))
+                .transpose()?,
+            limit: self.limit,
+            offset: self.offset,
+            ..Default::default()
+        };
+
+        let sql = dialect.select_sql(&columns, &from, filter.as_deref(), &options);
+        Ok((sql, renderer.params))
+    }
+
+    /// Run the query on a connection
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<DataFrame> {
+        let (sql, params) = self.render(conn.dialect())?;
+        conn.execute_with_params(&sql, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect, SqliteDialect};
+
+    #[test]
+    fn test_render_postgres() {
+        let query = Query::select("readings")
+            .columns(["ts", "value"])
+            .filter(col("tag").eq("TI-101"))
+            .filter(col("ts").gt("2024-01-01").or(col("value").is_null()))
+            .order_by_desc("ts")
+            .limit(10);
+
+        let (sql, params) = query.render(&PostgresDialect).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT \"ts\", \"value\" FROM \"readings\" \
+             WHERE (\"tag\" = $1 AND (\"ts\" > $2 OR \"value\" IS NULL)) \
+             ORDER BY \"ts\" DESC LIMIT 10"
+        );
+        assert_eq!(
+            params,
+            vec![Value::from("TI-101"), Value::from("2024-01-01")]
+        );
+    }
+
+    #[test]
+    fn test_render_per_dialect() {
+        let query = Query::select("readings")
+            .filter(col("value").between(1, 5))
+            .limit(3);
+
+        let (sql, _) = query.render(&SqliteDialect).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM \"readings\" WHERE \"value\" BETWEEN ?1 AND ?2 LIMIT 3"
+        );
+
+        let (sql, params) = query.render(&MssqlDialect).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT TOP (3) * FROM [readings] WHERE [value] BETWEEN @P1 AND @P2"
+        );
+        assert_eq!(params, vec![Value::Int(1), Value::Int(5)]);
+
+        let (sql, _) = Query::select("readings")
+            .order_by("ts")
+            .limit(50)
+            .offset(100)
+            .render(&MssqlDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM [readings] ORDER BY [ts] ASC OFFSET 100 ROWS FETCH NEXT 50 ROWS ONLY"
+        );
+    }
+
+    #[test]
+    fn test_render_null_order_and_collation() {
+        let query = Query::select("tags")
+            .order_by_with(OrderBy::asc("name").collate("NOCASE"))
+            .order_by_with(OrderBy::desc("updated").nulls_last());
+
+        let (sql, _) = query.render(&SqliteDialect).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM \"tags\" ORDER BY \"name\" COLLATE \"NOCASE\" ASC, \
+             \"updated\" DESC NULLS LAST"
+        );
+
+        let (sql, _) = Query::select("tags")
+            .order_by_with(OrderBy::desc("updated").nulls_last())
+            .render(&MssqlDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM [tags] ORDER BY \
+             CASE WHEN [updated] IS NULL THEN 1 ELSE 0 END, [updated] DESC"
+        );
+    }
+
+    #[test]
+    fn test_select_list_aggregates() {
+        let columns = [
+            "tag_id".to_string(),
+            "avg(value) as avg_v".to_string(),
+            "COUNT(*) AS n".to_string(),
+            "count(distinct unit)".to_string(),
+            "tag name".to_string(),
+        ];
+        assert_eq!(
+            select_list_sql(&columns, &MssqlDialect).unwrap(),
+            "[tag_id], AVG([value]) AS [avg_v], COUNT(*) AS [n], COUNT(DISTINCT [unit]), [tag name]"
+        );
+
+        for bad in [
+            "lower(name)",
+            "sum(*)",
+            "max(value) ) or (1",
+            "avg(sum(value))",
+            "value)",
+        ] {
+            assert!(
+                select_list_sql(&[bad.to_string()], &PostgresDialect).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_grouped_aggregates() {
+        let (sql, _) = Query::select("readings")
+            .columns(["tag_id"])
+            .aggregate(Aggregate::Max, "value", "peak")
+            .count_all("n")
+            .group_by(["tag_id"])
+            .order_by("tag_id")
+            .render(&PostgresDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT \"tag_id\", MAX(\"value\") AS \"peak\", COUNT(*) AS \"n\" \
+             FROM \"readings\" GROUP BY \"tag_id\" ORDER BY \"tag_id\" ASC"
+        );
+
+        let (sql, _) = Query::select("readings")
+            .columns(["tag_id"])
+            .distinct()
+            .render(&SqliteDialect)
+            .unwrap();
+        assert_eq!(sql, "SELECT DISTINCT \"tag_id\" FROM \"readings\"");
+    }
+
+    #[test]
+    fn test_render_joins() {
+        let (sql, params) = Query::select("readings")
+            .columns(["readings.ts", "tags.name", "readings.value"])
+            .inner_join("tags", col("readings.tag_id").eq(col("tags.id")))
+            .join(
+                JoinKind::Left,
+                "units",
+                col("tags.unit_id")
+                    .eq(col("units.id"))
+                    .and(col("units.system").eq("SI")),
+            )
+            .filter(col("tags.area").eq("boiler"))
+            .render(&PostgresDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT \"readings\".\"ts\", \"tags\".\"name\", \"readings\".\"value\" \
+             FROM \"readings\" \
+             INNER JOIN \"tags\" ON \"readings\".\"tag_id\" = \"tags\".\"id\" \
+             LEFT JOIN \"units\" ON (\"tags\".\"unit_id\" = \"units\".\"id\" AND \"units\".\"system\" = $1) \
+             WHERE \"tags\".\"area\" = $2"
+        );
+        assert_eq!(params, vec![Value::from("SI"), Value::from("boiler")]);
+    }
+
+    #[test]
+    fn test_in_list_and_identifier_validation() {
+        let (sql, params) = Query::select("t")
+            .filter(col("state").is_in(["run", "idle"]).not())
+            .render(&PostgresDialect)
+            .unwrap();
+        assert_eq!(sql, "SELECT * FROM \"t\" WHERE NOT (\"state\" IN ($1, $2))");
+        assert_eq!(params.len(), 2);
+
+        let (sql, _) = Query::select("t")
+            .filter(col("state").is_in(Vec::<Value>::new()))
+            .render(&PostgresDialect)
+            .unwrap();
+        assert!(sql.ends_with("WHERE 1 = 0"));
+
+        assert!(Query::select("bad\nname").render(&PostgresDialect).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+//! Replay of recorded data
+//!
+//! Re-inserts historical rows into a target table with their original
+//! spacing in time, optionally sped up, so dashboards and alerts can be
+//! exercised against a realistic stream without touching production
+//! equipment. Rows sharing a timestamp are inserted together as one batch.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::backfill::BackfillControl;
+use crate::error::{IndustryDbError, Result};
+use crate::time::{format_timestamp, parse_timestamp};
+use crate::traits::{CrudOperations, DatabaseConnector};
+
+/// Pause/resume/cancel handle for a running replay
+pub type ReplayControl = BackfillControl;
+
+/// Where replayed rows go and how fast
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    /// Table to insert into
+    pub target_table: String,
+    /// Column holding the recorded time of each row
+    pub time_column: String,
+    /// Playback speed; `2.0` replays twice as fast, `f64::INFINITY` without waiting
+    pub speed: f64,
+    /// Restamp rows with the (UTC) time they are replayed instead of the recorded time
+    pub rebase_timestamps: bool,
+}
+
+impl ReplayConfig {
+    /// Create a config replaying at the original speed with recorded timestamps
+    pub fn new(target_table: &str, time_column: &str) -> Self {
+        Self {
+            target_table: target_table.to_string(),
+            time_column: time_column.to_string(),
+            speed: 1.0,
+            rebase_timestamps: false,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.speed.is_nan() || self.speed <= 0.0 {
+            return Err(IndustryDbError::invalid_parameter(
+                "Replay speed must be positive",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Progress of a replay, reported after every batch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayProgress {
+    /// Rows inserted so far
+    pub rows_replayed: usize,
+    /// Rows in the recording
+    pub rows_total: usize,
+    /// Batches inserted so far
+    pub batches: usize,
+    /// Recorded time of the last inserted batch
+    pub recorded_time: String,
+    /// Whether the whole recording has been replayed
+    pub complete: bool,
+}
+
+/// Read a recording from a table, optionally limited to `start..end`
+pub async fn load_table<S: DatabaseConnector + ?Sized>(
+    source: &S,
+    table: &str,
+    time_column: &str,
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Result<DataFrame> {
+    let dialect = source.dialect();
+    let table = dialect.identifier(table)?;
+    let time_column = dialect.identifier(time_column)?;
+
+    let mut conditions = Vec::new();
+    if let Some(start) = start {
+        let start = format_timestamp(&parse_timestamp(start)?);
+        conditions.push(format!("{} >= '{}'", time_column, start));
+    }
+    if let Some(end) = end {
+        let end = format_timestamp(&parse_timestamp(end)?);
+        conditions.push(format!("{} < '{}'", time_column, end));
+    }
+
+    let mut sql = format!("SELECT * FROM {}", table);
+    if !conditions.is_empty() {
+        sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    sql.push_str(&format!(" ORDER BY {}", time_column));
+    source.execute(&sql).await
+}
+
+/// Read a recording from a Parquet file
+pub fn load_parquet(path: &Path) -> Result<DataFrame> {
+    let file = std::fs::File::open(path)?;
+    Ok(ParquetReader::new(file).finish()?)
+}
+
+/// Insert `data` into `target` with the spacing of its time column
+///
+/// The first batch is inserted immediately; each later batch waits until
+/// its recorded offset from the first, divided by `speed`, has elapsed.
+/// Time spent paused does not count. Returns the final progress;
+/// `complete` is false when cancelled.
+pub async fn replay<T: CrudOperations + ?Sized>(
+    target: &T,
+    data: DataFrame,
+    config: &ReplayConfig,
+    control: &ReplayControl,
+    on_progress: Option<&(dyn Fn(&ReplayProgress) + Send + Sync)>,
+) -> Result<ReplayProgress> {
+    config.validate()?;
+
+    let data = data.sort(
+        [config.time_column.as_str()],
+        SortMultipleOptions::default(),
+    )?;
+    let times = timestamps_ms(data.column(&config.time_column)?)?;
+    let mut progress = ReplayProgress {
+        rows_total: data.height(),
+        ..Default::default()
+    };
+    let Some(&first) = times.first() else {
+        progress.complete = true;
+        return Ok(progress);
+    };
+
+    let started_ms = Utc::now().timestamp_millis();
+    let mut origin = Instant::now();
+    let mut offset = 0;
+
+    while offset < times.len() {
+        let recorded = times[offset];
+        let due = offset_ms(recorded - first, config.speed);
+
+        loop {
+            if control.is_cancelled() {
+                return Ok(progress);
+            }
+            if control.is_paused() {
+                let paused_at = Instant::now();
+                while control.is_paused() && !control.is_cancelled() {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                origin += paused_at.elapsed();
+                continue;
+            }
+            match Duration::from_millis(due as u64).checked_sub(origin.elapsed()) {
+                // Sleep in short steps so pause and cancel take effect promptly
+                Some(wait) if !wait.is_zero() => {
+                    tokio::time::sleep(wait.min(Duration::from_millis(100))).await
+                }
+                _ => break,
+            }
+        }
+
+        let len = batch_len(&times, offset);
+        let mut batch = data.slice(offset as i64, len);
+        if config.rebase_timestamps {
+            let shift = started_ms + due - recorded;
+            let column = batch.column(&config.time_column)?.as_materialized_series();
+            let shifted = shift_column(column, shift)?;
+            batch.with_column(shifted)?;
+        }
+
+        let rows = target.insert(&config.target_table, batch).await?;
+        offset += len;
+        progress.rows_replayed += rows;
+        progress.batches += 1;
+        progress.recorded_time = DateTime::from_timestamp_millis(recorded)
+            .map(|t| format_timestamp(&t.naive_utc()))
+            .unwrap_or_default();
+        progress.complete = offset >= times.len();
+
+        if let Some(callback) = on_progress {
+            callback(&progress);
+        }
+    }
+
+    Ok(progress)
+}
+
+/// Wall-clock offset in milliseconds of a recorded offset at `speed`
+fn offset_ms(recorded_offset: i64, speed: f64) -> i64 {
+    (recorded_offset as f64 / speed) as i64
+}
+
+/// Number of rows from `offset` sharing its timestamp
+fn batch_len(times: &[i64], offset: usize) -> usize {
+    times[offset..]
+        .iter()
+        .take_while(|&&t| t == times[offset])
+        .count()
+}
+
+/// Milliseconds since the epoch for each value of a time column
+///
+/// Accepts Date, Datetime, timestamp strings and integers (taken as epoch
+/// milliseconds). Nulls are rejected as they cannot be placed in time.
+fn timestamps_ms(column: &Column) -> Result<Vec<i64>> {
+    let series = column.as_materialized_series();
+    let values: Vec<Option<i64>> = match series.dtype() {
+        DataType::String => series
+            .str()?
+            .into_iter()
+            .map(|v| {
+                v.map(|s| parse_timestamp(s).map(|t| t.and_utc().timestamp_millis()))
+                    .transpose()
+            })
+            .collect::<Result<_>>()?,
+        DataType::Date | DataType::Datetime(_, _) => series
+            .cast(&millis_dtype(series.dtype()))?
+            .cast(&DataType::Int64)?
+            .i64()?
+            .into_iter()
+            .collect(),
+        dtype if dtype.is_integer() => series.cast(&DataType::Int64)?.i64()?.into_iter().collect(),
+        other => {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Cannot replay on time column '{}' of type {}",
+                series.name(),
+                other
+            )))
+        }
+    };
+
+    values
+        .into_iter()
+        .map(|v| {
+            v.ok_or_else(|| {
+                IndustryDbError::invalid_parameter(format!(
+                    "Time column '{}' contains nulls",
+                    series.name()
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Millisecond Datetime type keeping the time zone of `dtype`
+fn millis_dtype(dtype: &DataType) -> DataType {
+    match dtype {
+        DataType::Datetime(_, tz) => DataType::Datetime(TimeUnit::Milliseconds, tz.clone()),
+        _ => DataType::Datetime(TimeUnit::Milliseconds, None),
+    }
+}
+
+/// Shift every value of a time column by `shift_ms`, keeping its dtype
+fn shift_column(series: &Series, shift_ms: i64) -> Result<Series> {
+    let dtype = series.dtype().clone();
+    match &dtype {
+        DataType::String => {
+            let shift = TimeDelta::milliseconds(shift_ms);
+            let shifted = series
+                .str()?
+                .into_iter()
+                .map(|v| {
+                    v.map(|s| parse_timestamp(s).map(|t| format_timestamp(&(t + shift))))
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Series::new(series.name().clone(), shifted))
+        }
+        DataType::Date | DataType::Datetime(_, _) => {
+            let millis = millis_dtype(&dtype);
+            let shifted = &series.cast(&millis)?.cast(&DataType::Int64)? + shift_ms;
+            Ok(shifted.cast(&millis)?.cast(&dtype)?)
+        }
+        _ => {
+            let shifted = &series.cast(&DataType::Int64)? + shift_ms;
+            Ok(shifted.cast(&dtype)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_bad_speed() {
+        let mut config = ReplayConfig::new("readings", "ts");
+        config.speed = 0.0;
+        assert!(config.validate().is_err());
+        config.speed = f64::NAN;
+        assert!(config.validate().is_err());
+        config.speed = f64::INFINITY;
+        assert!(config.validate().is_ok());
+        assert_eq!(offset_ms(60_000, config.speed), 0);
+    }
+
+    #[test]
+    fn test_batches_group_equal_timestamps() {
+        let times = [0, 0, 1000, 2000, 2000, 2000];
+        assert_eq!(batch_len(&times, 0), 2);
+        assert_eq!(batch_len(&times, 2), 1);
+        assert_eq!(batch_len(&times, 3), 3);
+    }
+
+    #[test]
+    fn test_timestamps_from_strings_and_integers() {
+        let strings = Column::new(
+            "ts".into(),
+            ["2024-01-01 00:00:00", "2024-01-01 00:00:01.500"],
+        );
+        let ms = timestamps_ms(&strings).unwrap();
+        assert_eq!(ms[1] - ms[0], 1500);
+
+        let ints = Column::new("ts".into(), [10i32, 20]);
+        assert_eq!(timestamps_ms(&ints).unwrap(), vec![10, 20]);
+
+        let nulls = Column::new("ts".into(), [Some(1i64), None]);
+        assert!(timestamps_ms(&nulls).is_err());
+    }
+
+    #[test]
+    fn test_shift_keeps_dtype() {
+        let strings = Series::new("ts".into(), ["2024-01-01 00:00:00"]);
+        let shifted = shift_column(&strings, 90_000).unwrap();
+        assert_eq!(shifted.str().unwrap().get(0), Some("2024-01-01 00:01:30"));
+
+        let datetimes = Series::new("ts".into(), [0i64, 1_000])
+            .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
+            .unwrap();
+        let shifted = shift_column(&datetimes, 1_000).unwrap();
+        assert_eq!(shifted.dtype(), datetimes.dtype());
+        assert_eq!(
+            shifted
+                .cast(&DataType::Int64)
+                .unwrap()
+                .i64()
+                .unwrap()
+                .get(0),
+            Some(1_000_000)
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Retention windows for time-series tables
+//!
+//! Removes the rows of a table older than a retention window. On
+//! PostgreSQL, when the table is range-partitioned on its timestamp, the
+//! partitions lying wholly before the cutoff are detached and dropped,
+//! which frees their space at once. The remaining expired rows are deleted
+//! in batches, each its own statement, so no single transaction locks or
+//! logs the whole range.
+//!
+//! ```ignore
+//! let policy = RetentionPolicy::new("readings", "ts", parse_interval("90d")?);
+//! let summary = policy.enforce(&conn, policy.cutoff(Utc::now().naive_utc())).await?;
+//! ```
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DatabaseType;
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::time::{format_timestamp, parse_timestamp};
+use crate::traits::DatabaseConnector;
+
+/// Rows deleted per statement unless configured otherwise
+pub const DEFAULT_RETENTION_BATCH: usize = 10_000;
+
+/// How long the rows of one table are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    table: String,
+    ts_column: String,
+    keep: TimeDelta,
+    batch_size: usize,
+    detach_only: bool,
+}
+
+/// What enforcing a [`RetentionPolicy`] removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionSummary {
+    /// Table the policy applies to
+    pub table: String,
+    /// Rows with an earlier timestamp were removed
+    pub cutoff: String,
+    /// Rows deleted by statement, not counting dropped partitions
+    pub rows_deleted: u64,
+    /// Partitions detached and dropped
+    pub partitions_dropped: Vec<String>,
+    /// Partitions detached and kept as standalone tables
+    pub partitions_detached: Vec<String>,
+}
+
+impl RetentionPolicy {
+    /// Keep the rows of `table` whose `ts_column` is within `keep` of now
+    pub fn new(table: &str, ts_column: &str, keep: TimeDelta) -> Self {
+        Self {
+            table: table.to_string(),
+            ts_column: ts_column.to_string(),
+            keep,
+            batch_size: DEFAULT_RETENTION_BATCH,
+            detach_only: false,
+        }
+    }
+
+    /// Delete at most this many rows per statement
+    pub fn batch_size(mut self, rows: usize) -> Self {
+        self.batch_size = rows;
+        self
+    }
+
+    /// Keep expired PostgreSQL partitions as standalone tables instead of
+    /// dropping them, to archive or drop them later
+    pub fn detach_only(mut self, enabled: bool) -> Self {
+        self.detach_only = enabled;
+        self
+    }
+
+    /// Table the policy applies to
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Oldest timestamp kept at `now`
+    pub fn cutoff(&self, now: NaiveDateTime) -> NaiveDateTime {
+        now - self.keep
+    }
+
+    /// Query for the rows older than `cutoff`, e.g. to archive them first
+    pub fn expired_sql(&self, dialect: &dyn Dialect, cutoff: NaiveDateTime) -> Result<String> {
+        Ok(format!(
+            "SELECT * FROM {} WHERE {} < '{}'",
+            dialect.identifier(&self.table)?,
+            dialect.identifier(&self.ts_column)?,
+            format_timestamp(&cutoff)
+        ))
+    }
+
+    /// Statement deleting one batch of rows older than the bound parameter
+    ///
+    /// SQLite deletes by `rowid`, so `WITHOUT ROWID` tables are not
+    /// supported there.
+    pub fn delete_sql(&self, dialect: &dyn Dialect) -> Result<String> {
+        if self.batch_size == 0 {
+            return Err(IndustryDbError::invalid_parameter(
+                "Retention batch size must be positive",
+            ));
+        }
+        let table = dialect.identifier(&self.table)?;
+        let ts = dialect.identifier(&self.ts_column)?;
+        let cutoff = dialect.placeholder(1);
+        let n = self.batch_size;
+        Ok(match dialect.db_type() {
+            // tableoid keeps ctids of different partitions apart
+            DatabaseType::Postgres => format!(
+                "DELETE FROM {table} WHERE (tableoid, ctid) IN \
+                 (SELECT tableoid, ctid FROM {table} WHERE {ts} < {cutoff} LIMIT {n})"
+            ),
+            DatabaseType::Sqlite => format!(
+                "DELETE FROM {table} WHERE rowid IN \
+                 (SELECT rowid FROM {table} WHERE {ts} < {cutoff} LIMIT {n})"
+            ),
+            DatabaseType::Mssql => format!("DELETE TOP ({n}) FROM {table} WHERE {ts} < {cutoff}"),
+        })
+    }
+
+    /// Remove the rows older than `cutoff`
+    ///
+    /// Batches are deleted until one comes back short. Each batch commits
+    /// on its own, so a failure leaves the batches before it deleted and
+    /// the policy can simply be enforced again.
+    pub async fn enforce<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        cutoff: NaiveDateTime,
+    ) -> Result<RetentionSummary> {
+        let sql = self.delete_sql(conn.dialect())?;
+        let mut summary = RetentionSummary {
+            table: self.table.clone(),
+            cutoff: format_timestamp(&cutoff),
+            ..Default::default()
+        };
+        if conn.dialect().db_type() == DatabaseType::Postgres {
+            self.drop_partitions(conn, cutoff, &mut summary).await?;
+        }
+
+        let params = [Value::Text(format_timestamp(&cutoff))];
+        loop {
+            let deleted = conn.execute_statement(&sql, &params).await?;
+            summary.rows_deleted += deleted;
+            if deleted < self.batch_size as u64 {
+                return Ok(summary);
+            }
+        }
+    }
+
+    /// Detach, and unless `detach_only` drop, the partitions whose range
+    /// ends at or before `cutoff`
+    ///
+    /// Only applies when the table is range-partitioned on the timestamp
+    /// column alone; otherwise nothing is done.
+    async fn drop_partitions<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        cutoff: NaiveDateTime,
+        summary: &mut RetentionSummary,
+    ) -> Result<()> {
+        let dialect = conn.dialect();
+        let parent = dialect.identifier(&self.table)?;
+        let regclass = [Value::Text(parent.clone())];
+        let key = conn
+            .fetch_scalar("SELECT pg_get_partkeydef($1::regclass)", &regclass)
+            .await?;
+        let Some(Value::Text(key)) = key else {
+            return Ok(());
+        };
+        let column = key
+            .strip_prefix("RANGE (")
+            .and_then(|key| key.strip_suffix(')'))
+            .map(|key| key.trim_matches('"').replace("\"\"", "\""));
+        if column.as_deref() != Some(self.ts_column.as_str()) {
+            return Ok(());
+        }
+
+        let partitions = conn
+            .execute_with_params(
+                "SELECT n.nspname AS schema_name, c.relname AS name, \
+                 pg_get_expr(c.relpartbound, c.oid) AS bound \
+                 FROM pg_inherits i \
+                 JOIN pg_class c ON c.oid = i.inhrelid \
+                 JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE i.inhparent = $1::regclass ORDER BY c.relname",
+                &regclass,
+            )
+            .await?;
+        if partitions.height() == 0 {
+            return Ok(());
+        }
+        let schemas = partitions.column("schema_name")?.str()?;
+        let names = partitions.column("name")?.str()?;
+        let bounds = partitions.column("bound")?.str()?;
+        for ((schema, name), bound) in schemas.into_iter().zip(names).zip(bounds) {
+            let (Some(schema), Some(name), Some(bound)) = (schema, name, bound) else {
+                continue;
+            };
+            if !upper_bound(bound).is_some_and(|upper| upper <= cutoff) {
+                continue;
+            }
+            let child = format!(
+                "{}.{}",
+                dialect.quote_identifier(schema),
+                dialect.quote_identifier(name)
+            );
+            conn.execute_statement(
+                &format!("ALTER TABLE {} DETACH PARTITION {}", parent, child),
+                &[],
+            )
+            .await?;
+            let qualified = format!("{}.{}", schema, name);
+            if self.detach_only {
+                summary.partitions_detached.push(qualified);
+            } else {
+                conn.execute_statement(&format!("DROP TABLE {}", child), &[])
+                    .await?;
+                summary.partitions_dropped.push(qualified);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Upper end of a single-column range partition bound such as
+/// `FOR VALUES FROM ('2024-01-01') TO ('2024-02-01')`
+///
+/// `None` for `MAXVALUE`, default partitions and multi-column bounds.
+/// Bounds of `timestamptz` partitions are compared in UTC.
+fn upper_bound(bound: &str) -> Option<NaiveDateTime> {
+    let (_, upper) = bound.split_once(" TO (")?;
+    let upper = upper
+        .strip_suffix(')')?
+        .strip_prefix('\'')?
+        .strip_suffix('\'')?;
+    parse_timestamp(upper).ok().or_else(|| {
+        DateTime::parse_from_str(upper, "%Y-%m-%d %H:%M:%S%.f%#z")
+            .ok()
+            .map(|ts| ts.naive_utc())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect, SqliteDialect};
+
+    #[test]
+    fn test_delete_sql() {
+        let policy = RetentionPolicy::new("readings", "ts", TimeDelta::days(90)).batch_size(500);
+        assert_eq!(
+            policy.delete_sql(&PostgresDialect).unwrap(),
+            "DELETE FROM \"readings\" WHERE (tableoid, ctid) IN \
+             (SELECT tableoid, ctid FROM \"readings\" WHERE \"ts\" < $1 LIMIT 500)"
+        );
+        assert_eq!(
+            policy.delete_sql(&SqliteDialect).unwrap(),
+            "DELETE FROM \"readings\" WHERE rowid IN \
+             (SELECT rowid FROM \"readings\" WHERE \"ts\" < ?1 LIMIT 500)"
+        );
+        assert_eq!(
+            policy.delete_sql(&MssqlDialect).unwrap(),
+            "DELETE TOP (500) FROM [readings] WHERE [ts] < @P1"
+        );
+        assert!(policy.batch_size(0).delete_sql(&SqliteDialect).is_err());
+
+        let policy = RetentionPolicy::new("readings", "ts", TimeDelta::days(1));
+        let cutoff = policy.cutoff(parse_timestamp("2024-03-02").unwrap());
+        assert_eq!(
+            policy.expired_sql(&SqliteDialect, cutoff).unwrap(),
+            "SELECT * FROM \"readings\" WHERE \"ts\" < '2024-03-01 00:00:00'"
+        );
+    }
+
+    #[test]
+    fn test_partition_upper_bound() {
+        let at = |s: &str| parse_timestamp(s).unwrap();
+        assert_eq!(
+            upper_bound("FOR VALUES FROM ('2024-01-01 00:00:00') TO ('2024-02-01 00:00:00')"),
+            Some(at("2024-02-01"))
+        );
+        assert_eq!(
+            upper_bound("FOR VALUES FROM ('2024-01-01') TO ('2024-02-01')"),
+            Some(at("2024-02-01"))
+        );
+        assert_eq!(
+            upper_bound("FOR VALUES FROM ('2024-01-01 01:00:00+01') TO ('2024-02-01 01:00:00+01')"),
+            Some(at("2024-02-01"))
+        );
+        assert_eq!(
+            upper_bound("FOR VALUES FROM ('2024-01-01') TO (MAXVALUE)"),
+            None
+        );
+        assert_eq!(upper_bound("DEFAULT"), None);
+        assert_eq!(
+            upper_bound("FOR VALUES FROM ('2024-01-01', 1) TO ('2024-02-01', 1)"),
+            None
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! Schema introspection types and helpers
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IndustryDbError, Result};
+
+/// Column names of the DataFrame returned by `describe_table`
+pub const DESCRIBE_COLUMNS: [&str; 4] =
+    ["column_name", "data_type", "is_nullable", "column_default"];
+
+/// Column names of the DataFrame returned by `primary_keys`
+pub const PRIMARY_KEY_COLUMNS: [&str; 2] = ["column_name", "ordinal_position"];
+
+/// Column names of the DataFrame returned by `indexes`
+pub const INDEX_COLUMNS: [&str; 4] = ["index_name", "column_name", "is_unique", "ordinal_position"];
+
+/// Column names of the DataFrame returned by `foreign_keys`
+pub const FOREIGN_KEY_COLUMNS: [&str; 4] = [
+    "constraint_name",
+    "column_name",
+    "referenced_table",
+    "referenced_column",
+];
+
+/// Description of a single table column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    /// Column name
+    pub name: String,
+    /// Native database type name
+    pub data_type: String,
+    /// Whether the column accepts NULL
+    pub nullable: bool,
+    /// Default value expression, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Description of a table index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexInfo {
+    /// Index name
+    pub name: String,
+    /// Indexed columns in key order
+    pub columns: Vec<String>,
+    /// Whether the index enforces uniqueness
+    #[serde(default)]
+    pub unique: bool,
+}
+
+/// Cast a catalog query result to the expected column types
+///
+/// Connectors alias their catalog queries to the expected column names; the
+/// dtypes they come back with differ per driver (e.g. SQLite returns booleans
+/// as integers), so every column is cast here. A frame with no columns (no
+/// matching rows) becomes an empty frame with the expected schema.
+pub(crate) fn normalize_catalog(df: DataFrame, spec: &[(&str, DataType)]) -> Result<DataFrame> {
+    let columns = spec
+        .iter()
+        .map(|(name, dtype)| {
+            if df.width() == 0 {
+                Ok(Series::new_empty((*name).into(), dtype).into_column())
+            } else {
+                Ok(df.column(name)?.cast(dtype)?)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Normalize a raw `describe_table` result
+pub fn normalize_describe(df: DataFrame) -> Result<DataFrame> {
+    normalize_catalog(
+        df,
+        &[
+            (DESCRIBE_COLUMNS[0], DataType::String),
+            (DESCRIBE_COLUMNS[1], DataType::String),
+            (DESCRIBE_COLUMNS[2], DataType::Boolean),
+            (DESCRIBE_COLUMNS[3], DataType::String),
+        ],
+    )
+}
+
+/// Normalize a raw `primary_keys` result
+pub fn normalize_primary_keys(df: DataFrame) -> Result<DataFrame> {
+    normalize_catalog(
+        df,
+        &[
+            (PRIMARY_KEY_COLUMNS[0], DataType::String),
+            (PRIMARY_KEY_COLUMNS[1], DataType::Int32),
+        ],
+    )
+}
+
+/// Normalize a raw `indexes` result
+pub fn normalize_indexes(df: DataFrame) -> Result<DataFrame> {
+    normalize_catalog(
+        df,
+        &[
+            (INDEX_COLUMNS[0], DataType::String),
+            (INDEX_COLUMNS[1], DataType::String),
+            (INDEX_COLUMNS[2], DataType::Boolean),
+            (INDEX_COLUMNS[3], DataType::Int32),
+        ],
+    )
+}
+
+/// Normalize a raw `foreign_keys` result
+pub fn normalize_foreign_keys(df: DataFrame) -> Result<DataFrame> {
+    normalize_catalog(
+        df,
+        &[
+            (FOREIGN_KEY_COLUMNS[0], DataType::String),
+            (FOREIGN_KEY_COLUMNS[1], DataType::String),
+            (FOREIGN_KEY_COLUMNS[2], DataType::String),
+            (FOREIGN_KEY_COLUMNS[3], DataType::String),
+        ],
+    )
+}
+
+/// Convert a normalized describe result into [`ColumnInfo`] values
+pub fn column_infos(df: &DataFrame) -> Result<Vec<ColumnInfo>> {
+    if df.height() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let names = df.column("column_name")?.str()?;
+    let types = df.column("data_type")?.str()?;
+    let nullable = df.column("is_nullable")?.bool()?;
+    let defaults = df.column("column_default")?.str()?;
+
+    Ok((0..df.height())
+        .map(|i| ColumnInfo {
+            name: names.get(i).unwrap_or_default().to_string(),
+            data_type: types.get(i).unwrap_or_default().to_string(),
+            nullable: nullable.get(i).unwrap_or(true),
+            default: defaults.get(i).map(|s| s.to_string()),
+        })
+        .collect())
+}
+
+/// Group a normalized indexes result into [`IndexInfo`] values
+///
+/// Indexes are returned in order of first appearance, columns in key order.
+pub fn index_infos(df: &DataFrame) -> Result<Vec<IndexInfo>> {
+    if df.height() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let names = df.column("index_name")?.str()?;
+    let columns = df.column("column_name")?.str()?;
+    let unique = df.column("is_unique")?.bool()?;
+    let positions = df.column("ordinal_position")?.i32()?;
+
+    let mut indexes: Vec<(IndexInfo, Vec<i32>)> = Vec::new();
+    for i in 0..df.height() {
+        let name = names.get(i).unwrap_or_default();
+        let column = columns.get(i).unwrap_or_default().to_string();
+        let position = positions.get(i).unwrap_or(0);
+        match indexes.iter_mut().find(|(idx, _)| idx.name == name) {
+            Some((idx, order)) => {
+                idx.columns.push(column);
+                order.push(position);
+            }
+            None => indexes.push((
+                IndexInfo {
+                    name: name.to_string(),
+                    columns: vec![column],
+                    unique: unique.get(i).unwrap_or(false),
+                },
+                vec![position],
+            )),
+        }
+    }
+
+    Ok(indexes
+        .into_iter()
+        .map(|(mut idx, order)| {
+            let mut keyed: Vec<_> = order.into_iter().zip(idx.columns).collect();
+            keyed.sort_by_key(|(position, _)| *position);
+            idx.columns = keyed.into_iter().map(|(_, column)| column).collect();
+            idx
+        })
+        .collect())
+}
+
+/// Collect the first column of a catalog query as strings
+///
+/// Used by `list_tables` / `list_schemas` implementations.
+pub fn first_column_strings(df: &DataFrame) -> Result<Vec<String>> {
+    if df.width() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let column = df.get_columns()[0].cast(&DataType::String)?;
+    Ok(column
+        .str()?
+        .into_iter()
+        .flatten()
+        .map(|s| s.to_string())
+        .collect())
+}
+
+/// Read the first value of a catalog query as a boolean
+///
+/// Integer results (e.g. `COUNT(*)`) are true when non-zero; an empty result
+/// is false. Used by `table_exists` implementations.
+pub fn first_value_bool(df: &DataFrame) -> Result<bool> {
+    if df.width() == 0 || df.height() == 0 {
+        return Ok(false);
+    }
+
+    let column = df.get_columns()[0].cast(&DataType::Boolean)?;
+    Ok(column.bool()?.get(0).unwrap_or(false))
+}
+
+/// Parse a dtype name into a Polars [`DataType`]
+///
+/// Accepts short names (`i64`, `f64`, `str`, `bool`, `datetime[ms]`,
+/// `datetime[us, UTC]`, `decimal(18,4)`), SQL-ish names (`bigint`, `double`,
+/// `text`) and Polars' own repr (`Int64`, `String`,
+/// `Datetime(time_unit='us', time_zone=None)`). Matching is case-insensitive.
+pub fn parse_dtype(name: &str) -> Result<DataType> {
+    let lower = name.trim().to_lowercase();
+    let invalid = || IndustryDbError::invalid_parameter(format!("Unknown dtype: {}", name));
+
+    if let Some(args) = lower
+        .strip_prefix("datetime")
+        .map(|rest| rest.trim_matches(|c| c == '[' || c == ']' || c == '(' || c == ')'))
+    {
+        let mut unit = TimeUnit::Microseconds;
+        let mut tz = None;
+        for part in args.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let value = part
+                .split_once('=')
+                .map(|(_, v)| v)
+                .unwrap_or(part)
+                .trim_matches(|c| c == '\'' || c == '"');
+            match value {
+                "ms" => unit = TimeUnit::Milliseconds,
+                "us" | "μs" => unit = TimeUnit::Microseconds,
+                "ns" => unit = TimeUnit::Nanoseconds,
+                "none" => tz = None,
+                _ => {
+                    // Preserve the original casing of zone names like "Europe/Berlin"
+                    let start = name.to_lowercase().find(value).ok_or_else(invalid)?;
+                    tz = Some(name[start..start + value.len()].into());
+                }
+            }
+        }
+        return Ok(DataType::Datetime(unit, tz));
+    }
+
+    if let Some(args) = lower
+        .strip_prefix("decimal")
+        .or_else(|| lower.strip_prefix("numeric"))
+    {
+        let args = args.trim_matches(|c| c == '(' || c == ')' || c == '[' || c == ']');
+        if args.is_empty() {
+            return Ok(DataType::Decimal(None, None));
+        }
+        let mut parts = args.split(',').map(|p| {
+            let p = p.split_once('=').map(|(_, v)| v).unwrap_or(p);
+            p.trim().parse::<usize>().map_err(|_| invalid())
+        });
+        let precision = parts.next().transpose()?;
+        let scale = parts.next().transpose()?;
+        return Ok(DataType::Decimal(precision, scale));
+    }
+
+    Ok(match lower.as_str() {
+        "bool" | "boolean" => DataType::Boolean,
+        "i8" | "int8" | "tinyint" => DataType::Int8,
+        "i16" | "int16" | "smallint" => DataType::Int16,
+        "i32" | "int32" | "int" | "integer" => DataType::Int32,
+        "i64" | "int64" | "bigint" => DataType::Int64,
+        "u8" | "uint8" => DataType::UInt8,
+        "u16" | "uint16" => DataType::UInt16,
+        "u32" | "uint32" => DataType::UInt32,
+        "u64" | "uint64" => DataType::UInt64,
+        "f32" | "float32" | "real" => DataType::Float32,
+        "f64" | "float64" | "float" | "double" => DataType::Float64,
+        "str" | "string" | "utf8" | "text" => DataType::String,
+        "binary" | "bytes" | "blob" => DataType::Binary,
+        "date" => DataType::Date,
+        "time" => DataType::Time,
+        _ => return Err(invalid()),
+    })
+}
+
+/// Cast the columns of a query result to the dtypes they are expected to
+/// have, e.g. from [`parse_dtype`]
+///
+/// SQLite columns take the type of their values, so a `REAL` column holding
+/// whole numbers reads as integers and timestamps read as text; hints pin
+/// them down. Text is parsed into `Datetime` and `Date` columns with
+/// [`parse_timestamp`](crate::time::parse_timestamp). A value that does not
+/// fit the dtype is an error rather than a null. Columns without a hint are
+/// left alone, and an empty result without columns is returned as is.
+pub fn coerce_columns(mut df: DataFrame, dtypes: &[(String, DataType)]) -> Result<DataFrame> {
+    if df.width() == 0 {
+        return Ok(df);
+    }
+    for (name, dtype) in dtypes {
+        let column = df.column(name).map_err(|_| {
+            IndustryDbError::invalid_parameter(format!(
+                "Column '{}' in the schema hints is not in the result",
+                name
+            ))
+        })?;
+        if column.dtype() == dtype {
+            continue;
+        }
+        let series = column.as_materialized_series();
+        let coerced = match (series.dtype(), dtype) {
+            (DataType::String, DataType::Datetime(unit, _) | DataType::Date) => {
+                let parsed = series
+                    .str()?
+                    .into_iter()
+                    .map(|value| value.map(crate::time::parse_timestamp).transpose())
+                    .collect::<Result<Vec<_>>>()?;
+                let physical: Series = match dtype {
+                    DataType::Date => Series::new(
+                        name.as_str().into(),
+                        parsed
+                            .iter()
+                            .map(|ts| ts.map(|ts| (ts.and_utc().timestamp() / 86_400) as i32))
+                            .collect::<Vec<_>>(),
+                    ),
+                    _ => Series::new(
+                        name.as_str().into(),
+                        parsed
+                            .iter()
+                            .map(|ts| {
+                                ts.and_then(|ts| {
+                                    let ts = ts.and_utc();
+                                    match unit {
+                                        TimeUnit::Milliseconds => Some(ts.timestamp_millis()),
+                                        TimeUnit::Microseconds => Some(ts.timestamp_micros()),
+                                        TimeUnit::Nanoseconds => ts.timestamp_nanos_opt(),
+                                    }
+                                })
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                };
+                physical.cast(dtype)?
+            }
+            _ => series.strict_cast(dtype).map_err(|e| {
+                IndustryDbError::invalid_parameter(format!(
+                    "Cannot read column '{}' as {}: {}",
+                    name, dtype, e
+                ))
+            })?,
+        };
+        df.with_column(coerced)?;
+    }
+    Ok(df)
+}
+
+/// Split a possibly schema-qualified table name into `(schema, table)`
+pub fn split_qualified(table: &str) -> (Option<&str>, &str) {
+    match table.rsplit_once('.') {
+        Some((schema, name)) => (Some(schema), name),
+        None => (None, table),
+    }
+}
+
+/// Quote a string as a SQL literal, doubling embedded single quotes
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_qualified() {
+        assert_eq!(split_qualified("readings"), (None, "readings"));
+        assert_eq!(split_qualified("dbo.readings"), (Some("dbo"), "readings"));
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn test_first_value_bool() {
+        let df = DataFrame::new(vec![
+            Series::new("table_exists".into(), vec![1i64]).into_column()
+        ])
+        .unwrap();
+        assert!(first_value_bool(&df).unwrap());
+        assert!(!first_value_bool(&DataFrame::empty()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_dtype() {
+        assert_eq!(parse_dtype("i64").unwrap(), DataType::Int64);
+        assert_eq!(parse_dtype("Float64").unwrap(), DataType::Float64);
+        assert_eq!(
+            parse_dtype("datetime[ms]").unwrap(),
+            DataType::Datetime(TimeUnit::Milliseconds, None)
+        );
+        assert_eq!(
+            parse_dtype("Datetime(time_unit='us', time_zone='Europe/Berlin')").unwrap(),
+            DataType::Datetime(TimeUnit::Microseconds, Some("Europe/Berlin".into()))
+        );
+        assert_eq!(
+            parse_dtype("decimal(18,4)").unwrap(),
+            DataType::Decimal(Some(18), Some(4))
+        );
+        assert!(parse_dtype("widget").is_err());
+    }
+
+    #[test]
+    fn test_coerce_columns() {
+        let df = DataFrame::new(vec![
+            Series::new("value".into(), vec![Some(1i64), None]).into_column(),
+            Series::new("ts".into(), vec!["2024-03-01 08:00:00", "2024-03-01"]).into_column(),
+            Series::new("tag".into(), vec!["TI-101", "TI-102"]).into_column(),
+        ])
+        .unwrap();
+        let hints = [
+            ("value".to_string(), parse_dtype("f64").unwrap()),
+            ("ts".to_string(), parse_dtype("datetime[ms]").unwrap()),
+        ];
+        let coerced = coerce_columns(df.clone(), &hints).unwrap();
+        assert_eq!(coerced.column("value").unwrap().dtype(), &DataType::Float64);
+        let ts = coerced.column("ts").unwrap();
+        assert_eq!(
+            ts.dtype(),
+            &DataType::Datetime(TimeUnit::Milliseconds, None)
+        );
+        assert_eq!(
+            ts.to_physical_repr().i64().unwrap().get(1),
+            Some(1_709_251_200_000)
+        );
+        assert_eq!(coerced.column("tag").unwrap().dtype(), &DataType::String);
+
+        let bad = [("tag".to_string(), DataType::Int64)];
+        assert!(coerce_columns(df.clone(), &bad).is_err());
+        let missing = [("pressure".to_string(), DataType::Float64)];
+        assert!(coerce_columns(df, &missing).is_err());
+        assert!(coerce_columns(DataFrame::empty(), &missing).is_ok());
+    }
+
+    #[test]
+    fn test_normalize_describe() {
+        let df = DataFrame::new(vec![
+            Series::new("column_name".into(), vec!["id", "name"]).into_column(),
+            Series::new("data_type".into(), vec!["INTEGER", "TEXT"]).into_column(),
+            Series::new("is_nullable".into(), vec![0i64, 1]).into_column(),
+            Series::new("column_default".into(), vec![None::<i64>, None]).into_column(),
+        ])
+        .unwrap();
+
+        let df = normalize_describe(df).unwrap();
+        let infos = column_infos(&df).unwrap();
+        assert_eq!(infos.len(), 2);
+        assert!(!infos[0].nullable);
+        assert!(infos[1].nullable);
+        assert_eq!(infos[1].data_type, "TEXT");
+
+        let empty = normalize_describe(DataFrame::empty()).unwrap();
+        assert_eq!(empty.width(), 4);
+    }
+
+    #[test]
+    fn test_normalize_indexes_casts_flags() {
+        let df = DataFrame::new(vec![
+            Series::new("index_name".into(), vec!["idx_ts"]).into_column(),
+            Series::new("column_name".into(), vec!["ts"]).into_column(),
+            Series::new("is_unique".into(), vec![1i64]).into_column(),
+            Series::new("ordinal_position".into(), vec![1i64]).into_column(),
+        ])
+        .unwrap();
+
+        let df = normalize_indexes(df).unwrap();
+        assert_eq!(df.column("is_unique").unwrap().dtype(), &DataType::Boolean);
+        assert_eq!(
+            df.column("ordinal_position").unwrap().dtype(),
+            &DataType::Int32
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! Destinations query results are streamed into
+//!
+//! [`DatabaseConnector::execute_into`](crate::traits::DatabaseConnector::execute_into)
+//! hands a result to a [`FrameSink`] chunk by chunk as the rows are decoded,
+//! so writing a result to a file never holds more than a chunk of it.
+
+use polars::prelude::*;
+
+use crate::error::Result;
+
+/// Destination of a result streamed chunk by chunk
+pub trait FrameSink: Send {
+    /// Take the next chunk of the result
+    ///
+    /// Every chunk has the columns of the first, though a column's dtype
+    /// can differ between chunks, e.g. a SQLite column holding whole
+    /// numbers and then fractions. A result without rows may arrive as a
+    /// single chunk with its columns but no rows, or as no chunk at all.
+    fn write(&mut self, chunk: &mut DataFrame) -> Result<()>;
+}
+
+/// Collects every chunk, for tests and small results
+impl FrameSink for Vec<DataFrame> {
+    fn write(&mut self, chunk: &mut DataFrame) -> Result<()> {
+        self.push(std::mem::take(chunk));
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Local spill storage for batches the target database cannot take
+//!
+//! Edge gateways on plant networks lose their database for minutes at a
+//! time. Rather than dropping the rows or stalling the producer, a
+//! [`BufferedWriter`](crate::writer::BufferedWriter) can spill its batches
+//! into a table of a local SQLite file and replay them, oldest first, once
+//! the database is back. Each batch is stored as one compressed row, see
+//! [`BatchCodec`].
+
+use polars::prelude::*;
+
+use crate::codec::{BatchCodec, CodecConfig};
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::traits::CrudOperations;
+
+/// Default name of the table spilled batches are kept in
+pub const DEFAULT_SPILL_TABLE: &str = "industrydb_spill";
+
+/// Batches kept in a table of a local SQLite database, in arrival order
+///
+/// The table survives restarts, so batches spilled by one process are
+/// replayed by the next writer opened on the same file.
+pub struct SpillStore<'a> {
+    conn: &'a dyn CrudOperations,
+    table: String,
+    codec: BatchCodec,
+}
+
+impl<'a> SpillStore<'a> {
+    /// Open the spill table in `conn`, creating it if needed
+    ///
+    /// `conn` must be a SQLite connection, normally to a file of its own.
+    pub async fn open(
+        conn: &'a dyn CrudOperations,
+        table: &str,
+        codec: CodecConfig,
+    ) -> Result<Self> {
+        if conn.db_type() != "sqlite" {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Batches can only be spilled to SQLite, not {}",
+                conn.db_type()
+            )));
+        }
+        let table = conn.dialect().identifier(table)?;
+        conn.execute_statement(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (\
+                 seq INTEGER PRIMARY KEY AUTOINCREMENT, \
+                 rows INTEGER NOT NULL, \
+                 payload BLOB NOT NULL)",
+                table
+            ),
+            &[],
+        )
+        .await?;
+        Ok(Self {
+            conn,
+            table,
+            codec: BatchCodec::new(codec),
+        })
+    }
+
+    /// Append a batch after those already spilled
+    pub async fn push(&self, batch: &mut DataFrame) -> Result<()> {
+        let payload = self.codec.encode(batch)?;
+        self.conn
+            .execute_statement(
+                &format!("INSERT INTO {} (rows, payload) VALUES (?1, ?2)", self.table),
+                &[Value::Int(batch.height() as i64), Value::Bytes(payload)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Oldest spilled batch and its sequence number, if any
+    pub async fn oldest(&self) -> Result<Option<(i64, DataFrame)>> {
+        let df = self
+            .conn
+            .execute(&format!(
+                "SELECT seq, payload FROM {} ORDER BY seq LIMIT 1",
+                self.table
+            ))
+            .await?;
+        if df.height() == 0 {
+            return Ok(None);
+        }
+        let seq = df.column("seq")?.cast(&DataType::Int64)?.i64()?.get(0);
+        let payload = df.column("payload")?.binary()?.get(0).map(|p| p.to_vec());
+        match (seq, payload) {
+            (Some(seq), Some(payload)) => Ok(Some((seq, self.codec.decode(&payload)?))),
+            _ => Err(IndustryDbError::query_error(format!(
+                "Spilled batch in {} has no sequence number or payload",
+                self.table
+            ))),
+        }
+    }
+
+    /// Remove the batch with sequence number `seq`, once replayed
+    pub async fn remove(&self, seq: i64) -> Result<()> {
+        self.conn
+            .execute_statement(
+                &format!("DELETE FROM {} WHERE seq = ?1", self.table),
+                &[Value::Int(seq)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Rows waiting in the spill table
+    pub async fn pending_rows(&self) -> Result<u64> {
+        let rows = self
+            .conn
+            .fetch_scalar(
+                &format!("SELECT COALESCE(SUM(rows), 0) FROM {}", self.table),
+                &[],
+            )
+            .await?;
+        match rows {
+            Some(Value::Int(rows)) => Ok(rows.max(0) as u64),
+            _ => Ok(0),
+        }
+    }
+}
@@ -0,0 +1,665 @@
+//! SQL parsing and statement classification
+//!
+//! Thin layer over `sqlparser` that understands the dialects IndustryDB
+//! connects to. Used to classify statements before execution and to find
+//! the tables a statement touches.
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{visit_relations, SetExpr, Statement};
+use sqlparser::dialect::{Dialect, GenericDialect, MsSqlDialect, PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+use std::collections::BTreeSet;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use crate::config::DatabaseType;
+use crate::error::{IndustryDbError, Result};
+
+/// Broad category of a SQL statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementKind {
+    /// Returns rows without modifying data (SELECT, EXPLAIN, SHOW, ...)
+    Read,
+    /// Modifies rows (INSERT, UPDATE, DELETE, MERGE, COPY)
+    Write,
+    /// Changes schema objects (CREATE, ALTER, DROP, TRUNCATE)
+    Ddl,
+    /// Anything else (transaction control, SET, EXEC, ...)
+    Other,
+}
+
+impl std::fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatementKind::Read => write!(f, "read"),
+            StatementKind::Write => write!(f, "write"),
+            StatementKind::Ddl => write!(f, "ddl"),
+            StatementKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// A single parsed statement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParsedStatement {
+    /// Statement category
+    pub kind: StatementKind,
+    /// Tables referenced by the statement (CTE names excluded)
+    pub tables: Vec<String>,
+    /// Statement re-rendered from the AST
+    pub sql: String,
+}
+
+/// Get the sqlparser dialect for a database type (generic when `None`)
+pub(crate) fn parser_dialect(db_type: Option<DatabaseType>) -> Box<dyn Dialect> {
+    match db_type {
+        Some(DatabaseType::Postgres) => Box::new(PostgreSqlDialect {}),
+        Some(DatabaseType::Sqlite) => Box::new(SQLiteDialect {}),
+        Some(DatabaseType::Mssql) => Box::new(MsSqlDialect {}),
+        None => Box::new(GenericDialect {}),
+    }
+}
+
+/// Parse SQL into sqlparser statements
+pub(crate) fn parse_statements(sql: &str, db_type: Option<DatabaseType>) -> Result<Vec<Statement>> {
+    let dialect = parser_dialect(db_type);
+    Parser::parse_sql(dialect.as_ref(), sql)
+        .map_err(|e| IndustryDbError::sql_parse_error(e.to_string()))
+}
+
+/// Parse one or more SQL statements
+///
+/// `db_type` selects the dialect; `None` uses a permissive generic dialect.
+pub fn parse_sql(sql: &str, db_type: Option<DatabaseType>) -> Result<Vec<ParsedStatement>> {
+    let statements = parse_statements(sql, db_type)?;
+
+    Ok(statements
+        .iter()
+        .map(|stmt| ParsedStatement {
+            kind: statement_kind(stmt),
+            tables: referenced_tables(stmt),
+            sql: stmt.to_string(),
+        })
+        .collect())
+}
+
+/// Validate SQL syntax without executing it
+pub fn validate_sql(sql: &str, db_type: Option<DatabaseType>) -> Result<()> {
+    parse_statements(sql, db_type).map(|_| ())
+}
+
+/// Classify a SQL string
+///
+/// For multi-statement input the most impactful kind wins
+/// (`Other` > `Ddl` > `Write` > `Read`).
+pub fn classify_sql(sql: &str, db_type: Option<DatabaseType>) -> Result<StatementKind> {
+    parse_statements(sql, db_type)?
+        .iter()
+        .map(statement_kind)
+        .max()
+        .ok_or_else(|| IndustryDbError::sql_parse_error("No SQL statement found"))
+}
+
+/// Tables referenced anywhere in a SQL string, deduplicated and sorted
+pub fn extract_tables(sql: &str, db_type: Option<DatabaseType>) -> Result<Vec<String>> {
+    let tables: BTreeSet<String> = parse_statements(sql, db_type)?
+        .iter()
+        .flat_map(referenced_tables)
+        .collect();
+    Ok(tables.into_iter().collect())
+}
+
+/// Classify a parsed statement
+pub(crate) fn statement_kind(stmt: &Statement) -> StatementKind {
+    match stmt {
+        Statement::Query(query) => match query.body.as_ref() {
+            SetExpr::Insert { .. } | SetExpr::Update { .. } => StatementKind::Write,
+            _ => StatementKind::Read,
+        },
+        Statement::Explain { .. }
+        | Statement::ExplainTable { .. }
+        | Statement::ShowTables { .. }
+        | Statement::ShowColumns { .. }
+        | Statement::ShowVariable { .. }
+        | Statement::Pragma { .. } => StatementKind::Read,
+        Statement::Insert { .. }
+        | Statement::Update { .. }
+        | Statement::Delete { .. }
+        | Statement::Merge { .. }
+        | Statement::Copy { .. } => StatementKind::Write,
+        Statement::CreateTable { .. }
+        | Statement::CreateView { .. }
+        | Statement::CreateIndex { .. }
+        | Statement::CreateSchema { .. }
+        | Statement::CreateDatabase { .. }
+        | Statement::CreateSequence { .. }
+        | Statement::CreateType { .. }
+        | Statement::CreateFunction { .. }
+        | Statement::CreateProcedure { .. }
+        | Statement::AlterTable { .. }
+        | Statement::AlterIndex { .. }
+        | Statement::AlterView { .. }
+        | Statement::Drop { .. }
+        | Statement::Truncate { .. } => StatementKind::Ddl,
+        _ => StatementKind::Other,
+    }
+}
+
+/// Relations referenced by a statement, excluding top-level CTE names
+pub(crate) fn referenced_tables(stmt: &Statement) -> Vec<String> {
+    let cte_names: BTreeSet<String> = match stmt {
+        Statement::Query(query) => query
+            .with
+            .iter()
+            .flat_map(|with| with.cte_tables.iter())
+            .map(|cte| cte.alias.name.value.clone())
+            .collect(),
+        _ => BTreeSet::new(),
+    };
+
+    let mut tables = Vec::new();
+    let _ = visit_relations(stmt, |relation| {
+        let name = relation.to_string();
+        if !cte_names.contains(&name) && !tables.contains(&name) {
+            tables.push(name);
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    tables
+}
+
+/// Leading keywords of statements that never return rows
+const DDL_KEYWORDS: &[&str] = &["CREATE", "ALTER", "DROP", "TRUNCATE"];
+
+/// Leading keywords of statements that return rows only with a RETURNING
+/// (PostgreSQL, SQLite) or OUTPUT (SQL Server) clause
+const DML_KEYWORDS: &[&str] = &["INSERT", "UPDATE", "DELETE", "MERGE", "REPLACE"];
+
+/// Words of a statement outside quotes and comments, upper-cased
+fn keywords(sql: &str) -> Vec<String> {
+    let bytes = sql.as_bytes();
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip_quoted(sql, i, true) {
+            i = end;
+        } else if bytes[i].is_ascii_alphabetic() || bytes[i] == b'_' {
+            let len = bytes[i..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                .count();
+            words.push(sql[i..i + len].to_ascii_uppercase());
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    words
+}
+
+/// Fail unless some statement of `sql` can return rows
+///
+/// Lexical and deliberately permissive: only schema changes (CREATE, ALTER,
+/// DROP, TRUNCATE) and data changes (INSERT, UPDATE, DELETE, MERGE, REPLACE)
+/// without a RETURNING or OUTPUT clause are rejected. Anything else, such as
+/// `EXEC` or `SET NOCOUNT ON; SELECT ...`, is left to the database.
+pub fn ensure_returns_rows(sql: &str) -> Result<()> {
+    let mut rejected = None;
+    for statement in split_statements(sql) {
+        let words = keywords(&statement);
+        let Some(first) = words.first() else {
+            continue;
+        };
+        let returns_rows = if DDL_KEYWORDS.contains(&first.as_str()) {
+            false
+        } else if DML_KEYWORDS.contains(&first.as_str()) {
+            words.iter().any(|w| w == "RETURNING" || w == "OUTPUT")
+        } else {
+            true
+        };
+        if returns_rows {
+            return Ok(());
+        }
+        rejected.get_or_insert_with(|| first.clone());
+    }
+
+    match rejected {
+        Some(keyword) => Err(IndustryDbError::query_error(format!(
+            "{} statements return no rows; run them with execute_statement() instead of execute()",
+            keyword
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Split a SQL script into individual statements on top-level `;`
+///
+/// Purely lexical, so it also works for SQL the parser does not understand:
+/// semicolons inside quoted strings, quoted identifiers, comments,
+/// PostgreSQL dollar-quoted bodies and the `BEGIN ... END` body of a
+/// `CREATE TRIGGER` are not treated as separators. Empty statements are
+/// dropped and each statement is trimmed.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    split_statement_batches(sql, true)
+        .into_iter()
+        .map(|batch| batch.sql)
+        .collect()
+}
+
+/// One unit of a script sent to the database at a time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptBatch {
+    /// SQL of the batch, trimmed
+    pub sql: String,
+    /// 1-based line of the script the batch starts on
+    pub line: usize,
+    /// Times to run the batch (`GO n` on SQL Server), at least 1
+    pub repeat: usize,
+}
+
+/// Collects trimmed, non-empty batches with their starting line
+struct BatchCollector<'a> {
+    sql: &'a str,
+    batches: Vec<ScriptBatch>,
+}
+
+impl BatchCollector<'_> {
+    fn push(&mut self, from: usize, to: usize, repeat: usize) {
+        let text = &self.sql[from..to];
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        let offset = from + (text.len() - text.trim_start().len());
+        self.batches.push(ScriptBatch {
+            sql: trimmed.to_string(),
+            line: self.sql[..offset].matches('\n').count() + 1,
+            repeat,
+        });
+    }
+}
+
+/// [`split_statements`] keeping the line each statement starts on
+///
+/// `$tag$` dollar quotes are only recognized with `dollar_quotes`, since
+/// outside PostgreSQL `$` starts identifiers and parameters instead.
+pub fn split_statement_batches(sql: &str, dollar_quotes: bool) -> Vec<ScriptBatch> {
+    let mut collector = BatchCollector {
+        sql,
+        batches: Vec::new(),
+    };
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < sql.len() {
+        if let Some(end) = skip_quoted(sql, i, dollar_quotes) {
+            i = end;
+        } else if sql.as_bytes()[i] == b';' && !inside_trigger_body(&sql[start..i]) {
+            collector.push(start, i, 1);
+            i += 1;
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    collector.push(start, sql.len(), 1);
+
+    collector.batches
+}
+
+/// Whether `statement` is a `CREATE TRIGGER` whose `BEGIN ... END` body is
+/// still open, so that a `;` ends a statement of the body, not the trigger
+fn inside_trigger_body(statement: &str) -> bool {
+    let words = keywords(statement);
+    let trigger_at = match words.get(1).map(String::as_str) {
+        Some("TEMP" | "TEMPORARY") => 2,
+        _ => 1,
+    };
+    if words.first().map(String::as_str) != Some("CREATE")
+        || words.get(trigger_at).map(String::as_str) != Some("TRIGGER")
+    {
+        return false;
+    }
+
+    // CASE ... END can appear inside the body
+    let mut depth = 0usize;
+    for word in &words {
+        match word.as_str() {
+            "BEGIN" | "CASE" => depth += 1,
+            "END" => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
+/// Read a SQL script file
+///
+/// Scripts saved by SQL Server Management Studio are often UTF-16 or carry
+/// a UTF-8 byte order mark; both are decoded, other files must be UTF-8.
+pub fn read_script(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)?;
+    let utf16 = |bytes: &[u8], decode: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| decode([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).map_err(|e| e.to_string())
+    };
+    let text = match bytes.as_slice() {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|e| e.to_string())
+        }
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8(bytes).map_err(|e| e.to_string()),
+    };
+    text.map_err(|e| {
+        IndustryDbError::invalid_parameter(format!(
+            "Script {} is not valid text: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Split a T-SQL script into batches on `GO` separator lines
+///
+/// As in sqlcmd and SSMS, a separator is a line holding only `GO`
+/// (any case), optionally followed by a repeat count (`GO 5`). `GO` inside
+/// strings and comments is ignored. Batches are not split further, so
+/// statements that must start a batch, such as `CREATE PROCEDURE`, work.
+pub fn split_batches(sql: &str) -> Vec<ScriptBatch> {
+    let mut collector = BatchCollector {
+        sql,
+        batches: Vec::new(),
+    };
+    let mut start = 0;
+    let mut i = 0;
+    let mut line_start = true;
+
+    while i < sql.len() {
+        if line_start {
+            let line_end = sql[i..].find('\n').map_or(sql.len(), |n| i + n);
+            if let Some(repeat) = go_separator(&sql[i..line_end]) {
+                collector.push(start, i, repeat);
+                i = (line_end + 1).min(sql.len());
+                start = i;
+                continue;
+            }
+            line_start = false;
+        }
+        if let Some(end) = skip_quoted(sql, i, false) {
+            i = end;
+        } else {
+            line_start = sql.as_bytes()[i] == b'\n';
+            i += 1;
+        }
+    }
+    collector.push(start, sql.len(), 1);
+
+    collector.batches
+}
+
+/// Repeat count when `line` is a `GO` batch separator
+fn go_separator(line: &str) -> Option<usize> {
+    let line = line.trim();
+    if !line.get(..2)?.eq_ignore_ascii_case("go") {
+        return None;
+    }
+    let rest = &line[2..];
+    if rest.is_empty() {
+        return Some(1);
+    }
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    rest.trim().parse::<usize>().ok().filter(|&n| n > 0)
+}
+
+/// Canonical text of a query, for use as a cache key
+///
+/// Comments are dropped, runs of whitespace outside quoted strings and
+/// identifiers become one space, and surrounding whitespace and trailing
+/// semicolons are removed, so queries differing only in layout compare
+/// equal. Case is kept, since quoted names and string values depend on it.
+pub fn normalize_sql(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut space = false;
+    let mut i = 0;
+    while i < sql.len() {
+        if let Some(end) = skip_quoted(sql, i, true) {
+            if sql[i..].starts_with("--") || sql[i..].starts_with("/*") {
+                space = true;
+            } else {
+                if space && !out.is_empty() {
+                    out.push(' ');
+                }
+                space = false;
+                out.push_str(&sql[i..end]);
+            }
+            i = end;
+            continue;
+        }
+        let c = sql[i..].chars().next().expect("i is a char boundary");
+        if c.is_whitespace() {
+            space = true;
+        } else {
+            if space && !out.is_empty() {
+                out.push(' ');
+            }
+            space = false;
+            out.push(c);
+        }
+        i += c.len_utf8();
+    }
+    while out.ends_with(';') || out.ends_with(' ') {
+        out.pop();
+    }
+    out
+}
+
+/// End of the quoted string, quoted identifier, comment or, with
+/// `dollar_quotes`, PostgreSQL dollar-quoted body starting at byte `i`, or
+/// `None` when none starts there
+pub(crate) fn skip_quoted(sql: &str, mut i: usize, dollar_quotes: bool) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    match bytes[i] {
+        b'\'' | b'"' | b'[' | b'`' => {
+            let close = match bytes[i] {
+                b'[' => b']',
+                other => other,
+            };
+            i += 1;
+            while i < bytes.len() {
+                if bytes[i] == close {
+                    // A doubled quote is an escaped quote
+                    if i + 1 < bytes.len() && bytes[i + 1] == close && close != b']' {
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            Some((i + 1).min(bytes.len()))
+        }
+        b'-' if bytes.get(i + 1) == Some(&b'-') => {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            Some(i)
+        }
+        b'/' if bytes.get(i + 1) == Some(&b'*') => {
+            i += 2;
+            while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                i += 1;
+            }
+            Some((i + 2).min(bytes.len()))
+        }
+        b'$' if dollar_quotes => {
+            // Dollar quote: $$ or $tag$
+            let end = sql[i + 1..].find('$').map(|n| i + 1 + n).filter(|&end| {
+                sql[i + 1..end]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !sql[i + 1..end].starts_with(|c: char| c.is_ascii_digit())
+            })?;
+            let tag = &sql[i..=end];
+            Some(match sql[end + 1..].find(tag) {
+                Some(n) => end + 1 + n + tag.len(),
+                None => bytes.len(),
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_sql() {
+        assert_eq!(
+            normalize_sql("  SELECT *\n\tFROM readings -- latest\n WHERE tag = 'a  b' ;\n"),
+            "SELECT * FROM readings WHERE tag = 'a  b'"
+        );
+        assert_eq!(
+            normalize_sql("SELECT /* all */ \"Tag  Name\" FROM t WHERE v = $1;"),
+            "SELECT \"Tag  Name\" FROM t WHERE v = $1"
+        );
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let script = "CREATE TABLE t (a TEXT DEFAULT ';'); -- trailing; comment\n\
+                      INSERT INTO t VALUES ('x;y');\n\
+                      /* block; */ CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;\n;";
+        let statements = split_statements(script);
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "CREATE TABLE t (a TEXT DEFAULT ';')");
+        assert!(statements[1].ends_with("VALUES ('x;y')"));
+        assert!(statements[2].ends_with("LANGUAGE sql"));
+    }
+
+    #[test]
+    fn test_split_go_batches() {
+        let script = "CREATE TABLE t (a INT);\nGO\n\
+                      -- GO in a comment\n\
+                      CREATE PROCEDURE p AS\n  SELECT 'GO\nGO';\n  \
+                      go  \n\
+                      /*\nGO\n*/ INSERT INTO t VALUES (1)\nGO 3\n\
+                      GOTO_TABLE_CHECK\nGO";
+        let batches = split_batches(script);
+        assert_eq!(batches.len(), 4);
+        assert_eq!(batches[0].sql, "CREATE TABLE t (a INT);");
+        assert_eq!(batches[0].line, 1);
+        assert!(batches[1]
+            .sql
+            .starts_with("-- GO in a comment\nCREATE PROCEDURE p"));
+        assert!(batches[1].sql.ends_with("SELECT 'GO\nGO';"));
+        assert_eq!(batches[1].line, 3);
+        assert_eq!(batches[2].repeat, 3);
+        assert!(batches[2].sql.ends_with("INSERT INTO t VALUES (1)"));
+        assert_eq!(batches[3].sql, "GOTO_TABLE_CHECK");
+
+        let statements = split_statement_batches("SELECT 1;\n\n  SELECT 2", false);
+        assert_eq!(statements[1].line, 3);
+
+        // `$` is not a quote in T-SQL
+        let batches = split_batches("SELECT $x$\nGO\nSELECT $x$");
+        assert_eq!(batches.len(), 2);
+        assert_eq!(
+            split_statement_batches("SELECT $a$; SELECT $a$", false).len(),
+            2
+        );
+        assert_eq!(
+            split_statement_batches("SELECT $a$; SELECT $a$", true).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_split_keeps_trigger_bodies() {
+        let script = "CREATE TABLE t (a INT, b TEXT);\n\
+                      CREATE TEMP TRIGGER tr AFTER INSERT ON t BEGIN\n  \
+                      UPDATE t SET b = CASE WHEN a > 0 THEN 'pos' ELSE 'neg' END;\n  \
+                      DELETE FROM t WHERE a IS NULL;\n\
+                      END;\n\
+                      INSERT INTO t VALUES (1, 'begin; end');";
+        let statements = split_statements(script);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[1].starts_with("CREATE TEMP TRIGGER"));
+        assert!(statements[1].ends_with("END"));
+
+        // Transaction control is not a trigger body
+        assert_eq!(split_statements("BEGIN; SELECT 1; END;").len(), 3);
+    }
+
+    #[test]
+    fn test_read_script_decodes_bom() {
+        let dir = std::env::temp_dir();
+        let utf8 = dir.join(format!("industrydb-script-utf8-{}.sql", std::process::id()));
+        std::fs::write(&utf8, b"\xEF\xBB\xBFSELECT 1").unwrap();
+        assert_eq!(read_script(&utf8).unwrap(), "SELECT 1");
+
+        let utf16 = dir.join(format!(
+            "industrydb-script-utf16-{}.sql",
+            std::process::id()
+        ));
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("SELECT 2".encode_utf16().flat_map(u16::to_le_bytes));
+        std::fs::write(&utf16, bytes).unwrap();
+        assert_eq!(read_script(&utf16).unwrap(), "SELECT 2");
+
+        std::fs::remove_file(utf8).unwrap();
+        std::fs::remove_file(utf16).unwrap();
+    }
+
+    #[test]
+    fn test_classify_statements() {
+        let kind = |sql| classify_sql(sql, None).unwrap();
+        assert_eq!(kind("SELECT * FROM readings"), StatementKind::Read);
+        assert_eq!(
+            kind("INSERT INTO readings VALUES (1)"),
+            StatementKind::Write
+        );
+        assert_eq!(kind("UPDATE readings SET v = 1"), StatementKind::Write);
+        assert_eq!(kind("CREATE TABLE t (id INT)"), StatementKind::Ddl);
+        assert_eq!(kind("SELECT 1; DROP TABLE readings"), StatementKind::Ddl);
+    }
+
+    #[test]
+    fn test_ensure_returns_rows() {
+        assert!(ensure_returns_rows("SELECT * FROM readings").is_ok());
+        assert!(ensure_returns_rows("WITH x AS (SELECT 1) DELETE FROM t").is_ok());
+        assert!(ensure_returns_rows("INSERT INTO t VALUES (1) RETURNING id").is_ok());
+        assert!(ensure_returns_rows("UPDATE t SET v = 1 OUTPUT inserted.id").is_ok());
+        assert!(ensure_returns_rows("SET NOCOUNT ON; EXEC dbo.report").is_ok());
+        assert!(ensure_returns_rows("CREATE TABLE t (a INT); SELECT 1").is_ok());
+        assert!(ensure_returns_rows("").is_ok());
+
+        let err = ensure_returns_rows("/* seed */ insert into t values ('RETURNING')").unwrap_err();
+        assert!(err.to_string().contains("INSERT statements return no rows"));
+        assert!(ensure_returns_rows("CREATE TABLE t (a INT); DROP TABLE u").is_err());
+    }
+
+    #[test]
+    fn test_extract_tables_skips_ctes() {
+        let tables = extract_tables(
+            "WITH recent AS (SELECT * FROM readings) \
+             SELECT * FROM recent JOIN tags ON recent.tag_id = tags.id",
+            Some(DatabaseType::Postgres),
+        )
+        .unwrap();
+        assert_eq!(tables, vec!["readings".to_string(), "tags".to_string()]);
+    }
+
+    #[test]
+    fn test_invalid_sql() {
+        let err = validate_sql("SELEC * FORM t", None).unwrap_err();
+        assert!(matches!(err, IndustryDbError::SqlParseError(_)));
+    }
+}
@@ -0,0 +1,430 @@
+//! Tag metadata registry
+//!
+//! Historians keep the engineering metadata of each tag (unit, description,
+//! scaling and source system) apart from its values. [`TagRegistry`] keeps
+//! it in one table laid out the same way on every database, registers tags
+//! into it, and joins it onto tag-value rows, optionally scaling raw values
+//! into engineering units on the way.
+//!
+//! ```ignore
+//! let registry = TagRegistry::new(DEFAULT_TAG_TABLE);
+//! registry.create(&conn).await?;
+//! registry
+//!     .register(&conn, &[TagInfo::new("TI-101").unit("degC").scaling(0.1, 0.0)])
+//!     .await?;
+//!
+//! let rows = ValueAt::new("readings", "ts", ["TI-101"], at).fetch(&conn).await?;
+//! let rows = registry.annotate(&conn, rows, "tag", Some("value")).await?;
+//! ```
+
+use polars::prelude::{col, lit, DataFrame, DataType, IntoLazy, JoinArgs, JoinType, Series};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DatabaseType;
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::schema::{normalize_catalog, quote_literal};
+use crate::traits::{CrudOperations, DatabaseConnector};
+
+/// Default name of the tag metadata table
+pub const DEFAULT_TAG_TABLE: &str = "tags";
+
+/// Tags looked up per statement, below the SQL Server parameter limit
+const LOOKUP_CHUNK: usize = 1000;
+
+/// Columns of the tag metadata table, key first
+const TAG_COLUMNS: &[(&str, DataType)] = &[
+    ("tag", DataType::String),
+    ("unit", DataType::String),
+    ("description", DataType::String),
+    ("scale", DataType::Float64),
+    ("scale_offset", DataType::Float64),
+    ("source", DataType::String),
+];
+
+/// Metadata of one tag
+///
+/// A raw value is scaled into engineering units as
+/// `value * scale + scale_offset`; a missing scale counts as 1 and a
+/// missing offset as 0.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagInfo {
+    /// Tag name, unique in the registry
+    pub tag: String,
+    /// Engineering unit, e.g. `degC`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Free-text description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Factor raw values are multiplied by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    /// Added to raw values after scaling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale_offset: Option<f64>,
+    /// System the tag comes from, e.g. an OPC UA node id or PLC address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl TagInfo {
+    /// Tag without metadata
+    pub fn new(tag: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Engineering unit
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    /// Free-text description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Scale raw values as `value * scale + offset`
+    pub fn scaling(mut self, scale: f64, offset: f64) -> Self {
+        self.scale = Some(scale);
+        self.scale_offset = Some(offset);
+        self
+    }
+
+    /// System the tag comes from
+    pub fn source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+}
+
+/// Tag metadata kept in a table of the connected database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRegistry {
+    table: String,
+}
+
+impl Default for TagRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_TAG_TABLE)
+    }
+}
+
+impl TagRegistry {
+    /// Registry kept in `table`, possibly schema-qualified
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+        }
+    }
+
+    /// Table the metadata is kept in
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Statement creating the metadata table unless it exists, keyed by tag
+    pub fn create_sql(&self, dialect: &dyn Dialect) -> Result<String> {
+        let table = dialect.identifier(&self.table)?;
+        // SQL Server cannot key on NVARCHAR(MAX)
+        let key = match dialect.db_type() {
+            DatabaseType::Mssql => "NVARCHAR(255)".to_string(),
+            _ => dialect.column_type(&DataType::String)?,
+        };
+        let mut definitions = vec![format!("{} {} NOT NULL", dialect.identifier("tag")?, key)];
+        for (name, dtype) in &TAG_COLUMNS[1..] {
+            definitions.push(format!(
+                "{} {}",
+                dialect.identifier(name)?,
+                dialect.column_type(dtype)?
+            ));
+        }
+        definitions.push(format!("PRIMARY KEY ({})", dialect.identifier("tag")?));
+        let definitions = definitions.join(", ");
+        Ok(match dialect.db_type() {
+            DatabaseType::Mssql => format!(
+                "IF OBJECT_ID({}, 'U') IS NULL CREATE TABLE {} ({})",
+                quote_literal(&table),
+                table,
+                definitions
+            ),
+            _ => format!("CREATE TABLE IF NOT EXISTS {} ({})", table, definitions),
+        })
+    }
+
+    /// Create the metadata table unless it exists
+    pub async fn create<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<()> {
+        conn.execute_statement(&self.create_sql(conn.dialect())?, &[])
+            .await?;
+        Ok(())
+    }
+
+    /// Register tags, replacing the metadata of those already registered
+    pub async fn register<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        tags: &[TagInfo],
+    ) -> Result<usize> {
+        if tags.is_empty() {
+            return Ok(0);
+        }
+        let mut seen = std::collections::HashSet::new();
+        for info in tags {
+            if info.tag.is_empty() {
+                return Err(IndustryDbError::invalid_parameter(
+                    "Tag names must not be empty",
+                ));
+            }
+            if !seen.insert(info.tag.as_str()) {
+                return Err(IndustryDbError::invalid_parameter(format!(
+                    "Tag '{}' is registered twice",
+                    info.tag
+                )));
+            }
+        }
+        conn.upsert(&self.table, tag_frame(tags)?, &["tag".to_string()])
+            .await
+    }
+
+    /// Metadata of `tags`, or of every registered tag when empty, ordered
+    /// by tag
+    ///
+    /// Tags that are not registered are left out.
+    pub async fn lookup<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        tags: &[String],
+    ) -> Result<DataFrame> {
+        let dialect = conn.dialect();
+        let columns = TAG_COLUMNS
+            .iter()
+            .map(|(name, _)| dialect.identifier(name))
+            .collect::<Result<Vec<_>>>()?;
+        let select = format!(
+            "SELECT {} FROM {}",
+            columns.join(", "),
+            dialect.identifier(&self.table)?
+        );
+        let order = format!(" ORDER BY {}", columns[0]);
+        if tags.is_empty() {
+            let df = conn.execute(&format!("{}{}", select, order)).await?;
+            return normalize_catalog(df, TAG_COLUMNS);
+        }
+
+        let mut found = normalize_catalog(DataFrame::empty(), TAG_COLUMNS)?;
+        for chunk in tags.chunks(LOOKUP_CHUNK) {
+            let placeholders: Vec<String> =
+                (1..=chunk.len()).map(|i| dialect.placeholder(i)).collect();
+            let params: Vec<Value> = chunk.iter().map(|t| Value::Text(t.clone())).collect();
+            let sql = format!(
+                "{} WHERE {} IN ({}){}",
+                select,
+                columns[0],
+                placeholders.join(", "),
+                order
+            );
+            let df = conn.execute_with_params(&sql, &params).await?;
+            found.vstack_mut(&normalize_catalog(df, TAG_COLUMNS)?)?;
+        }
+        if tags.len() > LOOKUP_CHUNK {
+            found = found.sort(["tag"], Default::default())?;
+        }
+        Ok(found)
+    }
+
+    /// Remove tags from the registry, returning how many were registered
+    pub async fn remove<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        tags: &[String],
+    ) -> Result<u64> {
+        let dialect = conn.dialect();
+        let mut removed = 0;
+        for chunk in tags.chunks(LOOKUP_CHUNK) {
+            let placeholders: Vec<String> =
+                (1..=chunk.len()).map(|i| dialect.placeholder(i)).collect();
+            let params: Vec<Value> = chunk.iter().map(|t| Value::Text(t.clone())).collect();
+            let sql = format!(
+                "DELETE FROM {} WHERE {} IN ({})",
+                dialect.identifier(&self.table)?,
+                dialect.identifier("tag")?,
+                placeholders.join(", ")
+            );
+            removed += conn.execute_statement(&sql, &params).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Join the metadata of the tags in `tag_column` onto `df`, see
+    /// [`join_metadata`]
+    pub async fn annotate<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        df: DataFrame,
+        tag_column: &str,
+        value_column: Option<&str>,
+    ) -> Result<DataFrame> {
+        let names = df
+            .column(tag_column)?
+            .cast(&DataType::String)?
+            .as_materialized_series()
+            .unique()?;
+        let tags: Vec<String> = names
+            .str()?
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect();
+        let metadata = match tags.is_empty() {
+            true => normalize_catalog(DataFrame::empty(), TAG_COLUMNS)?,
+            false => self.lookup(conn, &tags).await?,
+        };
+        join_metadata(df, metadata, tag_column, value_column)
+    }
+}
+
+/// Frame of tag metadata in the layout of the registry table
+fn tag_frame(tags: &[TagInfo]) -> Result<DataFrame> {
+    let text = |name: &str, values: Vec<Option<&str>>| Series::new(name.into(), values).into();
+    let number = |name: &str, values: Vec<Option<f64>>| Series::new(name.into(), values).into();
+    Ok(DataFrame::new(vec![
+        text("tag", tags.iter().map(|t| Some(t.tag.as_str())).collect()),
+        text("unit", tags.iter().map(|t| t.unit.as_deref()).collect()),
+        text(
+            "description",
+            tags.iter().map(|t| t.description.as_deref()).collect(),
+        ),
+        number("scale", tags.iter().map(|t| t.scale).collect()),
+        number(
+            "scale_offset",
+            tags.iter().map(|t| t.scale_offset).collect(),
+        ),
+        text("source", tags.iter().map(|t| t.source.as_deref()).collect()),
+    ])?)
+}
+
+/// Tag metadata joined onto rows with a tag column
+///
+/// Adds the `unit`, `description`, `scale`, `scale_offset` and `source`
+/// columns of `metadata`, as [`TagRegistry::lookup`] returns it; they are
+/// null for tags that are not registered. With `value_column`, its raw
+/// values are replaced by scaled float values. Row order is kept.
+pub fn join_metadata(
+    df: DataFrame,
+    metadata: DataFrame,
+    tag_column: &str,
+    value_column: Option<&str>,
+) -> Result<DataFrame> {
+    let metadata = normalize_catalog(metadata, TAG_COLUMNS)?;
+    if let Some((name, _)) = TAG_COLUMNS[1..]
+        .iter()
+        .find(|(name, _)| *name != tag_column && df.column(name).is_ok())
+    {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Rows already have a '{}' column to join tag metadata into",
+            name
+        )));
+    }
+
+    let mut extra = vec![col("tag").alias(tag_column)];
+    extra.extend(TAG_COLUMNS[1..].iter().map(|(name, _)| col(*name)));
+    let on = [col(tag_column)];
+    let joined = df
+        .lazy()
+        .with_column(col(tag_column).cast(DataType::String))
+        .join(
+            metadata.lazy().select(extra),
+            on.clone(),
+            on,
+            JoinArgs::new(JoinType::Left),
+        );
+    let joined = match value_column {
+        Some(value) => joined.with_column(
+            (col(value).cast(DataType::Float64) * col("scale").fill_null(lit(1.0))
+                + col("scale_offset").fill_null(lit(0.0)))
+            .alias(value),
+        ),
+        None => joined,
+    };
+    Ok(joined.collect()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect, SqliteDialect};
+    use polars::prelude::df;
+
+    #[test]
+    fn test_create_tag_table_sql() {
+        let registry = TagRegistry::default();
+        assert_eq!(
+            registry.create_sql(&PostgresDialect).unwrap(),
+            "CREATE TABLE IF NOT EXISTS \"tags\" (\"tag\" TEXT NOT NULL, \"unit\" TEXT, \
+             \"description\" TEXT, \"scale\" DOUBLE PRECISION, \"scale_offset\" DOUBLE PRECISION, \
+             \"source\" TEXT, PRIMARY KEY (\"tag\"))"
+        );
+        assert!(registry
+            .create_sql(&SqliteDialect)
+            .unwrap()
+            .starts_with("CREATE TABLE IF NOT EXISTS \"tags\" (\"tag\" TEXT NOT NULL"));
+        assert!(registry.create_sql(&MssqlDialect).unwrap().starts_with(
+            "IF OBJECT_ID('[tags]', 'U') IS NULL CREATE TABLE [tags] ([tag] NVARCHAR(255) NOT NULL"
+        ));
+    }
+
+    #[test]
+    fn test_join_metadata() {
+        let metadata = tag_frame(&[
+            TagInfo::new("TI-101").unit("degC").scaling(0.5, -5.0),
+            TagInfo::new("FI-201").unit("m3/h"),
+        ])
+        .unwrap();
+        let rows = df!(
+            "tag" => ["TI-101", "XX-999", "FI-201"],
+            "value" => [500i64, 7, 12],
+        )
+        .unwrap();
+
+        let joined = join_metadata(rows.clone(), metadata.clone(), "tag", None).unwrap();
+        assert_eq!(
+            joined.get_column_names_str(),
+            [
+                "tag",
+                "value",
+                "unit",
+                "description",
+                "scale",
+                "scale_offset",
+                "source"
+            ]
+        );
+        let units: Vec<_> = joined
+            .column("unit")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(units, [Some("degC"), None, Some("m3/h")]);
+
+        let scaled = join_metadata(rows.clone(), metadata.clone(), "tag", Some("value")).unwrap();
+        let values: Vec<_> = scaled
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(245.0), Some(7.0), Some(12.0)]);
+
+        let clash = rows.hstack(&[Series::new("unit".into(), ["a", "b", "c"]).into()]);
+        assert!(join_metadata(clash.unwrap(), metadata, "tag", None).is_err());
+    }
+}
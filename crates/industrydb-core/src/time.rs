@@ -0,0 +1,156 @@
+//! Timestamp and interval parsing shared by time-range operations
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use polars::prelude::*;
+
+use crate::config::TimestampMode;
+use crate::error::{IndustryDbError, Result};
+
+/// Parse a timestamp such as `2024-01-31`, `2024-01-31 08:00:00` or
+/// `2024-01-31T08:00:00.250`
+pub fn parse_timestamp(value: &str) -> Result<NaiveDateTime> {
+    let value = value.trim();
+    for format in [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(ts) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(ts);
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|d| d.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+        .map_err(|_| IndustryDbError::invalid_parameter(format!("Invalid timestamp: {}", value)))
+}
+
+/// Format a timestamp as a literal every supported database accepts
+pub fn format_timestamp(ts: &NaiveDateTime) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+}
+
+/// Parse an interval such as `500ms`, `30s`, `15m`, `1h`, `1d` or `1w`
+pub fn parse_interval(value: &str) -> Result<TimeDelta> {
+    let value = value.trim();
+    let invalid = || IndustryDbError::invalid_parameter(format!("Invalid interval: {}", value));
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    let delta = match unit.trim() {
+        "ms" => TimeDelta::try_milliseconds(amount),
+        "s" | "sec" => TimeDelta::try_seconds(amount),
+        "m" | "min" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    if delta <= TimeDelta::zero() {
+        return Err(invalid());
+    }
+    Ok(delta)
+}
+
+/// Put the zone-aware datetime columns of a query result in the zone
+/// `mode` asks for
+///
+/// Connectors read `timestamptz`/`datetimeoffset` values as UTC instants,
+/// so only the column's zone changes, never the stored instant. Naive
+/// columns are left alone.
+pub fn apply_timestamp_mode(df: DataFrame, mode: &TimestampMode) -> Result<DataFrame> {
+    let aware = |column: &Column| matches!(column.dtype(), DataType::Datetime(_, Some(_)));
+    if *mode == TimestampMode::Utc || !df.get_columns().iter().any(aware) {
+        return Ok(df);
+    }
+
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|column| match column.dtype() {
+            DataType::Datetime(unit, Some(_)) => {
+                let zone = match mode {
+                    TimestampMode::Utc => Some("UTC".into()),
+                    TimestampMode::Naive => None,
+                    TimestampMode::Zone(zone) => Some(zone.as_str().into()),
+                };
+                column
+                    .to_physical_repr()
+                    .cast(&DataType::Datetime(*unit, zone))
+                    .map_err(|e| IndustryDbError::PolarsError(e.to_string()))
+            }
+            _ => Ok(column.clone()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    DataFrame::new(columns).map_err(|e| IndustryDbError::PolarsError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let ts = parse_timestamp("2024-01-31T08:15:00").unwrap();
+        assert_eq!(format_timestamp(&ts), "2024-01-31 08:15:00");
+        assert_eq!(
+            parse_timestamp("2024-01-31").unwrap(),
+            parse_timestamp("2024-01-31 00:00:00").unwrap()
+        );
+        assert!(parse_timestamp("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("15m").unwrap(), TimeDelta::minutes(15));
+        assert_eq!(parse_interval("1d").unwrap(), TimeDelta::hours(24));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("h").is_err());
+    }
+
+    #[test]
+    fn test_apply_timestamp_mode() {
+        let instants = Series::new("ts".into(), [1_700_000_000_000_000i64])
+            .cast(&DataType::Datetime(
+                TimeUnit::Microseconds,
+                Some("UTC".into()),
+            ))
+            .unwrap();
+        let wall = Series::new("local".into(), [0i64])
+            .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
+            .unwrap();
+        let df = DataFrame::new(vec![instants.into(), wall.into()]).unwrap();
+
+        let zoned = apply_timestamp_mode(
+            df.clone(),
+            &TimestampMode::Zone("Europe/Berlin".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            zoned.column("ts").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, Some("Europe/Berlin".into()))
+        );
+        assert_eq!(
+            zoned.column("local").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+
+        let naive = apply_timestamp_mode(df, &TimestampMode::Naive).unwrap();
+        let ts = naive.column("ts").unwrap();
+        assert_eq!(
+            ts.dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+        // The instant is kept as UTC wall-clock time
+        assert_eq!(
+            ts.to_physical_repr().i64().unwrap().get(0),
+            Some(1_700_000_000_000_000)
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! TimescaleDB continuous aggregates
+//!
+//! A continuous aggregate is a materialized view of [`TimeBuckets`] that
+//! TimescaleDB refreshes incrementally. [`ContinuousAggregate`] creates,
+//! refreshes and drops one, and reads it only where it can: on a server
+//! without the extension, or before the view is created, the same buckets
+//! are aggregated from the raw table instead, so callers need not know
+//! which server they talk to.
+//!
+//! ```ignore
+//! let buckets = TimeBuckets::new("readings", "ts", ["value"], parse_interval("1h")?);
+//! let hourly = ContinuousAggregate::new("readings_hourly");
+//! if timescale_version(&conn).await?.is_some() {
+//!     hourly.create(&conn, &buckets, false).await?;
+//!     let (start, end) = (Some(TimeDelta::days(3)), Some(TimeDelta::hours(1)));
+//!     hourly.add_policy(&conn, start, end, TimeDelta::hours(1)).await?;
+//! }
+//! let df = hourly.fetch(&conn, &buckets).await?;
+//! ```
+
+use chrono::{NaiveDateTime, TimeDelta};
+use polars::prelude::DataFrame;
+
+use crate::config::DatabaseType;
+use crate::dialect::{Dialect, PostgresDialect};
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::schema::quote_literal;
+use crate::time::format_timestamp;
+use crate::timeseries::{TimeBuckets, BUCKET_COLUMN};
+use crate::traits::DatabaseConnector;
+
+/// Version of the TimescaleDB extension installed in the connected
+/// database, `None` when it is not installed or the server is not
+/// PostgreSQL
+pub async fn timescale_version<C: DatabaseConnector + ?Sized>(conn: &C) -> Result<Option<String>> {
+    if conn.dialect().db_type() != DatabaseType::Postgres {
+        return Ok(None);
+    }
+    let version = conn
+        .fetch_scalar(
+            "SELECT extversion FROM pg_extension WHERE extname = 'timescaledb'",
+            &[],
+        )
+        .await?;
+    Ok(match version {
+        Some(Value::Text(version)) => Some(version),
+        _ => None,
+    })
+}
+
+async fn require_timescale<C: DatabaseConnector + ?Sized>(conn: &C) -> Result<()> {
+    match timescale_version(conn).await? {
+        Some(_) => Ok(()),
+        None => Err(IndustryDbError::config_error(
+            "Continuous aggregates need the TimescaleDB extension",
+        )),
+    }
+}
+
+/// Interval literal of a whole number of seconds, or NULL
+fn interval(offset: Option<TimeDelta>) -> String {
+    match offset {
+        Some(offset) => format!("INTERVAL '{} seconds'", offset.num_seconds()),
+        None => "NULL".to_string(),
+    }
+}
+
+/// A TimescaleDB continuous aggregate, by view name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContinuousAggregate {
+    name: String,
+}
+
+impl ContinuousAggregate {
+    /// The continuous aggregate named `name`, possibly schema-qualified
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    /// View name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Quoted view name as a string literal, for functions taking a
+    /// regclass
+    fn regclass(&self) -> Result<String> {
+        Ok(quote_literal(&PostgresDialect.identifier(&self.name)?))
+    }
+
+    /// Statement creating the view over `buckets`, bucketed with
+    /// `time_bucket` as TimescaleDB requires
+    ///
+    /// Without `with_data` the view starts empty until it is refreshed,
+    /// which keeps creation fast on large tables.
+    pub fn create_sql(&self, buckets: &TimeBuckets, with_data: bool) -> Result<String> {
+        let select = buckets
+            .clone()
+            .time_bucket(true)
+            .select_sql(&PostgresDialect)?;
+        Ok(format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {} WITH (timescaledb.continuous) \
+             AS {} WITH {}DATA",
+            PostgresDialect.identifier(&self.name)?,
+            select,
+            if with_data { "" } else { "NO " }
+        ))
+    }
+
+    /// Statement refreshing the buckets from `start` up to `end`, open
+    /// ended where `None`
+    pub fn refresh_sql(
+        &self,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+    ) -> Result<String> {
+        let bound = |ts: Option<NaiveDateTime>| match ts {
+            Some(ts) => quote_literal(&format_timestamp(&ts)),
+            None => "NULL".to_string(),
+        };
+        Ok(format!(
+            "CALL refresh_continuous_aggregate({}, {}, {})",
+            self.regclass()?,
+            bound(start),
+            bound(end)
+        ))
+    }
+
+    /// Statement adding a policy that refreshes the view every `every`,
+    /// over the buckets from `start_offset` to `end_offset` before now
+    ///
+    /// `None` offsets leave that end of the window open.
+    pub fn policy_sql(
+        &self,
+        start_offset: Option<TimeDelta>,
+        end_offset: Option<TimeDelta>,
+        every: TimeDelta,
+    ) -> Result<String> {
+        Ok(format!(
+            "SELECT add_continuous_aggregate_policy({}, start_offset => {}, \
+             end_offset => {}, schedule_interval => {}, if_not_exists => true)",
+            self.regclass()?,
+            interval(start_offset),
+            interval(end_offset),
+            interval(Some(every))
+        ))
+    }
+
+    /// Create the view over `buckets`, see [`create_sql`](Self::create_sql);
+    /// nothing happens when it exists
+    pub async fn create<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        buckets: &TimeBuckets,
+        with_data: bool,
+    ) -> Result<()> {
+        require_timescale(conn).await?;
+        conn.execute_statement(&self.create_sql(buckets, with_data)?, &[])
+            .await?;
+        Ok(())
+    }
+
+    /// Refresh the buckets from `start` up to `end`
+    pub async fn refresh<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        start: Option<NaiveDateTime>,
+        end: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        require_timescale(conn).await?;
+        conn.execute_statement(&self.refresh_sql(start, end)?, &[])
+            .await?;
+        Ok(())
+    }
+
+    /// Refresh the view on a schedule, see [`policy_sql`](Self::policy_sql)
+    pub async fn add_policy<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        start_offset: Option<TimeDelta>,
+        end_offset: Option<TimeDelta>,
+        every: TimeDelta,
+    ) -> Result<()> {
+        require_timescale(conn).await?;
+        conn.execute(&self.policy_sql(start_offset, end_offset, every)?)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop the view, and with it its refresh policy, if it exists
+    pub async fn remove<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<()> {
+        require_timescale(conn).await?;
+        let sql = format!(
+            "DROP MATERIALIZED VIEW IF EXISTS {}",
+            PostgresDialect.identifier(&self.name)?
+        );
+        conn.execute_statement(&sql, &[]).await?;
+        Ok(())
+    }
+
+    /// Whether the server has TimescaleDB and the view exists
+    pub async fn exists<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<bool> {
+        if timescale_version(conn).await?.is_none() {
+            return Ok(false);
+        }
+        let found = conn
+            .fetch_scalar(
+                "SELECT EXISTS (SELECT 1 FROM timescaledb_information.continuous_aggregates \
+                 WHERE format('%I.%I', view_schema, view_name)::regclass = to_regclass($1)) \
+                 AS found",
+                &[Value::Text(PostgresDialect.identifier(&self.name)?)],
+            )
+            .await?;
+        Ok(matches!(found, Some(Value::Bool(true))))
+    }
+
+    /// Buckets of `buckets`, read from the view when it exists and from the
+    /// raw table otherwise
+    ///
+    /// `buckets` should be those the view was created over, so both paths
+    /// return the same columns. Without TimescaleDB the raw table is
+    /// bucketed without `time_bucket`.
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        buckets: &TimeBuckets,
+    ) -> Result<DataFrame> {
+        if !self.exists(conn).await? {
+            let buckets = match timescale_version(conn).await? {
+                Some(_) => buckets.clone(),
+                None => buckets.clone().time_bucket(false),
+            };
+            return buckets.fetch(conn).await;
+        }
+        let sql = format!(
+            "SELECT * FROM {} ORDER BY {}",
+            PostgresDialect.identifier(&self.name)?,
+            PostgresDialect.identifier(BUCKET_COLUMN)?
+        );
+        let df = conn.execute(&sql).await?;
+        buckets.finish(df, DatabaseType::Postgres)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Aggregate;
+    use crate::time::parse_timestamp;
+
+    #[test]
+    fn test_continuous_aggregate_sql() {
+        let buckets = TimeBuckets::new("readings", "ts", ["value"], TimeDelta::hours(1))
+            .aggregates([Aggregate::Max]);
+        let hourly = ContinuousAggregate::new("plant.readings_hourly");
+        assert_eq!(
+            hourly.create_sql(&buckets, false).unwrap(),
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS \"plant\".\"readings_hourly\" \
+             WITH (timescaledb.continuous) AS SELECT time_bucket(INTERVAL '3600 seconds', \"ts\") \
+             AS \"bucket\", MAX(\"value\") AS \"value_max\" FROM \"readings\" \
+             GROUP BY time_bucket(INTERVAL '3600 seconds', \"ts\") WITH NO DATA"
+        );
+        assert_eq!(
+            hourly
+                .refresh_sql(Some(parse_timestamp("2024-03-01").unwrap()), None)
+                .unwrap(),
+            "CALL refresh_continuous_aggregate('\"plant\".\"readings_hourly\"', \
+             '2024-03-01 00:00:00', NULL)"
+        );
+        assert_eq!(
+            hourly
+                .policy_sql(Some(TimeDelta::days(1)), None, TimeDelta::minutes(30))
+                .unwrap(),
+            "SELECT add_continuous_aggregate_policy('\"plant\".\"readings_hourly\"', \
+             start_offset => INTERVAL '86400 seconds', end_offset => NULL, \
+             schedule_interval => INTERVAL '1800 seconds', if_not_exists => true)"
+        );
+    }
+}
@@ -0,0 +1,908 @@
+//! Time-series queries
+//!
+//! Reads the rows of a time range, optionally for some tags only and
+//! resampled after the fetch, and aggregates a table into fixed time
+//! buckets with SQL rendered for each dialect: `date_trunc`/`date_bin` (or
+//! TimescaleDB's `time_bucket`) on PostgreSQL, Unix-epoch arithmetic
+//! through `strftime` on SQLite and `DATEADD`/`DATEDIFF` on SQL Server.
+//! Buckets without rows can be added back and filled, see [`fill_gaps`].
+//!
+//! ```ignore
+//! let df = TimeRange::new("readings", "ts", parse_timestamp("2024-03-01")?, end)
+//!     .tags("tag", ["TI-101", "TI-102"])
+//!     .resample(parse_interval("1m")?, Aggregate::Avg)
+//!     .fill(FillMethod::Linear)
+//!     .fetch(&conn)
+//!     .await?;
+//!
+//! let df = TimeBuckets::new("readings", "ts", ["value"], parse_interval("5m")?)
+//!     .aggregates([Aggregate::Avg, Aggregate::Max])
+//!     .fetch(&conn)
+//!     .await?;
+//! ```
+
+use chrono::{NaiveDateTime, TimeDelta};
+use polars::prelude::{
+    col, ChunkAgg, DataFrame, DataType, Expr, FillNullStrategy, IdxCa, IdxSize, Int64Chunked,
+    IntoLazy, IntoSeries, JoinArgs, JoinType, NewChunkedArray, Series, SortMultipleOptions,
+    TimeUnit, UniqueKeepStrategy,
+};
+
+use crate::config::DatabaseType;
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::query::Aggregate;
+use crate::schema::coerce_columns;
+use crate::time::format_timestamp;
+use crate::traits::DatabaseConnector;
+
+/// Name of the bucket start column of bucketed results
+pub const BUCKET_COLUMN: &str = "bucket";
+
+/// Whole-bucket origin; every bucket starts a multiple of its width after it
+const ORIGIN: &str = "2000-01-01 00:00:00";
+/// [`ORIGIN`] in microseconds since the Unix epoch
+const ORIGIN_MICROS: i64 = 946_684_800_000_000;
+
+/// Rows of a table between two timestamps
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeRange {
+    table: String,
+    ts_column: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    tag_column: Option<String>,
+    tags: Vec<Value>,
+    columns: Vec<String>,
+    resample: Option<(TimeDelta, Aggregate)>,
+    fill: Option<FillMethod>,
+}
+
+impl TimeRange {
+    /// Rows of `table` with `ts_column` from `start` up to, but not
+    /// including, `end`
+    pub fn new(table: &str, ts_column: &str, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        Self {
+            table: table.to_string(),
+            ts_column: ts_column.to_string(),
+            start,
+            end,
+            tag_column: None,
+            tags: Vec::new(),
+            columns: Vec::new(),
+            resample: None,
+            fill: None,
+        }
+    }
+
+    /// Only the rows whose `tag_column` is one of `tags`
+    pub fn tags<I, V>(mut self, tag_column: &str, tags: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        self.tag_column = Some(tag_column.to_string());
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Return these columns instead of all of them
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.columns = columns
+            .into_iter()
+            .map(|c| c.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Aggregate the fetched rows into `every`-wide buckets, see
+    /// [`resample`]; per tag when filtering on tags
+    pub fn resample(mut self, every: TimeDelta, aggregate: Aggregate) -> Self {
+        self.resample = Some((every, aggregate));
+        self
+    }
+
+    /// Add the resampled buckets without rows between the start and end,
+    /// see [`fill_gaps`]; needs [`resample`](Self::resample)
+    pub fn fill(mut self, method: FillMethod) -> Self {
+        self.fill = Some(method);
+        self
+    }
+
+    /// Render the query and its parameters in `dialect`
+    ///
+    /// The bounds are bound as timestamp text, which every database
+    /// compares with its timestamp types and SQLite with the text it keeps
+    /// them as. Rows are ordered by timestamp.
+    pub fn render(&self, dialect: &dyn Dialect) -> Result<(String, Vec<Value>)> {
+        if self.fill.is_some() && self.resample.is_none() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Gaps can only be filled in resampled time ranges",
+            ));
+        }
+        if self.start >= self.end {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Time range start {} is not before its end {}",
+                self.start, self.end
+            )));
+        }
+        let ts = dialect.identifier(&self.ts_column)?;
+        let columns = match self.columns.as_slice() {
+            [] => "*".to_string(),
+            columns => dialect.identifiers(columns)?.join(", "),
+        };
+
+        let mut params = vec![
+            Value::Text(format_timestamp(&self.start)),
+            Value::Text(format_timestamp(&self.end)),
+        ];
+        let mut filter = format!(
+            "{} >= {} AND {} < {}",
+            ts,
+            dialect.placeholder(1),
+            ts,
+            dialect.placeholder(2)
+        );
+        if let Some(tag_column) = &self.tag_column {
+            if self.tags.is_empty() {
+                return Err(IndustryDbError::invalid_parameter(
+                    "Tag filter must name at least one tag",
+                ));
+            }
+            let placeholders: Vec<String> = self
+                .tags
+                .iter()
+                .map(|tag| {
+                    params.push(tag.clone());
+                    dialect.placeholder(params.len())
+                })
+                .collect();
+            filter.push_str(&format!(
+                " AND {} IN ({})",
+                dialect.identifier(tag_column)?,
+                placeholders.join(", ")
+            ));
+        }
+
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {} ORDER BY {}",
+            columns,
+            dialect.identifier(&self.table)?,
+            filter,
+            ts
+        );
+        Ok((sql, params))
+    }
+
+    /// Run the query on a connection
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<DataFrame> {
+        let (sql, params) = self.render(conn.dialect())?;
+        let df = conn.execute_with_params(&sql, &params).await?;
+        let Some((every, aggregate)) = self.resample else {
+            return Ok(df);
+        };
+        let by = self.tag_column.as_slice();
+        let df = resample(df, &self.ts_column, every, aggregate, by)?;
+        match self.fill {
+            Some(method) => fill_gaps(
+                df,
+                &self.ts_column,
+                every,
+                Some(self.start),
+                Some(self.end),
+                method,
+                by,
+            ),
+            None => Ok(df),
+        }
+    }
+}
+
+/// Width of a bucket in microseconds
+fn step_micros(every: TimeDelta) -> Result<i64> {
+    every
+        .num_microseconds()
+        .filter(|step| *step > 0)
+        .ok_or_else(|| IndustryDbError::invalid_parameter(format!("Invalid interval: {}", every)))
+}
+
+/// Start of the `step`-wide bucket holding `micros`
+fn align(micros: i64, step: i64) -> i64 {
+    ORIGIN_MICROS + (micros - ORIGIN_MICROS).div_euclid(step) * step
+}
+
+/// `df` with `ts_column` as microsecond datetimes, and that dtype
+///
+/// Text timestamps, as SQLite returns them, are parsed and dates become
+/// midnight; a time zone is kept.
+pub(crate) fn with_micros(df: DataFrame, ts_column: &str) -> Result<(DataFrame, DataType)> {
+    let mut df = match df.column(ts_column)?.dtype() {
+        DataType::String => coerce_columns(
+            df,
+            &[(
+                ts_column.to_string(),
+                DataType::Datetime(TimeUnit::Microseconds, None),
+            )],
+        )?,
+        _ => df,
+    };
+    let column = df.column(ts_column)?;
+    let dtype = match column.dtype() {
+        DataType::Datetime(_, tz) => DataType::Datetime(TimeUnit::Microseconds, tz.clone()),
+        DataType::Date => DataType::Datetime(TimeUnit::Microseconds, None),
+        other => {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Column '{}' of type {} is not a timestamp",
+                ts_column, other
+            )))
+        }
+    };
+    let micros = column.cast(&dtype)?;
+    df.with_column(micros)?;
+    Ok((df, dtype))
+}
+
+/// Aggregate fetched rows into `every`-wide buckets of `ts_column`
+///
+/// Rows are grouped by the start of their bucket, aligned like
+/// [`TimeBuckets`], and by the `by` columns; every other column is
+/// aggregated with `aggregate` and keeps its name, and the bucket start
+/// replaces the timestamp. Text timestamps, as SQLite returns them, are
+/// parsed first. The result is ordered by bucket, then by the `by` columns.
+pub fn resample(
+    df: DataFrame,
+    ts_column: &str,
+    every: TimeDelta,
+    aggregate: Aggregate,
+    by: &[String],
+) -> Result<DataFrame> {
+    let step = step_micros(every)?;
+    if df.width() == 0 {
+        return Ok(df);
+    }
+    let (mut df, dtype) = with_micros(df, ts_column)?;
+    let buckets: Int64Chunked = df
+        .column(ts_column)?
+        .as_materialized_series()
+        .to_physical_repr()
+        .i64()?
+        .into_iter()
+        .map(|t| t.map(|t| align(t, step)))
+        .collect();
+    df.with_column(
+        buckets
+            .into_series()
+            .with_name(ts_column.into())
+            .cast(&dtype)?,
+    )?;
+
+    let keys: Vec<&str> = std::iter::once(ts_column)
+        .chain(by.iter().map(String::as_str))
+        .collect();
+    let values: Vec<Expr> = df
+        .get_column_names()
+        .into_iter()
+        .filter(|name| !keys.contains(&name.as_str()))
+        .map(|name| {
+            let value = col(name.as_str());
+            match aggregate {
+                Aggregate::Avg => value.mean(),
+                Aggregate::Min => value.min(),
+                Aggregate::Max => value.max(),
+                Aggregate::Sum => value.sum(),
+                Aggregate::Count => value.count(),
+            }
+        })
+        .collect();
+    Ok(df
+        .lazy()
+        .group_by(keys.iter().map(|key| col(*key)).collect::<Vec<_>>())
+        .agg(values)
+        .sort(keys, SortMultipleOptions::default())
+        .collect()?)
+}
+
+/// Most buckets [`fill_gaps`] creates, so a mistyped interval cannot
+/// exhaust memory
+const MAX_FILLED_BUCKETS: usize = 10_000_000;
+
+/// How [`fill_gaps`] fills the values of buckets without rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillMethod {
+    /// Leave them null
+    Null,
+    /// Repeat the previous bucket's values
+    Forward,
+    /// Interpolate numeric values linearly in time between the buckets
+    /// around the gap; other columns are filled forward
+    Linear,
+}
+
+impl std::str::FromStr for FillMethod {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "null" | "none" => Ok(FillMethod::Null),
+            "forward" | "ffill" => Ok(FillMethod::Forward),
+            "linear" | "interpolate" => Ok(FillMethod::Linear),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported fill method: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Add a row for every `every`-wide bucket of `ts_column` missing from
+/// bucketed rows, such as those of [`TimeBuckets`] or [`resample`]
+///
+/// Buckets run from the one holding `start` up to `end`, exclusive, or
+/// over the span of the rows when not given, for each combination of the
+/// `by` columns in the rows. The added rows' other columns are filled
+/// with `method`; nulls in the rows themselves are filled the same way.
+/// Leading and trailing gaps are not interpolated. The result is ordered
+/// by the `by` columns, then by bucket.
+pub fn fill_gaps(
+    df: DataFrame,
+    ts_column: &str,
+    every: TimeDelta,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    method: FillMethod,
+    by: &[String],
+) -> Result<DataFrame> {
+    let step = step_micros(every)?;
+    if df.width() == 0 {
+        return Ok(df);
+    }
+    let columns: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+    let (df, dtype) = with_micros(df, ts_column)?;
+
+    let (lo, hi) = {
+        let times = df
+            .column(ts_column)?
+            .as_materialized_series()
+            .to_physical_repr();
+        let times = times.i64()?;
+        (
+            start
+                .map(|start| start.and_utc().timestamp_micros())
+                .or_else(|| times.min()),
+            end.map(|end| end.and_utc().timestamp_micros())
+                .or_else(|| times.max().map(|max| max + 1)),
+        )
+    };
+    let (Some(lo), Some(hi)) = (lo, hi) else {
+        return Ok(df);
+    };
+    let first = align(lo, step);
+    let buckets = usize::try_from((hi - first + step - 1) / step).unwrap_or(0);
+
+    let mut frame = match by {
+        [] => DataFrame::empty(),
+        by => df
+            .select(by.iter().map(String::as_str))?
+            .lazy()
+            .unique_stable(None, UniqueKeepStrategy::First)
+            .collect()?,
+    };
+    let groups = match by {
+        [] => 1,
+        _ => frame.height(),
+    };
+    if buckets.saturating_mul(groups) > MAX_FILLED_BUCKETS {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Filling gaps of {} would create more than {} buckets",
+            every, MAX_FILLED_BUCKETS
+        )));
+    }
+    let grid: Vec<i64> = (0..buckets as i64).map(|i| first + i * step).collect();
+    if !by.is_empty() {
+        let repeat: Vec<IdxSize> = (0..groups as IdxSize)
+            .flat_map(|g| std::iter::repeat(g).take(grid.len()))
+            .collect();
+        frame = frame.take(&IdxCa::from_vec("".into(), repeat))?;
+    }
+    let tiled: Vec<i64> = grid
+        .iter()
+        .copied()
+        .cycle()
+        .take(grid.len() * groups)
+        .collect();
+    frame.with_column(Series::new(ts_column.into(), tiled).cast(&dtype)?)?;
+
+    let keys: Vec<&str> = by
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(ts_column))
+        .collect();
+    let on: Vec<Expr> = keys.iter().map(|key| col(*key)).collect();
+    let mut frame = frame
+        .lazy()
+        .join(df.lazy(), on.clone(), on, JoinArgs::new(JoinType::Left))
+        .sort(keys.clone(), SortMultipleOptions::default())
+        .collect()?
+        .select(columns.iter().map(String::as_str))?;
+    if method == FillMethod::Null || grid.is_empty() {
+        return Ok(frame);
+    }
+
+    // Each group's buckets are now a run of `grid.len()` rows
+    let run = grid.len();
+    for name in &columns {
+        if keys.contains(&name.as_str()) {
+            continue;
+        }
+        let series = frame.column(name)?.as_materialized_series().clone();
+        let filled = if method == FillMethod::Linear && series.dtype().is_numeric() {
+            let mut values: Vec<Option<f64>> = series
+                .cast(&DataType::Float64)?
+                .f64()?
+                .into_iter()
+                .collect();
+            for chunk in values.chunks_mut(run) {
+                interpolate(&grid, chunk);
+            }
+            Series::new(name.as_str().into(), values)
+        } else {
+            let mut filled: Option<Series> = None;
+            for offset in (0..series.len()).step_by(run) {
+                let part = series
+                    .slice(offset as i64, run)
+                    .fill_null(FillNullStrategy::Forward(None))?;
+                match filled.as_mut() {
+                    Some(filled) => {
+                        filled.append(&part)?;
+                    }
+                    None => filled = Some(part),
+                }
+            }
+            filled.unwrap_or(series)
+        };
+        frame.with_column(filled)?;
+    }
+    Ok(frame)
+}
+
+/// Fill the nulls between known values of one group linearly in time
+fn interpolate(times: &[i64], values: &mut [Option<f64>]) {
+    let known: Vec<usize> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| v.map(|_| i))
+        .collect();
+    for pair in known.windows(2) {
+        let (p, i) = (pair[0], pair[1]);
+        let (Some(v0), Some(v1)) = (values[p], values[i]) else {
+            continue;
+        };
+        let (t0, t1) = (times[p], times[i]);
+        for (t, value) in times[p + 1..i].iter().zip(&mut values[p + 1..i]) {
+            let share = (t - t0) as f64 / (t1 - t0) as f64;
+            *value = Some(v0 + (v1 - v0) * share);
+        }
+    }
+}
+
+/// `aggregate` of `column`
+fn aggregate_sql(aggregate: Aggregate, column: &str, dialect: &dyn Dialect) -> Result<String> {
+    Ok(match aggregate {
+        // SQL Server averages integers as integers
+        Aggregate::Avg => format!(
+            "AVG(CAST({} AS {}))",
+            column,
+            dialect.column_type(&DataType::Float64)?
+        ),
+        _ => format!("{}({})", aggregate.as_sql(), column),
+    })
+}
+
+/// Expression truncating `column` to the start of its bucket
+///
+/// Buckets are whole seconds, aligned to midnight of 2000-01-01 so that
+/// e.g. 15-minute buckets start on the quarter hour.
+fn bucket_expr(
+    column: &str,
+    width: TimeDelta,
+    dialect: &dyn Dialect,
+    time_bucket: bool,
+) -> Result<String> {
+    let seconds = width.num_seconds();
+    if seconds <= 0 || width.subsec_nanos() != 0 {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Time buckets must be a positive number of whole seconds, not {}",
+            width
+        )));
+    }
+    Ok(match dialect.db_type() {
+        DatabaseType::Postgres if time_bucket => {
+            format!("time_bucket(INTERVAL '{} seconds', {})", seconds, column)
+        }
+        DatabaseType::Postgres => {
+            let unit = match seconds {
+                1 => Some("second"),
+                60 => Some("minute"),
+                3_600 => Some("hour"),
+                86_400 => Some("day"),
+                _ => None,
+            };
+            match unit {
+                Some(unit) => format!("date_trunc('{}', {})", unit, column),
+                None => format!(
+                    "date_bin(INTERVAL '{} seconds', {}, TIMESTAMP '{}')",
+                    seconds, column, ORIGIN
+                ),
+            }
+        }
+        DatabaseType::Sqlite => format!(
+            "datetime(CAST(strftime('%s', {}) AS INTEGER) / {} * {}, 'unixepoch')",
+            column, seconds, seconds
+        ),
+        DatabaseType::Mssql => {
+            // Minutes since 2000 fit DATEADD's int for millennia, seconds
+            // only until 2068
+            let (unit, amount) = match seconds % 60 {
+                0 => ("minute", seconds / 60),
+                _ => ("second", seconds),
+            };
+            format!(
+                "DATEADD({unit}, DATEDIFF({unit}, CAST('{origin}' AS DATETIME2), {column}) \
+                 / {amount} * {amount}, CAST('{origin}' AS DATETIME2))",
+                unit = unit,
+                origin = ORIGIN,
+                column = column,
+                amount = amount
+            )
+        }
+    })
+}
+
+/// Aggregates of value columns over fixed time buckets
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeBuckets {
+    table: String,
+    ts_column: String,
+    value_columns: Vec<String>,
+    width: TimeDelta,
+    aggregates: Vec<Aggregate>,
+    time_bucket: bool,
+    fill: Option<FillMethod>,
+}
+
+impl TimeBuckets {
+    /// Buckets of `width` over `ts_column` of `table`
+    ///
+    /// Averages, minima and maxima are computed unless other aggregates
+    /// are given.
+    pub fn new<I, S>(table: &str, ts_column: &str, value_columns: I, width: TimeDelta) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            table: table.to_string(),
+            ts_column: ts_column.to_string(),
+            value_columns: value_columns
+                .into_iter()
+                .map(|c| c.as_ref().to_string())
+                .collect(),
+            width,
+            aggregates: vec![Aggregate::Avg, Aggregate::Min, Aggregate::Max],
+            time_bucket: false,
+            fill: None,
+        }
+    }
+
+    /// Compute these aggregates of every value column
+    pub fn aggregates<I: IntoIterator<Item = Aggregate>>(mut self, aggregates: I) -> Self {
+        self.aggregates = aggregates.into_iter().collect();
+        self
+    }
+
+    /// Bucket with TimescaleDB's `time_bucket` on PostgreSQL, which can
+    /// use its chunk indexes
+    pub fn time_bucket(mut self, enabled: bool) -> Self {
+        self.time_bucket = enabled;
+        self
+    }
+
+    /// Add the buckets without rows between the first and last bucket,
+    /// see [`fill_gaps`]
+    pub fn fill(mut self, method: FillMethod) -> Self {
+        self.fill = Some(method);
+        self
+    }
+
+    /// Render the query in `dialect`
+    ///
+    /// Returns a [`BUCKET_COLUMN`] with the start of each bucket followed
+    /// by one `<column>_<aggregate>` column per value column and
+    /// aggregate, ordered by bucket. Buckets without rows are absent.
+    pub fn render(&self, dialect: &dyn Dialect) -> Result<String> {
+        Ok(format!(
+            "{} ORDER BY {}",
+            self.select_sql(dialect)?,
+            dialect.identifier(BUCKET_COLUMN)?
+        ))
+    }
+
+    /// The query without its ORDER BY, which materialized views reject
+    pub(crate) fn select_sql(&self, dialect: &dyn Dialect) -> Result<String> {
+        if self.value_columns.is_empty() || self.aggregates.is_empty() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Time buckets need at least one value column and aggregate",
+            ));
+        }
+        let bucket = bucket_expr(
+            &dialect.identifier(&self.ts_column)?,
+            self.width,
+            dialect,
+            self.time_bucket,
+        )?;
+
+        let mut columns = vec![format!(
+            "{} AS {}",
+            bucket,
+            dialect.identifier(BUCKET_COLUMN)?
+        )];
+        for column in &self.value_columns {
+            let quoted = dialect.identifier(column)?;
+            for aggregate in &self.aggregates {
+                let alias = format!("{}_{}", column, aggregate.as_sql().to_lowercase());
+                columns.push(format!(
+                    "{} AS {}",
+                    aggregate_sql(*aggregate, &quoted, dialect)?,
+                    dialect.quote_identifier(&alias)
+                ));
+            }
+        }
+        Ok(format!(
+            "SELECT {} FROM {} GROUP BY {}",
+            columns.join(", "),
+            dialect.identifier(&self.table)?,
+            bucket
+        ))
+    }
+
+    /// Run the query on a connection
+    ///
+    /// SQLite returns bucket starts as text; they are parsed into
+    /// timestamps so every database yields the same dtype.
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<DataFrame> {
+        let df = conn.execute(&self.render(conn.dialect())?).await?;
+        self.finish(df, conn.dialect().db_type())
+    }
+
+    /// Parse SQLite's bucket starts and fill gaps in fetched buckets
+    pub(crate) fn finish(&self, mut df: DataFrame, db_type: DatabaseType) -> Result<DataFrame> {
+        if db_type == DatabaseType::Sqlite {
+            df = coerce_columns(
+                df,
+                &[(
+                    BUCKET_COLUMN.to_string(),
+                    DataType::Datetime(TimeUnit::Microseconds, None),
+                )],
+            )?;
+        }
+        match self.fill {
+            Some(method) => fill_gaps(df, BUCKET_COLUMN, self.width, None, None, method, &[]),
+            None => Ok(df),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect, SqliteDialect};
+
+    fn minutes(n: i64) -> TimeDelta {
+        TimeDelta::minutes(n)
+    }
+
+    #[test]
+    fn test_render_range() {
+        let start = crate::time::parse_timestamp("2024-03-01").unwrap();
+        let range = TimeRange::new("readings", "ts", start, start + TimeDelta::days(1));
+        let (sql, params) = range.render(&PostgresDialect).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM \"readings\" WHERE \"ts\" >= $1 AND \"ts\" < $2 ORDER BY \"ts\""
+        );
+        assert_eq!(
+            params,
+            vec![
+                Value::Text("2024-03-01 00:00:00".into()),
+                Value::Text("2024-03-02 00:00:00".into())
+            ]
+        );
+
+        let (sql, params) = range
+            .clone()
+            .tags("tag", ["TI-101", "TI-102"])
+            .columns(["ts", "value"])
+            .render(&MssqlDialect)
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT [ts], [value] FROM [readings] WHERE [ts] >= @P1 AND [ts] < @P2 \
+             AND [tag] IN (@P3, @P4) ORDER BY [ts]"
+        );
+        assert_eq!(params[3], Value::Text("TI-102".into()));
+
+        assert!(range
+            .clone()
+            .tags("tag", Vec::<String>::new())
+            .render(&SqliteDialect)
+            .is_err());
+        assert!(TimeRange::new("readings", "ts", start, start)
+            .render(&SqliteDialect)
+            .is_err());
+        assert!(range
+            .clone()
+            .fill(FillMethod::Linear)
+            .render(&SqliteDialect)
+            .is_err());
+    }
+
+    #[test]
+    fn test_resample() {
+        let df = polars::prelude::df!(
+            "ts" => [
+                "2024-03-01 08:00:10",
+                "2024-03-01 08:00:50",
+                "2024-03-01 08:00:20",
+                "2024-03-01 08:02:00",
+            ],
+            "tag" => ["a", "a", "b", "a"],
+            "value" => [1.0, 3.0, 5.0, 7.0],
+        )
+        .unwrap();
+        let resampled =
+            resample(df, "ts", minutes(1), Aggregate::Avg, &["tag".to_string()]).unwrap();
+        assert_eq!(
+            resampled.column("ts").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+        let tags: Vec<_> = resampled
+            .column("tag")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(tags, [Some("a"), Some("b"), Some("a")]);
+        let values: Vec<_> = resampled
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(2.0), Some(5.0), Some(7.0)]);
+
+        let df = polars::prelude::df!("ts" => [1i64], "value" => [1.0]).unwrap();
+        assert!(resample(df.clone(), "ts", minutes(1), Aggregate::Avg, &[]).is_err());
+        assert!(resample(df, "value", TimeDelta::zero(), Aggregate::Avg, &[]).is_err());
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        let ts = |s: &str| crate::time::parse_timestamp(s).unwrap();
+        let df = polars::prelude::df!(
+            "ts" => [ts("2024-03-01 08:00"), ts("2024-03-01 08:03"), ts("2024-03-01 08:01")],
+            "tag" => ["a", "a", "b"],
+            "value" => [Some(1.0), Some(4.0), None],
+        )
+        .unwrap();
+        let fill = |method: FillMethod| {
+            fill_gaps(
+                df.clone(),
+                "ts",
+                minutes(1),
+                Some(ts("2024-03-01 08:00")),
+                Some(ts("2024-03-01 08:04")),
+                method,
+                &["tag".to_string()],
+            )
+            .unwrap()
+        };
+        let values = |df: &DataFrame| -> Vec<Option<f64>> {
+            df.column("value")
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+
+        let nulls = fill(FillMethod::Null);
+        assert_eq!(nulls.height(), 8);
+        assert_eq!(nulls.get_column_names_str(), ["ts", "tag", "value"]);
+        assert_eq!(values(&nulls)[..4], [Some(1.0), None, None, Some(4.0)]);
+        assert_eq!(
+            values(&fill(FillMethod::Forward))[..4],
+            [Some(1.0), Some(1.0), Some(1.0), Some(4.0)]
+        );
+        let linear = values(&fill(FillMethod::Linear));
+        assert_eq!(linear[..4], [Some(1.0), Some(2.0), Some(3.0), Some(4.0)]);
+        // Tag b has no value to start from
+        assert_eq!(linear[4..], [None, None, None, None]);
+
+        assert!(fill_gaps(
+            df,
+            "ts",
+            TimeDelta::microseconds(1),
+            None,
+            None,
+            FillMethod::Null,
+            &[]
+        )
+        .is_err());
+        assert_eq!("ffill".parse::<FillMethod>().unwrap(), FillMethod::Forward);
+        assert!("cubic".parse::<FillMethod>().is_err());
+    }
+
+    #[test]
+    fn test_render_postgres() {
+        let buckets = TimeBuckets::new("readings", "ts", ["value"], minutes(5))
+            .aggregates([Aggregate::Avg, Aggregate::Count]);
+        assert_eq!(
+            buckets.render(&PostgresDialect).unwrap(),
+            "SELECT date_bin(INTERVAL '300 seconds', \"ts\", TIMESTAMP '2000-01-01 00:00:00') \
+             AS \"bucket\", AVG(CAST(\"value\" AS DOUBLE PRECISION)) AS \"value_avg\", \
+             COUNT(\"value\") AS \"value_count\" FROM \"readings\" \
+             GROUP BY date_bin(INTERVAL '300 seconds', \"ts\", TIMESTAMP '2000-01-01 00:00:00') \
+             ORDER BY \"bucket\""
+        );
+        let sql = TimeBuckets::new("readings", "ts", ["value"], minutes(60))
+            .render(&PostgresDialect)
+            .unwrap();
+        assert!(sql.starts_with("SELECT date_trunc('hour', \"ts\") AS \"bucket\""));
+        let sql = TimeBuckets::new("readings", "ts", ["value"], minutes(60))
+            .time_bucket(true)
+            .render(&PostgresDialect)
+            .unwrap();
+        assert!(sql.starts_with("SELECT time_bucket(INTERVAL '3600 seconds', \"ts\")"));
+    }
+
+    #[test]
+    fn test_render_sqlite_and_mssql() {
+        let buckets =
+            TimeBuckets::new("readings", "ts", ["value"], minutes(15)).aggregates([Aggregate::Max]);
+        assert_eq!(
+            buckets.render(&SqliteDialect).unwrap(),
+            "SELECT datetime(CAST(strftime('%s', \"ts\") AS INTEGER) / 900 * 900, 'unixepoch') \
+             AS \"bucket\", MAX(\"value\") AS \"value_max\" FROM \"readings\" \
+             GROUP BY datetime(CAST(strftime('%s', \"ts\") AS INTEGER) / 900 * 900, 'unixepoch') \
+             ORDER BY \"bucket\""
+        );
+        assert!(buckets.render(&MssqlDialect).unwrap().starts_with(
+            "SELECT DATEADD(minute, DATEDIFF(minute, CAST('2000-01-01 00:00:00' AS DATETIME2), \
+             [ts]) / 15 * 15, CAST('2000-01-01 00:00:00' AS DATETIME2)) AS [bucket]"
+        ));
+        let sql = TimeBuckets::new("readings", "ts", ["value"], TimeDelta::seconds(10))
+            .render(&MssqlDialect)
+            .unwrap();
+        assert!(sql.contains("DATEDIFF(second, "));
+    }
+
+    #[test]
+    fn test_invalid_buckets() {
+        let render = |width: TimeDelta, columns: &[&str]| {
+            TimeBuckets::new("readings", "ts", columns, width).render(&SqliteDialect)
+        };
+        assert!(render(TimeDelta::milliseconds(500), &["value"]).is_err());
+        assert!(render(minutes(5), &[]).is_err());
+    }
+}
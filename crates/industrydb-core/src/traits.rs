@@ -3,8 +3,18 @@
 use async_trait::async_trait;
 use polars::prelude::*;
 use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::error::Result;
+use crate::copy::{CopyFormat, CsvCopyOptions};
+use crate::dialect::{Dialect, SelectOptions};
+use crate::error::{IndustryDbError, Result};
+use crate::metrics::QueryMetrics;
+use crate::params::{bind_named, parameter_set_error, Value};
+use crate::procedure::{first_row, function_call_sql, ProcedureArg, ProcedureResult};
+use crate::sink::FrameSink;
+use crate::sql::{ensure_returns_rows, read_script};
 
 /// Core trait that all database connectors must implement
 #[async_trait]
@@ -12,9 +22,350 @@ pub trait DatabaseConnector: Send + Sync {
     /// Get the database type name
     fn db_type(&self) -> &str;
 
+    /// SQL dialect used to build statements for this database
+    fn dialect(&self) -> &'static dyn Dialect;
+
+    /// Resource accounting of the queries run through this connector
+    fn metrics(&self) -> &QueryMetrics;
+
     /// Execute a raw SQL query and return a DataFrame
+    ///
+    /// Fails for statements that cannot return rows, such as DDL or an INSERT
+    /// without RETURNING; run those with [`execute_statement`].
+    ///
+    /// [`execute_statement`]: DatabaseConnector::execute_statement
     async fn execute(&self, sql: &str) -> Result<DataFrame>;
 
+    /// Execute a SQL query with values bound to its placeholders
+    ///
+    /// Placeholders use the database's own syntax, see [`Dialect::placeholder`].
+    /// Text values are bound as text, so PostgreSQL needs a cast where another
+    /// type is expected, e.g. `ts >= $1::timestamptz`. Fails like [`execute`]
+    /// for statements that cannot return rows.
+    ///
+    /// [`execute`]: DatabaseConnector::execute
+    async fn execute_with_params(&self, sql: &str, params: &[Value]) -> Result<DataFrame>;
+
+    /// Execute a SQL query and stream its rows into `sink`
+    ///
+    /// The built-in connectors hand each chunk of [`DECODE_CHUNK_ROWS`]
+    /// rows to the sink as soon as it is decoded and keep none of them, so
+    /// memory stays bounded however large the result. Only the first
+    /// result set of a SQL Server batch is streamed. The default writes
+    /// the whole result of [`execute_with_params`] as one chunk.
+    ///
+    /// [`DECODE_CHUNK_ROWS`]: crate::metrics::DECODE_CHUNK_ROWS
+    /// [`execute_with_params`]: DatabaseConnector::execute_with_params
+    async fn execute_into(
+        &self,
+        sql: &str,
+        params: &[Value],
+        sink: &mut dyn FrameSink,
+    ) -> Result<()> {
+        let mut df = self.execute_with_params(sql, params).await?;
+        sink.write(&mut df)
+    }
+
+    /// Execute a query and return every result set it produces
+    ///
+    /// A SQL Server batch or stored procedure can return several result
+    /// sets, of which [`execute_with_params`] keeps only the first. Result
+    /// sets without rows keep their columns. The default returns the single
+    /// result of [`execute_with_params`].
+    ///
+    /// [`execute_with_params`]: DatabaseConnector::execute_with_params
+    async fn execute_result_sets(&self, sql: &str, params: &[Value]) -> Result<Vec<DataFrame>> {
+        Ok(vec![self.execute_with_params(sql, params).await?])
+    }
+
+    /// Execute a statement (INSERT, UPDATE, DDL, ...) and return the number
+    /// of rows it affected
+    ///
+    /// Any rows the statement returns are discarded. Parameters are bound as
+    /// in [`execute_with_params`]. DDL reports 0 rows.
+    ///
+    /// [`execute_with_params`]: DatabaseConnector::execute_with_params
+    async fn execute_statement(&self, sql: &str, params: &[Value]) -> Result<u64>;
+
+    /// Execute one statement once per parameter set and return the total
+    /// number of rows affected
+    ///
+    /// This default runs [`execute_statement`] per set, so a failure leaves
+    /// the earlier sets applied. Connectors override it to prepare the
+    /// statement once and run every set in one transaction, so that a
+    /// failure applies none of them.
+    ///
+    /// [`execute_statement`]: DatabaseConnector::execute_statement
+    async fn execute_many(&self, sql: &str, param_sets: &[Vec<Value>]) -> Result<u64> {
+        let mut affected = 0;
+        for (index, params) in param_sets.iter().enumerate() {
+            affected += self
+                .execute_statement(sql, params)
+                .await
+                .map_err(|e| parameter_set_error(index, e))?;
+        }
+        Ok(affected)
+    }
+
+    /// Execute statements in order in one transaction and return the total
+    /// number of rows affected
+    ///
+    /// This default runs [`execute_statement`] per statement, so a failure
+    /// leaves the earlier ones applied. Connectors override it to run them
+    /// on one connection in a transaction that a failure rolls back.
+    ///
+    /// [`execute_statement`]: DatabaseConnector::execute_statement
+    async fn execute_transaction(&self, statements: &[String]) -> Result<u64> {
+        let mut affected = 0;
+        for statement in statements {
+            affected += self.execute_statement(statement, &[]).await?;
+        }
+        Ok(affected)
+    }
+
+    /// Execute a SQL query with `:name` / `@name` parameters
+    ///
+    /// The names are rewritten to this database's placeholders, see
+    /// [`bind_named`].
+    async fn execute_named(&self, sql: &str, params: &HashMap<String, Value>) -> Result<DataFrame> {
+        let (sql, values) = bind_named(sql, params, self.dialect())?;
+        self.execute_with_params(&sql, &values).await
+    }
+
+    /// Run a query or statement and report how it went
+    ///
+    /// Statements that cannot return rows (see [`ensure_returns_rows`]) run
+    /// through [`execute_statement`] and report `rows_affected`; anything
+    /// else runs through [`execute_with_params`] and returns its rows.
+    ///
+    /// [`execute_statement`]: DatabaseConnector::execute_statement
+    /// [`execute_with_params`]: DatabaseConnector::execute_with_params
+    async fn query(&self, sql: &str, params: &[Value]) -> Result<QueryResult> {
+        let started = Instant::now();
+        if ensure_returns_rows(sql).is_err() {
+            let rows_affected = self.execute_statement(sql, params).await?;
+            return Ok(QueryResult {
+                frame: DataFrame::empty(),
+                rows_affected: Some(rows_affected),
+                warnings: Vec::new(),
+                elapsed: started.elapsed(),
+            });
+        }
+
+        let frame = self.execute_with_params(sql, params).await?;
+        let mut warnings = Vec::new();
+        if frame.width() == 0 {
+            warnings.push("The query returned no rows, so its columns are unknown".to_string());
+        }
+        Ok(QueryResult {
+            frame,
+            rows_affected: None,
+            warnings,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// First row of a query as `(column, value)` pairs, or `None` when it
+    /// returns no rows
+    ///
+    /// Meant for lookups such as a configuration row by key; only the first
+    /// row is converted, so add a `LIMIT` to queries that may match many.
+    async fn fetch_one(&self, sql: &str, params: &[Value]) -> Result<Option<Vec<(String, Value)>>> {
+        let df = self.execute_with_params(sql, params).await?;
+        if df.height() == 0 {
+            return Ok(None);
+        }
+        first_row(&df).map(Some)
+    }
+
+    /// First column of the first row of a query, or `None` when it returns
+    /// no rows
+    ///
+    /// For single values such as `SELECT max(ts) FROM readings`; an aggregate
+    /// over no rows gives `Some(Value::Null)`.
+    async fn fetch_scalar(&self, sql: &str, params: &[Value]) -> Result<Option<Value>> {
+        let df = self.execute_with_params(sql, params).await?;
+        let column = df
+            .get_columns()
+            .first()
+            .ok_or_else(|| IndustryDbError::query_error("Query returned no columns"))?;
+        if column.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Value::from(column.get(0)?)))
+    }
+
+    /// Run a script of several statements in order
+    ///
+    /// The script is split with [`Dialect::split_script`]: into `GO`
+    /// batches on SQL Server, into `;`-separated statements elsewhere. Stops
+    /// at the first failure, naming the batch and the line it starts on.
+    /// Returns the number of batches run, counting `GO n` repeats.
+    async fn execute_batch(&self, sql: &str) -> Result<usize> {
+        let batches = self.dialect().split_script(sql);
+        let mut executed = 0;
+        for (index, batch) in batches.iter().enumerate() {
+            for _ in 0..batch.repeat {
+                self.execute_statement(&batch.sql, &[]).await.map_err(|e| {
+                    IndustryDbError::query_error(format!(
+                        "Batch {} of {} (line {}) failed: {}",
+                        index + 1,
+                        batches.len(),
+                        batch.line,
+                        e
+                    ))
+                })?;
+                executed += 1;
+            }
+        }
+        Ok(executed)
+    }
+
+    /// Run a SQL script file with [`execute_batch`]
+    ///
+    /// The file is read with [`read_script`], so scripts saved as UTF-16 by
+    /// SQL Server Management Studio work. Failures name the file.
+    ///
+    /// [`execute_batch`]: DatabaseConnector::execute_batch
+    async fn execute_script(&self, path: &Path) -> Result<usize> {
+        let sql = read_script(path)?;
+        self.execute_batch(&sql).await.map_err(|e| match e {
+            IndustryDbError::QueryError(msg) => {
+                IndustryDbError::QueryError(format!("{}: {}", path.display(), msg))
+            }
+            other => other,
+        })
+    }
+
+    /// Call a function in the FROM clause and return its rows
+    ///
+    /// Runs `SELECT * FROM name(...)`, so set-returning and table-valued
+    /// functions return all their rows and a scalar function one row. An
+    /// argument with a SQL type is cast to it, which selects overloads the
+    /// bound type would miss, e.g. an `integer` parameter on PostgreSQL
+    /// where integers bind as `bigint`. See [`function_call_sql`].
+    async fn call_function(&self, name: &str, args: &[ProcedureArg]) -> Result<DataFrame> {
+        let (sql, params) = function_call_sql(self.dialect(), name, args)?;
+        self.execute_with_params(&sql, &params).await
+    }
+
+    /// Call a stored procedure and return its result sets, output
+    /// parameters and return value
+    ///
+    /// `name` is quoted like a table name. SQL Server and PostgreSQL
+    /// override this; the default fails, as SQLite has no procedures.
+    async fn call_procedure(&self, name: &str, args: &[ProcedureArg]) -> Result<ProcedureResult> {
+        let _ = (name, args);
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support stored procedures",
+            self.db_type()
+        )))
+    }
+
+    /// Attach the database file at `path` under `alias`, so queries can
+    /// join across files (`SELECT ... FROM alias.readings`)
+    ///
+    /// Applies to every connection of the pool, including those opened
+    /// later. SQLite overrides this; the default fails.
+    async fn attach(&self, path: &Path, alias: &str) -> Result<()> {
+        let _ = (path, alias);
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support attaching database files",
+            self.db_type()
+        )))
+    }
+
+    /// Detach the database attached under `alias`
+    async fn detach(&self, alias: &str) -> Result<()> {
+        let _ = alias;
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support attaching database files",
+            self.db_type()
+        )))
+    }
+
+    /// Copy the database to the file at `path` while it stays in use
+    ///
+    /// The copy is a consistent snapshot; an existing file is overwritten.
+    /// SQLite overrides this with its online backup API; the default fails.
+    async fn backup_to(&self, path: &Path) -> Result<()> {
+        let _ = path;
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support file backups",
+            self.db_type()
+        )))
+    }
+
+    /// Replace the database's contents with the file at `path`, e.g. one
+    /// written by [`backup_to`]
+    ///
+    /// [`backup_to`]: DatabaseConnector::backup_to
+    async fn restore_from(&self, path: &Path) -> Result<()> {
+        let _ = path;
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support file backups",
+            self.db_type()
+        )))
+    }
+
+    /// Run a large query through ConnectorX, which reads straight into
+    /// Arrow columns instead of decoding row by row
+    ///
+    /// Much faster than [`execute`] on big results, but takes no parameters
+    /// and opens its own connections outside the pool. PostgreSQL and SQL
+    /// Server built with the `connectorx` feature override this; the
+    /// default fails.
+    ///
+    /// [`execute`]: DatabaseConnector::execute
+    async fn fast_read(&self, sql: &str) -> Result<DataFrame> {
+        let _ = sql;
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support ConnectorX reads",
+            self.db_type()
+        )))
+    }
+
+    /// Stream the rows of `sql` to `out` with the database's bulk export,
+    /// returning the number of bytes written
+    ///
+    /// The rows arrive in the server's own encoding as fast as it produces
+    /// them, without being decoded into a DataFrame. PostgreSQL overrides
+    /// this with `COPY (...) TO STDOUT`; the default fails.
+    async fn copy_out(
+        &self,
+        sql: &str,
+        format: CopyFormat,
+        out: &mut (dyn Write + Send),
+    ) -> Result<u64> {
+        let _ = (sql, format, out);
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support COPY exports",
+            self.db_type()
+        )))
+    }
+
+    /// Load the CSV read from `input` into `table` with the database's bulk
+    /// import, returning the number of rows loaded
+    ///
+    /// The input is sent as is in chunks of [`COPY_CHUNK_BYTES`], so the
+    /// columns must match the table's in number and order, and a bad row
+    /// fails the whole load. PostgreSQL overrides this with
+    /// `COPY ... FROM STDIN`; the default fails.
+    ///
+    /// [`COPY_CHUNK_BYTES`]: crate::copy::COPY_CHUNK_BYTES
+    async fn copy_in(
+        &self,
+        table: &str,
+        options: &CsvCopyOptions,
+        input: &mut (dyn Read + Send),
+    ) -> Result<u64> {
+        let _ = (table, options, input);
+        Err(IndustryDbError::NotImplemented(format!(
+            "{} does not support COPY imports",
+            self.db_type()
+        )))
+    }
+
     /// Check if the connection is alive
     async fn is_alive(&self) -> bool;
 
@@ -23,6 +374,35 @@ pub trait DatabaseConnector: Send + Sync {
 
     /// Check if connection is closed
     fn is_closed(&self) -> bool;
+
+    /// List the schemas visible to this connection
+    async fn list_schemas(&self) -> Result<Vec<String>>;
+
+    /// List the tables in a schema (the connection's default schema when `None`)
+    async fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>>;
+
+    /// Describe the columns of a table
+    ///
+    /// Returns a DataFrame with `column_name`, `data_type`, `is_nullable`
+    /// and `column_default` columns, one row per column in table order.
+    async fn describe_table(&self, table: &str) -> Result<DataFrame>;
+
+    /// Primary key columns of a table
+    ///
+    /// Returns a DataFrame with `column_name` and `ordinal_position`.
+    async fn primary_keys(&self, table: &str) -> Result<DataFrame>;
+
+    /// Indexes defined on a table, one row per indexed column
+    ///
+    /// Returns a DataFrame with `index_name`, `column_name`, `is_unique`
+    /// and `ordinal_position`.
+    async fn indexes(&self, table: &str) -> Result<DataFrame>;
+
+    /// Foreign keys defined on a table, one row per referencing column
+    ///
+    /// Returns a DataFrame with `constraint_name`, `column_name`,
+    /// `referenced_table` and `referenced_column`.
+    async fn foreign_keys(&self, table: &str) -> Result<DataFrame>;
 }
 
 /// CRUD operations trait
@@ -31,25 +411,157 @@ pub trait CrudOperations: DatabaseConnector {
     /// Insert data into a table
     async fn insert(&self, table: &str, data: DataFrame) -> Result<usize>;
 
+    /// Insert rows, updating those whose key columns already exist
+    ///
+    /// `key_columns` must be covered by a primary key or unique constraint
+    /// (PostgreSQL, SQLite) or identify at most one row (SQL Server MERGE).
+    async fn upsert(&self, table: &str, data: DataFrame, key_columns: &[String]) -> Result<usize> {
+        self.upsert_keeping(table, data, key_columns, &[]).await
+    }
+
+    /// Upsert as [`upsert`](Self::upsert), writing the `keep` columns for
+    /// new rows only and leaving them as they are in existing ones
+    async fn upsert_keeping(
+        &self,
+        table: &str,
+        data: DataFrame,
+        key_columns: &[String],
+        keep: &[String],
+    ) -> Result<usize>;
+
     /// Select data from a table
+    ///
+    /// `columns` entries are column names or aggregates such as
+    /// `"avg(value) as avg_v"`, see
+    /// [`select_list_sql`](crate::query::select_list_sql). `options` carries
+    /// DISTINCT, the GROUP BY, HAVING and ORDER BY clause bodies and the row
+    /// limit and offset, which are rendered in the database's own syntax
+    /// (LIMIT/OFFSET, TOP or OFFSET/FETCH). `params` are bound to
+    /// placeholders in `where_clause`, then in `options.having`.
     async fn select(
         &self,
         table: &str,
         columns: Option<&[String]>,
         where_clause: Option<&str>,
-        limit: Option<usize>,
+        params: &[Value],
+        options: &SelectOptions,
     ) -> Result<DataFrame>;
 
+    /// Number of rows matching `where_clause` (all rows when `None`)
+    ///
+    /// Runs `SELECT COUNT(*)`, so no rows are transferred.
+    async fn count(
+        &self,
+        table: &str,
+        where_clause: Option<&str>,
+        params: &[Value],
+    ) -> Result<u64> {
+        let dialect = self.dialect();
+        let sql = dialect.select_sql(
+            "COUNT(*)",
+            &dialect.identifier(table)?,
+            where_clause,
+            &SelectOptions::default(),
+        );
+        let df = self.execute_with_params(&sql, params).await?;
+        let count = df
+            .get_columns()
+            .first()
+            .map(|c| c.cast(&DataType::UInt64))
+            .transpose()?
+            .and_then(|c| c.u64().ok().and_then(|c| c.get(0)));
+        count.ok_or_else(|| IndustryDbError::query_error("COUNT(*) returned no value"))
+    }
+
+    /// Whether any row matches `where_clause` (any row at all when `None`)
+    ///
+    /// Selects a constant from at most one row, so the database can stop at
+    /// the first match.
+    async fn exists(
+        &self,
+        table: &str,
+        where_clause: Option<&str>,
+        params: &[Value],
+    ) -> Result<bool> {
+        let dialect = self.dialect();
+        let sql = dialect.select_sql(
+            "1",
+            &dialect.identifier(table)?,
+            where_clause,
+            &SelectOptions {
+                limit: Some(1),
+                ..Default::default()
+            },
+        );
+        let df = self.execute_with_params(&sql, params).await?;
+        Ok(df.height() > 0)
+    }
+
     /// Update rows in a table
+    ///
+    /// `params` are bound to placeholders in `where_clause`.
     async fn update(
         &self,
         table: &str,
         values: &HashMap<String, String>,
         where_clause: Option<&str>,
+        params: &[Value],
     ) -> Result<usize>;
 
     /// Delete rows from a table
-    async fn delete(&self, table: &str, where_clause: Option<&str>) -> Result<usize>;
+    ///
+    /// `params` are bound to placeholders in `where_clause`.
+    async fn delete(
+        &self,
+        table: &str,
+        where_clause: Option<&str>,
+        params: &[Value],
+    ) -> Result<usize>;
+
+    /// Create a table whose columns match a Polars schema
+    ///
+    /// Polars dtypes are mapped to the closest native column type of the
+    /// database. Pass `df.schema()` to create a table for a DataFrame.
+    async fn create_table(&self, table: &str, schema: &Schema, if_not_exists: bool) -> Result<()>;
+
+    /// Check whether a table exists
+    async fn table_exists(&self, table: &str) -> Result<bool>;
+
+    /// Drop a table
+    async fn drop_table(&self, table: &str, if_exists: bool) -> Result<()>;
+
+    /// Remove all rows from a table, keeping its definition
+    async fn truncate(&self, table: &str) -> Result<()>;
+
+    /// Rename a table within its schema
+    ///
+    /// `new_name` is the bare table name; a schema qualifier on it is ignored.
+    async fn rename_table(&self, table: &str, new_name: &str) -> Result<()>;
+}
+
+/// Result of [`DatabaseConnector::query`]
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    /// Rows returned; empty for statements
+    pub frame: DataFrame,
+    /// Rows inserted, updated or deleted by a statement; `None` for queries
+    pub rows_affected: Option<u64>,
+    /// Things the caller may want to know, e.g. that an empty result has
+    /// no column information
+    pub warnings: Vec<String>,
+    /// Wall time from sending the SQL to having the result
+    pub elapsed: Duration,
+}
+
+impl QueryResult {
+    /// Name and type of each column
+    pub fn column_types(&self) -> Vec<(String, DataType)> {
+        self.frame
+            .get_columns()
+            .iter()
+            .map(|c| (c.name().to_string(), c.dtype().clone()))
+            .collect()
+    }
 }
 
 /// Result of an operation
@@ -0,0 +1,179 @@
+//! Copying whole tables between databases
+//!
+//! Streams a table from any connector to any other a page of rows at a
+//! time, e.g. from a plant-floor SQLite file to a central PostgreSQL server,
+//! without holding the table in memory. Column types travel as Polars
+//! dtypes, so the target table is created with the target database's own
+//! column type for each.
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dialect::SelectOptions;
+use crate::error::{IndustryDbError, Result};
+use crate::schema;
+use crate::traits::CrudOperations;
+
+/// Rows read and inserted per page when none is configured
+pub const DEFAULT_COPY_CHUNK_ROWS: usize = 10_000;
+
+/// What happens to a target table that already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyMode {
+    /// Keep its rows and add the copied ones
+    #[default]
+    Append,
+    /// Drop it and create it afresh from the source's columns
+    Replace,
+}
+
+impl std::str::FromStr for CopyMode {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "append" => Ok(CopyMode::Append),
+            "replace" => Ok(CopyMode::Replace),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported copy mode: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// What to copy and how
+#[derive(Debug, Clone)]
+pub struct TableCopyConfig {
+    /// Table to read from
+    pub source_table: String,
+    /// Table to write to
+    pub target_table: String,
+    /// What happens to an existing target table
+    pub mode: CopyMode,
+    /// Rows read and inserted per page
+    pub chunk_rows: usize,
+}
+
+impl TableCopyConfig {
+    /// Create a config appending `table` to a table of the same name
+    pub fn new(table: &str) -> Self {
+        Self {
+            source_table: table.to_string(),
+            target_table: table.to_string(),
+            mode: CopyMode::default(),
+            chunk_rows: DEFAULT_COPY_CHUNK_ROWS,
+        }
+    }
+}
+
+/// Progress of a table copy, reported after every page
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableCopyProgress {
+    /// Pages copied
+    pub chunks_done: usize,
+    /// Rows inserted into the target
+    pub rows_copied: usize,
+    /// Rows in the source when the copy started
+    pub rows_total: usize,
+    /// Whether the last page has been copied
+    pub complete: bool,
+}
+
+/// Copy every row of `config.source_table` into `config.target_table`
+///
+/// Pages are read in primary key order where the source table has one, so
+/// each row lands in exactly one page; rows written to the source while it
+/// is copied may be skipped or copied twice. The target table
+/// is created from the dtypes of the first page when it does not exist;
+/// with [`CopyMode::Replace`] it is dropped first, along with its indexes
+/// and constraints. A failure leaves the pages already copied in place.
+pub async fn copy_table<S, T>(
+    source: &S,
+    target: &T,
+    config: &TableCopyConfig,
+    on_progress: Option<&(dyn Fn(&TableCopyProgress) + Send + Sync)>,
+) -> Result<TableCopyProgress>
+where
+    S: CrudOperations + ?Sized,
+    T: CrudOperations + ?Sized,
+{
+    if config.chunk_rows == 0 {
+        return Err(IndustryDbError::invalid_parameter(
+            "chunk_rows must be positive",
+        ));
+    }
+
+    let keys = schema::first_column_strings(&source.primary_keys(&config.source_table).await?)?;
+    let order_by = if keys.is_empty() {
+        None
+    } else {
+        Some(source.dialect().identifiers(&keys)?.join(", "))
+    };
+    let mut progress = TableCopyProgress {
+        rows_total: source.count(&config.source_table, None, &[]).await? as usize,
+        ..Default::default()
+    };
+
+    let mut offset = 0;
+    while !progress.complete {
+        let options = SelectOptions {
+            order_by: order_by.clone(),
+            limit: Some(config.chunk_rows),
+            offset: Some(offset),
+            ..Default::default()
+        };
+        let chunk = source
+            .select(&config.source_table, None, None, &[], &options)
+            .await?;
+        if offset == 0 {
+            prepare_target(target, config, &chunk.schema()).await?;
+        }
+
+        let rows = chunk.height();
+        if rows > 0 {
+            progress.rows_copied += target.insert(&config.target_table, chunk).await?;
+        }
+        offset += rows;
+        progress.chunks_done += 1;
+        progress.complete = rows < config.chunk_rows;
+
+        if let Some(callback) = on_progress {
+            callback(&progress);
+        }
+    }
+    Ok(progress)
+}
+
+/// Create, or drop and recreate, the target table for rows of `schema`
+async fn prepare_target<T: CrudOperations + ?Sized>(
+    target: &T,
+    config: &TableCopyConfig,
+    schema: &Schema,
+) -> Result<()> {
+    // An empty result can come without columns, leaving nothing to create
+    if schema.is_empty() {
+        if config.mode == CopyMode::Replace && target.table_exists(&config.target_table).await? {
+            target.truncate(&config.target_table).await?;
+        }
+        return Ok(());
+    }
+    if config.mode == CopyMode::Replace {
+        target.drop_table(&config.target_table, true).await?;
+    }
+    target
+        .create_table(&config.target_table, schema, true)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_mode_from_str() {
+        assert_eq!("Replace".parse::<CopyMode>().unwrap(), CopyMode::Replace);
+        assert_eq!("append".parse::<CopyMode>().unwrap(), CopyMode::Append);
+        assert!("merge".parse::<CopyMode>().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Value of a tag at a point in time
+//!
+//! Historians store a value when it changes, so the value at a given time
+//! is that of the last row at or before it, or a linear interpolation
+//! between the rows around it. The last row per tag is found in the
+//! database with a `LATERAL` join on PostgreSQL, `CROSS APPLY` on SQL
+//! Server and `ROW_NUMBER()` on SQLite, so only one row per tag is fetched.
+//!
+//! ```ignore
+//! let df = ValueAt::new("readings", "ts", ["TI-101", "TI-102"], at)
+//!     .mode(AtMode::Interpolated)
+//!     .fetch(&conn)
+//!     .await?;
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use polars::prelude::{
+    col, DataFrame, DataType, Int64Chunked, IntoLazy, IntoSeries, JoinArgs, JoinType,
+    NewChunkedArray, Series, TimeUnit,
+};
+
+use crate::config::DatabaseType;
+use crate::dialect::Dialect;
+use crate::error::{IndustryDbError, Result};
+use crate::params::Value;
+use crate::time::format_timestamp;
+use crate::timeseries::with_micros;
+use crate::traits::DatabaseConnector;
+
+/// How [`ValueAt`] derives the value at its timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AtMode {
+    /// Value of the last row at or before the timestamp
+    #[default]
+    Previous,
+    /// Linear interpolation between the last row at or before the
+    /// timestamp and the first row after it; null unless both exist
+    Interpolated,
+}
+
+impl std::str::FromStr for AtMode {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "previous" | "prev" => Ok(AtMode::Previous),
+            "interpolated" | "interpolate" | "linear" => Ok(AtMode::Interpolated),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported value-at mode: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Values of some tags at one timestamp
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueAt {
+    table: String,
+    ts_column: String,
+    tag_column: String,
+    value_column: String,
+    tags: Vec<String>,
+    at: NaiveDateTime,
+    mode: AtMode,
+}
+
+impl ValueAt {
+    /// Values of `tags` in narrow `table` at `at`, from the `tag` and
+    /// `value` columns unless [`columns`](Self::columns) names others
+    pub fn new<I, S>(table: &str, ts_column: &str, tags: I, at: NaiveDateTime) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            table: table.to_string(),
+            ts_column: ts_column.to_string(),
+            tag_column: "tag".to_string(),
+            value_column: "value".to_string(),
+            tags: tags.into_iter().map(|t| t.as_ref().to_string()).collect(),
+            at,
+            mode: AtMode::Previous,
+        }
+    }
+
+    /// Read tags from `tag_column` and values from `value_column`
+    pub fn columns(mut self, tag_column: &str, value_column: &str) -> Self {
+        self.tag_column = tag_column.to_string();
+        self.value_column = value_column.to_string();
+        self
+    }
+
+    /// How the value is derived
+    pub fn mode(mut self, mode: AtMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Render the query for each tag's last row at or before the timestamp,
+    /// or with `after` its first row after it, and its parameters
+    pub fn render(&self, dialect: &dyn Dialect, after: bool) -> Result<(String, Vec<Value>)> {
+        if self.tags.is_empty() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Value-at query must name at least one tag",
+            ));
+        }
+        let table = dialect.identifier(&self.table)?;
+        let ts = dialect.identifier(&self.ts_column)?;
+        let tag = dialect.identifier(&self.tag_column)?;
+        let value = dialect.identifier(&self.value_column)?;
+        let (op, order) = if after { (">", "ASC") } else { ("<=", "DESC") };
+
+        let mut params: Vec<Value> = self.tags.iter().cloned().map(Value::Text).collect();
+        params.push(Value::Text(format_timestamp(&self.at)));
+        let at = dialect.placeholder(params.len());
+        let tags: Vec<String> = (1..params.len()).map(|i| dialect.placeholder(i)).collect();
+
+        let sql = match dialect.db_type() {
+            DatabaseType::Postgres => format!(
+                "SELECT wanted.tag AS {tag}, last_row.{ts}, last_row.{value} \
+                 FROM (VALUES ({wanted})) AS wanted(tag) \
+                 CROSS JOIN LATERAL (SELECT {ts}, {value} FROM {table} \
+                 WHERE {tag} = wanted.tag AND {ts} {op} {at} \
+                 ORDER BY {ts} {order} LIMIT 1) AS last_row",
+                wanted = tags.join("), ("),
+            ),
+            DatabaseType::Mssql => format!(
+                "SELECT wanted.tag AS {tag}, last_row.{ts}, last_row.{value} \
+                 FROM (VALUES ({wanted})) AS wanted(tag) \
+                 CROSS APPLY (SELECT TOP (1) {ts}, {value} FROM {table} \
+                 WHERE {tag} = wanted.tag AND {ts} {op} {at} \
+                 ORDER BY {ts} {order}) AS last_row",
+                wanted = tags.join("), ("),
+            ),
+            DatabaseType::Sqlite => format!(
+                "SELECT {tag}, {ts}, {value} FROM (SELECT {tag}, {ts}, {value}, \
+                 ROW_NUMBER() OVER (PARTITION BY {tag} ORDER BY {ts} {order}) AS row_num \
+                 FROM {table} WHERE {tag} IN ({wanted}) AND {ts} {op} {at}) AS ranked \
+                 WHERE row_num = 1",
+                wanted = tags.join(", "),
+            ),
+        };
+        Ok((sql, params))
+    }
+
+    /// Run the query on a connection
+    ///
+    /// Returns one row per tag, in the order given, with the tag, a
+    /// timestamp and the value. In [`AtMode::Previous`] the timestamp is
+    /// that of the row the value comes from, null with the value when the
+    /// tag has no row at or before the timestamp; interpolated rows carry
+    /// the requested timestamp.
+    pub async fn fetch<C: DatabaseConnector + ?Sized>(&self, conn: &C) -> Result<DataFrame> {
+        let (sql, params) = self.render(conn.dialect(), false)?;
+        let before = conn.execute_with_params(&sql, &params).await?;
+        match self.mode {
+            AtMode::Previous => self.previous(before),
+            AtMode::Interpolated => {
+                let (sql, params) = self.render(conn.dialect(), true)?;
+                let after = conn.execute_with_params(&sql, &params).await?;
+                self.interpolated(before, after)
+            }
+        }
+    }
+
+    fn tag_series(&self) -> Series {
+        Series::new(self.tag_column.as_str().into(), self.tags.clone())
+    }
+
+    /// One row per tag without a timestamp or value
+    fn no_values(&self) -> Result<DataFrame> {
+        let n = self.tags.len();
+        Ok(DataFrame::new(vec![
+            self.tag_series().into(),
+            Series::full_null(
+                self.ts_column.as_str().into(),
+                n,
+                &DataType::Datetime(TimeUnit::Microseconds, None),
+            )
+            .into(),
+            Series::full_null(self.value_column.as_str().into(), n, &DataType::Float64).into(),
+        ])?)
+    }
+
+    fn previous(&self, rows: DataFrame) -> Result<DataFrame> {
+        if rows.height() == 0 {
+            return self.no_values();
+        }
+        let (mut rows, _) = with_micros(rows, &self.ts_column)?;
+        let tags = rows.column(&self.tag_column)?.cast(&DataType::String)?;
+        rows.with_column(tags)?;
+        let wanted = DataFrame::new(vec![self.tag_series().into()])?;
+        let on = [col(self.tag_column.as_str())];
+        Ok(wanted
+            .lazy()
+            .join(rows.lazy(), on.clone(), on, JoinArgs::new(JoinType::Left))
+            .collect()?)
+    }
+
+    /// Timestamp in microseconds and value of each tag's row
+    fn by_tag(&self, rows: DataFrame) -> Result<HashMap<String, (i64, Option<f64>)>> {
+        if rows.height() == 0 {
+            return Ok(HashMap::new());
+        }
+        let (rows, _) = with_micros(rows, &self.ts_column)?;
+        let values = rows.column(&self.value_column)?;
+        if !values.dtype().is_numeric() {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Cannot interpolate column '{}' of type {}",
+                self.value_column,
+                values.dtype()
+            )));
+        }
+        let values = values.cast(&DataType::Float64)?;
+        let tags = rows.column(&self.tag_column)?.cast(&DataType::String)?;
+        let times = rows
+            .column(&self.ts_column)?
+            .as_materialized_series()
+            .to_physical_repr()
+            .into_owned();
+        Ok(tags
+            .str()?
+            .into_iter()
+            .zip(times.i64()?)
+            .zip(values.f64()?)
+            .filter_map(|((tag, ts), value)| Some((tag?.to_string(), (ts?, value))))
+            .collect())
+    }
+
+    fn interpolated(&self, before: DataFrame, after: DataFrame) -> Result<DataFrame> {
+        let at = self.at.and_utc().timestamp_micros();
+        let before = self.by_tag(before)?;
+        let after = self.by_tag(after)?;
+        let values: Vec<Option<f64>> = self
+            .tags
+            .iter()
+            .map(|tag| match (before.get(tag), after.get(tag)) {
+                (Some((t0, v0)), _) if *t0 == at => *v0,
+                (Some((t0, Some(v0))), Some((t1, Some(v1)))) => {
+                    Some(v0 + (v1 - v0) * (at - t0) as f64 / (t1 - t0) as f64)
+                }
+                _ => None,
+            })
+            .collect();
+        let times =
+            Int64Chunked::from_vec(self.ts_column.as_str().into(), vec![at; self.tags.len()])
+                .into_series()
+                .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?;
+        Ok(DataFrame::new(vec![
+            self.tag_series().into(),
+            times.into(),
+            Series::new(self.value_column.as_str().into(), values).into(),
+        ])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::{MssqlDialect, PostgresDialect, SqliteDialect};
+    use crate::time::parse_timestamp;
+    use polars::prelude::df;
+
+    fn query() -> ValueAt {
+        ValueAt::new(
+            "readings",
+            "ts",
+            ["TI-101", "TI-102"],
+            parse_timestamp("2024-03-01 08:00").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_render_value_at() {
+        let (sql, params) = query().render(&PostgresDialect, false).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT wanted.tag AS \"tag\", last_row.\"ts\", last_row.\"value\" \
+             FROM (VALUES ($1), ($2)) AS wanted(tag) \
+             CROSS JOIN LATERAL (SELECT \"ts\", \"value\" FROM \"readings\" \
+             WHERE \"tag\" = wanted.tag AND \"ts\" <= $3 \
+             ORDER BY \"ts\" DESC LIMIT 1) AS last_row"
+        );
+        assert_eq!(params.len(), 3);
+
+        let (sql, _) = query().render(&MssqlDialect, true).unwrap();
+        assert!(sql.contains("CROSS APPLY (SELECT TOP (1) [ts], [value]"));
+        assert!(sql.contains("[ts] > @P3 ORDER BY [ts] ASC"));
+
+        let (sql, _) = query().render(&SqliteDialect, false).unwrap();
+        assert!(sql.contains("ROW_NUMBER() OVER (PARTITION BY \"tag\" ORDER BY \"ts\" DESC)"));
+        assert!(sql.contains("\"tag\" IN (?1, ?2) AND \"ts\" <= ?3"));
+
+        let none: [&str; 0] = [];
+        let at = parse_timestamp("2024-03-01").unwrap();
+        assert!(ValueAt::new("readings", "ts", none, at)
+            .render(&SqliteDialect, false)
+            .is_err());
+        assert_eq!("linear".parse::<AtMode>().unwrap(), AtMode::Interpolated);
+    }
+
+    #[test]
+    fn test_previous_and_interpolated() {
+        let before = df!(
+            "tag" => ["TI-102", "TI-101"],
+            "ts" => ["2024-03-01 07:59:00", "2024-03-01 07:50:00"],
+            "value" => [5.0, 1.0],
+        )
+        .unwrap();
+        let after = df!(
+            "tag" => ["TI-101"],
+            "ts" => ["2024-03-01 08:10:00"],
+            "value" => [3.0],
+        )
+        .unwrap();
+
+        let previous = query().previous(before.clone()).unwrap();
+        assert_eq!(previous.get_column_names_str(), ["tag", "ts", "value"]);
+        let values: Vec<_> = previous
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(1.0), Some(5.0)]);
+
+        let interpolated = query().interpolated(before, after).unwrap();
+        let values: Vec<_> = interpolated
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        // TI-102 has no later value to interpolate towards
+        assert_eq!(values, [Some(2.0), None]);
+
+        let empty = query().previous(DataFrame::empty()).unwrap();
+        assert_eq!(empty.height(), 2);
+        assert_eq!(empty.column("value").unwrap().null_count(), 2);
+    }
+}
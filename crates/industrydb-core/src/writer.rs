@@ -0,0 +1,625 @@
+//! Buffered writes for high-rate streams
+//!
+//! A sensor sampled at a kHz cannot afford one INSERT per sample. A
+//! [`BufferedWriter`] takes rows as they arrive and hands them to a
+//! [`WriterTask`], which inserts them in batches once enough rows are
+//! buffered or the oldest has waited long enough. The queue between the
+//! two is bounded in rows, so a database that falls behind slows the
+//! producer down, drops rows or fails, as configured, instead of growing
+//! memory without limit.
+//!
+//! Run with a [`SpillStore`], the task keeps writing while the database is
+//! unreachable: batches go to a local SQLite file and are replayed in order
+//! once an insert gets through again.
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tokio::time::Instant;
+
+use crate::batching::{AdaptiveBatchConfig, AdaptiveBatcher};
+use crate::codec::CodecConfig;
+use crate::error::{IndustryDbError, Result};
+use crate::locks::TableLocks;
+use crate::spill::SpillStore;
+use crate::traits::CrudOperations;
+
+/// What a write does when the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueueOverflow {
+    /// Wait until flushed rows make room
+    #[default]
+    Block,
+    /// Drop the rows being written and count them
+    Drop,
+    /// Fail the write
+    Error,
+}
+
+impl std::str::FromStr for QueueOverflow {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "block" => Ok(QueueOverflow::Block),
+            "drop" => Ok(QueueOverflow::Drop),
+            "error" => Ok(QueueOverflow::Error),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported queue overflow handling: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Table and thresholds of a [`BufferedWriter`]
+#[derive(Debug, Clone)]
+pub struct BufferedWriterConfig {
+    /// Table rows are inserted into; it must exist
+    pub table: String,
+    /// Buffered rows that trigger a flush, and the most rows per insert
+    pub max_rows: usize,
+    /// Longest a row waits in the buffer before it is flushed
+    pub max_delay: Duration,
+    /// Rows written but not yet inserted beyond which writes overflow
+    pub queue_rows: usize,
+    /// What a write does when the queue is full
+    pub overflow: QueueOverflow,
+    /// How often spilled batches are retried while the database is down
+    pub retry_interval: Duration,
+    /// Key columns replayed batches are upserted on, so rows that reached
+    /// the table before the connection dropped are not inserted twice;
+    /// plain inserts when empty
+    pub dedup_keys: Vec<String>,
+    /// Codec batches are compressed with in the spill file
+    pub spill_codec: CodecConfig,
+    /// Size flushes adaptively from the insert latency instead of
+    /// `max_rows`; fixed flushes when `None`
+    pub batching: Option<AdaptiveBatchConfig>,
+}
+
+impl BufferedWriterConfig {
+    /// Create a config flushing every 10,000 rows or every second
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            max_rows: 10_000,
+            max_delay: Duration::from_secs(1),
+            queue_rows: 100_000,
+            overflow: QueueOverflow::default(),
+            retry_interval: Duration::from_secs(5),
+            dedup_keys: Vec::new(),
+            spill_codec: CodecConfig::default(),
+            batching: None,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.max_rows == 0 {
+            return Err(IndustryDbError::invalid_parameter(
+                "max_rows must be positive",
+            ));
+        }
+        if self.queue_rows < self.max_rows {
+            return Err(IndustryDbError::invalid_parameter(
+                "queue_rows must be at least max_rows",
+            ));
+        }
+        self.spill_codec.validate()?;
+        if let Some(batching) = &self.batching {
+            batching.validate()?;
+            if self.queue_rows < batching.max_rows {
+                return Err(IndustryDbError::invalid_parameter(
+                    "queue_rows must be at least the batching max_rows",
+                ));
+            }
+        }
+        if self.queue_rows > Semaphore::MAX_PERMITS {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "queue_rows must be at most {}",
+                Semaphore::MAX_PERMITS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Counters of a buffered writer
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriterStats {
+    /// Rows inserted
+    pub rows_written: u64,
+    /// Rows dropped because the queue was full
+    pub rows_dropped: u64,
+    /// Rows written but not yet inserted
+    pub rows_queued: u64,
+    /// Inserts made
+    pub flushes: u64,
+    /// Rows waiting in the spill file for the database to come back
+    pub rows_spilled: u64,
+    /// Rows inserted from the spill file, included in `rows_written`
+    pub rows_replayed: u64,
+    /// Rows that trigger the next flush
+    pub batch_rows: usize,
+    /// Error that stopped the writer
+    pub error: Option<String>,
+}
+
+enum Command {
+    Rows(DataFrame),
+    Flush(oneshot::Sender<()>),
+}
+
+/// State shared by the writer and its task
+struct Shared {
+    stats: Mutex<WriterStats>,
+    /// Rows the queue still has room for
+    room: Semaphore,
+}
+
+impl Shared {
+    fn stats(&self) -> std::sync::MutexGuard<'_, WriterStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn stopped(&self) -> IndustryDbError {
+        let error = self.stats().error.clone();
+        IndustryDbError::query_error(match error {
+            Some(error) => format!("Buffered writer stopped: {}", error),
+            None => "Buffered writer stopped".to_string(),
+        })
+    }
+}
+
+/// Accepts rows and queues them for a [`WriterTask`] to insert
+///
+/// Dropping the writer lets the task flush what is buffered and finish.
+pub struct BufferedWriter {
+    commands: mpsc::UnboundedSender<Command>,
+    shared: Arc<Shared>,
+    overflow: QueueOverflow,
+    queue_rows: usize,
+}
+
+/// Inserts the rows queued by a [`BufferedWriter`]; see [`run`](Self::run)
+pub struct WriterTask {
+    commands: mpsc::UnboundedReceiver<Command>,
+    shared: Arc<Shared>,
+    config: BufferedWriterConfig,
+    /// Sizes flushes when `config.batching` is set
+    batcher: Option<AdaptiveBatcher>,
+    /// Whether batches go to the spill file until it has been replayed
+    offline: bool,
+    /// When to next try replaying spilled batches
+    retry_at: Option<Instant>,
+}
+
+impl BufferedWriter {
+    /// Create a writer and the task that inserts its rows
+    pub fn new(config: BufferedWriterConfig) -> Result<(Self, WriterTask)> {
+        config.validate()?;
+        let batcher = config
+            .batching
+            .clone()
+            .map(AdaptiveBatcher::new)
+            .transpose()?;
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            stats: Mutex::new(WriterStats {
+                batch_rows: batcher
+                    .as_ref()
+                    .map_or(config.max_rows, AdaptiveBatcher::batch_rows),
+                ..Default::default()
+            }),
+            room: Semaphore::new(config.queue_rows),
+        });
+        let writer = Self {
+            commands,
+            shared: shared.clone(),
+            overflow: config.overflow,
+            queue_rows: config.queue_rows,
+        };
+        let task = WriterTask {
+            commands: receiver,
+            shared,
+            config,
+            batcher,
+            offline: false,
+            retry_at: None,
+        };
+        Ok((writer, task))
+    }
+
+    /// Queue `rows` for insertion
+    ///
+    /// Every write must have the same columns and dtypes. Fails once the
+    /// task has stopped, e.g. after a failed insert.
+    pub async fn write(&self, rows: DataFrame) -> Result<()> {
+        let count = rows.height();
+        if count == 0 {
+            return Ok(());
+        }
+        if count > self.queue_rows {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Cannot queue {} rows at once; queue_rows is {}",
+                count, self.queue_rows
+            )));
+        }
+
+        let room = &self.shared.room;
+        let permit = match self.overflow {
+            QueueOverflow::Block => room.acquire_many(count as u32).await.ok(),
+            QueueOverflow::Drop | QueueOverflow::Error => {
+                match room.try_acquire_many(count as u32) {
+                    Ok(permit) => Some(permit),
+                    Err(tokio::sync::TryAcquireError::Closed) => None,
+                    Err(tokio::sync::TryAcquireError::NoPermits) => {
+                        if self.overflow == QueueOverflow::Error {
+                            return Err(IndustryDbError::query_error(format!(
+                                "Buffered writer queue is full ({} rows)",
+                                self.queue_rows
+                            )));
+                        }
+                        self.shared.stats().rows_dropped += count as u64;
+                        return Ok(());
+                    }
+                }
+            }
+        };
+        // The room is closed once the task stops
+        let Some(permit) = permit else {
+            return Err(self.shared.stopped());
+        };
+
+        // Counted before sending, so the task never sees more rows than queued
+        self.shared.stats().rows_queued += count as u64;
+        if self.commands.send(Command::Rows(rows)).is_err() {
+            self.shared.stats().rows_queued -= count as u64;
+            return Err(self.shared.stopped());
+        }
+        permit.forget();
+        Ok(())
+    }
+
+    /// Wait until every row written so far has been inserted, or spilled
+    /// while the database is unreachable
+    pub async fn flush(&self) -> Result<()> {
+        let (done, flushed) = oneshot::channel();
+        self.commands
+            .send(Command::Flush(done))
+            .map_err(|_| self.shared.stopped())?;
+        flushed.await.map_err(|_| self.shared.stopped())
+    }
+
+    /// Counters so far
+    pub fn stats(&self) -> WriterStats {
+        self.shared.stats().clone()
+    }
+}
+
+impl WriterTask {
+    /// Insert queued rows through `conn` until the writer is dropped
+    ///
+    /// Rows are inserted once `max_rows`, or the adaptive batch size with
+    /// `batching`, are buffered or the oldest has waited `max_delay`, holding the table's lock in `locks` for each
+    /// insert. Whatever is buffered when the writer is dropped is inserted
+    /// before returning. A failed insert stops the task: its rows are lost,
+    /// later writes fail, and the error is returned.
+    pub async fn run<C: CrudOperations + ?Sized>(
+        self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+    ) -> Result<WriterStats> {
+        self.run_inner(conn, locks, None).await
+    }
+
+    /// Like [`run`](Self::run), but keep going while `conn` is unreachable
+    ///
+    /// When an insert fails and `conn` no longer answers
+    /// [`is_alive`](crate::traits::DatabaseConnector::is_alive), the batch
+    /// goes to `spill`, and so do all later batches until the spilled ones
+    /// have been replayed, oldest first, which is retried every
+    /// `retry_interval`. Batches left in `spill` by an earlier run are
+    /// replayed first. An insert that fails while `conn` is alive still
+    /// stops the task. Whatever cannot be replayed by the time the writer
+    /// is dropped stays in `spill` for the next run. Open `spill` with
+    /// `spill_codec` for it to take effect.
+    pub async fn run_with_spill<C: CrudOperations + ?Sized>(
+        self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+        spill: &SpillStore<'_>,
+    ) -> Result<WriterStats> {
+        self.run_inner(conn, locks, Some(spill)).await
+    }
+
+    async fn run_inner<C: CrudOperations + ?Sized>(
+        mut self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+        spill: Option<&SpillStore<'_>>,
+    ) -> Result<WriterStats> {
+        let result = self.insert_queued(conn, locks, spill).await;
+        // Wake writers waiting for room and refuse new rows
+        self.shared.room.close();
+        self.commands.close();
+        if let Err(e) = &result {
+            self.shared.stats().error = Some(e.to_string());
+        }
+        result.map(|_| self.shared.stats().clone())
+    }
+
+    async fn insert_queued<C: CrudOperations + ?Sized>(
+        &mut self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+        spill: Option<&SpillStore<'_>>,
+    ) -> Result<()> {
+        let mut buffer: Vec<DataFrame> = Vec::new();
+        let mut buffered = 0;
+        let mut deadline: Option<Instant> = None;
+
+        if let Some(spill) = spill {
+            let pending = spill.pending_rows().await?;
+            if pending > 0 {
+                self.shared.stats().rows_spilled = pending;
+                self.offline = true;
+                self.retry_at = Some(Instant::now());
+            }
+        }
+
+        loop {
+            let wake = match (deadline, self.retry_at) {
+                (Some(deadline), Some(retry_at)) => Some(deadline.min(retry_at)),
+                (deadline, retry_at) => deadline.or(retry_at),
+            };
+            let command = match wake {
+                Some(wake) => {
+                    match tokio::time::timeout_at(wake, self.commands.recv()).await {
+                        Ok(command) => command,
+                        Err(_) => {
+                            let now = Instant::now();
+                            if let Some(spill) = spill {
+                                if self.retry_at.is_some_and(|at| at <= now) {
+                                    self.replay(conn, locks, spill).await?;
+                                }
+                            }
+                            // The oldest buffered row has waited long enough
+                            if deadline.is_some_and(|at| at <= now) {
+                                while buffered > 0 {
+                                    self.insert(conn, locks, spill, &mut buffer, &mut buffered)
+                                        .await?;
+                                }
+                                deadline = None;
+                            }
+                            continue;
+                        }
+                    }
+                }
+                None => self.commands.recv().await,
+            };
+            match command {
+                Some(Command::Rows(rows)) => {
+                    deadline.get_or_insert_with(|| Instant::now() + self.config.max_delay);
+                    buffered += rows.height();
+                    buffer.push(rows);
+                    while buffered >= self.batch_rows() {
+                        self.insert(conn, locks, spill, &mut buffer, &mut buffered)
+                            .await?;
+                    }
+                }
+                Some(Command::Flush(done)) => {
+                    while buffered > 0 {
+                        self.insert(conn, locks, spill, &mut buffer, &mut buffered)
+                            .await?;
+                    }
+                    let _ = done.send(());
+                }
+                None => {
+                    // One last try, so a short outage does not leave rows behind
+                    if let (Some(spill), true) = (spill, self.offline) {
+                        self.replay(conn, locks, spill).await?;
+                    }
+                    while buffered > 0 {
+                        self.insert(conn, locks, spill, &mut buffer, &mut buffered)
+                            .await?;
+                    }
+                    return Ok(());
+                }
+            }
+            if buffered == 0 {
+                deadline = None;
+            }
+        }
+    }
+
+    /// Rows per flush: the batcher's current size, or `max_rows`
+    fn batch_rows(&self) -> usize {
+        self.batcher
+            .as_ref()
+            .map_or(self.config.max_rows, AdaptiveBatcher::batch_rows)
+    }
+
+    /// Insert up to a batch of the oldest buffered rows
+    async fn insert<C: CrudOperations + ?Sized>(
+        &mut self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+        spill: Option<&SpillStore<'_>>,
+        buffer: &mut Vec<DataFrame>,
+        buffered: &mut usize,
+    ) -> Result<()> {
+        let batch_rows = self.batch_rows();
+        let mut batch = DataFrame::empty();
+        while let Some(rows) = buffer.first_mut() {
+            let want = batch_rows - batch.height();
+            if want == 0 {
+                break;
+            }
+            let taken = if rows.height() <= want {
+                buffer.remove(0)
+            } else {
+                let taken = rows.slice(0, want);
+                *rows = rows.slice(want as i64, rows.height() - want);
+                taken
+            };
+            if batch.width() == 0 {
+                batch = taken;
+            } else {
+                batch.vstack_mut(&taken)?;
+            }
+        }
+        let count = batch.height();
+        *buffered -= count;
+
+        let inserted = match spill {
+            // Later batches must not overtake those waiting in the spill file
+            Some(spill) if self.offline => self.spill(spill, &mut batch).await,
+            _ => {
+                let started = Instant::now();
+                let inserted = self.write_batch(conn, locks, batch.clone()).await;
+                if let Some(batcher) = &mut self.batcher {
+                    match &inserted {
+                        Ok(()) => batcher.record_success(started.elapsed()),
+                        Err(_) => batcher.record_failure(),
+                    }
+                    self.shared.stats().batch_rows = batcher.batch_rows();
+                }
+                match (inserted, spill) {
+                    (Err(_), Some(spill)) if !conn.is_alive().await => {
+                        self.offline = true;
+                        self.retry_at = Some(Instant::now() + self.config.retry_interval);
+                        self.spill(spill, &mut batch).await
+                    }
+                    (inserted, _) => {
+                        if inserted.is_ok() {
+                            let mut stats = self.shared.stats();
+                            stats.rows_written += count as u64;
+                            stats.flushes += 1;
+                        }
+                        inserted
+                    }
+                }
+            }
+        };
+        self.shared.stats().rows_queued -= count as u64;
+        self.shared.room.add_permits(count);
+        inserted
+    }
+
+    /// Insert or, with `dedup_keys`, upsert a batch under the table's lock
+    async fn write_batch<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+        batch: DataFrame,
+    ) -> Result<()> {
+        let _guard = match locks {
+            Some(locks) => Some(locks.lock(&self.config.table).await),
+            None => None,
+        };
+        if self.config.dedup_keys.is_empty() {
+            conn.insert(&self.config.table, batch).await?;
+        } else {
+            conn.upsert(&self.config.table, batch, &self.config.dedup_keys)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn spill(&self, spill: &SpillStore<'_>, batch: &mut DataFrame) -> Result<()> {
+        spill.push(batch).await?;
+        self.shared.stats().rows_spilled += batch.height() as u64;
+        Ok(())
+    }
+
+    /// Replay spilled batches, oldest first, until none are left or the
+    /// database is still unreachable
+    async fn replay<C: CrudOperations + ?Sized>(
+        &mut self,
+        conn: &C,
+        locks: Option<&TableLocks>,
+        spill: &SpillStore<'_>,
+    ) -> Result<()> {
+        while let Some((seq, batch)) = spill.oldest().await? {
+            let count = batch.height() as u64;
+            if let Err(e) = self.write_batch(conn, locks, batch).await {
+                if conn.is_alive().await {
+                    return Err(IndustryDbError::query_error(format!(
+                        "Replaying spilled batch {} failed: {}",
+                        seq, e
+                    )));
+                }
+                self.retry_at = Some(Instant::now() + self.config.retry_interval);
+                return Ok(());
+            }
+            spill.remove(seq).await?;
+            {
+                let mut stats = self.shared.stats();
+                stats.rows_spilled = stats.rows_spilled.saturating_sub(count);
+                stats.rows_replayed += count;
+                stats.rows_written += count;
+                stats.flushes += 1;
+            }
+        }
+        self.offline = false;
+        self.retry_at = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(n: i64) -> DataFrame {
+        DataFrame::new(vec![Column::new(
+            "value".into(),
+            (0..n).collect::<Vec<_>>(),
+        )])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = BufferedWriterConfig::new("samples");
+        assert!(config.validate().is_ok());
+        config.queue_rows = config.max_rows - 1;
+        assert!(config.validate().is_err());
+        config.queue_rows = 20_000;
+        config.batching = Some(AdaptiveBatchConfig::default());
+        assert!(config.validate().is_err());
+        config.batching = Some(AdaptiveBatchConfig {
+            max_rows: 20_000,
+            ..Default::default()
+        });
+        assert!(config.validate().is_ok());
+        assert!("spill".parse::<QueueOverflow>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_overflow_drop_and_error() {
+        let mut config = BufferedWriterConfig::new("samples");
+        config.max_rows = 10;
+        config.queue_rows = 10;
+        config.overflow = QueueOverflow::Drop;
+        let (writer, _task) = BufferedWriter::new(config.clone()).unwrap();
+        assert_eq!(writer.stats().batch_rows, 10);
+        writer.write(rows(8)).await.unwrap();
+        writer.write(rows(5)).await.unwrap();
+        let stats = writer.stats();
+        assert_eq!(stats.rows_queued, 8);
+        assert_eq!(stats.rows_dropped, 5);
+        assert!(writer.write(rows(11)).await.is_err());
+
+        config.overflow = QueueOverflow::Error;
+        let (writer, task) = BufferedWriter::new(config).unwrap();
+        writer.write(rows(8)).await.unwrap();
+        assert!(writer.write(rows(5)).await.is_err());
+
+        drop(task);
+        assert!(writer.write(rows(1)).await.is_err());
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * IndustryDB C API
+ *
+ * Results and inserted data are exchanged as Arrow C streams; see
+ * https://arrow.apache.org/docs/format/CStreamInterface.html
+ *
+ * Every function returns an IdbStatus. On failure, idb_last_error() returns
+ * the message for the calling thread until its next idb_* call.
+ */
+
+#ifndef INDUSTRYDB_H
+#define INDUSTRYDB_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#ifndef ARROW_C_DATA_INTERFACE
+#define ARROW_C_DATA_INTERFACE
+
+struct ArrowSchema {
+    const char *format;
+    const char *name;
+    const char *metadata;
+    int64_t flags;
+    int64_t n_children;
+    struct ArrowSchema **children;
+    struct ArrowSchema *dictionary;
+    void (*release)(struct ArrowSchema *);
+    void *private_data;
+};
+
+struct ArrowArray {
+    int64_t length;
+    int64_t null_count;
+    int64_t offset;
+    int64_t n_buffers;
+    int64_t n_children;
+    const void **buffers;
+    struct ArrowArray **children;
+    struct ArrowArray *dictionary;
+    void (*release)(struct ArrowArray *);
+    void *private_data;
+};
+
+#endif /* ARROW_C_DATA_INTERFACE */
+
+#ifndef ARROW_C_STREAM_INTERFACE
+#define ARROW_C_STREAM_INTERFACE
+
+struct ArrowArrayStream {
+    int (*get_schema)(struct ArrowArrayStream *, struct ArrowSchema *out);
+    int (*get_next)(struct ArrowArrayStream *, struct ArrowArray *out);
+    const char *(*get_last_error)(struct ArrowArrayStream *);
+    void (*release)(struct ArrowArrayStream *);
+    void *private_data;
+};
+
+#endif /* ARROW_C_STREAM_INTERFACE */
+
+typedef enum IdbStatus {
+    IDB_OK = 0,
+    IDB_INVALID_ARGUMENT = 1,
+    IDB_CONNECTION_ERROR = 2,
+    IDB_QUERY_ERROR = 3,
+    IDB_CONFIG_ERROR = 4,
+    IDB_ACCESS_DENIED = 5,
+    IDB_CONNECTION_CLOSED = 6,
+    IDB_OTHER = 99
+} IdbStatus;
+
+/* Opaque connection handle */
+typedef struct IdbConnection IdbConnection;
+
+/* Message of the last error on this thread, or NULL */
+const char *idb_last_error(void);
+
+/* Open a connection from a URI; release it with idb_close */
+IdbStatus idb_connect(const char *uri, IdbConnection **out);
+
+/* Run a query; the caller must release *out */
+IdbStatus idb_execute(IdbConnection *conn, const char *sql, struct ArrowArrayStream *out);
+
+/* Run a statement that returns no rows (INSERT, DDL, ...); rows may be NULL */
+IdbStatus idb_execute_statement(IdbConnection *conn, const char *sql, int64_t *rows);
+
+/* Insert every batch of the stream into table; the stream is released.
+ * rows may be NULL. */
+IdbStatus idb_insert(IdbConnection *conn, const char *table, struct ArrowArrayStream *stream,
+                     int64_t *rows);
+
+/* Close the connection and free the handle; NULL is ignored */
+IdbStatus idb_close(IdbConnection *conn);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* INDUSTRYDB_H */
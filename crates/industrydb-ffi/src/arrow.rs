@@ -0,0 +1,95 @@
+//! Conversion between DataFrames and Arrow C streams
+//!
+//! Each stream item is a struct array holding one record batch, the layout
+//! used by Arrow C++, .NET (`Apache.Arrow.C`) and pyarrow.
+
+use industrydb_core::error::{IndustryDbError, Result};
+use polars::export::arrow::array::{Array, StructArray};
+use polars::export::arrow::datatypes::{ArrowDataType, Field as ArrowField};
+use polars::export::arrow::ffi::{export_iterator, ArrowArrayStream, ArrowArrayStreamReader};
+use polars::prelude::*;
+
+/// Export a DataFrame as an Arrow C stream of struct arrays
+pub(crate) fn export_stream(mut df: DataFrame) -> ArrowArrayStream {
+    df.as_single_chunk_par();
+
+    let fields: Vec<ArrowField> = df
+        .schema()
+        .iter_fields()
+        .map(|f| f.to_arrow(CompatLevel::newest()))
+        .collect();
+    let dtype = ArrowDataType::Struct(fields);
+
+    let batches: Vec<PolarsResult<Box<dyn Array>>> = df
+        .iter_chunks(CompatLevel::newest(), false)
+        .map(|batch| Ok(StructArray::new(dtype.clone(), batch.into_arrays(), None).boxed()))
+        .collect();
+
+    export_iterator(
+        Box::new(batches.into_iter()),
+        ArrowField::new("".into(), dtype, false),
+    )
+}
+
+/// Import an Arrow C stream of struct arrays as one DataFrame
+///
+/// # Safety
+///
+/// `stream` must be a valid, unreleased Arrow C stream; it is moved out of
+/// and released.
+pub(crate) unsafe fn import_stream(stream: *mut ArrowArrayStream) -> Result<DataFrame> {
+    let owned = Box::new(std::ptr::replace(stream, ArrowArrayStream::empty()));
+    let mut reader = ArrowArrayStreamReader::try_new(owned)?;
+
+    let ArrowDataType::Struct(fields) = reader.field().dtype().clone() else {
+        return Err(IndustryDbError::invalid_parameter(
+            "Arrow stream items must be struct arrays (record batches)",
+        ));
+    };
+
+    let mut result: Option<DataFrame> = None;
+    while let Some(array) = reader.next() {
+        let array = array?;
+        let batch = array
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .ok_or_else(|| IndustryDbError::invalid_parameter("Expected a struct array"))?;
+
+        let columns = fields
+            .iter()
+            .zip(batch.values())
+            .map(|(field, values)| {
+                Ok(Series::from_arrow(field.name.clone(), values.clone())?.into_column())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let df = DataFrame::new(columns)?;
+
+        result = Some(match result {
+            Some(mut acc) => {
+                acc.vstack_mut(&df)?;
+                acc
+            }
+            None => df,
+        });
+    }
+
+    Ok(result.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_roundtrip() {
+        let df = df!(
+            "tag" => ["TI-101", "TI-102"],
+            "value" => [20.5, 21.0],
+        )
+        .unwrap();
+
+        let mut stream = export_stream(df.clone());
+        let imported = unsafe { import_stream(&mut stream) }.unwrap();
+        assert!(imported.equals(&df));
+    }
+}
@@ -0,0 +1,11 @@
+//! Versioned SQL migrations for IndustryDB
+//!
+//! Migrations are plain SQL files named `<version>_<name>.up.sql` with an
+//! optional `<version>_<name>.down.sql`, e.g. `0001_create_readings.up.sql`.
+//! Applied versions are tracked in a `schema_migrations` table.
+
+mod migration;
+mod migrator;
+
+pub use migration::Migration;
+pub use migrator::{Migrate, MigrationStatus, Migrator, DEFAULT_MIGRATIONS_TABLE};
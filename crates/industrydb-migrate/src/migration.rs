@@ -0,0 +1,127 @@
+//! Migration definitions and loading from disk
+
+use industrydb_core::error::{IndustryDbError, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A single versioned migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// Version number; migrations are applied in ascending order
+    pub version: i64,
+    /// Descriptive name taken from the file name
+    pub name: String,
+    /// SQL applied by `migrate_up`
+    pub up: String,
+    /// SQL applied by `migrate_down`, if the migration is reversible
+    pub down: Option<String>,
+}
+
+impl Migration {
+    /// Create a migration from SQL strings
+    pub fn new<S: Into<String>>(version: i64, name: S, up: S, down: Option<S>) -> Self {
+        Self {
+            version,
+            name: name.into(),
+            up: up.into(),
+            down: down.map(Into::into),
+        }
+    }
+
+    /// Load all migrations from a directory, sorted by version
+    ///
+    /// Files not ending in `.up.sql` / `.down.sql` are ignored. A `.down.sql`
+    /// file without a matching `.up.sql` is an error, as are duplicate versions.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<Migration>> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            IndustryDbError::config_error(format!(
+                "Cannot read migrations directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        let mut ups: BTreeMap<i64, (String, String)> = BTreeMap::new();
+        let mut downs: BTreeMap<i64, String> = BTreeMap::new();
+
+        for entry in entries {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some((version, name, is_up)) = parse_file_name(file_name)? else {
+                continue;
+            };
+            let sql = std::fs::read_to_string(&path)?;
+
+            if is_up {
+                if ups.insert(version, (name, sql)).is_some() {
+                    return Err(IndustryDbError::config_error(format!(
+                        "Duplicate migration version {}",
+                        version
+                    )));
+                }
+            } else {
+                downs.insert(version, sql);
+            }
+        }
+
+        if let Some(version) = downs.keys().find(|v| !ups.contains_key(v)) {
+            return Err(IndustryDbError::config_error(format!(
+                "Down migration {} has no matching up migration",
+                version
+            )));
+        }
+
+        Ok(ups
+            .into_iter()
+            .map(|(version, (name, up))| Migration {
+                version,
+                name,
+                up,
+                down: downs.remove(&version),
+            })
+            .collect())
+    }
+}
+
+/// Parse `<version>_<name>.(up|down).sql` into `(version, name, is_up)`
+fn parse_file_name(file_name: &str) -> Result<Option<(i64, String, bool)>> {
+    let (stem, is_up) = if let Some(stem) = file_name.strip_suffix(".up.sql") {
+        (stem, true)
+    } else if let Some(stem) = file_name.strip_suffix(".down.sql") {
+        (stem, false)
+    } else {
+        return Ok(None);
+    };
+
+    let (version, name) = stem.split_once('_').unwrap_or((stem, ""));
+    let version = version.parse::<i64>().map_err(|_| {
+        IndustryDbError::config_error(format!(
+            "Migration file {} must start with a numeric version",
+            file_name
+        ))
+    })?;
+
+    Ok(Some((version, name.to_string(), is_up)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(
+            parse_file_name("0001_create_readings.up.sql").unwrap(),
+            Some((1, "create_readings".to_string(), true))
+        );
+        assert_eq!(
+            parse_file_name("20240101_add_index.down.sql").unwrap(),
+            Some((20240101, "add_index".to_string(), false))
+        );
+        assert_eq!(parse_file_name("README.md").unwrap(), None);
+        assert!(parse_file_name("init.up.sql").is_err());
+    }
+}
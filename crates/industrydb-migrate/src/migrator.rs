@@ -0,0 +1,291 @@
+//! Applying and reverting migrations against a connection
+
+use async_trait::async_trait;
+use industrydb_core::{
+    error::{IndustryDbError, Result},
+    schema::first_column_strings,
+    sql::split_statements,
+    traits::CrudOperations,
+};
+use polars::prelude::*;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::migration::Migration;
+
+/// Default name of the table that records applied migrations
+pub const DEFAULT_MIGRATIONS_TABLE: &str = "schema_migrations";
+
+/// Whether a known migration has been applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// Migration version
+    pub version: i64,
+    /// Migration name
+    pub name: String,
+    /// Whether the version is recorded in the migrations table
+    pub applied: bool,
+}
+
+/// An ordered set of migrations plus the table that tracks them
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    table: String,
+}
+
+impl Migrator {
+    /// Create a migrator from migrations in any order
+    pub fn new(mut migrations: Vec<Migration>) -> Result<Self> {
+        migrations.sort_by_key(|m| m.version);
+        if let Some(pair) = migrations.windows(2).find(|w| w[0].version == w[1].version) {
+            return Err(IndustryDbError::config_error(format!(
+                "Duplicate migration version {}",
+                pair[0].version
+            )));
+        }
+
+        Ok(Self {
+            migrations,
+            table: DEFAULT_MIGRATIONS_TABLE.to_string(),
+        })
+    }
+
+    /// Create a migrator from a directory of `.up.sql` / `.down.sql` files
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        Self::new(Migration::load_dir(dir)?)
+    }
+
+    /// Track applied migrations in a different table
+    pub fn with_table<S: Into<String>>(mut self, table: S) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Known migrations in version order
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// Apply pending migrations up to and including `target` (all when `None`)
+    ///
+    /// Returns the versions applied, in order. On databases with
+    /// transactional DDL each migration runs in one transaction with the
+    /// INSERT recording its version, so a failed migration leaves nothing
+    /// behind. Elsewhere its statements run one at a time and the version
+    /// is recorded after they all succeed.
+    pub async fn up<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        target: Option<i64>,
+    ) -> Result<Vec<i64>> {
+        self.ensure_table(conn).await?;
+        let applied = self.applied_versions(conn).await?;
+
+        let mut done = Vec::new();
+        for migration in &self.migrations {
+            if applied.contains(&migration.version) || target.is_some_and(|t| migration.version > t)
+            {
+                continue;
+            }
+
+            let record = format!(
+                "INSERT INTO {} (version, name, applied_at) VALUES ({}, '{}', CURRENT_TIMESTAMP)",
+                conn.dialect().identifier(&self.table)?,
+                migration.version,
+                migration.name.replace('\'', "''")
+            );
+            run_script(conn, migration, &migration.up, record).await?;
+            done.push(migration.version);
+        }
+
+        Ok(done)
+    }
+
+    /// Revert the `steps` most recently applied migrations
+    ///
+    /// Returns the versions reverted, newest first, each in a transaction
+    /// as in [`up`](Self::up). Fails before touching the database if any of
+    /// them has no down migration.
+    pub async fn down<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+        steps: usize,
+    ) -> Result<Vec<i64>> {
+        self.ensure_table(conn).await?;
+        let applied = self.applied_versions(conn).await?;
+
+        let to_revert = applied
+            .iter()
+            .rev()
+            .take(steps)
+            .map(|version| {
+                let migration = self
+                    .migrations
+                    .iter()
+                    .find(|m| m.version == *version)
+                    .ok_or_else(|| {
+                        IndustryDbError::config_error(format!(
+                            "Applied migration {} is not among the known migrations",
+                            version
+                        ))
+                    })?;
+                let down = migration.down.as_deref().ok_or_else(|| {
+                    IndustryDbError::config_error(format!(
+                        "Migration {} ({}) has no down migration",
+                        migration.version, migration.name
+                    ))
+                })?;
+                Ok((migration, down))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut done = Vec::new();
+        for (migration, down) in to_revert {
+            let record = format!(
+                "DELETE FROM {} WHERE version = {}",
+                conn.dialect().identifier(&self.table)?,
+                migration.version
+            );
+            run_script(conn, migration, down, record).await?;
+            done.push(migration.version);
+        }
+
+        Ok(done)
+    }
+
+    /// Applied/pending state of every known migration
+    pub async fn status<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+    ) -> Result<Vec<MigrationStatus>> {
+        self.ensure_table(conn).await?;
+        let applied = self.applied_versions(conn).await?;
+
+        Ok(self
+            .migrations
+            .iter()
+            .map(|m| MigrationStatus {
+                version: m.version,
+                name: m.name.clone(),
+                applied: applied.contains(&m.version),
+            })
+            .collect())
+    }
+
+    async fn ensure_table<C: CrudOperations + ?Sized>(&self, conn: &C) -> Result<()> {
+        let schema = Schema::from_iter([
+            Field::new("version".into(), DataType::Int64),
+            Field::new("name".into(), DataType::String),
+            Field::new(
+                "applied_at".into(),
+                DataType::Datetime(TimeUnit::Microseconds, None),
+            ),
+        ]);
+        conn.create_table(&self.table, &schema, true).await
+    }
+
+    async fn applied_versions<C: CrudOperations + ?Sized>(
+        &self,
+        conn: &C,
+    ) -> Result<BTreeSet<i64>> {
+        let df = conn
+            .execute(&format!(
+                "SELECT version FROM {}",
+                conn.dialect().identifier(&self.table)?
+            ))
+            .await?;
+        first_column_strings(&df)?
+            .iter()
+            .map(|v| {
+                v.parse::<i64>().map_err(|_| {
+                    IndustryDbError::query_error(format!("Invalid migration version: {}", v))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Run each statement of a migration script in order, then `record`,
+/// which updates the migrations table
+///
+/// All of them run in one transaction where DDL is transactional.
+async fn run_script<C: CrudOperations + ?Sized>(
+    conn: &C,
+    migration: &Migration,
+    sql: &str,
+    record: String,
+) -> Result<()> {
+    let failed = |e: IndustryDbError| {
+        IndustryDbError::query_error(format!(
+            "Migration {} ({}) failed: {}",
+            migration.version, migration.name, e
+        ))
+    };
+    let mut statements = split_statements(sql);
+    if conn.dialect().transactional_ddl() {
+        statements.push(record);
+        conn.execute_transaction(&statements)
+            .await
+            .map_err(failed)?;
+        return Ok(());
+    }
+
+    for statement in statements {
+        conn.execute_statement(&statement, &[])
+            .await
+            .map_err(failed)?;
+    }
+    conn.execute_statement(&record, &[]).await?;
+    Ok(())
+}
+
+/// Migration APIs on a connection
+#[async_trait]
+pub trait Migrate {
+    /// Apply pending migrations up to `target` (all when `None`)
+    async fn migrate_up(&self, migrator: &Migrator, target: Option<i64>) -> Result<Vec<i64>>;
+
+    /// Revert the `steps` most recently applied migrations
+    async fn migrate_down(&self, migrator: &Migrator, steps: usize) -> Result<Vec<i64>>;
+
+    /// Applied/pending state of every known migration
+    async fn migration_status(&self, migrator: &Migrator) -> Result<Vec<MigrationStatus>>;
+}
+
+#[async_trait]
+impl<C: CrudOperations + ?Sized> Migrate for C {
+    async fn migrate_up(&self, migrator: &Migrator, target: Option<i64>) -> Result<Vec<i64>> {
+        migrator.up(self, target).await
+    }
+
+    async fn migrate_down(&self, migrator: &Migrator, steps: usize) -> Result<Vec<i64>> {
+        migrator.down(self, steps).await
+    }
+
+    async fn migration_status(&self, migrator: &Migrator) -> Result<Vec<MigrationStatus>> {
+        migrator.status(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_sorts_and_rejects_duplicates() {
+        let migrator = Migrator::new(vec![
+            Migration::new(2, "b", "SELECT 2", None),
+            Migration::new(1, "a", "SELECT 1", None),
+        ])
+        .unwrap();
+        let versions: Vec<i64> = migrator.migrations().iter().map(|m| m.version).collect();
+        assert_eq!(versions, vec![1, 2]);
+
+        assert!(Migrator::new(vec![
+            Migration::new(1, "a", "SELECT 1", None),
+            Migration::new(1, "b", "SELECT 1", None),
+        ])
+        .is_err());
+    }
+}
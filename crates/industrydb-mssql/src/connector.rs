@@ -2,21 +2,43 @@
 
 use async_trait::async_trait;
 use bb8::Pool;
-use bb8_tiberius::ConnectionManager;
+use futures_util::TryStreamExt;
 use industrydb_core::{
-    config::ConnectionConfig,
+    config::{ConnectionConfig, DatabaseType, DecimalMode, TimestampMode},
+    decimal,
+    dialect::{Dialect, MssqlDialect},
     error::{IndustryDbError, Result},
+    metrics::{ChunkedDecode, QueryMetrics, DECODE_CHUNK_ROWS},
+    params::{parameter_set_error, Value},
+    procedure::{first_row, ProcedureArg, ProcedureResult},
+    schema,
+    sink::FrameSink,
+    sql::ensure_returns_rows,
+    time::apply_timestamp_mode,
     traits::DatabaseConnector,
 };
 use polars::prelude::*;
-use tiberius::{Config, Row as TiberiusRow};
+use std::borrow::Cow;
+use tiberius::numeric::Numeric;
+use tiberius::time::chrono::{
+    DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike,
+};
+use tiberius::{
+    Column, ColumnData, ColumnType, Config, FromSql, QueryItem, Row as TiberiusRow, ToSql, Uuid,
+};
+
+use crate::introspection;
+use crate::pool::MssqlManager;
+use crate::procedure;
 
-type TiberiusPool = Pool<ConnectionManager>;
+type TiberiusPool = Pool<MssqlManager>;
 
 /// MSSQL database connector with connection pool
 pub struct MssqlConnector {
     pool: TiberiusPool,
     db_type: String,
+    config: ConnectionConfig,
+    metrics: QueryMetrics,
 }
 
 impl MssqlConnector {
@@ -34,7 +56,7 @@ impl MssqlConnector {
             tiberius_config.database(db);
         }
 
-        let manager = ConnectionManager::new(tiberius_config);
+        let manager = MssqlManager::new(tiberius_config);
         let pool = Pool::builder()
             .build(manager)
             .await
@@ -43,6 +65,8 @@ impl MssqlConnector {
         Ok(Self {
             pool,
             db_type: "mssql".to_string(),
+            config: config.clone(),
+            metrics: QueryMetrics::new(),
         })
     }
 
@@ -50,6 +74,93 @@ impl MssqlConnector {
     pub fn pool(&self) -> &TiberiusPool {
         &self.pool
     }
+
+    /// Get the configuration this connector was created with
+    pub fn config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    /// How exact numeric columns are read
+    fn decimals(&self) -> DecimalMode {
+        self.config
+            .mssql
+            .as_ref()
+            .map(|options| options.decimals)
+            .unwrap_or_default()
+    }
+
+    /// Zone timestamps are returned in
+    fn timestamps(&self) -> TimestampMode {
+        self.config.timestamps.clone().unwrap_or_default()
+    }
+
+    /// Run a query and decode every result set it returns, streaming the
+    /// first into `sink` when given
+    async fn read_sets(
+        &self,
+        sql: &str,
+        params: &[Value],
+        mut sink: Option<&mut dyn FrameSink>,
+    ) -> Result<Vec<DataFrame>> {
+        let sql = self.enforce_policy(sql)?;
+        ensure_returns_rows(&sql)?;
+        let params = to_sql_params(params);
+        let (decimals, timestamps) = (self.decimals(), self.timestamps());
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+
+        let mut stream = conn
+            .query(&*sql, &param_refs(&params))
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+
+        // Each result set starts with its column metadata, even without rows
+        let mut sets = Vec::new();
+        let mut current: Option<ResultSet> = None;
+        while let Some(item) = stream
+            .try_next()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?
+        {
+            match item {
+                QueryItem::Metadata(meta) => {
+                    if let Some(set) = current.take() {
+                        sets.push(set.finish()?);
+                    }
+                    let mut decode = self.metrics.chunked(&sql);
+                    if let Some(sink) = sink.take() {
+                        decode = decode.into_sink(sink);
+                    }
+                    current = Some(ResultSet::new(
+                        meta.columns().to_vec(),
+                        decode,
+                        decimals,
+                        &timestamps,
+                    ));
+                }
+                QueryItem::Row(row) => {
+                    if let Some(set) = current.as_mut() {
+                        set.push(row)?;
+                    }
+                }
+            }
+        }
+        if let Some(set) = current {
+            sets.push(set.finish()?);
+        }
+        Ok(sets)
+    }
+
+    /// Apply the connection's access policy to a query
+    pub(crate) fn enforce_policy<'a>(&self, sql: &'a str) -> Result<Cow<'a, str>> {
+        match &self.config.policy {
+            Some(policy) => policy.enforce(sql, DatabaseType::Mssql),
+            None => Ok(Cow::Borrowed(sql)),
+        }
+    }
 }
 
 #[async_trait]
@@ -58,28 +169,148 @@ impl DatabaseConnector for MssqlConnector {
         &self.db_type
     }
 
+    fn dialect(&self) -> &'static dyn Dialect {
+        &MssqlDialect
+    }
+
+    fn metrics(&self) -> &QueryMetrics {
+        &self.metrics
+    }
+
     async fn execute(&self, sql: &str) -> Result<DataFrame> {
+        self.execute_with_params(sql, &[]).await
+    }
+
+    async fn execute_with_params(&self, sql: &str, params: &[Value]) -> Result<DataFrame> {
+        let sets = self.execute_result_sets(sql, params).await?;
+        Ok(sets.into_iter().next().unwrap_or_else(DataFrame::empty))
+    }
+
+    async fn execute_into(
+        &self,
+        sql: &str,
+        params: &[Value],
+        sink: &mut dyn FrameSink,
+    ) -> Result<()> {
+        self.read_sets(sql, params, Some(sink)).await?;
+        Ok(())
+    }
+
+    async fn execute_result_sets(&self, sql: &str, params: &[Value]) -> Result<Vec<DataFrame>> {
+        self.read_sets(sql, params, None).await
+    }
+
+    async fn execute_statement(&self, sql: &str, params: &[Value]) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        let params = to_sql_params(params);
         let mut conn = self
             .pool
             .get()
             .await
             .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
 
-        let stream = conn
-            .query(sql, &[])
+        let result = conn
+            .execute(&*sql, &param_refs(&params))
             .await
             .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(result.total())
+    }
 
-        let rows = stream
-            .into_results()
+    async fn execute_many(&self, sql: &str, param_sets: &[Vec<Value>]) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+
+        conn.begin()
             .await
             .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
 
-        if rows.is_empty() {
-            return Ok(DataFrame::empty());
+        let mut affected = 0;
+        for (index, params) in param_sets.iter().enumerate() {
+            let params = to_sql_params(params);
+            match conn.execute(&*sql, &param_refs(&params)).await {
+                Ok(result) => affected += result.total(),
+                Err(e) => {
+                    // A failed rollback leaves the connection to be closed
+                    let _ = conn.rollback().await;
+                    return Err(parameter_set_error(
+                        index,
+                        IndustryDbError::QueryError(e.to_string()),
+                    ));
+                }
+            }
         }
 
-        rows_to_dataframe(&rows[0])
+        conn.commit()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(affected)
+    }
+
+    async fn execute_transaction(&self, statements: &[String]) -> Result<u64> {
+        let statements = statements
+            .iter()
+            .map(|sql| self.enforce_policy(sql))
+            .collect::<Result<Vec<_>>>()?;
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+
+        conn.begin()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+
+        let mut affected = 0;
+        for sql in &statements {
+            match conn.execute(&**sql, &[]).await {
+                Ok(result) => affected += result.total(),
+                Err(e) => {
+                    let _ = conn.rollback().await;
+                    return Err(IndustryDbError::QueryError(e.to_string()));
+                }
+            }
+        }
+
+        conn.commit()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(affected)
+    }
+
+    async fn call_procedure(&self, name: &str, args: &[ProcedureArg]) -> Result<ProcedureResult> {
+        let (sql, params) = procedure::call_sql(name, args)?;
+        let mut result_sets = self.execute_result_sets(&sql, &params).await?;
+        // The batch ends with the SELECT of the return value and outputs
+        let mut outputs = match result_sets.pop() {
+            Some(df) => first_row(&df)?,
+            None => Vec::new(),
+        };
+        let return_value = (!outputs.is_empty()).then(|| outputs.remove(0).1);
+        Ok(ProcedureResult {
+            result_sets,
+            outputs,
+            return_value,
+        })
+    }
+
+    #[cfg(feature = "connectorx")]
+    async fn fast_read(&self, sql: &str) -> Result<DataFrame> {
+        let sql = self.enforce_policy(sql)?.into_owned();
+        ensure_returns_rows(&sql)?;
+        let started = std::time::Instant::now();
+        let (url, query) = (crate::fast_read::connection_url(&self.config), sql.clone());
+        let df = tokio::task::spawn_blocking(move || crate::fast_read::read_sql(&url, &query))
+            .await
+            .map_err(|e| IndustryDbError::ConnectorXError(e.to_string()))??;
+        let df = self
+            .metrics
+            .record_decode(&sql, started.elapsed(), || Ok(df))?;
+        apply_timestamp_mode(df, &self.timestamps())
     }
 
     async fn is_alive(&self) -> bool {
@@ -99,62 +330,296 @@ impl DatabaseConnector for MssqlConnector {
         // bb8 pool doesn't track closed state
         false
     }
+
+    async fn list_schemas(&self) -> Result<Vec<String>> {
+        let df = self.execute(&introspection::list_schemas_sql()).await?;
+        schema::first_column_strings(&df)
+    }
+
+    async fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        let df = self
+            .execute(&introspection::list_tables_sql(schema))
+            .await?;
+        schema::first_column_strings(&df)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::describe_table_sql(table))
+            .await?;
+        schema::normalize_describe(df)
+    }
+
+    async fn primary_keys(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::primary_keys_sql(table))
+            .await?;
+        schema::normalize_primary_keys(df)
+    }
+
+    async fn indexes(&self, table: &str) -> Result<DataFrame> {
+        let df = self.execute(&introspection::indexes_sql(table)).await?;
+        schema::normalize_indexes(df)
+    }
+
+    async fn foreign_keys(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::foreign_keys_sql(table))
+            .await?;
+        schema::normalize_foreign_keys(df)
+    }
+}
+
+/// Convert parameter values to owned tiberius parameters
+pub(crate) fn to_sql_params(params: &[Value]) -> Vec<Box<dyn ToSql>> {
+    params
+        .iter()
+        .map(|param| -> Box<dyn ToSql> {
+            match param {
+                Value::Null => Box::new(None::<String>),
+                Value::Bool(v) => Box::new(*v),
+                Value::Int(v) => Box::new(*v),
+                Value::Float(v) => Box::new(*v),
+                Value::Text(v) => Box::new(v.clone()),
+                Value::Bytes(v) => Box::new(v.clone()),
+                Value::Decimal(v) => match decimal::parse(v) {
+                    Ok((value, scale)) => Box::new(Numeric::new_with_scale(value, scale as u8)),
+                    // SQL Server converts the text itself, or reports why not
+                    _ => Box::new(v.clone()),
+                },
+                Value::Uuid(v) => match Uuid::parse_str(v) {
+                    Ok(u) => Box::new(u),
+                    Err(_) => Box::new(v.clone()),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Borrow owned parameters in the form tiberius queries take
+pub(crate) fn param_refs(params: &[Box<dyn ToSql>]) -> Vec<&dyn ToSql> {
+    params.iter().map(|p| p.as_ref()).collect()
 }
 
-/// Convert tiberius rows to Polars DataFrame
-fn rows_to_dataframe(rows: &[TiberiusRow]) -> Result<DataFrame> {
+/// A result set being read: its columns and the rows not yet decoded
+struct ResultSet<'a> {
+    columns: Vec<Column>,
+    rows: Vec<TiberiusRow>,
+    decode: ChunkedDecode<'a>,
+    decoded: bool,
+    decimals: DecimalMode,
+    timestamps: &'a TimestampMode,
+}
+
+impl<'a> ResultSet<'a> {
+    fn new(
+        columns: Vec<Column>,
+        decode: ChunkedDecode<'a>,
+        decimals: DecimalMode,
+        timestamps: &'a TimestampMode,
+    ) -> Self {
+        Self {
+            columns,
+            rows: Vec::with_capacity(DECODE_CHUNK_ROWS),
+            decode,
+            decoded: false,
+            decimals,
+            timestamps,
+        }
+    }
+
+    /// Add a row, decoding the buffered rows once there is a chunk of them
+    fn push(&mut self, row: TiberiusRow) -> Result<()> {
+        self.rows.push(row);
+        if self.rows.len() == DECODE_CHUNK_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let rows = std::mem::replace(&mut self.rows, Vec::with_capacity(DECODE_CHUNK_ROWS));
+        let (decimals, timestamps) = (self.decimals, self.timestamps);
+        self.decode
+            .decode(|| apply_timestamp_mode(rows_to_dataframe(&rows, decimals)?, timestamps))?;
+        self.decoded = true;
+        Ok(())
+    }
+
+    /// Decode the remaining rows and record the result set
+    fn finish(mut self) -> Result<DataFrame> {
+        if !self.rows.is_empty() {
+            self.flush()?;
+        } else if !self.decoded {
+            let (columns, decimals, timestamps) = (&self.columns, self.decimals, self.timestamps);
+            self.decode
+                .decode(|| apply_timestamp_mode(empty_frame(columns, decimals)?, timestamps))?;
+        }
+        self.decode.finish()
+    }
+}
+
+/// Empty DataFrame with the columns of a result set without rows
+fn empty_frame(columns: &[Column], decimals: DecimalMode) -> Result<DataFrame> {
+    let columns: Vec<_> = columns
+        .iter()
+        .map(|column| {
+            let dtype = column_dtype(column.column_type(), None, decimals);
+            Series::new_empty(column.name().into(), &dtype).into_column()
+        })
+        .collect();
+    DataFrame::new(columns).map_err(|e| IndustryDbError::PolarsError(e.to_string()))
+}
+
+/// Polars dtype for a result column
+///
+/// Nullable `int` and `float` columns only carry their width in the column
+/// data, which `first` is taken from; without a value they are read at 64
+/// bits.
+/// `decimal`, `numeric` and `money` columns follow `decimals`; decimal
+/// columns take their scale from the values. Types without a dedicated
+/// mapping become text.
+fn column_dtype(
+    column_type: ColumnType,
+    first: Option<&ColumnData<'static>>,
+    decimals: DecimalMode,
+) -> DataType {
+    match column_type {
+        ColumnType::Int1 => DataType::UInt8,
+        ColumnType::Int2 => DataType::Int16,
+        ColumnType::Int4 => DataType::Int32,
+        ColumnType::Int8 => DataType::Int64,
+        ColumnType::Intn => match first {
+            Some(ColumnData::U8(_)) => DataType::UInt8,
+            Some(ColumnData::I16(_)) => DataType::Int16,
+            Some(ColumnData::I32(_)) => DataType::Int32,
+            _ => DataType::Int64,
+        },
+        ColumnType::Float4 => DataType::Float32,
+        ColumnType::Floatn => match first {
+            Some(ColumnData::F32(_)) => DataType::Float32,
+            _ => DataType::Float64,
+        },
+        ColumnType::Float8 => DataType::Float64,
+        ColumnType::Bit | ColumnType::Bitn => DataType::Boolean,
+        ColumnType::Decimaln | ColumnType::Numericn | ColumnType::Money | ColumnType::Money4
+            if decimals == DecimalMode::Float =>
+        {
+            DataType::Float64
+        }
+        ColumnType::Decimaln | ColumnType::Numericn => {
+            DataType::Decimal(Some(decimal::MAX_PRECISION), Some(0))
+        }
+        ColumnType::Money | ColumnType::Money4 => {
+            DataType::Decimal(Some(MONEY_PRECISION), Some(MONEY_SCALE as usize))
+        }
+        ColumnType::BigVarBin | ColumnType::BigBinary | ColumnType::Image => DataType::Binary,
+        ColumnType::Datetime
+        | ColumnType::Datetime4
+        | ColumnType::Datetimen
+        | ColumnType::Datetime2 => DataType::Datetime(TimeUnit::Nanoseconds, None),
+        ColumnType::DatetimeOffsetn => {
+            DataType::Datetime(TimeUnit::Nanoseconds, Some("UTC".into()))
+        }
+        ColumnType::Daten => DataType::Date,
+        ColumnType::Timen => DataType::Time,
+        _ => DataType::String,
+    }
+}
+
+/// Convert tiberius rows to Polars DataFrame, typing each column by its
+/// metadata
+fn rows_to_dataframe(rows: &[TiberiusRow], decimals: DecimalMode) -> Result<DataFrame> {
     if rows.is_empty() {
         return Ok(DataFrame::empty());
     }
 
-    let column_count = rows[0].len();
     let mut series_vec: Vec<Series> = Vec::new();
 
-    for col_idx in 0..column_count {
-        let col_name = rows[0]
-            .columns()
-            .get(col_idx)
-            .map(|c| c.name())
-            .unwrap_or("unknown");
+    for (col_idx, column) in rows[0].columns().iter().enumerate() {
+        let col_name = column.name();
+        let column_type = column.column_type();
 
-        // Try different types - tiberius doesn't expose ColumnData type easily
-        // So we try to decode each type and use the first one that works
-        let series = if let Ok(values) = rows
-            .iter()
-            .map(|row| row.try_get::<i32, _>(col_idx))
-            .collect::<std::result::Result<Vec<_>, _>>()
-        {
-            Series::new(col_name.into(), values)
-        } else if let Ok(values) = rows
-            .iter()
-            .map(|row| row.try_get::<i64, _>(col_idx))
-            .collect::<std::result::Result<Vec<_>, _>>()
-        {
-            Series::new(col_name.into(), values)
-        } else if let Ok(values) = rows
-            .iter()
-            .map(|row| row.try_get::<f64, _>(col_idx))
-            .collect::<std::result::Result<Vec<_>, _>>()
-        {
-            Series::new(col_name.into(), values)
-        } else if let Ok(values) = rows
-            .iter()
-            .map(|row| row.try_get::<bool, _>(col_idx))
-            .collect::<std::result::Result<Vec<_>, _>>()
-        {
-            Series::new(col_name.into(), values)
-        } else {
-            // Default to string
-            let values: Vec<Option<String>> = rows
-                .iter()
-                .map(|row| {
-                    row.try_get::<&str, _>(col_idx)
-                        .ok()
-                        .flatten()
-                        .map(|s| s.to_string())
-                })
-                .collect();
-            Series::new(col_name.into(), values)
+        let is_numeric = matches!(column_type, ColumnType::Decimaln | ColumnType::Numericn);
+        let series = match column_dtype(column_type, first_value(rows, col_idx), decimals) {
+            DataType::UInt8 => typed_series::<u8>(rows, col_idx, col_name)?,
+            DataType::Int16 => typed_series::<i16>(rows, col_idx, col_name)?,
+            DataType::Int32 => typed_series::<i32>(rows, col_idx, col_name)?,
+            DataType::Int64 => typed_series::<i64>(rows, col_idx, col_name)?,
+            DataType::Float32 => typed_series::<f32>(rows, col_idx, col_name)?,
+            DataType::Float64 if is_numeric => {
+                let values: Vec<Option<f64>> = cells::<Numeric>(rows, col_idx, col_name)?
+                    .into_iter()
+                    .map(|n| n.map(|n| n.value() as f64 / 10f64.powi(n.scale() as i32)))
+                    .collect();
+                Series::new(col_name.into(), values)
+            }
+            DataType::Float64 => typed_series::<f64>(rows, col_idx, col_name)?,
+            DataType::Boolean => typed_series::<bool>(rows, col_idx, col_name)?,
+            DataType::Decimal(_, _) if !is_numeric => {
+                // Money arrives as a float of ten-thousandths, which rounds
+                // back to them exactly
+                let values: Vec<Option<(i128, u32)>> = cells::<f64>(rows, col_idx, col_name)?
+                    .into_iter()
+                    .map(|m| m.map(|m| ((m * MONEY_FACTOR).round() as i128, MONEY_SCALE)))
+                    .collect();
+                decimal::to_series(col_name, &values)?
+            }
+            DataType::Decimal(_, _) => {
+                let values: Vec<Option<(i128, u32)>> = cells::<Numeric>(rows, col_idx, col_name)?
+                    .into_iter()
+                    .map(|n| n.map(|n| (n.value(), n.scale() as u32)))
+                    .collect();
+                decimal::to_series(col_name, &values)?
+            }
+            DataType::Binary => {
+                let values: Vec<Option<&[u8]>> = cells(rows, col_idx, col_name)?;
+                BinaryChunked::from_iter_options(col_name.into(), values.into_iter()).into_series()
+            }
+            DataType::Datetime(_, None) => datetime_series(
+                col_name,
+                &cells::<NaiveDateTime>(rows, col_idx, col_name)?,
+                None,
+            )?,
+            DataType::Datetime(_, Some(_)) => {
+                // Instants at any offset, kept in UTC
+                let values: Vec<Option<NaiveDateTime>> =
+                    cells::<DateTime<FixedOffset>>(rows, col_idx, col_name)?
+                        .into_iter()
+                        .map(|t| t.map(|t| t.naive_utc()))
+                        .collect();
+                datetime_series(col_name, &values, Some("UTC"))?
+            }
+            DataType::Date => {
+                let days: Vec<Option<i32>> = cells::<NaiveDate>(rows, col_idx, col_name)?
+                    .into_iter()
+                    .map(|d| d.map(|d| d.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE))
+                    .collect();
+                cast_series(Series::new(col_name.into(), days), DataType::Date)?
+            }
+            DataType::Time => {
+                let nanos: Vec<Option<i64>> = cells::<NaiveTime>(rows, col_idx, col_name)?
+                    .into_iter()
+                    .map(|t| {
+                        t.map(|t| {
+                            t.num_seconds_from_midnight() as i64 * 1_000_000_000
+                                + t.nanosecond() as i64
+                        })
+                    })
+                    .collect();
+                cast_series(Series::new(col_name.into(), nanos), DataType::Time)?
+            }
+            _ if column_type == ColumnType::Guid => {
+                let values: Vec<Option<String>> = cells::<Uuid>(rows, col_idx, col_name)?
+                    .into_iter()
+                    .map(|u| u.map(|u| u.to_string()))
+                    .collect();
+                Series::new(col_name.into(), values)
+            }
+            _ => {
+                let values: Vec<Option<&str>> = cells(rows, col_idx, col_name)?;
+                Series::new(col_name.into(), values)
+            }
         };
 
         series_vec.push(series);
@@ -163,3 +628,228 @@ fn rows_to_dataframe(rows: &[TiberiusRow]) -> Result<DataFrame> {
     let columns: Vec<_> = series_vec.into_iter().map(|s| s.into_column()).collect();
     DataFrame::new(columns).map_err(|e| IndustryDbError::PolarsError(e.to_string()))
 }
+
+/// Data of the first non-null value of a column
+fn first_value(rows: &[TiberiusRow], col_idx: usize) -> Option<&ColumnData<'static>> {
+    rows.iter()
+        .filter_map(|row| row.cells().nth(col_idx).map(|(_, data)| data))
+        .find(|data| match data {
+            ColumnData::U8(v) => v.is_some(),
+            ColumnData::I16(v) => v.is_some(),
+            ColumnData::I32(v) => v.is_some(),
+            ColumnData::I64(v) => v.is_some(),
+            ColumnData::F32(v) => v.is_some(),
+            ColumnData::F64(v) => v.is_some(),
+            _ => true,
+        })
+}
+
+/// Values of one column as `T`; only SQL nulls read as null, and a value
+/// of another type is a decode error naming the column
+fn cells<'a, T: FromSql<'a>>(
+    rows: &'a [TiberiusRow],
+    col_idx: usize,
+    col_name: &str,
+) -> Result<Vec<Option<T>>> {
+    rows.iter()
+        .map(|row| match row.cells().nth(col_idx) {
+            Some((_, data)) if is_null(data) => Ok(None),
+            _ => row.try_get::<T, _>(col_idx).map_err(|e| {
+                IndustryDbError::query_error(format!("Cannot decode column '{}': {}", col_name, e))
+            }),
+        })
+        .collect()
+}
+
+/// Whether `data` is an SQL null, whatever its type
+fn is_null(data: &ColumnData<'_>) -> bool {
+    match data {
+        ColumnData::U8(v) => v.is_none(),
+        ColumnData::I16(v) => v.is_none(),
+        ColumnData::I32(v) => v.is_none(),
+        ColumnData::I64(v) => v.is_none(),
+        ColumnData::F32(v) => v.is_none(),
+        ColumnData::F64(v) => v.is_none(),
+        ColumnData::Bit(v) => v.is_none(),
+        ColumnData::String(v) => v.is_none(),
+        ColumnData::Guid(v) => v.is_none(),
+        ColumnData::Binary(v) => v.is_none(),
+        ColumnData::Numeric(v) => v.is_none(),
+        ColumnData::Xml(v) => v.is_none(),
+        ColumnData::DateTime(v) => v.is_none(),
+        ColumnData::SmallDateTime(v) => v.is_none(),
+        ColumnData::Time(v) => v.is_none(),
+        ColumnData::Date(v) => v.is_none(),
+        ColumnData::DateTime2(v) => v.is_none(),
+        ColumnData::DateTimeOffset(v) => v.is_none(),
+    }
+}
+
+/// Series of one column decoded as `T`
+fn typed_series<T>(rows: &[TiberiusRow], col_idx: usize, col_name: &str) -> Result<Series>
+where
+    T: for<'a> FromSql<'a>,
+    Series: NamedFrom<Vec<Option<T>>, [Option<T>]>,
+{
+    Ok(Series::new(
+        col_name.into(),
+        cells::<T>(rows, col_idx, col_name)?,
+    ))
+}
+
+/// Days between 0001-01-01 and 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Digits and scale of `money`; `smallmoney` fits in them
+const MONEY_PRECISION: usize = 19;
+const MONEY_SCALE: u32 = 4;
+const MONEY_FACTOR: f64 = 10_000.0;
+
+/// Datetime column in nanoseconds, the precision of `datetime2`
+///
+/// Nanoseconds only reach the years 1677 to 2262, so a column holding a
+/// value outside them (such as a 9999-12-31 end date) is kept in
+/// microseconds instead.
+fn datetime_series(
+    name: &str,
+    values: &[Option<NaiveDateTime>],
+    tz: Option<&str>,
+) -> Result<Series> {
+    let nanos: Option<Vec<Option<i64>>> = values
+        .iter()
+        .map(|v| match v {
+            Some(t) => t.and_utc().timestamp_nanos_opt().map(Some),
+            None => Some(None),
+        })
+        .collect();
+    let (physical, unit) = match nanos {
+        Some(nanos) => (nanos, TimeUnit::Nanoseconds),
+        None => (
+            values
+                .iter()
+                .map(|v| v.map(|t| t.and_utc().timestamp_micros()))
+                .collect(),
+            TimeUnit::Microseconds,
+        ),
+    };
+    cast_series(
+        Series::new(name.into(), physical),
+        DataType::Datetime(unit, tz.map(Into::into)),
+    )
+}
+
+/// Series of physical values cast to their logical type
+fn cast_series(series: Series, dtype: DataType) -> Result<Series> {
+    series
+        .cast(&dtype)
+        .map_err(|e| IndustryDbError::PolarsError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_dtype_from_metadata() {
+        let dtype = |column_type, first| column_dtype(column_type, first, DecimalMode::Decimal);
+        assert_eq!(dtype(ColumnType::Int2, None), DataType::Int16);
+        assert_eq!(dtype(ColumnType::Bitn, None), DataType::Boolean);
+        assert_eq!(dtype(ColumnType::Guid, None), DataType::String);
+        assert_eq!(
+            dtype(ColumnType::Datetime2, None),
+            DataType::Datetime(TimeUnit::Nanoseconds, None)
+        );
+
+        // Nullable ints and floats take their width from the data
+        let small = ColumnData::I16(Some(3));
+        assert_eq!(dtype(ColumnType::Intn, Some(&small)), DataType::Int16);
+        assert_eq!(dtype(ColumnType::Intn, None), DataType::Int64);
+        let real = ColumnData::F32(Some(0.5));
+        assert_eq!(dtype(ColumnType::Floatn, Some(&real)), DataType::Float32);
+    }
+
+    #[tokio::test]
+    async fn test_policy_applies_to_crud() {
+        use industrydb_core::policy::AccessPolicy;
+        use industrydb_core::traits::CrudOperations;
+        use std::collections::HashMap;
+
+        let mut config = ConnectionConfig::mssql(
+            "localhost".to_string(),
+            "test".to_string(),
+            "sa".to_string(),
+            "pass".to_string(),
+        );
+        config.policy = Some(AccessPolicy::new(HashMap::from([(
+            "readings".to_string(),
+            vec!["ts".to_string(), "value".to_string()],
+        )])));
+        // The pool connects on first use, after the policy check
+        let conn = MssqlConnector::new(&config).await.unwrap();
+
+        let denied = |r: Result<usize>| matches!(r, Err(IndustryDbError::AccessDenied(_)));
+        assert!(denied(
+            conn.delete("readings", Some("operator = 'x'"), &[]).await
+        ));
+        let values = HashMap::from([("value".to_string(), "0".to_string())]);
+        assert!(denied(
+            conn.update("readings", &values, Some("operator = 'x'"), &[])
+                .await
+        ));
+    }
+
+    #[test]
+    fn test_is_null() {
+        assert!(is_null(&ColumnData::I64(None)));
+        assert!(is_null(&ColumnData::String(None)));
+        assert!(is_null(&ColumnData::DateTime2(None)));
+        assert!(!is_null(&ColumnData::I32(Some(0))));
+        assert!(!is_null(&ColumnData::String(Some("".into()))));
+    }
+
+    #[test]
+    fn test_decimal_mode() {
+        for column_type in [ColumnType::Numericn, ColumnType::Money] {
+            assert_eq!(
+                column_dtype(column_type, None, DecimalMode::Float),
+                DataType::Float64
+            );
+        }
+        assert_eq!(
+            column_dtype(ColumnType::Numericn, None, DecimalMode::Decimal),
+            DataType::Decimal(Some(decimal::MAX_PRECISION), Some(0))
+        );
+        assert_eq!(
+            column_dtype(ColumnType::Money4, None, DecimalMode::Decimal),
+            DataType::Decimal(Some(MONEY_PRECISION), Some(4))
+        );
+    }
+
+    #[test]
+    fn test_datetime_precision() {
+        let t = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_nano_opt(12, 0, 0, 123_456_700)
+            .unwrap();
+        let series = datetime_series("ts", &[Some(t), None], None).unwrap();
+        assert_eq!(
+            series.dtype(),
+            &DataType::Datetime(TimeUnit::Nanoseconds, None)
+        );
+        let physical = series.to_physical_repr();
+        assert_eq!(
+            physical.i64().unwrap().get(0),
+            t.and_utc().timestamp_nanos_opt()
+        );
+
+        let end = NaiveDate::from_ymd_opt(9999, 12, 31)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let series = datetime_series("valid_to", &[Some(t), Some(end)], Some("UTC")).unwrap();
+        assert_eq!(
+            series.dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into()))
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! Reads through ConnectorX (`connectorx` feature)
+
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use connectorx::get_arrow::get_arrow;
+use connectorx::prelude::{CXQuery, SourceConn};
+use industrydb_core::config::ConnectionConfig;
+use industrydb_core::error::{IndustryDbError, Result};
+use polars::prelude::*;
+use std::io::Cursor;
+
+/// ConnectorX URL of the configured server, with the same defaults as
+/// [`MssqlConnector::new`](crate::MssqlConnector::new)
+pub(crate) fn connection_url(config: &ConnectionConfig) -> String {
+    format!(
+        "mssql://{}:{}@{}:{}/{}",
+        encode(config.username.as_deref().unwrap_or("sa")),
+        encode(config.password.as_deref().unwrap_or("")),
+        config
+            .host
+            .as_deref()
+            .or(config.server.as_deref())
+            .unwrap_or("localhost"),
+        config.port.unwrap_or(1433),
+        encode(config.database.as_deref().unwrap_or("master"))
+    )
+}
+
+/// Percent-encode a URL part, since SQL Server passwords often hold `@`
+/// and `:`
+fn encode(part: &str) -> String {
+    part.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Run `sql` through ConnectorX against the database at `url`
+///
+/// ConnectorX runs its own threads and runtime, so this blocks; call it
+/// from a blocking task.
+pub(crate) fn read_sql(url: &str, sql: &str) -> Result<DataFrame> {
+    let source = SourceConn::try_from(url).map_err(cx_error)?;
+    let destination = get_arrow(&source, None, &[CXQuery::naked(sql)], None).map_err(cx_error)?;
+    to_dataframe(&destination.arrow().map_err(cx_error)?)
+}
+
+/// Arrow batches as one DataFrame, passed through an in-memory IPC file
+///
+/// ConnectorX and Polars use different Arrow implementations; IPC is the
+/// format both read and write without copying column by column.
+fn to_dataframe(batches: &[RecordBatch]) -> Result<DataFrame> {
+    let Some(first) = batches.first() else {
+        return Ok(DataFrame::empty());
+    };
+    let mut buffer = Vec::new();
+    let mut writer = FileWriter::try_new(&mut buffer, &first.schema()).map_err(cx_error)?;
+    for batch in batches {
+        writer.write(batch).map_err(cx_error)?;
+    }
+    writer.finish().map_err(cx_error)?;
+    drop(writer);
+    Ok(IpcReader::new(Cursor::new(buffer)).finish()?)
+}
+
+fn cx_error<E: std::fmt::Display>(err: E) -> IndustryDbError {
+    IndustryDbError::ConnectorXError(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_url() {
+        let config = ConnectionConfig::mssql(
+            "plant-sql".to_string(),
+            "historian".to_string(),
+            "reader".to_string(),
+            "p@ss:word".to_string(),
+        );
+        assert!(connection_url(&config).ends_with("reader:p%40ss%3Aword@plant-sql:1433/historian"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Catalog queries for MSSQL schema introspection
+
+use industrydb_core::schema::quote_literal;
+
+/// All schemas in the current database
+pub(crate) fn list_schemas_sql() -> String {
+    "SELECT SCHEMA_NAME AS schema_name FROM INFORMATION_SCHEMA.SCHEMATA ORDER BY SCHEMA_NAME"
+        .to_string()
+}
+
+/// Base tables in the given schema (the user's default schema when `None`)
+pub(crate) fn list_tables_sql(schema: Option<&str>) -> String {
+    let schema = schema
+        .map(quote_literal)
+        .unwrap_or_else(|| "SCHEMA_NAME()".to_string());
+    format!(
+        "SELECT TABLE_NAME AS table_name FROM INFORMATION_SCHEMA.TABLES \
+         WHERE TABLE_TYPE = 'BASE TABLE' AND TABLE_SCHEMA = {} ORDER BY TABLE_NAME",
+        schema
+    )
+}
+
+/// Whether a user table exists; `OBJECT_ID` resolves qualified names
+pub(crate) fn table_exists_sql(table: &str) -> String {
+    format!(
+        "SELECT CASE WHEN OBJECT_ID({}, 'U') IS NULL THEN 0 ELSE 1 END AS table_exists",
+        quote_literal(table)
+    )
+}
+
+/// Column metadata from `sys.columns`; `OBJECT_ID` resolves qualified names
+pub(crate) fn describe_table_sql(table: &str) -> String {
+    format!(
+        "SELECT c.name AS column_name, t.name AS data_type, \
+         CAST(c.is_nullable AS INT) AS is_nullable, \
+         OBJECT_DEFINITION(c.default_object_id) AS column_default \
+         FROM sys.columns c JOIN sys.types t ON c.user_type_id = t.user_type_id \
+         WHERE c.object_id = OBJECT_ID({}) ORDER BY c.column_id",
+        quote_literal(table)
+    )
+}
+
+/// Primary key columns via `sys.indexes`
+pub(crate) fn primary_keys_sql(table: &str) -> String {
+    format!(
+        "SELECT c.name AS column_name, CAST(ic.key_ordinal AS INT) AS ordinal_position \
+         FROM sys.indexes i \
+         JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id \
+         JOIN sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id \
+         WHERE i.object_id = OBJECT_ID({}) AND i.is_primary_key = 1 \
+         ORDER BY ic.key_ordinal",
+        quote_literal(table)
+    )
+}
+
+/// Index key columns via `sys.indexes`; heaps and INCLUDE columns are skipped
+pub(crate) fn indexes_sql(table: &str) -> String {
+    format!(
+        "SELECT i.name AS index_name, c.name AS column_name, \
+         CAST(i.is_unique AS INT) AS is_unique, CAST(ic.key_ordinal AS INT) AS ordinal_position \
+         FROM sys.indexes i \
+         JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id \
+         JOIN sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id \
+         WHERE i.object_id = OBJECT_ID({}) AND i.name IS NOT NULL \
+         AND ic.is_included_column = 0 \
+         ORDER BY i.name, ic.key_ordinal",
+        quote_literal(table)
+    )
+}
+
+/// Foreign key columns via `sys.foreign_keys`
+pub(crate) fn foreign_keys_sql(table: &str) -> String {
+    format!(
+        "SELECT fk.name AS constraint_name, pc.name AS column_name, \
+         OBJECT_SCHEMA_NAME(fk.referenced_object_id) + '.' + OBJECT_NAME(fk.referenced_object_id) \
+         AS referenced_table, rc.name AS referenced_column \
+         FROM sys.foreign_keys fk \
+         JOIN sys.foreign_key_columns fkc ON fkc.constraint_object_id = fk.object_id \
+         JOIN sys.columns pc ON pc.object_id = fkc.parent_object_id \
+         AND pc.column_id = fkc.parent_column_id \
+         JOIN sys.columns rc ON rc.object_id = fkc.referenced_object_id \
+         AND rc.column_id = fkc.referenced_column_id \
+         WHERE fk.parent_object_id = OBJECT_ID({}) \
+         ORDER BY fk.name, fkc.constraint_column_id",
+        quote_literal(table)
+    )
+}
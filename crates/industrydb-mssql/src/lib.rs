@@ -1,7 +1,13 @@
 //! MSSQL connector implementation for IndustryDB
 
 mod connector;
+#[cfg(feature = "connectorx")]
+mod fast_read;
+mod introspection;
 mod operations;
+mod pool;
+mod procedure;
 
 pub use connector::MssqlConnector;
 pub use industrydb_core::traits::{CrudOperations, DatabaseConnector};
+pub use pool::{MssqlClient, MssqlManager};
@@ -1,9 +1,14 @@
 //! CRUD operations for MSSQL
 
 use crate::connector::MssqlConnector;
+use crate::introspection;
 use async_trait::async_trait;
 use industrydb_core::{
+    dialect::SelectOptions,
     error::{IndustryDbError, Result},
+    params::Value,
+    query::select_list_sql,
+    schema::{self, quote_literal},
     traits::{CrudOperations, DatabaseConnector},
 };
 use polars::prelude::*;
@@ -22,6 +27,10 @@ impl CrudOperations for MssqlConnector {
             .map(|s| s.to_string())
             .collect();
 
+        let dialect = self.dialect();
+        let table_name = dialect.identifier(table)?;
+        let column_list = dialect.identifiers(&columns)?.join(", ");
+
         let mut rows_inserted = 0;
 
         for row_idx in 0..data.height() {
@@ -30,18 +39,18 @@ impl CrudOperations for MssqlConnector {
             for col_name in columns.iter() {
                 let column = data.column(col_name)?;
                 let series = column.as_materialized_series();
-                let value = format_value(series, row_idx)?;
+                let value = dialect.format_value(series, row_idx)?;
                 values.push(value);
             }
 
             let sql = format!(
                 "INSERT INTO {} ({}) VALUES ({})",
-                table,
-                columns.join(", "),
+                table_name,
+                column_list,
                 values.join(", ")
             );
 
-            match self.execute(&sql).await {
+            match self.execute_statement(&sql, &[]).await {
                 Ok(_) => rows_inserted += 1,
                 Err(e) => {
                     return Err(IndustryDbError::query_error(format!(
@@ -55,28 +64,61 @@ impl CrudOperations for MssqlConnector {
         Ok(rows_inserted)
     }
 
-    async fn select(
+    async fn upsert_keeping(
         &self,
         table: &str,
-        columns: Option<&[String]>,
-        where_clause: Option<&str>,
-        limit: Option<usize>,
-    ) -> Result<DataFrame> {
-        let cols = columns
-            .map(|c| c.join(", "))
-            .unwrap_or_else(|| "*".to_string());
+        data: DataFrame,
+        key_columns: &[String],
+        keep: &[String],
+    ) -> Result<usize> {
+        if data.height() == 0 {
+            return Ok(0);
+        }
 
-        let mut sql = format!("SELECT {} FROM {}", cols, table);
+        let dialect = self.dialect();
+        let columns: Vec<String> = data
+            .get_column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let rows = dialect.format_rows(&data)?;
 
-        if let Some(where_cond) = where_clause {
-            sql.push_str(&format!(" WHERE {}", where_cond));
-        }
+        let mut conn = self
+            .pool()
+            .get()
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
 
-        if let Some(lim) = limit {
-            sql.push_str(&format!(" LIMIT {}", lim));
+        let mut rows_affected = 0;
+        for chunk in rows.chunks(dialect.max_rows_per_statement()) {
+            let sql = dialect.upsert_sql(table, &columns, key_columns, keep, chunk)?;
+            let sql = self.enforce_policy(&sql)?;
+            let result = conn
+                .execute(&*sql, &[])
+                .await
+                .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+            rows_affected += result.rows_affected().iter().sum::<u64>() as usize;
         }
 
-        self.execute(&sql).await
+        Ok(rows_affected)
+    }
+
+    async fn select(
+        &self,
+        table: &str,
+        columns: Option<&[String]>,
+        where_clause: Option<&str>,
+        params: &[Value],
+        options: &SelectOptions,
+    ) -> Result<DataFrame> {
+        let dialect = self.dialect();
+        let cols = match columns {
+            Some(c) => select_list_sql(c, dialect)?,
+            None => "*".to_string(),
+        };
+
+        let sql = dialect.select_sql(&cols, &dialect.identifier(table)?, where_clause, options);
+        self.execute_with_params(&sql, params).await
     }
 
     async fn update(
@@ -84,94 +126,126 @@ impl CrudOperations for MssqlConnector {
         table: &str,
         values: &HashMap<String, String>,
         where_clause: Option<&str>,
+        params: &[Value],
     ) -> Result<usize> {
         if values.is_empty() {
             return Err(IndustryDbError::invalid_parameter("No values to update"));
         }
 
-        let set_clause: Vec<String> = values
+        let dialect = self.dialect();
+        let set_clause = values
             .iter()
-            .map(|(col, val)| format!("{} = {}", col, val))
-            .collect();
+            .map(|(col, val)| Ok(format!("{} = {}", dialect.identifier(col)?, val)))
+            .collect::<Result<Vec<_>>>()?;
 
-        let mut sql = format!("UPDATE {} SET {}", table, set_clause.join(", "));
+        let mut sql = format!(
+            "UPDATE {} SET {}",
+            dialect.identifier(table)?,
+            set_clause.join(", ")
+        );
 
         if let Some(where_cond) = where_clause {
             sql.push_str(&format!(" WHERE {}", where_cond));
         }
 
-        let mut conn = self
-            .pool()
-            .get()
-            .await
-            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
-
-        let result = conn
-            .execute(&sql, &[])
-            .await
-            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
-
-        Ok(result.rows_affected().iter().sum::<u64>() as usize)
+        Ok(self.execute_statement(&sql, params).await? as usize)
     }
 
-    async fn delete(&self, table: &str, where_clause: Option<&str>) -> Result<usize> {
-        let mut sql = format!("DELETE FROM {}", table);
+    async fn delete(
+        &self,
+        table: &str,
+        where_clause: Option<&str>,
+        params: &[Value],
+    ) -> Result<usize> {
+        let mut sql = format!("DELETE FROM {}", self.dialect().identifier(table)?);
 
         if let Some(where_cond) = where_clause {
             sql.push_str(&format!(" WHERE {}", where_cond));
         }
 
-        let mut conn = self
-            .pool()
-            .get()
-            .await
-            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+        Ok(self.execute_statement(&sql, params).await? as usize)
+    }
 
-        let result = conn
-            .execute(&sql, &[])
-            .await
-            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+    async fn create_table(&self, table: &str, schema: &Schema, if_not_exists: bool) -> Result<()> {
+        if schema.is_empty() {
+            return Err(IndustryDbError::invalid_parameter(
+                "Cannot create a table without columns",
+            ));
+        }
 
-        Ok(result.rows_affected().iter().sum::<u64>() as usize)
+        let columns = schema
+            .iter()
+            .map(|(name, dtype)| {
+                Ok(format!(
+                    "{} {}",
+                    self.dialect().identifier(name)?,
+                    self.dialect().column_type(dtype)?
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let sql = {
+            let create = format!(
+                "CREATE TABLE {} ({})",
+                self.dialect().identifier(table)?,
+                columns.join(", ")
+            );
+            if if_not_exists {
+                format!(
+                    "IF OBJECT_ID({}, 'U') IS NULL {}",
+                    quote_literal(&self.dialect().identifier(table)?),
+                    create
+                )
+            } else {
+                create
+            }
+        };
+
+        self.execute_statement(&sql, &[]).await?;
+        Ok(())
     }
-}
 
-fn format_value(series: &Series, idx: usize) -> Result<String> {
-    if series.is_null().get(idx).unwrap_or(false) {
-        return Ok("NULL".to_string());
+    async fn table_exists(&self, table: &str) -> Result<bool> {
+        let df = self
+            .execute(&introspection::table_exists_sql(table))
+            .await?;
+        schema::first_value_bool(&df)
     }
 
-    match series.dtype() {
-        DataType::Int8
-        | DataType::Int16
-        | DataType::Int32
-        | DataType::Int64
-        | DataType::UInt8
-        | DataType::UInt16
-        | DataType::UInt32
-        | DataType::UInt64
-        | DataType::Float32
-        | DataType::Float64 => {
-            let val = series.get(idx).unwrap();
-            Ok(format!("{}", val))
-        }
-        DataType::String => {
-            let val = series.get(idx).unwrap();
-            let s = val.to_string().replace('\'', "''");
-            Ok(format!("'{}'", s))
-        }
-        DataType::Boolean => {
-            let val = series.get(idx).unwrap();
-            Ok(if val.to_string() == "true" {
-                "TRUE"
-            } else {
-                "FALSE"
-            }
-            .to_string())
-        }
-        _ => {
-            let val = series.get(idx).unwrap();
-            Ok(format!("'{}'", val.to_string().replace('\'', "''")))
-        }
+    async fn drop_table(&self, table: &str, if_exists: bool) -> Result<()> {
+        let drop = format!("DROP TABLE {}", self.dialect().identifier(table)?);
+        let sql = if if_exists {
+            format!(
+                "IF OBJECT_ID({}, 'U') IS NOT NULL {}",
+                quote_literal(&self.dialect().identifier(table)?),
+                drop
+            )
+        } else {
+            drop
+        };
+        self.execute_statement(&sql, &[]).await?;
+        Ok(())
+    }
+
+    async fn truncate(&self, table: &str) -> Result<()> {
+        self.execute_statement(
+            &format!("TRUNCATE TABLE {}", self.dialect().identifier(table)?),
+            &[],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Uses `sp_rename`, which takes the current (qualified) name and the new bare name
+    async fn rename_table(&self, table: &str, new_name: &str) -> Result<()> {
+        let (_, new_name) = schema::split_qualified(new_name);
+        self.dialect().validate_identifier(new_name)?;
+        let sql = format!(
+            "EXEC sp_rename {}, {}",
+            quote_literal(&self.dialect().identifier(table)?),
+            quote_literal(new_name)
+        );
+        self.execute_statement(&sql, &[]).await?;
+        Ok(())
     }
 }
@@ -0,0 +1,97 @@
+//! Connection pool that never hands out a connection inside a transaction
+//!
+//! `BEGIN` and `COMMIT` go out as plain batches on a pooled connection, so
+//! a failed `COMMIT` or `ROLLBACK`, or a future dropped halfway through,
+//! would return the connection with its transaction still open and the next
+//! borrower would run inside it. [`MssqlClient`] records an open
+//! transaction, and [`MssqlManager`] closes such connections instead of
+//! returning them to the pool.
+
+use async_trait::async_trait;
+use bb8::ManageConnection;
+use bb8_tiberius::ConnectionManager;
+use std::ops::{Deref, DerefMut};
+
+type Client = <ConnectionManager as ManageConnection>::Connection;
+
+/// Pool manager discarding connections left inside a transaction
+pub struct MssqlManager(ConnectionManager);
+
+impl MssqlManager {
+    /// Manage connections made with `config`
+    pub fn new(config: tiberius::Config) -> Self {
+        Self(ConnectionManager::new(config))
+    }
+}
+
+/// Pooled tiberius client that remembers an open transaction
+pub struct MssqlClient {
+    client: Client,
+    in_transaction: bool,
+}
+
+impl MssqlClient {
+    /// Run `BEGIN TRANSACTION`; until [`commit`](Self::commit) or
+    /// [`rollback`](Self::rollback) succeed, the connection is closed
+    /// rather than returned to the pool
+    pub(crate) async fn begin(&mut self) -> tiberius::Result<()> {
+        self.in_transaction = true;
+        self.batch("BEGIN TRANSACTION").await
+    }
+
+    /// Run `COMMIT TRANSACTION`
+    pub(crate) async fn commit(&mut self) -> tiberius::Result<()> {
+        self.batch("COMMIT TRANSACTION").await?;
+        self.in_transaction = false;
+        Ok(())
+    }
+
+    /// Run `ROLLBACK TRANSACTION`
+    pub(crate) async fn rollback(&mut self) -> tiberius::Result<()> {
+        self.batch("ROLLBACK TRANSACTION").await?;
+        self.in_transaction = false;
+        Ok(())
+    }
+
+    /// Run `sql` as a plain batch: inside sp_executesql, BEGIN and COMMIT
+    /// would trip the server's transaction count check
+    async fn batch(&mut self, sql: &str) -> tiberius::Result<()> {
+        self.client.simple_query(sql).await?.into_results().await?;
+        Ok(())
+    }
+}
+
+impl Deref for MssqlClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl DerefMut for MssqlClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        &mut self.client
+    }
+}
+
+#[async_trait]
+impl ManageConnection for MssqlManager {
+    type Connection = MssqlClient;
+    type Error = <ConnectionManager as ManageConnection>::Error;
+
+    async fn connect(&self) -> Result<MssqlClient, Self::Error> {
+        Ok(MssqlClient {
+            client: self.0.connect().await?,
+            in_transaction: false,
+        })
+    }
+
+    async fn is_valid(&self, conn: &mut MssqlClient) -> Result<(), Self::Error> {
+        self.0.is_valid(&mut conn.client).await
+    }
+
+    fn has_broken(&self, conn: &mut MssqlClient) -> bool {
+        conn.in_transaction || self.0.has_broken(&mut conn.client)
+    }
+}
@@ -0,0 +1,102 @@
+//! Stored procedure calls for MSSQL
+//!
+//! A call runs as one batch: output parameters are bound to declared
+//! variables, the return value is captured with `EXEC @rc = ...`, and a
+//! final SELECT returns both after the procedure's own result sets.
+
+use industrydb_core::{
+    dialect::{Dialect, MssqlDialect},
+    error::Result,
+    params::Value,
+    procedure::{validate_args, ParamMode, ProcedureArg},
+};
+
+/// Type of output variables declared without an explicit type
+const DEFAULT_OUTPUT_TYPE: &str = "NVARCHAR(4000)";
+
+/// Batch calling `name` and the values bound to its placeholders
+///
+/// The last result set of the batch holds the return value in its first
+/// column, followed by one column per output argument.
+pub(crate) fn call_sql(name: &str, args: &[ProcedureArg]) -> Result<(String, Vec<Value>)> {
+    validate_args(args)?;
+    let dialect = &MssqlDialect;
+    let mut params = Vec::new();
+    let mut bind = |value: &Value| {
+        params.push(value.clone());
+        dialect.placeholder(params.len())
+    };
+
+    let mut declarations = vec!["DECLARE @__return INT;".to_string()];
+    let mut passed = Vec::new();
+    let mut outputs = vec!["@__return AS [return_value]".to_string()];
+    for (index, arg) in args.iter().enumerate() {
+        let name = arg
+            .name
+            .as_deref()
+            .map(|n| n.strip_prefix('@').unwrap_or(n));
+        let value = match arg.mode {
+            ParamMode::In => bind(&arg.value),
+            ParamMode::Out | ParamMode::InOut => {
+                let var = format!("@__out{}", index + 1);
+                let ty = arg.sql_type.as_deref().unwrap_or(DEFAULT_OUTPUT_TYPE);
+                let init = match arg.mode {
+                    ParamMode::InOut => format!(" = {}", bind(&arg.value)),
+                    _ => String::new(),
+                };
+                declarations.push(format!("DECLARE {} {}{};", var, ty, init));
+                outputs.push(format!(
+                    "{} AS {}",
+                    var,
+                    dialect.quote_identifier(name.unwrap_or_default())
+                ));
+                format!("{} OUTPUT", var)
+            }
+        };
+        passed.push(match name {
+            Some(name) => format!("@{} = {}", name, value),
+            None => value,
+        });
+    }
+
+    let mut exec = format!("EXEC @__return = {}", dialect.identifier(name)?);
+    if !passed.is_empty() {
+        exec.push(' ');
+        exec.push_str(&passed.join(", "));
+    }
+    let sql = format!(
+        "SET NOCOUNT ON; {} {}; SELECT {};",
+        declarations.join(" "),
+        exec,
+        outputs.join(", ")
+    );
+    Ok((sql, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_sql_binds_outputs_to_variables() {
+        let args = vec![
+            ProcedureArg::input(3),
+            ProcedureArg::named("@line", "L1"),
+            ProcedureArg::output("total").with_type("INT"),
+            ProcedureArg::in_out("note", "x"),
+        ];
+        let (sql, params) = call_sql("dbo.line_stats", &args).unwrap();
+        assert_eq!(
+            sql,
+            "SET NOCOUNT ON; DECLARE @__return INT; DECLARE @__out3 INT; \
+             DECLARE @__out4 NVARCHAR(4000) = @P3; \
+             EXEC @__return = [dbo].[line_stats] @P1, @line = @P2, \
+             @total = @__out3 OUTPUT, @note = @__out4 OUTPUT; \
+             SELECT @__return AS [return_value], @__out3 AS [total], @__out4 AS [note];"
+        );
+        assert_eq!(
+            params,
+            vec![Value::Int(3), Value::from("L1"), Value::from("x")]
+        );
+    }
+}
@@ -0,0 +1,703 @@
+//! Pipeline configuration files
+
+use industrydb_core::batching::{AdaptiveBatchConfig, AdaptiveBatcher};
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::quality::Check;
+use industrydb_core::schema::parse_dtype;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+fn default_name() -> String {
+    "pipeline".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_qos() -> u8 {
+    1
+}
+
+fn default_stream_batch_rows() -> usize {
+    1_000
+}
+
+fn default_flush_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_file_batch_rows() -> usize {
+    10_000
+}
+
+fn default_factor() -> f64 {
+    1.0
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1_000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60_000
+}
+
+/// A pipeline: where rows come from, how they are reshaped and checked, and
+/// where they go
+///
+/// ```toml
+/// name = "line1-temperatures"
+///
+/// [source]
+/// type = "mqtt"
+/// broker = "mqtt.plant.local"
+/// topics = ["line1/+/temperature"]
+///
+/// [[transforms]]
+/// op = "rename"
+/// columns = { temp = "value" }
+///
+/// [[transforms]]
+/// op = "scale"
+/// column = "value"
+/// factor = 0.1
+///
+/// [validation]
+/// not_null = ["value"]
+/// ranges = { value = { min = -50.0, max = 400.0 } }
+/// reject_table = "readings_rejected"
+///
+/// [target]
+/// table = "readings"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    /// Name used in logs and metrics
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Where rows come from
+    pub source: SourceConfig,
+    /// Steps applied to every batch, in order
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// Checks rows must pass before they are written
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// Where rows go
+    pub target: TargetConfig,
+    /// What to do when the source or target fails
+    #[serde(default)]
+    pub restart: RestartConfig,
+}
+
+impl PipelineConfig {
+    /// Load a `.toml`, `.yaml` or `.yml` file
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("yaml" | "yml") => Self::from_yaml(&content),
+            _ => Err(IndustryDbError::config_error(format!(
+                "Pipeline config {} must end in .toml, .yaml or .yml",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parse a TOML document
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse a YAML document
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let config: Self = serde_yaml::from_str(content)
+            .map_err(|e| IndustryDbError::config_error(format!("Invalid pipeline YAML: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check settings that parse but cannot work
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(IndustryDbError::config_error(msg));
+
+        match &self.source {
+            SourceConfig::Mqtt(mqtt) => {
+                if mqtt.topics.is_empty() {
+                    return invalid("MQTT source needs at least one topic".to_string());
+                }
+                if mqtt.qos > 2 {
+                    return invalid(format!("MQTT QoS must be 0, 1 or 2, not {}", mqtt.qos));
+                }
+            }
+            SourceConfig::Opcua(opcua) => {
+                if opcua.nodes.is_empty() {
+                    return invalid("OPC UA source needs at least one node".to_string());
+                }
+                if opcua.interval_ms == 0 {
+                    return invalid("OPC UA interval_ms must be positive".to_string());
+                }
+                let secured = opcua.security_mode != OpcUaSecurityMode::None;
+                if secured != (opcua.security_policy != OpcUaSecurityPolicy::None) {
+                    return invalid(
+                        "OPC UA security_policy and security_mode must both be none or both be set"
+                            .to_string(),
+                    );
+                }
+                if secured && opcua.pki_dir.is_none() {
+                    return invalid(
+                        "OPC UA security needs a pki_dir holding the client certificate"
+                            .to_string(),
+                    );
+                }
+                if opcua.username.is_some() != opcua.password.is_some() {
+                    return invalid(
+                        "OPC UA username and password must be given together".to_string(),
+                    );
+                }
+                if opcua.certificate.is_some() != opcua.private_key.is_some() {
+                    return invalid(
+                        "OPC UA certificate and private_key must be given together".to_string(),
+                    );
+                }
+                if opcua.username.is_some() && opcua.certificate.is_some() {
+                    return invalid(
+                        "OPC UA identity is either a username or a certificate, not both"
+                            .to_string(),
+                    );
+                }
+            }
+            SourceConfig::Table(table) => {
+                if table.table.is_some() == table.query.is_some() {
+                    return invalid("Table source needs exactly one of table or query".to_string());
+                }
+                if table.poll_interval_ms.is_some() && table.time_column.is_none() {
+                    return invalid(
+                        "Table source needs a time_column to poll for new rows".to_string(),
+                    );
+                }
+            }
+            SourceConfig::File(file) => {
+                file.format()?;
+            }
+        }
+
+        for transform in &self.transforms {
+            if let Transform::Cast { columns } = transform {
+                for dtype in columns.values() {
+                    parse_dtype(dtype)?;
+                }
+            }
+        }
+        for (column, range) in &self.validation.ranges {
+            if let (Some(min), Some(max)) = (range.min, range.max) {
+                if min > max {
+                    return invalid(format!("Range of {} has min above max", column));
+                }
+            }
+        }
+        for check in &self.validation.checks {
+            check.validate()?;
+        }
+        if self.validation.on_invalid == OnInvalid::Fail && self.validation.reject_table.is_some() {
+            return invalid("reject_table cannot be used with on_invalid = \"fail\"".to_string());
+        }
+        if self.target.table.is_empty() {
+            return invalid("Pipeline target needs a table".to_string());
+        }
+        if let Some(batching) = &self.target.batching {
+            AdaptiveBatcher::new(batching.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Where rows come from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceConfig {
+    /// Messages on MQTT topics (`mqtt` feature)
+    Mqtt(MqttSourceConfig),
+    /// Values of OPC UA nodes, polled (`opcua` feature)
+    Opcua(OpcUaSourceConfig),
+    /// Rows of a table or query on a source connection
+    Table(TableSourceConfig),
+    /// A CSV, Parquet or Arrow IPC file
+    File(FileSourceConfig),
+}
+
+/// MQTT subscription
+///
+/// Every message becomes rows with a `topic` and `received_at` column. JSON
+/// objects contribute one column per field and arrays of objects one row per
+/// element; any other payload is stored in a `value` column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttSourceConfig {
+    /// Broker host name
+    pub broker: String,
+    /// Broker port
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    /// Topic filters, wildcards allowed
+    pub topics: Vec<String>,
+    /// Client id; derived from the pipeline name when missing
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// User name
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Subscription QoS (0, 1 or 2)
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+    /// Rows collected before a batch is written
+    #[serde(default = "default_stream_batch_rows")]
+    pub batch_rows: usize,
+    /// Longest wait before a partial batch is written
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+}
+
+/// OPC UA nodes read on an interval
+///
+/// Every poll writes one row per node with `tag`, `value`, `source_time`
+/// and `good` (status) columns. Connects without security and anonymously
+/// unless configured otherwise; a secured connection needs the client
+/// certificate in `pki_dir` (or `create_keypair`) and the server
+/// certificate in its trusted folder (or `trust_server_certs`).
+///
+/// ```toml
+/// [source]
+/// type = "opcua"
+/// endpoint = "opc.tcp://plc:4840"
+/// security_policy = "basic256_sha256"
+/// security_mode = "sign_and_encrypt"
+/// pki_dir = "/etc/industrydb/pki"
+/// username = "historian"
+/// password = "secret"
+/// nodes = [{ node_id = "ns=2;s=Line1.Temperature", tag = "line1.temp" }]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpcUaSourceConfig {
+    /// Server endpoint, e.g. `opc.tcp://plc:4840`
+    pub endpoint: String,
+    /// Nodes to read
+    pub nodes: Vec<OpcUaNode>,
+    /// Time between polls
+    #[serde(default = "default_flush_interval_ms")]
+    pub interval_ms: u64,
+    /// Directory for the client's certificates and the trusted and
+    /// rejected server certificates; required with security
+    #[serde(default)]
+    pub pki_dir: Option<PathBuf>,
+    /// Security policy of the secure channel
+    #[serde(default)]
+    pub security_policy: OpcUaSecurityPolicy,
+    /// Whether messages are signed, or signed and encrypted
+    #[serde(default)]
+    pub security_mode: OpcUaSecurityMode,
+    /// User name to authenticate with
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password of `username`
+    #[serde(default)]
+    pub password: Option<String>,
+    /// User certificate (DER) to authenticate with
+    #[serde(default)]
+    pub certificate: Option<PathBuf>,
+    /// Private key (PEM) of `certificate`
+    #[serde(default)]
+    pub private_key: Option<PathBuf>,
+    /// Trust server certificates not yet in `pki_dir`; only for servers
+    /// whose identity is known some other way
+    #[serde(default)]
+    pub trust_server_certs: bool,
+    /// Generate a self-signed client certificate in `pki_dir` when it has
+    /// none
+    #[serde(default)]
+    pub create_keypair: bool,
+}
+
+/// OPC UA security policy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpcUaSecurityPolicy {
+    /// No security
+    #[default]
+    None,
+    /// Basic128Rsa15 (deprecated)
+    Basic128Rsa15,
+    /// Basic256 (deprecated)
+    Basic256,
+    /// Basic256Sha256
+    Basic256Sha256,
+    /// Aes128_Sha256_RsaOaep
+    Aes128Sha256RsaOaep,
+    /// Aes256_Sha256_RsaPss
+    Aes256Sha256RsaPss,
+}
+
+/// OPC UA message security mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpcUaSecurityMode {
+    /// Messages are neither signed nor encrypted
+    #[default]
+    None,
+    /// Messages are signed
+    Sign,
+    /// Messages are signed and encrypted
+    SignAndEncrypt,
+}
+
+/// One OPC UA node and the tag name its values are stored under
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpcUaNode {
+    /// Node id such as `ns=2;s=Line1.Temperature`
+    pub node_id: String,
+    /// Tag name; the node id when missing
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl OpcUaNode {
+    /// Name values of this node are stored under
+    pub fn tag(&self) -> &str {
+        self.tag.as_deref().unwrap_or(&self.node_id)
+    }
+}
+
+/// Rows of a table or query
+///
+/// Without `time_column` the rows are read once. With it, only rows newer
+/// than the last one read are fetched, so a restarted pipeline continues
+/// where it stopped; with `poll_interval_ms` as well the table is polled for
+/// new rows indefinitely. Rows sharing the last timestamp that arrive after
+/// it was read are missed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSourceConfig {
+    /// Table to read
+    #[serde(default)]
+    pub table: Option<String>,
+    /// Query to read instead of a table
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Increasing column used to fetch only new rows
+    #[serde(default)]
+    pub time_column: Option<String>,
+    /// Keep polling with this interval once caught up
+    #[serde(default)]
+    pub poll_interval_ms: Option<u64>,
+}
+
+/// A data file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSourceConfig {
+    /// File to read
+    pub path: PathBuf,
+    /// File format; taken from the extension when missing
+    #[serde(default)]
+    pub format: Option<FileFormat>,
+    /// Rows per batch
+    #[serde(default = "default_file_batch_rows")]
+    pub batch_rows: usize,
+}
+
+impl FileSourceConfig {
+    /// Format of the file
+    pub fn format(&self) -> Result<FileFormat> {
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+        match self.path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Ok(FileFormat::Csv),
+            Some("parquet") => Ok(FileFormat::Parquet),
+            Some("arrow" | "ipc" | "feather") => Ok(FileFormat::Ipc),
+            _ => Err(IndustryDbError::config_error(format!(
+                "Cannot tell the format of {}; set format to csv, parquet or ipc",
+                self.path.display()
+            ))),
+        }
+    }
+}
+
+/// Supported file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileFormat {
+    /// CSV with a header row
+    Csv,
+    /// Apache Parquet
+    Parquet,
+    /// Arrow IPC (Feather v2)
+    Ipc,
+}
+
+/// A step applied to every batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Transform {
+    /// Rename columns, old name -> new name
+    Rename { columns: BTreeMap<String, String> },
+    /// Keep only these columns, in this order
+    Select { columns: Vec<String> },
+    /// Remove these columns; missing ones are ignored
+    Drop { columns: Vec<String> },
+    /// Cast columns, name -> dtype (as accepted by `parse_dtype`)
+    Cast { columns: BTreeMap<String, String> },
+    /// `column * factor + offset`, for raw counts to engineering units
+    Scale {
+        column: String,
+        #[serde(default = "default_factor")]
+        factor: f64,
+        #[serde(default)]
+        offset: f64,
+    },
+    /// Polars SQL over the batch, registered as the table `batch`
+    Sql { query: String },
+}
+
+/// Checks applied after the transforms
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    /// Columns that must not be null
+    #[serde(default)]
+    pub not_null: Vec<String>,
+    /// Inclusive bounds on numeric columns
+    #[serde(default)]
+    pub ranges: BTreeMap<String, ValueRange>,
+    /// Checks on the batch as a whole; when one fails, every row of the
+    /// batch fails
+    #[serde(default)]
+    pub checks: Vec<Check>,
+    /// What happens to rows failing a check
+    #[serde(default)]
+    pub on_invalid: OnInvalid,
+    /// Table invalid rows are written to instead of being discarded
+    #[serde(default)]
+    pub reject_table: Option<String>,
+}
+
+/// Inclusive bounds; either side may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    /// Smallest allowed value
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// Largest allowed value
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+/// Handling of rows failing validation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnInvalid {
+    /// Write the valid rows and drop (or reject) the others
+    #[default]
+    Drop,
+    /// Stop the pipeline without writing the batch
+    Fail,
+}
+
+/// Where rows go
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetConfig {
+    /// Table rows are inserted into
+    pub table: String,
+    /// Create the target and reject tables from the first batch when missing
+    #[serde(default = "default_true")]
+    pub create: bool,
+    /// Insert in adaptively sized batches; one insert per batch when missing
+    #[serde(default)]
+    pub batching: Option<AdaptiveBatchConfig>,
+}
+
+/// Restarts after the source or target fails
+///
+/// The delay doubles after every consecutive failure and resets once a
+/// batch is written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestartConfig {
+    /// Give up after this many restarts in total; never when missing
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+    /// Delay before the first restart
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Longest delay between restarts
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: None,
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+impl RestartConfig {
+    /// Delay before restarting after `failures` consecutive failures
+    pub fn backoff(&self, failures: u32) -> std::time::Duration {
+        let exponent = failures.saturating_sub(1).min(32);
+        let millis = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ms);
+        std::time::Duration::from_millis(millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+name = "line1"
+
+[source]
+type = "mqtt"
+broker = "localhost"
+topics = ["line1/#"]
+
+[[transforms]]
+op = "rename"
+columns = { temp = "value" }
+
+[[transforms]]
+op = "scale"
+column = "value"
+factor = 0.1
+
+[validation]
+not_null = ["value"]
+ranges = { value = { min = -50.0, max = 400.0 } }
+
+[target]
+table = "readings"
+"#;
+
+    const YAML: &str = r#"
+name: line1
+source:
+  type: mqtt
+  broker: localhost
+  topics: ["line1/#"]
+transforms:
+  - op: rename
+    columns: {temp: value}
+  - op: scale
+    column: value
+    factor: 0.1
+validation:
+  not_null: [value]
+  ranges:
+    value: {min: -50.0, max: 400.0}
+target:
+  table: readings
+"#;
+
+    #[test]
+    fn test_toml_and_yaml_agree() {
+        let toml = PipelineConfig::from_toml(TOML).unwrap();
+        let yaml = PipelineConfig::from_yaml(YAML).unwrap();
+        assert_eq!(toml, yaml);
+
+        let SourceConfig::Mqtt(mqtt) = &toml.source else {
+            panic!("expected an MQTT source");
+        };
+        assert_eq!(mqtt.port, 1883);
+        assert_eq!(mqtt.qos, 1);
+        assert_eq!(
+            toml.transforms[1],
+            Transform::Scale {
+                column: "value".to_string(),
+                factor: 0.1,
+                offset: 0.0,
+            }
+        );
+        assert!(toml.target.create);
+        assert_eq!(toml.restart, RestartConfig::default());
+    }
+
+    #[test]
+    fn test_validate_rejects_unusable_settings() {
+        let table_source = |extra: &str| {
+            format!(
+                "[source]\ntype = \"table\"\n{}\n[target]\ntable = \"t\"\n",
+                extra
+            )
+        };
+        assert!(PipelineConfig::from_toml(&table_source("table = \"a\"")).is_ok());
+        assert!(PipelineConfig::from_toml(&table_source("")).is_err());
+        assert!(
+            PipelineConfig::from_toml(&table_source("table = \"a\"\npoll_interval_ms = 500"))
+                .is_err()
+        );
+
+        let file = "[source]\ntype = \"file\"\npath = \"data.json\"\n[target]\ntable = \"t\"\n";
+        assert!(PipelineConfig::from_toml(file).is_err());
+
+        let cast = "[source]\ntype = \"file\"\npath = \"data.csv\"\n\
+                    [[transforms]]\nop = \"cast\"\ncolumns = { value = \"nope\" }\n\
+                    [target]\ntable = \"t\"\n";
+        assert!(PipelineConfig::from_toml(cast).is_err());
+    }
+
+    #[test]
+    fn test_opcua_security_settings() {
+        let opcua = |extra: &str| {
+            format!(
+                "[source]\ntype = \"opcua\"\nendpoint = \"opc.tcp://plc:4840\"\n\
+                 nodes = [{{ node_id = \"ns=2;s=T\" }}]\n{}\n[target]\ntable = \"t\"\n",
+                extra
+            )
+        };
+        let config = PipelineConfig::from_toml(&opcua("")).unwrap();
+        let SourceConfig::Opcua(source) = &config.source else {
+            panic!("expected an OPC UA source");
+        };
+        assert_eq!(source.security_policy, OpcUaSecurityPolicy::None);
+        assert!(!source.trust_server_certs);
+
+        let secured = "security_policy = \"basic256_sha256\"\n\
+                       security_mode = \"sign_and_encrypt\"\npki_dir = \"pki\"";
+        assert!(PipelineConfig::from_toml(&opcua(secured)).is_ok());
+        assert!(PipelineConfig::from_toml(&opcua("security_mode = \"sign\"")).is_err());
+        assert!(PipelineConfig::from_toml(&opcua(
+            "security_policy = \"basic256_sha256\"\nsecurity_mode = \"sign\""
+        ))
+        .is_err());
+        assert!(PipelineConfig::from_toml(&opcua("username = \"historian\"")).is_err());
+        assert!(PipelineConfig::from_toml(&opcua(
+            "username = \"u\"\npassword = \"p\"\ncertificate = \"c.der\"\n\
+             private_key = \"k.pem\""
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let restart = RestartConfig {
+            max_restarts: None,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1_000,
+        };
+        let millis: Vec<u128> = (1..=6).map(|n| restart.backoff(n).as_millis()).collect();
+        assert_eq!(millis, vec![100, 200, 400, 800, 1_000, 1_000]);
+    }
+}
@@ -0,0 +1,381 @@
+//! Ingestion of OPC UA history reads
+//!
+//! A history read returns, per node, the values the server archived with
+//! their source timestamps and status codes. [`ingest_history`] takes that
+//! result set as rows of `node_id`, `timestamp`, `value` and an optional
+//! `quality` status code, maps it to a [`HistoryLayout`] and inserts it in
+//! batches. Reading the history is left to the OPC UA client in use, so
+//! this works without the `opcua` feature.
+
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::params::Value;
+use industrydb_core::time::parse_timestamp;
+use industrydb_core::traits::CrudOperations;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::source::{frame_from_rows, millis_to_datetime};
+
+/// Rows inserted per statement batch
+const HISTORY_BATCH_ROWS: usize = 10_000;
+
+/// Status code of a Bad value, for sources that only give a good flag
+const STATUS_BAD: i64 = 0x8000_0000;
+
+/// How history values are laid out in the target table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HistoryShape {
+    /// One row per node and timestamp: tag, time, value and quality columns
+    #[default]
+    Long,
+    /// One row per timestamp with a column per tag; quality is not kept
+    Wide,
+}
+
+impl std::str::FromStr for HistoryShape {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "long" => Ok(HistoryShape::Long),
+            "wide" => Ok(HistoryShape::Wide),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported history layout: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which values are kept by their OPC UA status code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QualityFilter {
+    /// Every value, Bad ones included
+    #[default]
+    All,
+    /// Good and Uncertain values
+    NotBad,
+    /// Good values only
+    Good,
+}
+
+impl std::str::FromStr for QualityFilter {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(QualityFilter::All),
+            "not_bad" => Ok(QualityFilter::NotBad),
+            "good" => Ok(QualityFilter::Good),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported quality filter: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl QualityFilter {
+    /// Whether a value with status code `status` is kept
+    ///
+    /// The two top bits of a status code give its severity: Good,
+    /// Uncertain or Bad.
+    fn keeps(&self, status: i64) -> bool {
+        let severity = (status >> 30) & 0b11;
+        match self {
+            QualityFilter::All => true,
+            QualityFilter::NotBad => severity <= 1,
+            QualityFilter::Good => severity == 0,
+        }
+    }
+}
+
+/// Target table and columns of an OPC UA history ingestion
+#[derive(Debug, Clone)]
+pub struct HistoryLayout {
+    /// Table to insert into
+    pub table: String,
+    /// Long or wide rows
+    pub shape: HistoryShape,
+    /// Column holding the tag in [`HistoryShape::Long`]
+    pub tag_column: String,
+    /// Column holding the source timestamp
+    pub time_column: String,
+    /// Column holding the value in [`HistoryShape::Long`]
+    pub value_column: String,
+    /// Column the status code is stored in with [`HistoryShape::Long`];
+    /// not stored when `None`
+    pub quality_column: Option<String>,
+    /// Tag name of each node id; the node id itself when missing
+    pub tags: HashMap<String, String>,
+    /// Values kept by quality
+    pub min_quality: QualityFilter,
+    /// Create the table from the mapped rows when it does not exist
+    pub create_table: bool,
+}
+
+impl HistoryLayout {
+    /// Create a long layout with `tag`, `ts`, `value` and `quality` columns
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            shape: HistoryShape::default(),
+            tag_column: "tag".to_string(),
+            time_column: "ts".to_string(),
+            value_column: "value".to_string(),
+            quality_column: Some("quality".to_string()),
+            tags: HashMap::new(),
+            min_quality: QualityFilter::default(),
+            create_table: true,
+        }
+    }
+
+    fn tag<'a>(&'a self, node_id: &'a str) -> &'a str {
+        self.tags.get(node_id).map_or(node_id, String::as_str)
+    }
+}
+
+/// Outcome of an ingestion
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistorySummary {
+    /// Values in the history result set
+    pub values_read: usize,
+    /// Values dropped for their quality or a missing timestamp
+    pub values_dropped: usize,
+    /// Rows inserted into the table
+    pub rows_inserted: usize,
+}
+
+/// Map a history result set to `layout` and insert it
+///
+/// `history` needs `node_id`, `timestamp` and `value` columns and may have
+/// a `quality` column of status codes, where a null is Good, or of good
+/// flags. Timestamps can be datetimes, text or Unix epoch milliseconds.
+pub async fn ingest_history<C: CrudOperations + ?Sized>(
+    conn: &C,
+    history: &DataFrame,
+    layout: &HistoryLayout,
+) -> Result<HistorySummary> {
+    let (rows, values_dropped) = map_history(history, layout)?;
+    let mut summary = HistorySummary {
+        values_read: history.height(),
+        values_dropped,
+        rows_inserted: 0,
+    };
+    if rows.height() == 0 {
+        return Ok(summary);
+    }
+
+    if layout.create_table {
+        conn.create_table(&layout.table, &rows.schema(), true)
+            .await?;
+    }
+    for offset in (0..rows.height()).step_by(HISTORY_BATCH_ROWS) {
+        summary.rows_inserted += conn
+            .insert(&layout.table, rows.slice(offset as i64, HISTORY_BATCH_ROWS))
+            .await?;
+    }
+    Ok(summary)
+}
+
+/// The rows of `history` in `layout`, and the number of values dropped
+pub fn map_history(history: &DataFrame, layout: &HistoryLayout) -> Result<(DataFrame, usize)> {
+    let column = |name: &str| {
+        history.column(name).map_err(|_| {
+            IndustryDbError::invalid_parameter(format!("History rows need a {} column", name))
+        })
+    };
+    let node_ids = column("node_id")?.cast(&DataType::String)?;
+    let node_ids = node_ids.str()?;
+    let times = timestamps_ms(column("timestamp")?)?;
+    let values = column("value")?.as_materialized_series();
+    let qualities = match history.column("quality") {
+        Ok(quality) => status_codes(quality)?,
+        Err(_) => vec![None; history.height()],
+    };
+
+    let kept: Vec<usize> = (0..history.height())
+        .filter(|&i| {
+            times[i].is_some()
+                && node_ids.get(i).is_some()
+                && layout.min_quality.keeps(qualities[i].unwrap_or(0))
+        })
+        .collect();
+    let dropped = history.height() - kept.len();
+
+    let mut rows = match layout.shape {
+        HistoryShape::Long => {
+            let indices = IdxCa::from_vec("".into(), kept.iter().map(|&i| i as IdxSize).collect());
+            let tags: Vec<&str> = kept
+                .iter()
+                .map(|&i| layout.tag(node_ids.get(i).unwrap_or_default()))
+                .collect();
+            let ts: Vec<i64> = kept.iter().filter_map(|&i| times[i]).collect();
+            let mut columns = vec![
+                Column::new(layout.tag_column.as_str().into(), tags),
+                Column::new(layout.time_column.as_str().into(), ts),
+                values
+                    .take(&indices)?
+                    .with_name(layout.value_column.as_str().into())
+                    .into_column(),
+            ];
+            if let Some(quality_column) = &layout.quality_column {
+                let quality: Vec<i64> = kept.iter().map(|&i| qualities[i].unwrap_or(0)).collect();
+                columns.push(Column::new(quality_column.as_str().into(), quality));
+            }
+            DataFrame::new(columns)?
+        }
+        HistoryShape::Wide => {
+            let mut by_time: BTreeMap<i64, Vec<(String, Value)>> = BTreeMap::new();
+            for &i in &kept {
+                let Some(time) = times[i] else { continue };
+                let row = by_time
+                    .entry(time)
+                    .or_insert_with(|| vec![(layout.time_column.clone(), Value::Int(time))]);
+                let tag = layout.tag(node_ids.get(i).unwrap_or_default()).to_string();
+                row.push((tag, Value::from(values.get(i)?)));
+            }
+            let rows: Vec<Vec<(String, Value)>> = by_time.into_values().collect();
+            frame_from_rows(&rows)?
+        }
+    };
+    if rows.height() > 0 {
+        millis_to_datetime(&mut rows, &layout.time_column)?;
+    }
+    Ok((rows, dropped))
+}
+
+/// Timestamps as Unix epoch milliseconds
+fn timestamps_ms(column: &Column) -> Result<Vec<Option<i64>>> {
+    match column.dtype() {
+        DataType::String => column
+            .str()?
+            .into_iter()
+            .map(|text| {
+                text.map(|text| parse_timestamp(text).map(|ts| ts.and_utc().timestamp_millis()))
+                    .transpose()
+            })
+            .collect(),
+        DataType::Datetime(_, _) | DataType::Date => {
+            let millis = column
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+                .cast(&DataType::Int64)?;
+            Ok(millis.i64()?.into_iter().collect())
+        }
+        dtype if dtype.is_integer() => {
+            Ok(column.cast(&DataType::Int64)?.i64()?.into_iter().collect())
+        }
+        dtype => Err(IndustryDbError::invalid_parameter(format!(
+            "History timestamps must be datetimes, text or epoch milliseconds, not {}",
+            dtype
+        ))),
+    }
+}
+
+/// Status codes of a quality column of codes or good flags
+fn status_codes(column: &Column) -> Result<Vec<Option<i64>>> {
+    if column.dtype() == &DataType::Boolean {
+        return Ok(column
+            .bool()?
+            .into_iter()
+            .map(|good| good.map(|good| if good { 0 } else { STATUS_BAD }))
+            .collect());
+    }
+    Ok(column.cast(&DataType::Int64)?.i64()?.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> DataFrame {
+        DataFrame::new(vec![
+            Column::new(
+                "node_id".into(),
+                &[
+                    "ns=2;s=TI101",
+                    "ns=2;s=PI200",
+                    "ns=2;s=TI101",
+                    "ns=2;s=TI101",
+                ],
+            ),
+            Column::new(
+                "timestamp".into(),
+                &[
+                    "2024-03-01 08:00:00",
+                    "2024-03-01 08:00:00",
+                    "2024-03-01 08:01:00",
+                    "2024-03-01 08:02:00",
+                ],
+            ),
+            Column::new("value".into(), &[21.5, 3.2, 21.7, 99.0]),
+            Column::new(
+                "quality".into(),
+                &[Some(0i64), None, Some(0x4000_0000), Some(0x8000_0000)],
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_map_history_long() {
+        let mut layout = HistoryLayout::new("history");
+        layout
+            .tags
+            .insert("ns=2;s=TI101".to_string(), "TI-101".to_string());
+        layout.min_quality = QualityFilter::NotBad;
+        let (rows, dropped) = map_history(&history(), &layout).unwrap();
+
+        assert_eq!(dropped, 1);
+        assert_eq!(
+            rows.get_column_names_str(),
+            vec!["tag", "ts", "value", "quality"]
+        );
+        assert_eq!(
+            rows.column("tag").unwrap().str().unwrap().get(1),
+            Some("ns=2;s=PI200")
+        );
+        assert_eq!(
+            rows.column("tag").unwrap().str().unwrap().get(2),
+            Some("TI-101")
+        );
+        assert!(matches!(
+            rows.column("ts").unwrap().dtype(),
+            DataType::Datetime(_, _)
+        ));
+        assert_eq!(
+            rows.column("quality").unwrap().i64().unwrap().get(2),
+            Some(0x4000_0000)
+        );
+    }
+
+    #[test]
+    fn test_map_history_wide() {
+        let mut layout = HistoryLayout::new("history");
+        layout.shape = HistoryShape::Wide;
+        layout.min_quality = QualityFilter::Good;
+        let (rows, dropped) = map_history(&history(), &layout).unwrap();
+
+        assert_eq!(dropped, 2);
+        assert_eq!(rows.height(), 1);
+        assert_eq!(
+            rows.get_column_names_str(),
+            vec!["ts", "ns=2;s=TI101", "ns=2;s=PI200"]
+        );
+        assert_eq!(
+            rows.column("ns=2;s=PI200").unwrap().f64().unwrap().get(0),
+            Some(3.2)
+        );
+    }
+
+    #[test]
+    fn test_quality_filter() {
+        assert!(QualityFilter::Good.keeps(0x0000_0000));
+        assert!(!QualityFilter::Good.keeps(0x4000_0000));
+        assert!(QualityFilter::NotBad.keeps(0x4000_0000));
+        assert!(!QualityFilter::NotBad.keeps(0x8000_0000));
+        assert!(QualityFilter::All.keeps(0x8000_0000));
+        assert!("uncertain".parse::<QualityFilter>().is_err());
+    }
+}
@@ -0,0 +1,453 @@
+//! Publishing rows to Kafka topics
+//!
+//! A [`KafkaSink`] sends the rows of a DataFrame to a topic, so change
+//! events from a change poller or feed, or the result of a query run on a
+//! schedule, reach a plant event bus. Rows go either as one JSON object per
+//! message or as one Arrow IPC stream per chunk of rows. Connecting needs
+//! the `kafka` feature; encoding does not.
+
+use industrydb_core::backfill::BackfillControl;
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::params::Value;
+use industrydb_core::traits::DatabaseConnector;
+use polars::prelude::*;
+use std::sync::atomic::AtomicUsize;
+use std::time::{Duration, Instant};
+
+/// Rows per message in [`KafkaFormat::Arrow`] when none is configured
+pub const DEFAULT_ARROW_MESSAGE_ROWS: usize = 10_000;
+
+/// Most messages sent to a partition in one produce request
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+const PRODUCE_BATCH_MESSAGES: usize = 500;
+
+/// How rows are encoded into messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KafkaFormat {
+    /// One JSON object per row, keyed by column name
+    #[default]
+    Json,
+    /// One Arrow IPC stream per chunk of rows
+    Arrow,
+}
+
+impl std::str::FromStr for KafkaFormat {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(KafkaFormat::Json),
+            "arrow" => Ok(KafkaFormat::Arrow),
+            _ => Err(IndustryDbError::invalid_parameter(format!(
+                "Unsupported Kafka format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl KafkaFormat {
+    /// Value of the `content-type` header of messages in this format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            KafkaFormat::Json => "application/json",
+            KafkaFormat::Arrow => "application/vnd.apache.arrow.stream",
+        }
+    }
+}
+
+/// Where and how to publish
+#[derive(Debug, Clone)]
+pub struct KafkaSinkConfig {
+    /// Bootstrap brokers as `host:port`
+    pub brokers: Vec<String>,
+    /// Topic to publish to; it must exist
+    pub topic: String,
+    /// How rows are encoded
+    pub format: KafkaFormat,
+    /// Column whose value keys each JSON message, so the rows of one key
+    /// stay in order on one partition; rows are spread over the partitions
+    /// when `None`
+    pub key_column: Option<String>,
+    /// Rows per message in [`KafkaFormat::Arrow`]
+    pub arrow_message_rows: usize,
+}
+
+impl KafkaSinkConfig {
+    /// Create a config publishing JSON messages to `topic`
+    pub fn new(brokers: Vec<String>, topic: &str) -> Self {
+        Self {
+            brokers,
+            topic: topic.to_string(),
+            format: KafkaFormat::default(),
+            key_column: None,
+            arrow_message_rows: DEFAULT_ARROW_MESSAGE_ROWS,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.brokers.is_empty() {
+            return Err(IndustryDbError::config_error(
+                "Kafka sink needs at least one broker",
+            ));
+        }
+        if self.arrow_message_rows == 0 {
+            return Err(IndustryDbError::invalid_parameter(
+                "arrow_message_rows must be positive",
+            ));
+        }
+        if self.key_column.is_some() && self.format == KafkaFormat::Arrow {
+            return Err(IndustryDbError::invalid_parameter(
+                "Arrow messages hold many rows and cannot be keyed by a column",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A message before it is handed to the client
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+struct Message {
+    key: Option<Vec<u8>>,
+    value: Vec<u8>,
+}
+
+/// Publishes the rows of DataFrames to a Kafka topic
+///
+/// Delivery is at least once: a publish that fails partway may have sent
+/// some messages, which a retry sends again.
+pub struct KafkaSink {
+    config: KafkaSinkConfig,
+    #[cfg(feature = "kafka")]
+    partitions: Vec<rskafka::client::partition::PartitionClient>,
+    /// Partition the next unkeyed messages go to
+    #[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+    next_partition: AtomicUsize,
+}
+
+#[cfg(feature = "kafka")]
+fn kafka_err(e: impl std::fmt::Display) -> IndustryDbError {
+    IndustryDbError::connection_error(format!("Kafka: {}", e))
+}
+
+impl KafkaSink {
+    /// Connect to the brokers and look up the partitions of the topic
+    #[cfg(feature = "kafka")]
+    pub async fn connect(config: KafkaSinkConfig) -> Result<Self> {
+        use rskafka::client::partition::UnknownTopicHandling;
+        use rskafka::client::ClientBuilder;
+
+        config.validate()?;
+        let client = ClientBuilder::new(config.brokers.clone())
+            .build()
+            .await
+            .map_err(kafka_err)?;
+        let topic = client
+            .list_topics()
+            .await
+            .map_err(kafka_err)?
+            .into_iter()
+            .find(|topic| topic.name == config.topic)
+            .ok_or_else(|| {
+                IndustryDbError::config_error(format!(
+                    "Kafka topic {} does not exist",
+                    config.topic
+                ))
+            })?;
+
+        let mut partitions = Vec::with_capacity(topic.partitions.len());
+        for partition in topic.partitions {
+            partitions.push(
+                client
+                    .partition_client(config.topic.clone(), partition, UnknownTopicHandling::Error)
+                    .await
+                    .map_err(kafka_err)?,
+            );
+        }
+
+        Ok(Self {
+            config,
+            partitions,
+            next_partition: AtomicUsize::new(0),
+        })
+    }
+
+    /// Connect to the brokers and look up the partitions of the topic
+    #[cfg(not(feature = "kafka"))]
+    pub async fn connect(config: KafkaSinkConfig) -> Result<Self> {
+        config.validate()?;
+        Err(IndustryDbError::config_error(
+            "Kafka publishing is not included in this build (feature `kafka`)",
+        ))
+    }
+
+    /// Where and how this sink publishes
+    pub fn config(&self) -> &KafkaSinkConfig {
+        &self.config
+    }
+
+    /// Publish the rows of `frame`; returns the number of messages sent
+    ///
+    /// `table`, when given, is sent along in a `table` header so consumers
+    /// can tell the tables of a change feed apart. Each call's unkeyed
+    /// messages go to one partition, in order, and the next call's to the
+    /// next partition.
+    pub async fn publish(&self, frame: &DataFrame, table: Option<&str>) -> Result<usize> {
+        let messages = encode(frame, &self.config)?;
+        if messages.is_empty() {
+            return Ok(0);
+        }
+        let sent = messages.len();
+        self.send(messages, table).await?;
+        Ok(sent)
+    }
+
+    /// Run `sql` and publish its result, every `interval` until `control`
+    /// is cancelled, or once when `interval` is `None`
+    ///
+    /// Returns the number of messages sent over all runs. A failed query or
+    /// publish ends the schedule with its error.
+    pub async fn publish_query<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        sql: &str,
+        params: &[Value],
+        interval: Option<Duration>,
+        control: &BackfillControl,
+    ) -> Result<usize> {
+        let mut sent = 0;
+        loop {
+            while control.is_paused() && !control.is_cancelled() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            if control.is_cancelled() {
+                return Ok(sent);
+            }
+
+            let started = Instant::now();
+            let frame = conn.execute_with_params(sql, params).await?;
+            sent += self.publish(&frame, None).await?;
+
+            let Some(interval) = interval else {
+                return Ok(sent);
+            };
+            // Sleep in short steps so cancel takes effect promptly
+            while started.elapsed() < interval && !control.is_cancelled() {
+                tokio::time::sleep((interval - started.elapsed()).min(Duration::from_millis(100)))
+                    .await;
+            }
+        }
+    }
+
+    #[cfg(feature = "kafka")]
+    async fn send(&self, messages: Vec<Message>, table: Option<&str>) -> Result<()> {
+        use rskafka::client::partition::Compression;
+        use rskafka::record::Record;
+        use std::collections::BTreeMap;
+        use std::sync::atomic::Ordering;
+
+        let count = self.partitions.len();
+        let unkeyed = self.next_partition.fetch_add(1, Ordering::Relaxed) % count;
+        let mut headers = BTreeMap::new();
+        headers.insert(
+            "content-type".to_string(),
+            self.config.format.content_type().as_bytes().to_vec(),
+        );
+        if let Some(table) = table {
+            headers.insert("table".to_string(), table.as_bytes().to_vec());
+        }
+
+        let timestamp = chrono::Utc::now();
+        let mut batches: Vec<Vec<Record>> = vec![Vec::new(); count];
+        for message in messages {
+            let partition = match &message.key {
+                Some(key) => partition_for_key(key, count),
+                None => unkeyed,
+            };
+            batches[partition].push(Record {
+                key: message.key,
+                value: Some(message.value),
+                headers: headers.clone(),
+                timestamp,
+            });
+        }
+
+        for (client, records) in self.partitions.iter().zip(batches) {
+            let mut records = records.into_iter().peekable();
+            while records.peek().is_some() {
+                let batch: Vec<Record> = records.by_ref().take(PRODUCE_BATCH_MESSAGES).collect();
+                client
+                    .produce(batch, Compression::NoCompression)
+                    .await
+                    .map_err(kafka_err)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "kafka"))]
+    async fn send(&self, _messages: Vec<Message>, _table: Option<&str>) -> Result<()> {
+        unreachable!("connect never returns a sink without the kafka feature")
+    }
+}
+
+/// Encode the rows of `frame` into messages as `config` says
+fn encode(frame: &DataFrame, config: &KafkaSinkConfig) -> Result<Vec<Message>> {
+    if frame.height() == 0 {
+        return Ok(Vec::new());
+    }
+    match config.format {
+        KafkaFormat::Json => encode_json(frame, config.key_column.as_deref()),
+        KafkaFormat::Arrow => {
+            let mut messages = Vec::new();
+            let mut offset = 0;
+            while offset < frame.height() {
+                let mut chunk = frame.slice(offset as i64, config.arrow_message_rows);
+                let mut value = Vec::new();
+                IpcStreamWriter::new(&mut value).finish(&mut chunk)?;
+                messages.push(Message { key: None, value });
+                offset += config.arrow_message_rows;
+            }
+            Ok(messages)
+        }
+    }
+}
+
+fn encode_json(frame: &DataFrame, key_column: Option<&str>) -> Result<Vec<Message>> {
+    let key_index = key_column
+        .map(|key| {
+            frame.get_column_index(key).ok_or_else(|| {
+                IndustryDbError::invalid_parameter(format!("Key column {} is not in the rows", key))
+            })
+        })
+        .transpose()?;
+    let columns: Vec<&Series> = frame
+        .get_columns()
+        .iter()
+        .map(|c| c.as_materialized_series())
+        .collect();
+
+    let mut messages = Vec::with_capacity(frame.height());
+    for row in 0..frame.height() {
+        let mut object = serde_json::Map::with_capacity(columns.len());
+        let mut key = None;
+        for (index, column) in columns.iter().enumerate() {
+            let value = Value::from(column.get(row)?);
+            if Some(index) == key_index {
+                key = match &value {
+                    Value::Null => None,
+                    Value::Text(text) | Value::Decimal(text) | Value::Uuid(text) => {
+                        Some(text.as_bytes().to_vec())
+                    }
+                    Value::Bytes(bytes) => Some(bytes.clone()),
+                    other => Some(serde_json::to_vec(other)?),
+                };
+            }
+            object.insert(column.name().to_string(), serde_json::to_value(value)?);
+        }
+        messages.push(Message {
+            key,
+            value: serde_json::to_vec(&object)?,
+        });
+    }
+    Ok(messages)
+}
+
+/// Partition of a keyed message, the same one Kafka's own clients pick
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+fn partition_for_key(key: &[u8], partitions: usize) -> usize {
+    (murmur2(key) & 0x7fff_ffff) as usize % partitions
+}
+
+/// Kafka's variant of MurmurHash2
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+fn murmur2(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    let mut h = 0x9747_b28c ^ data.len() as u32;
+
+    let chunks = data.chunks_exact(4);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M) ^ k;
+    }
+    if tail.len() >= 3 {
+        h ^= (tail[2] as u32) << 16;
+    }
+    if tail.len() >= 2 {
+        h ^= (tail[1] as u32) << 8;
+    }
+    if !tail.is_empty() {
+        h ^= tail[0] as u32;
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readings() -> DataFrame {
+        DataFrame::new(vec![
+            Series::new("tag".into(), &["PT-101", "PT-102", "PT-101"]).into_column(),
+            Series::new("value".into(), &[Some(1.5), None, Some(2.0)]).into_column(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_murmur2_matches_kafka() {
+        // Values from Kafka's own test suite
+        assert_eq!(murmur2(b"21") as i32, -973932308);
+        assert_eq!(murmur2(b"foobar") as i32, -790332482);
+        assert_eq!(murmur2(b"a-little-bit-long-string") as i32, -985981536);
+        assert_eq!(murmur2(b"abc") as i32, 479470107);
+    }
+
+    #[test]
+    fn test_encode_json_rows() {
+        let mut config = KafkaSinkConfig::new(vec!["localhost:9092".to_string()], "readings");
+        config.key_column = Some("tag".to_string());
+        let messages = encode(&readings(), &config).unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].key.as_deref(), Some(&b"PT-101"[..]));
+        assert_eq!(messages[0].value, br#"{"tag":"PT-101","value":1.5}"#);
+        assert_eq!(messages[1].value, br#"{"tag":"PT-102","value":null}"#);
+
+        config.key_column = Some("unit".to_string());
+        assert!(encode(&readings(), &config).is_err());
+    }
+
+    #[test]
+    fn test_encode_arrow_chunks() {
+        let mut config = KafkaSinkConfig::new(vec!["localhost:9092".to_string()], "readings");
+        config.format = KafkaFormat::Arrow;
+        config.arrow_message_rows = 2;
+        let messages = encode(&readings(), &config).unwrap();
+        assert_eq!(messages.len(), 2);
+
+        let chunk = IpcStreamReader::new(std::io::Cursor::new(&messages[1].value))
+            .finish()
+            .unwrap();
+        assert_eq!(chunk.height(), 1);
+        assert_eq!(
+            chunk
+                .column("tag")
+                .unwrap()
+                .as_materialized_series()
+                .str()
+                .unwrap()
+                .get(0),
+            Some("PT-101")
+        );
+    }
+}
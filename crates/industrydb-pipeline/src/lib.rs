@@ -0,0 +1,38 @@
+//! Declarative ingestion pipelines for IndustryDB
+//!
+//! Most acquisition setups are the same program with different names in
+//! it: subscribe to a broker or poll a server, rename and scale a few
+//! fields, drop implausible values, insert into a table. A
+//! [`PipelineConfig`] describes that in a TOML or YAML file and a
+//! [`PipelineRunner`] executes it:
+//!
+//! ```text
+//! source (MQTT | OPC UA | table | file)
+//!   -> transforms (rename, select, drop, cast, scale, sql)
+//!   -> validation (not null, ranges, batch checks; drop, reject or fail)
+//!   -> target table
+//! ```
+//!
+//! The runner keeps [`PipelineMetrics`] and restarts the source with
+//! exponential backoff when it or the target fails. MQTT and OPC UA sources
+//! need the `mqtt` and `opcua` features.
+//!
+//! Rows can also go the other way: a [`KafkaSink`] publishes change events
+//! or query results to a Kafka topic (feature `kafka`). Archived OPC UA
+//! values are loaded with [`ingest_history`] rather than a pipeline, as
+//! a history read is a one-off result set instead of a stream.
+
+pub mod config;
+pub mod history;
+pub mod kafka;
+mod runner;
+pub mod source;
+pub mod transform;
+
+pub use config::{
+    FileFormat, OnInvalid, PipelineConfig, RestartConfig, SourceConfig, TargetConfig, Transform,
+    ValidationConfig,
+};
+pub use history::{ingest_history, HistoryLayout, HistoryShape, HistorySummary, QualityFilter};
+pub use kafka::{KafkaFormat, KafkaSink, KafkaSinkConfig};
+pub use runner::{PipelineControl, PipelineMetrics, PipelineRunner};
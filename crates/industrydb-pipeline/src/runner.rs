@@ -0,0 +1,320 @@
+//! Pipeline execution
+
+use industrydb_core::batching::{insert_adaptive, AdaptiveBatcher};
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::params::Value;
+use industrydb_core::time::format_timestamp;
+use industrydb_core::traits::{CrudOperations, DatabaseConnector};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::{OnInvalid, PipelineConfig};
+use crate::source;
+use crate::transform::{apply_transforms, validate};
+
+/// Counters of a pipeline, updated after every batch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineMetrics {
+    /// Batches written
+    pub batches: usize,
+    /// Rows received from the source
+    pub rows_read: usize,
+    /// Rows inserted into the target table
+    pub rows_written: usize,
+    /// Rows failing validation
+    pub rows_rejected: usize,
+    /// Times the source was reopened after a failure
+    pub restarts: u32,
+    /// Message of the most recent failure
+    pub last_error: Option<String>,
+    /// When the last batch was written (UTC)
+    pub last_batch_at: Option<String>,
+    /// Current insert batch size when batching adaptively
+    pub batch_rows: usize,
+}
+
+/// Stop handle for a running pipeline
+///
+/// Clones share state, so one clone can be handed to another thread.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineControl {
+    stopped: Arc<AtomicBool>,
+}
+
+impl PipelineControl {
+    /// Create a handle in the running state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop after the current batch
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the pipeline has been stopped
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+}
+
+/// Runs a [`PipelineConfig`]: reads batches from the source, transforms and
+/// validates them and inserts them into the target table
+///
+/// Failures of the source or target (a broker restart, a dropped database
+/// connection) reopen the source after a backoff, resuming from the last
+/// written batch where the source supports it. Configuration, transform and
+/// validation errors stop the pipeline, since retrying cannot fix them.
+pub struct PipelineRunner {
+    config: PipelineConfig,
+    control: PipelineControl,
+    metrics: Arc<Mutex<PipelineMetrics>>,
+}
+
+impl PipelineRunner {
+    /// Create a runner for a validated config
+    pub fn new(config: PipelineConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            control: PipelineControl::new(),
+            metrics: Arc::default(),
+        })
+    }
+
+    /// Create a runner from a `.toml`, `.yaml` or `.yml` file
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::new(PipelineConfig::from_path(path)?)
+    }
+
+    /// The pipeline's configuration
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    /// Handle that stops [`run`](Self::run) from another task or thread
+    pub fn control(&self) -> PipelineControl {
+        self.control.clone()
+    }
+
+    /// Counters so far
+    pub fn metrics(&self) -> PipelineMetrics {
+        self.lock_metrics().clone()
+    }
+
+    fn lock_metrics(&self) -> std::sync::MutexGuard<'_, PipelineMetrics> {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run until the source is exhausted, the pipeline is stopped, or a
+    /// failure cannot be restarted
+    ///
+    /// Table sources read through `source`; other sources ignore it.
+    pub async fn run<T: CrudOperations + ?Sized>(
+        &self,
+        target: &T,
+        source: Option<&dyn DatabaseConnector>,
+    ) -> Result<PipelineMetrics> {
+        let mut state = RunState {
+            checkpoint: None,
+            failures: 0,
+            created: HashSet::new(),
+            batcher: self
+                .config
+                .target
+                .batching
+                .clone()
+                .map(AdaptiveBatcher::new)
+                .transpose()?,
+        };
+
+        loop {
+            let Err(e) = self.run_source(target, source, &mut state).await else {
+                return Ok(self.metrics());
+            };
+
+            let restarts = {
+                let mut metrics = self.lock_metrics();
+                metrics.last_error = Some(e.to_string());
+                metrics.restarts
+            };
+            let exhausted = self
+                .config
+                .restart
+                .max_restarts
+                .is_some_and(|max| restarts >= max);
+            if is_fatal(&e) || exhausted {
+                return Err(e);
+            }
+
+            state.failures += 1;
+            self.lock_metrics().restarts += 1;
+            let backoff = self.config.restart.backoff(state.failures);
+            let step = Duration::from_millis(100);
+            let mut waited = Duration::ZERO;
+            while waited < backoff && !self.control.is_stopped() {
+                tokio::time::sleep(step.min(backoff - waited)).await;
+                waited += step;
+            }
+            if self.control.is_stopped() {
+                return Ok(self.metrics());
+            }
+        }
+    }
+
+    async fn run_source<T: CrudOperations + ?Sized>(
+        &self,
+        target: &T,
+        db: Option<&dyn DatabaseConnector>,
+        state: &mut RunState,
+    ) -> Result<()> {
+        let mut source = source::open(
+            &self.config.source,
+            &self.config.name,
+            db,
+            state.checkpoint.clone(),
+        )
+        .await?;
+        let validation = &self.config.validation;
+
+        while !self.control.is_stopped() {
+            let Some(batch) = source.next_batch().await? else {
+                return Ok(());
+            };
+            if batch.height() == 0 {
+                continue;
+            }
+            let rows_read = batch.height();
+
+            let batch = apply_transforms(batch, &self.config.transforms)?;
+            let checked = validate(&batch, validation)?;
+            let rejected = checked.rejected.height();
+            if rejected > 0 && validation.on_invalid == OnInvalid::Fail {
+                let checks: Vec<&str> = checked
+                    .failed_checks
+                    .iter()
+                    .map(|c| c.check.as_str())
+                    .collect();
+                return Err(IndustryDbError::constraint_violation(
+                    match checks.as_slice() {
+                        [] => format!(
+                            "{} rows of a batch failed validation in pipeline {}",
+                            rejected, self.config.name
+                        ),
+                        checks => format!(
+                            "A batch failed the {} checks in pipeline {}",
+                            checks.join(", "),
+                            self.config.name
+                        ),
+                    },
+                ));
+            }
+
+            if let Some(reject_table) = &validation.reject_table {
+                self.write(target, reject_table, &checked.rejected, state, false)
+                    .await?;
+            }
+            let written = self
+                .write(
+                    target,
+                    &self.config.target.table,
+                    &checked.valid,
+                    state,
+                    true,
+                )
+                .await?;
+
+            state.checkpoint = source.checkpoint();
+            state.failures = 0;
+
+            let mut metrics = self.lock_metrics();
+            metrics.batches += 1;
+            metrics.rows_read += rows_read;
+            metrics.rows_written += written;
+            metrics.rows_rejected += rejected;
+            metrics.last_batch_at = Some(format_timestamp(&chrono::Utc::now().naive_utc()));
+            if let Some(batcher) = &state.batcher {
+                metrics.batch_rows = batcher.batch_rows();
+            }
+        }
+        Ok(())
+    }
+
+    async fn write<T: CrudOperations + ?Sized>(
+        &self,
+        target: &T,
+        table: &str,
+        df: &DataFrame,
+        state: &mut RunState,
+        adaptive: bool,
+    ) -> Result<usize> {
+        if df.height() == 0 {
+            return Ok(0);
+        }
+        if self.config.target.create && !state.created.contains(table) {
+            target.create_table(table, &df.schema(), true).await?;
+            state.created.insert(table.to_string());
+        }
+        match &mut state.batcher {
+            Some(batcher) if adaptive => insert_adaptive(target, table, df, batcher).await,
+            _ => target.insert(table, df.clone()).await,
+        }
+    }
+}
+
+/// State carried across restarts of one run
+struct RunState {
+    /// Checkpoint of the source after the last written batch
+    checkpoint: Option<Value>,
+    /// Consecutive failures, for the backoff
+    failures: u32,
+    /// Tables already created (or found) during this run
+    created: HashSet<String>,
+    batcher: Option<AdaptiveBatcher>,
+}
+
+/// Whether an error would recur however often the pipeline restarts
+fn is_fatal(e: &IndustryDbError) -> bool {
+    matches!(
+        e,
+        IndustryDbError::ConfigError(_)
+            | IndustryDbError::InvalidParameter(_)
+            | IndustryDbError::PolarsError(_)
+            | IndustryDbError::ConstraintViolation(_)
+            | IndustryDbError::SqlParseError(_)
+            | IndustryDbError::AccessDenied(_)
+            | IndustryDbError::NotImplemented(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fatal_errors() {
+        assert!(is_fatal(&IndustryDbError::config_error("bad")));
+        assert!(is_fatal(&IndustryDbError::constraint_violation("invalid")));
+        assert!(!is_fatal(&IndustryDbError::connection_error("broker gone")));
+        assert!(!is_fatal(&IndustryDbError::query_error("deadlock")));
+    }
+
+    #[test]
+    fn test_control_shares_state() {
+        let runner = PipelineRunner::new(
+            PipelineConfig::from_toml(
+                "[source]\ntype = \"file\"\npath = \"data.csv\"\n[target]\ntable = \"t\"\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        runner.control().stop();
+        assert!(runner.control().is_stopped());
+        assert_eq!(runner.metrics(), PipelineMetrics::default());
+    }
+}
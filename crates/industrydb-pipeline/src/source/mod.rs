@@ -0,0 +1,198 @@
+//! Pipeline sources
+
+use async_trait::async_trait;
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::params::Value;
+use industrydb_core::traits::DatabaseConnector;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+use crate::config::SourceConfig;
+
+mod file;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "opcua")]
+mod opcua;
+mod table;
+
+/// Produces batches of rows for a pipeline
+#[async_trait]
+pub trait Source: Send {
+    /// Next batch, empty when nothing arrived in time, `None` once the
+    /// source is exhausted
+    async fn next_batch(&mut self) -> Result<Option<DataFrame>>;
+
+    /// Position after the last batch returned, passed back to [`open`] when
+    /// the pipeline restarts once that batch has been written
+    fn checkpoint(&self) -> Option<Value>;
+}
+
+/// Open the source described by `config`
+///
+/// Table sources read through `db`; `resume` is a checkpoint from an earlier
+/// run of the same source.
+pub async fn open<'a>(
+    config: &SourceConfig,
+    name: &str,
+    db: Option<&'a dyn DatabaseConnector>,
+    resume: Option<Value>,
+) -> Result<Box<dyn Source + 'a>> {
+    Ok(match config {
+        SourceConfig::File(file) => Box::new(file::FileSource::new(file.clone(), resume)?),
+        SourceConfig::Table(table) => {
+            let db = db.ok_or_else(|| {
+                IndustryDbError::config_error("A table source needs a source connection")
+            })?;
+            Box::new(table::TableSource::new(table.clone(), db, resume)?)
+        }
+        #[cfg(feature = "mqtt")]
+        SourceConfig::Mqtt(mqtt) => Box::new(mqtt::MqttSource::connect(mqtt, name).await?),
+        #[cfg(not(feature = "mqtt"))]
+        SourceConfig::Mqtt(_) => return Err(not_built("MQTT", "mqtt", name)),
+        #[cfg(feature = "opcua")]
+        SourceConfig::Opcua(opcua) => Box::new(opcua::OpcUaSource::connect(opcua.clone())?),
+        #[cfg(not(feature = "opcua"))]
+        SourceConfig::Opcua(_) => return Err(not_built("OPC UA", "opcua", name)),
+    })
+}
+
+#[allow(dead_code)]
+fn not_built(kind: &str, feature: &str, name: &str) -> IndustryDbError {
+    IndustryDbError::config_error(format!(
+        "Pipeline {} reads from {}, which this build does not include (feature `{}`)",
+        name, kind, feature
+    ))
+}
+
+/// Build a DataFrame from rows of `(column, value)` pairs
+///
+/// Columns appear in the order first seen; rows missing a column get null.
+/// A column holding only integers becomes `Int64`, integers and floats
+/// `Float64`, only booleans `Boolean`, and anything else `String`.
+#[allow(dead_code)]
+pub(crate) fn frame_from_rows(rows: &[Vec<(String, Value)>]) -> Result<DataFrame> {
+    let mut names: Vec<&str> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for row in rows {
+        for (name, _) in row {
+            index.entry(name.as_str()).or_insert_with(|| {
+                names.push(name.as_str());
+                names.len() - 1
+            });
+        }
+    }
+
+    let mut values: Vec<Vec<&Value>> = vec![vec![&Value::Null; rows.len()]; names.len()];
+    for (i, row) in rows.iter().enumerate() {
+        for (name, value) in row {
+            values[index[name.as_str()]][i] = value;
+        }
+    }
+
+    let columns = names
+        .iter()
+        .zip(&values)
+        .map(|(name, values)| column_from_values(name, values))
+        .collect::<Vec<_>>();
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Cast a column of Unix epoch milliseconds to a datetime column
+#[allow(dead_code)]
+pub(crate) fn millis_to_datetime(df: &mut DataFrame, column: &str) -> Result<()> {
+    let datetime = df
+        .column(column)?
+        .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?;
+    df.with_column(datetime)?;
+    Ok(())
+}
+
+fn column_from_values(name: &str, values: &[&Value]) -> Column {
+    let present = || values.iter().filter(|v| !matches!(v, Value::Null));
+    let name = PlSmallStr::from(name);
+
+    if present().all(|v| matches!(v, Value::Int(_))) {
+        let ints: Vec<Option<i64>> = values
+            .iter()
+            .map(|v| match v {
+                Value::Int(i) => Some(*i),
+                _ => None,
+            })
+            .collect();
+        return Column::new(name, ints);
+    }
+    if present().all(|v| matches!(v, Value::Int(_) | Value::Float(_))) {
+        let floats: Vec<Option<f64>> = values
+            .iter()
+            .map(|v| match v {
+                Value::Int(i) => Some(*i as f64),
+                Value::Float(f) => Some(*f),
+                _ => None,
+            })
+            .collect();
+        return Column::new(name, floats);
+    }
+    if present().all(|v| matches!(v, Value::Bool(_))) {
+        let bools: Vec<Option<bool>> = values
+            .iter()
+            .map(|v| match v {
+                Value::Bool(b) => Some(*b),
+                _ => None,
+            })
+            .collect();
+        return Column::new(name, bools);
+    }
+    let strings: Vec<Option<String>> = values
+        .iter()
+        .map(|v| match v {
+            Value::Null => None,
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Int(i) => Some(i.to_string()),
+            Value::Float(f) => Some(f.to_string()),
+            Value::Text(s) => Some(s.clone()),
+            Value::Bytes(b) => Some(String::from_utf8_lossy(b).into_owned()),
+            Value::Decimal(d) => Some(d.clone()),
+            Value::Uuid(u) => Some(u.clone()),
+        })
+        .collect();
+    Column::new(name, strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pairs: &[(&str, Value)]) -> Vec<(String, Value)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_frame_from_rows_infers_types() {
+        let rows = vec![
+            row(&[("tag", "TI-101".into()), ("value", 1i64.into())]),
+            row(&[("value", 2.5f64.into()), ("count", 3i64.into())]),
+            row(&[("tag", 7i64.into()), ("ok", true.into())]),
+        ];
+        let df = frame_from_rows(&rows).unwrap();
+
+        assert_eq!(
+            df.get_column_names_str(),
+            vec!["tag", "value", "count", "ok"]
+        );
+        assert_eq!(
+            df.dtypes(),
+            vec![
+                DataType::String,
+                DataType::Float64,
+                DataType::Int64,
+                DataType::Boolean
+            ]
+        );
+        assert_eq!(df.column("tag").unwrap().str().unwrap().get(2), Some("7"));
+        assert_eq!(df.column("count").unwrap().null_count(), 2);
+    }
+}
@@ -0,0 +1,156 @@
+//! MQTT source
+
+use async_trait::async_trait;
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::params::Value;
+use polars::prelude::*;
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::{frame_from_rows, millis_to_datetime, Source};
+use crate::config::MqttSourceConfig;
+
+fn mqtt_err(e: impl std::fmt::Display) -> IndustryDbError {
+    IndustryDbError::connection_error(format!("MQTT: {}", e))
+}
+
+/// Collects messages from subscribed topics into batches
+pub(crate) struct MqttSource {
+    config: MqttSourceConfig,
+    // Dropping the client would end the event loop
+    _client: AsyncClient,
+    events: EventLoop,
+}
+
+impl MqttSource {
+    pub(crate) async fn connect(config: &MqttSourceConfig, name: &str) -> Result<Self> {
+        let client_id = config
+            .client_id
+            .clone()
+            .unwrap_or_else(|| format!("industrydb-{}", name));
+        let mut options = MqttOptions::new(client_id, &config.broker, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+
+        let (client, events) = AsyncClient::new(options, 1024);
+        let qos = match config.qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            _ => QoS::ExactlyOnce,
+        };
+        for topic in &config.topics {
+            client.subscribe(topic, qos).await.map_err(mqtt_err)?;
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            _client: client,
+            events,
+        })
+    }
+}
+
+#[async_trait]
+impl Source for MqttSource {
+    async fn next_batch(&mut self) -> Result<Option<DataFrame>> {
+        let deadline = Instant::now() + Duration::from_millis(self.config.flush_interval_ms);
+        let mut rows = Vec::new();
+
+        while rows.len() < self.config.batch_rows.max(1) {
+            let event = match tokio::time::timeout_at(deadline, self.events.poll()).await {
+                Ok(event) => event.map_err(mqtt_err)?,
+                Err(_) => break,
+            };
+            if let Event::Incoming(Packet::Publish(publish)) = event {
+                let received_at = chrono::Utc::now().timestamp_millis();
+                rows.extend(payload_rows(&publish.topic, received_at, &publish.payload));
+            }
+        }
+
+        let mut df = frame_from_rows(&rows)?;
+        if df.height() > 0 {
+            millis_to_datetime(&mut df, "received_at")?;
+        }
+        Ok(Some(df))
+    }
+
+    fn checkpoint(&self) -> Option<Value> {
+        None
+    }
+}
+
+/// Rows for one message
+fn payload_rows(topic: &str, received_at: i64, payload: &[u8]) -> Vec<Vec<(String, Value)>> {
+    let header = || {
+        vec![
+            ("topic".to_string(), Value::Text(topic.to_string())),
+            ("received_at".to_string(), Value::Int(received_at)),
+        ]
+    };
+    let with_fields = |object: serde_json::Map<String, serde_json::Value>| {
+        let mut row = header();
+        row.extend(object.into_iter().map(|(k, v)| (k, json_value(v))));
+        row
+    };
+    let with_value = |value: Value| {
+        let mut row = header();
+        row.push(("value".to_string(), value));
+        row
+    };
+
+    match serde_json::from_slice::<serde_json::Value>(payload) {
+        Ok(serde_json::Value::Object(object)) => vec![with_fields(object)],
+        Ok(serde_json::Value::Array(items)) if items.iter().all(|i| i.is_object()) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                serde_json::Value::Object(object) => Some(with_fields(object)),
+                _ => None,
+            })
+            .collect(),
+        Ok(value) => vec![with_value(json_value(value))],
+        Err(_) => vec![with_value(Value::Text(
+            String::from_utf8_lossy(payload).into_owned(),
+        ))],
+    }
+}
+
+fn json_value(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => Value::Text(s),
+        nested => Value::Text(nested.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_rows() {
+        let rows = payload_rows("line1/TI-101", 0, br#"{"value": 20.5, "unit": "C"}"#);
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].contains(&("value".to_string(), Value::Float(20.5))));
+        assert!(rows[0].contains(&("topic".to_string(), Value::Text("line1/TI-101".into()))));
+
+        let rows = payload_rows("line1", 0, br#"[{"tag": "a"}, {"tag": "b"}]"#);
+        assert_eq!(rows.len(), 2);
+
+        let rows = payload_rows("line1/TI-101", 0, b"21");
+        assert_eq!(rows[0][2], ("value".to_string(), Value::Int(21)));
+
+        let rows = payload_rows("line1/state", 0, b"RUNNING");
+        assert_eq!(
+            rows[0][2],
+            ("value".to_string(), Value::Text("RUNNING".into()))
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! OPC UA source
+
+use async_trait::async_trait;
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::params::Value;
+use opcua::client::prelude::*;
+use opcua::sync::RwLock;
+use polars::prelude::*;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::{frame_from_rows, millis_to_datetime, Source};
+use crate::config::{OpcUaSecurityMode, OpcUaSecurityPolicy, OpcUaSourceConfig};
+
+fn opcua_err(e: impl std::fmt::Display) -> IndustryDbError {
+    IndustryDbError::connection_error(format!("OPC UA: {}", e))
+}
+
+/// Polls node values on a dedicated thread
+///
+/// The OPC UA client is synchronous and runs its own runtime, so it lives on
+/// a thread of its own and sends one batch per poll.
+pub(crate) struct OpcUaSource {
+    batches: mpsc::Receiver<Result<DataFrame>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl OpcUaSource {
+    pub(crate) fn connect(config: OpcUaSourceConfig) -> Result<Self> {
+        let (sender, batches) = mpsc::channel(16);
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        std::thread::Builder::new()
+            .name("industrydb-opcua".to_string())
+            .spawn(move || {
+                if let Err(e) = poll(&config, &sender, &stopped) {
+                    let _ = sender.blocking_send(Err(e));
+                }
+            })?;
+        Ok(Self { batches, stop })
+    }
+}
+
+impl Drop for OpcUaSource {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Source for OpcUaSource {
+    async fn next_batch(&mut self) -> Result<Option<DataFrame>> {
+        match self.batches.recv().await {
+            Some(batch) => batch.map(Some),
+            None => Err(opcua_err("polling thread stopped")),
+        }
+    }
+
+    fn checkpoint(&self) -> Option<Value> {
+        None
+    }
+}
+
+fn poll(
+    config: &OpcUaSourceConfig,
+    sender: &mpsc::Sender<Result<DataFrame>>,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let session = connect(config)?;
+    let nodes = config
+        .nodes
+        .iter()
+        .map(|node| {
+            NodeId::from_str(&node.node_id)
+                .map(ReadValueId::from)
+                .map_err(|_| {
+                    IndustryDbError::config_error(format!("Invalid node id {}", node.node_id))
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let interval = Duration::from_millis(config.interval_ms);
+
+    while !stop.load(Ordering::SeqCst) {
+        let started = std::time::Instant::now();
+        let values = session
+            .read()
+            .read(&nodes, TimestampsToReturn::Source, 0.0)
+            .map_err(opcua_err)?;
+        let polled_at = chrono::Utc::now().timestamp_millis();
+
+        let rows: Vec<Vec<(String, Value)>> = config
+            .nodes
+            .iter()
+            .zip(values)
+            .map(|(node, value)| {
+                let source_time = value
+                    .source_timestamp
+                    .map(|t| t.as_chrono().timestamp_millis())
+                    .unwrap_or(polled_at);
+                vec![
+                    ("tag".to_string(), Value::Text(node.tag().to_string())),
+                    ("value".to_string(), variant_value(value.value)),
+                    ("source_time".to_string(), Value::Int(source_time)),
+                    (
+                        "good".to_string(),
+                        Value::Bool(value.status.is_none_or(|s| s.is_good())),
+                    ),
+                ]
+            })
+            .collect();
+        let mut df = frame_from_rows(&rows)?;
+        millis_to_datetime(&mut df, "source_time")?;
+        if sender.blocking_send(Ok(df)).is_err() {
+            break;
+        }
+
+        if let Some(wait) = interval.checked_sub(started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+
+    session.read().disconnect();
+    Ok(())
+}
+
+fn connect(config: &OpcUaSourceConfig) -> Result<Arc<RwLock<Session>>> {
+    // Certificates are only used with security, which requires a pki_dir
+    let pki_dir = config
+        .pki_dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("industrydb-opcua-pki"));
+    let mut client = ClientBuilder::new()
+        .application_name("IndustryDB")
+        .application_uri("urn:industrydb")
+        .product_uri("urn:industrydb")
+        .pki_dir(pki_dir)
+        .create_sample_keypair(config.create_keypair)
+        .trust_server_certs(config.trust_server_certs)
+        .session_retry_limit(0)
+        .client()
+        .ok_or_else(|| opcua_err("invalid client configuration"))?;
+
+    let policy = match config.security_policy {
+        OpcUaSecurityPolicy::None => SecurityPolicy::None,
+        OpcUaSecurityPolicy::Basic128Rsa15 => SecurityPolicy::Basic128Rsa15,
+        OpcUaSecurityPolicy::Basic256 => SecurityPolicy::Basic256,
+        OpcUaSecurityPolicy::Basic256Sha256 => SecurityPolicy::Basic256Sha256,
+        OpcUaSecurityPolicy::Aes128Sha256RsaOaep => SecurityPolicy::Aes128Sha256RsaOaep,
+        OpcUaSecurityPolicy::Aes256Sha256RsaPss => SecurityPolicy::Aes256Sha256RsaPss,
+    };
+    let mode = match config.security_mode {
+        OpcUaSecurityMode::None => MessageSecurityMode::None,
+        OpcUaSecurityMode::Sign => MessageSecurityMode::Sign,
+        OpcUaSecurityMode::SignAndEncrypt => MessageSecurityMode::SignAndEncrypt,
+    };
+    let identity = match (&config.username, &config.certificate) {
+        (Some(username), _) => IdentityToken::UserName(
+            username.clone(),
+            config.password.clone().unwrap_or_default(),
+        ),
+        (None, Some(certificate)) => IdentityToken::X509(
+            certificate.clone(),
+            config.private_key.clone().unwrap_or_default(),
+        ),
+        (None, None) => IdentityToken::Anonymous,
+    };
+
+    client
+        .connect_to_endpoint((config.endpoint.as_str(), policy.to_str(), mode), identity)
+        .map_err(opcua_err)
+}
+
+fn variant_value(value: Option<Variant>) -> Value {
+    match value {
+        None | Some(Variant::Empty) => Value::Null,
+        Some(Variant::Boolean(b)) => Value::Bool(b),
+        Some(Variant::String(s)) => s.value().clone().map_or(Value::Null, Value::Text),
+        Some(variant) => match variant.as_f64() {
+            Some(f) => Value::Float(f),
+            None => Value::Text(variant.to_string()),
+        },
+    }
+}
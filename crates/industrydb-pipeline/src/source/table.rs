@@ -0,0 +1,126 @@
+//! Table source
+
+use async_trait::async_trait;
+use industrydb_core::decimal;
+use industrydb_core::error::Result;
+use industrydb_core::params::Value;
+use industrydb_core::schema::quote_literal;
+use industrydb_core::traits::DatabaseConnector;
+use polars::prelude::*;
+use std::time::Duration;
+
+use super::Source;
+use crate::config::TableSourceConfig;
+
+/// Reads a table or query, optionally only rows past a time watermark
+pub(crate) struct TableSource<'a> {
+    config: TableSourceConfig,
+    db: &'a dyn DatabaseConnector,
+    watermark: Option<Value>,
+    done: bool,
+}
+
+impl<'a> TableSource<'a> {
+    pub(crate) fn new(
+        config: TableSourceConfig,
+        db: &'a dyn DatabaseConnector,
+        resume: Option<Value>,
+    ) -> Result<Self> {
+        Ok(Self {
+            config,
+            db,
+            watermark: resume.filter(|v| *v != Value::Null),
+            done: false,
+        })
+    }
+
+    fn sql(&self) -> Result<String> {
+        let dialect = self.db.dialect();
+        let mut sql = match (&self.config.table, &self.config.query) {
+            (Some(table), _) => format!("SELECT * FROM {}", dialect.identifier(table)?),
+            (None, Some(query)) => format!("SELECT * FROM ({}) src", query),
+            (None, None) => unreachable!("validated by PipelineConfig"),
+        };
+        if let Some(time_column) = &self.config.time_column {
+            let time_column = dialect.identifier(time_column)?;
+            if let Some(watermark) = &self.watermark {
+                sql.push_str(&format!(
+                    " WHERE {} > {}",
+                    time_column,
+                    sql_literal(watermark)
+                ));
+            }
+            sql.push_str(&format!(" ORDER BY {}", time_column));
+        }
+        Ok(sql)
+    }
+}
+
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Int(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Bool(b) => (*b as i32).to_string(),
+        Value::Text(s) | Value::Uuid(s) => quote_literal(s),
+        Value::Bytes(b) => quote_literal(&String::from_utf8_lossy(b)),
+        Value::Decimal(d) => match decimal::parse(d) {
+            Ok((value, scale)) => decimal::format(value, scale),
+            Err(_) => quote_literal(d),
+        },
+        Value::Null => "NULL".to_string(),
+    }
+}
+
+#[async_trait]
+impl Source for TableSource<'_> {
+    async fn next_batch(&mut self) -> Result<Option<DataFrame>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let df = self.db.execute(&self.sql()?).await?;
+        let Some(time_column) = &self.config.time_column else {
+            self.done = true;
+            return Ok(Some(df));
+        };
+
+        if df.height() == 0 {
+            return match self.config.poll_interval_ms {
+                Some(interval) => {
+                    tokio::time::sleep(Duration::from_millis(interval)).await;
+                    Ok(Some(df))
+                }
+                None => {
+                    self.done = true;
+                    Ok(None)
+                }
+            };
+        }
+
+        let latest = df
+            .column(time_column)?
+            .as_materialized_series()
+            .max_reduce()?;
+        self.watermark = Some(Value::from(latest.value().clone()));
+        Ok(Some(df))
+    }
+
+    fn checkpoint(&self) -> Option<Value> {
+        self.watermark.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_literal() {
+        assert_eq!(sql_literal(&Value::Int(42)), "42");
+        assert_eq!(
+            sql_literal(&Value::Text("2024-01-01 00:00:00".to_string())),
+            "'2024-01-01 00:00:00'"
+        );
+        assert_eq!(sql_literal(&Value::Text("it's".to_string())), "'it''s'");
+    }
+}
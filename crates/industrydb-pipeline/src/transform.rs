@@ -0,0 +1,219 @@
+//! Batch transforms and validation
+
+use industrydb_core::error::Result;
+use industrydb_core::quality::{run_checks, CheckResult};
+use industrydb_core::schema::parse_dtype;
+use polars::prelude::*;
+use polars::sql::SQLContext;
+
+use crate::config::{Transform, ValidationConfig};
+
+/// Apply `transforms` to a batch in order
+///
+/// `rename`, `drop`, `cast` and `scale` skip columns the batch does not
+/// have, since message sources do not send every field every time;
+/// `select` and `sql` fail on them.
+pub fn apply_transforms(mut df: DataFrame, transforms: &[Transform]) -> Result<DataFrame> {
+    for transform in transforms {
+        df = apply(df, transform)?;
+    }
+    Ok(df)
+}
+
+fn apply(mut df: DataFrame, transform: &Transform) -> Result<DataFrame> {
+    let has = |df: &DataFrame, name: &str| df.get_column_index(name).is_some();
+
+    match transform {
+        Transform::Rename { columns } => {
+            for (old, new) in columns {
+                if has(&df, old) {
+                    df.rename(old, new.as_str().into())?;
+                }
+            }
+        }
+        Transform::Select { columns } => df = df.select(columns.iter().map(String::as_str))?,
+        Transform::Drop { columns } => {
+            for name in columns {
+                if has(&df, name) {
+                    df = df.drop(name)?;
+                }
+            }
+        }
+        Transform::Cast { columns } => {
+            for (name, dtype) in columns {
+                if has(&df, name) {
+                    let cast = df.column(name)?.cast(&parse_dtype(dtype)?)?;
+                    df.with_column(cast)?;
+                }
+            }
+        }
+        Transform::Scale {
+            column,
+            factor,
+            offset,
+        } => {
+            if has(&df, column) {
+                let values = df
+                    .column(column)?
+                    .cast(&DataType::Float64)?
+                    .as_materialized_series()
+                    .clone();
+                let scaled = (&values * *factor) + *offset;
+                df.with_column(scaled.with_name(column.as_str().into()))?;
+            }
+        }
+        Transform::Sql { query } => {
+            let mut ctx = SQLContext::new();
+            ctx.register("batch", df.lazy());
+            df = ctx.execute(query)?.collect()?;
+        }
+    }
+    Ok(df)
+}
+
+/// A batch split by validation
+#[derive(Debug, Clone)]
+pub struct Validated {
+    /// Rows passing every check
+    pub valid: DataFrame,
+    /// Rows failing at least one check
+    pub rejected: DataFrame,
+    /// Batch checks that failed, rejecting the whole batch
+    pub failed_checks: Vec<CheckResult>,
+}
+
+/// Split a batch into rows that pass `config`'s checks and rows that do not
+///
+/// A `not_null` column missing from the batch fails every row. Range checks
+/// pass nulls (use `not_null` to reject them) and fail NaN. When one of the
+/// batch `checks` fails, every row is rejected.
+pub fn validate(df: &DataFrame, config: &ValidationConfig) -> Result<Validated> {
+    let failed_checks: Vec<CheckResult> = run_checks(df, &config.checks)?
+        .failures()
+        .cloned()
+        .collect();
+    if !failed_checks.is_empty() {
+        return Ok(Validated {
+            valid: df.clear(),
+            rejected: df.clone(),
+            failed_checks,
+        });
+    }
+
+    let mut ok = BooleanChunked::full("valid".into(), true, df.height());
+
+    for name in &config.not_null {
+        let present = match df.column(name) {
+            Ok(column) => column.as_materialized_series().is_not_null(),
+            Err(_) => BooleanChunked::full("valid".into(), false, df.height()),
+        };
+        ok = &ok & &present;
+    }
+    for (name, range) in &config.ranges {
+        let Ok(column) = df.column(name) else {
+            continue;
+        };
+        let values = column.cast(&DataType::Float64)?;
+        let in_range: BooleanChunked = values
+            .f64()?
+            .into_iter()
+            .map(|v| match v {
+                None => true,
+                Some(v) => {
+                    !v.is_nan()
+                        && !range.min.is_some_and(|min| v < min)
+                        && !range.max.is_some_and(|max| v > max)
+                }
+            })
+            .collect();
+        ok = &ok & &in_range;
+    }
+
+    Ok(Validated {
+        valid: df.filter(&ok)?,
+        rejected: df.filter(&!&ok)?,
+        failed_checks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ValueRange;
+    use industrydb_core::quality::Check;
+    use std::collections::BTreeMap;
+
+    fn batch() -> DataFrame {
+        df!(
+            "tag" => ["TI-101", "TI-102", "TI-103"],
+            "raw" => [Some(205i64), None, Some(9_000)],
+            "unit" => ["C", "C", "C"]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transforms_in_order() {
+        let transforms = vec![
+            Transform::Rename {
+                columns: BTreeMap::from([("raw".to_string(), "value".to_string())]),
+            },
+            Transform::Scale {
+                column: "value".to_string(),
+                factor: 0.1,
+                offset: 0.0,
+            },
+            Transform::Drop {
+                columns: vec!["unit".to_string(), "missing".to_string()],
+            },
+            Transform::Sql {
+                query: "SELECT tag, value FROM batch WHERE tag <> 'TI-103'".to_string(),
+            },
+        ];
+        let df = apply_transforms(batch(), &transforms).unwrap();
+        assert_eq!(df.get_column_names_str(), vec!["tag", "value"]);
+        assert_eq!(df.height(), 2);
+        let value = df.column("value").unwrap().f64().unwrap().get(0).unwrap();
+        assert!((value - 20.5).abs() < 1e-9);
+
+        let select = Transform::Select {
+            columns: vec!["missing".to_string()],
+        };
+        assert!(apply_transforms(batch(), &[select]).is_err());
+    }
+
+    #[test]
+    fn test_validate_splits_rows() {
+        let config = ValidationConfig {
+            not_null: vec!["raw".to_string()],
+            ranges: BTreeMap::from([(
+                "raw".to_string(),
+                ValueRange {
+                    min: Some(0.0),
+                    max: Some(1_000.0),
+                },
+            )]),
+            ..Default::default()
+        };
+        let checked = validate(&batch(), &config).unwrap();
+        assert_eq!(checked.valid.height(), 1);
+        assert_eq!(checked.rejected.height(), 2);
+
+        let missing = ValidationConfig {
+            not_null: vec!["value".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(validate(&batch(), &missing).unwrap().valid.height(), 0);
+
+        let unique = ValidationConfig {
+            checks: vec![Check::DuplicateKeys {
+                columns: vec!["unit".to_string()],
+            }],
+            ..Default::default()
+        };
+        let checked = validate(&batch(), &unique).unwrap();
+        assert_eq!(checked.valid.height(), 0);
+        assert_eq!(checked.rejected.height(), 3);
+        assert_eq!(checked.failed_checks[0].check, "duplicate_keys");
+    }
+}
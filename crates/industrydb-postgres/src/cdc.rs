@@ -0,0 +1,555 @@
+//! Change data capture through logical replication
+//!
+//! A [`ChangeFeed`] reads a logical replication slot with the built-in
+//! `pgoutput` plugin and decodes its messages into row [`Change`]s, so
+//! downstream systems react to inserts, updates and deletes on MES tables
+//! without polling them. The tables are chosen by a publication:
+//!
+//! ```sql
+//! CREATE PUBLICATION mes_changes FOR TABLE work_orders, batches;
+//! ```
+//!
+//! The slot is read with `pg_logical_slot_peek_binary_changes` over an
+//! ordinary connection, so the server needs `wal_level = logical` and the
+//! role the `REPLICATION` attribute, but no replication connection. Changes
+//! are acknowledged, and the slot moved past them, on the next poll, so a
+//! consumer that stops before then sees them again.
+//!
+//! Values arrive as PostgreSQL prints them. An update or delete carries the
+//! old row's key columns, or the whole old row with `REPLICA IDENTITY FULL`;
+//! a large value an update left unchanged arrives as `None`.
+
+use futures_util::Stream;
+use industrydb_core::{
+    config::ConnectionConfig,
+    error::{IndustryDbError, Result},
+};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::connector::database_url;
+
+/// Changes asked for per poll of the slot when none is given
+pub const DEFAULT_POLL_CHANGES: usize = 1000;
+
+/// Kind of row change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+    /// The whole table was truncated; the change has no values
+    Truncate,
+}
+
+impl ChangeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Insert => "insert",
+            ChangeKind::Update => "update",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Truncate => "truncate",
+        }
+    }
+}
+
+/// One row change read from the slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    /// Position of the change in the write-ahead log, e.g. `0/16B3748`
+    pub lsn: String,
+    pub kind: ChangeKind,
+    /// Schema-qualified table name
+    pub table: String,
+    /// Column names of the table
+    pub columns: Vec<String>,
+    /// Type OIDs of the columns
+    #[serde(skip)]
+    pub type_oids: Vec<u32>,
+    /// New row of an insert or update; old key or row of a delete
+    pub values: Vec<Option<String>>,
+    /// Old key or row of an update that changed it
+    pub old_values: Option<Vec<Option<String>>>,
+}
+
+/// A table's columns, as announced by a pgoutput Relation message
+#[derive(Debug, Clone)]
+struct Relation {
+    table: String,
+    columns: Vec<String>,
+    type_oids: Vec<u32>,
+}
+
+/// Relations announced so far and the changes of the messages decoded
+#[derive(Debug, Default)]
+struct Decoder {
+    relations: HashMap<u32, Relation>,
+}
+
+/// Changes of one table as a DataFrame
+///
+/// `_lsn` and `_op` come first, then the table's columns. Booleans,
+/// integers and floats are typed; every other column holds text.
+#[derive(Debug, Clone)]
+pub struct ChangeBatch {
+    pub table: String,
+    pub frame: DataFrame,
+}
+
+/// Row changes of the tables in a publication, read from a replication slot
+pub struct ChangeFeed {
+    pool: PgPool,
+    slot: String,
+    publication: String,
+    decoder: Decoder,
+    /// Commit LSN of the last transaction returned, acknowledged on the next poll
+    pending: Option<String>,
+}
+
+impl ChangeFeed {
+    /// Open a connection for `config` reading `slot` for `publication`
+    pub async fn connect(config: &ConnectionConfig, slot: &str, publication: &str) -> Result<Self> {
+        let pool = PgPool::connect(&database_url(config))
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+        Ok(Self::with_pool(&pool, slot, publication))
+    }
+
+    /// Read `slot` for `publication` through connections of `pool`
+    pub fn with_pool(pool: &PgPool, slot: &str, publication: &str) -> Self {
+        Self {
+            pool: pool.clone(),
+            slot: slot.to_string(),
+            publication: publication.to_string(),
+            decoder: Decoder::default(),
+            pending: None,
+        }
+    }
+
+    /// Create the slot unless it exists
+    ///
+    /// A new slot starts at the current end of the log; earlier changes
+    /// are not delivered.
+    pub async fn create_slot(&self) -> Result<()> {
+        sqlx::query(
+            "SELECT pg_create_logical_replication_slot($1, 'pgoutput') \
+             WHERE NOT EXISTS (SELECT 1 FROM pg_replication_slots WHERE slot_name = $1)",
+        )
+        .bind(&self.slot)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Drop the slot, letting the server discard the log it retained
+    ///
+    /// An unused slot keeps the server from recycling its write-ahead log,
+    /// so drop slots that are no longer read.
+    pub async fn drop_slot(&self) -> Result<()> {
+        sqlx::query("SELECT pg_drop_replication_slot($1)")
+            .bind(&self.slot)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Acknowledge the changes returned so far and read the next ones
+    ///
+    /// Reads whole transactions until at least `max_changes` changes were
+    /// decoded or the slot is drained, so a large transaction can return
+    /// more. Returns no changes when nothing new was committed.
+    pub async fn poll(&mut self, max_changes: usize) -> Result<Vec<Change>> {
+        if let Some(lsn) = self.pending.take() {
+            self.acknowledge(&lsn).await?;
+        }
+
+        let rows = sqlx::query(
+            "SELECT lsn::text, data FROM pg_logical_slot_peek_binary_changes(\
+             $1, NULL, $2, 'proto_version', '1', 'publication_names', $3)",
+        )
+        .bind(&self.slot)
+        .bind(i32::try_from(max_changes).unwrap_or(i32::MAX))
+        .bind(&self.publication)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            let lsn: String = row.get(0);
+            let data: Vec<u8> = row.get(1);
+            if data.first() == Some(&b'C') {
+                self.pending = Some(lsn.clone());
+            }
+            self.decoder.decode(&lsn, &data, &mut changes)?;
+        }
+        Ok(changes)
+    }
+
+    /// [`poll`](Self::poll) the changes as one [`ChangeBatch`] per table, in
+    /// the order each table first changed
+    pub async fn poll_frames(&mut self, max_changes: usize) -> Result<Vec<ChangeBatch>> {
+        changes_to_frames(&self.poll(max_changes).await?)
+    }
+
+    /// Changes as a stream, polling every `interval` while the slot is drained
+    ///
+    /// Each poll acknowledges the changes of the one before, so a consumer
+    /// that stops partway through a poll's changes sees them again.
+    pub fn into_stream(self, interval: Duration) -> impl Stream<Item = Result<Change>> {
+        futures_util::stream::try_unfold(
+            (self, VecDeque::new()),
+            move |(mut feed, mut buffered)| async move {
+                loop {
+                    if let Some(change) = buffered.pop_front() {
+                        return Ok(Some((change, (feed, buffered))));
+                    }
+                    let changes = feed.poll(DEFAULT_POLL_CHANGES).await?;
+                    if changes.is_empty() {
+                        tokio::time::sleep(interval).await;
+                    }
+                    buffered.extend(changes);
+                }
+            },
+        )
+    }
+
+    /// Consume the slot up to the transaction committed at `lsn`
+    async fn acknowledge(&self, lsn: &str) -> Result<()> {
+        sqlx::query(
+            "SELECT count(*) FROM pg_logical_slot_get_binary_changes(\
+             $1, $2::pg_lsn, NULL, 'proto_version', '1', 'publication_names', $3)",
+        )
+        .bind(&self.slot)
+        .bind(lsn)
+        .bind(&self.publication)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+impl Decoder {
+    /// Decode one pgoutput message, adding the changes it carries
+    fn decode(&mut self, lsn: &str, data: &[u8], changes: &mut Vec<Change>) -> Result<()> {
+        let mut message = Message::new(data);
+        let change = |relation: &Relation, kind, values, old_values| Change {
+            lsn: lsn.to_string(),
+            kind,
+            table: relation.table.clone(),
+            columns: relation.columns.clone(),
+            type_oids: relation.type_oids.clone(),
+            values,
+            old_values,
+        };
+
+        match message.u8()? {
+            b'R' => {
+                let id = message.u32()?;
+                let schema = message.cstr()?;
+                let table = message.cstr()?;
+                message.u8()?; // replica identity setting
+                let count = message.i16()?;
+                let mut relation = Relation {
+                    table: format!("{}.{}", schema, table),
+                    columns: Vec::new(),
+                    type_oids: Vec::new(),
+                };
+                for _ in 0..count {
+                    message.u8()?; // flags
+                    relation.columns.push(message.cstr()?);
+                    relation.type_oids.push(message.u32()?);
+                    message.i32()?; // type modifier
+                }
+                self.relations.insert(id, relation);
+            }
+            b'I' => {
+                let relation = self.relation(message.u32()?)?;
+                message.tag(b'N')?;
+                let values = message.tuple()?;
+                changes.push(change(relation, ChangeKind::Insert, values, None));
+            }
+            b'U' => {
+                let relation = self.relation(message.u32()?)?;
+                let old_values = match message.u8()? {
+                    b'K' | b'O' => {
+                        let old = message.tuple()?;
+                        message.tag(b'N')?;
+                        Some(old)
+                    }
+                    b'N' => None,
+                    _ => return Err(malformed()),
+                };
+                let values = message.tuple()?;
+                changes.push(change(relation, ChangeKind::Update, values, old_values));
+            }
+            b'D' => {
+                let relation = self.relation(message.u32()?)?;
+                if !matches!(message.u8()?, b'K' | b'O') {
+                    return Err(malformed());
+                }
+                let values = message.tuple()?;
+                changes.push(change(relation, ChangeKind::Delete, values, None));
+            }
+            b'T' => {
+                let count = message.i32()?;
+                message.u8()?; // CASCADE / RESTART IDENTITY
+                for _ in 0..count {
+                    let relation = self.relation(message.u32()?)?;
+                    changes.push(change(relation, ChangeKind::Truncate, Vec::new(), None));
+                }
+            }
+            // Begin, commit, origin, type and logical messages carry no rows
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn relation(&self, id: u32) -> Result<&Relation> {
+        self.relations.get(&id).ok_or_else(|| {
+            IndustryDbError::QueryError(format!("pgoutput change for unknown relation {}", id))
+        })
+    }
+}
+
+/// Changes grouped into one [`ChangeBatch`] per table and column list
+fn changes_to_frames(changes: &[Change]) -> Result<Vec<ChangeBatch>> {
+    let mut groups: Vec<Vec<&Change>> = Vec::new();
+    for change in changes {
+        match groups
+            .iter_mut()
+            .find(|g| g[0].table == change.table && g[0].columns == change.columns)
+        {
+            Some(group) => group.push(change),
+            None => groups.push(vec![change]),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|group| {
+            let first = group[0];
+            let lsns: Vec<&str> = group.iter().map(|c| c.lsn.as_str()).collect();
+            let ops: Vec<&str> = group.iter().map(|c| c.kind.as_str()).collect();
+            let mut columns = vec![
+                Series::new("_lsn".into(), lsns).into_column(),
+                Series::new("_op".into(), ops).into_column(),
+            ];
+            for (index, (name, oid)) in first.columns.iter().zip(&first.type_oids).enumerate() {
+                let values: Vec<Option<&str>> = group
+                    .iter()
+                    .map(|c| c.values.get(index).and_then(|v| v.as_deref()))
+                    .collect();
+                columns.push(typed_series(name, *oid, &values)?.into_column());
+            }
+            Ok(ChangeBatch {
+                table: first.table.clone(),
+                frame: DataFrame::new(columns)?,
+            })
+        })
+        .collect()
+}
+
+/// Text values of a column parsed into the dtype of its type OID
+fn typed_series(name: &str, oid: u32, values: &[Option<&str>]) -> Result<Series> {
+    fn parse<T: std::str::FromStr>(name: &str, values: &[Option<&str>]) -> Result<Vec<Option<T>>> {
+        values
+            .iter()
+            .map(|v| {
+                v.map(|v| {
+                    v.parse().map_err(|_| {
+                        IndustryDbError::QueryError(format!("Cannot parse {:?} in {}", v, name))
+                    })
+                })
+                .transpose()
+            })
+            .collect()
+    }
+
+    Ok(match oid {
+        16 => Series::new(
+            name.into(),
+            values
+                .iter()
+                .map(|v| v.map(|v| v == "t"))
+                .collect::<Vec<_>>(),
+        ),
+        21 => Series::new(name.into(), parse::<i16>(name, values)?),
+        23 => Series::new(name.into(), parse::<i32>(name, values)?),
+        20 => Series::new(name.into(), parse::<i64>(name, values)?),
+        700 => Series::new(name.into(), parse::<f32>(name, values)?),
+        701 => Series::new(name.into(), parse::<f64>(name, values)?),
+        _ => Series::new(name.into(), values),
+    })
+}
+
+fn malformed() -> IndustryDbError {
+    IndustryDbError::QueryError("Malformed pgoutput message".to_string())
+}
+
+/// Cursor over a pgoutput message, all integers big-endian
+struct Message<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Message<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(malformed());
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn tag(&mut self, expected: u8) -> Result<()> {
+        match self.u8()? {
+            tag if tag == expected => Ok(()),
+            _ => Err(malformed()),
+        }
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        let bytes = self.take(2)?;
+        Ok(i16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(self.i32()? as u32)
+    }
+
+    fn cstr(&mut self) -> Result<String> {
+        let end = self
+            .data
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(malformed)?;
+        let text = String::from_utf8_lossy(&self.data[..end]).into_owned();
+        self.data = &self.data[end + 1..];
+        Ok(text)
+    }
+
+    /// TupleData: a value per column; NULLs and unchanged TOAST values are `None`
+    fn tuple(&mut self) -> Result<Vec<Option<String>>> {
+        let count = self.i16()?;
+        (0..count)
+            .map(|_| match self.u8()? {
+                b'n' | b'u' => Ok(None),
+                b't' => {
+                    let len = usize::try_from(self.i32()?).map_err(|_| malformed())?;
+                    Ok(Some(String::from_utf8_lossy(self.take(len)?).into_owned()))
+                }
+                _ => Err(malformed()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELATION_ID: u32 = 16384;
+
+    fn relation_message() -> Vec<u8> {
+        let mut data = vec![b'R'];
+        data.extend_from_slice(&RELATION_ID.to_be_bytes());
+        data.extend_from_slice(b"mes\0work_orders\0d");
+        data.extend_from_slice(&2i16.to_be_bytes());
+        for (name, oid) in [("id", 23u32), ("status", 25)] {
+            data.push(1);
+            data.extend_from_slice(name.as_bytes());
+            data.push(0);
+            data.extend_from_slice(&oid.to_be_bytes());
+            data.extend_from_slice(&(-1i32).to_be_bytes());
+        }
+        data
+    }
+
+    fn change_message(tag: u8, parts: &[(u8, &[Option<&str>])]) -> Vec<u8> {
+        let mut data = vec![tag];
+        data.extend_from_slice(&RELATION_ID.to_be_bytes());
+        for (kind, values) in parts {
+            data.push(*kind);
+            data.extend_from_slice(&(values.len() as i16).to_be_bytes());
+            for value in values.iter() {
+                match value {
+                    Some(value) => {
+                        data.push(b't');
+                        data.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                        data.extend_from_slice(value.as_bytes());
+                    }
+                    None => data.push(b'n'),
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_decode_pgoutput() {
+        let mut decoder = Decoder::default();
+        let mut changes = Vec::new();
+        decoder
+            .decode("0/1", &relation_message(), &mut changes)
+            .unwrap();
+
+        let insert = change_message(b'I', &[(b'N', &[Some("7"), Some("open")])]);
+        decoder.decode("0/2", &insert, &mut changes).unwrap();
+        let update = change_message(
+            b'U',
+            &[
+                (b'K', &[Some("7"), None]),
+                (b'N', &[Some("8"), Some("closed")]),
+            ],
+        );
+        decoder.decode("0/3", &update, &mut changes).unwrap();
+        let delete = change_message(b'D', &[(b'K', &[Some("8"), None])]);
+        decoder.decode("0/4", &delete, &mut changes).unwrap();
+        decoder.decode("0/5", b"C", &mut changes).unwrap();
+
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].table, "mes.work_orders");
+        assert_eq!(changes[0].values, [Some("7".into()), Some("open".into())]);
+        assert_eq!(changes[1].kind, ChangeKind::Update);
+        assert_eq!(changes[1].old_values, Some(vec![Some("7".into()), None]));
+        assert_eq!(changes[2].kind, ChangeKind::Delete);
+
+        let batches = changes_to_frames(&changes).unwrap();
+        assert_eq!(batches.len(), 1);
+        let frame = &batches[0].frame;
+        let names: Vec<_> = frame
+            .get_column_names()
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(names, ["_lsn", "_op", "id", "status"]);
+        assert_eq!(frame.column("id").unwrap().dtype(), &DataType::Int32);
+        assert_eq!(frame.column("status").unwrap().null_count(), 1);
+
+        let mut unknown = vec![b'I'];
+        unknown.extend_from_slice(&1u32.to_be_bytes());
+        assert!(decoder.decode("0/6", &unknown, &mut changes).is_err());
+        assert!(decoder.decode("0/6", &insert[..6], &mut changes).is_err());
+    }
+}
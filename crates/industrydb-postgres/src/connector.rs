@@ -1,39 +1,61 @@
 //! PostgreSQL connector implementation using sqlx with connection pooling
 
 use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use futures_util::TryStreamExt;
 use industrydb_core::{
-    config::ConnectionConfig,
+    config::{ConnectionConfig, DatabaseType, OverflowMode, TimestampMode},
+    copy::{CopyFormat, CsvCopyOptions, COPY_CHUNK_BYTES},
+    decimal,
+    dialect::{Dialect, PostgresDialect},
     error::{IndustryDbError, Result},
+    metrics::{ChunkedDecode, QueryMetrics, DECODE_CHUNK_ROWS},
+    params::{parameter_set_error, Value},
+    procedure::{first_row, ProcedureArg, ProcedureResult},
+    schema,
+    sink::FrameSink,
+    sql::ensure_returns_rows,
+    time::apply_timestamp_mode,
     traits::DatabaseConnector,
 };
 use polars::prelude::*;
-use sqlx::{postgres::PgRow, Column as SqlxColumn, PgPool, Row, TypeInfo};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::types::Oid;
+use sqlx::postgres::{
+    PgArgumentBuffer, PgArguments, PgPoolCopyExt, PgRow, PgTypeInfo, PgTypeKind, PgValueFormat,
+    PgValueRef,
+};
+use sqlx::query::Query;
+use sqlx::{Column as SqlxColumn, PgPool, Postgres, Row, TypeInfo, ValueRef};
+use std::borrow::Cow;
+use std::io::{ErrorKind, Read, Write};
+
+use crate::cdc::ChangeFeed;
+use crate::introspection;
+use crate::notify::Subscription;
+use crate::procedure;
 
 /// PostgreSQL database connector with connection pool
 pub struct PostgresConnector {
     pool: PgPool,
     db_type: String,
+    config: ConnectionConfig,
+    metrics: QueryMetrics,
 }
 
 impl PostgresConnector {
     /// Create a new PostgreSQL connector with connection pool
     pub async fn new(config: &ConnectionConfig) -> Result<Self> {
-        let database_url = format!(
-            "postgresql://{}:{}@{}:{}/{}",
-            config.username.as_deref().unwrap_or("postgres"),
-            config.password.as_deref().unwrap_or(""),
-            config.host.as_deref().unwrap_or("localhost"),
-            config.port.unwrap_or(5432),
-            config.database.as_deref().unwrap_or("postgres")
-        );
-
-        let pool = PgPool::connect(&database_url)
+        let pool = PgPool::connect(&database_url(config))
             .await
             .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
 
         Ok(Self {
             pool,
             db_type: "postgres".to_string(),
+            config: config.clone(),
+            metrics: QueryMetrics::new(),
         })
     }
 
@@ -41,6 +63,72 @@ impl PostgresConnector {
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
+
+    /// Get the configuration this connector was created with
+    pub fn config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    /// Subscribe to notifications on `channels`
+    ///
+    /// The subscription holds its own connection from the pool.
+    pub async fn listen(&self, channels: &[&str]) -> Result<Subscription> {
+        Subscription::with_pool(&self.pool, channels).await
+    }
+
+    /// Read the changes of the tables in `publication` from the logical
+    /// replication slot `slot`, see [`ChangeFeed`]
+    pub fn change_feed(&self, slot: &str, publication: &str) -> ChangeFeed {
+        ChangeFeed::with_pool(&self.pool, slot, publication)
+    }
+
+    /// Zone timestamps are returned in
+    fn timestamps(&self) -> TimestampMode {
+        self.config.timestamps.clone().unwrap_or_default()
+    }
+
+    /// What happens to values too wide for their column's dtype
+    fn overflow(&self) -> OverflowMode {
+        self.config.overflow.unwrap_or_default()
+    }
+
+    /// Fetch a query's rows and decode them a chunk at a time
+    async fn fetch_chunks(
+        &self,
+        sql: &str,
+        params: &[Value],
+        decode: &mut ChunkedDecode<'_>,
+    ) -> Result<()> {
+        let (overflow, timestamps) = (self.overflow(), self.timestamps());
+        let decode_rows = |rows: Vec<PgRow>| {
+            apply_timestamp_mode(rows_to_dataframe(rows, overflow)?, &timestamps)
+        };
+        let mut stream = bind_params(sqlx::query(sql), params).fetch(&self.pool);
+        let mut rows = Vec::with_capacity(DECODE_CHUNK_ROWS);
+        while let Some(row) = stream
+            .try_next()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?
+        {
+            rows.push(row);
+            if rows.len() == DECODE_CHUNK_ROWS {
+                let chunk = std::mem::replace(&mut rows, Vec::with_capacity(DECODE_CHUNK_ROWS));
+                decode.decode(|| decode_rows(chunk))?;
+            }
+        }
+        if !rows.is_empty() {
+            decode.decode(|| decode_rows(rows))?;
+        }
+        Ok(())
+    }
+
+    /// Apply the connection's access policy to a query
+    pub(crate) fn enforce_policy<'a>(&self, sql: &'a str) -> Result<Cow<'a, str>> {
+        match &self.config.policy {
+            Some(policy) => policy.enforce(sql, DatabaseType::Postgres),
+            None => Ok(Cow::Borrowed(sql)),
+        }
+    }
 }
 
 #[async_trait]
@@ -49,19 +137,222 @@ impl DatabaseConnector for PostgresConnector {
         &self.db_type
     }
 
+    fn dialect(&self) -> &'static dyn Dialect {
+        &PostgresDialect
+    }
+
+    fn metrics(&self) -> &QueryMetrics {
+        &self.metrics
+    }
+
     async fn execute(&self, sql: &str) -> Result<DataFrame> {
-        // Execute query and fetch all rows
-        let rows = sqlx::query(sql)
-            .fetch_all(&self.pool)
+        self.execute_with_params(sql, &[]).await
+    }
+
+    async fn execute_with_params(&self, sql: &str, params: &[Value]) -> Result<DataFrame> {
+        let sql = self.enforce_policy(sql)?;
+        ensure_returns_rows(&sql)?;
+        let mut decode = self.metrics.chunked(&sql);
+        self.fetch_chunks(&sql, params, &mut decode).await?;
+        decode.finish()
+    }
+
+    async fn execute_into(
+        &self,
+        sql: &str,
+        params: &[Value],
+        sink: &mut dyn FrameSink,
+    ) -> Result<()> {
+        let sql = self.enforce_policy(sql)?;
+        ensure_returns_rows(&sql)?;
+        let mut decode = self.metrics.chunked(&sql).into_sink(sink);
+        self.fetch_chunks(&sql, params, &mut decode).await?;
+        decode.finish()?;
+        Ok(())
+    }
+
+    async fn execute_statement(&self, sql: &str, params: &[Value]) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        let result = bind_params(sqlx::query(&sql), params)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    async fn execute_many(&self, sql: &str, param_sets: &[Vec<Value>]) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        // One connection and transaction for every set; sqlx prepares the
+        // statement on first use and reuses it for the rest
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        let mut affected = 0;
+        for (index, params) in param_sets.iter().enumerate() {
+            let result = bind_params(sqlx::query(&sql), params)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    parameter_set_error(index, IndustryDbError::QueryError(e.to_string()))
+                })?;
+            affected += result.rows_affected();
+        }
+        tx.commit()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(affected)
+    }
+
+    async fn execute_transaction(&self, statements: &[String]) -> Result<u64> {
+        let statements = statements
+            .iter()
+            .map(|sql| self.enforce_policy(sql))
+            .collect::<Result<Vec<_>>>()?;
+        let mut tx = self
+            .pool
+            .begin()
             .await
             .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        let mut affected = 0;
+        for sql in &statements {
+            // Dropping `tx` on failure rolls it back
+            let result = sqlx::query(sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+            affected += result.rows_affected();
+        }
+        tx.commit()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(affected)
+    }
+
+    async fn call_procedure(&self, name: &str, args: &[ProcedureArg]) -> Result<ProcedureResult> {
+        let (kind_sql, kind_params) = procedure::routine_kind_sql(name);
+        let kind = self.fetch_scalar(&kind_sql, &kind_params).await?;
+        let is_procedure = match kind {
+            Some(Value::Text(kind)) => kind == "p",
+            _ => {
+                return Err(IndustryDbError::query_error(format!(
+                    "No procedure or function named '{}'",
+                    name
+                )))
+            }
+        };
+
+        let (sql, params) = procedure::call_sql(name, args, is_procedure)?;
+        let df = self.execute_with_params(&sql, &params).await?;
+        if is_procedure {
+            // CALL returns the OUT and INOUT parameters as its only row
+            return Ok(ProcedureResult {
+                outputs: first_row(&df)?,
+                ..Default::default()
+            });
+        }
+
+        let row = first_row(&df)?;
+        let outputs = args
+            .iter()
+            .filter(|arg| arg.is_output())
+            .filter_map(|arg| {
+                let name = arg.name.as_deref()?;
+                row.iter()
+                    .find(|(column, _)| column.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        // A scalar function returns one row with one column
+        let return_value =
+            (outputs.is_empty() && df.height() == 1 && df.width() == 1).then(|| row[0].1.clone());
+        Ok(ProcedureResult {
+            result_sets: vec![df],
+            outputs,
+            return_value,
+        })
+    }
+
+    #[cfg(feature = "connectorx")]
+    async fn fast_read(&self, sql: &str) -> Result<DataFrame> {
+        let sql = self.enforce_policy(sql)?.into_owned();
+        ensure_returns_rows(&sql)?;
+        let started = std::time::Instant::now();
+        let (url, query) = (database_url(&self.config), sql.clone());
+        let df = tokio::task::spawn_blocking(move || crate::fast_read::read_sql(&url, &query))
+            .await
+            .map_err(|e| IndustryDbError::ConnectorXError(e.to_string()))??;
+        let df = self
+            .metrics
+            .record_decode(&sql, started.elapsed(), || Ok(df))?;
+        apply_timestamp_mode(df, &self.timestamps())
+    }
 
-        if rows.is_empty() {
-            return Ok(DataFrame::empty());
+    async fn copy_out(
+        &self,
+        sql: &str,
+        format: CopyFormat,
+        out: &mut (dyn Write + Send),
+    ) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        ensure_returns_rows(&sql)?;
+        let statement = format!(
+            "COPY ({}) TO STDOUT WITH ({})",
+            sql.trim().trim_end_matches(';'),
+            format.options()
+        );
+        let mut stream = self
+            .pool
+            .copy_out_raw(&statement)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        let mut bytes = 0;
+        while let Some(chunk) = stream
+            .try_next()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?
+        {
+            out.write_all(&chunk)?;
+            bytes += chunk.len() as u64;
         }
+        Ok(bytes)
+    }
 
-        // Convert rows to Polars DataFrame
-        rows_to_dataframe(rows)
+    async fn copy_in(
+        &self,
+        table: &str,
+        options: &CsvCopyOptions,
+        input: &mut (dyn Read + Send),
+    ) -> Result<u64> {
+        let statement = format!(
+            "COPY {} FROM STDIN WITH ({})",
+            self.dialect().identifier(table)?,
+            options.options()?
+        );
+        let mut copy = self
+            .pool
+            .copy_in_raw(&statement)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        let mut buffer = vec![0; COPY_CHUNK_BYTES];
+        loop {
+            let read = match input.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    let _ = copy.abort(e.to_string()).await;
+                    return Err(e.into());
+                }
+            };
+            copy.send(&buffer[..read])
+                .await
+                .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        }
+        copy.finish()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))
     }
 
     async fn is_alive(&self) -> bool {
@@ -76,10 +367,192 @@ impl DatabaseConnector for PostgresConnector {
     fn is_closed(&self) -> bool {
         self.pool.is_closed()
     }
+
+    async fn list_schemas(&self) -> Result<Vec<String>> {
+        let df = self.execute(&introspection::list_schemas_sql()).await?;
+        schema::first_column_strings(&df)
+    }
+
+    async fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        let df = self
+            .execute(&introspection::list_tables_sql(schema))
+            .await?;
+        schema::first_column_strings(&df)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::describe_table_sql(table))
+            .await?;
+        schema::normalize_describe(df)
+    }
+
+    async fn primary_keys(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::primary_keys_sql(table))
+            .await?;
+        schema::normalize_primary_keys(df)
+    }
+
+    async fn indexes(&self, table: &str) -> Result<DataFrame> {
+        let df = self.execute(&introspection::indexes_sql(table)).await?;
+        schema::normalize_indexes(df)
+    }
+
+    async fn foreign_keys(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::foreign_keys_sql(table))
+            .await?;
+        schema::normalize_foreign_keys(df)
+    }
+}
+
+/// Connection URL of `config`
+pub(crate) fn database_url(config: &ConnectionConfig) -> String {
+    format!(
+        "postgresql://{}:{}@{}:{}/{}",
+        config.username.as_deref().unwrap_or("postgres"),
+        config.password.as_deref().unwrap_or(""),
+        config.host.as_deref().unwrap_or("localhost"),
+        config.port.unwrap_or(5432),
+        config.database.as_deref().unwrap_or("postgres")
+    )
+}
+
+/// Bind parameter values to a query in placeholder order
+pub(crate) fn bind_params<'q>(
+    mut query: Query<'q, Postgres, PgArguments>,
+    params: &[Value],
+) -> Query<'q, Postgres, PgArguments> {
+    for param in params {
+        query = match param {
+            Value::Null => query.bind(None::<UntypedText>),
+            Value::Bool(v) => query.bind(*v),
+            Value::Int(v) => query.bind(*v),
+            Value::Float(v) => query.bind(*v),
+            Value::Text(v) => query.bind(UntypedText(v.clone())),
+            Value::Bytes(v) => query.bind(v.clone()),
+            Value::Decimal(v) => match numeric(v) {
+                Some(d) => query.bind(d),
+                // Beyond what NUMERIC parameters can carry; the server
+                // reports the mismatch
+                None => query.bind(v.clone()),
+            },
+            Value::Uuid(v) => match sqlx::types::Uuid::parse_str(v) {
+                Ok(u) => query.bind(u),
+                Err(_) => query.bind(v.clone()),
+            },
+        };
+    }
+    query
+}
+
+/// `unknown`, the type of quoted literals
+const UNKNOWN_OID: Oid = Oid(705);
+
+/// Text parameter sent without a type
+///
+/// The server then types it from where it is used, as it does a quoted
+/// literal, so enum and domain columns accept it along with text columns.
+struct UntypedText(String);
+
+impl sqlx::Type<Postgres> for UntypedText {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_oid(UNKNOWN_OID)
+    }
+}
+
+impl sqlx::Encode<'_, Postgres> for UntypedText {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> std::result::Result<IsNull, BoxDynError> {
+        <String as sqlx::Encode<'_, Postgres>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+/// NUMERIC parameter for decimal text, if it fits in 28 digits
+fn numeric(text: &str) -> Option<rust_decimal::Decimal> {
+    let (value, scale) = decimal::parse(text).ok()?;
+    rust_decimal::Decimal::try_from_i128_with_scale(value, scale).ok()
+}
+
+/// Exact text of a `numeric` value: digits, `NaN`, `Infinity` or `-Infinity`
+///
+/// Decoded from the wire format itself, as decimal types would drop values
+/// beyond their own precision.
+fn numeric_text(value: PgValueRef<'_>) -> Result<Option<String>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let format = value.format();
+    let bytes = value
+        .as_bytes()
+        .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+    if format == PgValueFormat::Text {
+        return Ok(Some(String::from_utf8_lossy(bytes).into_owned()));
+    }
+    numeric_from_binary(bytes)
+        .map(Some)
+        .ok_or_else(|| IndustryDbError::QueryError("Malformed numeric value".to_string()))
+}
+
+/// Text of a binary `numeric`: digit count, weight, sign and display scale
+/// as 16-bit integers, then base-10000 digits, the first one weighted
+/// 10000^weight
+fn numeric_from_binary(bytes: &[u8]) -> Option<String> {
+    let word = |i: usize| -> Option<i16> {
+        Some(i16::from_be_bytes(
+            bytes.get(i * 2..i * 2 + 2)?.try_into().ok()?,
+        ))
+    };
+    let ndigits = word(0)?.max(0) as usize;
+    let weight = word(1)? as i64;
+    let sign = word(2)? as u16;
+    let dscale = word(3)?.max(0) as usize;
+    let digits = (0..ndigits)
+        .map(|i| word(4 + i))
+        .collect::<Option<Vec<_>>>()?;
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => {}
+    }
+    let digit = |i: i64| {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| digits.get(i).copied())
+            .unwrap_or(0)
+    };
+
+    let mut whole = String::new();
+    for i in 0..=weight {
+        if whole.is_empty() {
+            whole.push_str(&digit(i).to_string());
+        } else {
+            whole.push_str(&format!("{:04}", digit(i)));
+        }
+    }
+    if whole.is_empty() {
+        whole.push('0');
+    }
+    let mut fraction = String::new();
+    for i in 0..dscale.div_ceil(4) as i64 {
+        fraction.push_str(&format!("{:04}", digit(weight + 1 + i)));
+    }
+    fraction.truncate(dscale);
+
+    let sign = if sign == 0x4000 { "-" } else { "" };
+    Some(if fraction.is_empty() {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fraction)
+    })
 }
 
 /// Convert PostgreSQL rows to Polars DataFrame
-fn rows_to_dataframe(rows: Vec<PgRow>) -> Result<DataFrame> {
+fn rows_to_dataframe(rows: Vec<PgRow>, overflow: OverflowMode) -> Result<DataFrame> {
     if rows.is_empty() {
         return Ok(DataFrame::empty());
     }
@@ -89,38 +562,137 @@ fn rows_to_dataframe(rows: Vec<PgRow>) -> Result<DataFrame> {
 
     for column in columns {
         let col_name = column.name();
-        let col_type = column.type_info();
+        let col_type = base_type(column.type_info());
 
-        // Extract values based on type
+        // Extract values based on type; the name has been matched, so values
+        // decode without sqlx's own type check, which rejects domains
         let series = match col_type.name() {
             "INT2" | "SMALLINT" => {
-                let values: Vec<Option<i16>> =
-                    rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+                let values: Vec<Option<i16>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
                 Series::new(col_name.into(), values)
             }
             "INT4" | "INT" | "INTEGER" => {
-                let values: Vec<Option<i32>> =
-                    rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+                let values: Vec<Option<i32>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
                 Series::new(col_name.into(), values)
             }
             "INT8" | "BIGINT" => {
-                let values: Vec<Option<i64>> =
-                    rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+                let values: Vec<Option<i64>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
                 Series::new(col_name.into(), values)
             }
             "FLOAT4" | "REAL" => {
-                let values: Vec<Option<f32>> =
-                    rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+                let values: Vec<Option<f32>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
                 Series::new(col_name.into(), values)
             }
             "FLOAT8" | "DOUBLE PRECISION" => {
-                let values: Vec<Option<f64>> =
-                    rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+                let values: Vec<Option<f64>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
                 Series::new(col_name.into(), values)
             }
             "BOOL" | "BOOLEAN" => {
-                let values: Vec<Option<bool>> =
-                    rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+                let values: Vec<Option<bool>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
+                Series::new(col_name.into(), values)
+            }
+            "NUMERIC" | "DECIMAL" => {
+                let values = rows
+                    .iter()
+                    .map(|row| {
+                        let value = row
+                            .try_get_raw(col_name)
+                            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+                        numeric_text(value)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                decimal::fit_series(col_name, &values, overflow)?
+            }
+            "INT2[]" => array_series::<i16>(&rows, col_name, DataType::Int16)?,
+            "INT4[]" => array_series::<i32>(&rows, col_name, DataType::Int32)?,
+            "INT8[]" => array_series::<i64>(&rows, col_name, DataType::Int64)?,
+            "FLOAT4[]" => array_series::<f32>(&rows, col_name, DataType::Float32)?,
+            "FLOAT8[]" => array_series::<f64>(&rows, col_name, DataType::Float64)?,
+            "BOOL[]" => array_series::<bool>(&rows, col_name, DataType::Boolean)?,
+            "TEXT[]" | "VARCHAR[]" | "CHAR[]" | "NAME[]" => {
+                array_series::<String>(&rows, col_name, DataType::String)?
+            }
+            "BYTEA" => {
+                let values: Vec<Option<Vec<u8>>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
+                BinaryChunked::from_iter_options(col_name.into(), values.into_iter()).into_series()
+            }
+            "UUID" => {
+                let values: Vec<Option<String>> = rows
+                    .iter()
+                    .map(|row| {
+                        row.try_get_unchecked::<sqlx::types::Uuid, _>(col_name)
+                            .ok()
+                            .map(|u| u.to_string())
+                    })
+                    .collect();
+                Series::new(col_name.into(), values)
+            }
+            "TIMESTAMP" => {
+                let values: Vec<Option<i64>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok().map(timestamp_micros))
+                    .collect();
+                cast_series(
+                    Series::new(col_name.into(), values),
+                    DataType::Datetime(TimeUnit::Microseconds, None),
+                )?
+            }
+            "TIMESTAMPTZ" => {
+                // Stored as an instant; read back in UTC
+                let values: Vec<Option<i64>> = rows
+                    .iter()
+                    .map(|row| {
+                        row.try_get_unchecked::<DateTime<Utc>, _>(col_name)
+                            .ok()
+                            .map(|t| timestamp_micros(t.naive_utc()))
+                    })
+                    .collect();
+                cast_series(
+                    Series::new(col_name.into(), values),
+                    DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into())),
+                )?
+            }
+            "DATE" => {
+                let values: Vec<Option<i32>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok().map(epoch_days))
+                    .collect();
+                cast_series(Series::new(col_name.into(), values), DataType::Date)?
+            }
+            "TIME" => {
+                let values: Vec<Option<i64>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok().map(time_nanos))
+                    .collect();
+                cast_series(Series::new(col_name.into(), values), DataType::Time)?
+            }
+            _ if matches!(col_type.kind(), PgTypeKind::Enum(_)) => {
+                // Enum values arrive as their labels
+                let values: Vec<Option<String>> = rows
+                    .iter()
+                    .map(|row| row.try_get_unchecked(col_name).ok())
+                    .collect();
                 Series::new(col_name.into(), values)
             }
             _ => {
@@ -138,11 +710,164 @@ fn rows_to_dataframe(rows: Vec<PgRow>) -> Result<DataFrame> {
     DataFrame::new(columns).map_err(|e| IndustryDbError::PolarsError(e.to_string()))
 }
 
+/// Days between 0001-01-01 and 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Microseconds since the epoch, as Polars stores `Datetime(us)`
+fn timestamp_micros(t: NaiveDateTime) -> i64 {
+    t.and_utc().timestamp_micros()
+}
+
+/// Days since the epoch, as Polars stores `Date`
+fn epoch_days(d: NaiveDate) -> i32 {
+    d.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+/// Nanoseconds since midnight, as Polars stores `Time`
+fn time_nanos(t: NaiveTime) -> i64 {
+    t.num_seconds_from_midnight() as i64 * 1_000_000_000 + t.nanosecond() as i64
+}
+
+/// Type a column's values are decoded as: the base type for a domain
+fn base_type(ty: &PgTypeInfo) -> &PgTypeInfo {
+    match ty.kind() {
+        PgTypeKind::Domain(base) => base_type(base),
+        _ => ty,
+    }
+}
+
+/// List column of a one-dimensional array column with `T` elements
+///
+/// Arrays of more dimensions read as null.
+fn array_series<T>(rows: &[PgRow], col_name: &str, inner: DataType) -> Result<Series>
+where
+    Vec<Option<T>>: for<'r> sqlx::Decode<'r, Postgres> + sqlx::Type<Postgres>,
+    Series: NamedFrom<Vec<Option<T>>, [Option<T>]>,
+{
+    let values: Vec<Option<Series>> = rows
+        .iter()
+        .map(|row| {
+            row.try_get_unchecked::<Vec<Option<T>>, _>(col_name)
+                .ok()
+                .map(|items| Series::new(PlSmallStr::EMPTY, items))
+        })
+        .collect();
+    cast_series(
+        Series::new(col_name.into(), values),
+        DataType::List(Box::new(inner)),
+    )
+}
+
+/// Series cast to its logical type
+fn cast_series(series: Series, dtype: DataType) -> Result<Series> {
+    series
+        .cast(&dtype)
+        .map_err(|e| IndustryDbError::PolarsError(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use industrydb_core::config::DatabaseType;
 
+    #[test]
+    fn test_temporal_physical_values() {
+        let t = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_micro_opt(12, 30, 15, 250)
+            .unwrap();
+        let series = cast_series(
+            Series::new("ts".into(), [Some(timestamp_micros(t)), None]),
+            DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into())),
+        )
+        .unwrap();
+        let physical = series.to_physical_repr();
+        let micros = physical.i64().unwrap();
+        assert_eq!(micros.get(0), Some(1_709_296_215_000_250));
+        assert_eq!(micros.get(1), None);
+
+        assert_eq!(epoch_days(NaiveDate::from_ymd_opt(1970, 1, 2).unwrap()), 1);
+        assert_eq!(
+            epoch_days(NaiveDate::from_ymd_opt(1969, 12, 31).unwrap()),
+            -1
+        );
+        let time = NaiveTime::from_hms_milli_opt(0, 0, 1, 500).unwrap();
+        assert_eq!(time_nanos(time), 1_500_000_000);
+    }
+
+    #[test]
+    fn test_text_parameters_are_untyped() {
+        let ty = <UntypedText as sqlx::Type<Postgres>>::type_info();
+        assert_eq!(ty.oid(), Some(UNKNOWN_OID));
+    }
+
+    #[test]
+    fn test_numeric_parameter() {
+        assert_eq!(numeric("-12.50").unwrap().to_string(), "-12.50");
+        assert!(numeric(&"9".repeat(30)).is_none());
+        assert!(numeric("abc").is_none());
+    }
+
+    #[test]
+    fn test_numeric_from_binary() {
+        let encode =
+            |words: &[i16]| -> Vec<u8> { words.iter().flat_map(|w| w.to_be_bytes()).collect() };
+        let negative = 0x4000;
+        assert_eq!(
+            numeric_from_binary(&encode(&[2, 0, negative, 2, 12, 5000])).unwrap(),
+            "-12.50"
+        );
+        assert_eq!(
+            numeric_from_binary(&encode(&[1, -1, 0, 4, 1])).unwrap(),
+            "0.0001"
+        );
+        assert_eq!(
+            numeric_from_binary(&encode(&[1, 10, 0, 0, 1])).unwrap(),
+            format!("1{}", "0".repeat(40))
+        );
+        assert_eq!(
+            numeric_from_binary(&encode(&[0, 0, 0xC000u16 as i16, 0])).unwrap(),
+            "NaN"
+        );
+        assert!(numeric_from_binary(&[0, 1]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_policy_applies_to_crud() {
+        use industrydb_core::policy::AccessPolicy;
+        use industrydb_core::traits::CrudOperations;
+        use std::collections::HashMap;
+
+        let mut config = ConnectionConfig::postgres(
+            "localhost".to_string(),
+            5432,
+            "test".to_string(),
+            "user".to_string(),
+            "pass".to_string(),
+        );
+        config.policy = Some(AccessPolicy::new(HashMap::from([(
+            "readings".to_string(),
+            vec!["ts".to_string(), "value".to_string()],
+        )])));
+        // The policy is checked before a connection is needed
+        let conn = PostgresConnector {
+            pool: PgPool::connect_lazy(&database_url(&config)).unwrap(),
+            db_type: "postgres".to_string(),
+            config,
+            metrics: QueryMetrics::new(),
+        };
+
+        let denied = |r: Result<usize>| matches!(r, Err(IndustryDbError::AccessDenied(_)));
+        assert!(denied(
+            conn.delete("readings", Some("operator = 'x'"), &[]).await
+        ));
+        let values = HashMap::from([("value".to_string(), "0".to_string())]);
+        assert!(denied(
+            conn.update("readings", &values, Some("operator = 'x'"), &[])
+                .await
+        ));
+    }
+
     #[tokio::test]
     async fn test_connector_creation() {
         let config = ConnectionConfig {
@@ -156,6 +881,12 @@ mod tests {
             path: None,
             trusted_connection: None,
             timeout: None,
+            policy: None,
+            connector: None,
+            sqlite: None,
+            mssql: None,
+            timestamps: None,
+            overflow: None,
             extra: Default::default(),
         };
 
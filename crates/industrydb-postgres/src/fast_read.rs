@@ -0,0 +1,41 @@
+//! Reads through ConnectorX (`connectorx` feature)
+
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use connectorx::get_arrow::get_arrow;
+use connectorx::prelude::{CXQuery, SourceConn};
+use industrydb_core::error::{IndustryDbError, Result};
+use polars::prelude::*;
+use std::io::Cursor;
+
+/// Run `sql` through ConnectorX against the database at `url`
+///
+/// ConnectorX runs its own threads and runtime, so this blocks; call it
+/// from a blocking task.
+pub(crate) fn read_sql(url: &str, sql: &str) -> Result<DataFrame> {
+    let source = SourceConn::try_from(url).map_err(cx_error)?;
+    let destination = get_arrow(&source, None, &[CXQuery::naked(sql)], None).map_err(cx_error)?;
+    to_dataframe(&destination.arrow().map_err(cx_error)?)
+}
+
+/// Arrow batches as one DataFrame, passed through an in-memory IPC file
+///
+/// ConnectorX and Polars use different Arrow implementations; IPC is the
+/// format both read and write without copying column by column.
+fn to_dataframe(batches: &[RecordBatch]) -> Result<DataFrame> {
+    let Some(first) = batches.first() else {
+        return Ok(DataFrame::empty());
+    };
+    let mut buffer = Vec::new();
+    let mut writer = FileWriter::try_new(&mut buffer, &first.schema()).map_err(cx_error)?;
+    for batch in batches {
+        writer.write(batch).map_err(cx_error)?;
+    }
+    writer.finish().map_err(cx_error)?;
+    drop(writer);
+    Ok(IpcReader::new(Cursor::new(buffer)).finish()?)
+}
+
+fn cx_error<E: std::fmt::Display>(err: E) -> IndustryDbError {
+    IndustryDbError::ConnectorXError(err.to_string())
+}
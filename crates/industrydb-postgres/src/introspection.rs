@@ -0,0 +1,96 @@
+//! Catalog queries for PostgreSQL schema introspection
+
+use industrydb_core::schema::{quote_literal, split_qualified};
+
+/// Schema predicate, defaulting to the session's `current_schema()`
+fn schema_predicate(schema: Option<&str>) -> String {
+    schema
+        .map(quote_literal)
+        .unwrap_or_else(|| "current_schema()".to_string())
+}
+
+/// User schemas, excluding the system catalogs
+pub(crate) fn list_schemas_sql() -> String {
+    "SELECT schema_name::text AS schema_name FROM information_schema.schemata \
+     WHERE schema_name NOT IN ('pg_catalog', 'information_schema') \
+     AND schema_name NOT LIKE 'pg_toast%' AND schema_name NOT LIKE 'pg_temp%' \
+     ORDER BY schema_name"
+        .to_string()
+}
+
+/// Base tables in the given schema
+pub(crate) fn list_tables_sql(schema: Option<&str>) -> String {
+    format!(
+        "SELECT table_name::text AS table_name FROM information_schema.tables \
+         WHERE table_schema = {} AND table_type = 'BASE TABLE' ORDER BY table_name",
+        schema_predicate(schema)
+    )
+}
+
+/// Whether a table exists, defaulting to the current schema
+pub(crate) fn table_exists_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    format!(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.tables \
+         WHERE table_schema = {} AND table_name = {}) AS table_exists",
+        schema_predicate(schema),
+        quote_literal(name)
+    )
+}
+
+/// Column metadata from `information_schema.columns`
+pub(crate) fn describe_table_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    format!(
+        "SELECT column_name::text AS column_name, data_type::text AS data_type, \
+         (is_nullable = 'YES') AS is_nullable, column_default::text AS column_default \
+         FROM information_schema.columns WHERE table_schema = {} AND table_name = {} \
+         ORDER BY ordinal_position",
+        schema_predicate(schema),
+        quote_literal(name)
+    )
+}
+
+/// Primary key columns via `pg_index`, numbered from 1 in key order
+pub(crate) fn primary_keys_sql(table: &str) -> String {
+    format!(
+        "SELECT a.attname::text AS column_name, k.ord::int4 AS ordinal_position \
+         FROM pg_index i \
+         CROSS JOIN LATERAL unnest(i.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord) \
+         JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum \
+         WHERE i.indrelid = {}::regclass AND i.indisprimary \
+         ORDER BY ordinal_position",
+        quote_literal(table)
+    )
+}
+
+/// Index columns via `pg_index`, expression columns are skipped
+pub(crate) fn indexes_sql(table: &str) -> String {
+    format!(
+        "SELECT ic.relname::text AS index_name, a.attname::text AS column_name, \
+         i.indisunique AS is_unique, k.ord::int4 AS ordinal_position \
+         FROM pg_index i \
+         JOIN pg_class ic ON ic.oid = i.indexrelid \
+         CROSS JOIN LATERAL unnest(i.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord) \
+         JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum \
+         WHERE i.indrelid = {}::regclass \
+         ORDER BY index_name, ordinal_position",
+        quote_literal(table)
+    )
+}
+
+/// Foreign key columns via `pg_constraint`
+pub(crate) fn foreign_keys_sql(table: &str) -> String {
+    format!(
+        "SELECT c.conname::text AS constraint_name, a.attname::text AS column_name, \
+         c.confrelid::regclass::text AS referenced_table, \
+         fa.attname::text AS referenced_column \
+         FROM pg_constraint c \
+         CROSS JOIN LATERAL unnest(c.conkey, c.confkey) WITH ORDINALITY AS k(attnum, fattnum, ord) \
+         JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum \
+         JOIN pg_attribute fa ON fa.attrelid = c.confrelid AND fa.attnum = k.fattnum \
+         WHERE c.contype = 'f' AND c.conrelid = {}::regclass \
+         ORDER BY constraint_name, k.ord",
+        quote_literal(table)
+    )
+}
@@ -1,9 +1,17 @@
 //! PostgreSQL connector implementation for IndustryDB
 
+mod cdc;
 mod connector;
+#[cfg(feature = "connectorx")]
+mod fast_read;
+mod introspection;
+mod notify;
 mod operations;
+mod procedure;
 
+pub use cdc::{Change, ChangeBatch, ChangeFeed, ChangeKind, DEFAULT_POLL_CHANGES};
 pub use connector::PostgresConnector;
+pub use notify::{Notification, Subscription};
 
 // Re-export for convenience
 pub use industrydb_core::traits::{CrudOperations, DatabaseConnector};
@@ -0,0 +1,116 @@
+//! LISTEN/NOTIFY subscriptions
+//!
+//! A [`Subscription`] holds a dedicated connection listening on one or more
+//! channels, so applications react to notifications sent by triggers
+//! (`PERFORM pg_notify('alarms', NEW.tag)`) instead of polling tables.
+//! Channel names are case-sensitive, as with `pg_notify`; an unquoted name
+//! in `NOTIFY Alarms` is lower-cased by the server.
+//!
+//! When the connection drops, the next receive reconnects and listens on
+//! the same channels again. Notifications sent in between are lost, as
+//! PostgreSQL only delivers them to sessions listening at the time.
+
+use futures_util::{Stream, StreamExt};
+use industrydb_core::{
+    config::ConnectionConfig,
+    error::{IndustryDbError, Result},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgListener, PgNotification};
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::connector::database_url;
+
+/// One notification received on a channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Channel it was sent on
+    pub channel: String,
+    /// Payload; empty when none was given
+    pub payload: String,
+    /// Backend process id of the sending session
+    pub process_id: u32,
+}
+
+impl From<PgNotification> for Notification {
+    fn from(n: PgNotification) -> Self {
+        Self {
+            channel: n.channel().to_string(),
+            payload: n.payload().to_string(),
+            process_id: n.process_id(),
+        }
+    }
+}
+
+/// Notifications of the channels listened on
+pub struct Subscription {
+    listener: PgListener,
+}
+
+impl Subscription {
+    /// Open a connection for `config` and listen on `channels`
+    pub async fn connect(config: &ConnectionConfig, channels: &[&str]) -> Result<Self> {
+        let listener = PgListener::connect(&database_url(config))
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+        Self::listening(listener, channels).await
+    }
+
+    /// Take a connection from `pool` and listen on `channels`
+    pub async fn with_pool(pool: &PgPool, channels: &[&str]) -> Result<Self> {
+        let listener = PgListener::connect_with(pool)
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+        Self::listening(listener, channels).await
+    }
+
+    async fn listening(listener: PgListener, channels: &[&str]) -> Result<Self> {
+        let mut subscription = Self { listener };
+        for channel in channels {
+            subscription.listen(channel).await?;
+        }
+        Ok(subscription)
+    }
+
+    /// Also listen on `channel`
+    pub async fn listen(&mut self, channel: &str) -> Result<()> {
+        self.listener
+            .listen(channel)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))
+    }
+
+    /// Stop listening on `channel`
+    pub async fn unlisten(&mut self, channel: &str) -> Result<()> {
+        self.listener
+            .unlisten(channel)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))
+    }
+
+    /// Wait for the next notification
+    pub async fn recv(&mut self) -> Result<Notification> {
+        self.listener
+            .recv()
+            .await
+            .map(Notification::from)
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))
+    }
+
+    /// Wait up to `timeout` for the next notification
+    pub async fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Notification>> {
+        match tokio::time::timeout(timeout, self.recv()).await {
+            Ok(notification) => notification.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Notifications as a stream, ending only with the subscription
+    pub fn into_stream(self) -> impl Stream<Item = Result<Notification>> + Unpin {
+        self.listener.into_stream().map(|n| {
+            n.map(Notification::from)
+                .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))
+        })
+    }
+}
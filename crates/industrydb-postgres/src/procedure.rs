@@ -0,0 +1,94 @@
+//! Procedure and function calls for PostgreSQL
+//!
+//! Procedures run with `CALL`, which returns their OUT and INOUT parameters
+//! as one row; OUT arguments are passed as NULL. Functions run with
+//! `SELECT * FROM`, where OUT parameters are not passed and come back as
+//! columns of the result.
+
+use industrydb_core::{
+    dialect::{Dialect, PostgresDialect},
+    error::Result,
+    params::Value,
+    procedure::{function_call_sql, typed, validate_args, ParamMode, ProcedureArg},
+};
+
+/// Kind of the routine `name` (`p` procedure, `f` function, ...), looked up
+/// in its schema or on the search path
+pub(crate) fn routine_kind_sql(name: &str) -> (String, Vec<Value>) {
+    let sql = "SELECT p.prokind::text AS kind FROM pg_proc p \
+               JOIN pg_namespace n ON n.oid = p.pronamespace \
+               WHERE p.proname = $1 AND ($2::text IS NULL AND pg_function_is_visible(p.oid) \
+               OR n.nspname = $2::text) LIMIT 1"
+        .to_string();
+    let (schema, routine) = match name.rsplit_once('.') {
+        Some((schema, routine)) => (Value::from(schema), routine),
+        None => (Value::Null, name),
+    };
+    (sql, vec![Value::from(routine), schema])
+}
+
+/// `CALL` of a procedure, or `SELECT * FROM` of a function, with the values
+/// bound to its placeholders
+pub(crate) fn call_sql(
+    name: &str,
+    args: &[ProcedureArg],
+    is_procedure: bool,
+) -> Result<(String, Vec<Value>)> {
+    let dialect = &PostgresDialect;
+    if !is_procedure {
+        return function_call_sql(dialect, name, args);
+    }
+
+    validate_args(args)?;
+    let mut params = Vec::new();
+    let mut passed = Vec::new();
+    for arg in args {
+        let value = match arg.mode {
+            ParamMode::Out => typed("NULL".to_string(), arg),
+            ParamMode::In | ParamMode::InOut => {
+                params.push(arg.value.clone());
+                typed(dialect.placeholder(params.len()), arg)
+            }
+        };
+        passed.push(match &arg.name {
+            Some(name) => format!("{} => {}", dialect.quote_identifier(name), value),
+            None => value,
+        });
+    }
+    let sql = format!("CALL {}({})", dialect.identifier(name)?, passed.join(", "));
+    Ok((sql, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Vec<ProcedureArg> {
+        vec![
+            ProcedureArg::input(3).with_type("integer"),
+            ProcedureArg::output("total").with_type("numeric"),
+            ProcedureArg::in_out("note", "x"),
+        ]
+    }
+
+    #[test]
+    fn test_procedures_pass_outputs_as_null() {
+        let (sql, params) = call_sql("plant.line_stats", &args(), true).unwrap();
+        assert_eq!(
+            sql,
+            "CALL \"plant\".\"line_stats\"(CAST($1 AS integer), \
+             \"total\" => CAST(NULL AS numeric), \"note\" => $2)"
+        );
+        assert_eq!(params, vec![Value::Int(3), Value::from("x")]);
+    }
+
+    #[test]
+    fn test_functions_leave_out_outputs() {
+        let (sql, params) = call_sql("line_stats", &args(), false).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM \"line_stats\"(CAST($1 AS integer), \"note\" => $2)"
+        );
+        assert_eq!(params.len(), 2);
+    }
+}
@@ -0,0 +1,135 @@
+//! Python bindings for backfill orchestration
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+use crate::connection::{to_python, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::backfill::{self, BackfillConfig, BackfillControl, BackfillProgress};
+use industrydb_core::time::{parse_interval, parse_timestamp};
+
+/// Pause/resume/cancel handle for a running backfill
+#[pyclass(name = "BackfillControl")]
+#[derive(Clone, Default)]
+pub struct PyBackfillControl {
+    pub(crate) inner: BackfillControl,
+}
+
+#[pymethods]
+impl PyBackfillControl {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause after the current chunk
+    fn pause(&self) {
+        self.inner.pause()
+    }
+
+    /// Resume a paused backfill
+    fn resume(&self) {
+        self.inner.resume()
+    }
+
+    /// Stop after the current chunk, keeping the checkpoint
+    fn cancel(&self) {
+        self.inner.cancel()
+    }
+
+    #[getter]
+    fn is_paused(&self) -> bool {
+        self.inner.is_paused()
+    }
+
+    #[getter]
+    fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+}
+
+/// Copy a historical time range from one connection to another in chunks
+///
+/// Runs without holding the GIL, so another thread can pause or cancel it
+/// through `control`. `on_progress` is called with a progress dict after
+/// every chunk.
+#[pyfunction]
+#[pyo3(signature = (
+    source, target, table, time_column, start, end, chunk_interval,
+    max_rows_per_minute=None, target_table=None, checkpoint=None, control=None, on_progress=None,
+    batching=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn backfill(
+    py: Python,
+    source: PyRef<'_, PyConnection>,
+    target: PyRef<'_, PyConnection>,
+    table: &str,
+    time_column: &str,
+    start: &str,
+    end: &str,
+    chunk_interval: &str,
+    max_rows_per_minute: Option<u64>,
+    target_table: Option<String>,
+    checkpoint: Option<PathBuf>,
+    control: Option<PyBackfillControl>,
+    on_progress: Option<PyObject>,
+    batching: Option<&Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    let mut config = BackfillConfig::new(
+        table,
+        time_column,
+        parse_timestamp(start).map_err(to_py_err)?,
+        parse_timestamp(end).map_err(to_py_err)?,
+        parse_interval(chunk_interval).map_err(to_py_err)?,
+    );
+    config.max_rows_per_minute = max_rows_per_minute;
+    config.checkpoint_path = checkpoint;
+    config.batching = batching
+        .map(|b| {
+            pythonize::depythonize_bound(b.clone().into_any()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid batching settings: {}",
+                    e
+                ))
+            })
+        })
+        .transpose()?;
+    if let Some(target_table) = target_table {
+        config.target_table = target_table;
+    }
+
+    let control = control.map(|c| c.inner).unwrap_or_default();
+    let source_conn = source.connector()?;
+    let target_conn = target.connector()?;
+    let runtime = source.runtime.clone();
+
+    let callback = on_progress.map(|callback| {
+        move |progress: &BackfillProgress| {
+            Python::with_gil(|py| {
+                let result = to_python(py, progress).and_then(|p| callback.call1(py, (p,)));
+                if let Err(e) = result {
+                    e.print(py);
+                }
+            })
+        }
+    });
+    let callback_ref = callback
+        .as_ref()
+        .map(|c| c as &(dyn Fn(&BackfillProgress) + Send + Sync));
+
+    let progress = py
+        .allow_threads(|| {
+            runtime.block_on(backfill::backfill(
+                source_conn,
+                target_conn,
+                &config,
+                &control,
+                callback_ref,
+            ))
+        })
+        .map_err(to_py_err)?;
+
+    to_python(py, &progress)
+}
@@ -0,0 +1,111 @@
+//! Python bindings for PostgreSQL change data capture
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+use crate::connection::{dataframe_to_py_dict, to_python};
+use crate::errors::to_py_err;
+use industrydb_core::config::ConnectionConfig;
+use industrydb_core::error::IndustryDbError;
+use industrydb_postgres::{ChangeFeed, DEFAULT_POLL_CHANGES};
+
+/// Row changes read from a PostgreSQL logical replication slot
+#[pyclass(name = "ChangeFeed")]
+pub struct PyChangeFeed {
+    inner: Option<ChangeFeed>,
+    runtime: Arc<Runtime>,
+}
+
+impl PyChangeFeed {
+    pub(crate) fn connect(
+        runtime: Arc<Runtime>,
+        config: &ConnectionConfig,
+        slot: &str,
+        publication: &str,
+        create_slot: bool,
+    ) -> PyResult<Self> {
+        let inner = runtime
+            .block_on(async {
+                let feed = ChangeFeed::connect(config, slot, publication).await?;
+                if create_slot {
+                    feed.create_slot().await?;
+                }
+                Ok::<_, IndustryDbError>(feed)
+            })
+            .map_err(to_py_err)?;
+        Ok(Self {
+            inner: Some(inner),
+            runtime,
+        })
+    }
+
+    fn feed(&mut self) -> PyResult<&mut ChangeFeed> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| to_py_err(IndustryDbError::ConnectionClosed))
+    }
+}
+
+#[pymethods]
+impl PyChangeFeed {
+    /// Acknowledge the changes returned so far and read the next ones as
+    /// a list of dicts
+    #[pyo3(signature = (max_changes=DEFAULT_POLL_CHANGES))]
+    fn poll(&mut self, py: Python, max_changes: usize) -> PyResult<PyObject> {
+        let runtime = self.runtime.clone();
+        let feed = self.feed()?;
+        let changes = py
+            .allow_threads(|| runtime.block_on(feed.poll(max_changes)))
+            .map_err(to_py_err)?;
+        to_python(py, &changes)
+    }
+
+    /// Like `poll`, with the changes as one DataFrame per table
+    #[pyo3(signature = (max_changes=DEFAULT_POLL_CHANGES))]
+    fn poll_frames(&mut self, py: Python, max_changes: usize) -> PyResult<Py<PyDict>> {
+        let runtime = self.runtime.clone();
+        let feed = self.feed()?;
+        let batches = py
+            .allow_threads(|| runtime.block_on(feed.poll_frames(max_changes)))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        for batch in batches {
+            dict.set_item(batch.table, dataframe_to_py_dict(py, &batch.frame)?)?;
+        }
+        Ok(dict.unbind())
+    }
+
+    /// Drop the replication slot; the feed cannot be read afterwards
+    fn drop_slot(&mut self, py: Python) -> PyResult<()> {
+        let runtime = self.runtime.clone();
+        let feed = self.feed()?;
+        py.allow_threads(|| runtime.block_on(feed.drop_slot()))
+            .map_err(to_py_err)?;
+        self.inner = None;
+        Ok(())
+    }
+
+    /// Release the connection, keeping the slot and its position
+    fn close(&mut self) {
+        self.inner = None;
+    }
+
+    /// Context manager entry
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Context manager exit
+    fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
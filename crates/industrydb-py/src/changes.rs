@@ -0,0 +1,124 @@
+//! Python bindings for change capture by polling
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::connection::{dataframe_to_py_dict, value_to_py, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::changes::{ChangePoller, ChangedRows, PolledTable, DEFAULT_POLL_ROWS};
+use industrydb_core::time::parse_interval;
+
+/// Rows of a set of tables changed since the last poll, found through a
+/// watermark column
+#[pyclass(name = "ChangePoller")]
+pub struct PyChangePoller {
+    conn: Py<PyConnection>,
+    inner: ChangePoller,
+}
+
+impl PyChangePoller {
+    fn frames(py: Python, changes: Vec<ChangedRows>) -> PyResult<Py<PyDict>> {
+        let dict = PyDict::new_bound(py);
+        for changed in changes {
+            dict.set_item(changed.table, dataframe_to_py_dict(py, &changed.frame)?)?;
+        }
+        Ok(dict.unbind())
+    }
+}
+
+#[pymethods]
+impl PyChangePoller {
+    /// Create a poller reading through `conn`; `tables` maps each table to
+    /// its watermark column
+    #[new]
+    #[pyo3(signature = (conn, tables, state=None, chunk_size=DEFAULT_POLL_ROWS))]
+    fn new(
+        conn: Py<PyConnection>,
+        tables: HashMap<String, String>,
+        state: Option<PathBuf>,
+        chunk_size: usize,
+    ) -> PyResult<Self> {
+        let mut tables: Vec<PolledTable> = tables
+            .iter()
+            .map(|(table, column)| PolledTable::new(table, column))
+            .collect();
+        // Poll in a stable order whatever the dict's
+        tables.sort_by(|a, b| a.table.cmp(&b.table));
+        let inner = ChangePoller::new(tables, state)
+            .and_then(|poller| poller.with_chunk_rows(chunk_size))
+            .map_err(to_py_err)?;
+        Ok(Self { conn, inner })
+    }
+
+    /// Acknowledge the rows returned so far and read the next ones, as a
+    /// dict of table to frame dict
+    fn poll(&mut self, py: Python) -> PyResult<Py<PyDict>> {
+        let conn = self.conn.borrow(py);
+        let connector = conn.connector()?;
+        let runtime = conn.runtime.clone();
+        let inner = &mut self.inner;
+        let changes = py
+            .allow_threads(|| runtime.block_on(inner.poll(connector)))
+            .map_err(to_py_err)?;
+        drop(conn);
+        Self::frames(py, changes)
+    }
+
+    /// Poll every `interval` until some rows changed; an empty dict when
+    /// `timeout` seconds pass first
+    #[pyo3(signature = (interval="5s", timeout=None))]
+    fn wait(&mut self, py: Python, interval: &str, timeout: Option<f64>) -> PyResult<Py<PyDict>> {
+        let step = parse_interval(interval)
+            .map_err(to_py_err)?
+            .to_std()
+            .map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Poll interval must not be negative: {}",
+                    interval
+                ))
+            })?;
+        let deadline = timeout
+            .map(|t| {
+                Duration::try_from_secs_f64(t).map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "timeout must be a non-negative number",
+                    )
+                })
+            })
+            .transpose()?
+            .map(|t| Instant::now() + t);
+
+        loop {
+            let changes = self.poll(py)?;
+            if !changes.bind(py).is_empty() {
+                return Ok(changes);
+            }
+            let now = Instant::now();
+            let pause = match deadline {
+                Some(deadline) if now >= deadline => return Ok(changes),
+                Some(deadline) => step.min(deadline - now),
+                None => step,
+            };
+            py.allow_threads(|| std::thread::sleep(pause));
+            // Let Ctrl-C interrupt a wait with no timeout
+            py.check_signals()?;
+        }
+    }
+
+    /// Advance the watermarks past the rows returned by the last poll and
+    /// persist them
+    fn acknowledge(&mut self) -> PyResult<()> {
+        self.inner.acknowledge().map_err(to_py_err)
+    }
+
+    /// Watermark of the last acknowledged row of `table`
+    fn watermark(&self, py: Python, table: &str) -> PyObject {
+        match self.inner.watermark(table) {
+            Some(value) => value_to_py(py, value),
+            None => py.None(),
+        }
+    }
+}
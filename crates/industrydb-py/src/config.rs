@@ -44,6 +44,12 @@ impl PyDatabaseConfig {
             path,
             trusted_connection: None,
             timeout: None,
+            policy: None,
+            connector: None,
+            sqlite: None,
+            mssql: None,
+            timestamps: None,
+            overflow: None,
             extra: HashMap::new(),
         };
 
@@ -61,6 +67,51 @@ impl PyDatabaseConfig {
             }
         }
 
+        if let Some(policy) = config.extra.remove("policy") {
+            config.policy = Some(serde_json::from_value(policy).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid policy: {}", e))
+            })?);
+        }
+
+        if let Some(connector) = config.extra.remove("connector") {
+            config.connector = Some(serde_json::from_value(connector).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid connector: {}", e))
+            })?);
+        }
+
+        if let Some(sqlite) = config.extra.remove("sqlite") {
+            config.sqlite = Some(serde_json::from_value(sqlite).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid SQLite options: {}",
+                    e
+                ))
+            })?);
+        }
+
+        if let Some(mssql) = config.extra.remove("mssql") {
+            config.mssql = Some(serde_json::from_value(mssql).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid SQL Server options: {}",
+                    e
+                ))
+            })?);
+        }
+
+        if let Some(overflow) = config.extra.remove("overflow") {
+            config.overflow = Some(serde_json::from_value(overflow).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid overflow: {}", e))
+            })?);
+        }
+
+        if let Some(timestamps) = config.extra.remove("timestamps") {
+            config.timestamps = Some(serde_json::from_value(timestamps).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid timestamps: {}",
+                    e
+                ))
+            })?);
+        }
+
         config.validate().map_err(to_py_err)?;
 
         Ok(PyDatabaseConfig { inner: config })
@@ -1,23 +1,63 @@
 //! Python connection bindings
 
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBool, PyBytes, PyDict, PyList, PyLong};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 use crate::config::PyDatabaseConfig;
 use crate::errors::to_py_err;
+use crate::procedure::{apply_types, procedure_args};
+use crate::quality::parse_checks;
+use crate::query::{order_spec, PyQuery};
+use crate::reader::PyReader;
+use crate::result::PyQueryResult;
+use crate::storage::open_target;
+use crate::synth::parse_spec;
+use industrydb_cache::{CacheConfig, QueryCache};
 use industrydb_core::{
+    audit::AuditColumns,
     config::{ConnectionConfig, DatabaseType},
+    conform::{Coercion, CoercionPolicy, InsertOptions},
+    copy::{CopyFormat, CsvCopyOptions},
+    ddl, decimal,
+    dialect::{Dialect, SelectOptions},
+    diff::DatabaseSchema,
+    factory::ConnectionFactory,
+    hierarchy::Hierarchy,
+    locks::{TableLocks, TableWriteGuard},
+    params::{bind_named, Value},
+    partition::read_partitioned,
+    pivot::{unpivot, Pivoted},
+    quality::check_source,
+    query::{order_by_sql, Aggregate},
+    retention::RetentionPolicy,
+    schema::coerce_columns,
+    synth,
+    tags::{TagInfo, TagRegistry, DEFAULT_TAG_TABLE},
+    time::{parse_interval, parse_timestamp},
+    timescale::{timescale_version, ContinuousAggregate},
+    timeseries::{FillMethod, TimeBuckets, TimeRange},
     traits::CrudOperations,
+    value_at::{AtMode, ValueAt},
+};
+use industrydb_migrate::Migrator;
+use industrydb_storage::{
+    export_copy, export_csv, export_ipc, export_parquet, export_query, import_copy, import_csv,
+    import_objects, parse_compression, parse_ipc_compression, ExportFormat, IpcOptions,
+    SnapshotStore,
 };
 
 /// Python-exposed database connection
 #[pyclass(name = "PyConnection")]
 pub struct PyConnection {
-    inner: Option<Box<dyn CrudOperations>>,
-    runtime: Arc<Runtime>,
+    inner: Option<Arc<dyn CrudOperations>>,
+    pub(crate) runtime: Arc<Runtime>,
+    cache: Option<QueryCache>,
+    config: ConnectionConfig,
+    write_locks: Option<TableLocks>,
 }
 
 #[pymethods]
@@ -37,8 +77,11 @@ impl PyConnection {
             .map_err(to_py_err)?;
 
         Ok(PyConnection {
-            inner: Some(connector),
+            inner: Some(Arc::from(connector)),
             runtime,
+            cache: None,
+            config: config.inner().clone(),
+            write_locks: None,
         })
     }
 
@@ -64,15 +107,22 @@ impl PyConnection {
             .map_err(to_py_err)?;
 
         Ok(PyConnection {
-            inner: Some(connector),
+            inner: Some(Arc::from(connector)),
             runtime,
+            cache: None,
+            config,
+            write_locks: None,
         })
     }
 
     /// Close the connection
     fn close(&mut self) -> PyResult<()> {
         if let Some(mut conn) = self.inner.take() {
-            self.runtime.block_on(conn.close()).map_err(to_py_err)?;
+            // A running buffered writer holds the connector too; it is
+            // closed when the writer drops it
+            if let Some(conn) = Arc::get_mut(&mut conn) {
+                self.runtime.block_on(conn.close()).map_err(to_py_err)?;
+            }
         }
         Ok(())
     }
@@ -83,317 +133,2389 @@ impl PyConnection {
     }
 
     /// Execute SQL query
-    #[pyo3(signature = (sql, params=None))]
+    #[pyo3(signature = (sql, params=None, schema=None))]
     fn execute(
         &self,
         py: Python,
         sql: String,
-        params: Option<&Bound<'_, PyList>>,
+        params: Option<&Bound<'_, PyAny>>,
+        schema: Option<&Bound<'_, PyDict>>,
     ) -> PyResult<Py<PyDict>> {
         let conn = self.inner.as_ref().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
         })?;
 
-        // TODO: Implement parameter binding
-        let _ = params;
-
-        let df = self
+        let hints = schema.map(py_to_dtypes).transpose()?;
+        let (sql, params) = resolve_params(&sql, params, conn.dialect())?;
+        let mut df = self
             .runtime
-            .block_on(conn.execute(&sql))
+            .block_on(conn.execute_with_params(&sql, &params))
             .map_err(to_py_err)?;
+        if let Some(hints) = hints {
+            df = coerce_columns(df, &hints).map_err(to_py_err)?;
+        }
         dataframe_to_py_dict(py, &df)
     }
 
-    /// Insert data into table
-    #[pyo3(signature = (table, data, **_kwargs))]
-    fn insert(
+    /// Execute SQL returning every result set, e.g. of a SQL Server batch or
+    /// stored procedure
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_result_sets(
         &self,
-        table: String,
-        data: &Bound<'_, PyDict>,
-        _kwargs: Option<&Bound<'_, PyDict>>,
-    ) -> PyResult<usize> {
-        let conn = self.inner.as_ref().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
-        })?;
+        py: Python,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyList>> {
+        let conn = self.connector()?;
+        let (sql, params) = resolve_params(sql, params, conn.dialect())?;
+        let sets = self
+            .runtime
+            .block_on(conn.execute_result_sets(&sql, &params))
+            .map_err(to_py_err)?;
+        let list = PyList::empty_bound(py);
+        for df in &sets {
+            list.append(dataframe_to_py_dict(py, df)?)?;
+        }
+        Ok(list.unbind())
+    }
 
-        let df = py_dict_to_dataframe(data)?;
-        let rows = self
+    /// Execute a large query through ConnectorX (`connectorx` feature)
+    fn fast_read(&self, py: Python, sql: &str) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
             .runtime
-            .block_on(conn.insert(&table, df))
+            .block_on(conn.fast_read(sql))
             .map_err(to_py_err)?;
-        Ok(rows)
+        dataframe_to_py_dict(py, &df)
     }
 
-    /// Select data from table
-    #[allow(clippy::too_many_arguments)]
-    #[pyo3(signature = (table, columns=None, where_clause=None, params=None, limit=None, **_kwargs))]
-    fn select(
+    /// Subscribe to PostgreSQL notifications on `channels`
+    ///
+    /// The subscription uses its own connection, so it keeps receiving
+    /// while this connection runs queries.
+    fn listen(&self, py: Python, channels: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let channels: Vec<String> = match channels.extract::<String>() {
+            Ok(channel) => vec![channel],
+            Err(_) => channels.extract()?,
+        };
+        if self.config.db_type != DatabaseType::Postgres || self.config.connector.is_some() {
+            return Err(to_py_err(
+                industrydb_core::error::IndustryDbError::config_error(
+                    "LISTEN/NOTIFY subscriptions need a PostgreSQL connection",
+                ),
+            ));
+        }
+
+        #[cfg(feature = "postgres")]
+        {
+            let subscription = crate::notify::PySubscription::connect(
+                self.runtime.clone(),
+                &self.config,
+                &channels,
+            )?;
+            Ok(Py::new(py, subscription)?.into_any())
+        }
+        #[cfg(not(feature = "postgres"))]
+        {
+            let _ = (py, channels);
+            Err(to_py_err(
+                industrydb_core::error::IndustryDbError::config_error(
+                    "The postgres connector is not included in this build of industrydb; \
+             install it with `pip install industrydb[postgres]`",
+                ),
+            ))
+        }
+    }
+
+    /// Read row changes from a PostgreSQL logical replication slot
+    ///
+    /// The feed uses its own connection, like `listen`.
+    #[pyo3(signature = (slot, publication, create_slot=true))]
+    fn change_feed(
         &self,
         py: Python,
-        table: String,
-        columns: Option<Vec<String>>,
-        where_clause: Option<String>,
-        params: Option<&Bound<'_, PyList>>,
-        limit: Option<usize>,
-        _kwargs: Option<&Bound<'_, PyDict>>,
-    ) -> PyResult<Py<PyDict>> {
-        let conn = self.inner.as_ref().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
-        })?;
+        slot: &str,
+        publication: &str,
+        create_slot: bool,
+    ) -> PyResult<PyObject> {
+        if self.config.db_type != DatabaseType::Postgres || self.config.connector.is_some() {
+            return Err(to_py_err(
+                industrydb_core::error::IndustryDbError::config_error(
+                    "Change feeds need a PostgreSQL connection",
+                ),
+            ));
+        }
 
-        let _ = params;
+        #[cfg(feature = "postgres")]
+        {
+            let feed = crate::cdc::PyChangeFeed::connect(
+                self.runtime.clone(),
+                &self.config,
+                slot,
+                publication,
+                create_slot,
+            )?;
+            Ok(Py::new(py, feed)?.into_any())
+        }
+        #[cfg(not(feature = "postgres"))]
+        {
+            let _ = (py, slot, publication, create_slot);
+            Err(to_py_err(
+                industrydb_core::error::IndustryDbError::config_error(
+                    "The postgres connector is not included in this build of industrydb; \
+             install it with `pip install industrydb[postgres]`",
+                ),
+            ))
+        }
+    }
 
+    /// Call a function in the FROM clause and return its rows
+    #[pyo3(signature = (name, args=None, types=None))]
+    fn call_function(
+        &self,
+        py: Python,
+        name: &str,
+        args: Option<&Bound<'_, PyAny>>,
+        types: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let mut args = procedure_args(args)?;
+        apply_types(&mut args, types)?;
         let df = self
             .runtime
-            .block_on(conn.select(&table, columns.as_deref(), where_clause.as_deref(), limit))
+            .block_on(conn.call_function(name, &args))
             .map_err(to_py_err)?;
-
         dataframe_to_py_dict(py, &df)
     }
 
-    /// Update rows in table
-    #[pyo3(signature = (table, values, where_clause=None, params=None, **_kwargs))]
-    fn update(
+    /// Call a stored procedure
+    ///
+    /// Returns a dict with `result_sets`, `outputs` (by name) and
+    /// `return_value`.
+    #[pyo3(signature = (name, args=None))]
+    fn call_procedure(
         &self,
-        table: String,
-        values: &Bound<'_, PyDict>,
-        where_clause: Option<String>,
-        params: Option<&Bound<'_, PyList>>,
-        _kwargs: Option<&Bound<'_, PyDict>>,
-    ) -> PyResult<usize> {
-        let conn = self.inner.as_ref().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
-        })?;
-
-        let mut values_map = HashMap::new();
-        for (key, value) in values.iter() {
-            let key_str: String = key.extract()?;
-            let value_str: String = value.str()?.extract()?;
-            values_map.insert(key_str, value_str);
-        }
-
-        let _ = params;
-
-        let rows = self
+        py: Python,
+        name: &str,
+        args: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let args = procedure_args(args)?;
+        let result = self
             .runtime
-            .block_on(conn.update(&table, &values_map, where_clause.as_deref()))
+            .block_on(conn.call_procedure(name, &args))
             .map_err(to_py_err)?;
 
-        Ok(rows)
+        let sets = PyList::empty_bound(py);
+        for df in &result.result_sets {
+            sets.append(dataframe_to_py_dict(py, df)?)?;
+        }
+        let outputs = PyDict::new_bound(py);
+        for (name, value) in &result.outputs {
+            outputs.set_item(name, value_to_py(py, value))?;
+        }
+        let dict = PyDict::new_bound(py);
+        dict.set_item("result_sets", sets)?;
+        dict.set_item("outputs", outputs)?;
+        dict.set_item(
+            "return_value",
+            result
+                .return_value
+                .as_ref()
+                .map_or_else(|| py.None(), |v| value_to_py(py, v)),
+        )?;
+        Ok(dict.unbind())
     }
 
-    /// Delete rows from table
-    #[pyo3(signature = (table, where_clause=None, params=None, **_kwargs))]
-    fn delete(
-        &self,
-        table: String,
-        where_clause: Option<String>,
-        params: Option<&Bound<'_, PyList>>,
-        _kwargs: Option<&Bound<'_, PyDict>>,
-    ) -> PyResult<usize> {
-        let conn = self.inner.as_ref().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+    /// Cache query results run through `execute_cached`
+    #[pyo3(signature = (redis_url=None, ttl=60.0, prefix="industrydb", max_entries=1024, max_bytes=None))]
+    fn enable_cache(
+        &mut self,
+        redis_url: Option<&str>,
+        ttl: f64,
+        prefix: &str,
+        max_entries: usize,
+        max_bytes: Option<usize>,
+    ) -> PyResult<()> {
+        let ttl = Duration::try_from_secs_f64(ttl).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>("ttl must be a non-negative number")
         })?;
+        let config = CacheConfig {
+            ttl,
+            prefix: prefix.to_string(),
+            max_entries,
+            max_bytes,
+            ..Default::default()
+        };
+        self.cache = Some(match redis_url {
+            None => QueryCache::memory(config),
+            #[cfg(feature = "redis")]
+            Some(url) => self
+                .runtime
+                .block_on(QueryCache::redis(url, config))
+                .map_err(to_py_err)?,
+            #[cfg(not(feature = "redis"))]
+            Some(_) => {
+                return Err(to_py_err(
+                    industrydb_core::error::IndustryDbError::config_error(
+                        "Redis caching is not included in this build of industrydb; \
+                     install it with `pip install industrydb[all]`",
+                    ),
+                ))
+            }
+        });
+        Ok(())
+    }
 
-        let _ = params;
-
-        let rows = self
+    /// Execute a query, reusing a cached result when one is fresh
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_cached(
+        &self,
+        py: Python,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let cache = self.cache.as_ref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "Caching is not enabled; call enable_cache() first",
+            )
+        })?;
+        let (sql, params) = resolve_params(sql, params, conn.dialect())?;
+        let df = self
             .runtime
-            .block_on(conn.delete(&table, where_clause.as_deref()))
+            .block_on(cache.fetch(conn, &sql, &params))
             .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
 
-        Ok(rows)
+    /// Make cached results reading `table` stale, or every cached result
+    #[pyo3(signature = (table=None))]
+    fn invalidate_cache(&self, table: Option<&str>) -> PyResult<()> {
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        let invalidated = match table {
+            Some(table) => self.runtime.block_on(cache.invalidate(table)),
+            None => self.runtime.block_on(cache.invalidate_all()),
+        };
+        invalidated.map_err(to_py_err)
     }
 
-    /// Context manager entry
-    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
-        slf
+    /// Serialize writes to the same table within this process
+    ///
+    /// Without a scope, every connection to the same database shares the
+    /// locks; connections enabling the same named scope share them instead.
+    #[pyo3(signature = (scope=None))]
+    fn enable_write_locks(&mut self, scope: Option<&str>) {
+        self.write_locks = Some(match scope {
+            Some(scope) => TableLocks::new(scope),
+            None => TableLocks::for_database(&self.config),
+        });
     }
 
-    /// Context manager exit
-    fn __exit__(
-        &mut self,
-        _exc_type: Option<&Bound<'_, PyAny>>,
-        _exc_value: Option<&Bound<'_, PyAny>>,
-        _traceback: Option<&Bound<'_, PyAny>>,
-    ) -> PyResult<bool> {
-        self.close()?;
-        Ok(false)
+    /// Stop taking write locks on this connection
+    fn disable_write_locks(&mut self) {
+        self.write_locks = None;
     }
 
-    fn __repr__(&self) -> String {
-        if self.is_closed() {
-            "Connection(closed)".to_string()
-        } else {
-            "Connection(active)".to_string()
+    /// Wait statistics of the write locks in this connection's scope, by table
+    fn write_lock_stats(&self, py: Python) -> PyResult<PyObject> {
+        match &self.write_locks {
+            Some(locks) => to_python(py, &locks.stats()),
+            None => Ok(PyDict::new_bound(py).into()),
         }
     }
 
-    fn __str__(&self) -> String {
-        self.__repr__()
+    /// Execute a statement and return the number of rows it affected
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_statement(&self, sql: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<u64> {
+        let conn = self.connector()?;
+        let (sql, params) = resolve_params(sql, params, conn.dialect())?;
+        self.runtime
+            .block_on(conn.execute_statement(&sql, &params))
+            .map_err(to_py_err)
     }
-}
 
-impl Drop for PyConnection {
-    fn drop(&mut self) {
-        let _ = self.close();
+    /// Execute a statement once per parameter set in one transaction and
+    /// return the total number of rows affected
+    fn execute_many(&self, sql: &str, params_list: &Bound<'_, PyAny>) -> PyResult<u64> {
+        let conn = self.connector()?;
+        let mut bound_sql: Option<String> = None;
+        let mut param_sets = Vec::new();
+        for params in params_list.iter()? {
+            let (sql, values) = resolve_params(sql, Some(&params?), conn.dialect())?;
+            match &bound_sql {
+                Some(first) if *first != sql => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "Every parameter set must use the same parameter names",
+                    ))
+                }
+                Some(_) => {}
+                None => bound_sql = Some(sql),
+            }
+            param_sets.push(values);
+        }
+        let Some(sql) = bound_sql else {
+            return Ok(0);
+        };
+        self.runtime
+            .block_on(conn.execute_many(&sql, &param_sets))
+            .map_err(to_py_err)
     }
-}
 
-/// Factory function to create the appropriate connector
-async fn create_connector(
-    config: &ConnectionConfig,
-) -> Result<Box<dyn CrudOperations>, industrydb_core::error::IndustryDbError> {
-    match config.db_type {
-        DatabaseType::Postgres => {
-            let connector = industrydb_postgres::PostgresConnector::new(config).await?;
-            Ok(Box::new(connector))
-        }
-        DatabaseType::Sqlite => {
-            let connector = industrydb_sqlite::SqliteConnector::new(config).await?;
-            Ok(Box::new(connector))
-        }
-        DatabaseType::Mssql => {
-            let connector = industrydb_mssql::MssqlConnector::new(config).await?;
-            Ok(Box::new(connector))
-        }
+    /// Run any SQL and return its rows with column types, rows affected,
+    /// warnings and elapsed time
+    #[pyo3(signature = (sql, params=None))]
+    fn query(
+        &self,
+        py: Python,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyQueryResult> {
+        let conn = self.connector()?;
+        let (sql, params) = resolve_params(sql, params, conn.dialect())?;
+        let result = self
+            .runtime
+            .block_on(conn.query(&sql, &params))
+            .map_err(to_py_err)?;
+        PyQueryResult::new(py, result)
     }
-}
 
-/// Convert Polars DataFrame to Python dict
-fn dataframe_to_py_dict(py: Python, df: &polars::prelude::DataFrame) -> PyResult<Py<PyDict>> {
-    use polars::prelude::*;
+    /// First row of a query as a dict, or None
+    #[pyo3(signature = (sql, params=None))]
+    fn fetch_one(
+        &self,
+        py: Python,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Option<Py<PyDict>>> {
+        let conn = self.connector()?;
+        let (sql, params) = resolve_params(sql, params, conn.dialect())?;
+        let row = self
+            .runtime
+            .block_on(conn.fetch_one(&sql, &params))
+            .map_err(to_py_err)?;
+        row.map(|row| {
+            let dict = PyDict::new_bound(py);
+            for (column, value) in &row {
+                dict.set_item(column, value_to_py(py, value))?;
+            }
+            Ok(dict.unbind())
+        })
+        .transpose()
+    }
 
-    let dict = PyDict::new_bound(py);
+    /// First column of the first row of a query, or None
+    #[pyo3(signature = (sql, params=None))]
+    fn fetch_scalar(
+        &self,
+        py: Python,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        let (sql, params) = resolve_params(sql, params, conn.dialect())?;
+        let value = self
+            .runtime
+            .block_on(conn.fetch_scalar(&sql, &params))
+            .map_err(to_py_err)?;
+        Ok(value.map_or_else(|| py.None(), |v| value_to_py(py, &v)))
+    }
 
-    for col in df.get_columns() {
-        let col_name = col.name().as_str();
-        let values = PyList::empty_bound(py);
+    /// Run a script of several statements, or `GO` batches on SQL Server
+    fn execute_batch(&self, sql: &str) -> PyResult<usize> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.execute_batch(sql))
+            .map_err(to_py_err)
+    }
 
-        // Convert column to PyList based on dtype
-        for i in 0..col.len() {
-            if col.is_null().get(i).unwrap_or(false) {
-                values.append(py.None())?;
-            } else {
-                match col.dtype() {
-                    DataType::Int32 => {
-                        let val = col
-                            .i32()
-                            .map_err(|e| {
-                                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
-                            })?
-                            .get(i);
-                        values.append(val)?;
-                    }
-                    DataType::Int64 => {
-                        let val = col
-                            .i64()
-                            .map_err(|e| {
-                                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
-                            })?
-                            .get(i);
-                        values.append(val)?;
-                    }
-                    DataType::Float64 => {
-                        let val = col
-                            .f64()
-                            .map_err(|e| {
-                                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
-                            })?
-                            .get(i);
-                        values.append(val)?;
-                    }
-                    DataType::String => {
-                        let val = col
-                            .str()
-                            .map_err(|e| {
-                                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
-                            })?
-                            .get(i)
-                            .unwrap_or("");
-                        values.append(val)?;
-                    }
-                    DataType::Boolean => {
-                        let val = col
-                            .bool()
-                            .map_err(|e| {
-                                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
-                            })?
-                            .get(i);
-                        values.append(val)?;
-                    }
-                    _ => {
-                        // Fallback to string representation
-                        values.append(format!(
-                            "{:?}",
-                            col.get(i)
-                                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                                    e.to_string()
-                                ))?
-                        ))?;
-                    }
-                }
-            }
-        }
+    /// Run a SQL script given as a file path or as the script itself
+    ///
+    /// A `pathlib.Path`, or a single-line string naming an existing file, is
+    /// read from disk; any other string is run as SQL.
+    fn execute_script(&self, py: Python, script: &Bound<'_, PyAny>) -> PyResult<usize> {
+        let conn = self.connector()?;
+        let is_path = !script.is_instance_of::<pyo3::types::PyString>();
+        let text: String = if is_path {
+            let fspath = py.import_bound("os")?.getattr("fspath")?;
+            fspath.call1((script,))?.extract()?
+        } else {
+            script.extract()?
+        };
+        let path = std::path::Path::new(&text);
+        let result = if is_path || (!text.contains('\n') && path.is_file()) {
+            self.runtime.block_on(conn.execute_script(path))
+        } else {
+            self.runtime.block_on(conn.execute_batch(&text))
+        };
+        result.map_err(to_py_err)
+    }
 
-        dict.set_item(col_name, values)?;
+    /// Attach the SQLite database file at `path` under `alias`
+    fn attach(&self, path: std::path::PathBuf, alias: &str) -> PyResult<()> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.attach(&path, alias))
+            .map_err(to_py_err)
     }
 
-    Ok(dict.unbind())
-}
+    /// Detach the SQLite database attached under `alias`
+    fn detach(&self, alias: &str) -> PyResult<()> {
+        let conn = self.connector()?;
+        self.runtime.block_on(conn.detach(alias)).map_err(to_py_err)
+    }
 
-/// Convert Python dict to Polars DataFrame
-fn py_dict_to_dataframe(data: &Bound<'_, PyDict>) -> PyResult<polars::prelude::DataFrame> {
-    use polars::prelude::*;
+    /// Snapshot the database into the file at `path` while writers continue
+    fn backup_to(&self, py: Python, path: std::path::PathBuf) -> PyResult<()> {
+        let conn = self.connector()?;
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(conn.backup_to(&path)))
+            .map_err(to_py_err)
+    }
 
-    let mut series_vec: Vec<Series> = Vec::new();
+    /// Replace the database's contents with the file at `path`
+    fn restore_from(&self, py: Python, path: std::path::PathBuf) -> PyResult<()> {
+        let conn = self.connector()?;
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(conn.restore_from(&path)))
+            .map_err(to_py_err)
+    }
 
-    for (key, value) in data.iter() {
-        let col_name: String = key.extract()?;
-        let list: &Bound<'_, PyList> = value.downcast()?;
+    /// Run a query built with `Query`
+    fn fetch(&self, py: Python, query: PyRef<'_, PyQuery>) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
+            .runtime
+            .block_on(query.inner.fetch(conn))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
 
-        // Try to infer type from first non-null value
-        let mut values_i64: Vec<Option<i64>> = Vec::new();
-        let mut values_f64: Vec<Option<f64>> = Vec::new();
-        let mut values_str: Vec<Option<String>> = Vec::new();
-        let mut is_int = true;
-        let mut is_float = true;
+    /// Flatten a parent/child table with depth and path columns
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, id_column, parent_column, label_column=None, columns=None, roots=None, max_depth=100, separator="/"))]
+    fn hierarchy(
+        &self,
+        py: Python,
+        table: &str,
+        id_column: &str,
+        parent_column: &str,
+        label_column: Option<&str>,
+        columns: Option<Vec<String>>,
+        roots: Option<Vec<Bound<'_, PyAny>>>,
+        max_depth: usize,
+        separator: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let mut hierarchy = Hierarchy::new(table, id_column, parent_column)
+            .columns(columns.unwrap_or_default())
+            .max_depth(max_depth)
+            .separator(separator);
+        if let Some(label) = label_column {
+            hierarchy = hierarchy.label(label);
+        }
+        if let Some(roots) = roots {
+            let ids = roots
+                .iter()
+                .map(py_to_value)
+                .collect::<PyResult<Vec<_>>>()?;
+            hierarchy = hierarchy.roots(ids);
+        }
+        let df = self
+            .runtime
+            .block_on(hierarchy.fetch(conn))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
 
-        for item in list.iter() {
-            if item.is_none() {
-                values_i64.push(None);
-                values_f64.push(None);
-                values_str.push(None);
-            } else if let Ok(val) = item.extract::<i64>() {
-                values_i64.push(Some(val));
-                values_f64.push(Some(val as f64));
-                values_str.push(Some(val.to_string()));
-            } else if let Ok(val) = item.extract::<f64>() {
-                is_int = false;
-                values_f64.push(Some(val));
-                values_str.push(Some(val.to_string()));
-            } else if let Ok(val) = item.extract::<String>() {
-                is_int = false;
-                is_float = false;
-                values_str.push(Some(val));
-            } else {
-                is_int = false;
-                is_float = false;
-                values_str.push(Some(item.str()?.extract()?));
-            }
+    /// Read the rows between two timestamps, optionally for some tags only
+    /// and resampled
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, ts_column, start, end, tags=None, columns=None, tag_column="tag", resample=None, agg="mean", fill=None))]
+    fn select_range(
+        &self,
+        py: Python,
+        table: &str,
+        ts_column: &str,
+        start: &Bound<'_, PyAny>,
+        end: &Bound<'_, PyAny>,
+        tags: Option<Vec<Bound<'_, PyAny>>>,
+        columns: Option<Vec<String>>,
+        tag_column: &str,
+        resample: Option<&str>,
+        agg: &str,
+        fill: Option<&str>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let mut range = TimeRange::new(
+            table,
+            ts_column,
+            py_to_timestamp(start)?,
+            py_to_timestamp(end)?,
+        )
+        .columns(columns.unwrap_or_default());
+        if let Some(tags) = tags {
+            let tags = tags.iter().map(py_to_value).collect::<PyResult<Vec<_>>>()?;
+            range = range.tags(tag_column, tags);
+        }
+        if let Some(every) = resample {
+            let every = parse_interval(every).map_err(to_py_err)?;
+            range = range.resample(every, agg.parse::<Aggregate>().map_err(to_py_err)?);
+        }
+        if let Some(fill) = fill {
+            range = range.fill(fill.parse::<FillMethod>().map_err(to_py_err)?);
         }
+        let runtime = self.runtime.clone();
+        let df = py
+            .allow_threads(|| runtime.block_on(range.fetch(conn)))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
 
-        let series = if is_int {
-            Series::new(col_name.as_str().into(), values_i64)
-        } else if is_float {
-            Series::new(col_name.as_str().into(), values_f64)
-        } else {
-            Series::new(col_name.as_str().into(), values_str)
-        };
+    /// Aggregate value columns over fixed time buckets
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, ts_column, value_columns, bucket="5m", aggs=None, time_bucket=false, fill=None, continuous_aggregate=None))]
+    fn select_bucketed(
+        &self,
+        py: Python,
+        table: &str,
+        ts_column: &str,
+        value_columns: Vec<String>,
+        bucket: &str,
+        aggs: Option<Vec<String>>,
+        time_bucket: bool,
+        fill: Option<&str>,
+        continuous_aggregate: Option<&str>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let mut buckets =
+            time_buckets(table, ts_column, value_columns, bucket, aggs)?.time_bucket(time_bucket);
+        if let Some(fill) = fill {
+            buckets = buckets.fill(fill.parse::<FillMethod>().map_err(to_py_err)?);
+        }
+        let view = continuous_aggregate.map(ContinuousAggregate::new);
+        let runtime = self.runtime.clone();
+        let df = py
+            .allow_threads(|| {
+                runtime.block_on(async {
+                    match &view {
+                        Some(view) => view.fetch(conn, &buckets).await,
+                        None => buckets.fetch(conn).await,
+                    }
+                })
+            })
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
 
-        series_vec.push(series);
+    /// Version of the TimescaleDB extension, if the server has it
+    fn timescale_version(&self, py: Python) -> PyResult<Option<String>> {
+        let conn = self.connector()?;
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(timescale_version(conn)))
+            .map_err(to_py_err)
     }
 
-    DataFrame::new(series_vec.into_iter().map(|s| s.into_column()).collect())
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+    /// Create a TimescaleDB continuous aggregate of time buckets
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (name, table, ts_column, value_columns, bucket="1h", aggs=None, with_data=false))]
+    fn create_continuous_aggregate(
+        &self,
+        py: Python,
+        name: &str,
+        table: &str,
+        ts_column: &str,
+        value_columns: Vec<String>,
+        bucket: &str,
+        aggs: Option<Vec<String>>,
+        with_data: bool,
+    ) -> PyResult<()> {
+        let conn = self.connector()?;
+        let buckets = time_buckets(table, ts_column, value_columns, bucket, aggs)?;
+        let view = ContinuousAggregate::new(name);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(view.create(conn, &buckets, with_data)))
+            .map_err(to_py_err)
+    }
+
+    /// Refresh the buckets of a continuous aggregate between two timestamps
+    #[pyo3(signature = (name, start=None, end=None))]
+    fn refresh_continuous_aggregate(
+        &self,
+        py: Python,
+        name: &str,
+        start: Option<&Bound<'_, PyAny>>,
+        end: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let conn = self.connector()?;
+        let start = start.map(py_to_timestamp).transpose()?;
+        let end = end.map(py_to_timestamp).transpose()?;
+        let view = ContinuousAggregate::new(name);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(view.refresh(conn, start, end)))
+            .map_err(to_py_err)
+    }
+
+    /// Refresh a continuous aggregate on a schedule
+    #[pyo3(signature = (name, every, start_offset=None, end_offset=None))]
+    fn add_continuous_aggregate_policy(
+        &self,
+        py: Python,
+        name: &str,
+        every: &str,
+        start_offset: Option<&str>,
+        end_offset: Option<&str>,
+    ) -> PyResult<()> {
+        let conn = self.connector()?;
+        let every = parse_interval(every).map_err(to_py_err)?;
+        let start_offset = start_offset
+            .map(parse_interval)
+            .transpose()
+            .map_err(to_py_err)?;
+        let end_offset = end_offset
+            .map(parse_interval)
+            .transpose()
+            .map_err(to_py_err)?;
+        let view = ContinuousAggregate::new(name);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| {
+            runtime.block_on(view.add_policy(conn, start_offset, end_offset, every))
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Drop a continuous aggregate and its refresh policy
+    fn drop_continuous_aggregate(&self, py: Python, name: &str) -> PyResult<()> {
+        let conn = self.connector()?;
+        let view = ContinuousAggregate::new(name);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(view.remove(conn)))
+            .map_err(to_py_err)
+    }
+
+    /// Read narrow tag-value rows as one column per tag
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, ts_column, tag_column, value_column, tags=None, start=None, end=None))]
+    fn read_pivoted(
+        &self,
+        py: Python,
+        table: &str,
+        ts_column: &str,
+        tag_column: &str,
+        value_column: &str,
+        tags: Option<Vec<String>>,
+        start: Option<&Bound<'_, PyAny>>,
+        end: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let mut pivoted =
+            Pivoted::new(table, ts_column, tag_column, value_column).tags(tags.unwrap_or_default());
+        match (start, end) {
+            (Some(start), Some(end)) => {
+                pivoted = pivoted.between(py_to_timestamp(start)?, py_to_timestamp(end)?);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(to_py_err(
+                    industrydb_core::error::IndustryDbError::invalid_parameter(
+                        "Give both start and end, or neither",
+                    ),
+                ))
+            }
+        }
+        let runtime = self.runtime.clone();
+        let df = py
+            .allow_threads(|| runtime.block_on(pivoted.fetch(conn)))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Value of one or more tags at a point in time
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, ts_column, tag, timestamp, mode="previous", tag_column="tag", value_column="value", tag_table=None))]
+    fn value_at(
+        &self,
+        py: Python,
+        table: &str,
+        ts_column: &str,
+        tag: &Bound<'_, PyAny>,
+        timestamp: &Bound<'_, PyAny>,
+        mode: &str,
+        tag_column: &str,
+        value_column: &str,
+        tag_table: Option<&str>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let tags: Vec<String> = match tag.extract::<String>() {
+            Ok(tag) => vec![tag],
+            Err(_) => tag.extract()?,
+        };
+        let query = ValueAt::new(table, ts_column, tags, py_to_timestamp(timestamp)?)
+            .columns(tag_column, value_column)
+            .mode(mode.parse::<AtMode>().map_err(to_py_err)?);
+        let runtime = self.runtime.clone();
+        let df = py
+            .allow_threads(|| {
+                runtime.block_on(async {
+                    let df = query.fetch(conn).await?;
+                    match tag_table {
+                        Some(tag_table) => {
+                            TagRegistry::new(tag_table)
+                                .annotate(conn, df, tag_column, None)
+                                .await
+                        }
+                        None => Ok(df),
+                    }
+                })
+            })
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Create the tag metadata table unless it exists
+    #[pyo3(signature = (table=DEFAULT_TAG_TABLE))]
+    fn create_tag_registry(&self, py: Python, table: &str) -> PyResult<()> {
+        let conn = self.connector()?;
+        let registry = TagRegistry::new(table);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(registry.create(conn)))
+            .map_err(to_py_err)
+    }
+
+    /// Register tags, replacing the metadata of those already registered
+    #[pyo3(signature = (tags, table=DEFAULT_TAG_TABLE))]
+    fn register_tags(&self, py: Python, tags: &Bound<'_, PyList>, table: &str) -> PyResult<usize> {
+        let conn = self.connector()?;
+        let tags: Vec<TagInfo> =
+            pythonize::depythonize_bound(tags.clone().into_any()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid tag metadata: {}",
+                    e
+                ))
+            })?;
+        let registry = TagRegistry::new(table);
+        let _lock = self.write_lock(py, table);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(registry.register(conn, &tags)))
+            .map_err(to_py_err)
+    }
+
+    /// Metadata of registered tags
+    #[pyo3(signature = (tags=None, table=DEFAULT_TAG_TABLE))]
+    fn tag_metadata(
+        &self,
+        py: Python,
+        tags: Option<Vec<String>>,
+        table: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let registry = TagRegistry::new(table);
+        let tags = tags.unwrap_or_default();
+        let runtime = self.runtime.clone();
+        let df = py
+            .allow_threads(|| runtime.block_on(registry.lookup(conn, &tags)))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Remove tags from the registry
+    #[pyo3(signature = (tags, table=DEFAULT_TAG_TABLE))]
+    fn remove_tags(&self, py: Python, tags: Vec<String>, table: &str) -> PyResult<u64> {
+        let conn = self.connector()?;
+        let registry = TagRegistry::new(table);
+        let _lock = self.write_lock(py, table);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(registry.remove(conn, &tags)))
+            .map_err(to_py_err)
+    }
+
+    /// Join the metadata of the tags in a column onto rows
+    #[pyo3(signature = (data, tag_column="tag", scale_column=None, table=DEFAULT_TAG_TABLE))]
+    fn join_tag_metadata(
+        &self,
+        py: Python,
+        data: &Bound<'_, PyDict>,
+        tag_column: &str,
+        scale_column: Option<&str>,
+        table: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = py_dict_to_dataframe(data)?;
+        let registry = TagRegistry::new(table);
+        let runtime = self.runtime.clone();
+        let df = py
+            .allow_threads(|| {
+                runtime.block_on(registry.annotate(conn, df, tag_column, scale_column))
+            })
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Insert a frame with one column per tag as narrow tag-value rows
+    fn insert_pivoted(
+        &self,
+        py: Python,
+        table: &str,
+        data: &Bound<'_, PyDict>,
+        ts_column: &str,
+        tag_column: &str,
+        value_column: &str,
+    ) -> PyResult<usize> {
+        let conn = self.connector()?;
+        let df = unpivot(
+            py_dict_to_dataframe(data)?,
+            ts_column,
+            tag_column,
+            value_column,
+        )
+        .map_err(to_py_err)?;
+        let _lock = self.write_lock(py, table);
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(conn.insert(table, df)))
+            .map_err(to_py_err)
+    }
+
+    /// Read a table or query in concurrent ranges of one column
+    #[pyo3(signature = (source, partition_column, num_partitions=4))]
+    fn read_partitioned(
+        &self,
+        py: Python,
+        source: &str,
+        partition_column: &str,
+        num_partitions: usize,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
+            .runtime
+            .block_on(read_partitioned(
+                conn,
+                source,
+                partition_column,
+                num_partitions,
+            ))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Insert data into table
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, data, validate=false, coerce=None, audit=None, **_kwargs))]
+    fn insert(
+        &self,
+        py: Python,
+        table: String,
+        data: &Bound<'_, PyDict>,
+        validate: bool,
+        coerce: Option<&Bound<'_, PyAny>>,
+        audit: Option<&Bound<'_, PyAny>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let conn = self.inner.as_ref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+        })?;
+
+        let df = py_dict_to_dataframe(data)?;
+        let options = insert_options(validate, coerce, self.audit_columns(audit)?)?;
+        let _lock = self.write_lock(py, &table);
+        let rows = self
+            .runtime
+            .block_on(options.insert(conn.as_ref(), &table, df))
+            .map_err(to_py_err)?;
+        Ok(rows)
+    }
+
+    /// Insert rows, updating those whose key columns already exist
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, data, key_columns, validate=false, coerce=None, audit=None))]
+    fn upsert(
+        &self,
+        py: Python,
+        table: String,
+        data: &Bound<'_, PyDict>,
+        key_columns: Vec<String>,
+        validate: bool,
+        coerce: Option<&Bound<'_, PyAny>>,
+        audit: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<usize> {
+        let conn = self.connector()?;
+        let df = py_dict_to_dataframe(data)?;
+        let options = insert_options(validate, coerce, self.audit_columns(audit)?)?;
+        let _lock = self.write_lock(py, &table);
+        self.runtime
+            .block_on(options.upsert(conn, &table, df, &key_columns))
+            .map_err(to_py_err)
+    }
+
+    /// Select data from table
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, columns=None, where_clause=None, params=None, limit=None, order_by=None, group_by=None, having=None, offset=None, distinct=false, schema=None, **_kwargs))]
+    fn select(
+        &self,
+        py: Python,
+        table: String,
+        columns: Option<Vec<String>>,
+        where_clause: Option<String>,
+        params: Option<&Bound<'_, PyAny>>,
+        limit: Option<usize>,
+        order_by: Option<&Bound<'_, PyAny>>,
+        group_by: Option<String>,
+        having: Option<String>,
+        offset: Option<usize>,
+        distinct: bool,
+        schema: Option<&Bound<'_, PyDict>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.inner.as_ref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+        })?;
+
+        let hints = schema.map(py_to_dtypes).transpose()?;
+        let (where_clause, having, params) =
+            resolve_select_params(where_clause, having, params, conn.dialect())?;
+        let options = SelectOptions {
+            distinct,
+            group_by,
+            having,
+            order_by: order_by
+                .map(|o| resolve_order_by(o, conn.dialect()))
+                .transpose()?,
+            limit,
+            offset,
+        };
+        let mut df = self
+            .runtime
+            .block_on(conn.select(
+                &table,
+                columns.as_deref(),
+                where_clause.as_deref(),
+                &params,
+                &options,
+            ))
+            .map_err(to_py_err)?;
+        if let Some(hints) = hints {
+            df = coerce_columns(df, &hints).map_err(to_py_err)?;
+        }
+
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Number of rows matching the WHERE clause
+    #[pyo3(signature = (table, where_clause=None, params=None))]
+    fn count(
+        &self,
+        table: &str,
+        where_clause: Option<String>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<u64> {
+        let conn = self.connector()?;
+        let (where_clause, params) = resolve_where_params(where_clause, params, conn.dialect())?;
+        self.runtime
+            .block_on(conn.count(table, where_clause.as_deref(), &params))
+            .map_err(to_py_err)
+    }
+
+    /// Whether any row matches the WHERE clause
+    #[pyo3(signature = (table, where_clause=None, params=None))]
+    fn exists(
+        &self,
+        table: &str,
+        where_clause: Option<String>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        let conn = self.connector()?;
+        let (where_clause, params) = resolve_where_params(where_clause, params, conn.dialect())?;
+        self.runtime
+            .block_on(conn.exists(table, where_clause.as_deref(), &params))
+            .map_err(to_py_err)
+    }
+
+    /// Update rows in table
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, values, where_clause=None, params=None, audit=None, **_kwargs))]
+    fn update(
+        &self,
+        py: Python,
+        table: String,
+        values: &Bound<'_, PyDict>,
+        where_clause: Option<String>,
+        params: Option<&Bound<'_, PyAny>>,
+        audit: Option<&Bound<'_, PyAny>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let conn = self.inner.as_ref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+        })?;
+
+        let mut values_map = HashMap::new();
+        for (key, value) in values.iter() {
+            let key_str: String = key.extract()?;
+            let value_str: String = value.str()?.extract()?;
+            values_map.insert(key_str, value_str);
+        }
+
+        let (where_clause, params) = resolve_where_params(where_clause, params, conn.dialect())?;
+        let audit = self.audit_columns(audit)?;
+        let _lock = self.write_lock(py, &table);
+        let rows = self
+            .runtime
+            .block_on(async {
+                let where_clause = where_clause.as_deref();
+                match &audit {
+                    Some(audit) => {
+                        audit
+                            .update(conn.as_ref(), &table, &values_map, where_clause, &params)
+                            .await
+                    }
+                    None => {
+                        conn.update(&table, &values_map, where_clause, &params)
+                            .await
+                    }
+                }
+            })
+            .map_err(to_py_err)?;
+
+        Ok(rows)
+    }
+
+    /// Delete rows from table
+    #[pyo3(signature = (table, where_clause=None, params=None, **_kwargs))]
+    fn delete(
+        &self,
+        py: Python,
+        table: String,
+        where_clause: Option<String>,
+        params: Option<&Bound<'_, PyAny>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let conn = self.inner.as_ref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+        })?;
+
+        let (where_clause, params) = resolve_where_params(where_clause, params, conn.dialect())?;
+        let _lock = self.write_lock(py, &table);
+        let rows = self
+            .runtime
+            .block_on(conn.delete(&table, where_clause.as_deref(), &params))
+            .map_err(to_py_err)?;
+
+        Ok(rows)
+    }
+
+    /// Create a table from a DataFrame, a dict of column lists, or a dtype mapping
+    #[pyo3(signature = (table, data, if_not_exists=true))]
+    fn create_table(
+        &self,
+        table: String,
+        data: &Bound<'_, PyAny>,
+        if_not_exists: bool,
+    ) -> PyResult<()> {
+        let conn = self.connector()?;
+        let schema = py_to_schema(data)?;
+        self.runtime
+            .block_on(conn.create_table(&table, &schema, if_not_exists))
+            .map_err(to_py_err)
+    }
+
+    /// Check whether a table exists
+    fn table_exists(&self, table: String) -> PyResult<bool> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.table_exists(&table))
+            .map_err(to_py_err)
+    }
+
+    /// Drop a table
+    #[pyo3(signature = (table, if_exists=true))]
+    fn drop_table(&self, table: String, if_exists: bool) -> PyResult<()> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.drop_table(&table, if_exists))
+            .map_err(to_py_err)
+    }
+
+    /// Remove all rows from a table
+    fn truncate(&self, py: Python, table: String) -> PyResult<()> {
+        let conn = self.connector()?;
+        let _lock = self.write_lock(py, &table);
+        self.runtime
+            .block_on(conn.truncate(&table))
+            .map_err(to_py_err)
+    }
+
+    /// Rename a table within its schema
+    fn rename_table(&self, table: String, new_name: String) -> PyResult<()> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.rename_table(&table, &new_name))
+            .map_err(to_py_err)
+    }
+
+    /// List schemas visible to this connection
+    fn list_schemas(&self) -> PyResult<Vec<String>> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.list_schemas())
+            .map_err(to_py_err)
+    }
+
+    /// List tables in a schema (the default schema when not given)
+    #[pyo3(signature = (schema=None))]
+    fn list_tables(&self, schema: Option<String>) -> PyResult<Vec<String>> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(conn.list_tables(schema.as_deref()))
+            .map_err(to_py_err)
+    }
+
+    /// Describe the columns of a table
+    fn describe_table(&self, py: Python, table: String) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
+            .runtime
+            .block_on(conn.describe_table(&table))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Primary key columns of a table
+    fn primary_keys(&self, py: Python, table: String) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
+            .runtime
+            .block_on(conn.primary_keys(&table))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Indexes defined on a table
+    fn indexes(&self, py: Python, table: String) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
+            .runtime
+            .block_on(conn.indexes(&table))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Foreign keys defined on a table
+    fn foreign_keys(&self, py: Python, table: String) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let df = self
+            .runtime
+            .block_on(conn.foreign_keys(&table))
+            .map_err(to_py_err)?;
+        dataframe_to_py_dict(py, &df)
+    }
+
+    /// Run a query and upload the result to object storage
+    ///
+    /// `url` names the object, e.g. `s3://bucket/exports/readings.parquet`;
+    /// `options` carries provider settings such as `aws_endpoint` for MinIO.
+    #[pyo3(signature = (sql, url, format="parquet", options=None, max_retries=10))]
+    fn export_to_object_store(
+        &self,
+        py: Python,
+        sql: String,
+        url: String,
+        format: &str,
+        options: Option<HashMap<String, String>>,
+        max_retries: usize,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let format: ExportFormat = format.parse().map_err(to_py_err)?;
+        let target = open_target(&url, options, max_retries)?;
+
+        let runtime = self.runtime.clone();
+        let summary = py
+            .allow_threads(|| runtime.block_on(export_query(conn, &sql, &target, "", format)))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("url", summary.url)?;
+        dict.set_item("rows", summary.rows)?;
+        dict.set_item("bytes", summary.bytes)?;
+        Ok(dict.unbind())
+    }
+
+    /// Read a table or query into a local Parquet file
+    #[pyo3(signature = (source, path, compression="zstd"))]
+    fn read_to_parquet(
+        &self,
+        py: Python,
+        source: &str,
+        path: std::path::PathBuf,
+        compression: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let compression = parse_compression(compression).map_err(to_py_err)?;
+        let runtime = self.runtime.clone();
+        let summary = py
+            .allow_threads(|| runtime.block_on(export_parquet(conn, source, &path, compression)))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("path", path)?;
+        dict.set_item("rows", summary.rows)?;
+        dict.set_item("bytes", summary.bytes)?;
+        Ok(dict.unbind())
+    }
+
+    /// Read a table or query into a local Arrow IPC (Feather) file
+    #[pyo3(signature = (source, path, compression="uncompressed", batch_rows=None, stream=false))]
+    fn export_ipc(
+        &self,
+        py: Python,
+        source: &str,
+        path: std::path::PathBuf,
+        compression: &str,
+        batch_rows: Option<usize>,
+        stream: bool,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let options = IpcOptions {
+            stream,
+            batch_rows,
+            compression: parse_ipc_compression(compression).map_err(to_py_err)?,
+        };
+        let runtime = self.runtime.clone();
+        let summary = py
+            .allow_threads(|| runtime.block_on(export_ipc(conn, source, &path, options)))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("path", path)?;
+        dict.set_item("rows", summary.rows)?;
+        dict.set_item("bytes", summary.bytes)?;
+        Ok(dict.unbind())
+    }
+
+    /// Read a table or query into a local CSV file
+    fn export_csv(
+        &self,
+        py: Python,
+        source: &str,
+        path: std::path::PathBuf,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let runtime = self.runtime.clone();
+        let summary = py
+            .allow_threads(|| runtime.block_on(export_csv(conn, source, &path)))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("path", path)?;
+        dict.set_item("rows", summary.rows)?;
+        dict.set_item("bytes", summary.bytes)?;
+        Ok(dict.unbind())
+    }
+
+    /// Store a query result as a named snapshot in `directory`
+    #[pyo3(signature = (sql, name, directory="snapshots", compression="zstd", overwrite=false))]
+    fn snapshot(
+        &self,
+        py: Python,
+        sql: &str,
+        name: &str,
+        directory: std::path::PathBuf,
+        compression: &str,
+        overwrite: bool,
+    ) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        let compression = parse_compression(compression).map_err(to_py_err)?;
+        let store = SnapshotStore::open(&directory).map_err(to_py_err)?;
+        let runtime = self.runtime.clone();
+        let info = py
+            .allow_threads(|| {
+                runtime.block_on(store.create(conn, sql, name, compression, overwrite))
+            })
+            .map_err(to_py_err)?;
+        to_python(py, &info)
+    }
+
+    /// Remove the rows of a table older than a retention window
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, ts_column, keep, batch_size=10_000, detach_only=false, archive=None, compression="zstd", now=None))]
+    fn enforce_retention(
+        &self,
+        py: Python,
+        table: &str,
+        ts_column: &str,
+        keep: &str,
+        batch_size: usize,
+        detach_only: bool,
+        archive: Option<std::path::PathBuf>,
+        compression: &str,
+        now: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        let policy =
+            RetentionPolicy::new(table, ts_column, parse_interval(keep).map_err(to_py_err)?)
+                .batch_size(batch_size)
+                .detach_only(detach_only);
+        let now = match now {
+            Some(now) => py_to_timestamp(now)?,
+            None => chrono::Local::now().naive_local(),
+        };
+        let cutoff = policy.cutoff(now);
+        let compression = parse_compression(compression).map_err(to_py_err)?;
+        let _lock = self.write_lock(py, table);
+        let runtime = self.runtime.clone();
+        let summary = py
+            .allow_threads(|| {
+                runtime.block_on(async {
+                    if let Some(path) = &archive {
+                        let sql = policy.expired_sql(conn.dialect(), cutoff)?;
+                        export_parquet(conn, &sql, path, compression).await?;
+                    }
+                    policy.enforce(conn, cutoff).await
+                })
+            })
+            .map_err(to_py_err)?;
+        to_python(py, &summary)
+    }
+
+    /// Copy a table or query into a local file with PostgreSQL's COPY
+    #[pyo3(signature = (source, path, format="csv"))]
+    fn export_copy(
+        &self,
+        py: Python,
+        source: &str,
+        path: std::path::PathBuf,
+        format: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let format: CopyFormat = format.parse().map_err(to_py_err)?;
+        let conn = self.connector()?;
+        let runtime = self.runtime.clone();
+        let summary = py
+            .allow_threads(|| runtime.block_on(export_copy(conn, source, &path, format)))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("path", path)?;
+        dict.set_item("rows", summary.rows)?;
+        dict.set_item("bytes", summary.bytes)?;
+        Ok(dict.unbind())
+    }
+
+    /// Load CSV from a file or file-like object into a table with
+    /// PostgreSQL's COPY
+    #[pyo3(signature = (table, source, delimiter=',', null="", header=true))]
+    fn copy_from_csv(
+        &self,
+        py: Python,
+        table: &str,
+        source: &Bound<'_, PyAny>,
+        delimiter: char,
+        null: &str,
+        header: bool,
+    ) -> PyResult<u64> {
+        let options = CsvCopyOptions {
+            delimiter,
+            null: null.to_string(),
+            header,
+        };
+        let conn = self.connector()?;
+        let runtime = self.runtime.clone();
+        if source.hasattr("read")? {
+            let mut reader = PyReader::new(source.clone().unbind());
+            py.allow_threads(|| runtime.block_on(conn.copy_in(table, &options, &mut reader)))
+        } else {
+            let path: std::path::PathBuf = source.extract()?;
+            py.allow_threads(|| runtime.block_on(import_copy(conn, table, &path, &options)))
+        }
+        .map_err(to_py_err)
+    }
+
+    /// Load a CSV file into a table in batches
+    #[pyo3(signature = (table, path, schema=None, create_table=true))]
+    fn import_csv(
+        &self,
+        py: Python,
+        table: &str,
+        path: std::path::PathBuf,
+        schema: Option<&Bound<'_, PyDict>>,
+        create_table: bool,
+    ) -> PyResult<usize> {
+        let conn = self.connector()?;
+        let dtypes = schema.map(py_to_dtypes).transpose()?.unwrap_or_default();
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(import_csv(conn, table, &path, &dtypes, create_table)))
+            .map_err(to_py_err)
+    }
+
+    /// Load archived objects (one object or every object under a prefix) into a table
+    #[pyo3(signature = (url, table, format="parquet", options=None, create_table=true, max_retries=10))]
+    #[allow(clippy::too_many_arguments)]
+    fn import_from_object_store(
+        &self,
+        py: Python,
+        url: String,
+        table: String,
+        format: &str,
+        options: Option<HashMap<String, String>>,
+        create_table: bool,
+        max_retries: usize,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+        let format: ExportFormat = format.parse().map_err(to_py_err)?;
+        let target = open_target(&url, options, max_retries)?;
+
+        let summary = self
+            .runtime
+            .block_on(import_objects(
+                conn,
+                &target,
+                "",
+                &table,
+                format,
+                create_table,
+            ))
+            .map_err(to_py_err)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("objects", summary.objects)?;
+        dict.set_item("rows", summary.rows)?;
+        Ok(dict.unbind())
+    }
+
+    /// Resource usage of the most recent query, or None before the first
+    fn last_query_stats(&self, py: Python) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        match conn.metrics().last() {
+            Some(stats) => to_python(py, &stats),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Resource usage totals and the most recent queries on this connection
+    fn query_metrics(&self, py: Python) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        to_python(py, &conn.metrics().snapshot())
+    }
+
+    /// Clear the recorded query resource usage
+    fn reset_query_metrics(&self) -> PyResult<()> {
+        self.connector()?.metrics().reset();
+        Ok(())
+    }
+
+    /// Generate synthetic rows from a table definition dict and insert them
+    ///
+    /// Creates the table from the generated column types first when
+    /// `create_table` is set. Returns the number of rows inserted.
+    #[pyo3(signature = (spec, create_table=true))]
+    fn load_synthetic(&self, spec: &Bound<'_, PyDict>, create_table: bool) -> PyResult<usize> {
+        let conn = self.connector()?;
+        let table = parse_spec(spec)?;
+        self.runtime
+            .block_on(synth::load(conn, &table, create_table))
+            .map_err(to_py_err)
+    }
+
+    /// Fill an existing table with synthetic rows matched to its column types
+    #[pyo3(signature = (table, rows, seed=0))]
+    fn fill_synthetic(&self, table: String, rows: usize, seed: u64) -> PyResult<usize> {
+        let conn = self.connector()?;
+        self.runtime
+            .block_on(synth::fill_table(conn, &table, rows, seed))
+            .map_err(to_py_err)
+    }
+
+    /// Run data quality checks on a table or query result
+    fn check_quality(
+        &self,
+        py: Python,
+        source: &str,
+        checks: &Bound<'_, PyList>,
+    ) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        let checks = parse_checks(checks)?;
+        let runtime = self.runtime.clone();
+        let report = py
+            .allow_threads(|| runtime.block_on(check_source(conn, source, &checks)))
+            .map_err(to_py_err)?;
+        to_python(py, &report)
+    }
+
+    /// Introspect tables, columns and indexes as a declarative schema dict
+    #[pyo3(signature = (schema=None))]
+    fn introspect_schema(&self, py: Python, schema: Option<String>) -> PyResult<PyObject> {
+        let conn = self.connector()?;
+        let declared = self
+            .runtime
+            .block_on(DatabaseSchema::introspect(conn, schema.as_deref()))
+            .map_err(to_py_err)?;
+        to_python(py, &declared)
+    }
+
+    /// Generate CREATE TABLE / CREATE INDEX statements that recreate this connection's tables
+    ///
+    /// `dialect` selects the target database ("postgres", "sqlite", "mssql");
+    /// defaults to this connection's own dialect.
+    #[pyo3(signature = (dialect=None, schema=None))]
+    fn dump_schema(&self, dialect: Option<&str>, schema: Option<String>) -> PyResult<Vec<String>> {
+        let conn = self.connector()?;
+        let target = dialect
+            .map(|d| d.parse::<DatabaseType>())
+            .transpose()
+            .map_err(to_py_err)?;
+        self.runtime
+            .block_on(ddl::dump_schema(conn, target, schema.as_deref()))
+            .map_err(to_py_err)
+    }
+
+    /// Compare this connection's schema against another connection or a declared schema
+    ///
+    /// `expected` is a connection or a dict shaped like `introspect_schema()`
+    /// output. Returns `{"changes": [...], "ddl": [...]}`; `ddl` holds fix-up
+    /// statements for this connection when `generate_ddl` is set.
+    #[pyo3(signature = (expected, schema=None, generate_ddl=false))]
+    fn diff_schema(
+        &self,
+        py: Python,
+        expected: &Bound<'_, PyAny>,
+        schema: Option<String>,
+        generate_ddl: bool,
+    ) -> PyResult<Py<PyDict>> {
+        let conn = self.connector()?;
+
+        let expected = match expected.downcast::<PyConnection>() {
+            Ok(other) => {
+                let other = other.borrow();
+                self.runtime
+                    .block_on(DatabaseSchema::introspect(
+                        other.connector()?,
+                        schema.as_deref(),
+                    ))
+                    .map_err(to_py_err)?
+            }
+            Err(_) => pythonize::depythonize_bound(expected.clone()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid schema definition: {}",
+                    e
+                ))
+            })?,
+        };
+        let actual = self
+            .runtime
+            .block_on(DatabaseSchema::introspect(conn, schema.as_deref()))
+            .map_err(to_py_err)?;
+
+        let diff = expected.diff(&actual);
+        let ddl = if generate_ddl {
+            let db_type: DatabaseType = conn.db_type().parse().map_err(to_py_err)?;
+            diff.fixup_ddl(db_type)
+        } else {
+            Vec::new()
+        };
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("changes", to_python(py, &diff.changes)?)?;
+        dict.set_item("ddl", ddl)?;
+        Ok(dict.unbind())
+    }
+
+    /// Apply pending migrations from a directory up to `target` (all when omitted)
+    #[pyo3(signature = (path, target=None, table=None))]
+    fn migrate_up(
+        &self,
+        path: String,
+        target: Option<i64>,
+        table: Option<String>,
+    ) -> PyResult<Vec<i64>> {
+        let conn = self.connector()?;
+        let migrator = load_migrator(&path, table)?;
+        self.runtime
+            .block_on(migrator.up(conn, target))
+            .map_err(to_py_err)
+    }
+
+    /// Revert the most recently applied migrations
+    #[pyo3(signature = (path, steps=1, table=None))]
+    fn migrate_down(
+        &self,
+        path: String,
+        steps: usize,
+        table: Option<String>,
+    ) -> PyResult<Vec<i64>> {
+        let conn = self.connector()?;
+        let migrator = load_migrator(&path, table)?;
+        self.runtime
+            .block_on(migrator.down(conn, steps))
+            .map_err(to_py_err)
+    }
+
+    /// Applied/pending state of the migrations in a directory
+    #[pyo3(signature = (path, table=None))]
+    fn migration_status(
+        &self,
+        py: Python,
+        path: String,
+        table: Option<String>,
+    ) -> PyResult<Vec<Py<PyDict>>> {
+        let conn = self.connector()?;
+        let migrator = load_migrator(&path, table)?;
+        let statuses = self
+            .runtime
+            .block_on(migrator.status(conn))
+            .map_err(to_py_err)?;
+
+        statuses
+            .into_iter()
+            .map(|status| {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("version", status.version)?;
+                dict.set_item("name", status.name)?;
+                dict.set_item("applied", status.applied)?;
+                Ok(dict.unbind())
+            })
+            .collect()
+    }
+
+    /// Context manager entry
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Context manager exit
+    fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        if self.is_closed() {
+            "Connection(closed)".to_string()
+        } else {
+            "Connection(active)".to_string()
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+}
+
+impl PyConnection {
+    /// Audit columns from an `audit` argument: `True` for the default
+    /// names, or a dict of names and `user`
+    ///
+    /// `created_by` records the given user, else the configured username,
+    /// else the operating system user.
+    fn audit_columns(&self, audit: Option<&Bound<'_, PyAny>>) -> PyResult<Option<AuditColumns>> {
+        let Some(audit) = audit else {
+            return Ok(None);
+        };
+        let mut columns = match audit.extract::<bool>() {
+            Ok(false) => return Ok(None),
+            Ok(true) => AuditColumns::default(),
+            Err(_) => pythonize::depythonize_bound(audit.clone()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid audit columns: {}",
+                    e
+                ))
+            })?,
+        };
+        if columns.user.is_none() {
+            columns.user = self
+                .config
+                .username
+                .clone()
+                .or_else(|| std::env::var("USER").ok())
+                .or_else(|| std::env::var("USERNAME").ok());
+        }
+        Ok(Some(columns))
+    }
+
+    /// Take the write lock of `table` if write locks are enabled
+    ///
+    /// The GIL is released while waiting, so the writer holding the lock can
+    /// finish.
+    fn write_lock(&self, py: Python, table: &str) -> Option<TableWriteGuard> {
+        let locks = self.write_locks.as_ref()?;
+        let runtime = &self.runtime;
+        Some(py.allow_threads(|| runtime.block_on(locks.lock(table))))
+    }
+
+    /// Write locks of this connection's scope, when enabled
+    pub(crate) fn write_locks(&self) -> Option<&TableLocks> {
+        self.write_locks.as_ref()
+    }
+
+    /// Borrow the active connector, failing if the connection is closed
+    pub(crate) fn connector(&self) -> PyResult<&dyn CrudOperations> {
+        self.inner.as_deref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+        })
+    }
+
+    /// Share the active connector with work outliving this borrow, such as
+    /// a background thread
+    pub(crate) fn shared_connector(&self) -> PyResult<Arc<dyn CrudOperations>> {
+        self.inner.clone().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Connection is closed")
+        })
+    }
+}
+
+impl Drop for PyConnection {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+/// Convert a serializable value into Python objects
+pub(crate) fn to_python<T: serde::Serialize>(py: Python, value: &T) -> PyResult<PyObject> {
+    pythonize::pythonize(py, value)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
+/// Load migrations from a directory, optionally tracked in a custom table
+fn load_migrator(path: &str, table: Option<String>) -> PyResult<Migrator> {
+    let migrator = Migrator::from_dir(path).map_err(to_py_err)?;
+    Ok(match table {
+        Some(table) => migrator.with_table(table),
+        None => migrator,
+    })
+}
+
+/// Factory function to create the appropriate connector
+///
+/// Configs naming a `connector` go through the plugins registered with
+/// [`ConnectionFactory`]. Connectors left out of this build (see the crate
+/// features) fail with an error naming the pip extra that provides them.
+pub(crate) async fn create_connector(
+    config: &ConnectionConfig,
+) -> Result<Box<dyn CrudOperations>, industrydb_core::error::IndustryDbError> {
+    if config.connector.is_some() {
+        return ConnectionFactory::create(config).await;
+    }
+    match config.db_type {
+        #[cfg(feature = "postgres")]
+        DatabaseType::Postgres => {
+            let connector = industrydb_postgres::PostgresConnector::new(config).await?;
+            Ok(Box::new(connector))
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseType::Sqlite => {
+            let connector = industrydb_sqlite::SqliteConnector::new(config).await?;
+            Ok(Box::new(connector))
+        }
+        #[cfg(feature = "mssql")]
+        DatabaseType::Mssql => {
+            let connector = industrydb_mssql::MssqlConnector::new(config).await?;
+            Ok(Box::new(connector))
+        }
+        #[allow(unreachable_patterns)]
+        missing => Err(industrydb_core::error::IndustryDbError::config_error(
+            format!(
+                "The {0} connector is not included in this build of industrydb; \
+             install it with `pip install industrydb[{0}]`",
+                missing
+            ),
+        )),
+    }
+}
+
+/// Connectors compiled into this build, followed by registered plugins
+pub(crate) fn available_connectors() -> Vec<String> {
+    let mut connectors = Vec::new();
+    if cfg!(feature = "sqlite") {
+        connectors.push("sqlite".to_string());
+    }
+    if cfg!(feature = "postgres") {
+        connectors.push("postgres".to_string());
+    }
+    if cfg!(feature = "mssql") {
+        connectors.push("mssql".to_string());
+    }
+    connectors.extend(ConnectionFactory::registered());
+    connectors
+}
+
+/// Convert Polars DataFrame to Python dict
+pub(crate) fn dataframe_to_py_dict(
+    py: Python,
+    df: &polars::prelude::DataFrame,
+) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for col in df.get_columns() {
+        dict.set_item(col.name().as_str(), column_to_py_list(py, col)?)?;
+    }
+    Ok(dict.unbind())
+}
+
+/// Values of a column as a Python list; list cells become nested lists
+///
+/// The column is downcast once and its values handed to Python in bulk;
+/// only types needing a Python constructor (decimals, dates) go value by
+/// value.
+fn column_to_py_list<'py>(
+    py: Python<'py>,
+    col: &polars::prelude::Column,
+) -> PyResult<Bound<'py, PyList>> {
+    use polars::prelude::*;
+
+    let polars_err =
+        |e: PolarsError| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string());
+    let series = col.as_materialized_series();
+
+    Ok(match series.dtype() {
+        DataType::Int8 => PyList::new_bound(py, series.i8().map_err(polars_err)?),
+        DataType::Int16 => PyList::new_bound(py, series.i16().map_err(polars_err)?),
+        DataType::Int32 => PyList::new_bound(py, series.i32().map_err(polars_err)?),
+        DataType::Int64 => PyList::new_bound(py, series.i64().map_err(polars_err)?),
+        DataType::UInt8 => PyList::new_bound(py, series.u8().map_err(polars_err)?),
+        DataType::UInt16 => PyList::new_bound(py, series.u16().map_err(polars_err)?),
+        DataType::UInt32 => PyList::new_bound(py, series.u32().map_err(polars_err)?),
+        DataType::UInt64 => PyList::new_bound(py, series.u64().map_err(polars_err)?),
+        DataType::Float32 => PyList::new_bound(py, series.f32().map_err(polars_err)?),
+        DataType::Float64 => PyList::new_bound(py, series.f64().map_err(polars_err)?),
+        DataType::Boolean => PyList::new_bound(py, series.bool().map_err(polars_err)?),
+        DataType::String => PyList::new_bound(py, series.str().map_err(polars_err)?),
+        DataType::Binary => PyList::new_bound(
+            py,
+            series
+                .binary()
+                .map_err(polars_err)?
+                .into_iter()
+                .map(|b| b.map(|b| PyBytes::new_bound(py, b))),
+        ),
+        DataType::List(_) => {
+            let cells = series
+                .list()
+                .map_err(polars_err)?
+                .into_iter()
+                .map(|cell| match cell {
+                    Some(inner) => Ok(column_to_py_list(py, &inner.into_column())?.into_any()),
+                    None => Ok(py.None().into_bound(py)),
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, cells)
+        }
+        DataType::Date | DataType::Datetime(_, _) | DataType::Time => {
+            let cells = series
+                .iter()
+                .map(|val| match val {
+                    AnyValue::Null => Ok(py.None()),
+                    val => temporal_to_py(py, &val),
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, cells)
+        }
+        _ => {
+            // Decimals become decimal.Decimal, anything else its text
+            // representation
+            let cells: Vec<_> = series
+                .iter()
+                .map(|val| value_to_py(py, &Value::from(val)))
+                .collect();
+            PyList::new_bound(py, cells)
+        }
+    })
+}
+
+/// Python `date`, `datetime` or `time` for a temporal value
+///
+/// Datetimes with a time zone become aware datetimes in that zone; those
+/// without stay naive.
+fn temporal_to_py(py: Python, value: &polars::prelude::AnyValue) -> PyResult<PyObject> {
+    use chrono::{Datelike, Timelike};
+    use polars::prelude::{AnyValue, TimeUnit};
+
+    let out_of_range = || {
+        PyErr::new::<pyo3::exceptions::PyOverflowError, _>(format!(
+            "{} is out of range for Python",
+            value
+        ))
+    };
+    let datetime = py.import_bound("datetime")?;
+    match value {
+        AnyValue::Date(days) => {
+            let date = chrono::DateTime::from_timestamp(*days as i64 * 86_400, 0)
+                .ok_or_else(out_of_range)?;
+            Ok(datetime
+                .getattr("date")?
+                .call1((date.year(), date.month(), date.day()))?
+                .unbind())
+        }
+        AnyValue::Datetime(v, unit, tz) => {
+            let micros = match unit {
+                TimeUnit::Nanoseconds => v.div_euclid(1_000),
+                TimeUnit::Microseconds => *v,
+                TimeUnit::Milliseconds => v * 1_000,
+            };
+            let t = chrono::DateTime::from_timestamp_micros(micros).ok_or_else(out_of_range)?;
+            let fields = (
+                t.year(),
+                t.month(),
+                t.day(),
+                t.hour(),
+                t.minute(),
+                t.second(),
+                t.timestamp_subsec_micros(),
+            );
+            let Some(tz) = tz.as_ref() else {
+                return Ok(datetime.getattr("datetime")?.call1(fields)?.unbind());
+            };
+            // Values are UTC instants, shown in their zone
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item("tzinfo", datetime.getattr("timezone")?.getattr("utc")?)?;
+            let utc = datetime.getattr("datetime")?.call(fields, Some(&kwargs))?;
+            if tz.as_str() == "UTC" {
+                return Ok(utc.unbind());
+            }
+            let zone = py
+                .import_bound("zoneinfo")?
+                .getattr("ZoneInfo")?
+                .call1((tz.as_str(),))?;
+            Ok(utc.call_method1("astimezone", (zone,))?.unbind())
+        }
+        AnyValue::Time(nanos) => {
+            let time = chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+                (nanos / 1_000_000_000) as u32,
+                (nanos % 1_000_000_000) as u32,
+            )
+            .ok_or_else(out_of_range)?;
+            Ok(datetime
+                .getattr("time")?
+                .call1((
+                    time.hour(),
+                    time.minute(),
+                    time.second(),
+                    time.nanosecond() / 1_000,
+                ))?
+                .unbind())
+        }
+        other => Ok(other.to_string().into_py(py)),
+    }
+}
+
+/// Build a Polars schema from a Python object
+///
+/// Accepts a `{name: dtype_name}` mapping, a dict of column lists (dtypes are
+/// inferred as for `insert`), or any object with a `schema` mapping such as a
+/// `polars.DataFrame`.
+/// Insert options from the `validate`, `coerce` and `audit` arguments;
+/// `coerce` is a coercion name or a dict with a `default` and per-column
+/// `columns`
+fn insert_options(
+    validate: bool,
+    coerce: Option<&Bound<'_, PyAny>>,
+    audit: Option<AuditColumns>,
+) -> PyResult<InsertOptions> {
+    let mut options = InsertOptions::default().validate(validate);
+    if let Some(audit) = audit {
+        options = options.audit(audit);
+    }
+    let Some(coerce) = coerce else {
+        return Ok(options);
+    };
+    let policy = match coerce.extract::<String>() {
+        Ok(name) => CoercionPolicy::new(name.parse::<Coercion>().map_err(to_py_err)?),
+        Err(_) => pythonize::depythonize_bound(coerce.clone()).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid coercion policy: {}",
+                e
+            ))
+        })?,
+    };
+    Ok(options.coerce(policy))
+}
+
+fn py_to_schema(data: &Bound<'_, PyAny>) -> PyResult<polars::prelude::Schema> {
+    use polars::prelude::*;
+
+    let mapping: Bound<'_, PyDict> = if let Ok(dict) = data.downcast::<PyDict>() {
+        let is_dtype_mapping = dict.values().iter().all(|v| v.extract::<String>().is_ok());
+        if !is_dtype_mapping {
+            let df = py_dict_to_dataframe(dict)?;
+            return Ok(Schema::from_iter(
+                df.get_columns()
+                    .iter()
+                    .map(|c| Field::new(c.name().clone(), c.dtype().clone())),
+            ));
+        }
+        dict.clone()
+    } else {
+        let schema = data.getattr("schema")?;
+        let dict = PyDict::new_bound(data.py());
+        for item in schema.call_method0("items")?.iter()? {
+            let (name, dtype): (String, Bound<'_, PyAny>) = item?.extract()?;
+            dict.set_item(name, dtype.str()?)?;
+        }
+        dict
+    };
+
+    let mut fields = Vec::with_capacity(mapping.len());
+    for (key, value) in mapping.iter() {
+        let name: String = key.extract()?;
+        let dtype_name: String = value.extract()?;
+        let dtype = industrydb_core::schema::parse_dtype(&dtype_name).map_err(to_py_err)?;
+        fields.push(Field::new(name.as_str().into(), dtype));
+    }
+    Ok(Schema::from_iter(fields))
+}
+
+/// Column dtypes of a `{name: dtype}` mapping
+///
+/// Dtypes are names as accepted by `parse_dtype` or Polars dtype objects,
+/// which are read by their repr.
+fn py_to_dtypes(schema: &Bound<'_, PyDict>) -> PyResult<Vec<(String, polars::prelude::DataType)>> {
+    schema
+        .iter()
+        .map(|(key, value)| {
+            let dtype_name = match value.extract::<String>() {
+                Ok(name) => name,
+                Err(_) => value.str()?.to_string(),
+            };
+            let dtype = industrydb_core::schema::parse_dtype(&dtype_name).map_err(to_py_err)?;
+            Ok((key.extract()?, dtype))
+        })
+        .collect()
+}
+
+/// Resolve Python parameters against a statement
+///
+/// A sequence is bound positionally to the database's own placeholders. A
+/// dict binds `:name` / `@name` parameters, which are rewritten to those
+/// placeholders. Returns the statement to run and the values in order.
+pub(crate) fn resolve_params(
+    sql: &str,
+    params: Option<&Bound<'_, PyAny>>,
+    dialect: &dyn Dialect,
+) -> PyResult<(String, Vec<Value>)> {
+    let Some(params) = params else {
+        return Ok((sql.to_string(), Vec::new()));
+    };
+
+    if let Ok(dict) = params.downcast::<PyDict>() {
+        let named = dict
+            .iter()
+            .map(|(k, v)| Ok((k.extract::<String>()?, py_to_value(&v)?)))
+            .collect::<PyResult<HashMap<_, _>>>()?;
+        return bind_named(sql, &named, dialect).map_err(to_py_err);
+    }
+
+    let values = params
+        .iter()?
+        .map(|item| py_to_value(&item?))
+        .collect::<PyResult<_>>()?;
+    Ok((sql.to_string(), values))
+}
+
+/// Resolve Python parameters against an optional WHERE clause
+fn resolve_where_params(
+    where_clause: Option<String>,
+    params: Option<&Bound<'_, PyAny>>,
+    dialect: &dyn Dialect,
+) -> PyResult<(Option<String>, Vec<Value>)> {
+    let (clause, values) = resolve_params(where_clause.as_deref().unwrap_or(""), params, dialect)?;
+    Ok((where_clause.map(|_| clause), values))
+}
+
+/// Resolve Python parameters against the WHERE and HAVING clauses of a select
+///
+/// Positional parameters cover WHERE first, then HAVING. Named parameters
+/// may appear in either; the clauses are bound together so each name maps
+/// to one placeholder.
+fn resolve_select_params(
+    where_clause: Option<String>,
+    having: Option<String>,
+    params: Option<&Bound<'_, PyAny>>,
+    dialect: &dyn Dialect,
+) -> PyResult<(Option<String>, Option<String>, Vec<Value>)> {
+    let Some(having) = having else {
+        let (where_clause, values) = resolve_where_params(where_clause, params, dialect)?;
+        return Ok((where_clause, None, values));
+    };
+
+    // SQL text never contains NUL, so it separates the two clauses
+    let joined = format!("{}\0{}", where_clause.as_deref().unwrap_or(""), having);
+    let (bound, values) = resolve_params(&joined, params, dialect)?;
+    let (where_bound, having_bound) = bound
+        .split_once('\0')
+        .expect("separator survives parameter binding");
+    Ok((
+        where_clause.map(|_| where_bound.to_string()),
+        Some(having_bound.to_string()),
+        values,
+    ))
+}
+
+/// Resolve a select `order_by`: a clause body such as `"ts DESC"`, or a list
+/// of column names and dicts with `column`, `descending`, `nulls` and
+/// `collation` keys
+fn resolve_order_by(order_by: &Bound<'_, PyAny>, dialect: &dyn Dialect) -> PyResult<String> {
+    if let Ok(clause) = order_by.extract::<String>() {
+        return Ok(clause);
+    }
+
+    let orders = order_by
+        .iter()?
+        .map(|item| {
+            let item = item?;
+            if let Ok(column) = item.extract::<String>() {
+                return order_spec(&column, false, None, None);
+            }
+            let spec = item.downcast::<PyDict>()?;
+            let get = |key: &str| spec.get_item(key).map(|v| v.filter(|v| !v.is_none()));
+            let column: String = get("column")?
+                .ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "order_by entries need a 'column'",
+                    )
+                })?
+                .extract()?;
+            let descending = get("descending")?
+                .map(|v| v.extract::<bool>())
+                .transpose()?
+                .unwrap_or(false);
+            let nulls = get("nulls")?.map(|v| v.extract::<String>()).transpose()?;
+            let collation = get("collation")?
+                .map(|v| v.extract::<String>())
+                .transpose()?;
+            order_spec(&column, descending, nulls.as_deref(), collation.as_deref())
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    order_by_sql(&orders, dialect).map_err(to_py_err)
+}
+
+/// Convert a Python parameter to a bound value
+///
+/// Values other than None, bool, int, float, str and bytes (dates, for
+/// example) are bound as their `str()`.
+pub(crate) fn py_to_value(item: &Bound<'_, PyAny>) -> PyResult<Value> {
+    Ok(if item.is_none() {
+        Value::Null
+    } else if let Ok(v) = item.downcast::<PyBool>() {
+        Value::Bool(v.is_true())
+    } else if let Ok(v) = item.extract::<i64>() {
+        Value::Int(v)
+    } else if item.is_instance_of::<PyLong>() {
+        // Beyond i64, kept exact as a decimal
+        let text: String = item.str()?.extract()?;
+        decimal::parse(&text).map_err(to_py_err)?;
+        Value::Decimal(text)
+    } else if item.is_instance(&py_type(item.py(), "decimal", "Decimal")?)? {
+        let text: String = item.str()?.extract()?;
+        decimal::parse(&text).map_err(to_py_err)?;
+        Value::Decimal(text)
+    } else if item.is_instance(&py_type(item.py(), "uuid", "UUID")?)? {
+        Value::Uuid(item.str()?.extract()?)
+    } else if let Ok(v) = item.extract::<f64>() {
+        Value::Float(v)
+    } else if let Ok(v) = item.extract::<String>() {
+        Value::Text(v)
+    } else if let Ok(v) = item.downcast::<PyBytes>() {
+        Value::Bytes(v.as_bytes().to_vec())
+    } else {
+        Value::Text(item.str()?.extract()?)
+    })
+}
+
+/// Buckets of `value_columns` as `select_bucketed` and continuous
+/// aggregates take them
+fn time_buckets(
+    table: &str,
+    ts_column: &str,
+    value_columns: Vec<String>,
+    bucket: &str,
+    aggs: Option<Vec<String>>,
+) -> PyResult<TimeBuckets> {
+    let width = parse_interval(bucket).map_err(to_py_err)?;
+    let buckets = TimeBuckets::new(table, ts_column, value_columns, width);
+    let Some(aggs) = aggs else {
+        return Ok(buckets);
+    };
+    let aggregates = aggs
+        .iter()
+        .map(|a| a.parse::<Aggregate>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(to_py_err)?;
+    Ok(buckets.aggregates(aggregates))
+}
+
+/// Naive timestamp for a `datetime`, a `date` or timestamp text
+///
+/// Aware datetimes are converted to UTC first.
+pub(crate) fn py_to_timestamp(item: &Bound<'_, PyAny>) -> PyResult<chrono::NaiveDateTime> {
+    let text: String = if item.hasattr("tzinfo")? && !item.getattr("tzinfo")?.is_none() {
+        let utc = py_type(item.py(), "datetime", "timezone")?.getattr("utc")?;
+        item.call_method1("astimezone", (utc,))?
+            .call_method1("strftime", ("%Y-%m-%d %H:%M:%S.%f",))?
+            .extract()?
+    } else {
+        item.str()?.extract()?
+    };
+    parse_timestamp(&text).map_err(to_py_err)
+}
+
+/// Convert a value read from the database to a Python object
+pub(crate) fn value_to_py(py: Python, value: &Value) -> PyObject {
+    match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Int(n) => n.into_py(py),
+        Value::Float(f) => f.into_py(py),
+        Value::Text(s) | Value::Uuid(s) => s.into_py(py),
+        Value::Bytes(b) => PyBytes::new_bound(py, b).into_py(py),
+        Value::Decimal(d) => decimal_to_py(py, d).unwrap_or_else(|_| d.into_py(py)),
+    }
+}
+
+/// Python type `name` of standard library module `module`
+fn py_type<'py>(py: Python<'py>, module: &str, name: &str) -> PyResult<Bound<'py, PyAny>> {
+    py.import_bound(module)?.getattr(name)
+}
+
+/// `decimal.Decimal` for decimal text
+fn decimal_to_py(py: Python, text: &str) -> PyResult<PyObject> {
+    Ok(py_type(py, "decimal", "Decimal")?.call1((text,))?.unbind())
+}
+
+/// Convert Python dict to Polars DataFrame
+pub(crate) fn py_dict_to_dataframe(
+    data: &Bound<'_, PyDict>,
+) -> PyResult<polars::prelude::DataFrame> {
+    use polars::prelude::*;
+
+    let mut series_vec: Vec<Series> = Vec::new();
+
+    for (key, value) in data.iter() {
+        let col_name: String = key.extract()?;
+        let list: &Bound<'_, PyList> = value.downcast()?;
+        series_vec.push(py_list_to_series(&col_name, list)?);
+    }
+
+    DataFrame::new(series_vec.into_iter().map(|s| s.into_column()).collect())
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+}
+
+/// Series of the values of a Python list, typed from the values
+///
+/// When every value is a list (or None), the result is a list column whose
+/// elements are typed together.
+fn py_list_to_series(
+    col_name: &str,
+    list: &Bound<'_, PyList>,
+) -> PyResult<polars::prelude::Series> {
+    use polars::prelude::*;
+
+    let present = || list.iter().filter(|item| !item.is_none());
+    if present().next().is_some() && present().all(|item| item.is_instance_of::<PyList>()) {
+        let elements = PyList::empty_bound(list.py());
+        let mut lengths: Vec<Option<usize>> = Vec::new();
+        for item in list.iter() {
+            if item.is_none() {
+                lengths.push(None);
+            } else {
+                let cell: &Bound<'_, PyList> = item.downcast()?;
+                for element in cell.iter() {
+                    elements.append(element)?;
+                }
+                lengths.push(Some(cell.len()));
+            }
+        }
+        let elements = py_list_to_series("", &elements)?;
+        let mut offset = 0;
+        let cells: Vec<Option<Series>> = lengths
+            .into_iter()
+            .map(|len| {
+                len.map(|len| {
+                    let cell = elements.slice(offset as i64, len);
+                    offset += len;
+                    cell
+                })
+            })
+            .collect();
+        return Series::new(col_name.into(), cells)
+            .cast(&DataType::List(Box::new(elements.dtype().clone())))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()));
+    }
+
+    // Try to infer type from first non-null value
+    let mut values_int: Vec<Option<i128>> = Vec::new();
+    let mut values_f64: Vec<Option<f64>> = Vec::new();
+    let mut values_str: Vec<Option<String>> = Vec::new();
+    let mut values_bin: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut is_int = true;
+    let mut is_wide = false;
+    let mut is_float = true;
+    let mut is_binary = true;
+
+    for item in list.iter() {
+        if item.is_none() {
+            values_int.push(None);
+            values_f64.push(None);
+            values_str.push(None);
+            values_bin.push(None);
+        } else if let Ok(val) = item.downcast::<PyBytes>() {
+            is_int = false;
+            is_float = false;
+            values_str.push(Some(String::from_utf8_lossy(val.as_bytes()).into_owned()));
+            values_bin.push(Some(val.as_bytes().to_vec()));
+        } else if let Ok(val) = item.extract::<i64>() {
+            is_binary = false;
+            values_int.push(Some(val as i128));
+            values_f64.push(Some(val as f64));
+            values_str.push(Some(val.to_string()));
+        } else if item.is_instance_of::<PyLong>() {
+            let text: String = item.str()?.extract()?;
+            let (val, _) = decimal::parse(&text).map_err(to_py_err)?;
+            is_wide = true;
+            is_binary = false;
+            values_int.push(Some(val));
+            values_f64.push(Some(val as f64));
+            values_str.push(Some(text));
+        } else if let Ok(val) = item.extract::<f64>() {
+            is_int = false;
+            is_binary = false;
+            values_f64.push(Some(val));
+            values_str.push(Some(val.to_string()));
+        } else if let Ok(val) = item.extract::<String>() {
+            is_int = false;
+            is_float = false;
+            is_binary = false;
+            values_str.push(Some(val));
+        } else {
+            is_int = false;
+            is_float = false;
+            is_binary = false;
+            values_str.push(Some(item.str()?.extract()?));
+        }
+    }
+
+    let series = if is_int && !is_wide {
+        let values: Vec<Option<i64>> = values_int.iter().map(|v| v.map(|v| v as i64)).collect();
+        Series::new(col_name.into(), values)
+    } else if is_int {
+        // Ints beyond i64 stay exact: UInt64 when they all fit, else decimals
+        let unsigned: Option<Vec<Option<u64>>> = values_int
+            .iter()
+            .map(|v| v.map(u64::try_from).transpose().ok())
+            .collect();
+        match unsigned {
+            Some(values) => Series::new(col_name.into(), values),
+            None => {
+                let values: Vec<_> = values_int.iter().map(|v| v.map(|v| (v, 0))).collect();
+                decimal::to_series(col_name, &values).map_err(to_py_err)?
+            }
+        }
+    } else if is_float {
+        Series::new(col_name.into(), values_f64)
+    } else if is_binary {
+        BinaryChunked::from_iter_options(col_name.into(), values_bin.into_iter()).into_series()
+    } else {
+        Series::new(col_name.into(), values_str)
+    };
+
+    Ok(series)
 }
@@ -0,0 +1,195 @@
+//! Python bindings for the dashboard query kit
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::time::Duration;
+
+use crate::connection::{dataframe_to_py_dict, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_cache::{CacheConfig, Dashboard, DashboardConfig, Panel, QueryCache};
+use industrydb_core::time::{parse_interval, parse_timestamp};
+
+/// Cached latest values, trends and alarm counts for HMI screens
+#[pyclass(name = "Dashboard")]
+pub struct PyDashboard {
+    conn: Py<PyConnection>,
+    inner: Dashboard,
+    stale: bool,
+    last_error: Option<String>,
+}
+
+#[pymethods]
+impl PyDashboard {
+    /// Create a dashboard reading through `conn`
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (conn, table="readings", time_column="ts", tag_column="tag", value_column="value", alarm_table="alarms", alarm_time_column="ts", ttl=5.0, max_stale=None, redis_url=None))]
+    fn new(
+        conn: Py<PyConnection>,
+        table: &str,
+        time_column: &str,
+        tag_column: &str,
+        value_column: &str,
+        alarm_table: &str,
+        alarm_time_column: &str,
+        ttl: f64,
+        max_stale: Option<f64>,
+        redis_url: Option<&str>,
+    ) -> PyResult<Self> {
+        let seconds = |name: &str, value: f64| {
+            Duration::try_from_secs_f64(value).map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "{} must be a non-negative number",
+                    name
+                ))
+            })
+        };
+        let config = DashboardConfig {
+            table: table.to_string(),
+            time_column: time_column.to_string(),
+            tag_column: tag_column.to_string(),
+            value_column: value_column.to_string(),
+            alarm_table: alarm_table.to_string(),
+            alarm_time_column: alarm_time_column.to_string(),
+            ttl: seconds("ttl", ttl)?,
+            max_stale: max_stale.map(|s| seconds("max_stale", s)).transpose()?,
+        };
+
+        let inner = match redis_url {
+            None => Dashboard::new(config),
+            #[cfg(feature = "redis")]
+            Some(url) => {
+                let cache_config = CacheConfig {
+                    ttl: config.ttl,
+                    prefix: "industrydb:dashboard".to_string(),
+                    ..Default::default()
+                };
+                let runtime = Python::with_gil(|py| conn.borrow(py).runtime.clone());
+                let cache = runtime
+                    .block_on(QueryCache::redis(url, cache_config))
+                    .map_err(to_py_err)?;
+                Dashboard::with_cache(config, cache)
+            }
+            #[cfg(not(feature = "redis"))]
+            Some(_) => {
+                return Err(to_py_err(
+                    industrydb_core::error::IndustryDbError::config_error(
+                        "Redis caching is not included in this build of industrydb; \
+                     install it with `pip install industrydb[all]`",
+                    ),
+                ))
+            }
+        };
+
+        Ok(Self {
+            conn,
+            inner,
+            stale: false,
+            last_error: None,
+        })
+    }
+
+    /// Latest reading of each tag, or of every tag when `tags` is None
+    #[pyo3(signature = (tags=None))]
+    fn latest(&mut self, py: Python, tags: Option<Vec<String>>) -> PyResult<Py<PyDict>> {
+        let conn = self.conn.borrow(py);
+        let connector = conn.connector()?;
+        let panel = conn
+            .runtime
+            .block_on(self.inner.latest(connector, &tags.unwrap_or_default()))
+            .map_err(to_py_err)?;
+        drop(conn);
+        self.show(py, panel)
+    }
+
+    /// Readings averaged per tag and interval over `start`..`end`, or over
+    /// the last `window`
+    #[pyo3(signature = (tags=None, start=None, end=None, window="1h", interval="1m"))]
+    fn trend(
+        &mut self,
+        py: Python,
+        tags: Option<Vec<String>>,
+        start: Option<&str>,
+        end: Option<&str>,
+        window: &str,
+        interval: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let interval = parse_interval(interval).map_err(to_py_err)?;
+        let (start, end) = time_range(start, end, window, interval)?;
+        let conn = self.conn.borrow(py);
+        let connector = conn.connector()?;
+        let panel = conn
+            .runtime
+            .block_on(
+                self.inner
+                    .trend(connector, &tags.unwrap_or_default(), start, end, interval),
+            )
+            .map_err(to_py_err)?;
+        drop(conn);
+        self.show(py, panel)
+    }
+
+    /// Alarms per value of `by` over `start`..`end`, or over the last `window`
+    #[pyo3(signature = (start=None, end=None, window="24h", by="tag"))]
+    fn alarm_counts(
+        &mut self,
+        py: Python,
+        start: Option<&str>,
+        end: Option<&str>,
+        window: &str,
+        by: &str,
+    ) -> PyResult<Py<PyDict>> {
+        // Align to the minute so refreshes share cached counts
+        let minute = chrono::TimeDelta::minutes(1);
+        let (start, end) = time_range(start, end, window, minute)?;
+        let conn = self.conn.borrow(py);
+        let connector = conn.connector()?;
+        let panel = conn
+            .runtime
+            .block_on(self.inner.alarm_counts(connector, start, end, by))
+            .map_err(to_py_err)?;
+        drop(conn);
+        self.show(py, panel)
+    }
+
+    /// Whether the last result was served stale because the database failed
+    #[getter]
+    fn stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Database error behind the last stale result
+    #[getter]
+    fn last_error(&self) -> Option<String> {
+        self.last_error.clone()
+    }
+}
+
+impl PyDashboard {
+    fn show(&mut self, py: Python, panel: Panel) -> PyResult<Py<PyDict>> {
+        self.stale = panel.stale;
+        self.last_error = panel.error;
+        dataframe_to_py_dict(py, &panel.frame)
+    }
+}
+
+/// Explicit `start`/`end`, or the last `window` aligned to `interval`
+fn time_range(
+    start: Option<&str>,
+    end: Option<&str>,
+    window: &str,
+    interval: chrono::TimeDelta,
+) -> PyResult<(chrono::NaiveDateTime, chrono::NaiveDateTime)> {
+    let window = parse_interval(window).map_err(to_py_err)?;
+    let (recent_start, recent_end) = Dashboard::recent(window, interval);
+    let end = match end {
+        Some(end) => parse_timestamp(end).map_err(to_py_err)?,
+        None => recent_end,
+    };
+    let start = match start {
+        Some(start) => parse_timestamp(start).map_err(to_py_err)?,
+        None if end == recent_end => recent_start,
+        None => end - window,
+    };
+    Ok((start, end))
+}
@@ -13,6 +13,8 @@ create_exception!(industrydb, QueryExecutionError, IndustryDbError);
 create_exception!(industrydb, ConfigurationError, IndustryDbError);
 create_exception!(industrydb, ConnectionClosedError, IndustryDbError);
 create_exception!(industrydb, ConstraintViolationError, IndustryDbError);
+create_exception!(industrydb, SqlParseError, QueryExecutionError);
+create_exception!(industrydb, AccessDeniedError, IndustryDbError);
 
 /// Convert core errors to Python exceptions
 pub fn to_py_err(err: CoreError) -> PyErr {
@@ -24,6 +26,8 @@ pub fn to_py_err(err: CoreError) -> PyErr {
             PyErr::new::<ConnectionClosedError, _>("Connection is closed")
         }
         CoreError::ConstraintViolation(msg) => PyErr::new::<ConstraintViolationError, _>(msg),
+        CoreError::SqlParseError(msg) => PyErr::new::<SqlParseError, _>(msg),
+        CoreError::AccessDenied(msg) => PyErr::new::<AccessDeniedError, _>(msg),
         CoreError::InvalidParameter(msg) => {
             PyErr::new::<IndustryDbError, _>(format!("Invalid parameter: {}", msg))
         }
@@ -0,0 +1,112 @@
+//! Python bindings for publishing to Kafka
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+use crate::backfill::PyBackfillControl;
+use crate::connection::{py_dict_to_dataframe, resolve_params, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::time::parse_interval;
+use industrydb_pipeline::kafka::DEFAULT_ARROW_MESSAGE_ROWS;
+use industrydb_pipeline::{KafkaSink, KafkaSinkConfig};
+
+/// Publishes rows to a Kafka topic as JSON or Arrow messages
+#[pyclass(name = "KafkaSink")]
+pub struct PyKafkaSink {
+    inner: KafkaSink,
+    runtime: Arc<Runtime>,
+}
+
+#[pymethods]
+impl PyKafkaSink {
+    /// Connect to `brokers` and look up the partitions of `topic`
+    #[new]
+    #[pyo3(signature = (brokers, topic, format="json", key_column=None, arrow_message_rows=DEFAULT_ARROW_MESSAGE_ROWS))]
+    fn new(
+        py: Python,
+        brokers: Vec<String>,
+        topic: &str,
+        format: &str,
+        key_column: Option<String>,
+        arrow_message_rows: usize,
+    ) -> PyResult<Self> {
+        let mut config = KafkaSinkConfig::new(brokers, topic);
+        config.format = format.parse().map_err(to_py_err)?;
+        config.key_column = key_column;
+        config.arrow_message_rows = arrow_message_rows;
+
+        let runtime = Arc::new(Runtime::new().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to create runtime: {}",
+                e
+            ))
+        })?);
+        let inner = py
+            .allow_threads(|| runtime.block_on(KafkaSink::connect(config)))
+            .map_err(to_py_err)?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Publish rows given as a dict of columns; returns the number of
+    /// messages sent
+    #[pyo3(signature = (data, table=None))]
+    fn publish(
+        &self,
+        py: Python,
+        data: &Bound<'_, PyDict>,
+        table: Option<&str>,
+    ) -> PyResult<usize> {
+        let df = py_dict_to_dataframe(data)?;
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(self.inner.publish(&df, table)))
+            .map_err(to_py_err)
+    }
+
+    /// Run a query and publish its result, every `interval` until `control`
+    /// is cancelled, or once
+    ///
+    /// Runs without holding the GIL, so another thread can cancel it
+    /// through `control`.
+    #[pyo3(signature = (conn, sql, params=None, interval=None, control=None))]
+    fn publish_query(
+        &self,
+        py: Python,
+        conn: PyRef<'_, PyConnection>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        interval: Option<&str>,
+        control: Option<PyBackfillControl>,
+    ) -> PyResult<usize> {
+        let interval = interval
+            .map(|i| {
+                let interval = parse_interval(i).map_err(to_py_err)?;
+                interval.to_std().map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Publish interval must not be negative: {}",
+                        i
+                    ))
+                })
+            })
+            .transpose()?;
+        let control = control.map(|c| c.inner).unwrap_or_default();
+
+        let connector = conn.connector()?;
+        let (sql, params) = resolve_params(sql, params, connector.dialect())?;
+        let runtime = conn.runtime.clone();
+        py.allow_threads(|| {
+            runtime.block_on(
+                self.inner
+                    .publish_query(connector, &sql, &params, interval, &control),
+            )
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Topic the sink publishes to
+    #[getter]
+    fn topic(&self) -> String {
+        self.inner.config().topic.clone()
+    }
+}
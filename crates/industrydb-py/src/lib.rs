@@ -4,13 +4,51 @@
 
 use pyo3::prelude::*;
 
+mod backfill;
+#[cfg(feature = "postgres")]
+mod cdc;
+mod changes;
 mod config;
 mod connection;
+mod dashboard;
 mod errors;
+mod kafka;
+#[cfg(feature = "postgres")]
+mod notify;
+mod pipeline;
+mod procedure;
+mod quality;
+mod query;
+mod reader;
+mod replay;
+mod result;
+mod scheduler;
+mod sql;
+mod storage;
+mod synth;
+mod transfer;
+mod writer;
 
 use config::PyDatabaseConfig;
 use connection::PyConnection;
 
+/// Database types whose connectors are compiled into this build, followed by
+/// the connectors of loaded plugins
+#[pyfunction]
+fn available_connectors() -> Vec<String> {
+    connection::available_connectors()
+}
+
+/// Load a connector plugin from a shared library and return the names of
+/// the connectors it registered
+#[cfg(feature = "plugins")]
+#[pyfunction]
+fn load_plugin(path: std::path::PathBuf) -> PyResult<Vec<String>> {
+    // SAFETY: loading runs the library's code; callers only pass plugins
+    // they trust, as documented on the Python side
+    unsafe { industrydb_core::plugin::load_plugin(path) }.map_err(errors::to_py_err)
+}
+
 /// IndustryDB - High-performance database middleware
 #[pymodule]
 fn industrydb(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
@@ -21,6 +59,41 @@ fn industrydb(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Classes
     m.add_class::<PyDatabaseConfig>()?;
     m.add_class::<PyConnection>()?;
+    m.add_class::<backfill::PyBackfillControl>()?;
+    m.add_class::<query::PyExpr>()?;
+    m.add_class::<query::PyQuery>()?;
+    m.add_class::<result::PyQueryResult>()?;
+    m.add_class::<pipeline::PyPipeline>()?;
+    m.add_class::<procedure::PyOutput>()?;
+    #[cfg(feature = "postgres")]
+    m.add_class::<notify::PySubscription>()?;
+    #[cfg(feature = "postgres")]
+    m.add_class::<cdc::PyChangeFeed>()?;
+    m.add_class::<changes::PyChangePoller>()?;
+    m.add_class::<dashboard::PyDashboard>()?;
+    m.add_class::<kafka::PyKafkaSink>()?;
+    m.add_class::<writer::PyBufferedWriter>()?;
+    m.add_class::<scheduler::PyScheduler>()?;
+
+    // Functions
+    m.add_function(wrap_pyfunction!(sql::parse_sql, m)?)?;
+    m.add_function(wrap_pyfunction!(sql::validate_sql, m)?)?;
+    m.add_function(wrap_pyfunction!(storage::read_object_store, m)?)?;
+    m.add_function(wrap_pyfunction!(storage::tier_to_object_store, m)?)?;
+    m.add_function(wrap_pyfunction!(storage::read_snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(storage::list_snapshots, m)?)?;
+    m.add_function(wrap_pyfunction!(backfill::backfill, m)?)?;
+    m.add_function(wrap_pyfunction!(transfer::copy_table, m)?)?;
+    m.add_function(wrap_pyfunction!(transfer::sync_table, m)?)?;
+    m.add_function(wrap_pyfunction!(replay::replay, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::ingest_opcua_history, m)?)?;
+    m.add_function(wrap_pyfunction!(synth::generate_synthetic, m)?)?;
+    m.add_function(wrap_pyfunction!(quality::check_frame, m)?)?;
+    m.add_function(wrap_pyfunction!(available_connectors, m)?)?;
+    #[cfg(feature = "plugins")]
+    m.add_function(wrap_pyfunction!(load_plugin, m)?)?;
+    m.add_function(wrap_pyfunction!(query::col, m)?)?;
+    m.add_function(wrap_pyfunction!(query::param, m)?)?;
 
     // Exceptions
     m.add(
@@ -47,6 +120,14 @@ fn industrydb(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
         "ConstraintViolationError",
         py.get_type_bound::<errors::ConstraintViolationError>(),
     )?;
+    m.add(
+        "SqlParseError",
+        py.get_type_bound::<errors::SqlParseError>(),
+    )?;
+    m.add(
+        "AccessDeniedError",
+        py.get_type_bound::<errors::AccessDeniedError>(),
+    )?;
 
     Ok(())
 }
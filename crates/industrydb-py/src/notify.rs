@@ -0,0 +1,133 @@
+//! Python bindings for PostgreSQL LISTEN/NOTIFY subscriptions
+
+use pyo3::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+
+use crate::connection::to_python;
+use crate::errors::to_py_err;
+use industrydb_core::config::ConnectionConfig;
+use industrydb_core::error::IndustryDbError;
+use industrydb_postgres::Subscription;
+
+/// How long a blocking receive waits before checking for Ctrl-C
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Notifications of the PostgreSQL channels listened on
+#[pyclass(name = "Subscription")]
+pub struct PySubscription {
+    inner: Option<Subscription>,
+    runtime: Arc<Runtime>,
+}
+
+impl PySubscription {
+    pub(crate) fn connect(
+        runtime: Arc<Runtime>,
+        config: &ConnectionConfig,
+        channels: &[String],
+    ) -> PyResult<Self> {
+        let channels: Vec<&str> = channels.iter().map(String::as_str).collect();
+        let inner = runtime
+            .block_on(Subscription::connect(config, &channels))
+            .map_err(to_py_err)?;
+        Ok(Self {
+            inner: Some(inner),
+            runtime,
+        })
+    }
+
+    fn subscription(&mut self) -> PyResult<&mut Subscription> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| to_py_err(IndustryDbError::ConnectionClosed))
+    }
+}
+
+#[pymethods]
+impl PySubscription {
+    /// Also listen on `channel`
+    fn listen(&mut self, channel: &str) -> PyResult<()> {
+        let runtime = self.runtime.clone();
+        let inner = self.subscription()?;
+        runtime.block_on(inner.listen(channel)).map_err(to_py_err)
+    }
+
+    /// Stop listening on `channel`
+    fn unlisten(&mut self, channel: &str) -> PyResult<()> {
+        let runtime = self.runtime.clone();
+        let inner = self.subscription()?;
+        runtime.block_on(inner.unlisten(channel)).map_err(to_py_err)
+    }
+
+    /// Next notification as a dict, or None once `timeout` seconds pass;
+    /// waits indefinitely without a timeout
+    #[pyo3(signature = (timeout=None))]
+    fn recv(&mut self, py: Python, timeout: Option<f64>) -> PyResult<Option<PyObject>> {
+        let deadline = timeout
+            .map(|t| {
+                Duration::try_from_secs_f64(t).map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "timeout must be a non-negative number",
+                    )
+                })
+            })
+            .transpose()?
+            .map(|t| Instant::now() + t);
+        let runtime = self.runtime.clone();
+        let inner = self.subscription()?;
+
+        // Wait in slices so Ctrl-C interrupts a blocked receive
+        loop {
+            let slice = match deadline {
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .min(SIGNAL_CHECK_INTERVAL),
+                None => SIGNAL_CHECK_INTERVAL,
+            };
+            let received = py
+                .allow_threads(|| runtime.block_on(inner.recv_timeout(slice)))
+                .map_err(to_py_err)?;
+            if let Some(notification) = received {
+                return to_python(py, &notification).map(Some);
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(None);
+            }
+            py.check_signals()?;
+        }
+    }
+
+    /// Stop listening and release the connection
+    fn close(&mut self) {
+        self.inner = None;
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Next notification, waiting indefinitely; iteration ends once closed
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        if self.inner.is_none() {
+            return Ok(None);
+        }
+        self.recv(py, None)
+    }
+
+    /// Context manager entry
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Context manager exit
+    fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
@@ -0,0 +1,130 @@
+//! Python bindings for ingestion pipelines
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::connection::{py_dict_to_dataframe, to_python, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::traits::DatabaseConnector;
+use industrydb_pipeline::{HistoryLayout, PipelineConfig, PipelineRunner};
+
+/// Ingestion pipeline loaded from a TOML or YAML file
+#[pyclass(name = "Pipeline")]
+pub struct PyPipeline {
+    inner: PipelineRunner,
+}
+
+#[pymethods]
+impl PyPipeline {
+    /// Load a pipeline from a `.toml`, `.yaml` or `.yml` file
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        let inner = PipelineRunner::from_path(&path).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Build a pipeline from a dict with the same layout as the file
+    #[staticmethod]
+    fn from_dict(config: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let config: PipelineConfig = pythonize::depythonize_bound(config.clone().into_any())
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid pipeline config: {}",
+                    e
+                ))
+            })?;
+        let inner = PipelineRunner::new(config).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Pipeline name
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.config().name.clone()
+    }
+
+    /// Counters so far, as a dict
+    #[getter]
+    fn metrics(&self, py: Python) -> PyResult<PyObject> {
+        to_python(py, &self.inner.metrics())
+    }
+
+    /// Run until the source is exhausted, `stop()` is called, or a failure
+    /// cannot be restarted; returns the final metrics
+    ///
+    /// Runs without holding the GIL, so another thread can call `stop()` or
+    /// read `metrics`. Table sources read from `source`.
+    #[pyo3(signature = (target, source=None))]
+    fn run(
+        &self,
+        py: Python,
+        target: PyRef<'_, PyConnection>,
+        source: Option<PyRef<'_, PyConnection>>,
+    ) -> PyResult<PyObject> {
+        let target_conn = target.connector()?;
+        let source_conn = source
+            .as_ref()
+            .map(|s| s.connector().map(|c| c as &dyn DatabaseConnector))
+            .transpose()?;
+        let runtime = target.runtime.clone();
+
+        let metrics = py
+            .allow_threads(|| runtime.block_on(self.inner.run(target_conn, source_conn)))
+            .map_err(to_py_err)?;
+        to_python(py, &metrics)
+    }
+
+    /// Stop after the current batch
+    fn stop(&self) {
+        self.inner.control().stop()
+    }
+}
+
+/// Map an OPC UA history read result set to a table layout and insert it
+///
+/// `history` holds `node_id`, `timestamp`, `value` and optionally `quality`
+/// columns. Returns a summary dict.
+#[pyfunction]
+#[pyo3(signature = (
+    conn, history, table, layout="long", tags=None, tag_column="tag", time_column="ts",
+    value_column="value", quality_column=Some("quality"), min_quality="all", create_table=true
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn ingest_opcua_history(
+    py: Python,
+    conn: PyRef<'_, PyConnection>,
+    history: &Bound<'_, PyDict>,
+    table: &str,
+    layout: &str,
+    tags: Option<HashMap<String, String>>,
+    tag_column: &str,
+    time_column: &str,
+    value_column: &str,
+    quality_column: Option<&str>,
+    min_quality: &str,
+    create_table: bool,
+) -> PyResult<PyObject> {
+    let mut config = HistoryLayout::new(table);
+    config.shape = layout.parse().map_err(to_py_err)?;
+    config.tags = tags.unwrap_or_default();
+    config.tag_column = tag_column.to_string();
+    config.time_column = time_column.to_string();
+    config.value_column = value_column.to_string();
+    config.quality_column = quality_column.map(str::to_string);
+    config.min_quality = min_quality.parse().map_err(to_py_err)?;
+    config.create_table = create_table;
+
+    let history = py_dict_to_dataframe(history)?;
+    let connector = conn.connector()?;
+    let runtime = conn.runtime.clone();
+    let summary = py
+        .allow_threads(|| {
+            runtime.block_on(industrydb_pipeline::ingest_history(
+                connector, &history, &config,
+            ))
+        })
+        .map_err(to_py_err)?;
+    to_python(py, &summary)
+}
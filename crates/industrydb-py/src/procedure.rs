@@ -0,0 +1,129 @@
+//! Python bindings for stored procedure and function arguments
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::connection::{py_to_value, value_to_py};
+use industrydb_core::params::Value;
+use industrydb_core::procedure::{ParamMode, ProcedureArg};
+
+/// Marks a procedure argument as an output parameter
+///
+/// With a `value`, the argument is passed in as well (INOUT).
+#[pyclass(name = "Output")]
+#[derive(Clone)]
+pub struct PyOutput {
+    sql_type: Option<String>,
+    value: Option<Value>,
+}
+
+#[pymethods]
+impl PyOutput {
+    #[new]
+    #[pyo3(signature = (sql_type=None, value=None))]
+    fn new(sql_type: Option<String>, value: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        Ok(Self {
+            sql_type,
+            value: value.map(py_to_value).transpose()?,
+        })
+    }
+
+    #[getter]
+    fn sql_type(&self) -> Option<String> {
+        self.sql_type.clone()
+    }
+
+    #[getter]
+    fn value(&self, py: Python) -> PyObject {
+        self.value
+            .as_ref()
+            .map_or_else(|| py.None(), |v| value_to_py(py, v))
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.sql_type {
+            Some(ty) => format!("Output('{}')", ty),
+            None => "Output()".to_string(),
+        }
+    }
+}
+
+/// Procedure arguments from a list of positional values or a dict of named
+/// ones; `Output` values become output parameters
+pub(crate) fn procedure_args(args: Option<&Bound<'_, PyAny>>) -> PyResult<Vec<ProcedureArg>> {
+    let Some(args) = args else {
+        return Ok(Vec::new());
+    };
+    if let Ok(dict) = args.downcast::<PyDict>() {
+        return dict
+            .iter()
+            .map(|(k, v)| procedure_arg(Some(k.extract()?), &v))
+            .collect();
+    }
+    args.iter()?
+        .map(|item| procedure_arg(None, &item?))
+        .collect()
+}
+
+/// Set the SQL types of procedure arguments from a list (by position) or a
+/// dict (by parameter name)
+pub(crate) fn apply_types(
+    args: &mut [ProcedureArg],
+    types: Option<&Bound<'_, PyAny>>,
+) -> PyResult<()> {
+    let Some(types) = types else {
+        return Ok(());
+    };
+    if let Ok(dict) = types.downcast::<PyDict>() {
+        for (name, ty) in dict.iter() {
+            let name: String = name.extract()?;
+            let arg = args
+                .iter_mut()
+                .find(|arg| arg.name.as_deref() == Some(name.as_str()))
+                .ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "No argument named '{}' to set the type of",
+                        name
+                    ))
+                })?;
+            arg.sql_type = Some(ty.extract()?);
+        }
+        return Ok(());
+    }
+    let types: Vec<Option<String>> = types.extract()?;
+    if types.len() > args.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{} types given for {} arguments",
+            types.len(),
+            args.len()
+        )));
+    }
+    for (arg, ty) in args.iter_mut().zip(types) {
+        if ty.is_some() {
+            arg.sql_type = ty;
+        }
+    }
+    Ok(())
+}
+
+fn procedure_arg(name: Option<String>, value: &Bound<'_, PyAny>) -> PyResult<ProcedureArg> {
+    let Ok(output) = value.extract::<PyOutput>() else {
+        return Ok(ProcedureArg {
+            name,
+            value: py_to_value(value)?,
+            mode: ParamMode::In,
+            sql_type: None,
+        });
+    };
+    let mode = if output.value.is_some() {
+        ParamMode::InOut
+    } else {
+        ParamMode::Out
+    };
+    Ok(ProcedureArg {
+        name,
+        value: output.value.unwrap_or(Value::Null),
+        mode,
+        sql_type: output.sql_type,
+    })
+}
@@ -0,0 +1,30 @@
+//! Python bindings for data quality checks
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::connection::{py_dict_to_dataframe, to_python};
+use crate::errors::to_py_err;
+use industrydb_core::quality::{run_checks, Check};
+
+/// Parse a list of check definitions
+pub(crate) fn parse_checks(checks: &Bound<'_, PyList>) -> PyResult<Vec<Check>> {
+    pythonize::depythonize_bound(checks.clone().into_any()).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid quality check definition: {}",
+            e
+        ))
+    })
+}
+
+/// Run data quality checks on a frame and return the report
+#[pyfunction]
+pub fn check_frame(
+    py: Python,
+    data: &Bound<'_, PyDict>,
+    checks: &Bound<'_, PyList>,
+) -> PyResult<PyObject> {
+    let df = py_dict_to_dataframe(data)?;
+    let report = run_checks(&df, &parse_checks(checks)?).map_err(to_py_err)?;
+    to_python(py, &report)
+}
@@ -0,0 +1,238 @@
+//! Python bindings for the query builder
+
+use pyo3::basic::CompareOp as PyCompareOp;
+use pyo3::prelude::*;
+
+use crate::connection::{py_to_value, to_python};
+use crate::errors::to_py_err;
+use industrydb_core::config::DatabaseType;
+use industrydb_core::dialect::{dialect_for, NullsOrder};
+use industrydb_core::query::{self, Aggregate, Expr, JoinKind, OrderBy, Query};
+
+/// Filter expression built from `col()` and `param()`
+///
+/// Supports the comparison operators and `&`, `|`, `~` for AND, OR and NOT.
+#[pyclass(name = "Expr")]
+#[derive(Clone)]
+pub struct PyExpr {
+    pub(crate) inner: Expr,
+}
+
+/// Convert an expression or a plain value (bound as a parameter)
+fn to_expr(value: &Bound<'_, PyAny>) -> PyResult<Expr> {
+    match value.extract::<PyExpr>() {
+        Ok(expr) => Ok(expr.inner),
+        Err(_) => Ok(Expr::Param(py_to_value(value)?)),
+    }
+}
+
+fn wrap(inner: Expr) -> PyExpr {
+    PyExpr { inner }
+}
+
+#[pymethods]
+impl PyExpr {
+    fn __richcmp__(&self, other: &Bound<'_, PyAny>, op: PyCompareOp) -> PyResult<PyExpr> {
+        let left = self.inner.clone();
+        let right = to_expr(other)?;
+        Ok(wrap(match op {
+            PyCompareOp::Eq => left.eq(right),
+            PyCompareOp::Ne => left.not_eq(right),
+            PyCompareOp::Gt => left.gt(right),
+            PyCompareOp::Ge => left.gt_eq(right),
+            PyCompareOp::Lt => left.lt(right),
+            PyCompareOp::Le => left.lt_eq(right),
+        }))
+    }
+
+    fn __and__(&self, other: PyExpr) -> PyExpr {
+        wrap(self.inner.clone().and(other.inner))
+    }
+
+    fn __or__(&self, other: PyExpr) -> PyExpr {
+        wrap(self.inner.clone().or(other.inner))
+    }
+
+    fn __invert__(&self) -> PyExpr {
+        wrap(self.inner.clone().not())
+    }
+
+    /// `self LIKE pattern`
+    fn like(&self, pattern: &Bound<'_, PyAny>) -> PyResult<PyExpr> {
+        Ok(wrap(self.inner.clone().like(to_expr(pattern)?)))
+    }
+
+    /// `self IS NULL`
+    fn is_null(&self) -> PyExpr {
+        wrap(self.inner.clone().is_null())
+    }
+
+    /// `self IS NOT NULL`
+    fn is_not_null(&self) -> PyExpr {
+        wrap(self.inner.clone().is_not_null())
+    }
+
+    /// `self IN (values...)`
+    fn is_in(&self, values: Vec<Bound<'_, PyAny>>) -> PyResult<PyExpr> {
+        let values = values.iter().map(to_expr).collect::<PyResult<Vec<_>>>()?;
+        Ok(wrap(self.inner.clone().is_in(values)))
+    }
+
+    /// `self BETWEEN low AND high`
+    fn between(&self, low: &Bound<'_, PyAny>, high: &Bound<'_, PyAny>) -> PyResult<PyExpr> {
+        Ok(wrap(
+            self.inner.clone().between(to_expr(low)?, to_expr(high)?),
+        ))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Expr({:?})", self.inner)
+    }
+}
+
+/// Sort order from the Python `order_by` arguments
+pub(crate) fn order_spec(
+    column: &str,
+    descending: bool,
+    nulls: Option<&str>,
+    collation: Option<&str>,
+) -> PyResult<OrderBy> {
+    let mut order = if descending {
+        OrderBy::desc(column)
+    } else {
+        OrderBy::asc(column)
+    };
+    order.nulls = nulls
+        .map(|n| n.parse::<NullsOrder>())
+        .transpose()
+        .map_err(to_py_err)?;
+    order.collation = collation.map(str::to_string);
+    Ok(order)
+}
+
+/// Reference a column
+#[pyfunction]
+pub fn col(name: &str) -> PyExpr {
+    wrap(query::col(name))
+}
+
+/// Bind a value as a parameter
+#[pyfunction]
+pub fn param(value: &Bound<'_, PyAny>) -> PyResult<PyExpr> {
+    Ok(wrap(Expr::Param(py_to_value(value)?)))
+}
+
+/// SELECT on one table and its joins; every builder method returns a new query
+#[pyclass(name = "Query")]
+#[derive(Clone)]
+pub struct PyQuery {
+    pub(crate) inner: Query,
+}
+
+#[pymethods]
+impl PyQuery {
+    #[new]
+    fn new(table: &str) -> Self {
+        Self {
+            inner: Query::select(table),
+        }
+    }
+
+    /// Select only these columns
+    fn columns(&self, columns: Vec<String>) -> Self {
+        Self {
+            inner: self.inner.clone().columns(columns),
+        }
+    }
+
+    /// Join `table` on a condition; `how` is inner, left, right or full
+    #[pyo3(signature = (table, on, how="inner"))]
+    fn join(&self, table: &str, on: PyExpr, how: &str) -> PyResult<Self> {
+        let kind: JoinKind = how.parse().map_err(to_py_err)?;
+        Ok(Self {
+            inner: self.inner.clone().join(kind, table, on.inner),
+        })
+    }
+
+    /// Add `func(column) AS alias`; `func` is count, sum, avg, min or max
+    fn aggregate(&self, func: &str, column: &str, alias: &str) -> PyResult<Self> {
+        let func: Aggregate = func.parse().map_err(to_py_err)?;
+        Ok(Self {
+            inner: self.inner.clone().aggregate(func, column, alias),
+        })
+    }
+
+    /// Add `COUNT(*) AS alias`
+    #[pyo3(signature = (alias="count"))]
+    fn count_all(&self, alias: &str) -> Self {
+        Self {
+            inner: self.inner.clone().count_all(alias),
+        }
+    }
+
+    /// Drop duplicate rows
+    fn distinct(&self) -> Self {
+        Self {
+            inner: self.inner.clone().distinct(),
+        }
+    }
+
+    /// Group by these columns
+    fn group_by(&self, columns: Vec<String>) -> Self {
+        Self {
+            inner: self.inner.clone().group_by(columns),
+        }
+    }
+
+    /// Add a filter; repeated filters are combined with AND
+    fn filter(&self, expr: PyExpr) -> Self {
+        Self {
+            inner: self.inner.clone().filter(expr.inner),
+        }
+    }
+
+    /// Sort by a column, after any earlier sort columns
+    ///
+    /// `nulls` is `"first"` or `"last"`; `collation` names a collation of the
+    /// target database.
+    #[pyo3(signature = (column, descending=false, nulls=None, collation=None))]
+    fn order_by(
+        &self,
+        column: &str,
+        descending: bool,
+        nulls: Option<&str>,
+        collation: Option<&str>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: self
+                .inner
+                .clone()
+                .order_by_with(order_spec(column, descending, nulls, collation)?),
+        })
+    }
+
+    /// Return at most `n` rows
+    fn limit(&self, n: usize) -> Self {
+        Self {
+            inner: self.inner.clone().limit(n),
+        }
+    }
+
+    /// Skip the first `n` rows
+    fn offset(&self, n: usize) -> Self {
+        Self {
+            inner: self.inner.clone().offset(n),
+        }
+    }
+
+    /// Render for a database type, returning `(sql, params)`
+    fn to_sql(&self, py: Python, db_type: &str) -> PyResult<(String, PyObject)> {
+        let db_type: DatabaseType = db_type.parse().map_err(to_py_err)?;
+        let (sql, params) = self.inner.render(dialect_for(db_type)).map_err(to_py_err)?;
+        Ok((sql, to_python(py, &params)?))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+}
@@ -0,0 +1,48 @@
+//! Python file objects read from Rust
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use std::io::Read;
+
+/// A Python object with a `read(size)` method, in binary or text mode
+///
+/// Each refill takes the GIL for one `read` call; text is sent as UTF-8.
+pub(crate) struct PyReader {
+    file: PyObject,
+    pending: Vec<u8>,
+    offset: usize,
+}
+
+impl PyReader {
+    pub(crate) fn new(file: PyObject) -> Self {
+        Self {
+            file,
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    fn refill(&mut self, size: usize) -> PyResult<()> {
+        self.pending = Python::with_gil(|py| -> PyResult<Vec<u8>> {
+            let chunk = self.file.bind(py).call_method1("read", (size,))?;
+            match chunk.downcast::<PyString>() {
+                Ok(text) => Ok(text.to_str()?.as_bytes().to_vec()),
+                Err(_) => Ok(chunk.downcast::<PyBytes>()?.as_bytes().to_vec()),
+            }
+        })?;
+        self.offset = 0;
+        Ok(())
+    }
+}
+
+impl Read for PyReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.offset == self.pending.len() {
+            self.refill(buf.len()).map_err(std::io::Error::other)?;
+        }
+        let len = buf.len().min(self.pending.len() - self.offset);
+        buf[..len].copy_from_slice(&self.pending[self.offset..self.offset + len]);
+        self.offset += len;
+        Ok(len)
+    }
+}
@@ -0,0 +1,99 @@
+//! Python bindings for replaying recorded data
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+use crate::backfill::PyBackfillControl;
+use crate::connection::{py_dict_to_dataframe, to_python, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::replay::{self, ReplayConfig, ReplayProgress};
+
+/// Replay recorded rows into a table with their original timing
+///
+/// The recording is read from `source_table` on `source` (optionally limited
+/// to `start..end`), from a `parquet` file, or taken from `data`. Runs
+/// without holding the GIL, so another thread can pause or cancel it through
+/// `control`. `on_progress` is called with a progress dict after every batch.
+#[pyfunction]
+#[pyo3(signature = (
+    target, table, time_column, source=None, source_table=None, parquet=None, data=None,
+    start=None, end=None, speed=1.0, rebase_timestamps=false, control=None, on_progress=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn replay(
+    py: Python,
+    target: PyRef<'_, PyConnection>,
+    table: &str,
+    time_column: &str,
+    source: Option<PyRef<'_, PyConnection>>,
+    source_table: Option<String>,
+    parquet: Option<PathBuf>,
+    data: Option<&Bound<'_, PyDict>>,
+    start: Option<String>,
+    end: Option<String>,
+    speed: f64,
+    rebase_timestamps: bool,
+    control: Option<PyBackfillControl>,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let runtime = target.runtime.clone();
+
+    let recording = match (source, parquet, data) {
+        (Some(source), None, None) => {
+            let source_conn = source.connector()?;
+            let source_table = source_table.as_deref().unwrap_or(table);
+            runtime
+                .block_on(replay::load_table(
+                    source_conn,
+                    source_table,
+                    time_column,
+                    start.as_deref(),
+                    end.as_deref(),
+                ))
+                .map_err(to_py_err)?
+        }
+        (None, Some(path), None) => replay::load_parquet(&path).map_err(to_py_err)?,
+        (None, None, Some(data)) => py_dict_to_dataframe(data)?,
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Pass exactly one of source, parquet or data",
+            ))
+        }
+    };
+
+    let mut config = ReplayConfig::new(table, time_column);
+    config.speed = speed;
+    config.rebase_timestamps = rebase_timestamps;
+
+    let control = control.map(|c| c.inner).unwrap_or_default();
+    let target_conn = target.connector()?;
+
+    let callback = on_progress.map(|callback| {
+        move |progress: &ReplayProgress| {
+            Python::with_gil(|py| {
+                let result = to_python(py, progress).and_then(|p| callback.call1(py, (p,)));
+                if let Err(e) = result {
+                    e.print(py);
+                }
+            })
+        }
+    });
+    let callback_ref = callback
+        .as_ref()
+        .map(|c| c as &(dyn Fn(&ReplayProgress) + Send + Sync));
+
+    let progress = py
+        .allow_threads(|| {
+            runtime.block_on(replay::replay(
+                target_conn,
+                recording,
+                &config,
+                &control,
+                callback_ref,
+            ))
+        })
+        .map_err(to_py_err)?;
+
+    to_python(py, &progress)
+}
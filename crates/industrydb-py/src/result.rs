@@ -0,0 +1,105 @@
+//! Python binding for query results with metadata
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::connection::dataframe_to_py_dict;
+use industrydb_core::traits::QueryResult;
+
+/// Rows of a query plus what is known about how it ran
+#[pyclass(name = "QueryResult")]
+pub struct PyQueryResult {
+    data: Py<PyDict>,
+    column_types: Vec<(String, String)>,
+    height: usize,
+    rows_affected: Option<u64>,
+    warnings: Vec<String>,
+    elapsed: f64,
+}
+
+impl PyQueryResult {
+    pub(crate) fn new(py: Python, result: QueryResult) -> PyResult<Self> {
+        Ok(Self {
+            data: dataframe_to_py_dict(py, &result.frame)?,
+            column_types: result
+                .column_types()
+                .into_iter()
+                .map(|(name, dtype)| (name, dtype.to_string()))
+                .collect(),
+            height: result.frame.height(),
+            rows_affected: result.rows_affected,
+            warnings: result.warnings,
+            elapsed: result.elapsed.as_secs_f64(),
+        })
+    }
+}
+
+#[pymethods]
+impl PyQueryResult {
+    /// Rows as a dict of column lists
+    #[getter]
+    fn data(&self, py: Python) -> Py<PyDict> {
+        self.data.clone_ref(py)
+    }
+
+    /// Column name -> type name, in column order
+    #[getter]
+    fn column_types(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let dict = PyDict::new_bound(py);
+        for (name, dtype) in &self.column_types {
+            dict.set_item(name, dtype)?;
+        }
+        Ok(dict.unbind())
+    }
+
+    /// Number of rows returned
+    #[getter]
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Rows changed by a statement; None for queries
+    #[getter]
+    fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+
+    /// Notes about the result
+    #[getter]
+    fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+
+    /// Seconds taken to run the query
+    #[getter]
+    fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    fn __len__(&self) -> usize {
+        self.height
+    }
+
+    fn __getitem__(&self, py: Python, column: &str) -> PyResult<PyObject> {
+        self.data
+            .bind(py)
+            .get_item(column)?
+            .map(|values| values.unbind())
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(column.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        match self.rows_affected {
+            Some(rows) => format!(
+                "QueryResult(rows_affected={}, elapsed={:.3}s)",
+                rows, self.elapsed
+            ),
+            None => format!(
+                "QueryResult(rows={}, columns={}, elapsed={:.3}s)",
+                self.height,
+                self.column_types.len(),
+                self.elapsed
+            ),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Python bindings for scheduled query and export jobs
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::connection::{to_python, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_scheduler::{Connections, Scheduler, SchedulerConfig};
+
+/// Borrow the connectors of named connections
+fn connectors<'a>(
+    connections: &'a HashMap<String, PyRef<'_, PyConnection>>,
+) -> PyResult<Connections<'a>> {
+    connections
+        .iter()
+        .map(|(name, conn)| Ok((name.clone(), conn.connector()?)))
+        .collect()
+}
+
+/// Any runtime of the given connections, to drive the jobs on
+fn runtime(
+    connections: &HashMap<String, PyRef<'_, PyConnection>>,
+) -> PyResult<std::sync::Arc<tokio::runtime::Runtime>> {
+    connections
+        .values()
+        .next()
+        .map(|conn| conn.runtime.clone())
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>("At least one connection is needed")
+        })
+}
+
+/// Query and export jobs run on cron-like schedules
+#[pyclass(name = "Scheduler")]
+pub struct PyScheduler {
+    inner: Scheduler,
+}
+
+#[pymethods]
+impl PyScheduler {
+    /// Load jobs from a `.toml` file
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        let inner = Scheduler::from_path(&path).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Build a scheduler from a dict with the same layout as the file
+    #[staticmethod]
+    fn from_dict(config: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let config: SchedulerConfig = pythonize::depythonize_bound(config.clone().into_any())
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid scheduler config: {}",
+                    e
+                ))
+            })?;
+        let inner = Scheduler::new(config).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Names of the configured jobs
+    #[getter]
+    fn jobs(&self) -> Vec<String> {
+        self.inner
+            .config()
+            .jobs
+            .iter()
+            .map(|job| job.name.clone())
+            .collect()
+    }
+
+    /// Next time each enabled job is due, as a dict of job name to
+    /// timestamp
+    #[getter]
+    fn next_runs(&self, py: Python) -> PyResult<PyObject> {
+        let next: HashMap<String, _> = self.inner.next_runs().into_iter().collect();
+        to_python(py, &next)
+    }
+
+    /// Runs so far, oldest first, as dicts
+    #[getter]
+    fn history(&self, py: Python) -> PyResult<PyObject> {
+        to_python(py, &self.inner.history())
+    }
+
+    /// Run jobs as they fall due until `stop()` is called
+    ///
+    /// `connections` maps the connection names used in the config to open
+    /// connections; jobs naming none use `"default"`. Runs without holding
+    /// the GIL, so another thread can call `stop()` or read `history`.
+    fn run(
+        &self,
+        py: Python,
+        connections: HashMap<String, PyRef<'_, PyConnection>>,
+    ) -> PyResult<()> {
+        let runtime = runtime(&connections)?;
+        let connectors = connectors(&connections)?;
+        py.allow_threads(|| runtime.block_on(self.inner.run(&connectors)))
+            .map_err(to_py_err)
+    }
+
+    /// Run one job now, whatever its schedule; returns the run as a dict
+    fn run_job(
+        &self,
+        py: Python,
+        name: &str,
+        connections: HashMap<String, PyRef<'_, PyConnection>>,
+    ) -> PyResult<PyObject> {
+        let runtime = runtime(&connections)?;
+        let connectors = connectors(&connections)?;
+        let run = py
+            .allow_threads(|| runtime.block_on(self.inner.run_job(name, &connectors)))
+            .map_err(to_py_err)?;
+        to_python(py, &run)
+    }
+
+    /// Stop once the running job, if any, has finished
+    fn stop(&self) {
+        self.inner.control().stop()
+    }
+}
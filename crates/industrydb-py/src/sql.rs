@@ -0,0 +1,46 @@
+//! Python bindings for SQL parsing
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::errors::to_py_err;
+use industrydb_core::config::DatabaseType;
+use industrydb_core::sql;
+
+/// Parse an optional dialect name ("generic" or None for the generic dialect)
+fn parse_dialect(dialect: Option<&str>) -> PyResult<Option<DatabaseType>> {
+    match dialect {
+        None | Some("generic") => Ok(None),
+        Some(name) => name.parse().map(Some).map_err(to_py_err),
+    }
+}
+
+/// Parse SQL into a list of statements
+///
+/// Each statement is returned as a dict with `kind` ("read", "write", "ddl"
+/// or "other"), `tables` and the normalized `sql`.
+#[pyfunction]
+#[pyo3(signature = (sql, dialect=None))]
+pub fn parse_sql(py: Python, sql: &str, dialect: Option<&str>) -> PyResult<Vec<Py<PyDict>>> {
+    let db_type = parse_dialect(dialect)?;
+    let statements = sql::parse_sql(sql, db_type).map_err(to_py_err)?;
+
+    statements
+        .into_iter()
+        .map(|stmt| {
+            let dict = PyDict::new_bound(py);
+            dict.set_item("kind", stmt.kind.to_string())?;
+            dict.set_item("tables", stmt.tables)?;
+            dict.set_item("sql", stmt.sql)?;
+            Ok(dict.unbind())
+        })
+        .collect()
+}
+
+/// Validate SQL syntax, raising `SqlParseError` if it does not parse
+#[pyfunction]
+#[pyo3(signature = (sql, dialect=None))]
+pub fn validate_sql(sql: &str, dialect: Option<&str>) -> PyResult<()> {
+    let db_type = parse_dialect(dialect)?;
+    sql::validate_sql(sql, db_type).map_err(to_py_err)
+}
@@ -0,0 +1,128 @@
+//! Python bindings for object storage archives
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+
+use crate::connection::{dataframe_to_py_dict, to_python};
+use crate::errors::to_py_err;
+use industrydb_core::time::parse_interval;
+use industrydb_storage::{
+    query_objects, read_objects, tier_files, ExportFormat, ObjectStoreTarget, SnapshotStore,
+    StorageOptions, TieringPolicy,
+};
+
+/// Open an object store target from a URL and provider options
+pub(crate) fn open_target(
+    url: &str,
+    options: Option<HashMap<String, String>>,
+    max_retries: usize,
+) -> PyResult<ObjectStoreTarget> {
+    let storage_options = StorageOptions {
+        max_retries,
+        ..StorageOptions::with_config(options.unwrap_or_default())
+    };
+    ObjectStoreTarget::open(url, storage_options).map_err(to_py_err)
+}
+
+/// Read archived objects from object storage, optionally filtered with SQL
+///
+/// `url` names one object or a prefix; with `sql` the archive is queried in
+/// place as `table_name` instead of being returned whole.
+#[pyfunction]
+#[pyo3(signature = (url, format="parquet", options=None, sql=None, table_name="archive", max_retries=10))]
+pub fn read_object_store(
+    py: Python,
+    url: &str,
+    format: &str,
+    options: Option<HashMap<String, String>>,
+    sql: Option<&str>,
+    table_name: &str,
+    max_retries: usize,
+) -> PyResult<Py<PyDict>> {
+    let format: ExportFormat = format.parse().map_err(to_py_err)?;
+    let target = open_target(url, options, max_retries)?;
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+            "Failed to create runtime: {}",
+            e
+        ))
+    })?;
+
+    let df = py
+        .allow_threads(|| match sql {
+            Some(sql) => runtime.block_on(query_objects(&target, "", format, table_name, sql)),
+            None => runtime
+                .block_on(read_objects(&target, "", format))
+                .map(|(df, _)| df),
+        })
+        .map_err(to_py_err)?;
+
+    dataframe_to_py_dict(py, &df)
+}
+
+/// Move local files not modified for `older_than` to object storage
+///
+/// Files below `directory` are uploaded under `url` by their relative path
+/// and deleted locally unless `keep_local`. Runs without holding the GIL.
+#[pyfunction]
+#[pyo3(signature = (
+    directory, url, older_than="7d", extensions=None, keep_local=false, options=None,
+    max_retries=10
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn tier_to_object_store(
+    py: Python,
+    directory: std::path::PathBuf,
+    url: &str,
+    older_than: &str,
+    extensions: Option<Vec<String>>,
+    keep_local: bool,
+    options: Option<HashMap<String, String>>,
+    max_retries: usize,
+) -> PyResult<PyObject> {
+    let age = parse_interval(older_than).map_err(to_py_err)?;
+    let policy = TieringPolicy {
+        extensions: extensions.unwrap_or_default(),
+        keep_local,
+        ..TieringPolicy::new(age.to_std().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "older_than must not be negative: {}",
+                older_than
+            ))
+        })?)
+    };
+    let target = open_target(url, options, max_retries)?;
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+            "Failed to create runtime: {}",
+            e
+        ))
+    })?;
+
+    let summary = py
+        .allow_threads(|| runtime.block_on(tier_files(&directory, &target, &policy)))
+        .map_err(to_py_err)?;
+    to_python(py, &summary)
+}
+
+/// Read a snapshot stored with `PyConnection.snapshot`
+#[pyfunction]
+#[pyo3(signature = (name, directory="snapshots"))]
+pub fn read_snapshot(
+    py: Python,
+    name: &str,
+    directory: std::path::PathBuf,
+) -> PyResult<Py<PyDict>> {
+    let store = SnapshotStore::open(&directory).map_err(to_py_err)?;
+    let df = py.allow_threads(|| store.read(name)).map_err(to_py_err)?;
+    dataframe_to_py_dict(py, &df)
+}
+
+/// Metadata of the snapshots in `directory`, oldest first
+#[pyfunction]
+#[pyo3(signature = (directory="snapshots"))]
+pub fn list_snapshots(py: Python, directory: std::path::PathBuf) -> PyResult<PyObject> {
+    let store = SnapshotStore::open(&directory).map_err(to_py_err)?;
+    to_python(py, &store.list().map_err(to_py_err)?)
+}
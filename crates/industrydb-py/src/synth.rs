@@ -0,0 +1,28 @@
+//! Python bindings for synthetic data generation
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::connection::dataframe_to_py_dict;
+use crate::errors::to_py_err;
+use industrydb_core::synth::SyntheticTable;
+
+/// Parse and validate a synthetic table definition from a dict
+pub(crate) fn parse_spec(spec: &Bound<'_, PyDict>) -> PyResult<SyntheticTable> {
+    let table: SyntheticTable =
+        pythonize::depythonize_bound(spec.clone().into_any()).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid synthetic table definition: {}",
+                e
+            ))
+        })?;
+    table.validate().map_err(to_py_err)?;
+    Ok(table)
+}
+
+/// Generate synthetic rows from a table definition without loading them
+#[pyfunction]
+pub fn generate_synthetic(py: Python, spec: &Bound<'_, PyDict>) -> PyResult<Py<PyDict>> {
+    let df = parse_spec(spec)?.generate().map_err(to_py_err)?;
+    dataframe_to_py_dict(py, &df)
+}
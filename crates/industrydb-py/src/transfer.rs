@@ -0,0 +1,164 @@
+//! Python bindings for copying and syncing tables between connections
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+use crate::backfill::PyBackfillControl;
+use crate::connection::{to_python, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::sync::{self, SyncConfig, SyncProgress};
+use industrydb_core::time::parse_interval;
+use industrydb_core::transfer::{
+    self, TableCopyConfig, TableCopyProgress, DEFAULT_COPY_CHUNK_ROWS,
+};
+
+/// Copy every row of a table from one connection to another in pages
+///
+/// `on_progress` is called with a progress dict after every page.
+#[pyfunction]
+#[pyo3(signature = (
+    source, target, table, mode="append", chunk_size=DEFAULT_COPY_CHUNK_ROWS, target_table=None,
+    on_progress=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn copy_table(
+    py: Python,
+    source: PyRef<'_, PyConnection>,
+    target: PyRef<'_, PyConnection>,
+    table: &str,
+    mode: &str,
+    chunk_size: usize,
+    target_table: Option<String>,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let mut config = TableCopyConfig::new(table);
+    config.mode = mode.parse().map_err(to_py_err)?;
+    config.chunk_rows = chunk_size;
+    if let Some(target_table) = target_table {
+        config.target_table = target_table;
+    }
+
+    let source_conn = source.connector()?;
+    let target_conn = target.connector()?;
+    let runtime = source.runtime.clone();
+
+    let callback = on_progress.map(|callback| {
+        move |progress: &TableCopyProgress| {
+            Python::with_gil(|py| {
+                let result = to_python(py, progress).and_then(|p| callback.call1(py, (p,)));
+                if let Err(e) = result {
+                    e.print(py);
+                }
+            })
+        }
+    });
+    let callback_ref = callback
+        .as_ref()
+        .map(|c| c as &(dyn Fn(&TableCopyProgress) + Send + Sync));
+
+    let progress = py
+        .allow_threads(|| {
+            runtime.block_on(transfer::copy_table(
+                source_conn,
+                target_conn,
+                &config,
+                callback_ref,
+            ))
+        })
+        .map_err(to_py_err)?;
+
+    to_python(py, &progress)
+}
+
+/// Replicate the rows of a table past a watermark to another connection
+///
+/// Runs without holding the GIL, so another thread can pause or cancel it
+/// through `control`. `on_progress` is called with a progress dict after
+/// every page.
+#[pyfunction]
+#[pyo3(signature = (
+    source, target, table, watermark_column, target_table=None, key_columns=None,
+    on_conflict="error", chunk_size=10_000, state=None, interval=None, control=None,
+    on_progress=None, batching=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn sync_table(
+    py: Python,
+    source: PyRef<'_, PyConnection>,
+    target: PyRef<'_, PyConnection>,
+    table: &str,
+    watermark_column: &str,
+    target_table: Option<String>,
+    key_columns: Option<Vec<String>>,
+    on_conflict: &str,
+    chunk_size: usize,
+    state: Option<PathBuf>,
+    interval: Option<&str>,
+    control: Option<PyBackfillControl>,
+    on_progress: Option<PyObject>,
+    batching: Option<&Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    let mut config = SyncConfig::new(table, watermark_column);
+    config.on_conflict = on_conflict.parse().map_err(to_py_err)?;
+    config.key_columns = key_columns.unwrap_or_default();
+    config.chunk_rows = chunk_size;
+    config.state_path = state;
+    config.batching = batching
+        .map(|b| {
+            pythonize::depythonize_bound(b.clone().into_any()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid batching settings: {}",
+                    e
+                ))
+            })
+        })
+        .transpose()?;
+    config.interval = interval
+        .map(|i| {
+            let interval = parse_interval(i).map_err(to_py_err)?;
+            interval.to_std().map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Sync interval must not be negative: {}",
+                    i
+                ))
+            })
+        })
+        .transpose()?;
+    if let Some(target_table) = target_table {
+        config.target_table = target_table;
+    }
+
+    let control = control.map(|c| c.inner).unwrap_or_default();
+    let source_conn = source.connector()?;
+    let target_conn = target.connector()?;
+    let runtime = source.runtime.clone();
+
+    let callback = on_progress.map(|callback| {
+        move |progress: &SyncProgress| {
+            Python::with_gil(|py| {
+                let result = to_python(py, progress).and_then(|p| callback.call1(py, (p,)));
+                if let Err(e) = result {
+                    e.print(py);
+                }
+            })
+        }
+    });
+    let callback_ref = callback
+        .as_ref()
+        .map(|c| c as &(dyn Fn(&SyncProgress) + Send + Sync));
+
+    let progress = py
+        .allow_threads(|| {
+            runtime.block_on(sync::sync_table(
+                source_conn,
+                target_conn,
+                &config,
+                &control,
+                callback_ref,
+            ))
+        })
+        .map_err(to_py_err)?;
+
+    to_python(py, &progress)
+}
@@ -0,0 +1,197 @@
+//! Python bindings for the buffered high-rate writer
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use crate::connection::{create_connector, py_dict_to_dataframe, to_python, PyConnection};
+use crate::errors::to_py_err;
+use industrydb_core::config::ConnectionConfig;
+use industrydb_core::spill::{SpillStore, DEFAULT_SPILL_TABLE};
+use industrydb_core::writer::{BufferedWriter, BufferedWriterConfig, WriterStats};
+
+/// Buffers rows and inserts them in batches on a background thread
+#[pyclass(name = "BufferedWriter")]
+pub struct PyBufferedWriter {
+    inner: Option<BufferedWriter>,
+    task: Option<JoinHandle<PyResult<WriterStats>>>,
+    runtime: Arc<Runtime>,
+    stats: WriterStats,
+}
+
+impl PyBufferedWriter {
+    fn writer(&self) -> PyResult<&BufferedWriter> {
+        self.inner.as_ref().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Buffered writer is closed")
+        })
+    }
+}
+
+#[pymethods]
+impl PyBufferedWriter {
+    /// Create a writer inserting into `table` through `conn`
+    #[new]
+    #[pyo3(signature = (
+        conn,
+        table,
+        max_rows=10_000,
+        max_delay=1.0,
+        queue_rows=100_000,
+        overflow="block",
+        spill_path=None,
+        retry_interval=5.0,
+        dedup_keys=None,
+        batching=None,
+        spill_codec="zstd",
+        spill_level=3,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        conn: PyRef<'_, PyConnection>,
+        table: &str,
+        max_rows: usize,
+        max_delay: f64,
+        queue_rows: usize,
+        overflow: &str,
+        spill_path: Option<String>,
+        retry_interval: f64,
+        dedup_keys: Option<Vec<String>>,
+        batching: Option<&Bound<'_, PyDict>>,
+        spill_codec: &str,
+        spill_level: i32,
+    ) -> PyResult<Self> {
+        let mut config = BufferedWriterConfig::new(table);
+        config.max_rows = max_rows;
+        config.max_delay = Duration::try_from_secs_f64(max_delay).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "max_delay must be a non-negative number",
+            )
+        })?;
+        config.queue_rows = queue_rows;
+        config.overflow = overflow.parse().map_err(to_py_err)?;
+        config.retry_interval = Duration::try_from_secs_f64(retry_interval).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "retry_interval must be a non-negative number",
+            )
+        })?;
+        config.dedup_keys = dedup_keys.unwrap_or_default();
+        config.batching = batching
+            .map(|b| {
+                pythonize::depythonize_bound(b.clone().into_any()).map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Invalid batching settings: {}",
+                        e
+                    ))
+                })
+            })
+            .transpose()?;
+        config.spill_codec.codec = spill_codec.parse().map_err(to_py_err)?;
+        config.spill_codec.level = spill_level;
+        let codec = config.spill_codec.clone();
+        let (inner, task) = BufferedWriter::new(config).map_err(to_py_err)?;
+
+        // The thread owns its handles, so the connection stays free to use
+        // and to close while the writer runs
+        let connector = conn.shared_connector()?;
+        let locks = conn.write_locks().cloned();
+        let runtime = conn.runtime.clone();
+        let task_runtime = runtime.clone();
+        let task = std::thread::Builder::new()
+            .name("industrydb-writer".to_string())
+            .spawn(move || {
+                task_runtime
+                    .block_on(async {
+                        let connector = connector.as_ref();
+                        let locks = locks.as_ref();
+                        let Some(path) = spill_path else {
+                            return task.run(connector, locks).await;
+                        };
+                        let spill_conn = create_connector(&ConnectionConfig::sqlite(path)).await?;
+                        let spill =
+                            SpillStore::open(spill_conn.as_ref(), DEFAULT_SPILL_TABLE, codec)
+                                .await?;
+                        task.run_with_spill(connector, locks, &spill).await
+                    })
+                    .map_err(to_py_err)
+            })?;
+
+        Ok(Self {
+            inner: Some(inner),
+            task: Some(task),
+            runtime,
+            stats: WriterStats::default(),
+        })
+    }
+
+    /// Queue rows given as a dict of columns, or one row as a dict of values
+    fn write(&self, py: Python, data: &Bound<'_, PyDict>) -> PyResult<()> {
+        let is_row = data
+            .values()
+            .iter()
+            .all(|value| !value.is_instance_of::<PyList>());
+        let df = if is_row {
+            let columns = PyDict::new_bound(py);
+            for (name, value) in data.iter() {
+                columns.set_item(name, PyList::new_bound(py, [value]))?;
+            }
+            py_dict_to_dataframe(&columns)?
+        } else {
+            py_dict_to_dataframe(data)?
+        };
+
+        let writer = self.writer()?;
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(writer.write(df)))
+            .map_err(to_py_err)
+    }
+
+    /// Wait until every row written so far has been inserted
+    fn flush(&self, py: Python) -> PyResult<()> {
+        let writer = self.writer()?;
+        let runtime = self.runtime.clone();
+        py.allow_threads(|| runtime.block_on(writer.flush()))
+            .map_err(to_py_err)
+    }
+
+    /// Counters so far, as a dict
+    #[getter]
+    fn stats(&self, py: Python) -> PyResult<PyObject> {
+        match &self.inner {
+            Some(writer) => to_python(py, &writer.stats()),
+            None => to_python(py, &self.stats),
+        }
+    }
+
+    /// Insert what is buffered and stop; returns the final counters
+    fn close(&mut self, py: Python) -> PyResult<PyObject> {
+        // Dropping the writer ends the task once it has inserted the rest
+        self.inner = None;
+        if let Some(task) = self.task.take() {
+            let result = py.allow_threads(|| task.join()).map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Buffered writer thread panicked")
+            })?;
+            self.stats = result?;
+        }
+        to_python(py, &self.stats)
+    }
+
+    /// Context manager entry
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Context manager exit
+    fn __exit__(
+        &mut self,
+        py: Python,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+}
@@ -0,0 +1,338 @@
+//! Scheduler configuration files
+
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::retention::{RetentionPolicy, DEFAULT_RETENTION_BATCH};
+use industrydb_core::time::parse_interval;
+use industrydb_core::transfer::CopyMode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::schedule::Schedule;
+
+/// Name of the connection jobs use unless they name another
+pub const DEFAULT_CONNECTION: &str = "default";
+
+fn default_connection() -> String {
+    DEFAULT_CONNECTION.to_string()
+}
+
+fn default_history_limit() -> usize {
+    1_000
+}
+
+fn default_compression() -> String {
+    "zstd".to_string()
+}
+
+fn default_retention_batch() -> usize {
+    DEFAULT_RETENTION_BATCH
+}
+
+/// Jobs and where their runs are recorded
+///
+/// ```toml
+/// history_table = "job_runs"
+///
+/// [[jobs]]
+/// name = "line1-daily-export"
+/// schedule = "0 6 * * *"
+/// sql = "SELECT * FROM readings WHERE ts >= CURRENT_DATE - 1"
+/// destination = { type = "parquet", path = "exports/line1_%Y%m%d.parquet" }
+///
+/// [[jobs]]
+/// name = "hourly-rollup"
+/// schedule = "@every 1h"
+/// sql = "SELECT tag, avg(value) AS value FROM readings GROUP BY tag"
+/// destination = { type = "table", table = "rollup", connection = "warehouse" }
+///
+/// [[jobs]]
+/// name = "readings-retention"
+/// schedule = "@daily"
+/// retention = { table = "readings", ts_column = "ts", keep = "90d" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Jobs, each with a unique name
+    #[serde(default)]
+    pub jobs: Vec<JobConfig>,
+    /// Table every run is recorded in; kept in memory only when `None`
+    #[serde(default)]
+    pub history_table: Option<String>,
+    /// Connection holding `history_table`
+    #[serde(default = "default_connection")]
+    pub history_connection: String,
+    /// Runs kept in memory, oldest dropped first
+    #[serde(default = "default_history_limit")]
+    pub history_limit: usize,
+    /// Evaluate schedules in UTC instead of the host's local time
+    #[serde(default)]
+    pub utc: bool,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            jobs: Vec::new(),
+            history_table: None,
+            history_connection: default_connection(),
+            history_limit: default_history_limit(),
+            utc: false,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Load a `.toml` file
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse a TOML document
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check settings that parse but cannot work
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for job in &self.jobs {
+            if !names.insert(job.name.as_str()) {
+                return Err(IndustryDbError::config_error(format!(
+                    "Duplicate job name '{}'",
+                    job.name
+                )));
+            }
+            job.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// A query run on a schedule and where its result goes, or a retention
+/// window enforced on a schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobConfig {
+    /// Name used in the run history
+    pub name: String,
+    /// When the job runs, see [`Schedule`]
+    pub schedule: String,
+    /// Query whose result is delivered
+    #[serde(default)]
+    pub sql: String,
+    /// Connection the query or retention runs on
+    #[serde(default = "default_connection")]
+    pub connection: String,
+    /// Where the result goes; required with `sql`
+    #[serde(default)]
+    pub destination: Option<Destination>,
+    /// Retention window to enforce instead of running a query
+    #[serde(default)]
+    pub retention: Option<RetentionConfig>,
+    /// Skipped by the scheduler, but can still be run by name
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl JobConfig {
+    /// Parsed [`schedule`](Self::schedule)
+    pub fn parsed_schedule(&self) -> Result<Schedule> {
+        Schedule::parse(&self.schedule)
+            .map_err(|e| IndustryDbError::config_error(format!("Job '{}': {}", self.name, e)))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(IndustryDbError::config_error("Job name must not be empty"));
+        }
+        self.parsed_schedule()?;
+        let has_sql = !self.sql.trim().is_empty();
+        match (&self.retention, has_sql, &self.destination) {
+            (Some(retention), false, None) => {
+                retention.policy()?;
+            }
+            (Some(_), _, _) => {
+                return Err(IndustryDbError::config_error(format!(
+                    "Job '{}' enforces retention and cannot also have SQL or a destination",
+                    self.name
+                )))
+            }
+            (None, false, _) => {
+                return Err(IndustryDbError::config_error(format!(
+                    "Job '{}' has no SQL",
+                    self.name
+                )))
+            }
+            (None, true, None) => {
+                return Err(IndustryDbError::config_error(format!(
+                    "Job '{}' has no destination",
+                    self.name
+                )))
+            }
+            (None, true, Some(Destination::Table { mode, .. })) => {
+                mode.parse::<CopyMode>()?;
+            }
+            (None, true, Some(_)) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Rows of a table to remove once older than a window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Table to trim
+    pub table: String,
+    /// Timestamp column the window applies to
+    pub ts_column: String,
+    /// How long rows are kept, such as `90d`, see `parse_interval`
+    pub keep: String,
+    /// Rows deleted per statement
+    #[serde(default = "default_retention_batch")]
+    pub batch_size: usize,
+    /// Keep expired PostgreSQL partitions as tables instead of dropping
+    /// them
+    #[serde(default)]
+    pub detach_only: bool,
+    /// Parquet file the expired rows are written to before they are
+    /// removed; may contain `strftime` fields like other paths
+    #[serde(default)]
+    pub archive: Option<String>,
+    /// Compression of the archive
+    #[serde(default = "default_compression")]
+    pub compression: String,
+}
+
+impl RetentionConfig {
+    /// Policy the job enforces
+    pub fn policy(&self) -> Result<RetentionPolicy> {
+        if self.batch_size == 0 {
+            return Err(IndustryDbError::config_error(format!(
+                "Retention of '{}' needs a positive batch_size",
+                self.table
+            )));
+        }
+        Ok(
+            RetentionPolicy::new(&self.table, &self.ts_column, parse_interval(&self.keep)?)
+                .batch_size(self.batch_size)
+                .detach_only(self.detach_only),
+        )
+    }
+}
+
+/// Where a job's result goes
+///
+/// File paths may contain `strftime` fields such as `%Y%m%d`, filled in
+/// with the time the run started, so each run writes its own file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Destination {
+    /// Insert into a table, on the job's connection unless another is named
+    Table {
+        /// Target table, created from the result's columns if missing
+        table: String,
+        /// Connection holding the table
+        #[serde(default)]
+        connection: Option<String>,
+        /// `append` to add the rows, `replace` to recreate the table first
+        #[serde(default = "default_mode")]
+        mode: String,
+    },
+    /// Write a Parquet file
+    Parquet {
+        /// File path
+        path: String,
+        /// Compression, see `industrydb_storage::parse_compression`
+        #[serde(default = "default_compression")]
+        compression: String,
+    },
+    /// Write a CSV file with a header row
+    Csv {
+        /// File path
+        path: String,
+    },
+}
+
+fn default_mode() -> String {
+    "append".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_toml() {
+        let config = SchedulerConfig::from_toml(
+            r#"
+            history_table = "job_runs"
+
+            [[jobs]]
+            name = "export"
+            schedule = "0 6 * * *"
+            sql = "SELECT * FROM readings"
+            destination = { type = "parquet", path = "out_%Y%m%d.parquet" }
+
+            [[jobs]]
+            name = "rollup"
+            schedule = "@every 1h"
+            sql = "SELECT 1"
+            destination = { type = "table", table = "rollup", connection = "warehouse" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.jobs.len(), 2);
+        assert_eq!(config.jobs[0].connection, DEFAULT_CONNECTION);
+        assert_eq!(
+            config.jobs[1].destination,
+            Some(Destination::Table {
+                table: "rollup".to_string(),
+                connection: Some("warehouse".to_string()),
+                mode: "append".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_retention_job() {
+        let config = SchedulerConfig::from_toml(
+            r#"
+            [[jobs]]
+            name = "retention"
+            schedule = "@daily"
+            retention = { table = "readings", ts_column = "ts", keep = "90d", archive = "old_%Y%m%d.parquet" }
+            "#,
+        )
+        .unwrap();
+        let retention = config.jobs[0].retention.as_ref().unwrap();
+        assert_eq!(retention.batch_size, DEFAULT_RETENTION_BATCH);
+        assert_eq!(retention.policy().unwrap().table(), "readings");
+
+        let job = |extra: &str| {
+            format!(
+                "[[jobs]]\nname = \"a\"\nschedule = \"@daily\"\n{}\n\
+                 retention = {{ table = \"t\", ts_column = \"ts\", keep = \"1d\" }}\n",
+                extra
+            )
+        };
+        assert!(SchedulerConfig::from_toml(&job("")).is_ok());
+        assert!(SchedulerConfig::from_toml(&job("sql = \"SELECT 1\"")).is_err());
+        assert!(SchedulerConfig::from_toml(&job("").replace("1d", "soon")).is_err());
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let job = |name: &str, schedule: &str| {
+            format!(
+                "[[jobs]]\nname = \"{}\"\nschedule = \"{}\"\nsql = \"SELECT 1\"\n\
+                 destination = {{ type = \"csv\", path = \"out.csv\" }}\n",
+                name, schedule
+            )
+        };
+        assert!(SchedulerConfig::from_toml(&job("a", "61 * * * *")).is_err());
+        let twice = format!("{}{}", job("a", "@daily"), job("a", "@hourly"));
+        assert!(SchedulerConfig::from_toml(&twice).is_err());
+    }
+}
@@ -0,0 +1,17 @@
+//! Scheduled query and export jobs for IndustryDB
+//!
+//! Replaces the cron entries that each start a Python script to run one
+//! query and save the result somewhere. A [`SchedulerConfig`] lists jobs,
+//! each a query, a cron-like [`Schedule`] and a [`Destination`]: a table on
+//! the same or another connection, or a Parquet or CSV file. Jobs can also
+//! enforce a [`RetentionConfig`], removing the rows of a table older than
+//! a window. A [`Scheduler`] runs them as they fall due and records every
+//! run, in memory and optionally in a history table.
+
+mod config;
+mod schedule;
+mod scheduler;
+
+pub use config::{Destination, JobConfig, RetentionConfig, SchedulerConfig, DEFAULT_CONNECTION};
+pub use schedule::Schedule;
+pub use scheduler::{Connections, JobRun, Scheduler, SchedulerControl};
@@ -0,0 +1,251 @@
+//! Cron-like schedules
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::time::parse_interval;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Allowed values of one cron field, as a bit set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    bits: u64,
+    /// Written as `*`, which matters for the day-of-month/day-of-week rule
+    any: bool,
+}
+
+impl Field {
+    fn parse(text: &str, min: u32, max: u32, names: &[&str]) -> Result<Self> {
+        let invalid = || IndustryDbError::config_error(format!("Invalid cron field: {}", text));
+        let value = |s: &str| -> Result<u32> {
+            let lower = s.to_lowercase();
+            if let Some(i) = names.iter().position(|n| *n == lower) {
+                // Month names count from 1, weekday names from 0
+                return Ok(i as u32 + min);
+            }
+            let v: u32 = s.parse().map_err(|_| invalid())?;
+            if v < min || v > max {
+                return Err(invalid());
+            }
+            Ok(v)
+        };
+
+        let mut bits = 0u64;
+        for item in text.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step.parse().map_err(|_| invalid())?;
+                    if step == 0 {
+                        return Err(invalid());
+                    }
+                    (range, step)
+                }
+                None => (item, 1),
+            };
+            let (start, end) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (value(start)?, value(end)?),
+                    // `5/15` runs from 5 to the end of the range
+                    None if step > 1 => (value(range)?, max),
+                    None => {
+                        let v = value(range)?;
+                        (v, v)
+                    }
+                },
+            };
+            if start > end {
+                return Err(invalid());
+            }
+            for v in (start..=end).step_by(step as usize) {
+                bits |= 1 << v;
+            }
+        }
+        Ok(Self {
+            bits,
+            any: text == "*",
+        })
+    }
+
+    fn contains(&self, v: u32) -> bool {
+        has(self.bits, v)
+    }
+}
+
+/// When a job runs
+///
+/// Either a five-field cron expression (`minute hour day-of-month month
+/// day-of-week`) with `*`, lists, ranges, steps and three-letter month and
+/// weekday names; one of `@hourly`, `@daily`, `@weekly`, `@monthly` and
+/// `@yearly`; or `@every <interval>` such as `@every 15m`, see
+/// [`parse_interval`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// Cron expression, matched against wall-clock minutes
+    Cron {
+        /// Minutes 0-59
+        minutes: u64,
+        /// Hours 0-23
+        hours: u64,
+        /// Days of the month 1-31, `None` for `*`
+        days: Option<u64>,
+        /// Months 1-12
+        months: u64,
+        /// Days of the week 0-6 from Sunday, `None` for `*`
+        weekdays: Option<u64>,
+    },
+    /// Fixed interval after the previous run
+    Every(TimeDelta),
+}
+
+impl Schedule {
+    /// Parse a cron expression, `@` shorthand or `@every` interval
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Some(interval) = text.strip_prefix("@every") {
+            return Ok(Schedule::Every(parse_interval(interval)?));
+        }
+        let expr = match text {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(IndustryDbError::config_error(format!(
+                "Cron expression needs five fields (minute hour day month weekday): {}",
+                text
+            )));
+        };
+        let mut weekdays = Field::parse(weekday, 0, 7, &WEEKDAYS)?;
+        // 7 is another name for Sunday
+        if weekdays.contains(7) {
+            weekdays.bits = (weekdays.bits | 1) & !(1 << 7);
+        }
+        let days = Field::parse(day, 1, 31, &[])?;
+        Ok(Schedule::Cron {
+            minutes: Field::parse(minute, 0, 59, &[])?.bits,
+            hours: Field::parse(hour, 0, 23, &[])?.bits,
+            days: (!days.any).then_some(days.bits),
+            months: Field::parse(month, 1, 12, &MONTHS)?.bits,
+            weekdays: (!weekdays.any).then_some(weekdays.bits),
+        })
+    }
+
+    /// First time strictly after `after` at which the schedule fires
+    ///
+    /// Cron schedules fire on whole minutes; `None` when the expression
+    /// never matches, e.g. `0 0 31 2 *`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let (minutes, hours, days, months, weekdays) = match self {
+            Schedule::Every(interval) => return after.checked_add_signed(*interval),
+            Schedule::Cron {
+                minutes,
+                hours,
+                days,
+                months,
+                weekdays,
+            } => (*minutes, *hours, *days, *months, *weekdays),
+        };
+        // Cron's rule: when both day fields are restricted, either may match
+        let day_matches = |t: &NaiveDateTime| {
+            let day = days.map_or(true, |days| has(days, t.day()));
+            let weekday = weekdays.map_or(true, |weekdays| {
+                has(weekdays, t.weekday().num_days_from_sunday())
+            });
+            match (days, weekdays) {
+                (Some(_), Some(_)) => day || weekday,
+                _ => day && weekday,
+            }
+        };
+
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        // Every schedule that can match does so within eight years (Feb 29)
+        let limit = t + TimeDelta::days(366 * 8);
+        while t < limit {
+            if !has(months, t.month()) {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    m => (t.year(), m + 1),
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !day_matches(&t) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !has(hours, t.hour()) {
+                t = t.with_minute(0)? + TimeDelta::hours(1);
+            } else if !has(minutes, t.minute()) {
+                t += TimeDelta::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+fn has(bits: u64, v: u32) -> bool {
+    bits & (1 << v) != 0
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = IndustryDbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use industrydb_core::time::parse_timestamp;
+
+    fn next(schedule: &str, after: &str) -> String {
+        let schedule = Schedule::parse(schedule).unwrap();
+        let next = schedule
+            .next_after(parse_timestamp(after).unwrap())
+            .unwrap();
+        next.format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        assert_eq!(next("*/15 * * * *", "2024-03-01 10:07"), "2024-03-01 10:15");
+        assert_eq!(next("0 6 * * *", "2024-03-01 06:00"), "2024-03-02 06:00");
+        assert_eq!(
+            next("30 2 * * mon-fri", "2024-03-01 03:00"),
+            "2024-03-04 02:30"
+        );
+        assert_eq!(next("0 0 1 jan *", "2024-03-01 00:00"), "2025-01-01 00:00");
+        assert_eq!(next("0 0 29 2 *", "2024-03-01 00:00"), "2028-02-29 00:00");
+        // Both day fields restricted: the 15th or any Sunday
+        assert_eq!(next("0 0 15 * 0", "2024-03-01 00:00"), "2024-03-03 00:00");
+        assert_eq!(next("0 12 * * 7", "2024-03-01 00:00"), "2024-03-03 12:00");
+        assert_eq!(next("@hourly", "2024-03-01 10:59:30"), "2024-03-01 11:00");
+        assert_eq!(next("@every 90s", "2024-03-01 10:00"), "2024-03-01 10:01");
+        assert!(Schedule::parse("0 0 31 2 *")
+            .unwrap()
+            .next_after(parse_timestamp("2024-01-01").unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        for schedule in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "@every",
+        ] {
+            assert!(Schedule::parse(schedule).is_err(), "{}", schedule);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Running jobs on their schedules
+
+use chrono::{Local, NaiveDateTime, Utc};
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::traits::CrudOperations;
+use industrydb_core::transfer::CopyMode;
+use industrydb_storage::{export_csv, export_parquet, parse_compression};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::{Destination, JobConfig, RetentionConfig, SchedulerConfig};
+use crate::schedule::Schedule;
+
+/// Connections jobs run on, by the names their configs use
+pub type Connections<'a> = HashMap<String, &'a dyn CrudOperations>;
+
+/// Longest the scheduler sleeps before checking whether it was stopped
+const STOP_POLL: Duration = Duration::from_millis(500);
+
+/// Outcome of one run of a job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRun {
+    /// Job name
+    pub job: String,
+    /// When the run started, in the scheduler's time zone
+    pub started_at: NaiveDateTime,
+    /// When the run finished
+    pub finished_at: NaiveDateTime,
+    /// Whether the result was delivered
+    pub success: bool,
+    /// Rows delivered
+    pub rows: usize,
+    /// Why the run failed
+    pub error: Option<String>,
+}
+
+/// Stop handle for a running scheduler
+///
+/// Clones share state, so one clone can be handed to another thread.
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerControl {
+    stopped: Arc<AtomicBool>,
+}
+
+impl SchedulerControl {
+    /// Create a handle in the running state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop once the running job, if any, has finished
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the scheduler has been stopped
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+}
+
+/// Runs the jobs of a [`SchedulerConfig`] when they are due and keeps a
+/// history of their runs
+///
+/// Jobs run one at a time. A failed run is recorded and the job runs again
+/// at its next time; nothing is retried or caught up, so runs due while
+/// another job was running are skipped until the next time after it.
+pub struct Scheduler {
+    config: SchedulerConfig,
+    schedules: Vec<Schedule>,
+    control: SchedulerControl,
+    history: Arc<Mutex<VecDeque<JobRun>>>,
+}
+
+impl Scheduler {
+    /// Create a scheduler for a validated config
+    pub fn new(config: SchedulerConfig) -> Result<Self> {
+        config.validate()?;
+        let schedules = config
+            .jobs
+            .iter()
+            .map(JobConfig::parsed_schedule)
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            config,
+            schedules,
+            control: SchedulerControl::new(),
+            history: Arc::default(),
+        })
+    }
+
+    /// Create a scheduler from a `.toml` file
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::new(SchedulerConfig::from_path(path)?)
+    }
+
+    /// The scheduler's configuration
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Handle that stops [`run`](Self::run) from another task or thread
+    pub fn control(&self) -> SchedulerControl {
+        self.control.clone()
+    }
+
+    /// Runs kept in memory, oldest first
+    pub fn history(&self) -> Vec<JobRun> {
+        self.lock_history().iter().cloned().collect()
+    }
+
+    fn lock_history(&self) -> std::sync::MutexGuard<'_, VecDeque<JobRun>> {
+        self.history.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn now(&self) -> NaiveDateTime {
+        if self.config.utc {
+            Utc::now().naive_utc()
+        } else {
+            Local::now().naive_local()
+        }
+    }
+
+    /// Next time each enabled job is due, by job name
+    pub fn next_runs(&self) -> Vec<(String, NaiveDateTime)> {
+        let now = self.now();
+        self.config
+            .jobs
+            .iter()
+            .zip(&self.schedules)
+            .filter(|(job, _)| !job.disabled)
+            .filter_map(|(job, schedule)| Some((job.name.clone(), schedule.next_after(now)?)))
+            .collect()
+    }
+
+    /// Run jobs as they fall due until stopped
+    ///
+    /// Returns once [`SchedulerControl::stop`] is called, or when no
+    /// enabled job will ever be due again. Failed runs do not end it; they
+    /// are in the history.
+    pub async fn run(&self, connections: &Connections<'_>) -> Result<()> {
+        let now = self.now();
+        let mut due: Vec<Option<NaiveDateTime>> = self
+            .config
+            .jobs
+            .iter()
+            .zip(&self.schedules)
+            .map(|(job, schedule)| (!job.disabled).then(|| schedule.next_after(now)).flatten())
+            .collect();
+
+        while !self.control.is_stopped() {
+            let Some(next) = due.iter().flatten().min().copied() else {
+                return Ok(());
+            };
+            let now = self.now();
+            if next > now {
+                let wait = (next - now).to_std().unwrap_or_default();
+                tokio::time::sleep(wait.min(STOP_POLL)).await;
+                continue;
+            }
+
+            for (index, job) in self.config.jobs.iter().enumerate() {
+                if self.control.is_stopped() {
+                    break;
+                }
+                if due[index].is_some_and(|at| at <= now) {
+                    self.run_job(&job.name, connections).await?;
+                    due[index] = self.schedules[index].next_after(self.now());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a job now, whatever its schedule, and record the run
+    ///
+    /// Fails only for an unknown job name or when the run cannot be
+    /// recorded in `history_table`; a failed run is returned with
+    /// `success` unset.
+    pub async fn run_job(&self, name: &str, connections: &Connections<'_>) -> Result<JobRun> {
+        let job = self
+            .config
+            .jobs
+            .iter()
+            .find(|job| job.name == name)
+            .ok_or_else(|| IndustryDbError::invalid_parameter(format!("Unknown job '{}'", name)))?;
+
+        let started_at = self.now();
+        let result = deliver(job, started_at, connections).await;
+        let run = JobRun {
+            job: job.name.clone(),
+            started_at,
+            finished_at: self.now(),
+            success: result.is_ok(),
+            rows: *result.as_ref().unwrap_or(&0),
+            error: result.err().map(|e| e.to_string()),
+        };
+
+        {
+            let mut history = self.lock_history();
+            history.push_back(run.clone());
+            while history.len() > self.config.history_limit {
+                history.pop_front();
+            }
+        }
+        if let Some(table) = &self.config.history_table {
+            let conn = connection(connections, &self.config.history_connection)?;
+            record_run(conn, table, &run).await?;
+        }
+        Ok(run)
+    }
+}
+
+fn connection<'a>(connections: &Connections<'a>, name: &str) -> Result<&'a dyn CrudOperations> {
+    connections.get(name).copied().ok_or_else(|| {
+        IndustryDbError::config_error(format!("No connection named '{}' was given", name))
+    })
+}
+
+/// Run a job's query and deliver the result, returning the rows delivered,
+/// or enforce its retention window, returning the rows deleted
+async fn deliver(
+    job: &JobConfig,
+    started_at: NaiveDateTime,
+    connections: &Connections<'_>,
+) -> Result<usize> {
+    let source = connection(connections, &job.connection)?;
+    if let Some(retention) = &job.retention {
+        return enforce_retention(source, retention, started_at).await;
+    }
+    let destination = job.destination.as_ref().ok_or_else(|| {
+        IndustryDbError::config_error(format!("Job '{}' has no destination", job.name))
+    })?;
+    match destination {
+        Destination::Table {
+            table,
+            connection: target,
+            mode,
+        } => {
+            let target = match target {
+                Some(name) => connection(connections, name)?,
+                None => source,
+            };
+            let df = source.execute(&job.sql).await?;
+            if mode.parse::<CopyMode>()? == CopyMode::Replace {
+                target.drop_table(table, true).await?;
+            }
+            if !target.table_exists(table).await? {
+                target.create_table(table, &df.schema(), true).await?;
+            }
+            target.insert(table, df).await
+        }
+        Destination::Parquet { path, compression } => {
+            let path = expand_path(path, started_at)?;
+            let summary = export_parquet(
+                source,
+                &job.sql,
+                Path::new(&path),
+                parse_compression(compression)?,
+            )
+            .await?;
+            Ok(summary.rows)
+        }
+        Destination::Csv { path } => {
+            let path = expand_path(path, started_at)?;
+            let summary = export_csv(source, &job.sql, Path::new(&path)).await?;
+            Ok(summary.rows)
+        }
+    }
+}
+
+/// Archive the rows older than the window, if configured, then remove them
+///
+/// The window ends `keep` before the run started, in the scheduler's time
+/// zone.
+async fn enforce_retention(
+    conn: &dyn CrudOperations,
+    retention: &RetentionConfig,
+    started_at: NaiveDateTime,
+) -> Result<usize> {
+    let policy = retention.policy()?;
+    let cutoff = policy.cutoff(started_at);
+    if let Some(path) = &retention.archive {
+        let path = expand_path(path, started_at)?;
+        export_parquet(
+            conn,
+            &policy.expired_sql(conn.dialect(), cutoff)?,
+            Path::new(&path),
+            parse_compression(&retention.compression)?,
+        )
+        .await?;
+    }
+    let summary = policy.enforce(conn, cutoff).await?;
+    Ok(summary.rows_deleted as usize)
+}
+
+/// Fill the `strftime` fields of a destination path
+fn expand_path(path: &str, at: NaiveDateTime) -> Result<String> {
+    let mut expanded = String::new();
+    write!(expanded, "{}", at.format(path)).map_err(|_| {
+        IndustryDbError::config_error(format!("Invalid strftime field in path: {}", path))
+    })?;
+    Ok(expanded)
+}
+
+/// Append a run to the history table, creating it if needed
+async fn record_run(conn: &dyn CrudOperations, table: &str, run: &JobRun) -> Result<()> {
+    let df = df!(
+        "job" => [run.job.as_str()],
+        "started_at" => [run.started_at],
+        "finished_at" => [run.finished_at],
+        "success" => [run.success],
+        "rows" => [run.rows as i64],
+        "error" => [run.error.as_deref()],
+    )?;
+    if !conn.table_exists(table).await? {
+        conn.create_table(table, &df.schema(), true).await?;
+    }
+    conn.insert(table, df).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_jobs_are_not_scheduled() {
+        let scheduler = Scheduler::new(
+            SchedulerConfig::from_toml(
+                "[[jobs]]\nname = \"a\"\nschedule = \"@hourly\"\nsql = \"SELECT 1\"\n\
+                 destination = { type = \"csv\", path = \"a.csv\" }\n\
+                 [[jobs]]\nname = \"b\"\nschedule = \"@hourly\"\nsql = \"SELECT 1\"\n\
+                 disabled = true\ndestination = { type = \"csv\", path = \"b.csv\" }\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let next: Vec<_> = scheduler.next_runs().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(next, ["a"]);
+        scheduler.control().stop();
+        assert!(scheduler.control().is_stopped());
+        assert!(scheduler.history().is_empty());
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let at = industrydb_core::time::parse_timestamp("2024-03-01 06:00").unwrap();
+        assert_eq!(
+            expand_path("line1_%Y%m%d.parquet", at).unwrap(),
+            "line1_20240301.parquet"
+        );
+        assert!(expand_path("line1_%Q.parquet", at).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_job_and_connection() {
+        let scheduler = Scheduler::new(
+            SchedulerConfig::from_toml(
+                "[[jobs]]\nname = \"a\"\nschedule = \"@hourly\"\nsql = \"SELECT 1\"\n\
+                 destination = { type = \"csv\", path = \"a.csv\" }\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let connections = Connections::new();
+        assert!(scheduler.run_job("b", &connections).await.is_err());
+
+        let run = scheduler.run_job("a", &connections).await.unwrap();
+        assert!(!run.success);
+        assert!(run.error.unwrap().contains("No connection named 'default'"));
+        assert_eq!(scheduler.history().len(), 1);
+    }
+}
@@ -0,0 +1,197 @@
+//! Attached database files shared by every pooled connection
+//!
+//! `ATTACH` only affects the connection it runs on, so attachments are kept
+//! here and applied by the pool: new connections attach every file when
+//! they open, and connections opened before the last change bring their
+//! attachments up to date when they are next acquired. Every change bumps a
+//! generation, and each connection records the generation it last synced
+//! to, so it syncs once per change rather than on every acquire.
+
+use industrydb_core::{
+    dialect::{Dialect, SqliteDialect},
+    error::{IndustryDbError, Result},
+};
+use sqlx::sqlite::SqliteConnection;
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Schema names SQLite reserves for the connection's own databases
+const RESERVED: [&str; 2] = ["main", "temp"];
+
+/// Database files to attach, by alias
+pub(crate) struct Attachments {
+    state: Mutex<State>,
+}
+
+struct State {
+    files: BTreeMap<String, String>,
+    /// Bumped on every change of `files`
+    generation: u64,
+    changed: Instant,
+    /// Generation each connection last synced to, by [`connection_id`]
+    synced: HashMap<usize, u64>,
+}
+
+impl Attachments {
+    pub(crate) fn new(files: BTreeMap<String, String>) -> Self {
+        Self {
+            state: Mutex::new(State {
+                files,
+                generation: 0,
+                changed: Instant::now(),
+                synced: HashMap::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Files to attach, by alias
+    pub(crate) fn files(&self) -> BTreeMap<String, String> {
+        self.state().files.clone()
+    }
+
+    /// Current generation and files to attach
+    pub(crate) fn snapshot(&self) -> (u64, BTreeMap<String, String>) {
+        let state = self.state();
+        (state.generation, state.files.clone())
+    }
+
+    /// Whether a connection opened `age` ago may miss a change, which only
+    /// connections opened before the last one can
+    pub(crate) fn may_be_outdated(&self, age: Duration) -> bool {
+        age >= self.state().changed.elapsed()
+    }
+
+    /// [`snapshot`](Self::snapshot) when connection `id` has not synced
+    /// to the current generation
+    pub(crate) fn outdated(&self, id: usize) -> Option<(u64, BTreeMap<String, String>)> {
+        let state = self.state();
+        (state.synced.get(&id) != Some(&state.generation))
+            .then(|| (state.generation, state.files.clone()))
+    }
+
+    /// Record that connection `id` matches `generation`
+    pub(crate) fn synced(&self, id: usize, generation: u64) {
+        self.state().synced.insert(id, generation);
+    }
+
+    pub(crate) fn insert(&self, alias: &str, path: &str) {
+        let mut state = self.state();
+        state.files.insert(alias.to_string(), path.to_string());
+        state.changed();
+    }
+
+    pub(crate) fn remove(&self, alias: &str) -> bool {
+        let mut state = self.state();
+        let removed = state.files.remove(alias).is_some();
+        state.changed();
+        removed
+    }
+}
+
+impl State {
+    fn changed(&mut self) {
+        self.generation += 1;
+        self.changed = Instant::now();
+    }
+}
+
+/// Identity of a connection: the address of its SQLite handle, stable for
+/// the connection's life
+pub(crate) async fn connection_id(conn: &mut SqliteConnection) -> sqlx::Result<usize> {
+    Ok(conn.lock_handle().await?.as_raw_handle().as_ptr() as usize)
+}
+
+/// Check an alias to attach a file under
+pub(crate) fn validate_alias(alias: &str) -> Result<()> {
+    SqliteDialect.validate_identifier(alias)?;
+    if alias.contains('.') || RESERVED.iter().any(|r| r.eq_ignore_ascii_case(alias)) {
+        return Err(IndustryDbError::invalid_parameter(format!(
+            "Cannot attach a database as '{}'",
+            alias
+        )));
+    }
+    Ok(())
+}
+
+/// Attach `path` as `alias` on one connection
+pub(crate) async fn attach(
+    conn: &mut SqliteConnection,
+    alias: &str,
+    path: &str,
+) -> sqlx::Result<()> {
+    let sql = format!(
+        "ATTACH DATABASE ?1 AS {}",
+        SqliteDialect.quote_identifier(alias)
+    );
+    sqlx::query(&sql).bind(path).execute(conn).await?;
+    Ok(())
+}
+
+/// Attach and detach files on one connection until it matches `files`
+pub(crate) async fn sync(
+    conn: &mut SqliteConnection,
+    files: &BTreeMap<String, String>,
+) -> sqlx::Result<()> {
+    let attached: Vec<String> = sqlx::query("PRAGMA database_list")
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|row| row.try_get::<String, _>("name"))
+        .collect::<sqlx::Result<_>>()?;
+
+    for name in &attached {
+        if !RESERVED.contains(&name.as_str()) && !files.contains_key(name) {
+            let sql = format!("DETACH DATABASE {}", SqliteDialect.quote_identifier(name));
+            sqlx::query(&sql).execute(&mut *conn).await?;
+        }
+    }
+    for (alias, path) in files {
+        if !attached.contains(alias) {
+            attach(conn, alias, path).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_alias() {
+        assert!(validate_alias("day_20240301").is_ok());
+        assert!(validate_alias("main").is_err());
+        assert!(validate_alias("TEMP").is_err());
+        assert!(validate_alias("a.b").is_err());
+        assert!(validate_alias("").is_err());
+    }
+
+    #[test]
+    fn test_changes_outdate_older_connections() {
+        let attachments = Attachments::new(BTreeMap::new());
+        let (generation, _) = attachments.snapshot();
+        attachments.synced(1, generation);
+        std::thread::sleep(Duration::from_millis(5));
+        let opened = Instant::now();
+        assert!(!attachments.may_be_outdated(opened.elapsed()));
+        assert!(attachments.outdated(1).is_none());
+
+        attachments.insert("archive", "archive.db");
+        assert!(attachments.may_be_outdated(opened.elapsed()));
+        let (generation, files) = attachments.outdated(1).unwrap();
+        assert_eq!(files.len(), 1);
+
+        // Synced once, the connection stays current until the next change
+        attachments.synced(1, generation);
+        assert!(attachments.outdated(1).is_none());
+        assert!(attachments.remove("archive"));
+        assert!(attachments.outdated(1).is_some());
+        assert!(attachments.files().is_empty());
+    }
+}
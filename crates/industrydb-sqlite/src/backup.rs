@@ -0,0 +1,116 @@
+//! Online backup through SQLite's backup API
+//!
+//! The backup API copies a database page by page from a consistent
+//! snapshot, so a buffer database can be saved while writers keep
+//! appending to it. Under WAL (the default journal mode, see
+//! [`SqliteOptions`]) the copy only holds a read lock, which does not block
+//! writers. Pages are copied [`PAGES_PER_STEP`] at a time and the source
+//! lock is released between steps; when another connection writes in
+//! between, SQLite restarts the copy, so the result is still one snapshot.
+//!
+//! The copy blocks its thread, so callers run it off the async runtime.
+//!
+//! [`SqliteOptions`]: industrydb_core::config::SqliteOptions
+
+use industrydb_core::error::{IndustryDbError, Result};
+use libsqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::ptr::{self, NonNull};
+use std::time::Duration;
+
+/// Pause before retrying a step another connection's lock refused
+const BUSY_RETRY: Duration = Duration::from_millis(10);
+
+/// Pages copied per backup step, between which the source lock is released
+const PAGES_PER_STEP: i32 = 256;
+
+/// Which way [`copy`] runs between a connection and a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// Connection's database into the file
+    ToFile,
+    /// File into the connection's database
+    FromFile,
+}
+
+/// Copy the main database of `conn` to or from the file at `path`
+///
+/// # Safety
+///
+/// `conn` must be an open connection not used by anything else until this
+/// returns, as guaranteed by holding sqlx's handle lock.
+pub(crate) unsafe fn copy(
+    conn: NonNull<ffi::sqlite3>,
+    path: &Path,
+    direction: Direction,
+) -> Result<()> {
+    let file = File::open(path, direction)?;
+    let (dest, source) = match direction {
+        Direction::ToFile => (file.0, conn.as_ptr()),
+        Direction::FromFile => (conn.as_ptr(), file.0),
+    };
+
+    let main = c"main";
+    let backup = ffi::sqlite3_backup_init(dest, main.as_ptr(), source, main.as_ptr());
+    if backup.is_null() {
+        return Err(error(dest, "Backup failed"));
+    }
+    // A step refused by another connection's lock is retried
+    loop {
+        match ffi::sqlite3_backup_step(backup, PAGES_PER_STEP) {
+            ffi::SQLITE_DONE => break,
+            ffi::SQLITE_OK => {}
+            ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED => std::thread::sleep(BUSY_RETRY),
+            _ => break,
+        }
+    }
+    match ffi::sqlite3_backup_finish(backup) {
+        ffi::SQLITE_OK => Ok(()),
+        _ => Err(error(dest, "Backup failed")),
+    }
+}
+
+/// Database file opened for the copy, closed on drop
+struct File(*mut ffi::sqlite3);
+
+impl File {
+    unsafe fn open(path: &Path, direction: Direction) -> Result<Self> {
+        let name = CString::new(path.to_string_lossy().as_bytes()).map_err(|_| {
+            IndustryDbError::invalid_parameter(format!("Invalid backup path '{}'", path.display()))
+        })?;
+        let flags = match direction {
+            Direction::ToFile => ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+            Direction::FromFile => ffi::SQLITE_OPEN_READONLY,
+        };
+        let mut db = ptr::null_mut();
+        let rc = ffi::sqlite3_open_v2(name.as_ptr(), &mut db, flags, ptr::null());
+        // The handle is allocated even when opening fails, so it is closed
+        let file = File(db);
+        if rc != ffi::SQLITE_OK {
+            return Err(error(file.0, &format!("Cannot open '{}'", path.display())));
+        }
+        Ok(file)
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from sqlite3_open_v2 and is closed once
+        unsafe {
+            ffi::sqlite3_close(self.0);
+        }
+    }
+}
+
+/// `context: message` with the last error of `db`
+unsafe fn error(db: *mut ffi::sqlite3, context: &str) -> IndustryDbError {
+    let message = if db.is_null() {
+        "out of memory".to_string()
+    } else {
+        CStr::from_ptr(ffi::sqlite3_errmsg(db))
+            .to_string_lossy()
+            .into_owned()
+    };
+    IndustryDbError::QueryError(format!("{}: {}", context, message))
+}
@@ -1,42 +1,207 @@
 //! SQLite connector implementation using sqlx with connection pooling
 
 use async_trait::async_trait;
+use futures_util::TryStreamExt;
 use industrydb_core::{
-    config::ConnectionConfig,
+    config::{ConnectionConfig, DatabaseType, SqliteOptions},
+    dialect::{Dialect, SqliteDialect},
     error::{IndustryDbError, Result},
+    metrics::{ChunkedDecode, QueryMetrics, DECODE_CHUNK_ROWS},
+    params::{parameter_set_error, Value},
+    schema,
+    sink::FrameSink,
+    sql::ensure_returns_rows,
     traits::DatabaseConnector,
 };
 use polars::prelude::*;
-use sqlx::{sqlite::SqliteRow, Column as SqlxColumn, Row, SqlitePool};
+use sqlx::query::Query;
+use sqlx::sqlite::{
+    SqliteArguments, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow,
+    SqliteSynchronous,
+};
+use sqlx::{Column as SqlxColumn, Row, Sqlite, SqlitePool};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::attach::{self, Attachments};
+use crate::backup::{self, Direction};
+use crate::introspection;
 
 /// SQLite database connector with connection pool
 pub struct SqliteConnector {
     pool: SqlitePool,
     db_type: String,
+    config: ConnectionConfig,
+    metrics: QueryMetrics,
+    attachments: Arc<Attachments>,
 }
 
 impl SqliteConnector {
     /// Create a new SQLite connector with connection pool
     pub async fn new(config: &ConnectionConfig) -> Result<Self> {
-        let database_url = format!(
-            "sqlite://{}",
-            config.database.as_deref().unwrap_or(":memory:")
-        );
+        let files = config
+            .sqlite
+            .as_ref()
+            .map(|o| o.attach.clone())
+            .unwrap_or_default();
+        for alias in files.keys() {
+            attach::validate_alias(alias)?;
+        }
+        let attachments = Arc::new(Attachments::new(files));
 
-        let pool = SqlitePool::connect(&database_url)
+        let on_connect = attachments.clone();
+        let on_acquire = attachments.clone();
+        let pool = SqlitePoolOptions::new()
+            .after_connect(move |conn, _| {
+                let attachments = on_connect.clone();
+                Box::pin(async move {
+                    let (generation, files) = attachments.snapshot();
+                    attach::sync(conn, &files).await?;
+                    attachments.synced(attach::connection_id(conn).await?, generation);
+                    Ok(())
+                })
+            })
+            .before_acquire(move |conn, meta| {
+                let attachments = on_acquire
+                    .may_be_outdated(meta.age)
+                    .then(|| on_acquire.clone());
+                Box::pin(async move {
+                    let Some(attachments) = attachments else {
+                        return Ok(true);
+                    };
+                    let id = attach::connection_id(conn).await?;
+                    if let Some((generation, files)) = attachments.outdated(id) {
+                        attach::sync(conn, &files).await?;
+                        attachments.synced(id, generation);
+                    }
+                    Ok(true)
+                })
+            })
+            .connect_with(connect_options(config)?)
             .await
             .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
 
         Ok(Self {
             pool,
             db_type: "sqlite".to_string(),
+            config: config.clone(),
+            metrics: QueryMetrics::new(),
+            attachments,
         })
     }
 
+    /// Database files attached with [`attach`], by alias
+    ///
+    /// [`attach`]: DatabaseConnector::attach
+    pub fn attached(&self) -> BTreeMap<String, String> {
+        self.attachments.files()
+    }
+
     /// Get a reference to the connection pool
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
+
+    /// Get the configuration this connector was created with
+    pub fn config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    /// Run the backup API between a pooled connection and a file
+    ///
+    /// The copy runs on a blocking thread that owns the connection, so it
+    /// goes back to the pool only once the copy is over, even when the
+    /// caller stops waiting.
+    async fn copy_file(&self, path: &Path, direction: Direction) -> Result<()> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+        let path = path.to_path_buf();
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || {
+            let mut handle = runtime
+                .block_on(conn.lock_handle())
+                .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+            // SAFETY: the locked handle keeps the connection to ourselves
+            unsafe { backup::copy(handle.as_raw_handle(), &path, direction) }
+        })
+        .await
+        .map_err(|e| IndustryDbError::QueryError(format!("Backup failed: {}", e)))?
+    }
+
+    /// Fetch a query's rows and decode them a chunk at a time
+    async fn fetch_chunks(
+        &self,
+        sql: &str,
+        params: &[Value],
+        decode: &mut ChunkedDecode<'_>,
+    ) -> Result<()> {
+        let mut stream = bind_params(sqlx::query(sql), params).fetch(&self.pool);
+        let mut rows = Vec::with_capacity(DECODE_CHUNK_ROWS);
+        while let Some(row) = stream
+            .try_next()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?
+        {
+            rows.push(row);
+            if rows.len() == DECODE_CHUNK_ROWS {
+                let chunk = std::mem::replace(&mut rows, Vec::with_capacity(DECODE_CHUNK_ROWS));
+                decode.decode(|| rows_to_dataframe(chunk))?;
+            }
+        }
+        if !rows.is_empty() {
+            decode.decode(|| rows_to_dataframe(rows))?;
+        }
+        Ok(())
+    }
+
+    /// Apply the connection's access policy to a query
+    pub(crate) fn enforce_policy<'a>(&self, sql: &'a str) -> Result<Cow<'a, str>> {
+        match &self.config.policy {
+            Some(policy) => policy.enforce(sql, DatabaseType::Sqlite),
+            None => Ok(Cow::Borrowed(sql)),
+        }
+    }
+}
+
+/// Connection options of `config`: its file, created when missing, and the
+/// PRAGMAs of [`SqliteOptions`]
+///
+/// The file is `path`, or `database` for configs written before `path`
+/// existed; an in-memory database without either.
+fn connect_options(config: &ConnectionConfig) -> Result<SqliteConnectOptions> {
+    let options: SqliteOptions = config.sqlite.clone().unwrap_or_default();
+    let invalid = |name: &str, value: &str| {
+        IndustryDbError::config_error(format!("Invalid SQLite {}: '{}'", name, value))
+    };
+    let journal_mode = SqliteJournalMode::from_str(&options.journal_mode)
+        .map_err(|_| invalid("journal_mode", &options.journal_mode))?;
+    let synchronous = SqliteSynchronous::from_str(&options.synchronous)
+        .map_err(|_| invalid("synchronous", &options.synchronous))?;
+
+    let file = config.path.as_deref().or(config.database.as_deref());
+    let connect = match file {
+        Some(file) if file != ":memory:" => SqliteConnectOptions::new()
+            .filename(file)
+            .create_if_missing(true),
+        _ => SqliteConnectOptions::from_str("sqlite::memory:")
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?,
+    };
+    let mut connect = connect
+        .journal_mode(journal_mode)
+        .synchronous(synchronous)
+        .busy_timeout(Duration::from_millis(options.busy_timeout_ms))
+        .foreign_keys(options.foreign_keys);
+    if let Some(pages) = options.cache_size {
+        connect = connect.pragma("cache_size", pages.to_string());
+    }
+    Ok(connect)
 }
 
 #[async_trait]
@@ -45,17 +210,138 @@ impl DatabaseConnector for SqliteConnector {
         &self.db_type
     }
 
+    fn dialect(&self) -> &'static dyn Dialect {
+        &SqliteDialect
+    }
+
+    fn metrics(&self) -> &QueryMetrics {
+        &self.metrics
+    }
+
     async fn execute(&self, sql: &str) -> Result<DataFrame> {
-        let rows = sqlx::query(sql)
-            .fetch_all(&self.pool)
+        self.execute_with_params(sql, &[]).await
+    }
+
+    async fn execute_with_params(&self, sql: &str, params: &[Value]) -> Result<DataFrame> {
+        let sql = self.enforce_policy(sql)?;
+        ensure_returns_rows(&sql)?;
+        let mut decode = self.metrics.chunked(&sql);
+        self.fetch_chunks(&sql, params, &mut decode).await?;
+        decode.finish()
+    }
+
+    async fn execute_into(
+        &self,
+        sql: &str,
+        params: &[Value],
+        sink: &mut dyn FrameSink,
+    ) -> Result<()> {
+        let sql = self.enforce_policy(sql)?;
+        ensure_returns_rows(&sql)?;
+        let mut decode = self.metrics.chunked(&sql).into_sink(sink);
+        self.fetch_chunks(&sql, params, &mut decode).await?;
+        decode.finish()?;
+        Ok(())
+    }
+
+    async fn execute_statement(&self, sql: &str, params: &[Value]) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        let result = bind_params(sqlx::query(&sql), params)
+            .execute(&self.pool)
             .await
             .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
 
-        if rows.is_empty() {
-            return Ok(DataFrame::empty());
+    async fn execute_many(&self, sql: &str, param_sets: &[Vec<Value>]) -> Result<u64> {
+        let sql = self.enforce_policy(sql)?;
+        // One connection and transaction for every set; sqlx prepares the
+        // statement on first use and reuses it for the rest
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        let mut affected = 0;
+        for (index, params) in param_sets.iter().enumerate() {
+            let result = bind_params(sqlx::query(&sql), params)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    parameter_set_error(index, IndustryDbError::QueryError(e.to_string()))
+                })?;
+            affected += result.rows_affected();
+        }
+        tx.commit()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(affected)
+    }
+
+    async fn execute_transaction(&self, statements: &[String]) -> Result<u64> {
+        let statements = statements
+            .iter()
+            .map(|sql| self.enforce_policy(sql))
+            .collect::<Result<Vec<_>>>()?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        let mut affected = 0;
+        for sql in &statements {
+            // Dropping `tx` on failure rolls it back
+            let result = sqlx::query(sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+            affected += result.rows_affected();
+        }
+        tx.commit()
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        Ok(affected)
+    }
+
+    async fn attach(&self, path: &Path, alias: &str) -> Result<()> {
+        attach::validate_alias(alias)?;
+        let path = path.to_string_lossy();
+        // Attach on one connection first, so a bad file fails here rather
+        // than in every connection the pool opens later
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| IndustryDbError::ConnectionError(e.to_string()))?;
+        attach::attach(&mut conn, alias, &path)
+            .await
+            .map_err(|e| IndustryDbError::QueryError(e.to_string()))?;
+        self.attachments.insert(alias, &path);
+        Ok(())
+    }
+
+    async fn detach(&self, alias: &str) -> Result<()> {
+        if !self.attachments.remove(alias) {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "No database is attached as '{}'",
+                alias
+            )));
         }
+        Ok(())
+    }
 
-        rows_to_dataframe(rows)
+    async fn backup_to(&self, path: &Path) -> Result<()> {
+        self.copy_file(path, Direction::ToFile).await
+    }
+
+    async fn restore_from(&self, path: &Path) -> Result<()> {
+        if !path.is_file() {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "No database file at '{}' to restore from",
+                path.display()
+            )));
+        }
+        self.copy_file(path, Direction::FromFile).await
     }
 
     async fn is_alive(&self) -> bool {
@@ -70,6 +356,64 @@ impl DatabaseConnector for SqliteConnector {
     fn is_closed(&self) -> bool {
         self.pool.is_closed()
     }
+
+    async fn list_schemas(&self) -> Result<Vec<String>> {
+        let df = self.execute(&introspection::list_schemas_sql()).await?;
+        schema::first_column_strings(&df)
+    }
+
+    async fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        let df = self
+            .execute(&introspection::list_tables_sql(schema))
+            .await?;
+        schema::first_column_strings(&df)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::describe_table_sql(table))
+            .await?;
+        schema::normalize_describe(df)
+    }
+
+    async fn primary_keys(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::primary_keys_sql(table))
+            .await?;
+        schema::normalize_primary_keys(df)
+    }
+
+    async fn indexes(&self, table: &str) -> Result<DataFrame> {
+        let df = self.execute(&introspection::indexes_sql(table)).await?;
+        schema::normalize_indexes(df)
+    }
+
+    async fn foreign_keys(&self, table: &str) -> Result<DataFrame> {
+        let df = self
+            .execute(&introspection::foreign_keys_sql(table))
+            .await?;
+        schema::normalize_foreign_keys(df)
+    }
+}
+
+/// Bind parameter values to a query in placeholder order
+pub(crate) fn bind_params<'q>(
+    mut query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    params: &[Value],
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    for param in params {
+        query = match param {
+            Value::Null => query.bind(None::<String>),
+            Value::Bool(v) => query.bind(*v),
+            Value::Int(v) => query.bind(*v),
+            Value::Float(v) => query.bind(*v),
+            Value::Text(v) => query.bind(v.clone()),
+            Value::Bytes(v) => query.bind(v.clone()),
+            // Text keeps every digit; NUMERIC columns convert it on insert
+            Value::Decimal(v) | Value::Uuid(v) => query.bind(v.clone()),
+        };
+    }
+    query
 }
 
 fn rows_to_dataframe(rows: Vec<SqliteRow>) -> Result<DataFrame> {
@@ -102,10 +446,35 @@ fn rows_to_dataframe(rows: Vec<SqliteRow>) -> Result<DataFrame> {
             .collect::<sqlx::Result<Vec<_>>>()
         {
             Series::new(col_name.into(), values)
+        } else if let Ok(values) = rows
+            .iter()
+            .map(|row| row.try_get::<Option<Vec<u8>>, _>(col_name))
+            .collect::<sqlx::Result<Vec<_>>>()
+        {
+            BinaryChunked::from_iter_options(col_name.into(), values.into_iter()).into_series()
         } else {
-            // Fallback to string
-            let values: Vec<Option<String>> =
-                rows.iter().map(|row| row.try_get(col_name).ok()).collect();
+            // Values of mixed types read as text, each in its own way, so
+            // none is lost to null
+            let values: Vec<Option<String>> = rows
+                .iter()
+                .map(|row| {
+                    row.try_get::<Option<String>, _>(col_name)
+                        .or_else(|_| {
+                            row.try_get::<Option<i64>, _>(col_name)
+                                .map(|v| v.map(|v| v.to_string()))
+                        })
+                        .or_else(|_| {
+                            row.try_get::<Option<f64>, _>(col_name)
+                                .map(|v| v.map(|v| v.to_string()))
+                        })
+                        .or_else(|_| {
+                            row.try_get::<Option<Vec<u8>>, _>(col_name)
+                                .map(|v| v.map(|v| String::from_utf8_lossy(&v).into_owned()))
+                        })
+                        .ok()
+                        .flatten()
+                })
+                .collect();
             Series::new(col_name.into(), values)
         };
 
@@ -115,3 +484,315 @@ fn rows_to_dataframe(rows: Vec<SqliteRow>) -> Result<DataFrame> {
     let columns: Vec<_> = series_vec.into_iter().map(|s| s.into_column()).collect();
     DataFrame::new(columns).map_err(|e| IndustryDbError::PolarsError(e.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pragmas_applied_to_file() {
+        let path =
+            std::env::temp_dir().join(format!("industrydb-pragmas-{}.db", std::process::id()));
+        let mut config = ConnectionConfig::sqlite(&path);
+        config.sqlite = Some(SqliteOptions {
+            busy_timeout_ms: 1234,
+            cache_size: Some(-4000),
+            ..Default::default()
+        });
+
+        let conn = SqliteConnector::new(&config).await.unwrap();
+        let pragma = |name: &str| {
+            let sql = format!("PRAGMA {}", name);
+            let conn = &conn;
+            async move { conn.fetch_scalar(&sql, &[]).await.unwrap().unwrap() }
+        };
+        assert_eq!(pragma("journal_mode").await, Value::from("wal"));
+        assert_eq!(pragma("busy_timeout").await, Value::Int(1234));
+        assert_eq!(pragma("cache_size").await, Value::Int(-4000));
+        assert_eq!(pragma("foreign_keys").await, Value::Int(1));
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+
+        config.sqlite = Some(SqliteOptions {
+            journal_mode: "sideways".to_string(),
+            ..Default::default()
+        });
+        assert!(connect_options(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blobs_read_as_binary() {
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(":memory:"))
+            .await
+            .unwrap();
+        let df = conn
+            .execute_with_params("SELECT ?1 AS wave", &[Value::Bytes(vec![0, 255, 7])])
+            .await
+            .unwrap();
+        let wave = df.column("wave").unwrap();
+        assert_eq!(wave.dtype(), &DataType::Binary);
+        assert_eq!(wave.binary().unwrap().get(0), Some(&[0u8, 255, 7][..]));
+    }
+
+    #[tokio::test]
+    async fn test_results_decode_in_chunks() {
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(":memory:"))
+            .await
+            .unwrap();
+        // Whole numbers fill the first chunk; fractions only come later
+        let rows = DECODE_CHUNK_ROWS + 10;
+        let df = conn
+            .execute_with_params(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1) \
+                 SELECT CASE WHEN i > ?2 THEN i + 0.5 ELSE i END AS v FROM n",
+                &[
+                    Value::Int(rows as i64),
+                    Value::Int(DECODE_CHUNK_ROWS as i64),
+                ],
+            )
+            .await
+            .unwrap();
+        let v = df.column("v").unwrap();
+        assert_eq!(v.len(), rows);
+        assert_eq!(v.dtype(), &DataType::Float64);
+        assert_eq!(v.f64().unwrap().get(rows - 1), Some(rows as f64 + 0.5));
+        assert_eq!(conn.metrics().last().unwrap().rows, rows);
+    }
+
+    #[tokio::test]
+    async fn test_execute_into_streams_chunks() {
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(":memory:"))
+            .await
+            .unwrap();
+        let mut chunks: Vec<DataFrame> = Vec::new();
+        conn.execute_into(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1) \
+             SELECT i FROM n",
+            &[Value::Int(DECODE_CHUNK_ROWS as i64 + 1)],
+            &mut chunks,
+        )
+        .await
+        .unwrap();
+        let heights: Vec<_> = chunks.iter().map(|c| c.height()).collect();
+        assert_eq!(heights, [DECODE_CHUNK_ROWS, 1]);
+        assert_eq!(conn.metrics().last().unwrap().rows, DECODE_CHUNK_ROWS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_mixed_types_read_as_text() {
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(":memory:"))
+            .await
+            .unwrap();
+        let df = conn
+            .execute("SELECT 1 AS v UNION ALL SELECT 'two' UNION ALL SELECT 2.5")
+            .await
+            .unwrap();
+        let values: Vec<_> = df.column("v").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(values, [Some("1"), Some("two"), Some("2.5")]);
+    }
+
+    #[tokio::test]
+    async fn test_blob_column_round_trip() {
+        let path = std::env::temp_dir().join(format!("industrydb-blob-{}.db", std::process::id()));
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(&path))
+            .await
+            .unwrap();
+        conn.execute_batch("CREATE TABLE frames (id INTEGER, payload BLOB)")
+            .await
+            .unwrap();
+        conn.execute_many(
+            "INSERT INTO frames VALUES (?1, ?2)",
+            &[
+                vec![Value::Int(1), Value::Bytes(b"\x00\x01abc".to_vec())],
+                vec![Value::Int(2), Value::Null],
+            ],
+        )
+        .await
+        .unwrap();
+
+        let df = conn
+            .execute("SELECT payload FROM frames ORDER BY id")
+            .await
+            .unwrap();
+        let payload = df.column("payload").unwrap().binary().unwrap().clone();
+        assert_eq!(payload.get(0), Some(&b"\x00\x01abc"[..]));
+        assert_eq!(payload.get(1), None);
+
+        drop(conn);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_attached_files_reach_every_connection() {
+        let dir = std::env::temp_dir();
+        let main = dir.join(format!("industrydb-attach-main-{}.db", std::process::id()));
+        let day = dir.join(format!("industrydb-attach-day-{}.db", std::process::id()));
+
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(&main))
+            .await
+            .unwrap();
+        conn.attach(&day, "day1").await.unwrap();
+        conn.execute_batch(
+            "CREATE TABLE day1.readings (tag TEXT, value REAL); \
+             INSERT INTO day1.readings VALUES ('TI-101', 1.5);",
+        )
+        .await
+        .unwrap();
+
+        // Every pooled connection sees the attachment
+        let reads = (0..4).map(|_| conn.fetch_scalar("SELECT count(*) FROM day1.readings", &[]));
+        for count in futures_util::future::join_all(reads).await {
+            assert_eq!(count.unwrap(), Some(Value::Int(1)));
+        }
+        assert_eq!(conn.attached().len(), 1);
+        assert!(conn.attach(&day, "main").await.is_err());
+
+        conn.detach("day1").await.unwrap();
+        assert!(conn
+            .fetch_scalar("SELECT count(*) FROM day1.readings", &[])
+            .await
+            .is_err());
+        assert!(conn.detach("day1").await.is_err());
+
+        drop(conn);
+        for file in [main, day] {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_composite_primary_key_positions() {
+        let path = std::env::temp_dir().join(format!("industrydb-pk-{}.db", std::process::id()));
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(&path))
+            .await
+            .unwrap();
+        conn.execute_batch(
+            "CREATE TABLE readings (value REAL, tag TEXT, ts INTEGER, PRIMARY KEY (ts, tag))",
+        )
+        .await
+        .unwrap();
+
+        // Positions count from 1 on every backend
+        let keys = conn.primary_keys("readings").await.unwrap();
+        let names: Vec<_> = keys
+            .column("column_name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        let positions: Vec<_> = keys
+            .column("ordinal_position")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(names, [Some("ts"), Some("tag")]);
+        assert_eq!(positions, [Some(1), Some(2)]);
+
+        drop(conn);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_policy_applies_to_crud() {
+        use industrydb_core::policy::AccessPolicy;
+        use industrydb_core::traits::CrudOperations;
+        use std::collections::HashMap;
+
+        let path =
+            std::env::temp_dir().join(format!("industrydb-policy-crud-{}.db", std::process::id()));
+        let mut config = ConnectionConfig::sqlite(&path);
+        config.policy = Some(AccessPolicy::new(HashMap::from([(
+            "readings".to_string(),
+            vec!["ts".to_string(), "value".to_string()],
+        )])));
+        let conn = SqliteConnector::new(&config).await.unwrap();
+        conn.execute_batch(
+            "CREATE TABLE readings (ts INTEGER, value REAL, operator TEXT); \
+             INSERT INTO readings VALUES (1, 1.5, 'x');",
+        )
+        .await
+        .unwrap();
+
+        let denied = |r: Result<usize>| matches!(r, Err(IndustryDbError::AccessDenied(_)));
+        assert!(denied(
+            conn.delete("readings", Some("operator = 'x'"), &[]).await
+        ));
+        let values = HashMap::from([("value".to_string(), "0".to_string())]);
+        assert!(denied(
+            conn.update("readings", &values, Some("operator = 'x'"), &[])
+                .await
+        ));
+        let values = HashMap::from([("value".to_string(), "operator".to_string())]);
+        assert!(denied(conn.update("readings", &values, None, &[]).await));
+        assert_eq!(
+            conn.delete("readings", Some("ts = 1"), &[]).await.unwrap(),
+            1
+        );
+
+        drop(conn);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let dir = std::env::temp_dir();
+        let source = dir.join(format!("industrydb-backup-src-{}.db", std::process::id()));
+        let copy = dir.join(format!("industrydb-backup-copy-{}.db", std::process::id()));
+
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(&source))
+            .await
+            .unwrap();
+        conn.execute_batch("CREATE TABLE tags (name TEXT); INSERT INTO tags VALUES ('a');")
+            .await
+            .unwrap();
+        conn.backup_to(&copy).await.unwrap();
+
+        conn.execute_statement("DELETE FROM tags", &[])
+            .await
+            .unwrap();
+        conn.restore_from(&copy).await.unwrap();
+        let count = conn.fetch_scalar("SELECT count(*) FROM tags", &[]).await;
+        assert_eq!(count.unwrap(), Some(Value::Int(1)));
+        assert!(conn.restore_from(&dir.join("missing.db")).await.is_err());
+
+        drop(conn);
+        for file in [source, copy] {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spill_store_replays_in_order() {
+        use industrydb_core::codec::CodecConfig;
+        use industrydb_core::spill::{SpillStore, DEFAULT_SPILL_TABLE};
+
+        let path = std::env::temp_dir().join(format!("industrydb-spill-{}.db", std::process::id()));
+        let conn = SqliteConnector::new(&ConnectionConfig::sqlite(&path))
+            .await
+            .unwrap();
+        let spill = SpillStore::open(&conn, DEFAULT_SPILL_TABLE, CodecConfig::default())
+            .await
+            .unwrap();
+        assert!(spill.oldest().await.unwrap().is_none());
+
+        let mut first = df!("tag" => ["TI-101", "TI-102"], "value" => [1.5, 2.5]).unwrap();
+        let mut second = df!("tag" => ["TI-101"], "value" => [3.5]).unwrap();
+        spill.push(&mut first).await.unwrap();
+        spill.push(&mut second).await.unwrap();
+        assert_eq!(spill.pending_rows().await.unwrap(), 3);
+
+        let (seq, batch) = spill.oldest().await.unwrap().unwrap();
+        assert!(batch.equals(&first));
+        spill.remove(seq).await.unwrap();
+        let (_, batch) = spill.oldest().await.unwrap().unwrap();
+        assert!(batch.equals(&second));
+        assert_eq!(spill.pending_rows().await.unwrap(), 1);
+
+        drop(spill);
+        drop(conn);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,77 @@
+//! Catalog queries for SQLite schema introspection
+
+use industrydb_core::schema::{quote_literal, split_qualified};
+
+/// Attached databases (`main`, `temp` and any ATTACHed files)
+pub(crate) fn list_schemas_sql() -> String {
+    "SELECT name FROM pragma_database_list ORDER BY seq".to_string()
+}
+
+/// User tables in the given database, skipping SQLite internal tables
+pub(crate) fn list_tables_sql(schema: Option<&str>) -> String {
+    format!(
+        "SELECT name FROM \"{}\".sqlite_master \
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        schema.unwrap_or("main").replace('"', "\"\"")
+    )
+}
+
+/// Whether a table exists in the given (or `main`) database
+pub(crate) fn table_exists_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    format!(
+        "SELECT COUNT(*) AS table_exists FROM \"{}\".sqlite_master \
+         WHERE type = 'table' AND name = {}",
+        schema.unwrap_or("main").replace('"', "\"\""),
+        quote_literal(name)
+    )
+}
+
+/// Column metadata via `pragma_table_info`, aliased to the core describe shape
+pub(crate) fn describe_table_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    format!(
+        "SELECT name AS column_name, type AS data_type, \"notnull\" = 0 AS is_nullable, \
+         dflt_value AS column_default FROM pragma_table_info({}, {}) ORDER BY cid",
+        quote_literal(name),
+        quote_literal(schema.unwrap_or("main"))
+    )
+}
+
+/// Primary key columns, ordered by their position in the key
+pub(crate) fn primary_keys_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    format!(
+        "SELECT name AS column_name, pk AS ordinal_position \
+         FROM pragma_table_info({}, {}) WHERE pk > 0 ORDER BY pk",
+        quote_literal(name),
+        quote_literal(schema.unwrap_or("main"))
+    )
+}
+
+/// Index columns, including the implicit indexes behind UNIQUE constraints
+pub(crate) fn indexes_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    let schema = quote_literal(schema.unwrap_or("main"));
+    format!(
+        "SELECT il.name AS index_name, ii.name AS column_name, il.\"unique\" AS is_unique, \
+         ii.seqno + 1 AS ordinal_position \
+         FROM pragma_index_list({}, {}) il JOIN pragma_index_info(il.name, {}) ii \
+         ORDER BY il.name, ii.seqno",
+        quote_literal(name),
+        schema,
+        schema
+    )
+}
+
+/// Foreign keys; SQLite has no constraint names, so the key id is used
+pub(crate) fn foreign_keys_sql(table: &str) -> String {
+    let (schema, name) = split_qualified(table);
+    format!(
+        "SELECT 'fk_' || id AS constraint_name, \"from\" AS column_name, \
+         \"table\" AS referenced_table, \"to\" AS referenced_column \
+         FROM pragma_foreign_key_list({}, {}) ORDER BY id, seq",
+        quote_literal(name),
+        quote_literal(schema.unwrap_or("main"))
+    )
+}
@@ -1,6 +1,9 @@
 //! SQLite connector implementation for IndustryDB
 
+mod attach;
+mod backup;
 mod connector;
+mod introspection;
 mod operations;
 
 pub use connector::SqliteConnector;
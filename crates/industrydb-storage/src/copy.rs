@@ -0,0 +1,236 @@
+//! Export to and import from local files with PostgreSQL's COPY
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use industrydb_core::copy::{CopyFormat, CsvCopyOptions};
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::traits::DatabaseConnector;
+
+use crate::file::{create, source_query, summary, FileExportSummary};
+
+/// Position in a binary COPY stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Binary {
+    /// Signature and flags field
+    Header,
+    /// Length of the header extension area
+    Extension,
+    /// Field count of the next tuple, or the trailer
+    Tuple,
+    /// Length of the next field
+    Field,
+    /// Bytes left of an extension area or field
+    Skip(usize),
+    /// Past the trailer
+    Done,
+}
+
+/// Counts the rows of a COPY stream on their way into a writer
+///
+/// CSV rows end at a newline outside quotes, after the header line; binary
+/// rows are the tuples between the file header and the trailer.
+struct RowCounter<W> {
+    inner: W,
+    format: CopyFormat,
+    rows: usize,
+    quoted: bool,
+    binary: Binary,
+    word: Vec<u8>,
+    fields: usize,
+}
+
+impl<W: Write> RowCounter<W> {
+    fn new(inner: W, format: CopyFormat) -> Self {
+        Self {
+            inner,
+            format,
+            rows: 0,
+            quoted: false,
+            binary: Binary::Header,
+            word: Vec::with_capacity(15),
+            fields: 0,
+        }
+    }
+
+    /// Number of rows seen, not counting a CSV header
+    fn rows(&self) -> usize {
+        match self.format {
+            CopyFormat::Csv => self.rows.saturating_sub(1),
+            CopyFormat::Binary => self.rows,
+        }
+    }
+
+    fn count_csv(&mut self, data: &[u8]) {
+        for byte in data {
+            match byte {
+                b'"' => self.quoted = !self.quoted,
+                b'\n' if !self.quoted => self.rows += 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// State after a tuple's field count or a field
+    fn next_field(&self) -> Binary {
+        if self.fields > 0 {
+            Binary::Field
+        } else {
+            Binary::Tuple
+        }
+    }
+
+    fn skip(&self, len: usize) -> Binary {
+        if len > 0 {
+            Binary::Skip(len)
+        } else {
+            self.next_field()
+        }
+    }
+
+    fn count_binary(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let want = match self.binary {
+                Binary::Header => 15,
+                Binary::Extension | Binary::Field => 4,
+                Binary::Tuple => 2,
+                Binary::Skip(len) => {
+                    let taken = len.min(data.len());
+                    data = &data[taken..];
+                    self.binary = self.skip(len - taken);
+                    continue;
+                }
+                Binary::Done => return,
+            };
+            let taken = (want - self.word.len()).min(data.len());
+            self.word.extend_from_slice(&data[..taken]);
+            data = &data[taken..];
+            if self.word.len() < want {
+                return;
+            }
+
+            let word = std::mem::take(&mut self.word);
+            self.binary = match self.binary {
+                Binary::Header => Binary::Extension,
+                Binary::Extension => {
+                    self.skip(u32::from_be_bytes([word[0], word[1], word[2], word[3]]) as usize)
+                }
+                Binary::Tuple => match i16::from_be_bytes([word[0], word[1]]) {
+                    count if count < 0 => Binary::Done,
+                    count => {
+                        self.rows += 1;
+                        self.fields = count as usize;
+                        self.next_field()
+                    }
+                },
+                _ => {
+                    self.fields -= 1;
+                    let len = i32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+                    self.skip(len.max(0) as usize)
+                }
+            };
+        }
+    }
+}
+
+impl<W: Write> Write for RowCounter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        match self.format {
+            CopyFormat::Csv => self.count_csv(&buf[..written]),
+            CopyFormat::Binary => self.count_binary(&buf[..written]),
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Copy a table or query into a file at `path` with the database's bulk
+/// export, see [`DatabaseConnector::copy_out`]
+///
+/// Only PostgreSQL supports this. The file holds the server's own encoding:
+/// in CSV, booleans are `t`/`f` and timestamps carry the session's zone.
+/// An existing file is overwritten.
+pub async fn export_copy<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    source: &str,
+    path: &Path,
+    format: CopyFormat,
+) -> Result<FileExportSummary> {
+    let mut out = RowCounter::new(BufWriter::new(create(path)?), format);
+    conn.copy_out(&source_query(source, conn.dialect())?, format, &mut out)
+        .await?;
+    out.flush()?;
+    summary(out.rows(), path)
+}
+
+/// Load the CSV file at `path` into `table` with the database's bulk
+/// import, see [`DatabaseConnector::copy_in`]
+///
+/// Only PostgreSQL supports this. Unlike [`import_csv`], the file is not
+/// parsed on this side: its columns must match the table's, which must
+/// exist, and one bad row loads nothing. Returns the number of rows loaded.
+///
+/// [`import_csv`]: crate::import_csv
+pub async fn import_copy<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    table: &str,
+    path: &Path,
+    options: &CsvCopyOptions,
+) -> Result<u64> {
+    let mut file = File::open(path).map_err(|e| {
+        IndustryDbError::storage_error(format!("Cannot open {}: {}", path.display(), e))
+    })?;
+    conn.copy_in(table, options, &mut file).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(format: CopyFormat, data: &[u8], split: usize) -> usize {
+        let mut counter = RowCounter::new(Vec::new(), format);
+        for part in data.chunks(split) {
+            counter.write_all(part).unwrap();
+        }
+        assert_eq!(counter.inner, data);
+        counter.rows()
+    }
+
+    #[test]
+    fn test_count_csv_rows() {
+        let data = b"tag,note\nPT-101,\"line one\nline two\"\nPT-102,\"said \"\"ok\"\"\"\n";
+        for split in [1, 4, data.len()] {
+            assert_eq!(count(CopyFormat::Csv, data, split), 2);
+        }
+        assert_eq!(count(CopyFormat::Csv, b"tag,note\n", 3), 0);
+    }
+
+    #[test]
+    fn test_count_binary_rows() {
+        let mut data = b"PGCOPY\n\xff\r\n\0".to_vec();
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(b"xx");
+        for value in [Some(7i64), None] {
+            data.extend_from_slice(&2i16.to_be_bytes());
+            data.extend_from_slice(&8i32.to_be_bytes());
+            data.extend_from_slice(&value.unwrap_or(-1).to_be_bytes());
+            match value {
+                Some(value) => {
+                    data.extend_from_slice(&8i32.to_be_bytes());
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+                None => data.extend_from_slice(&(-1i32).to_be_bytes()),
+            }
+        }
+        data.extend_from_slice(&(-1i16).to_be_bytes());
+        for split in [1, 3, data.len()] {
+            assert_eq!(count(CopyFormat::Binary, &data, split), 2);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Export of query results to local files and import of CSV files
+
+use std::fs::File;
+use std::path::Path;
+
+use industrydb_core::config::DatabaseType;
+use industrydb_core::copy::CopyFormat;
+use industrydb_core::dialect::Dialect;
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::schema::coerce_columns;
+use industrydb_core::traits::{CrudOperations, DatabaseConnector};
+use polars::prelude::*;
+
+use crate::copy::export_copy;
+use crate::sink::FileSink;
+
+/// Rows inserted per `insert` call when importing a file
+pub const IMPORT_BATCH_ROWS: usize = 10_000;
+
+/// Outcome of an export to a local file
+#[derive(Debug, Clone)]
+pub struct FileExportSummary {
+    /// Number of rows written
+    pub rows: usize,
+    /// Size of the written file in bytes
+    pub bytes: u64,
+}
+
+/// Query reading `source`, a table name or a query of its own
+///
+/// A single word is taken as a (possibly schema-qualified) table and read
+/// whole; anything longer is run as is.
+pub(crate) fn source_query(source: &str, dialect: &dyn Dialect) -> Result<String> {
+    if source.split_whitespace().nth(1).is_some() {
+        Ok(source.to_string())
+    } else {
+        Ok(format!("SELECT * FROM {}", dialect.identifier(source)?))
+    }
+}
+
+/// Parse a Parquet compression name: `zstd`, `snappy`, `gzip`, `lz4`,
+/// `brotli` or `uncompressed`
+pub fn parse_compression(name: &str) -> Result<ParquetCompression> {
+    match name.to_lowercase().as_str() {
+        "zstd" => Ok(ParquetCompression::Zstd(None)),
+        "snappy" => Ok(ParquetCompression::Snappy),
+        "gzip" => Ok(ParquetCompression::Gzip(None)),
+        "lz4" => Ok(ParquetCompression::Lz4Raw),
+        "brotli" => Ok(ParquetCompression::Brotli(None)),
+        "uncompressed" | "none" => Ok(ParquetCompression::Uncompressed),
+        _ => Err(IndustryDbError::invalid_parameter(format!(
+            "Unsupported Parquet compression: {}",
+            name
+        ))),
+    }
+}
+
+pub(crate) fn create(path: &Path) -> Result<File> {
+    File::create(path).map_err(|e| {
+        IndustryDbError::storage_error(format!("Cannot create {}: {}", path.display(), e))
+    })
+}
+
+pub(crate) fn summary(rows: usize, path: &Path) -> Result<FileExportSummary> {
+    let bytes = std::fs::metadata(path)
+        .map_err(|e| IndustryDbError::storage_error(format!("{}: {}", path.display(), e)))?
+        .len();
+    Ok(FileExportSummary { rows, bytes })
+}
+
+/// Read a table or query into a Parquet file at `path`
+///
+/// The rows are streamed into the file through a [`FileSink`] as they are
+/// decoded, one row group per chunk, so memory use does not grow with the
+/// size of the result. An existing file is overwritten.
+pub async fn export_parquet<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    source: &str,
+    path: &Path,
+    compression: ParquetCompression,
+) -> Result<FileExportSummary> {
+    let mut sink = FileSink::parquet(path, compression);
+    conn.execute_into(&source_query(source, conn.dialect())?, &[], &mut sink)
+        .await?;
+    sink.finish()
+}
+
+/// How [`export_ipc`] writes Arrow IPC
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IpcOptions {
+    /// Write the streaming format, which readers consume batch by batch
+    /// without seeking, instead of the file (Feather v2) format
+    pub stream: bool,
+    /// Rows per record batch; the connector's decode chunks when `None`
+    pub batch_rows: Option<usize>,
+    /// Buffer compression; uncompressed when `None`
+    pub compression: Option<IpcCompression>,
+}
+
+/// Parse an Arrow IPC compression name: `zstd`, `lz4` or `uncompressed`
+pub fn parse_ipc_compression(name: &str) -> Result<Option<IpcCompression>> {
+    match name.to_lowercase().as_str() {
+        "zstd" => Ok(Some(IpcCompression::ZSTD)),
+        "lz4" => Ok(Some(IpcCompression::LZ4)),
+        "uncompressed" | "none" => Ok(None),
+        _ => Err(IndustryDbError::invalid_parameter(format!(
+            "Unsupported IPC compression: {}",
+            name
+        ))),
+    }
+}
+
+/// `df` split into record batches of `rows` rows
+///
+/// The IPC writers write one record batch per chunk, so the frame is
+/// rechunked into slices of the requested size.
+fn into_batches(mut df: DataFrame, rows: usize) -> Result<DataFrame> {
+    if rows == 0 {
+        return Err(IndustryDbError::invalid_parameter(
+            "batch_rows must be positive",
+        ));
+    }
+    df.as_single_chunk();
+    let mut batches = df.slice(0, rows);
+    for offset in (rows..df.height()).step_by(rows) {
+        batches.vstack_mut(&df.slice(offset as i64, rows))?;
+    }
+    Ok(batches)
+}
+
+/// Read a table or query into an Arrow IPC file at `path`
+///
+/// Arrow IPC keeps every dtype, including timestamps with their zone, and
+/// loads without parsing. An existing file is overwritten.
+pub async fn export_ipc<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    source: &str,
+    path: &Path,
+    options: IpcOptions,
+) -> Result<FileExportSummary> {
+    let mut df = conn.execute(&source_query(source, conn.dialect())?).await?;
+    if let Some(rows) = options.batch_rows {
+        df = into_batches(df, rows)?;
+    }
+    let file = create(path)?;
+    if options.stream {
+        IpcStreamWriter::new(file)
+            .with_compression(options.compression)
+            .finish(&mut df)?;
+    } else {
+        IpcWriter::new(file)
+            .with_compression(options.compression)
+            .finish(&mut df)?;
+    }
+    summary(df.height(), path)
+}
+
+/// Read a table or query into a CSV file with a header row at `path`
+///
+/// Streamed like [`export_parquet`]. Timestamps are written as ISO 8601.
+/// PostgreSQL exports through `COPY ... TO STDOUT` instead, see
+/// [`export_copy`], which is several times faster on large tables but
+/// writes values the way the server prints them. An existing file is
+/// overwritten.
+pub async fn export_csv<C: DatabaseConnector + ?Sized>(
+    conn: &C,
+    source: &str,
+    path: &Path,
+) -> Result<FileExportSummary> {
+    if conn.dialect().db_type() == DatabaseType::Postgres {
+        return export_copy(conn, source, path, CopyFormat::Csv).await;
+    }
+    let mut sink = FileSink::csv(path);
+    conn.execute_into(&source_query(source, conn.dialect())?, &[], &mut sink)
+        .await?;
+    sink.finish()
+}
+
+/// Read a CSV file with a header row
+///
+/// Column types are inferred, except for the columns in `dtypes`, which
+/// are read as text and converted like [`coerce_columns`] does, so that
+/// e.g. tag numbers keep their leading zeros.
+pub fn read_csv(path: &Path, dtypes: &[(String, DataType)]) -> Result<DataFrame> {
+    let as_text = Schema::from_iter(
+        dtypes
+            .iter()
+            .map(|(name, _)| Field::new(name.as_str().into(), DataType::String)),
+    );
+    let df = CsvReadOptions::default()
+        .with_has_header(true)
+        .with_schema_overwrite(Some(Arc::new(as_text)))
+        .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+        .finish()?;
+    coerce_columns(df, dtypes)
+}
+
+/// Load a CSV file with a header row into `table`
+///
+/// Rows are inserted [`IMPORT_BATCH_ROWS`] at a time, so a failure leaves
+/// the earlier batches in place. With `create_table` the table is created
+/// from the file's schema when it does not exist yet. See [`read_csv`] for
+/// `dtypes`. Returns the number of rows inserted.
+pub async fn import_csv<C: CrudOperations + ?Sized>(
+    conn: &C,
+    table: &str,
+    path: &Path,
+    dtypes: &[(String, DataType)],
+    create_table: bool,
+) -> Result<usize> {
+    let df = read_csv(path, dtypes)?;
+    if create_table {
+        conn.create_table(table, &df.schema(), true).await?;
+    }
+
+    let mut rows = 0;
+    for offset in (0..df.height()).step_by(IMPORT_BATCH_ROWS) {
+        rows += conn
+            .insert(table, df.slice(offset as i64, IMPORT_BATCH_ROWS))
+            .await?;
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use industrydb_core::dialect::PostgresDialect;
+
+    #[test]
+    fn test_source_query() {
+        assert_eq!(
+            source_query("plant.readings", &PostgresDialect).unwrap(),
+            r#"SELECT * FROM "plant"."readings""#
+        );
+        assert_eq!(
+            source_query("SELECT ts FROM readings", &PostgresDialect).unwrap(),
+            "SELECT ts FROM readings"
+        );
+    }
+
+    #[test]
+    fn test_parse_compression() {
+        assert_eq!(
+            parse_compression("ZSTD").unwrap(),
+            ParquetCompression::Zstd(None)
+        );
+        assert_eq!(
+            parse_compression("none").unwrap(),
+            ParquetCompression::Uncompressed
+        );
+        assert!(parse_compression("xz").is_err());
+    }
+
+    #[test]
+    fn test_into_batches() {
+        let df = df!("ts" => (0i64..10).collect::<Vec<_>>()).unwrap();
+        let batches = into_batches(df.clone(), 4).unwrap();
+        assert_eq!(batches.n_chunks(), 3);
+        assert!(batches.equals(&df));
+        assert!(into_batches(df, 0).is_err());
+    }
+
+    #[test]
+    fn test_read_csv_with_dtypes() {
+        let path = std::env::temp_dir().join(format!("industrydb_csv_{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "tag,ts,value\n007,2024-03-01 08:00:00,1.5\n012,2024-03-01 09:00:00,\n",
+        )
+        .unwrap();
+
+        let df = read_csv(
+            &path,
+            &[
+                ("tag".to_string(), DataType::String),
+                (
+                    "ts".to_string(),
+                    DataType::Datetime(TimeUnit::Microseconds, None),
+                ),
+            ],
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let tags: Vec<_> = df
+            .column("tag")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(tags, [Some("007"), Some("012")]);
+        assert_eq!(
+            df.column("ts").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Microseconds, None)
+        );
+        assert_eq!(df.column("value").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(df.column("value").unwrap().null_count(), 1);
+    }
+}
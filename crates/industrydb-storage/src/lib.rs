@@ -0,0 +1,29 @@
+//! Object storage support for IndustryDB
+//!
+//! Ships query results to S3, MinIO, Azure Blob Storage or GCS as Parquet,
+//! CSV or Arrow IPC objects, without staging them on local disk, and reads
+//! archived objects back for restores or in-place queries. Local files past
+//! a given age can be tiered off to a bucket. Results can also be written
+//! to local files or kept as named snapshots, and CSV files loaded into
+//! tables.
+
+mod copy;
+mod export;
+mod file;
+mod import;
+mod sink;
+mod snapshot;
+mod target;
+mod tier;
+
+pub use copy::{export_copy, import_copy};
+pub use export::{export_dataframe, export_query, ExportFormat, ExportSummary};
+pub use file::{
+    export_csv, export_ipc, export_parquet, import_csv, parse_compression, parse_ipc_compression,
+    read_csv, FileExportSummary, IpcOptions, IMPORT_BATCH_ROWS,
+};
+pub use import::{import_objects, query_objects, read_objects, ImportSummary};
+pub use sink::FileSink;
+pub use snapshot::{SnapshotColumn, SnapshotInfo, SnapshotStore};
+pub use target::{ObjectStoreTarget, StorageOptions};
+pub use tier::{tier_files, TierSummary, TieringPolicy};
@@ -0,0 +1,208 @@
+//! Files query results are streamed into chunk by chunk
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::sink::FrameSink;
+use polars::io::csv::write::BatchedWriter as CsvBatchedWriter;
+use polars::io::ipc::BatchedWriter as IpcBatchedWriter;
+use polars::io::parquet::write::BatchedWriter as ParquetBatchedWriter;
+use polars::prelude::*;
+
+use crate::file::{create, summary, FileExportSummary};
+
+/// Format a [`FileSink`] writes
+#[derive(Debug, Clone, Copy)]
+enum SinkFormat {
+    Parquet(ParquetCompression),
+    Csv,
+    Ipc(Option<IpcCompression>),
+}
+
+enum Writer {
+    Parquet(ParquetBatchedWriter<File>),
+    Csv(CsvBatchedWriter<File>),
+    Ipc(IpcBatchedWriter<File>),
+}
+
+/// A Parquet, CSV or Arrow IPC file written chunk by chunk, for
+/// [`DatabaseConnector::execute_into`]
+///
+/// The file is created on the first chunk and takes its schema from it;
+/// later chunks are cast to that schema, so a column must not change to a
+/// type its first chunk cannot hold: a cast that would change any value,
+/// such as 2.5 to an integer, fails the write. Each chunk becomes a Parquet
+/// row group or an IPC record batch. Call [`finish`](Self::finish) once the
+/// query is done, or the file is left incomplete.
+///
+/// [`DatabaseConnector::execute_into`]: industrydb_core::traits::DatabaseConnector::execute_into
+pub struct FileSink {
+    path: PathBuf,
+    format: SinkFormat,
+    writer: Option<(Writer, Schema)>,
+    rows: usize,
+}
+
+impl FileSink {
+    fn new(path: &Path, format: SinkFormat) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            format,
+            writer: None,
+            rows: 0,
+        }
+    }
+
+    /// Parquet file compressed with `compression`
+    pub fn parquet(path: &Path, compression: ParquetCompression) -> Self {
+        Self::new(path, SinkFormat::Parquet(compression))
+    }
+
+    /// CSV file with a header row
+    pub fn csv(path: &Path) -> Self {
+        Self::new(path, SinkFormat::Csv)
+    }
+
+    /// Arrow IPC file (Feather v2), uncompressed when `compression` is `None`
+    pub fn ipc(path: &Path, compression: Option<IpcCompression>) -> Self {
+        Self::new(path, SinkFormat::Ipc(compression))
+    }
+
+    fn open(&self, schema: &Schema) -> Result<Writer> {
+        let file = create(&self.path)?;
+        Ok(match self.format {
+            SinkFormat::Parquet(compression) => Writer::Parquet(
+                ParquetWriter::new(file)
+                    .with_compression(compression)
+                    .batched(schema)?,
+            ),
+            SinkFormat::Csv => {
+                Writer::Csv(CsvWriter::new(file).include_header(true).batched(schema)?)
+            }
+            SinkFormat::Ipc(compression) => Writer::Ipc(
+                IpcWriter::new(file)
+                    .with_compression(compression)
+                    .batched(schema)?,
+            ),
+        })
+    }
+
+    /// Close the file and report what was written
+    ///
+    /// A result without columns leaves a file without columns.
+    pub fn finish(mut self) -> Result<FileExportSummary> {
+        let writer = match self.writer.take() {
+            Some((writer, _)) => writer,
+            None => self.open(&Schema::default())?,
+        };
+        match writer {
+            Writer::Parquet(writer) => {
+                writer.finish()?;
+            }
+            Writer::Csv(mut writer) => writer.finish()?,
+            Writer::Ipc(mut writer) => writer.finish()?,
+        }
+        summary(self.rows, &self.path)
+    }
+}
+
+impl FrameSink for FileSink {
+    fn write(&mut self, chunk: &mut DataFrame) -> Result<()> {
+        if self.writer.is_none() {
+            if chunk.width() == 0 {
+                return Ok(());
+            }
+            let schema = chunk.schema();
+            self.writer = Some((self.open(&schema)?, schema));
+        }
+        let Some((writer, schema)) = self.writer.as_mut() else {
+            return Ok(());
+        };
+
+        for (index, (name, dtype)) in schema.iter().enumerate() {
+            let column = chunk.get_columns().get(index).ok_or_else(|| {
+                IndustryDbError::storage_error(format!("Chunk is missing column {}", name))
+            })?;
+            if column.dtype() == dtype {
+                continue;
+            }
+            let cast = column
+                .strict_cast(dtype)
+                .ok()
+                .filter(|cast| lossless(column, cast));
+            let cast = cast.ok_or_else(|| {
+                IndustryDbError::storage_error(format!(
+                    "Column {} changed from {} to {} after the first chunk of {}",
+                    name,
+                    dtype,
+                    column.dtype(),
+                    self.path.display()
+                ))
+            })?;
+            chunk.replace_column(index, cast)?;
+        }
+
+        match writer {
+            Writer::Parquet(writer) => writer.write_batch(chunk)?,
+            Writer::Csv(writer) => writer.write_batch(chunk)?,
+            Writer::Ipc(writer) => writer.write_batch(chunk)?,
+        }
+        self.rows += chunk.height();
+        Ok(())
+    }
+}
+
+/// Whether `cast` holds every value of `column` unchanged
+fn lossless(column: &Column, cast: &Column) -> bool {
+    // Exact, and NaN would not compare equal on the way back
+    if column.dtype().is_float() && cast.dtype() == &DataType::Float64 {
+        return true;
+    }
+    cast.cast(column.dtype())
+        .is_ok_and(|back| back.equals_missing(column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_sink_casts_chunks() {
+        let path =
+            std::env::temp_dir().join(format!("industrydb_sink_{}.parquet", std::process::id()));
+        let mut sink = FileSink::parquet(&path, ParquetCompression::Zstd(None));
+        sink.write(&mut df!("id" => [1i64, 2], "value" => [0.5f64, 1.5]).unwrap())
+            .unwrap();
+        sink.write(&mut df!("id" => [3i32], "value" => [2i64]).unwrap())
+            .unwrap();
+        let summary = sink.finish().unwrap();
+        assert_eq!(summary.rows, 3);
+
+        let df = ParquetReader::new(File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(df.column("id").unwrap().dtype(), &DataType::Int64);
+        let values: Vec<_> = df
+            .column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, [Some(0.5), Some(1.5), Some(2.0)]);
+
+        let mut sink = FileSink::csv(&path);
+        sink.write(&mut df!("tag" => [1i64]).unwrap()).unwrap();
+        assert!(sink.write(&mut df!("tag" => ["PT-101"]).unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // Whole numbers first, fractions later: 2.5 would be truncated
+        let mut sink = FileSink::csv(&path);
+        sink.write(&mut df!("value" => [1i64, 2]).unwrap()).unwrap();
+        sink.write(&mut df!("value" => [3.0f64]).unwrap()).unwrap();
+        assert!(sink.write(&mut df!("value" => [2.5f64]).unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
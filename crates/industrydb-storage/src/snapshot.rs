@@ -0,0 +1,224 @@
+//! Named snapshots of query results in a local directory
+//!
+//! A snapshot freezes what a query returned at one point in time, so an
+//! analysis can be rerun offline against exactly the same production data.
+//! Each snapshot is a Parquet file next to a JSON file recording the query,
+//! the database it ran on, when it ran and the resulting columns.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use industrydb_core::error::{IndustryDbError, Result};
+use industrydb_core::traits::DatabaseConnector;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::file::export_parquet;
+
+/// Name and dtype of a snapshot column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotColumn {
+    /// Column name
+    pub name: String,
+    /// Polars dtype, as polars prints it
+    pub dtype: String,
+}
+
+/// What a snapshot holds and where it came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Snapshot name
+    pub name: String,
+    /// Query whose result was stored
+    pub sql: String,
+    /// Type of the database the query ran on
+    pub db_type: String,
+    /// When the query ran, as RFC 3339 in UTC
+    pub created_at: String,
+    /// Rows stored
+    pub rows: usize,
+    /// Size of the Parquet file in bytes
+    pub bytes: u64,
+    /// Columns of the result
+    pub columns: Vec<SnapshotColumn>,
+}
+
+/// Directory of named snapshots
+///
+/// ```text
+/// snapshots/
+///   line1_2024q1.parquet
+///   line1_2024q1.json
+/// ```
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Use `dir` for snapshots, creating it if needed
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            IndustryDbError::storage_error(format!("Cannot create {}: {}", dir.display(), e))
+        })?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Directory holding the snapshots
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn paths(&self, name: &str) -> Result<(PathBuf, PathBuf)> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Invalid snapshot name '{}': use letters, digits, '_', '-' and '.'",
+                name
+            )));
+        }
+        Ok((
+            self.dir.join(format!("{}.parquet", name)),
+            self.dir.join(format!("{}.json", name)),
+        ))
+    }
+
+    /// Run `sql` and store its result as snapshot `name`
+    ///
+    /// The rows are streamed into the Parquet file like
+    /// [`export_parquet`] does, and the file only takes the snapshot's
+    /// name once it is complete, so a failed query leaves any earlier
+    /// snapshot of that name intact. Fails if the snapshot exists unless
+    /// `overwrite` is set.
+    pub async fn create<C: DatabaseConnector + ?Sized>(
+        &self,
+        conn: &C,
+        sql: &str,
+        name: &str,
+        compression: ParquetCompression,
+        overwrite: bool,
+    ) -> Result<SnapshotInfo> {
+        let (data, meta) = self.paths(name)?;
+        if !overwrite && meta.exists() {
+            return Err(IndustryDbError::invalid_parameter(format!(
+                "Snapshot '{}' already exists",
+                name
+            )));
+        }
+
+        let created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let partial = data.with_extension("parquet.partial");
+        let summary = match export_parquet(conn, sql, &partial, compression).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                return Err(e);
+            }
+        };
+        let schema =
+            LazyFrame::scan_parquet(&partial, ScanArgsParquet::default())?.collect_schema()?;
+        std::fs::rename(&partial, &data)
+            .map_err(|e| IndustryDbError::storage_error(format!("{}: {}", data.display(), e)))?;
+
+        let info = SnapshotInfo {
+            name: name.to_string(),
+            sql: sql.to_string(),
+            db_type: conn.db_type().to_string(),
+            created_at,
+            rows: summary.rows,
+            bytes: summary.bytes,
+            columns: schema
+                .iter()
+                .map(|(name, dtype)| SnapshotColumn {
+                    name: name.to_string(),
+                    dtype: dtype.to_string(),
+                })
+                .collect(),
+        };
+        std::fs::write(&meta, serde_json::to_vec_pretty(&info)?)
+            .map_err(|e| IndustryDbError::storage_error(format!("{}: {}", meta.display(), e)))?;
+        Ok(info)
+    }
+
+    /// Metadata of snapshot `name`
+    pub fn info(&self, name: &str) -> Result<SnapshotInfo> {
+        let (_, meta) = self.paths(name)?;
+        let content = std::fs::read(&meta).map_err(|_| {
+            IndustryDbError::invalid_parameter(format!(
+                "No snapshot named '{}' in {}",
+                name,
+                self.dir.display()
+            ))
+        })?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    /// Rows of snapshot `name`
+    pub fn read(&self, name: &str) -> Result<DataFrame> {
+        self.info(name)?;
+        let (data, _) = self.paths(name)?;
+        let file = File::open(&data)
+            .map_err(|e| IndustryDbError::storage_error(format!("{}: {}", data.display(), e)))?;
+        Ok(ParquetReader::new(file).finish()?)
+    }
+
+    /// Every snapshot in the directory, oldest first
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            IndustryDbError::storage_error(format!("{}: {}", self.dir.display(), e))
+        })?;
+        let mut snapshots = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let content = std::fs::read(&path)?;
+                // Other JSON files may share the directory
+                if let Ok(info) = serde_json::from_slice::<SnapshotInfo>(&content) {
+                    snapshots.push(info);
+                }
+            }
+        }
+        snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(snapshots)
+    }
+
+    /// Delete snapshot `name`
+    pub fn remove(&self, name: &str) -> Result<()> {
+        self.info(name)?;
+        let (data, meta) = self.paths(name)?;
+        std::fs::remove_file(&meta)?;
+        match std::fs::remove_file(&data) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_names() {
+        let dir = std::env::temp_dir().join(format!("industrydb_snap_{}", std::process::id()));
+        let store = SnapshotStore::open(&dir).unwrap();
+        assert!(store.paths("line1_2024-q1.v2").is_ok());
+        for name in ["", ".hidden", "../escape", "a/b", "a b"] {
+            assert!(store.paths(name).is_err(), "{}", name);
+        }
+        assert!(store.list().unwrap().is_empty());
+        assert!(store
+            .read("missing")
+            .unwrap_err()
+            .to_string()
+            .contains("No snapshot named 'missing'"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
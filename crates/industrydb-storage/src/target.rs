@@ -0,0 +1,308 @@
+//! Object store targets resolved from URLs
+
+use futures::TryStreamExt;
+use industrydb_core::error::{IndustryDbError, Result};
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::{ObjectMeta, ObjectStore, PutPayload, RetryConfig, WriteMultipart};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use url::Url;
+
+/// Parts of a file upload in flight at once, bounding its memory use
+const PARTS_IN_FLIGHT: usize = 4;
+
+/// Options for connecting to and writing to an object store
+#[derive(Debug, Clone)]
+pub struct StorageOptions {
+    /// Provider configuration, e.g. `aws_endpoint`, `aws_access_key_id`,
+    /// `aws_allow_http` for MinIO or `azure_storage_account_key`
+    pub config: HashMap<String, String>,
+    /// Maximum retries per request
+    pub max_retries: usize,
+    /// Give up retrying a request after this long
+    pub retry_timeout: Duration,
+    /// Objects at least this large are uploaded with multipart upload
+    pub multipart_threshold: usize,
+    /// Part size for multipart uploads
+    pub part_size: usize,
+}
+
+impl Default for StorageOptions {
+    fn default() -> Self {
+        Self {
+            config: HashMap::new(),
+            max_retries: 10,
+            retry_timeout: Duration::from_secs(180),
+            multipart_threshold: 8 * 1024 * 1024,
+            part_size: 8 * 1024 * 1024,
+        }
+    }
+}
+
+impl StorageOptions {
+    /// Create options with provider configuration
+    pub fn with_config(config: HashMap<String, String>) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+}
+
+/// A bucket/container plus key prefix that objects are written under
+pub struct ObjectStoreTarget {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+    url: Url,
+    options: StorageOptions,
+}
+
+impl ObjectStoreTarget {
+    /// Resolve a target from a URL
+    ///
+    /// Supported schemes: `s3://bucket/prefix` (also MinIO via `aws_endpoint`),
+    /// `az://container/prefix` / `abfss://`, `gs://bucket/prefix`,
+    /// `file:///path` and `memory://` (for tests).
+    pub fn open(url: &str, options: StorageOptions) -> Result<Self> {
+        let parsed = Url::parse(url)
+            .map_err(|e| IndustryDbError::config_error(format!("Invalid storage URL: {}", e)))?;
+
+        let retry = RetryConfig {
+            max_retries: options.max_retries,
+            retry_timeout: options.retry_timeout,
+            ..Default::default()
+        };
+
+        let store: Arc<dyn ObjectStore> = match parsed.scheme() {
+            "s3" | "s3a" => {
+                let mut builder = AmazonS3Builder::from_env().with_url(url).with_retry(retry);
+                for (key, value) in &options.config {
+                    builder = builder.with_config(key.parse().map_err(config_key_error)?, value);
+                }
+                Arc::new(builder.build().map_err(storage_error)?)
+            }
+            "az" | "azure" | "abfs" | "abfss" => {
+                let mut builder = MicrosoftAzureBuilder::from_env()
+                    .with_url(url)
+                    .with_retry(retry);
+                for (key, value) in &options.config {
+                    builder = builder.with_config(key.parse().map_err(config_key_error)?, value);
+                }
+                Arc::new(builder.build().map_err(storage_error)?)
+            }
+            "gs" => {
+                let mut builder = GoogleCloudStorageBuilder::from_env()
+                    .with_url(url)
+                    .with_retry(retry);
+                for (key, value) in &options.config {
+                    builder = builder.with_config(key.parse().map_err(config_key_error)?, value);
+                }
+                Arc::new(builder.build().map_err(storage_error)?)
+            }
+            "file" => Arc::new(LocalFileSystem::new()),
+            "memory" => Arc::new(InMemory::new()),
+            other => {
+                return Err(IndustryDbError::config_error(format!(
+                    "Unsupported storage scheme: {}",
+                    other
+                )))
+            }
+        };
+
+        let prefix = Path::from_url_path(parsed.path()).map_err(storage_error)?;
+
+        Ok(Self {
+            store,
+            prefix,
+            url: parsed,
+            options,
+        })
+    }
+
+    /// Full object path for a key under the target prefix
+    pub fn path(&self, key: &str) -> Path {
+        key.split('/')
+            .filter(|part| !part.is_empty())
+            .fold(self.prefix.clone(), |path, part| path.child(part))
+    }
+
+    /// URL of an object under this target
+    pub fn object_url(&self, key: &str) -> String {
+        let mut url = self.url.clone();
+        url.set_path(&format!("/{}", self.path(key)));
+        url.to_string()
+    }
+
+    /// Underlying object store
+    pub fn store(&self) -> &Arc<dyn ObjectStore> {
+        &self.store
+    }
+
+    /// Upload bytes to a key, using multipart upload for large objects
+    ///
+    /// Failed requests are retried by the store client according to
+    /// [`StorageOptions::max_retries`] and `retry_timeout`.
+    pub async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<Path> {
+        let path = self.path(key);
+
+        if bytes.len() < self.options.multipart_threshold {
+            self.store
+                .put(&path, PutPayload::from(bytes))
+                .await
+                .map_err(storage_error)?;
+            return Ok(path);
+        }
+
+        let upload = self
+            .store
+            .put_multipart(&path)
+            .await
+            .map_err(storage_error)?;
+        let mut writer = WriteMultipart::new_with_chunk_size(upload, self.options.part_size);
+        writer.write(&bytes);
+        writer.finish().await.map_err(storage_error)?;
+
+        Ok(path)
+    }
+
+    /// Upload a local file to a key, streaming large files part by part
+    /// instead of reading them whole
+    pub async fn put_file(&self, key: &str, file: &std::path::Path) -> Result<Path> {
+        let size = tokio::fs::metadata(file).await?.len();
+        if size < self.options.multipart_threshold as u64 {
+            return self.put(key, tokio::fs::read(file).await?).await;
+        }
+
+        let path = self.path(key);
+        let upload = self
+            .store
+            .put_multipart(&path)
+            .await
+            .map_err(storage_error)?;
+        let mut writer = WriteMultipart::new_with_chunk_size(upload, self.options.part_size);
+        let mut reader = tokio::fs::File::open(file).await?;
+        let mut chunk = vec![0; self.options.part_size];
+        loop {
+            let read = match reader.read(&mut chunk).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    let _ = writer.abort().await;
+                    return Err(e.into());
+                }
+            };
+            writer
+                .wait_for_capacity(PARTS_IN_FLIGHT)
+                .await
+                .map_err(storage_error)?;
+            writer.write(&chunk[..read]);
+        }
+        writer.finish().await.map_err(storage_error)?;
+
+        Ok(path)
+    }
+
+    /// Objects at `key`: the object itself if it exists, otherwise every
+    /// object below it as a prefix, sorted by path
+    pub async fn list(&self, key: &str) -> Result<Vec<ObjectMeta>> {
+        let path = self.path(key);
+
+        if !path.as_ref().is_empty() {
+            match self.store.head(&path).await {
+                Ok(meta) => return Ok(vec![meta]),
+                Err(object_store::Error::NotFound { .. }) => {}
+                Err(e) => return Err(storage_error(e)),
+            }
+        }
+
+        let prefix = (!path.as_ref().is_empty()).then_some(&path);
+        let mut objects: Vec<ObjectMeta> = self
+            .store
+            .list(prefix)
+            .try_collect()
+            .await
+            .map_err(storage_error)?;
+        objects.sort_by(|a, b| a.location.cmp(&b.location));
+        Ok(objects)
+    }
+
+    /// Download an object
+    pub async fn get(&self, path: &Path) -> Result<Vec<u8>> {
+        let result = self.store.get(path).await.map_err(storage_error)?;
+        let bytes = result.bytes().await.map_err(storage_error)?;
+        Ok(bytes.to_vec())
+    }
+}
+
+pub(crate) fn storage_error(err: impl std::fmt::Display) -> IndustryDbError {
+    IndustryDbError::storage_error(err.to_string())
+}
+
+fn config_key_error(err: object_store::Error) -> IndustryDbError {
+    IndustryDbError::config_error(format!("Invalid storage option: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_joins_prefix() {
+        let target =
+            ObjectStoreTarget::open("memory:///exports/site-a", Default::default()).unwrap();
+        assert_eq!(
+            target.path("2024/01/readings.parquet").as_ref(),
+            "exports/site-a/2024/01/readings.parquet"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_object_or_prefix() {
+        let target = ObjectStoreTarget::open("memory:///lake", Default::default()).unwrap();
+        target.put("2024/01.csv", b"a\n".to_vec()).await.unwrap();
+        target.put("2024/02.csv", b"a\n".to_vec()).await.unwrap();
+
+        assert_eq!(target.list("2024").await.unwrap().len(), 2);
+        assert_eq!(target.list("2024/02.csv").await.unwrap().len(), 1);
+        assert!(target.list("2025").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_put_small_object() {
+        let target = ObjectStoreTarget::open("memory:///", Default::default()).unwrap();
+        let path = target.put("a.csv", b"id\n1\n".to_vec()).await.unwrap();
+        let bytes = target
+            .store()
+            .get(&path)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(bytes.as_ref(), b"id\n1\n");
+    }
+
+    #[tokio::test]
+    async fn test_put_file_in_parts() {
+        let options = StorageOptions {
+            multipart_threshold: 16,
+            part_size: 16,
+            ..Default::default()
+        };
+        let target = ObjectStoreTarget::open("memory:///", options).unwrap();
+        let file = std::env::temp_dir().join(format!("industrydb-put-{}.bin", std::process::id()));
+        let content: Vec<u8> = (0..100u8).collect();
+        std::fs::write(&file, &content).unwrap();
+
+        let path = target.put_file("a.bin", &file).await.unwrap();
+        assert_eq!(target.get(&path).await.unwrap(), content);
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -23,7 +23,7 @@ def main():
 
         # Create a table
         print("2. Creating table...")
-        conn.execute("""
+        conn.execute_statement("""
             CREATE TABLE IF NOT EXISTS employees (
                 id INTEGER PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -72,14 +72,14 @@ def main():
 
         # Update data
         print("7. Updating salary...")
-        conn.execute("UPDATE employees SET salary = salary * 1.1 WHERE id = 1")
+        conn.execute_statement("UPDATE employees SET salary = salary * 1.1 WHERE id = 1")
         result = conn.execute("SELECT * FROM employees WHERE id = 1")
         print(f"   Updated: {result}")
         print()
 
         # Delete data
         print("8. Deleting a record...")
-        conn.execute("DELETE FROM employees WHERE id = 4")
+        conn.execute_statement("DELETE FROM employees WHERE id = 4")
         result = conn.execute("SELECT COUNT(*) as count FROM employees")
         print(f"   Remaining employees: {result['count'][0]}")
 
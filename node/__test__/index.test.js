@@ -0,0 +1,44 @@
+const assert = require('node:assert/strict')
+const { mkdtempSync } = require('node:fs')
+const { tmpdir } = require('node:os')
+const { join } = require('node:path')
+const { test } = require('node:test')
+const { tableFromIPC } = require('apache-arrow')
+
+const { connect } = require('..')
+
+test('queries return rows and Arrow tables', async () => {
+  const path = join(mkdtempSync(join(tmpdir(), 'industrydb-')), 'test.db')
+  const conn = await connect(`sqlite://${path}`)
+
+  await conn.executeStatement('CREATE TABLE readings (tag TEXT, value REAL)')
+  await assert.rejects(conn.execute('DELETE FROM readings'), /execute_statement/)
+  const inserted = await conn.insert('readings', [
+    { tag: 'TI-101', value: 20.5 },
+    { tag: 'TI-102', value: 21.0 },
+    { tag: 'TI-102', value: 21.5 },
+  ])
+  assert.equal(inserted, 3)
+
+  const rows = await conn.execute('SELECT * FROM readings WHERE tag = :tag ORDER BY value', {
+    tag: 'TI-102',
+  })
+  assert.deepEqual(rows, [
+    { tag: 'TI-102', value: 21.0 },
+    { tag: 'TI-102', value: 21.5 },
+  ])
+
+  const page = await conn.select('readings', { orderBy: 'value', limit: 1, offset: 1 })
+  assert.deepEqual(page, [{ tag: 'TI-102', value: 21.0 }])
+
+  const table = tableFromIPC(await conn.executeArrow('SELECT * FROM readings'))
+  assert.equal(table.numRows, 3)
+  assert.deepEqual(table.schema.fields.map((f) => f.name), ['tag', 'value'])
+
+  assert.deepEqual(await conn.listTables(), ['readings'])
+  assert.equal(await conn.executeStatement('DELETE FROM readings WHERE tag = ?', ['TI-102']), 2)
+
+  await conn.close()
+  assert.equal(conn.isClosed, true)
+  await assert.rejects(conn.execute('SELECT 1'), /closed/)
+})
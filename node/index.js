@@ -0,0 +1,25 @@
+// Loads the native addon built by `npm run build` for this platform
+const { existsSync } = require('node:fs')
+const { join } = require('node:path')
+
+function platformSuffix() {
+  const { platform, arch } = process
+  if (platform === 'linux') {
+    const musl = !process.report?.getReport()?.header?.glibcVersionRuntime
+    return `linux-${arch}-${musl ? 'musl' : 'gnu'}`
+  }
+  if (platform === 'win32') {
+    return `win32-${arch}-msvc`
+  }
+  return `${platform}-${arch}`
+}
+
+const local = join(__dirname, `industrydb.${platformSuffix()}.node`)
+if (!existsSync(local)) {
+  throw new Error(
+    `industrydb has no native addon for ${process.platform}-${process.arch}; ` +
+      'build it with `npm run build` (needs a Rust toolchain)',
+  )
+}
+
+module.exports = require(local)
@@ -5,15 +5,46 @@ A Rust-powered database connector supporting MSSQL, PostgreSQL, and SQLite
 with Polars DataFrame integration.
 """
 
-# Re-export main classes for convenience
+# Must run before the extension is imported: picks the build with the most connectors
+from . import _native  # noqa: I001
 from .config import load_config
 from .industrydb import (
+    AccessDeniedError,
+    BackfillControl,
+    BufferedWriter,
+    ChangePoller,
     ConfigurationError,
+    Dashboard,
     DatabaseConnectionError,
+    Expr,
     IndustryDbError,
+    KafkaSink,
+    Output,
+    Pipeline,
+    Query,
     QueryExecutionError,
+    QueryResult,
+    Scheduler,
+    SqlParseError,
     __author__,
     __version__,
+    available_connectors,
+    backfill,
+    check_frame,
+    col,
+    copy_table,
+    generate_synthetic,
+    ingest_opcua_history,
+    list_snapshots,
+    load_plugin,
+    param,
+    parse_sql,
+    read_object_store,
+    read_snapshot,
+    replay,
+    sync_table,
+    tier_to_object_store,
+    validate_sql,
 )
 from .industrydb import PyConnection as Connection
 from .industrydb import PyDatabaseConfig as DatabaseConfig
@@ -26,9 +57,52 @@ __all__ = [
     "load_config",
     # Connection
     "Connection",
+    "available_connectors",
+    "load_plugin",
+    "QueryResult",
+    "Output",
+    "BufferedWriter",
+    # Query builder
+    "Query",
+    "Expr",
+    "col",
+    "param",
+    # SQL
+    "parse_sql",
+    "validate_sql",
+    # Backfill
+    "backfill",
+    "BackfillControl",
+    # Table copy and sync
+    "copy_table",
+    "sync_table",
+    # Change capture
+    "ChangePoller",
+    "KafkaSink",
+    # Replay
+    "replay",
+    # Ingestion pipelines
+    "Pipeline",
+    "ingest_opcua_history",
+    # Scheduled jobs
+    "Scheduler",
+    # Dashboards
+    "Dashboard",
+    # Synthetic data
+    "generate_synthetic",
+    # Data quality
+    "check_frame",
+    # Object storage
+    "read_object_store",
+    "tier_to_object_store",
+    # Snapshots
+    "read_snapshot",
+    "list_snapshots",
     # Exceptions
     "IndustryDbError",
     "DatabaseConnectionError",
     "QueryExecutionError",
     "ConfigurationError",
+    "SqlParseError",
+    "AccessDeniedError",
 ]
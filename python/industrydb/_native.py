@@ -0,0 +1,37 @@
+"""
+Selection of the compiled extension.
+
+The base ``industrydb`` wheel only compiles the SQLite connector. The pip
+extras ``industrydb[postgres]``, ``industrydb[mssql]`` and ``industrydb[all]``
+install a companion wheel carrying the same extension built with more
+connectors; when one is present it is loaded in place of the bundled one.
+
+The postgres and mssql companions each carry one extra connector, so only
+one of them can be loaded. Installing both, e.g. with
+``industrydb[postgres,mssql]``, without ``industrydb-all`` is an error
+rather than a silent loss of one connector.
+"""
+
+import importlib
+import importlib.util
+import sys
+
+# Companion packages, most complete first
+_VARIANTS = ("industrydb_all", "industrydb_postgres", "industrydb_mssql")
+
+
+def _load_variant() -> None:
+    found = [package for package in _VARIANTS if importlib.util.find_spec(package) is not None]
+    if not found:
+        return
+    if len(found) > 1 and "industrydb_all" not in found:
+        extras = ",".join(package[len("industrydb_") :] for package in found)
+        raise ImportError(
+            f"industrydb[{extras}] installs companion wheels that each carry a single "
+            f"extra connector ({', '.join(found)}) and cannot be combined; "
+            "install industrydb[all] for every connector"
+        )
+    sys.modules["industrydb.industrydb"] = importlib.import_module(f"{found[0]}.industrydb")
+
+
+_load_variant()
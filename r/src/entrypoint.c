@@ -0,0 +1,6 @@
+// Registers the routines generated by extendr_module! in the Rust crate
+void R_init_industrydb_extendr(void *dll);
+
+void R_init_industrydb(void *dll) {
+    R_init_industrydb_extendr(dll);
+}
@@ -1,5 +1,7 @@
 """Basic tests for IndustryDB."""
 
+import time
+
 import polars as pl
 import pytest
 
@@ -16,6 +18,23 @@ def test_import():
     assert hasattr(idb, "DatabaseConfig")
 
 
+def test_available_connectors():
+    """Test the loaded extension reports its compiled connectors."""
+    connectors = idb.available_connectors()
+    assert "sqlite" in connectors
+    assert set(connectors) <= {"sqlite", "postgres", "mssql"}
+
+
+def test_plugin_connectors(tmp_path):
+    """Test plugin loading errors and configs naming an unknown connector."""
+    with pytest.raises(idb.ConfigurationError):
+        idb.load_plugin(str(tmp_path / "missing_plugin.so"))
+
+    config = idb.DatabaseConfig(db_type="postgres", connector="historian")
+    with pytest.raises(idb.ConfigurationError, match="No connector named 'historian'"):
+        idb.Connection(config)
+
+
 def test_database_config_creation():
     """Test creating database configurations."""
     # SQLite config
@@ -66,7 +85,7 @@ def test_sqlite_connection(tmp_path):
         assert not conn.is_closed()
 
         # Create table
-        conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+        conn.execute_statement("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
 
         # Insert data
         df = pl.DataFrame({"id": [1, 2, 3], "name": ["Alice", "Bob", "Charlie"]})
@@ -96,7 +115,7 @@ def test_context_manager(tmp_path):
 
     # Use as context manager
     with conn:
-        conn.execute("CREATE TABLE test (id INTEGER)")
+        conn.execute_statement("CREATE TABLE test (id INTEGER)")
 
     # Should be closed after exiting context
     assert conn.is_closed()
@@ -110,11 +129,11 @@ def test_execute_query(tmp_path):
 
     with idb.Connection(config) as conn:
         # DDL
-        conn.execute("CREATE TABLE products (id INTEGER, name TEXT, price REAL)")
+        conn.execute_statement("CREATE TABLE products (id INTEGER, name TEXT, price REAL)")
 
         # INSERT
-        conn.execute("INSERT INTO products VALUES (1, 'Widget', 9.99)")
-        conn.execute("INSERT INTO products VALUES (2, 'Gadget', 19.99)")
+        conn.execute_statement("INSERT INTO products VALUES (1, 'Widget', 9.99)")
+        conn.execute_statement("INSERT INTO products VALUES (2, 'Gadget', 19.99)")
 
         # SELECT
         df = conn.execute("SELECT * FROM products WHERE price > 10")
@@ -122,5 +141,1837 @@ def test_execute_query(tmp_path):
         assert df["name"][0] == "Gadget"
 
 
+def test_schema_hints(tmp_path):
+    """Test execute and select cast result columns to hinted dtypes."""
+    from datetime import datetime
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_hints.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, value REAL)")
+        conn.execute_statement("INSERT INTO readings VALUES ('2024-03-01 08:00:00', 2)")
+
+        df = conn.execute(
+            "SELECT * FROM readings", schema={"value": "f64", "ts": "datetime[ms]"}
+        )
+        assert df.schema["value"] == pl.Float64
+        assert df.schema["ts"] == pl.Datetime("ms")
+        assert df["ts"][0] == datetime(2024, 3, 1, 8)
+
+        df = conn.select("readings", columns=["value"], schema={"value": pl.Float32})
+        assert df.schema["value"] == pl.Float32
+
+        with pytest.raises(idb.IndustryDbError, match="not in the result"):
+            conn.execute("SELECT value FROM readings", schema={"pressure": "f64"})
+
+
+def test_table_utilities(tmp_path):
+    """Test table_exists, truncate, rename_table and drop_table."""
+    db_path = tmp_path / "test_tables.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts INTEGER, value REAL)")
+        conn.execute_statement("INSERT INTO readings VALUES (1, 0.5)")
+        assert conn.table_exists("readings")
+        assert not conn.table_exists("missing")
+
+        conn.truncate("readings")
+        assert conn.execute("SELECT * FROM readings").height == 0
+
+        conn.rename_table("readings", "readings_old")
+        assert not conn.table_exists("readings")
+        assert conn.table_exists("readings_old")
+
+        conn.drop_table("readings_old")
+        assert not conn.table_exists("readings_old")
+        conn.drop_table("readings_old")
+
+
+def test_upsert(tmp_path):
+    """Test upsert updates existing keys and inserts new ones."""
+    db_path = tmp_path / "test_upsert.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT PRIMARY KEY, value REAL)")
+        conn.insert("tags", {"name": ["a"], "value": [1.0]})
+
+        conn.upsert("tags", {"name": ["a", "b"], "value": [2.0, 3.0]}, ["name"])
+
+        df = conn.select("tags", where_clause="name = 'a'")
+        assert df["value"][0] == 2.0
+        assert conn.execute("SELECT * FROM tags").height == 2
+
+
+def test_insert_validation(tmp_path):
+    """Test validating data against the target table before inserting."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_validate.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT NOT NULL, tag TEXT, value REAL)")
+        assert conn.insert("readings", {"TS": ["2024-03-01 08:00:00"], "value": [1]}, validate=True) == 1
+
+        bad = {"ts": ["2024-03-01 08:01:00", None], "value": ["n/a", "2.5"], "unit": ["C", "C"]}
+        with pytest.raises(idb.IndustryDbError) as raised:
+            conn.insert("readings", bad, validate=True)
+        message = str(raised.value)
+        assert "column 'ts' has 1 null values" in message
+        assert "column 'value' holds str values but the table column is REAL" in message
+        assert "column 'unit' is not a column of the table" in message
+        assert conn.execute("SELECT * FROM readings").height == 1
+
+        with pytest.raises(idb.IndustryDbError, match="does not exist"):
+            conn.upsert("missing", {"ts": ["x"]}, ["ts"], validate=True)
+
+
+def test_insert_coercion(tmp_path):
+    """Test converting CSV-like text to the column types on insert."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_coerce.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement(
+            "CREATE TABLE readings (ts TIMESTAMP NOT NULL, tag VARCHAR(6), value REAL, ok BOOLEAN)"
+        )
+        messy = {
+            "ts": [" 2024-03-01 08:00:00", "2024-03-01T09:00:00"],
+            "tag": ["TI-101", "TI-1002"],
+            "value": ["1.5", "n/a"],
+            "ok": ["yes", ""],
+        }
+        with pytest.raises(idb.ConstraintViolationError) as raised:
+            conn.insert("readings", messy, coerce="strict")
+        message = str(raised.value)
+        assert "column 'tag' has 1 values longer than the column's 6 characters" in message
+        assert "such as 'n/a'" in message
+        assert conn.execute("SELECT * FROM readings").height == 0
+
+        policy = {"default": "lossy", "columns": {"tag": "truncate"}}
+        assert conn.insert("readings", messy, coerce=policy, validate=True) == 2
+        df = conn.execute("SELECT tag, value, ok FROM readings ORDER BY ts")
+        assert df["tag"].to_list() == ["TI-101", "TI-100"]
+        assert df["value"].to_list() == [1.5, None]
+        assert df["ok"][0] and df["ok"][1] is None
+
+        with pytest.raises(idb.IndustryDbError, match="Unsupported coercion"):
+            conn.insert("readings", messy, coerce="loose")
+
+
+def test_audit_columns(tmp_path):
+    """Test filling created/updated audit columns on write."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_audit.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement(
+            "CREATE TABLE tags (name TEXT PRIMARY KEY, value REAL, "
+            "created_at TIMESTAMP, updated_at TIMESTAMP, created_by TEXT)"
+        )
+        conn.insert("tags", {"name": ["a", "b"], "value": [1.0, 2.0]}, audit={"user": "etl"})
+        first = conn.execute("SELECT * FROM tags ORDER BY name")
+        assert first["created_by"].to_list() == ["etl", "etl"]
+        assert first["created_at"].null_count() == 0
+        assert first["updated_at"].to_list() == first["created_at"].to_list()
+
+        time.sleep(0.01)
+        conn.upsert("tags", {"name": ["a", "c"], "value": [5.0, 3.0]}, ["name"], audit={"user": "sync"})
+        rows = conn.execute("SELECT * FROM tags ORDER BY name")
+        assert rows["value"].to_list() == [5.0, 2.0, 3.0]
+        assert rows["created_by"].to_list() == ["etl", "etl", "sync"]
+        assert rows["created_at"][0] == first["created_at"][0]
+        assert rows["updated_at"][0] > first["updated_at"][0]
+
+        time.sleep(0.01)
+        conn.update("tags", {"value": 7}, where_clause="name = ?", params=["b"], audit=True)
+        b = conn.execute("SELECT * FROM tags WHERE name = 'b'")
+        assert b["updated_at"][0] > first["updated_at"][1]
+        assert b["created_at"][0] == first["created_at"][1]
+
+        conn.insert("tags", {"name": ["d"], "value": [4.0]})
+        assert conn.execute("SELECT created_at FROM tags WHERE name = 'd'")["created_at"][0] is None
+
+
+def test_bound_parameters(tmp_path):
+    """Test parameters are bound to placeholders instead of interpolated."""
+    db_path = tmp_path / "test_params.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT, value REAL)")
+        conn.insert("tags", {"name": ["a", "b'c", "d"], "value": [1.0, 2.0, 3.0]})
+
+        df = conn.select("tags", where_clause="name = ?", params=["b'c"])
+        assert df["value"][0] == 2.0
+        assert conn.execute("SELECT * FROM tags WHERE value > ?", [1.5]).height == 2
+
+        assert conn.update("tags", {"value": 10}, where_clause="name = ?", params=["a"]) == 1
+        assert conn.delete("tags", where_clause="value < ?", params=[5]) == 2
+
+
+def test_decimal_parameters(tmp_path):
+    """Test decimal.Decimal parameters are bound without going through float."""
+    from decimal import Decimal
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_decimal.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE prices (tag TEXT, price NUMERIC)")
+        conn.execute_statement(
+            "INSERT INTO prices VALUES (?, ?)", ["a", Decimal("12.50")]
+        )
+
+        df = conn.execute("SELECT price FROM prices WHERE price = ?", [Decimal("12.5")])
+        assert df["price"][0] == 12.5
+
+        with pytest.raises(Exception):
+            conn.execute("SELECT ?", [Decimal("NaN")])
+
+
+def test_null_round_trip(tmp_path):
+    """Test nulls read back as None in every column type, never as a default."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_nulls.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement(
+            "CREATE TABLE samples "
+            "(id INTEGER, count INTEGER, value REAL, tag TEXT, flag BOOLEAN, raw BLOB, note TEXT)"
+        )
+        conn.insert(
+            "samples",
+            {
+                "id": [1, 2, 3],
+                "count": [7, None, 0],
+                "value": [None, 0.5, 0.0],
+                "tag": ["", None, "TI-101"],
+                "flag": [None, False, True],
+                "raw": [b"", None, b"\x00"],
+                "note": [None, None, None],
+            },
+        )
+
+        df = conn.execute("SELECT * FROM samples ORDER BY id")
+        assert df["count"].to_list() == [7, None, 0]
+        assert df["value"].to_list() == [None, 0.5, 0.0]
+        assert df["tag"].to_list() == ["", None, "TI-101"]
+        assert df["flag"].to_list() == [None, False, True]
+        assert df["raw"].to_list() == [b"", None, b"\x00"]
+        assert df["note"].to_list() == [None, None, None]
+
+        df = conn.select("samples", columns=["tag"], where_clause="tag IS NULL")
+        assert df["tag"].to_list() == [None]
+
+
+def test_null_literals(tmp_path):
+    """Test NULL expressions next to values of each type read back as None."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_null_literals.db"))
+
+    with idb.Connection(config) as conn:
+        df = conn.execute(
+            "SELECT 'a' AS s, 1 AS i, 1.5 AS f, x'01' AS b "
+            "UNION ALL SELECT NULL, NULL, NULL, NULL"
+        )
+        for column in ["s", "i", "f", "b"]:
+            assert df[column][1] is None, column
+
+
+def test_wide_integers(tmp_path):
+    """Test ints beyond 64 bits stay exact instead of becoming floats."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_wide.db"))
+
+    with idb.Connection(config) as conn:
+        df = conn.execute("SELECT ? AS big", [2**70])
+        assert df["big"][0] == str(2**70)
+
+        with pytest.raises(Exception):
+            conn.execute("SELECT ?", [10**40])
+
+    with pytest.raises(ValueError, match="Invalid overflow"):
+        idb.DatabaseConfig(db_type="postgres", overflow="wrap")
+
+
+def test_uuid_values(tmp_path):
+    """Test uuid.UUID values are bound and inserted as their text form."""
+    import uuid
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_uuid.db"))
+    batch = uuid.uuid4()
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE batches (id TEXT, line TEXT)")
+        conn.insert("batches", {"id": [batch, uuid.uuid4()], "line": ["L1", "L2"]})
+
+        df = conn.execute("SELECT line FROM batches WHERE id = ?", [batch])
+        assert df["line"].to_list() == ["L1"]
+        assert conn.execute("SELECT ? AS id", [batch])["id"][0] == str(batch)
+
+
+def test_binary_values(tmp_path):
+    """Test bytes are inserted, bound and read back as bytes."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_binary.db"))
+    wave = bytes([0, 255, 7, 0])
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE waveforms (tag TEXT, wave BLOB)")
+        conn.insert("waveforms", {"tag": ["TI-101", "TI-102"], "wave": [wave, None]})
+        conn.execute_statement("INSERT INTO waveforms VALUES (?, ?)", ["TI-103", b"\x01"])
+
+        df = conn.execute("SELECT wave FROM waveforms ORDER BY tag")
+        assert df["wave"].to_list() == [wave, None, b"\x01"]
+        assert conn.execute("SELECT tag FROM waveforms WHERE wave = ?", [wave])["tag"][0] == "TI-101"
+
+
+def test_list_cells_need_array_columns(tmp_path):
+    """Test list-valued cells are refused by databases without arrays."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_lists.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE spectra (tag TEXT, bins TEXT)")
+        with pytest.raises(Exception, match="array"):
+            conn.insert("spectra", {"tag": ["VI-1"], "bins": [[1.0, 2.5]]})
+
+
+def test_named_parameters(tmp_path):
+    """Test :name and @name parameters are rewritten to placeholders."""
+    db_path = tmp_path / "test_named_params.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT, value REAL)")
+        conn.insert("tags", {"name": ["a", "b", "c"], "value": [1.0, 2.0, 3.0]})
+
+        df = conn.execute(
+            "SELECT * FROM tags WHERE value >= :low AND value <= @high AND name != :low_name",
+            {"low": 1.5, "high": 3.0, "low_name": "c"},
+        )
+        assert df["name"].to_list() == ["b"]
+
+        assert conn.select("tags", where_clause="name = :name", params={"name": "a"}).height == 1
+        assert conn.delete("tags", where_clause="value > :v", params={"v": 2}) == 1
+
+        with pytest.raises(Exception):
+            conn.execute("SELECT * FROM tags", {"unused": 1})
+
+
+def test_execute_statement(tmp_path):
+    """Test rows_affected from statements and execute() rejecting them."""
+    db_path = tmp_path / "test_statement.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        assert conn.execute_statement("CREATE TABLE tags (name TEXT, value REAL)") == 0
+        inserted = conn.execute_statement(
+            "INSERT INTO tags VALUES (?, ?), (?, ?)", ["a", 1.0, "b", 2.0]
+        )
+        assert inserted == 2
+        assert conn.execute_statement("UPDATE tags SET value = :v", {"v": 3.0}) == 2
+
+        with pytest.raises(idb.QueryExecutionError, match="execute_statement"):
+            conn.execute("DELETE FROM tags")
+        assert conn.fetch_scalar("SELECT count(*) FROM tags") == 2
+
+
+def test_execute_many(tmp_path):
+    """Test execute_many with positional and named parameter sets."""
+    db_path = tmp_path / "test_many.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT PRIMARY KEY, value REAL)")
+        rows = [("a", 1.0), ("b", 2.0), ("c", 3.0)]
+        assert conn.execute_many("INSERT INTO tags VALUES (?, ?)", rows) == 3
+        updated = conn.execute_many(
+            "UPDATE tags SET value = :value WHERE name = :name",
+            [{"name": "a", "value": 10.0}, {"name": "b", "value": 20.0}],
+        )
+        assert updated == 2
+        assert conn.execute_many("INSERT INTO tags VALUES (?, ?)", []) == 0
+
+        # The duplicate key fails the third set; the transaction keeps the
+        # first two out as well
+        with pytest.raises(idb.QueryExecutionError, match="Parameter set 3"):
+            conn.execute_many(
+                "INSERT INTO tags VALUES (?, ?)", [("d", 4.0), ("e", 5.0), ("a", 0.0)]
+            )
+        assert conn.fetch_scalar("SELECT count(*) FROM tags") == 3
+
+
+def test_execute_result_sets(tmp_path):
+    """Test result sets are returned as a list (one set outside SQL Server)."""
+    db_path = tmp_path / "test_result_sets.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT, value REAL)")
+        conn.insert("tags", {"name": ["a", "b"], "value": [1.0, 2.0]})
+
+        sets = conn.execute_result_sets("SELECT * FROM tags WHERE value > ?", [1.5])
+        assert len(sets) == 1
+        assert sets[0]["name"].to_list() == ["b"]
+
+
+def test_call_procedure(tmp_path):
+    """Test procedure calls fail on SQLite and outputs must be named."""
+    db_path = tmp_path / "test_procedure.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    out = idb.Output("INT", value=3)
+    assert out.sql_type == "INT"
+    assert out.value == 3
+    assert repr(idb.Output()) == "Output()"
+
+    with idb.Connection(config) as conn:
+        with pytest.raises(idb.IndustryDbError, match="stored procedures"):
+            conn.call_procedure("line_stats", {"line": "L1", "total": idb.Output("INT")})
+
+
+def test_call_function(tmp_path):
+    """Test table-valued functions with typed arguments."""
+    db_path = tmp_path / "test_function.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        df = conn.call_function("json_each", ["[10, 20, 30]"], types=["TEXT"])
+        assert df["value"].to_list() == [10, 20, 30]
+
+        with pytest.raises(ValueError):
+            conn.call_function("json_each", ["[1]"], types=["TEXT", "TEXT"])
+        with pytest.raises(idb.IndustryDbError, match="only supported by PostgreSQL"):
+            conn.call_function("json_each", {"json": "[1]"})
+
+
+def test_listen_requires_postgres(tmp_path):
+    """Test LISTEN/NOTIFY subscriptions are refused outside PostgreSQL."""
+    db_path = tmp_path / "test_listen.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        with pytest.raises(idb.ConfigurationError, match="PostgreSQL"):
+            conn.listen("alarms")
+
+
+def test_change_feed_requires_postgres(tmp_path):
+    """Test that change feeds are rejected on non-PostgreSQL connections."""
+    db_path = tmp_path / "test_change_feed.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        with pytest.raises(idb.ConfigurationError, match="PostgreSQL"):
+            conn.change_feed("mes_slot", "mes")
+
+
+def test_sqlite_pragmas(tmp_path):
+    """Test SQLite connections use WAL and the configured PRAGMAs."""
+    db_path = tmp_path / "test_pragmas.db"
+
+    config = idb.DatabaseConfig(
+        db_type="sqlite",
+        path=str(db_path),
+        sqlite={"busy_timeout_ms": 2000, "cache_size": -4000},
+    )
+
+    with idb.Connection(config) as conn:
+        assert conn.fetch_scalar("PRAGMA journal_mode") == "wal"
+        assert conn.fetch_scalar("PRAGMA busy_timeout") == 2000
+        assert conn.fetch_scalar("PRAGMA cache_size") == -4000
+    assert db_path.exists()
+
+    with pytest.raises(ValueError, match="Invalid SQLite options"):
+        idb.DatabaseConfig(db_type="sqlite", path=str(db_path), sqlite={"busy_timeout_ms": "x"})
+
+
+def test_timestamps_option():
+    """Test the timestamp zone setting is validated."""
+    idb.DatabaseConfig(db_type="sqlite", path=":memory:", timestamps="Europe/Berlin")
+    with pytest.raises(ValueError, match="Invalid timestamps"):
+        idb.DatabaseConfig(db_type="sqlite", path=":memory:", timestamps="Europe Berlin")
+
+
+def test_backup_and_restore(tmp_path):
+    """Test SQLite online backups restore the snapshot taken."""
+    db_path = tmp_path / "test_backup.db"
+    backup_path = tmp_path / "snapshot.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT)")
+        conn.insert("tags", {"name": ["a", "b"]})
+        conn.backup_to(backup_path)
+        assert backup_path.exists()
+
+        conn.insert("tags", {"name": ["c"]})
+        conn.restore_from(backup_path)
+        assert conn.count("tags") == 2
+
+        with pytest.raises(idb.IndustryDbError):
+            conn.restore_from(tmp_path / "missing.db")
+
+
+def test_attach(tmp_path):
+    """Test joins across attached SQLite files."""
+    db_path = tmp_path / "test_attach.db"
+    day1 = tmp_path / "day1.db"
+    day2 = tmp_path / "day2.db"
+
+    config = idb.DatabaseConfig(
+        db_type="sqlite", path=str(db_path), sqlite={"attach": {"day1": str(day1)}}
+    )
+
+    with idb.Connection(config) as conn:
+        conn.attach(day2, "day2")
+        conn.execute_batch(
+            """
+            CREATE TABLE day1.readings (tag TEXT, value REAL);
+            CREATE TABLE day2.readings (tag TEXT, value REAL);
+            INSERT INTO day1.readings VALUES ('TI-101', 1.0);
+            INSERT INTO day2.readings VALUES ('TI-101', 2.0);
+            """
+        )
+        total = conn.fetch_scalar(
+            "SELECT sum(value) FROM (SELECT value FROM day1.readings "
+            "UNION ALL SELECT value FROM day2.readings)"
+        )
+        assert total == 3.0
+
+        conn.detach("day2")
+        with pytest.raises(idb.QueryExecutionError):
+            conn.execute("SELECT * FROM day2.readings")
+        with pytest.raises(idb.IndustryDbError):
+            conn.attach(day2, "main")
+
+
+def test_write_locks(tmp_path):
+    """Test writers sharing a database serialize per table with wait stats."""
+    import threading
+
+    db_path = tmp_path / "test_write_locks.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as setup:
+        setup.execute_statement("CREATE TABLE tags (name TEXT, value REAL)")
+        assert setup.write_lock_stats() == {}
+
+    def writer(worker):
+        with idb.Connection(config) as conn:
+            conn.enable_write_locks()
+            for i in range(5):
+                conn.insert("tags", {"name": [f"w{worker}-{i}"], "value": [float(i)]})
+
+    threads = [threading.Thread(target=writer, args=(n,)) for n in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    with idb.Connection(config) as conn:
+        conn.enable_write_locks()
+        assert conn.count("tags") == 20
+        stats = conn.write_lock_stats()["tags"]
+        assert stats["acquisitions"] == 20
+        assert stats["max_wait_us"] <= stats["total_wait_us"]
+
+
+def test_dashboard(tmp_path):
+    """Test latest values, downsampled trends and alarm counts."""
+    db_path = tmp_path / "test_dashboard.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_batch(
+            """
+            CREATE TABLE readings (ts TEXT, tag TEXT, value REAL);
+            CREATE TABLE alarms (ts TEXT, tag TEXT);
+            """
+        )
+        conn.insert(
+            "readings",
+            {
+                "ts": [
+                    "2024-03-01 08:00:10",
+                    "2024-03-01 08:00:40",
+                    "2024-03-01 08:01:20",
+                    "2024-03-01 08:00:30",
+                ],
+                "tag": ["TI-101", "TI-101", "TI-101", "PI-200"],
+                "value": [1.0, 3.0, 10.0, 5.0],
+            },
+        )
+        conn.insert(
+            "alarms",
+            {
+                "ts": ["2024-03-01 08:00:00", "2024-03-01 09:00:00", "2024-03-01 09:30:00"],
+                "tag": ["PI-200", "TI-101", "TI-101"],
+            },
+        )
+
+        dash = idb.Dashboard(conn, ttl=60)
+        latest = dash.latest()
+        assert latest["tag"].to_list() == ["PI-200", "TI-101"]
+        assert latest["value"].to_list() == [5.0, 10.0]
+
+        trend = dash.trend(
+            ["TI-101"], start="2024-03-01 08:00:00", end="2024-03-01 09:00:00", interval="1m"
+        )
+        assert trend["avg"].to_list() == [2.0, 10.0]
+        assert trend["count"].to_list() == [2, 1]
+
+        alarms = dash.alarm_counts(start="2024-03-01", end="2024-03-02")
+        assert alarms["tag"].to_list() == ["TI-101", "PI-200"]
+        assert alarms["alarms"].to_list() == [2, 1]
+        assert not dash.stale
+
+        # Without caching, the last good result is served once queries fail
+        uncached = idb.Dashboard(conn, ttl=0)
+        assert uncached.latest()["value"].to_list() == [5.0, 10.0]
+        conn.drop_table("readings")
+        assert uncached.latest()["value"].to_list() == [5.0, 10.0]
+        assert uncached.stale
+        assert uncached.last_error is not None
+        with pytest.raises(idb.QueryExecutionError):
+            uncached.trend(start="2024-03-01", end="2024-03-02")
+
+
+def test_query_result(tmp_path):
+    """Test QueryResult metadata for statements and queries."""
+    db_path = tmp_path / "test_query_result.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        created = conn.query("CREATE TABLE tags (name TEXT, value REAL)")
+        assert created.rows_affected == 0
+        assert len(created) == 0
+
+        inserted = conn.query("INSERT INTO tags VALUES (?, ?), (?, ?)", ["a", 1.0, "b", 2.0])
+        assert inserted.rows_affected == 2
+
+        result = conn.query("SELECT name, value FROM tags ORDER BY name")
+        assert result.rows_affected is None
+        assert result.height == 2
+        assert list(result.column_types) == ["name", "value"]
+        assert result["name"] == ["a", "b"]
+        assert result.warnings == []
+        assert result.elapsed >= 0
+
+        empty = conn.query("SELECT name FROM tags WHERE value > 10")
+        assert empty.height == 0
+        assert empty.warnings
+
+
+def test_execute_cached(tmp_path):
+    """Test cached results and invalidation by table."""
+    db_path = tmp_path / "test_cache.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        with pytest.raises(RuntimeError, match="enable_cache"):
+            conn.execute_cached("SELECT 1")
+
+        conn.execute_statement("CREATE TABLE readings (tag TEXT, value REAL)")
+        conn.execute_statement("INSERT INTO readings VALUES ('TI-101', 20.5)")
+        conn.enable_cache(ttl=300)
+
+        sql = "SELECT * FROM readings WHERE tag = ?"
+        assert conn.execute_cached(sql, ["TI-101"]).height == 1
+        conn.execute_statement("INSERT INTO readings VALUES ('TI-101', 21.0)")
+        assert conn.execute_cached(sql, ["TI-101"]).height == 1
+
+        conn.invalidate_cache("readings")
+        assert conn.execute_cached(sql, ["TI-101"]).height == 2
+
+        conn.execute_statement("INSERT INTO readings VALUES ('TI-101', 21.5)")
+        reformatted = "SELECT *\n  FROM readings\n  WHERE tag = ?;"
+        assert conn.execute_cached(reformatted, ["TI-101"]).height == 2
+
+
+def test_fetch_one_and_scalar(tmp_path):
+    """Test single-row and single-value lookups."""
+    db_path = tmp_path / "test_fetch_one.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (tag TEXT, ts INTEGER, value REAL)")
+        conn.execute_statement("INSERT INTO readings VALUES ('TI-101', 1, 20.5), ('TI-101', 2, 21.0)")
+
+        row = conn.fetch_one("SELECT * FROM readings WHERE ts = ?", [2])
+        assert row == {"tag": "TI-101", "ts": 2, "value": 21.0}
+        assert conn.fetch_one("SELECT * FROM readings WHERE ts = :ts", {"ts": 9}) is None
+
+        assert conn.fetch_scalar("SELECT max(ts) FROM readings") == 2
+        assert conn.fetch_scalar("SELECT ts FROM readings WHERE tag = 'none'") is None
+
+
+def test_hierarchy(tmp_path):
+    """Test flattening an equipment hierarchy with depth and path."""
+    db_path = tmp_path / "test_hierarchy.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE equipment (id INTEGER, parent_id INTEGER, name TEXT)")
+        conn.execute_statement(
+            "INSERT INTO equipment VALUES "
+            "(1, NULL, 'Plant'), (2, 1, 'Line 1'), (3, 2, 'Pump 3'), (4, 1, 'Line 2')"
+        )
+
+        df = conn.hierarchy("equipment", "id", "parent_id", label_column="name")
+        assert df["path"].to_list() == [
+            "Plant",
+            "Plant/Line 1",
+            "Plant/Line 1/Pump 3",
+            "Plant/Line 2",
+        ]
+        assert df["depth"].to_list() == [0, 1, 2, 1]
+
+        df = conn.hierarchy("equipment", "id", "parent_id", roots=[2], max_depth=0)
+        assert df["id"].to_list() == [2]
+
+
+def test_select_range(tmp_path):
+    """Test reading a time range with datetime bounds and a tag filter."""
+    from datetime import date, datetime, timedelta, timezone
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_range.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, tag TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?, ?)",
+            [
+                ("2024-03-01 07:59:59", "TI-101", 1.0),
+                ("2024-03-01 08:00:00", "TI-101", 2.0),
+                ("2024-03-01 08:30:00", "TI-102", 3.0),
+                ("2024-03-01 09:00:00", "TI-101", 4.0),
+            ],
+        )
+
+        start = datetime(2024, 3, 1, 8)
+        df = conn.select_range("readings", "ts", start, "2024-03-01 09:00")
+        assert df["value"].to_list() == [2.0, 3.0]
+
+        df = conn.select_range(
+            "readings",
+            "ts",
+            datetime(2024, 3, 1, 7, tzinfo=timezone(timedelta(hours=-1))),
+            date(2024, 3, 2),
+            tags=["TI-101"],
+            columns=["value"],
+        )
+        assert df.columns == ["value"]
+        assert df["value"].to_list() == [2.0, 4.0]
+
+        df = conn.select_range(
+            "readings",
+            "ts",
+            "2024-03-01",
+            "2024-03-02",
+            tags=["TI-101", "TI-102"],
+            columns=["ts", "tag", "value"],
+            resample="1h",
+            agg="max",
+        )
+        assert [ts.hour for ts in df["ts"].to_list()] == [7, 8, 8, 9]
+        assert df["tag"].to_list() == ["TI-101", "TI-101", "TI-102", "TI-101"]
+        assert df["value"].to_list() == [1.0, 2.0, 3.0, 4.0]
+
+        df = conn.select_range(
+            "readings",
+            "ts",
+            "2024-03-01 07:00",
+            "2024-03-01 10:00",
+            tags=["TI-101", "TI-102"],
+            columns=["ts", "tag", "value"],
+            resample="1h",
+            fill="forward",
+        )
+        assert [ts.hour for ts in df["ts"].to_list()] == [7, 8, 9, 7, 8, 9]
+        assert df["tag"].to_list() == ["TI-101"] * 3 + ["TI-102"] * 3
+        assert df["value"].to_list() == [1.0, 2.0, 4.0, None, 3.0, 3.0]
+
+        with pytest.raises(idb.IndustryDbError, match="not before"):
+            conn.select_range("readings", "ts", start, start)
+        with pytest.raises(idb.IndustryDbError, match="resampled"):
+            conn.select_range("readings", "ts", start, "2024-03-02", fill="linear")
+
+
+def test_select_bucketed(tmp_path):
+    """Test aggregating readings into time buckets."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_bucketed.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?)",
+            [
+                ("2024-03-01 08:01:00", 1.0),
+                ("2024-03-01 08:04:59", 3.0),
+                ("2024-03-01 08:05:00", 10.0),
+                ("2024-03-01 08:17:30", 20.0),
+            ],
+        )
+
+        df = conn.select_bucketed("readings", "ts", ["value"], bucket="5m")
+        assert df.columns == ["bucket", "value_avg", "value_min", "value_max"]
+        assert [ts.strftime("%H:%M") for ts in df["bucket"].to_list()] == ["08:00", "08:05", "08:15"]
+        assert df["value_avg"].to_list() == [2.0, 10.0, 20.0]
+
+        df = conn.select_bucketed("readings", "ts", ["value"], bucket="1h", aggs=["count", "sum"])
+        assert df["value_count"].to_list() == [4]
+        assert df["value_sum"].to_list() == [34.0]
+
+        df = conn.select_bucketed("readings", "ts", ["value"], bucket="5m", aggs=["avg"], fill="linear")
+        assert [ts.strftime("%H:%M") for ts in df["bucket"].to_list()] == ["08:00", "08:05", "08:10", "08:15"]
+        assert df["value_avg"].to_list() == [2.0, 10.0, 15.0, 20.0]
+
+        with pytest.raises(idb.IndustryDbError, match="aggregate"):
+            conn.select_bucketed("readings", "ts", ["value"], aggs=["median"])
+
+
+def test_continuous_aggregate_fallback(tmp_path):
+    """Test that continuous aggregates fall back to the table without TimescaleDB."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_caggs.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?)",
+            [("2024-03-01 08:10:00", 1.0), ("2024-03-01 09:10:00", 3.0)],
+        )
+        assert conn.timescale_version() is None
+
+        df = conn.select_bucketed(
+            "readings", "ts", ["value"], bucket="1h", aggs=["max"], continuous_aggregate="readings_hourly"
+        )
+        assert df.columns == ["bucket", "value_max"]
+        assert df["value_max"].to_list() == [1.0, 3.0]
+
+        with pytest.raises(idb.IndustryDbError, match="TimescaleDB"):
+            conn.create_continuous_aggregate("readings_hourly", "readings", "ts", ["value"])
+        with pytest.raises(idb.IndustryDbError, match="TimescaleDB"):
+            conn.refresh_continuous_aggregate("readings_hourly")
+
+
+def test_pivoted(tmp_path):
+    """Test writing a wide frame to a tag-value table and reading it back."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_pivoted.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, tag TEXT, value REAL)")
+        rows = conn.insert_pivoted(
+            "readings",
+            {
+                "ts": ["2024-03-01 08:00:00", "2024-03-01 08:01:00"],
+                "TI-101": [1.0, 2.0],
+                "TI-102": [3.0, None],
+            },
+            "ts",
+            "tag",
+            "value",
+        )
+        assert rows == 3
+        assert conn.execute("SELECT count(*) AS n FROM readings")["n"].to_list() == [3]
+
+        df = conn.read_pivoted("readings", "ts", "tag", "value")
+        assert df.columns == ["ts", "TI-101", "TI-102"]
+        assert [ts.minute for ts in df["ts"].to_list()] == [0, 1]
+        assert df["TI-101"].to_list() == [1.0, 2.0]
+        assert df["TI-102"].to_list() == [3.0, None]
+
+        df = conn.read_pivoted(
+            "readings",
+            "ts",
+            "tag",
+            "value",
+            tags=["TI-102", "TI-103"],
+            start="2024-03-01 08:00",
+            end="2024-03-01 08:01",
+        )
+        assert df.columns == ["ts", "TI-102", "TI-103"]
+        assert df["TI-102"].to_list() == [3.0]
+        assert df["TI-103"].to_list() == [None]
+
+        with pytest.raises(idb.IndustryDbError, match="start and end"):
+            conn.read_pivoted("readings", "ts", "tag", "value", start="2024-03-01")
+
+
+def test_value_at(tmp_path):
+    """Test looking up tag values at a point in time."""
+    from datetime import datetime
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_value_at.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, tag TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?, ?)",
+            [
+                ("2024-03-01 07:50:00", "TI-101", 1.0),
+                ("2024-03-01 08:10:00", "TI-101", 3.0),
+                ("2024-03-01 07:59:00", "TI-102", 5.0),
+                ("2024-03-01 08:30:00", "TI-103", 7.0),
+            ],
+        )
+
+        at = datetime(2024, 3, 1, 8)
+        df = conn.value_at("readings", "ts", ["TI-101", "TI-102", "TI-103"], at)
+        assert df.columns == ["tag", "ts", "value"]
+        assert df["tag"].to_list() == ["TI-101", "TI-102", "TI-103"]
+        assert df["value"].to_list() == [1.0, 5.0, None]
+        assert df["ts"].to_list()[0] == datetime(2024, 3, 1, 7, 50)
+
+        df = conn.value_at("readings", "ts", "TI-101", at, mode="interpolated")
+        assert df["value"].to_list() == [2.0]
+        assert df["ts"].to_list() == [at]
+
+        with pytest.raises(idb.IndustryDbError, match="mode"):
+            conn.value_at("readings", "ts", "TI-101", at, mode="nearest")
+
+
+def test_quality_checks(tmp_path):
+    """Test running data quality checks on a frame and a table."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_quality.db"))
+    checks = [
+        {"check": "null_rate", "column": "value", "max_rate": 0.5},
+        {"check": "range", "column": "value", "min": 0, "max": 100},
+        {"check": "monotonic_timestamps", "column": "ts", "by": ["tag"]},
+        {"check": "duplicate_keys", "columns": ["ts", "tag"]},
+    ]
+
+    report = idb.check_frame(
+        {"ts": [1, 2, 2, 1], "tag": ["A", "A", "A", "B"], "value": [1.0, None, 250.0, 3.0]}, checks
+    )
+    assert report["rows"] == 4
+    assert [r["passed"] for r in report["results"]] == [True, False, True, False]
+    assert [r["failing_rows"] for r in report["results"]] == [1, 1, 0, 2]
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, tag TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?, ?)",
+            [("2024-03-01 08:00:00", "A", 1.0), ("2024-03-01 08:01:00", "A", 2.0)],
+        )
+        report = conn.check_quality("readings", checks)
+        assert all(r["passed"] for r in report["results"])
+
+        report = conn.check_quality("SELECT ts FROM readings ORDER BY ts DESC", checks[2:3])
+        assert report["results"][0]["error"] == "column 'tag' is missing"
+
+    with pytest.raises(idb.IndustryDbError, match="min above max"):
+        idb.check_frame({"value": [1.0]}, [{"check": "range", "column": "value", "min": 2, "max": 1}])
+
+
+def test_tag_registry(tmp_path):
+    """Test registering tag metadata and joining it onto values."""
+    from datetime import datetime
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_tags.db"))
+
+    with idb.Connection(config) as conn:
+        conn.create_tag_registry()
+        conn.create_tag_registry()
+        assert conn.register_tags(
+            [
+                {"tag": "TI-101", "unit": "degC", "scale": 0.5, "scale_offset": -5.0},
+                {"tag": "FI-201", "unit": "m3/h", "source": "ns=2;s=FI201"},
+            ]
+        ) == 2
+        conn.register_tags([{"tag": "TI-101", "unit": "degF"}])
+
+        df = conn.tag_metadata()
+        assert df["tag"].to_list() == ["FI-201", "TI-101"]
+        assert df["unit"].to_list() == ["m3/h", "degF"]
+        assert df["scale"].to_list() == [None, None]
+        assert conn.tag_metadata(["FI-201", "XX-999"])["source"].to_list() == ["ns=2;s=FI201"]
+
+        conn.register_tags([{"tag": "TI-101", "unit": "degC", "scale": 0.5, "scale_offset": -5.0}])
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, tag TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?, ?)",
+            [("2024-03-01 07:50:00", "TI-101", 500.0), ("2024-03-01 07:55:00", "FI-201", 12.0)],
+        )
+        df = conn.value_at("readings", "ts", ["TI-101", "FI-201"], datetime(2024, 3, 1, 8), tag_table="tags")
+        assert df["unit"].to_list() == ["degC", "m3/h"]
+
+        df = conn.join_tag_metadata({"tag": ["TI-101", "XX-999"], "value": [500, 7]}, scale_column="value")
+        assert df["value"].to_list() == [245.0, 7.0]
+        assert df["unit"].to_list() == ["degC", None]
+
+        with pytest.raises(idb.IndustryDbError, match="twice"):
+            conn.register_tags([{"tag": "TI-101"}, {"tag": "TI-101"}])
+        assert conn.remove_tags(["TI-101", "XX-999"]) == 1
+        assert conn.tag_metadata()["tag"].to_list() == ["FI-201"]
+
+
+def test_enforce_retention(tmp_path):
+    """Test deleting rows older than a retention window in batches."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_retention.db"))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?)",
+            [(f"2024-03-{day:02d} 12:00:00", float(day)) for day in range(1, 11)],
+        )
+
+        archive = tmp_path / "expired.parquet"
+        summary = conn.enforce_retention(
+            "readings", "ts", "3d", batch_size=2, archive=archive, now="2024-03-10 13:00"
+        )
+        assert summary["cutoff"] == "2024-03-07 13:00:00"
+        assert summary["rows_deleted"] == 7
+        assert summary["partitions_dropped"] == []
+        assert pl.read_parquet(archive)["value"].to_list() == [float(day) for day in range(1, 8)]
+
+        df = conn.execute("SELECT value FROM readings ORDER BY ts")
+        assert df["value"].to_list() == [8.0, 9.0, 10.0]
+
+        summary = conn.enforce_retention("readings", "ts", "3d", now="2024-03-10 13:00")
+        assert summary["rows_deleted"] == 0
+
+        with pytest.raises(idb.IndustryDbError, match="batch size"):
+            conn.enforce_retention("readings", "ts", "3d", batch_size=0)
+
+
+def test_read_partitioned(tmp_path):
+    """Test reading a table and a query in concurrent partitions."""
+    db_path = tmp_path / "test_read_partitioned.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (id INTEGER, ts TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?, ?)",
+            [(i, f"2024-03-01 {i:02d}:00:00", i * 1.5) for i in range(24)]
+            + [(None, None, -1.0)],
+        )
+
+        df = conn.read_partitioned("readings", "id", num_partitions=4)
+        assert df.height == 25
+        assert sorted(df["value"].to_list()) == sorted([i * 1.5 for i in range(24)] + [-1.0])
+
+        df = conn.read_partitioned("SELECT id, ts FROM readings WHERE id >= 12", "ts", 3)
+        assert sorted(df["id"].to_list()) == list(range(12, 24))
+
+        with pytest.raises(Exception):
+            conn.read_partitioned("readings", "id", num_partitions=0)
+
+
+def test_fast_read_unsupported(tmp_path):
+    """Test that fast_read fails where ConnectorX is not available."""
+    db_path = tmp_path / "test_fast_read.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        with pytest.raises(idb.IndustryDbError, match="ConnectorX"):
+            conn.fast_read("SELECT 1 AS x")
+
+
+def test_export_copy_unsupported(tmp_path):
+    """Test that COPY exports fail outside PostgreSQL."""
+    db_path = tmp_path / "test_export_copy.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (id INTEGER)")
+        with pytest.raises(idb.IndustryDbError, match="COPY"):
+            conn.export_copy("readings", tmp_path / "readings.csv")
+        with pytest.raises(idb.IndustryDbError, match="Unsupported COPY format"):
+            conn.export_copy("readings", tmp_path / "readings.bin", format="text")
+
+
+def test_copy_from_csv_unsupported(tmp_path):
+    """Test that COPY imports fail outside PostgreSQL."""
+    db_path = tmp_path / "test_copy_from_csv.db"
+    csv_path = tmp_path / "readings.csv"
+    csv_path.write_text("id\n1\n")
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (id INTEGER)")
+        with pytest.raises(idb.IndustryDbError, match="COPY"):
+            conn.copy_from_csv("readings", csv_path)
+        with open(csv_path, "rb") as f, pytest.raises(idb.IndustryDbError, match="COPY"):
+            conn.copy_from_csv("readings", f, delimiter="|")
+
+
+def test_read_to_parquet(tmp_path):
+    """Test dumping a table and a query to Parquet files."""
+    db_path = tmp_path / "test_read_to_parquet.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (id INTEGER, value REAL)")
+        conn.execute_many("INSERT INTO readings VALUES (?, ?)", [(i, i * 0.5) for i in range(100)])
+
+        summary = conn.read_to_parquet("readings", tmp_path / "readings.parquet")
+        assert summary["rows"] == 100
+        assert summary["bytes"] > 0
+        assert pl.read_parquet(tmp_path / "readings.parquet")["value"].sum() == 2475.0
+
+        summary = conn.read_to_parquet(
+            "SELECT id FROM readings WHERE id < 10",
+            str(tmp_path / "first.parquet"),
+            compression="snappy",
+        )
+        assert pl.read_parquet(tmp_path / "first.parquet")["id"].to_list() == list(range(10))
+
+        with pytest.raises(Exception, match="compression"):
+            conn.read_to_parquet("readings", tmp_path / "bad.parquet", compression="xz")
+
+
+def test_snapshot(tmp_path):
+    """Test storing a query result as a snapshot and reading it back."""
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "test_snapshot.db"))
+    snapshots = tmp_path / "snapshots"
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (tag TEXT, value REAL)")
+        conn.execute_many("INSERT INTO readings VALUES (?, ?)", [("TI-101", 20.5), ("TI-102", 21.0)])
+
+        sql = "SELECT * FROM readings WHERE value > 20.6"
+        info = conn.snapshot(sql, "line1", directory=snapshots)
+        assert info["sql"] == sql
+        assert info["db_type"] == "sqlite"
+        assert info["rows"] == 1
+        assert [c["name"] for c in info["columns"]] == ["tag", "value"]
+
+        conn.execute_statement("DELETE FROM readings")
+        assert idb.read_snapshot("line1", directory=snapshots)["tag"] == ["TI-102"]
+        assert [s["name"] for s in idb.list_snapshots(snapshots)] == ["line1"]
+
+        with pytest.raises(idb.IndustryDbError, match="already exists"):
+            conn.snapshot(sql, "line1", directory=snapshots)
+        assert conn.snapshot(sql, "line1", directory=snapshots, overwrite=True)["rows"] == 0
+        with pytest.raises(idb.IndustryDbError, match="snapshot name"):
+            conn.snapshot(sql, "../line1", directory=snapshots)
+
+
+def test_export_ipc(tmp_path):
+    """Test exporting results to Arrow IPC files and streams."""
+    db_path = tmp_path / "test_export_ipc.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (id INTEGER, value REAL)")
+        conn.execute_many("INSERT INTO readings VALUES (?, ?)", [(i, i * 0.5) for i in range(100)])
+
+        summary = conn.export_ipc("readings", tmp_path / "readings.arrow", compression="zstd")
+        assert summary["rows"] == 100
+        df = pl.read_ipc(tmp_path / "readings.arrow")
+        assert df["id"].to_list() == list(range(100))
+
+        conn.export_ipc(
+            "SELECT id FROM readings WHERE id < 25",
+            tmp_path / "first.arrows",
+            batch_rows=10,
+            stream=True,
+        )
+        assert pl.read_ipc_stream(tmp_path / "first.arrows")["id"].to_list() == list(range(25))
+
+
+def test_csv_export_and_import(tmp_path):
+    """Test exporting a table to CSV and loading it into another table."""
+    db_path = tmp_path / "test_csv.db"
+    csv_path = tmp_path / "readings.csv"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (tag TEXT, value REAL)")
+        conn.execute_many(
+            "INSERT INTO readings VALUES (?, ?)", [("007", 1.5), ("012", None), ("120", 3.0)]
+        )
+
+        summary = conn.export_csv("readings", csv_path)
+        assert summary["rows"] == 3
+        assert csv_path.read_text().splitlines()[0] == "tag,value"
+
+        rows = conn.import_csv("readings_copy", csv_path, schema={"tag": "str"})
+        assert rows == 3
+        df = conn.execute("SELECT tag, value FROM readings_copy ORDER BY tag")
+        assert df["tag"].to_list() == ["007", "012", "120"]
+        assert df["value"].to_list() == [1.5, None, 3.0]
+
+        assert conn.import_csv("readings_copy", csv_path, create_table=False) == 3
+        assert conn.count("readings_copy") == 6
+
+
+def test_count_and_exists(tmp_path):
+    """Test count() and exists() with and without a WHERE clause."""
+    db_path = tmp_path / "test_count_exists.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT, value REAL)")
+        assert conn.count("tags") == 0
+        assert conn.exists("tags") is False
+
+        conn.insert("tags", {"name": ["a", "b", "c"], "value": [1.0, 2.0, 3.0]})
+        assert conn.count("tags") == 3
+        assert conn.count("tags", where_clause="value > :v", params={"v": 1.5}) == 2
+        assert conn.exists("tags", where_clause="name = ?", params=["b"]) is True
+        assert conn.exists("tags", where_clause="name = ?", params=["z"]) is False
+
+
+def test_execute_batch(tmp_path):
+    """Test running a multi-statement script with per-batch error context."""
+    db_path = tmp_path / "test_execute_batch.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        script = """
+            CREATE TABLE tags (name TEXT); -- setup; done
+            INSERT INTO tags VALUES ('a;b');
+            INSERT INTO tags VALUES ('c');
+        """
+        assert conn.execute_batch(script) == 3
+        assert conn.select("tags")["name"].to_list() == ["a;b", "c"]
+
+        with pytest.raises(Exception, match=r"Batch 2 of 2 \(line 3\)"):
+            conn.execute_batch("INSERT INTO tags VALUES ('d');\n\nINSERT INTO missing VALUES (1)")
+
+
+def test_execute_script(tmp_path):
+    """Test running scripts from files and strings, with trigger bodies."""
+    db_path = tmp_path / "test_execute_script.db"
+    script_path = tmp_path / "bootstrap.sql"
+    script_path.write_text(
+        """
+        CREATE TABLE tags (name TEXT, value REAL);
+        CREATE TABLE audit (name TEXT);
+        CREATE TRIGGER tags_audit AFTER INSERT ON tags BEGIN
+            INSERT INTO audit VALUES (new.name);
+        END;
+        """
+    )
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        assert conn.execute_script(script_path) == 3
+        assert conn.execute_script("INSERT INTO tags VALUES ('a', 1.0);") == 1
+        assert conn.select("audit")["name"].to_list() == ["a"]
+
+        bad_path = tmp_path / "bad.sql"
+        bad_path.write_text("INSERT INTO missing VALUES (1);")
+        with pytest.raises(idb.QueryExecutionError, match="bad.sql"):
+            conn.execute_script(str(bad_path))
+
+
+def test_query_builder(tmp_path):
+    """Test queries built with Query render per dialect and run."""
+    db_path = tmp_path / "test_query_builder.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    query = (
+        idb.Query("tags")
+        .columns(["name"])
+        .filter((idb.col("value") > 1.5) & ~idb.col("name").is_in(["c"]))
+        .order_by("name", descending=True)
+        .limit(5)
+    )
+    sql, params = query.to_sql("mssql")
+    assert sql.startswith("SELECT [name] FROM [tags]")
+    assert params == [1.5, "c"]
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT, value REAL)")
+        conn.insert("tags", {"name": ["a", "b", "c", "d"], "value": [1.0, 2.0, 3.0, 4.0]})
+
+        df = conn.fetch(query)
+        assert df["name"].to_list() == ["d", "b"]
+
+
+def test_query_join(tmp_path):
+    """Test joining tag metadata to readings with the query builder."""
+    db_path = tmp_path / "test_query_join.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (id INTEGER, name TEXT)")
+        conn.execute_statement("CREATE TABLE readings (tag_id INTEGER, value REAL)")
+        conn.insert("tags", {"id": [1, 2], "name": ["TI-101", "PI-201"]})
+        conn.insert("readings", {"tag_id": [1, 1, 2, 3], "value": [20.5, 21.0, 3.2, 9.9]})
+
+        query = (
+            idb.Query("readings")
+            .columns(["tags.name", "readings.value"])
+            .join("tags", idb.col("readings.tag_id") == idb.col("tags.id"))
+            .filter(idb.col("tags.name") == "TI-101")
+            .order_by("readings.value")
+        )
+        df = conn.fetch(query)
+        assert df["value"].to_list() == [20.5, 21.0]
+
+        left = idb.Query("readings").join(
+            "tags", idb.col("readings.tag_id") == idb.col("tags.id"), how="left"
+        )
+        assert conn.fetch(left).height == 4
+
+        with pytest.raises(Exception):
+            left.join("tags", idb.col("a") == idb.col("b"), how="sideways")
+
+
+def test_null_order_and_collation(tmp_path):
+    """Test NULL placement and collation in select and the query builder."""
+    db_path = tmp_path / "test_null_order.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE tags (name TEXT, updated INTEGER)")
+        conn.insert("tags", {"name": ["b", "A", "c"], "updated": [2, None, 1]})
+
+        df = conn.select(
+            "tags", order_by=[{"column": "updated", "descending": True, "nulls": "last"}]
+        )
+        assert df["name"].to_list() == ["b", "c", "A"]
+
+        df = conn.select("tags", order_by=[{"column": "name", "collation": "NOCASE"}])
+        assert df["name"].to_list() == ["A", "b", "c"]
+
+        query = idb.Query("tags").order_by("updated", nulls="first")
+        assert conn.fetch(query)["name"].to_list() == ["A", "c", "b"]
+
+        sql, _ = idb.Query("tags").order_by("updated", descending=True, nulls="last").to_sql("mssql")
+        assert "CASE WHEN [updated] IS NULL THEN 1 ELSE 0 END" in sql
+
+        with pytest.raises(Exception):
+            idb.Query("tags").order_by("updated", nulls="middle")
+
+
+def test_distinct_and_aggregates(tmp_path):
+    """Test DISTINCT and aggregate columns in select and the query builder."""
+    db_path = tmp_path / "test_aggregates.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (tag_id INTEGER, value REAL)")
+        conn.insert("readings", {"tag_id": [1, 1, 2], "value": [1.0, 3.0, 5.0]})
+
+        df = conn.select("readings", columns=["tag_id"], distinct=True, order_by="tag_id")
+        assert df["tag_id"].to_list() == [1, 2]
+
+        df = conn.select(
+            "readings",
+            columns=["tag_id", "avg(value) as avg_v", "count(*) as n"],
+            group_by="tag_id",
+            order_by="tag_id",
+        )
+        assert df["avg_v"].to_list() == [2.0, 5.0]
+        assert df["n"].to_list() == [2, 1]
+
+        with pytest.raises(Exception):
+            conn.select("readings", columns=["value); DROP TABLE readings; --"])
+
+        query = (
+            idb.Query("readings")
+            .columns(["tag_id"])
+            .aggregate("max", "value", "peak")
+            .group_by(["tag_id"])
+            .order_by("tag_id")
+        )
+        assert conn.fetch(query)["peak"].to_list() == [3.0, 5.0]
+        assert conn.fetch(idb.Query("readings").count_all())["count"].to_list() == [3]
+
+
+def test_select_grouping_and_paging(tmp_path):
+    """Test select with group_by, having, order_by, limit and offset."""
+    db_path = tmp_path / "test_select_paging.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (tag TEXT, value REAL)")
+        conn.insert(
+            "readings",
+            {"tag": ["a", "a", "b", "b", "b", "c"], "value": [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]},
+        )
+
+        page = conn.select("readings", order_by="value", limit=2, offset=2)
+        assert page["value"].to_list() == [3.0, 4.0]
+
+        rest = conn.select("readings", order_by="value", offset=4)
+        assert rest["value"].to_list() == [5.0, 6.0]
+
+        df = conn.select(
+            "readings",
+            columns=["tag"],
+            where_clause="value > :min",
+            group_by="tag",
+            having="COUNT(*) >= :n",
+            order_by="tag",
+            params={"min": 1.5, "n": 2},
+        )
+        assert df["tag"].to_list() == ["b"]
+
+
+def test_replay(tmp_path):
+    """Test replay inserts every recorded row in batches per timestamp."""
+    db_path = tmp_path / "test_replay.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE readings (ts TEXT, value REAL)")
+        data = {
+            "ts": ["2024-01-01 00:00:00", "2024-01-01 00:00:00", "2024-01-01 00:00:10"],
+            "value": [1.0, 2.0, 3.0],
+        }
+
+        progress = idb.replay(conn, "readings", "ts", data=data, speed=float("inf"))
+        assert progress["complete"]
+        assert progress["batches"] == 2
+        assert conn.execute("SELECT * FROM readings").height == 3
+
+
+def test_load_synthetic(tmp_path):
+    """Test synthetic data generation and loading."""
+    db_path = tmp_path / "test_synthetic.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+    spec = {
+        "name": "readings",
+        "rows": 50,
+        "seed": 1,
+        "columns": [
+            {"name": "ts", "kind": "timestamp", "interval": "1m"},
+            {"name": "value", "kind": "sensor", "base": 20.0, "noise": 0.1},
+            {"name": "state", "kind": "state"},
+        ],
+    }
+
+    with idb.Connection(config) as conn:
+        assert conn.load_synthetic(spec) == 50
+        assert conn.fill_synthetic("readings", 25) == 25
+        assert conn.execute("SELECT * FROM readings").height == 75
+
+
+def test_identifier_quoting(tmp_path):
+    """Test table and column names needing quotes, and rejected identifiers."""
+    db_path = tmp_path / "test_identifiers.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement('CREATE TABLE "line 1" ("order" INTEGER, "tag name" TEXT)')
+        conn.insert("line 1", {"order": [1], "tag name": ["TI-101"]})
+
+        df = conn.select("line 1", columns=["tag name"])
+        assert df["tag name"][0] == "TI-101"
+
+        with pytest.raises(Exception):
+            conn.select("bad\nname")
+
+
+def test_query_metrics(tmp_path):
+    """Test per-query resource stats are recorded."""
+    db_path = tmp_path / "test_metrics.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        assert conn.last_query_stats() is None
+        conn.execute("SELECT 1 AS one")
+
+        stats = conn.last_query_stats()
+        assert stats["rows"] == 1
+        if not stats["memory_tracked"]:
+            # Built without track-memory: the DataFrame size stands in
+            assert stats["peak_memory_bytes"] == stats["output_bytes"]
+        assert conn.query_metrics()["queries"] == 1
+
+        conn.reset_query_metrics()
+        assert conn.query_metrics()["queries"] == 0
+
+
+def test_copy_table(tmp_path):
+    """Test copying a table between two databases page by page."""
+    source_config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "plant.db"))
+    target_config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "central.db"))
+
+    with idb.Connection(source_config) as source, idb.Connection(target_config) as target:
+        source.execute_statement("CREATE TABLE readings (id INTEGER PRIMARY KEY, value REAL)")
+        source.insert("readings", {"id": list(range(25)), "value": [i * 0.5 for i in range(25)]})
+
+        pages = []
+        progress = idb.copy_table(
+            source, target, "readings", chunk_size=10, on_progress=pages.append
+        )
+        assert progress["complete"]
+        assert progress["rows_copied"] == progress["rows_total"] == 25
+        assert [p["rows_copied"] for p in pages] == [10, 20, 25]
+
+        idb.copy_table(source, target, "readings", chunk_size=10)
+        assert target.count("readings") == 50
+
+        idb.copy_table(source, target, "readings", mode="replace", target_table="readings_copy")
+        idb.copy_table(source, target, "readings", mode="replace", target_table="readings_copy")
+        df = target.execute("SELECT id, value FROM readings_copy ORDER BY id")
+        assert df["id"].to_list() == list(range(25))
+
+        with pytest.raises(idb.IndustryDbError, match="copy mode"):
+            idb.copy_table(source, target, "readings", mode="merge")
+
+
+def test_sync_table_resumes_from_watermark(tmp_path):
+    """Test incremental sync picks up only rows past the saved watermark."""
+    source_config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "site.db"))
+    target_config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "cloud.db"))
+    state = tmp_path / "sync.json"
+
+    with idb.Connection(source_config) as source, idb.Connection(target_config) as target:
+        source.execute_statement(
+            "CREATE TABLE batches (id INTEGER PRIMARY KEY, status TEXT, updated_at INTEGER)"
+        )
+        source.insert(
+            "batches",
+            {"id": [1, 2, 3], "status": ["open", "open", "open"], "updated_at": [1, 2, 2]},
+        )
+        target.execute_statement(
+            "CREATE TABLE batches (id INTEGER PRIMARY KEY, status TEXT, updated_at INTEGER)"
+        )
+
+        progress = idb.sync_table(
+            source, target, "batches", "updated_at", on_conflict="update", chunk_size=2, state=state
+        )
+        assert progress["caught_up"]
+        assert progress["rows_synced"] == 3
+        assert progress["watermark"] == 2
+
+        source.execute_statement("UPDATE batches SET status = 'closed', updated_at = 3 WHERE id = 1")
+        progress = idb.sync_table(
+            source, target, "batches", "updated_at", on_conflict="update", state=state
+        )
+        assert progress["rows_synced"] == 4
+        df = target.execute("SELECT id, status FROM batches ORDER BY id")
+        assert df["status"].to_list() == ["closed", "open", "open"]
+
+        with pytest.raises(idb.IndustryDbError):
+            idb.sync_table(source, target, "batches", "id", state=state)
+
+
+def test_change_poller(tmp_path):
+    """Test the change poller returns rows past the acknowledged watermark."""
+    db_path = tmp_path / "test_poller.db"
+    state = tmp_path / "poller.json"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement(
+            "CREATE TABLE batches (id INTEGER PRIMARY KEY, status TEXT, updated_at INTEGER)"
+        )
+        conn.insert("batches", {"id": [1, 2], "status": ["open", "open"], "updated_at": [1, 2]})
+
+        poller = idb.ChangePoller(conn, {"batches": "updated_at"}, state=state)
+        changes = poller.poll()
+        assert changes["batches"]["id"].to_list() == [1, 2]
+        assert poller.watermark("batches") is None
+        assert poller.poll() == {}
+        assert poller.watermark("batches") == 2
+
+        conn.execute_statement("UPDATE batches SET status = 'closed', updated_at = 3 WHERE id = 1")
+        changes = poller.wait(interval="10ms", timeout=1.0)
+        assert changes["batches"]["status"].to_list() == ["closed"]
+
+        # Not acknowledged yet, so a restarted poller sees the update again
+        restarted = idb.ChangePoller(conn, {"batches": "updated_at"}, state=state)
+        assert restarted.poll()["batches"]["id"].to_list() == [1]
+        assert restarted.wait(interval="10ms", timeout=0.05) == {}
+
+        with pytest.raises(idb.ConfigurationError):
+            idb.ChangePoller(conn, {"batches": "id"}, state=state)
+
+
+def test_kafka_sink_config_errors():
+    """Test that a Kafka sink rejects bad settings before connecting."""
+    with pytest.raises(idb.ConfigurationError):
+        idb.KafkaSink([], "mes.changes")
+
+    with pytest.raises(idb.IndustryDbError, match="Kafka format"):
+        idb.KafkaSink(["localhost:9092"], "mes.changes", format="avro")
+
+    with pytest.raises(idb.IndustryDbError, match="keyed"):
+        idb.KafkaSink(["localhost:9092"], "mes.changes", format="arrow", key_column="id")
+
+
+def test_ingest_opcua_history(tmp_path):
+    """Test OPC UA history rows are mapped to tags and filtered by quality."""
+    db_path = tmp_path / "test_history.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+    history = {
+        "node_id": ["ns=2;s=TI101", "ns=2;s=PI200", "ns=2;s=TI101"],
+        "timestamp": ["2024-03-01 08:00:00", "2024-03-01 08:00:00", "2024-03-01 08:01:00"],
+        "value": [21.5, 3.2, 99.0],
+        "quality": [0, None, 0x80000000],
+    }
+
+    with idb.Connection(config) as conn:
+        summary = idb.ingest_opcua_history(
+            conn, history, "history", tags={"ns=2;s=TI101": "TI-101"}, min_quality="good"
+        )
+        assert summary == {"values_read": 3, "values_dropped": 1, "rows_inserted": 2}
+
+        df = conn.execute("SELECT tag, value FROM history ORDER BY tag")
+        assert df["tag"].to_list() == ["TI-101", "ns=2;s=PI200"]
+
+        summary = idb.ingest_opcua_history(conn, history, "history_wide", layout="wide")
+        assert summary["rows_inserted"] == 2
+        df = conn.execute('SELECT "ns=2;s=TI101" AS ti FROM history_wide ORDER BY ts')
+        assert df["ti"].to_list() == [21.5, 99.0]
+
+
+def test_buffered_writer(tmp_path):
+    """Test the buffered writer inserts single rows and column batches."""
+    db_path = tmp_path / "test_writer.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE samples (channel INTEGER, value REAL)")
+
+        with idb.BufferedWriter(conn, "samples", max_rows=100, max_delay=60.0) as writer:
+            for i in range(250):
+                writer.write({"channel": i % 4, "value": i * 0.5})
+            writer.write({"channel": [9, 9], "value": [1.0, 2.0]})
+            writer.flush()
+            assert writer.stats["rows_queued"] == 0
+
+        stats = writer.stats
+        assert stats["rows_written"] == 252
+        assert stats["flushes"] == 3
+        df = conn.execute("SELECT COUNT(*) AS n FROM samples")
+        assert df["n"].to_list() == [252]
+
+        with pytest.raises(idb.IndustryDbError, match="queue_rows"):
+            idb.BufferedWriter(conn, "samples", max_rows=100, queue_rows=10)
+
+    # Closing the connection does not wait for, or break, a running writer
+    conn = idb.Connection(config)
+    writer = idb.BufferedWriter(conn, "samples", max_rows=100, max_delay=60.0)
+    writer.write({"channel": [1, 2], "value": [1.0, 2.0]})
+    conn.close()
+    assert conn.is_closed()
+    assert writer.close()["rows_written"] == 2
+
+
+def test_buffered_writer_with_spill(tmp_path):
+    """Test a writer with a spill file inserts directly while the database is up."""
+    db_path = tmp_path / "test_spill.db"
+    spill_path = tmp_path / "spill.db"
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(db_path))
+
+    with idb.Connection(config) as conn:
+        conn.execute_statement("CREATE TABLE samples (id INTEGER PRIMARY KEY, value REAL)")
+
+        with idb.BufferedWriter(
+            conn, "samples", max_rows=10, spill_path=str(spill_path), dedup_keys=["id"]
+        ) as writer:
+            writer.write({"id": list(range(25)), "value": [i * 1.5 for i in range(25)]})
+            writer.flush()
+
+        stats = writer.stats
+        assert stats["rows_written"] == 25
+        assert stats["rows_spilled"] == 0
+        assert stats["rows_replayed"] == 0
+        assert spill_path.exists()
+        df = conn.execute("SELECT COUNT(*) AS n FROM samples")
+        assert df["n"].to_list() == [25]
+
+
+def test_scheduler_run_job(tmp_path):
+    """Test scheduled jobs deliver to tables and files and record their runs."""
+    source = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "source.db"))
+    warehouse = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "warehouse.db"))
+
+    with idb.Connection(source) as conn, idb.Connection(warehouse) as wh:
+        conn.execute_statement("CREATE TABLE readings (tag TEXT, value REAL)")
+        conn.insert("readings", {"tag": ["a", "a", "b"], "value": [1.0, 3.0, 5.0]})
+
+        scheduler = idb.Scheduler.from_dict(
+            {
+                "history_table": "job_runs",
+                "jobs": [
+                    {
+                        "name": "rollup",
+                        "schedule": "@every 1h",
+                        "sql": "SELECT tag, avg(value) AS value FROM readings GROUP BY tag",
+                        "destination": {
+                            "type": "table",
+                            "table": "rollup",
+                            "connection": "warehouse",
+                        },
+                    },
+                    {
+                        "name": "export",
+                        "schedule": "0 6 * * *",
+                        "sql": "SELECT * FROM readings",
+                        "destination": {
+                            "type": "csv",
+                            "path": str(tmp_path / "readings_%Y%m%d.csv"),
+                        },
+                    },
+                ],
+            }
+        )
+        assert scheduler.jobs == ["rollup", "export"]
+        assert set(scheduler.next_runs) == {"rollup", "export"}
+
+        connections = {"default": conn, "warehouse": wh}
+        run = scheduler.run_job("rollup", connections)
+        assert run["success"] and run["rows"] == 2
+        df = wh.execute("SELECT value FROM rollup ORDER BY tag")
+        assert df["value"].to_list() == [2.0, 5.0]
+
+        run = scheduler.run_job("export", connections)
+        assert run["rows"] == 3
+        assert len(list(tmp_path.glob("readings_*.csv"))) == 1
+
+        assert [r["job"] for r in scheduler.history] == ["rollup", "export"]
+        df = conn.execute("SELECT job, success FROM job_runs")
+        assert df["job"].to_list() == ["rollup", "export"]
+
+        with pytest.raises(idb.ConfigurationError):
+            idb.Scheduler.from_dict(
+                {
+                    "jobs": [
+                        {
+                            "name": "bad",
+                            "schedule": "61 * * * *",
+                            "sql": "SELECT 1",
+                            "destination": {"type": "csv", "path": "x.csv"},
+                        }
+                    ]
+                }
+            )
+
+
+def test_backfill_adaptive_batching(tmp_path):
+    """Test backfill grows the insert batch size while commits are fast."""
+    source_config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "source.db"))
+    target_config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "target.db"))
+
+    with idb.Connection(source_config) as source, idb.Connection(target_config) as target:
+        for conn in (source, target):
+            conn.execute_statement("CREATE TABLE readings (ts TEXT, value REAL)")
+        source.insert(
+            "readings",
+            {
+                "ts": [f"2024-01-01 00:00:{i:02d}" for i in range(10)],
+                "value": [float(i) for i in range(10)],
+            },
+        )
+
+        progress = idb.backfill(
+            source,
+            target,
+            "readings",
+            "ts",
+            "2024-01-01",
+            "2024-01-02",
+            "1d",
+            batching={"initial_rows": 2, "min_rows": 1, "increase_rows": 1},
+        )
+        assert progress["complete"]
+        assert progress["rows_copied"] == 10
+        assert progress["batch_rows"] > 2
+        assert target.execute("SELECT * FROM readings").height == 10
+
+
+def test_pipeline_from_file(tmp_path):
+    """Test a file -> transforms -> validation -> table pipeline from TOML."""
+    csv_path = tmp_path / "readings.csv"
+    csv_path.write_text("tag,raw\nTI-101,205\nTI-102,9000\nTI-103,198\n")
+    config_path = tmp_path / "pipeline.toml"
+    config_path.write_text(
+        f"""
+name = "csv-import"
+
+[source]
+type = "file"
+path = "{csv_path.as_posix()}"
+batch_rows = 2
+
+[[transforms]]
+op = "rename"
+columns = {{ raw = "value" }}
+
+[[transforms]]
+op = "scale"
+column = "value"
+factor = 0.1
+
+[validation]
+ranges = {{ value = {{ max = 500.0 }} }}
+reject_table = "readings_rejected"
+
+[target]
+table = "readings"
+"""
+    )
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "pipeline.db"))
+    with idb.Connection(config) as conn:
+        pipeline = idb.Pipeline(str(config_path))
+        assert pipeline.name == "csv-import"
+        metrics = pipeline.run(conn)
+
+        assert metrics["batches"] == 2
+        assert metrics["rows_read"] == 3
+        assert metrics["rows_written"] == 2
+        assert metrics["rows_rejected"] == 1
+        assert conn.fetch_scalar("SELECT count(*) FROM readings") == 2
+        assert conn.fetch_scalar("SELECT tag FROM readings_rejected") == "TI-102"
+
+    with pytest.raises(idb.ConfigurationError):
+        idb.Pipeline.from_dict({"source": {"type": "table"}, "target": {"table": "t"}})
+
+
+def test_failed_migration_rolls_back(tmp_path):
+    """Test a failing migration leaves neither its tables nor its version behind."""
+    migrations = tmp_path / "migrations"
+    migrations.mkdir()
+    (migrations / "0001_create_tags.up.sql").write_text("CREATE TABLE tags (name TEXT);")
+    (migrations / "0002_broken.up.sql").write_text(
+        "CREATE TABLE readings (value REAL);\nINSERT INTO missing VALUES (1);"
+    )
+
+    config = idb.DatabaseConfig(db_type="sqlite", path=str(tmp_path / "migrate.db"))
+    with idb.Connection(config) as conn:
+        with pytest.raises(idb.IndustryDbError, match="Migration 2"):
+            conn.migrate_up(str(migrations))
+
+        assert [m["applied"] for m in conn.migration_status(str(migrations))] == [True, False]
+        assert sorted(conn.list_tables()) == ["schema_migrations", "tags"]
+
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])